@@ -79,6 +79,15 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("translate_pragma")
+                .long("translate-pragma")
+                .value_name("TOOL")
+                .help("Honor `// TOOL translate_off/translate_on` comment pragmas")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1),
+        )
         .arg(
             Arg::with_name("preproc")
                 .short("E")
@@ -106,6 +115,21 @@ fn main() {
                 .default_value("0")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Sets the seed of the random number generator used by randomize()")
+                .default_value("0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("emit_metadata")
+                .long("emit-metadata")
+                .value_name("FILE")
+                .help("Write the attributes of the generated LLHD to a file")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("lib")
                 .short("l")
@@ -182,6 +206,8 @@ fn main() {
         };
     }
     session.opts.opt_level = matches.value_of("opt-level").unwrap().parse().unwrap();
+    session.opts.seed = matches.value_of("seed").unwrap().parse().unwrap();
+    session.opts.metadata_file = matches.value_of("emit_metadata").map(String::from);
 
     // Invoke the compiler.
     score(&session, &matches);
@@ -206,6 +232,9 @@ fn score(sess: &Session, matches: &ArgMatches) {
         None => Vec::new(),
     };
 
+    let translate_pragmas: Option<Vec<_>> =
+        matches.values_of("translate_pragma").map(|x| x.collect());
+
     // Establish into which library the entities will be compiled. Later on this
     // should be made configurable per entity.
     let lib = get_name_table().intern(matches.value_of("lib").unwrap_or("work"), true);
@@ -248,7 +277,11 @@ fn score(sess: &Session, matches: &ArgMatches) {
         // Parse the file.
         match language {
             Language::SystemVerilog | Language::Verilog => {
-                let preproc = svlog::preproc::Preprocessor::new(source, &include_paths, &defines);
+                let mut preproc =
+                    svlog::preproc::Preprocessor::new(source, &include_paths, &defines);
+                if let Some(ref pragmas) = translate_pragmas {
+                    preproc.set_translate_pragmas(pragmas.iter().cloned());
+                }
                 if matches.is_present("preproc") {
                     for token in preproc {
                         print!("{}", token.unwrap().1.extract());
@@ -460,12 +493,24 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
 
             let mut cg = svlog::CodeGenerator::new(ctx.svlog);
             cg.emit_module(m)?;
-            let mut module = cg.finalize();
+            let (mut module, metadata) = cg.finalize_with_metadata();
             if ctx.sess.opts.opt_level > 0 {
                 llhd::pass::const_folding::run_on_module(&mut module);
                 llhd::pass::dead_code_elim::run_on_module(&mut module);
             }
             llhd::assembly::write_module(&mut std::io::stdout().lock(), &module);
+
+            // LLHD assembly has no syntax for attributes, so they are written
+            // to a separate file.
+            if let Some(ref path) = ctx.sess.opts.metadata_file {
+                if let Err(e) = std::fs::write(path, metadata.to_string()) {
+                    ctx.sess.emit(DiagBuilder2::error(format!(
+                        "cannot write metadata to `{}`: {}",
+                        path, e
+                    )));
+                    return Err(());
+                }
+            }
         }
     }
     Ok(())
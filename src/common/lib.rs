@@ -78,6 +78,11 @@ pub struct SessionOptions {
     pub verbosity: Verbosity,
     /// The optimization level.
     pub opt_level: usize,
+    /// The seed of the random number generator used by `randomize()`.
+    pub seed: u64,
+    /// The file to write the metadata of the generated LLHD to, e.g. the
+    /// attributes present in the source text.
+    pub metadata_file: Option<String>,
 }
 
 bitflags! {
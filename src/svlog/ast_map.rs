@@ -38,6 +38,10 @@ impl<'ast> AstMap<'ast> {
 pub enum AstNode<'ast> {
     /// A module.
     Module(&'ast ast::ModDecl),
+    /// An interface.
+    Interface(&'ast ast::IntfDecl),
+    /// A modport, given as `(modport, interface)`.
+    Modport(&'ast ast::ModportItem, NodeId),
     /// A module or interface port.
     Port(&'ast ast::Port),
     /// A type.
@@ -58,36 +62,81 @@ pub enum AstNode<'ast> {
     VarDecl(&'ast ast::VarDeclName, &'ast ast::VarDecl, NodeId),
     /// A net declaration.
     NetDecl(&'ast ast::VarDeclName, &'ast ast::NetDecl, NodeId),
+    /// A port declaration in the body of a module with a non-ANSI port list,
+    /// which also declares the port as a net or variable.
+    PortDecl(&'ast ast::VarDeclName, &'ast ast::PortDecl, NodeId),
     /// A procedure.
     Proc(&'ast ast::Procedure),
     /// A statement.
     Stmt(&'ast ast::Stmt),
     /// An event expression.
     EventExpr(&'ast ast::EventExpr),
-    /// An if-generate statement.
-    GenIf(&'ast ast::GenerateIf),
-    /// A for-generate statement.
-    GenFor(&'ast ast::GenerateFor),
+    /// An if-generate statement, given as `(gen, index)`. The index is the
+    /// position of the construct among the generate constructs of its scope,
+    /// starting at 1, and is used to name implicit `genblk<index>` blocks.
+    GenIf(&'ast ast::GenerateIf, usize),
+    /// A for-generate statement, given as `(gen, index)`.
+    GenFor(&'ast ast::GenerateFor, usize),
+    /// A case-generate statement, given as `(gen, index)`.
+    GenCase(&'ast ast::GenerateCase, usize),
+    /// The block of a generate construct, given as `(block, index)` with the
+    /// index of the construct.
+    GenBlock(&'ast ast::GenerateBlock, usize),
     /// A genvar declaration.
     GenvarDecl(&'ast ast::GenvarDecl),
     /// A typedef.
     Typedef(&'ast ast::Typedef),
     /// A continuous assignment.
     ContAssign(&'ast ast::ContAssign, &'ast ast::Expr, &'ast ast::Expr),
+    /// A parameter override, given as `(defparam, target, value)`.
+    Defparam(&'ast ast::Defparam, &'ast ast::Expr, &'ast ast::Expr),
     /// A struct member.
     StructMember(&'ast ast::VarDeclName, &'ast ast::StructMember, NodeId),
     /// A package.
     Package(&'ast ast::PackageDecl),
+    /// The items of a compilation unit outside of any module, interface, or
+    /// package, also known as the `$unit` scope.
+    Unit(&'ast ast::Root),
     /// An enum variant, given as `(variant, enum_def, index)`.
     EnumVariant(&'ast ast::EnumName, NodeId, usize),
     /// An import.
     Import(&'ast ast::ImportItem),
+    /// An assertion.
+    Assertion(&'ast ast::Assertion),
+    /// A named sequence.
+    SeqDecl(&'ast ast::SeqDecl),
+    /// A named property.
+    PropDecl(&'ast ast::PropDecl),
+    /// A formal argument of a named sequence or property.
+    SeqPort(&'ast ast::SeqPort),
+    /// A function or task.
+    Subroutine(&'ast ast::SubroutineDecl),
+    /// A function or task prototype without a body, such as a `pure virtual`
+    /// method.
+    SubroutineProto(&'ast ast::SubroutinePrototype),
+    /// A function or task argument in the prototype, given as `(port, dir,
+    /// ty)`. The direction and type may be inherited from a preceding argument.
+    SubroutinePort(&'ast ast::SubroutinePort, ast::SubroutinePortDir, NodeId),
+    /// A function or task argument declared in the body.
+    SubroutinePortDecl(
+        &'ast ast::VarDeclName,
+        &'ast ast::SubroutinePortDecl,
+        NodeId,
+    ),
+    /// A class.
+    Class(&'ast ast::ClassDecl),
+    /// A constraint block of a class.
+    Constraint(&'ast ast::Constraint),
+    /// An index variable of a `foreach` constraint.
+    ForeachIndex(&'ast ast::Identifier),
 }
 
 impl<'ast> HasSpan for AstNode<'ast> {
     fn span(&self) -> Span {
         match *self {
             AstNode::Module(x) => x.span(),
+            AstNode::Interface(x) => x.span(),
+            AstNode::Modport(x, _) => x.span(),
             AstNode::Port(x) => x.span(),
             AstNode::Type(x) => x.span(),
             AstNode::Expr(x) => x.span(),
@@ -98,24 +147,42 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::TypeOrExpr(x) => x.span(),
             AstNode::VarDecl(_, x, _) => x.span(),
             AstNode::NetDecl(_, x, _) => x.span(),
+            AstNode::PortDecl(_, x, _) => x.span,
             AstNode::Proc(x) => x.span(),
             AstNode::Stmt(x) => x.span(),
             AstNode::EventExpr(x) => x.span(),
-            AstNode::GenIf(x) => x.span(),
-            AstNode::GenFor(x) => x.span(),
+            AstNode::GenIf(x, _) => x.span(),
+            AstNode::GenFor(x, _) => x.span(),
+            AstNode::GenCase(x, _) => x.span(),
+            AstNode::GenBlock(x, _) => x.span(),
             AstNode::GenvarDecl(x) => x.span(),
             AstNode::Typedef(x) => x.span(),
             AstNode::ContAssign(x, _, _) => x.span(),
+            AstNode::Defparam(x, _, _) => x.span(),
             AstNode::StructMember(_, x, _) => x.span(),
             AstNode::Package(x) => x.span(),
+            AstNode::Unit(x) => x.span(),
             AstNode::EnumVariant(x, _, _) => x.span(),
             AstNode::Import(x) => x.span(),
+            AstNode::Assertion(x) => x.span(),
+            AstNode::SeqDecl(x) => x.span(),
+            AstNode::PropDecl(x) => x.span(),
+            AstNode::SeqPort(x) => x.span(),
+            AstNode::Subroutine(x) => x.span(),
+            AstNode::SubroutineProto(x) => x.span,
+            AstNode::SubroutinePort(x, _, _) => x.span,
+            AstNode::SubroutinePortDecl(_, x, _) => x.span,
+            AstNode::Class(x) => x.span(),
+            AstNode::Constraint(x) => x.span(),
+            AstNode::ForeachIndex(x) => x.span,
         }
     }
 
     fn human_span(&self) -> Span {
         match *self {
             AstNode::Module(x) => x.human_span(),
+            AstNode::Interface(x) => x.human_span(),
+            AstNode::Modport(x, _) => x.human_span(),
             AstNode::Port(x) => x.human_span(),
             AstNode::Type(x) => x.human_span(),
             AstNode::Expr(x) => x.human_span(),
@@ -126,18 +193,36 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::TypeOrExpr(x) => x.human_span(),
             AstNode::VarDecl(x, _, _) => x.human_span(),
             AstNode::NetDecl(x, _, _) => x.human_span(),
+            AstNode::PortDecl(x, _, _) => x.human_span(),
             AstNode::Proc(x) => x.human_span(),
             AstNode::Stmt(x) => x.human_span(),
             AstNode::EventExpr(x) => x.human_span(),
-            AstNode::GenIf(x) => x.human_span(),
-            AstNode::GenFor(x) => x.human_span(),
+            AstNode::GenIf(x, _) => x.human_span(),
+            AstNode::GenFor(x, _) => x.human_span(),
+            AstNode::GenCase(x, _) => x.human_span(),
+            AstNode::GenBlock(x, _) => x.human_span(),
             AstNode::GenvarDecl(x) => x.human_span(),
             AstNode::Typedef(x) => x.human_span(),
             AstNode::ContAssign(x, _, _) => x.human_span(),
+            AstNode::Defparam(x, _, _) => x.human_span(),
             AstNode::StructMember(x, _, _) => x.human_span(),
             AstNode::Package(x) => x.human_span(),
+            AstNode::Unit(x) => x.human_span(),
             AstNode::EnumVariant(x, _, _) => x.human_span(),
             AstNode::Import(x) => x.human_span(),
+            AstNode::Assertion(x) => x.human_span(),
+            AstNode::SeqDecl(x) => x.human_span(),
+            AstNode::PropDecl(x) => x.human_span(),
+            AstNode::SeqPort(x) => x.human_span(),
+            AstNode::Subroutine(x) => x.human_span(),
+            AstNode::SubroutineProto(x) => x.name.span,
+            AstNode::SubroutinePort(x, _, _) => {
+                x.name.as_ref().map(|n| n.name.span).unwrap_or(x.span)
+            }
+            AstNode::SubroutinePortDecl(x, _, _) => x.human_span(),
+            AstNode::Class(x) => x.human_span(),
+            AstNode::Constraint(x) => x.human_span(),
+            AstNode::ForeachIndex(x) => x.span,
         }
     }
 }
@@ -146,6 +231,8 @@ impl<'ast> HasDesc for AstNode<'ast> {
     fn desc(&self) -> &'static str {
         match *self {
             AstNode::Module(x) => x.desc(),
+            AstNode::Interface(x) => x.desc(),
+            AstNode::Modport(x, _) => x.desc(),
             AstNode::Port(x) => x.desc(),
             AstNode::Type(x) => x.desc(),
             AstNode::Expr(x) => x.desc(),
@@ -156,24 +243,42 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::TypeOrExpr(x) => x.desc(),
             AstNode::VarDecl(x, _, _) => x.desc(),
             AstNode::NetDecl(x, _, _) => x.desc(),
+            AstNode::PortDecl(..) => "port",
             AstNode::Proc(x) => x.desc(),
             AstNode::Stmt(x) => x.desc(),
             AstNode::EventExpr(x) => x.desc(),
-            AstNode::GenIf(x) => x.desc(),
-            AstNode::GenFor(x) => x.desc(),
+            AstNode::GenIf(x, _) => x.desc(),
+            AstNode::GenFor(x, _) => x.desc(),
+            AstNode::GenCase(x, _) => x.desc(),
+            AstNode::GenBlock(x, _) => x.desc(),
             AstNode::GenvarDecl(x) => x.desc(),
             AstNode::Typedef(x) => x.desc(),
             AstNode::ContAssign(x, _, _) => x.desc(),
+            AstNode::Defparam(x, _, _) => x.desc(),
             AstNode::StructMember(x, _, _) => x.desc(),
             AstNode::Package(x) => x.desc(),
+            AstNode::Unit(x) => x.desc(),
             AstNode::EnumVariant(x, _, _) => x.desc(),
             AstNode::Import(x) => x.desc(),
+            AstNode::Assertion(x) => x.desc(),
+            AstNode::SeqDecl(x) => x.desc(),
+            AstNode::PropDecl(x) => x.desc(),
+            AstNode::SeqPort(x) => x.desc(),
+            AstNode::Subroutine(x) => x.desc(),
+            AstNode::SubroutineProto(x) => x.desc(),
+            AstNode::SubroutinePort(..) => "argument",
+            AstNode::SubroutinePortDecl(..) => "argument",
+            AstNode::Class(x) => x.desc(),
+            AstNode::Constraint(x) => x.desc(),
+            AstNode::ForeachIndex(_) => "index variable",
         }
     }
 
     fn desc_full(&self) -> String {
         match *self {
             AstNode::Module(x) => x.desc_full(),
+            AstNode::Interface(x) => x.desc_full(),
+            AstNode::Modport(x, _) => x.desc_full(),
             AstNode::Port(x) => x.desc_full(),
             AstNode::Type(x) => x.desc_full(),
             AstNode::Expr(x) => x.desc_full(),
@@ -184,18 +289,37 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::TypeOrExpr(x) => x.desc_full(),
             AstNode::VarDecl(x, _, _) => x.desc_full(),
             AstNode::NetDecl(x, _, _) => x.desc_full(),
+            AstNode::PortDecl(x, _, _) => format!("port `{}`", x.name),
             AstNode::Proc(x) => x.desc_full(),
             AstNode::Stmt(x) => x.desc_full(),
             AstNode::EventExpr(x) => x.desc_full(),
-            AstNode::GenIf(x) => x.desc_full(),
-            AstNode::GenFor(x) => x.desc_full(),
+            AstNode::GenIf(x, _) => x.desc_full(),
+            AstNode::GenFor(x, _) => x.desc_full(),
+            AstNode::GenCase(x, _) => x.desc_full(),
+            AstNode::GenBlock(x, _) => x.desc_full(),
             AstNode::GenvarDecl(x) => x.desc_full(),
             AstNode::Typedef(x) => x.desc_full(),
             AstNode::ContAssign(x, _, _) => x.desc_full(),
+            AstNode::Defparam(x, _, _) => x.desc_full(),
             AstNode::StructMember(x, _, _) => x.desc_full(),
             AstNode::Package(x) => x.desc_full(),
+            AstNode::Unit(x) => x.desc_full(),
             AstNode::EnumVariant(x, _, _) => x.desc_full(),
             AstNode::Import(x) => x.desc_full(),
+            AstNode::Assertion(x) => x.desc_full(),
+            AstNode::SeqDecl(x) => x.desc_full(),
+            AstNode::PropDecl(x) => x.desc_full(),
+            AstNode::SeqPort(x) => x.desc_full(),
+            AstNode::Subroutine(x) => x.desc_full(),
+            AstNode::SubroutineProto(x) => x.desc_full(),
+            AstNode::SubroutinePort(x, _, _) => match x.name {
+                Some(ref n) => format!("argument `{}`", n.name.name),
+                None => "argument".to_string(),
+            },
+            AstNode::SubroutinePortDecl(x, _, _) => format!("argument `{}`", x.name),
+            AstNode::Class(x) => x.desc_full(),
+            AstNode::Constraint(x) => x.desc_full(),
+            AstNode::ForeachIndex(x) => format!("index variable `{}`", x.name),
         }
     }
 }
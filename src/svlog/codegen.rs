@@ -4,7 +4,8 @@
 
 use crate::{
     crate_prelude::*,
-    hir::HirNode,
+    hir::{AccessedNode, HirNode},
+    resolver::interface_of,
     ty::{Type, TypeKind},
     value::{Value, ValueKind},
     ParamEnv, ParamEnvSource, PortMappingSource,
@@ -12,7 +13,7 @@ use crate::{
 use llhd::ir::{Unit, UnitBuilder};
 use num::{BigInt, One, Zero};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     ops::Deref,
     ops::DerefMut,
 };
@@ -27,6 +28,8 @@ pub struct CodeGenerator<'gcx, C> {
     into: llhd::ir::Module,
     /// Tables holding mappings and interned values.
     tables: Tables<'gcx>,
+    /// The metadata accompanying the LLHD module.
+    metadata: Metadata,
 }
 
 impl<'gcx, C> CodeGenerator<'gcx, C> {
@@ -36,6 +39,7 @@ impl<'gcx, C> CodeGenerator<'gcx, C> {
             cx,
             into: llhd::ir::Module::new(),
             tables: Default::default(),
+            metadata: Default::default(),
         }
     }
 
@@ -43,12 +47,83 @@ impl<'gcx, C> CodeGenerator<'gcx, C> {
     pub fn finalize(self) -> llhd::ir::Module {
         self.into
     }
+
+    /// Finalize code generation and return the generated LLHD module together
+    /// with its metadata.
+    pub fn finalize_with_metadata(self) -> (llhd::ir::Module, Metadata) {
+        (self.into, self.metadata)
+    }
+
+    /// Access the metadata collected so far.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+/// Metadata accompanying a generated LLHD module.
+///
+/// LLHD has no notion of attributes. The attributes present in the source
+/// text, e.g. `(* keep *)`, are therefore collected here and refer to the units
+/// and values they are attached to by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// The attributes attached to units and values.
+    pub attrs: Vec<AttrMetadata>,
+}
+
+impl Metadata {
+    /// Find the attributes attached to a unit, or to a value therein.
+    pub fn attrs_of<'a>(
+        &'a self,
+        unit: &'a llhd::ir::UnitName,
+        value: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a AttrMetadata> + 'a {
+        self.attrs
+            .iter()
+            .filter(move |a| &a.unit == unit && a.target.as_ref().map(|x| x.as_str()) == value)
+    }
+}
+
+/// Metadata is printed one attribute per line, e.g. `@foo %a ram_style =
+/// "block"`, naming the unit and the optional value the attribute is attached
+/// to.
+impl std::fmt::Display for Metadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for attr in &self.attrs {
+            write!(f, "{}", attr.unit)?;
+            if let Some(ref target) = attr.target {
+                write!(f, " %{}", target)?;
+            }
+            write!(f, " {}", attr.name)?;
+            if let Some(ref value) = attr.value {
+                write!(f, " = {:?}", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// An attribute attached to a unit or a value in the LLHD module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrMetadata {
+    /// The unit the attribute is attached to.
+    pub unit: llhd::ir::UnitName,
+    /// The name of the value within the unit the attribute is attached to. If
+    /// this is `None`, the attribute applies to the unit itself.
+    pub target: Option<String>,
+    /// The name of the attribute.
+    pub name: Name,
+    /// The optional value of the attribute.
+    pub value: Option<String>,
 }
 
 #[derive(Default)]
 struct Tables<'gcx> {
-    module_defs: HashMap<NodeEnvId, Result<llhd::ir::ModUnit>>,
-    module_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
+    module_defs: HashMap<(NodeEnvId, Vec<NodeId>), Result<llhd::ir::ModUnit>>,
+    module_signatures: HashMap<(NodeEnvId, Vec<NodeId>), (llhd::ir::UnitName, llhd::ir::Signature)>,
+    module_imports: HashMap<NodeId, Vec<NodeId>>,
+    subroutine_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
     interned_types: HashMap<(Type<'gcx>, ParamEnv), Result<llhd::Type>>,
 }
 
@@ -68,9 +143,44 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
 
     /// Emit the code for a module and all its dependent modules.
     pub fn emit_module_with_env(&mut self, id: NodeId, env: ParamEnv) -> Result<llhd::ir::ModUnit> {
-        if let Some(x) = self.tables.module_defs.get(&(id, env)) {
+        // Upward references that have not been resolved by any of the
+        // instantiating modules name a module that is not above them.
+        let imports = self.module_imports(id)?;
+        for &import in &imports {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` does not refer to a module above {}",
+                    self.hier_path(import)?,
+                    self.hir_of(id)?.desc_full()
+                ))
+                .span(self.span(import)),
+            );
+        }
+        if !imports.is_empty() {
+            return Err(());
+        }
+        self.emit_module_with_exports(id, env, vec![])
+    }
+
+    /// Emit the code for a module, exposing the declarations accessed through
+    /// hierarchical names from above.
+    ///
+    /// The hierarchical names in `exports` are relative to the module. Each of
+    /// them becomes an additional output of the entity, following the regular
+    /// ports. Similarly, each upward reference made within the module or its
+    /// children that starts at a module further up becomes an additional
+    /// input.
+    fn emit_module_with_exports(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+        exports: Vec<NodeId>,
+    ) -> Result<llhd::ir::ModUnit> {
+        let key = ((id, env), exports);
+        if let Some(x) = self.tables.module_defs.get(&key) {
             return x.clone();
         }
+        let exports = key.1.clone();
         let hir = match self.hir_of(id)? {
             HirNode::Module(m) => m,
             _ => panic!("expected {:?} to be a module", id),
@@ -81,12 +191,44 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         let mut sig = llhd::ir::Signature::new();
         let mut inputs = Vec::new();
         let mut outputs = Vec::new();
+        let mut undriven = Vec::new();
+        let mut inouts = Vec::new();
         let mut port_id_to_name = HashMap::new();
         for &port_id in hir.ports {
             let port = match self.hir_of(port_id)? {
                 HirNode::Port(p) => p,
                 _ => unreachable!(),
             };
+
+            // Interface ports are flattened into one port per signal, with
+            // the direction given by the modport.
+            if interface_of(self.cx, port_id)?.is_some() {
+                for intf_sig in self.interface_signals(port_id, env)? {
+                    let node = AccessedNode::Intf(port_id, intf_sig.id);
+                    let ty = llhd::signal_ty(self.emit_type(intf_sig.ty, env)?);
+                    match intf_sig.dir {
+                        ast::PortDir::Input | ast::PortDir::Ref => {
+                            sig.add_input(ty);
+                            inputs.push(node);
+                        }
+                        ast::PortDir::Output => {
+                            sig.add_output(ty);
+                            outputs.push(node);
+                            undriven.push(node);
+                        }
+                        ast::PortDir::Inout => {
+                            sig.add_input(ty.clone());
+                            sig.add_output(ty);
+                            inputs.push(node);
+                            outputs.push(node);
+                        }
+                    }
+                    port_id_to_name
+                        .insert(node, format!("{}.{}", port.name.value, intf_sig.name.value));
+                }
+                continue;
+            }
+
             let ty = self.type_of(port_id, env)?;
             debug!(
                 "port {}.{} has type {:?}",
@@ -97,23 +239,47 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 ast::PortDir::Ref => llhd::pointer_ty(ty),
                 _ => llhd::signal_ty(ty),
             };
+            let node = AccessedNode::Regular(port_id);
             match port.dir {
                 ast::PortDir::Input | ast::PortDir::Ref => {
                     sig.add_input(ty);
-                    inputs.push(port_id);
+                    inputs.push(node);
                 }
                 ast::PortDir::Output => {
                     sig.add_output(ty);
-                    outputs.push(port_id);
+                    outputs.push(node);
+                    undriven.push(node);
                 }
                 ast::PortDir::Inout => {
-                    sig.add_input(ty.clone());
-                    sig.add_output(ty);
-                    inputs.push(port_id);
-                    outputs.push(port_id);
+                    inouts.push((port_id, self.inout_port_net(port, env)?, ty));
+                    continue;
                 }
             }
-            port_id_to_name.insert(port_id, port.name);
+            port_id_to_name.insert(node, port.name.value.to_string());
+        }
+
+        // Add the signals accessed through hierarchical names.
+        let imports = self.module_imports(id)?;
+        for &import in imports.iter().chain(exports.iter()) {
+            let node = AccessedNode::Hier(import);
+            let ty = llhd::signal_ty(self.emit_type(self.type_of_accessed(node, env)?, env)?);
+            if imports.contains(&import) {
+                sig.add_input(ty);
+                inputs.push(node);
+            } else {
+                sig.add_output(ty);
+                outputs.push(node);
+            }
+            port_id_to_name.insert(node, self.hier_path(import)?);
+        }
+
+        // Add the inout ports, which are split into the value of the port as
+        // driven from outside, and the value and mask of driven bits with
+        // which the module drives it.
+        for &(_, _, ref ty) in &inouts {
+            sig.add_input(ty.clone());
+            sig.add_output(ty.clone());
+            sig.add_output(ty.clone());
         }
 
         // Pick an entity name.
@@ -121,13 +287,21 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         if env != self.default_param_env() {
             entity_name.push_str(&format!(".param{}", env.0));
         }
+        if !exports.is_empty() {
+            entity_name.push_str(&format!(".hier{}", self.tables.module_defs.len()));
+        }
         let name = llhd::ir::UnitName::Global(entity_name.clone());
 
+        // Record the module's attributes.
+        self.emit_attrs(&hir.attrs, env, &name, None)?;
+
         // Create entity.
         let mut ent = llhd::ir::Entity::new(name.clone(), sig.clone());
         let mut builder = llhd::ir::EntityBuilder::new(&mut ent);
-        self.tables.module_signatures.insert((id, env), (name, sig));
-        let mut values = HashMap::<NodeId, llhd::ir::Value>::new();
+        self.tables
+            .module_signatures
+            .insert(((id, env), exports.clone()), (name, sig));
+        let mut values = HashMap::<AccessedNode, llhd::ir::Value>::new();
         let mut gen = UnitGenerator {
             gen: self,
             builder: &mut builder,
@@ -136,31 +310,107 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            inout_drivers: Default::default(),
+            fork_sync: Default::default(),
+            disable_targets: Default::default(),
+            inlined_tasks: Default::default(),
+            gen_loop_values: Default::default(),
         };
 
         // Assign proper port names and collect ports into a lookup table.
-        for (index, port_id) in inputs.into_iter().enumerate() {
+        let inputs_len = inputs.len();
+        for (index, node) in inputs.into_iter().enumerate() {
             let arg = gen.builder.entity.input_arg(index);
             gen.builder
                 .dfg_mut()
-                .set_name(arg, port_id_to_name[&port_id].value.to_string());
-            gen.values.insert(port_id, arg);
+                .set_name(arg, port_id_to_name[&node].clone());
+            gen.values.insert(node, arg);
         }
-        for (index, &port_id) in outputs.iter().enumerate() {
+        for (index, &node) in outputs.iter().enumerate() {
             let arg = gen.builder.entity.output_arg(index);
             gen.builder
                 .dfg_mut()
-                .set_name(arg, port_id_to_name[&port_id].value.to_string());
-            gen.values.insert(port_id, arg);
+                .set_name(arg, port_id_to_name[&node].clone());
+            gen.values.insert(node, arg);
+        }
+        let mut inout_args = vec![];
+        for (index, &(port_id, net, _)) in inouts.iter().enumerate() {
+            let name = match gen.hir_of(port_id)? {
+                HirNode::Port(p) => p.name.value,
+                _ => unreachable!(),
+            };
+            let input = gen.builder.entity.input_arg(inputs_len + index);
+            let value = gen.builder.entity.output_arg(outputs.len() + 2 * index);
+            let enable = gen.builder.entity.output_arg(outputs.len() + 2 * index + 1);
+            gen.builder.dfg_mut().set_name(input, format!("{}.i", name));
+            gen.builder.dfg_mut().set_name(value, format!("{}.o", name));
+            gen.builder
+                .dfg_mut()
+                .set_name(enable, format!("{}.oe", name));
+            inout_args.push((net, (input, value, enable)));
+            gen.inout_drivers.insert(net, vec![]);
+
+            // Ports in an ANSI port list are nets of their own, whereas
+            // non-ANSI ports connect to a net declared in the module body.
+            if net == port_id {
+                let ty = gen.type_of(port_id, env)?;
+                let init = gen.type_default_value(ty);
+                let init = gen.emit_const(init, env)?;
+                let local = gen.builder.ins().sig(init);
+                gen.builder.dfg_mut().set_name(local, name.to_string());
+                gen.values.insert(port_id.into(), local);
+            }
+        }
+
+        // Create signals for the declarations accessed through hierarchical
+        // names that reach into the module's instances. These are driven by
+        // the instances, unless they are exported, in which case the output
+        // of the entity is passed on directly.
+        for hier_id in gen.local_hier_names(id)? {
+            if gen.values.contains_key(&AccessedNode::Hier(hier_id)) {
+                continue;
+            }
+            let ty = gen.type_of_accessed(AccessedNode::Hier(hier_id), env)?;
+            let init = gen.type_default_value(ty);
+            let init = gen.emit_const(init, env)?;
+            let value = gen.builder.ins().sig(init);
+            let name = gen.hier_path(hier_id)?;
+            gen.builder.dfg_mut().set_name(value, name);
+            gen.values.insert(AccessedNode::Hier(hier_id), value);
         }
 
         // Emit the actual contents of the entity.
-        gen.emit_module_block(id, env, &hir.block, &entity_name)?;
+        gen.emit_module_block(id, env, &hir.block, &entity_name, "")?;
+
+        // Connect the ports given as expressions to the module body.
+        for &port_id in hir.ports {
+            gen.emit_port_connection(port_id, env)?;
+        }
+
+        // Resolve the drivers of the inout ports.
+        for (net, args) in inout_args {
+            let drivers = gen.inout_drivers.remove(&net).unwrap();
+            gen.emit_inout_port(net, &drivers, args, env)?;
+        }
+
+        // Drive the exported declarations of the module itself.
+        for &export in &exports {
+            let hier = gen.hier_name(export);
+            if !hier.insts.is_empty() {
+                continue;
+            }
+            let value = gen.emitted_value(hier.target);
+            let value = gen.builder.ins().prb(value);
+            let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+            let one_epsilon = gen.builder.ins().const_time(one_epsilon);
+            let target = gen.emitted_value(AccessedNode::Hier(export));
+            gen.builder.ins().drv(target, value, one_epsilon);
+        }
 
         // Assign default values to undriven output ports.
-        for port_id in outputs {
+        for node in undriven {
             let driven = {
-                let value = gen.values[&port_id];
+                let value = gen.values[&node];
                 gen.builder.inst_layout().insts().any(|inst| {
                     match gen.builder.dfg()[inst].opcode() {
                         llhd::ir::Opcode::Drv => gen.builder.dfg()[inst].args()[0] == value,
@@ -174,98 +424,344 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             if driven {
                 continue;
             }
-            let hir = match gen.hir_of(port_id)? {
-                HirNode::Port(p) => p,
-                _ => unreachable!(),
+            let default_value = match node {
+                AccessedNode::Regular(port_id) => {
+                    let hir = match gen.hir_of(port_id)? {
+                        HirNode::Port(p) => p,
+                        _ => unreachable!(),
+                    };
+                    if let Some(default) = hir.default {
+                        gen.constant_value_of(default, env)?
+                    } else {
+                        gen.type_default_value(gen.type_of(port_id, env)?)
+                    }
+                }
+                AccessedNode::Intf(port_id, signal) => gen
+                    .type_default_value(typeck::type_of_intf_signal(gen.cx, port_id, signal, env)?),
+                AccessedNode::Hier(..) | AccessedNode::GenLoop(..) => unreachable!(),
             };
-            let default_value = gen.emit_const(
-                if let Some(default) = hir.default {
-                    gen.constant_value_of(default, env)?
-                } else {
-                    gen.type_default_value(gen.type_of(port_id, env)?)
-                },
-                env,
-            )?;
+            let default_value = gen.emit_const(default_value, env)?;
             let zero_time = llhd::ConstTime::new(num::zero(), 0, 0);
             let zero_time = gen.builder.ins().const_time(zero_time);
             gen.builder
                 .ins()
-                .drv(gen.values[&port_id], default_value, zero_time);
+                .drv(gen.values[&node], default_value, zero_time);
         }
 
         trace!("{}", ent.dump());
         let result = Ok(self.into.add_entity(ent));
-        self.tables.module_defs.insert((id, env), result.clone());
+        self.tables
+            .module_defs
+            .insert(((id, env), exports), result.clone());
         result
     }
 
+    /// Determine the net an `inout` port of a module connects to.
+    ///
+    /// This is the port itself for ports in an ANSI port list, or the net
+    /// named by the port expression otherwise.
+    fn inout_port_net(&mut self, port: &hir::Port, env: ParamEnv) -> Result<NodeId> {
+        let expr = match port.expr {
+            Some(expr) => expr,
+            None => return Ok(port.id),
+        };
+        if let HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        }) = self.hir_of(expr)?
+        {
+            let binding = self.resolve_node(expr, env)?;
+            if let HirNode::VarDecl(decl) = self.hir_of(binding)? {
+                if decl.kind.is_net() {
+                    return Ok(binding);
+                }
+            }
+        }
+        self.emit(
+            DiagBuilder2::error(format!("{} must connect to a single net", port.desc_full()))
+                .span(self.span(expr)),
+        );
+        Err(())
+    }
+
+    /// Determine the upward references a module must be passed from above.
+    ///
+    /// These are the upward references made within the module or any of the
+    /// modules below it which start at a module other than itself. They are
+    /// resolved by the first instantiating module with the given name.
+    fn module_imports(&mut self, id: NodeId) -> Result<Vec<NodeId>> {
+        if let Some(x) = self.tables.module_imports.get(&id) {
+            return Ok(x.clone());
+        }
+        // Guard against recursive instantiation.
+        self.tables.module_imports.insert(id, vec![]);
+        let (names, insts) = self.hier_names_in(id)?;
+        let mut imports: BTreeSet<_> = names
+            .into_iter()
+            .filter(|&name| self.hier_name(name).root.is_some())
+            .collect();
+        for inst in insts {
+            let module = resolver::inst_module(self.cx, inst)?.unwrap();
+            for import in self.module_imports(module)? {
+                if self.hier_name(import).root != Some(id) {
+                    imports.insert(import);
+                }
+            }
+        }
+        let imports: Vec<_> = imports.into_iter().collect();
+        self.tables.module_imports.insert(id, imports.clone());
+        Ok(imports)
+    }
+
+    /// Determine the hierarchical names that reach into the instances of a
+    /// module.
+    ///
+    /// These are the downward references made within the module, as well as
+    /// the upward references from below that start at the module. All names
+    /// are relative to the module.
+    fn local_hier_names(&mut self, id: NodeId) -> Result<BTreeSet<NodeId>> {
+        let (names, insts) = self.hier_names_in(id)?;
+        let mut local: BTreeSet<_> = names
+            .into_iter()
+            .filter(|&name| self.hier_name(name).root.is_none())
+            .collect();
+        for inst in insts {
+            let module = resolver::inst_module(self.cx, inst)?.unwrap();
+            for import in self.module_imports(module)? {
+                let hier = self.hier_name(import);
+                if hier.root == Some(id) && !hier.insts.is_empty() {
+                    local.insert(
+                        self.intern_hier_name(
+                            None,
+                            hier.insts.clone(),
+                            hier.target,
+                            self.span(hier.id),
+                        )
+                        .id,
+                    );
+                }
+            }
+        }
+        Ok(local)
+    }
+
+    /// Collect the hierarchical names used within a module, and the module
+    /// instances within it.
+    fn hier_names_in(&self, id: NodeId) -> Result<(BTreeSet<NodeId>, Vec<NodeId>)> {
+        let mut k = HierNameCollector {
+            cx: self.cx,
+            names: Default::default(),
+            insts: Default::default(),
+        };
+        hir::Visitor::visit_node_with_id(&mut k, id, false);
+        Ok((k.names, k.insts))
+    }
+
+    /// Format a hierarchical name as a dotted path.
+    fn hier_path(&self, id: NodeId) -> Result<String> {
+        let hier = self.hier_name(id);
+        let mut names = vec![];
+        for &id in hier.root.iter().chain(hier.insts.iter()) {
+            names.push(match self.hir_of(id)? {
+                HirNode::Module(x) => x.name.value,
+                HirNode::Inst(x) => x.name.value,
+                _ => unreachable!(),
+            });
+        }
+        names.push(match self.hir_of(hier.target)? {
+            HirNode::VarDecl(x) => x.name.value,
+            HirNode::Port(x) => x.name.value,
+            _ => unreachable!(),
+        });
+        Ok(names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+
     /// Emit the code for a procedure.
     fn emit_procedure(
         &mut self,
         id: NodeId,
         env: ParamEnv,
         name_prefix: &str,
-    ) -> Result<llhd::ir::ModUnit> {
+    ) -> Result<EmittedProcess> {
         let hir = match self.hir_of(id)? {
             HirNode::Proc(x) => x,
             _ => unreachable!(),
         };
+        let proc_name = format!(
+            "{}.{}.{}.{}",
+            name_prefix,
+            match hir.kind {
+                ast::ProcedureKind::Initial => "initial",
+                ast::ProcedureKind::Always => "always",
+                ast::ProcedureKind::AlwaysComb => "always_comb",
+                ast::ProcedureKind::AlwaysLatch => "always_latch",
+                ast::ProcedureKind::AlwaysFf => "always_ff",
+                ast::ProcedureKind::Final => "final",
+            },
+            id.as_usize(),
+            env.0,
+        );
+        let proc_name = llhd::ir::UnitName::Local(proc_name);
+        self.emit_attrs(&hir.attrs, env, &proc_name, None)?;
+        let repeats = match hir.kind {
+            ast::ProcedureKind::Initial | ast::ProcedureKind::Final => false,
+            _ => true,
+        };
+        self.emit_process(hir.stmt, Some(hir.kind), repeats, env, proc_name)
+    }
 
-        // Find the accessed nodes.
-        let acc = self.accessed_nodes(hir.stmt)?;
+    /// Emit the code for a branch of a `fork` statement.
+    ///
+    /// The branch is emitted as a separate process, which idles until it is
+    /// started by the process executing the `fork`. If the `fork` may be
+    /// executed repeatedly, so may the branch.
+    fn emit_fork_branch(
+        &mut self,
+        id: NodeId,
+        repeats: bool,
+        env: ParamEnv,
+        name_prefix: &str,
+    ) -> Result<EmittedProcess> {
+        let proc_name = format!("{}.fork.{}.{}", name_prefix, id.as_usize(), env.0);
+        let proc_name = llhd::ir::UnitName::Local(proc_name);
+        self.emit_process(id, None, repeats, env, proc_name)
+    }
+
+    /// Determine the variables declared within a process which are accessed
+    /// by the branches of its `fork` statements, or by branches nested
+    /// therein.
+    ///
+    /// These variables are shared with the branches as signals.
+    fn fork_shared_vars(&self, stmt: NodeId, branches: &[(NodeId, bool)]) -> Result<Vec<NodeId>> {
+        let mut shared = BTreeSet::new();
+        let mut todo: Vec<NodeId> = branches.iter().map(|&(id, _)| id).collect();
+        while let Some(branch) = todo.pop() {
+            let acc = self.accessed_nodes(branch)?;
+            for &node in acc.read.iter().chain(acc.written.iter()) {
+                if let AccessedNode::Regular(decl) = node {
+                    if self.is_parent_of(stmt, decl)
+                        && !branches.iter().any(|&(id, _)| self.is_parent_of(id, decl))
+                    {
+                        shared.insert(decl);
+                    }
+                }
+            }
+            let mut k = ForkBranchCollector::new(self.cx, false);
+            hir::Visitor::visit_node_with_id(&mut k, branch, false);
+            todo.extend(k.branches.into_iter().map(|(id, _)| id));
+        }
+        Ok(shared.into_iter().collect())
+    }
+
+    /// Emit a process which executes a statement.
+    ///
+    /// Procedures pass their `kind`, which determines how often the statement
+    /// is executed. Branches of `fork` statements pass no kind, and execute the
+    /// statement whenever they are started. The statement `repeats` if it may
+    /// be executed more than once.
+    ///
+    /// Variables declared within the process which are shared with the
+    /// branches of its `fork` statements are passed to the process as both
+    /// inputs and outputs, after the accessed nodes.
+    ///
+    /// Branches receive the signals which start and abort them as additional
+    /// inputs, and drive the signal reporting their completion as an
+    /// additional output. Processes spawning branches receive the completion
+    /// signal of each branch as an input, and drive each branch's start signal
+    /// and the signal aborting all branches as outputs.
+    fn emit_process(
+        &mut self,
+        stmt: NodeId,
+        kind: Option<ast::ProcedureKind>,
+        repeats: bool,
+        env: ParamEnv,
+        proc_name: llhd::ir::UnitName,
+    ) -> Result<EmittedProcess> {
+        // Find the accessed nodes, the spawned branches, and the variables
+        // shared with them.
+        let acc = self.accessed_nodes(stmt)?;
         trace!("process accesses {:#?}", acc);
+        let mut k = ForkBranchCollector::new(self.cx, repeats);
+        hir::Visitor::visit_node_with_id(&mut k, stmt, false);
+        let ForkBranchCollector {
+            branches,
+            repeated_forks,
+            ..
+        } = k;
+        let shared = self.fork_shared_vars(stmt, &branches)?;
         let mut sig = llhd::ir::Signature::new();
         let mut inputs = vec![];
         let mut outputs = vec![];
         for &id in &acc.read {
             sig.add_input(llhd::signal_ty(
-                self.emit_type(self.type_of(id, env)?, env)?,
+                self.emit_type(self.type_of_accessed(id, env)?, env)?,
             ));
             inputs.push(id);
         }
         for &id in &acc.written {
             sig.add_output(llhd::signal_ty(
-                self.emit_type(self.type_of(id, env)?, env)?,
+                self.emit_type(self.type_of_accessed(id, env)?, env)?,
             ));
             outputs.push(id);
         }
+        for &id in &shared {
+            let ty = llhd::signal_ty(self.emit_type(self.type_of(id, env)?, env)?);
+            sig.add_input(ty.clone());
+            sig.add_output(ty);
+            inputs.push(id.into());
+            outputs.push(id.into());
+        }
         trace!("process inputs = {:#?}", inputs);
         trace!("process outputs = {:#?}", outputs);
 
+        // Add the synchronization signals.
+        let sync_ty = llhd::signal_ty(llhd::int_ty(1));
+        if kind.is_none() {
+            sig.add_input(sync_ty.clone());
+            sig.add_input(sync_ty.clone());
+            sig.add_output(sync_ty.clone());
+        }
+        for _ in &branches {
+            sig.add_input(sync_ty.clone());
+            sig.add_output(sync_ty.clone());
+        }
+        if !branches.is_empty() {
+            sig.add_output(sync_ty.clone());
+        }
+
         // Create process and entry block.
-        let proc_name = format!(
-            "{}.{}.{}.{}",
-            name_prefix,
-            match hir.kind {
-                ast::ProcedureKind::Initial => "initial",
-                ast::ProcedureKind::Always => "always",
-                ast::ProcedureKind::AlwaysComb => "always_comb",
-                ast::ProcedureKind::AlwaysLatch => "always_latch",
-                ast::ProcedureKind::AlwaysFf => "always_ff",
-                ast::ProcedureKind::Final => "final",
-            },
-            id.as_usize(),
-            env.0,
-        );
-        let mut prok = llhd::ir::Process::new(llhd::ir::UnitName::Local(proc_name), sig);
+        let mut prok = llhd::ir::Process::new(proc_name, sig);
         let mut builder = llhd::ir::ProcessBuilder::new(&mut prok);
 
         // Assign names to inputs and outputs.
-        let guess_name = |id| match self.hir_of(id).ok()? {
+        let name_of = |id| match self.hir_of(id).ok()? {
             hir::HirNode::VarDecl(x) => Some(x.name),
             hir::HirNode::Port(x) => Some(x.name),
+            hir::HirNode::Inst(x) => Some(x.name),
             _ => None,
         };
-        for (i, &id) in acc.read.iter().enumerate() {
+        let guess_name = |node| match node {
+            AccessedNode::Regular(id) => name_of(id).map(|name| format!("{}", name)),
+            AccessedNode::Intf(binding, signal) => {
+                Some(format!("{}.{}", name_of(binding)?, name_of(signal)?))
+            }
+            AccessedNode::Hier(id) => self.hier_path(id).ok(),
+            AccessedNode::GenLoop(id) => Some(self.span(id).extract()),
+        };
+        for (i, &id) in inputs.iter().enumerate() {
             if let Some(name) = guess_name(id) {
                 let value = builder.prok.input_arg(i);
-                builder.dfg_mut().set_name(value, format!("{}", name));
+                builder.dfg_mut().set_name(value, name);
             }
         }
-        for (i, &id) in acc.written.iter().enumerate() {
+        for (i, &id) in outputs.iter().enumerate() {
             if let Some(name) = guess_name(id) {
                 let value = builder.prok.output_arg(i);
-                builder.dfg_mut().set_name(value, format!("{}", name));
+                builder.dfg_mut().set_name(value, name);
             }
         }
 
@@ -278,6 +774,47 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         {
             values.insert(id, arg);
         }
+
+        // Collect the synchronization signals.
+        let mut sync_inputs = builder
+            .prok
+            .input_args()
+            .skip(inputs.len())
+            .collect::<Vec<_>>()
+            .into_iter();
+        let mut sync_outputs = builder
+            .prok
+            .output_args()
+            .skip(outputs.len())
+            .collect::<Vec<_>>()
+            .into_iter();
+        let own_sync = match kind {
+            Some(_) => None,
+            None => Some((
+                sync_inputs.next().unwrap(),
+                sync_inputs.next().unwrap(),
+                sync_outputs.next().unwrap(),
+            )),
+        };
+        let branch_sync: Vec<_> = sync_inputs.zip(&mut sync_outputs).collect();
+        let kill = sync_outputs.next();
+        if let Some((start, kill_in, done)) = own_sync {
+            builder.dfg_mut().set_name(start, "fork.start".to_string());
+            builder.dfg_mut().set_name(kill_in, "fork.kill".to_string());
+            builder.dfg_mut().set_name(done, "fork.done".to_string());
+        }
+        for (i, &(done, start)) in branch_sync.iter().enumerate() {
+            builder
+                .dfg_mut()
+                .set_name(start, format!("fork{}.start", i));
+            builder.dfg_mut().set_name(done, format!("fork{}.done", i));
+        }
+        if let Some(kill) = kill {
+            builder
+                .dfg_mut()
+                .set_name(kill, "fork.kill_all".to_string());
+        }
+
         let mut pg = UnitGenerator {
             gen: self,
             builder: &mut builder,
@@ -286,6 +823,11 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            inout_drivers: Default::default(),
+            fork_sync: Default::default(),
+            disable_targets: Default::default(),
+            inlined_tasks: Default::default(),
+            gen_loop_values: Default::default(),
         };
         let entry_blk = pg.add_nameless_block();
         pg.builder.append_to(entry_blk);
@@ -309,9 +851,77 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             pg.shadows.insert(id, shadow);
         }
 
+        // Create the variables tracking the synchronization signals.
+        let mut own_done = None;
+        if own_sync.is_some() || !branch_sync.is_empty() {
+            let zero = pg.builder.ins().const_int(1, 0);
+            for (&(branch, _), &(done, start)) in branches.iter().zip(branch_sync.iter()) {
+                let start_var = pg.builder.ins().var(zero);
+                let expect = pg.builder.ins().var(zero);
+                pg.fork_sync.branches.push((
+                    branch,
+                    ForkBranch {
+                        start: (start, start_var),
+                        done,
+                        expect,
+                    },
+                ));
+            }
+            if let Some(kill) = kill {
+                let kill_var = pg.builder.ins().var(zero);
+                pg.fork_sync.kill = Some((kill, kill_var));
+            }
+            if let Some((_, _, done)) = own_sync {
+                own_done = Some((done, pg.builder.ins().var(zero)));
+            }
+        }
+        pg.fork_sync.repeated = repeated_forks;
+
         // Emit prologue and determine which basic block to jump back to.
-        let head_blk = match hir.kind {
-            ast::ProcedureKind::AlwaysComb | ast::ProcedureKind::AlwaysLatch => {
+        let head_blk = match kind {
+            // Branches idle until the start signal toggles.
+            None => {
+                let (start, kill_in, _) = own_sync.unwrap();
+                let idle_blk = pg.add_named_block("idle");
+                pg.builder.ins().br(idle_blk);
+                pg.builder.append_to(idle_blk);
+                let start_init = pg.builder.ins().prb(start);
+                let check_blk = pg.add_named_block("check");
+                match pg.fork_sync.kill {
+                    // Branches which spawn branches of their own pass on
+                    // aborts received while idle, since the spawned branches
+                    // may still be running.
+                    Some((kill, kill_var)) => {
+                        let kill_init = pg.builder.ins().prb(kill_in);
+                        pg.builder.ins().wait(check_blk, vec![start, kill_in]);
+                        pg.builder.append_to(check_blk);
+                        let kill_now = pg.builder.ins().prb(kill_in);
+                        let killed = pg.builder.ins().neq(kill_init, kill_now);
+                        let forward_blk = pg.add_named_block("forward_abort");
+                        let cont_blk = pg.add_nameless_block();
+                        pg.builder.ins().br_cond(killed, cont_blk, forward_blk);
+                        pg.builder.append_to(forward_blk);
+                        pg.emit_toggle(kill, kill_var);
+                        pg.builder.ins().br(cont_blk);
+                        pg.builder.append_to(cont_blk);
+                    }
+                    None => {
+                        pg.builder.ins().wait(check_blk, vec![start]);
+                        pg.builder.append_to(check_blk);
+                    }
+                }
+                let start_now = pg.builder.ins().prb(start);
+                let started = pg.builder.ins().neq(start_init, start_now);
+                let body_blk = pg.add_named_block("body");
+                pg.builder.ins().br_cond(started, idle_blk, body_blk);
+                pg.builder.append_to(body_blk);
+                pg.emit_shadow_update();
+                let kill_init = pg.builder.ins().prb(kill_in);
+                let abort_blk = pg.add_named_block("abort");
+                pg.fork_sync.abort = Some((kill_in, kill_init, abort_blk));
+                idle_blk
+            }
+            Some(ast::ProcedureKind::AlwaysComb) | Some(ast::ProcedureKind::AlwaysLatch) => {
                 let body_blk = pg.add_named_block("body");
                 let check_blk = pg.add_named_block("check");
                 pg.builder.ins().br(body_blk);
@@ -325,7 +935,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 pg.emit_shadow_update();
                 check_blk
             }
-            ast::ProcedureKind::Final => {
+            Some(ast::ProcedureKind::Final) => {
                 // TODO(fschuiki): Replace this with a cleverer way to implement a trigger-on-end.
                 let body_blk = pg.add_named_block("body");
                 let endtimes = pg.builder.ins().const_time(llhd::ConstTime::new(
@@ -345,119 +955,562 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         };
 
         // Emit the main statement.
-        pg.emit_stmt(hir.stmt, env)?;
+        pg.emit_stmt(stmt, env)?;
 
         // Emit epilogue.
-        match hir.kind {
-            ast::ProcedureKind::Initial => {
+        match kind {
+            // Branches abort their own branches when they are aborted, and
+            // signal their completion in either case.
+            None => {
+                let (_, _, abort_blk) = pg.fork_sync.abort.unwrap();
+                let exit_blk = pg.add_named_block("exit");
+                pg.builder.ins().br(exit_blk);
+                pg.builder.append_to(abort_blk);
+                if let Some((kill, kill_var)) = pg.fork_sync.kill {
+                    pg.emit_toggle(kill, kill_var);
+                }
+                pg.builder.ins().br(exit_blk);
+                pg.builder.append_to(exit_blk);
+                let (done, done_var) = own_done.unwrap();
+                pg.emit_toggle(done, done_var);
+                pg.builder.ins().br(head_blk);
+            }
+            Some(ast::ProcedureKind::Initial) => {
                 pg.builder.ins().halt();
             }
-            ast::ProcedureKind::Always
-            | ast::ProcedureKind::AlwaysComb
-            | ast::ProcedureKind::AlwaysLatch
-            | ast::ProcedureKind::AlwaysFf => {
+            Some(ast::ProcedureKind::Always)
+            | Some(ast::ProcedureKind::AlwaysComb)
+            | Some(ast::ProcedureKind::AlwaysLatch)
+            | Some(ast::ProcedureKind::AlwaysFf) => {
                 pg.builder.ins().br(head_blk);
             }
-            ast::ProcedureKind::Final => {
+            Some(ast::ProcedureKind::Final) => {
                 pg.builder.ins().halt();
             }
         }
-        Ok(self.into.add_process(prok))
-    }
-
-    /// Map a type to an LLHD type (interned).
-    fn emit_type(&mut self, ty: Type<'gcx>, env: ParamEnv) -> Result<llhd::Type> {
-        if let Some(x) = self.tables.interned_types.get(&(ty, env)) {
-            x.clone()
-        } else {
-            let x = self.emit_type_uninterned(ty, env);
-            self.tables.interned_types.insert((ty, env), x.clone());
-            x
-        }
-    }
-
-    /// Map a type to an LLHD type.
-    fn emit_type_uninterned(&mut self, ty: Type<'gcx>, env: ParamEnv) -> Result<llhd::Type> {
-        #[allow(unreachable_patterns)]
-        Ok(match *ty {
-            TypeKind::Void => llhd::void_ty(),
-            TypeKind::Bit(_) => llhd::int_ty(1),
-            TypeKind::Int(width, _) => llhd::int_ty(width),
-            TypeKind::Named(_, _, ty) => self.emit_type(ty, env)?,
-            TypeKind::Struct(id) => {
-                let fields = match self.hir_of(id)? {
-                    HirNode::Type(hir::Type {
-                        kind: hir::TypeKind::Struct(ref fields),
-                        ..
-                    }) => fields,
-                    _ => unreachable!(),
-                };
-                let mut types = vec![];
-                for &field_id in fields {
-                    types.push(self.emit_type(self.type_of(field_id, env)?, env)?);
-                }
-                llhd::struct_ty(types)
-            }
-            TypeKind::PackedArray(size, ty) => llhd::array_ty(size, self.emit_type(ty, env)?),
-            // TODO(fschuiki): emit logic type depending on value domain
-            TypeKind::BitScalar { .. } => llhd::int_ty(1),
-            // TODO(fschuiki): emit logic type depending on value domain
-            TypeKind::BitVector { range, .. } => llhd::int_ty(range.size),
-            _ => unimplemented!("emit type {:?}", ty),
+        Ok(EmittedProcess {
+            unit: self.into.add_process(prok),
+            branches,
+            shared,
         })
     }
 
-    /// Execute the initialization step of a generate loop.
-    fn execute_genvar_init(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        match hir {
-            HirNode::GenvarDecl(_) => (),
-            _ => unreachable!(),
+    /// Emit the code for a function or task.
+    ///
+    /// Returns the name and signature of the emitted LLHD function, which is
+    /// emitted only once per parameter environment. All functions and tasks
+    /// are treated as automatic, i.e. their arguments and variables are
+    /// allocated anew upon every call. Input arguments are passed by value,
+    /// output, inout, and ref arguments by pointer. Signals accessed directly
+    /// by the function or task are passed as additional arguments after the
+    /// regular ones.
+    fn emit_subroutine(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+    ) -> Result<(llhd::ir::UnitName, llhd::ir::Signature)> {
+        if let Some(x) = self.tables.subroutine_signatures.get(&(id, env)) {
+            return Ok(x.clone());
         }
-        Ok(env)
-    }
-
-    /// Execute the iteration step of a generate loop.
-    fn execute_genvar_step(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        let mut env_data = self.param_env_data(env).clone();
-        let next = match hir {
-            HirNode::Expr(expr) => match expr.kind {
-                hir::ExprKind::Unary(op, target_id) => {
-                    let target_id = self.resolve_node(target_id, env)?;
-                    let current_value = self.constant_value_of(target_id, env)?;
-                    let next_value = match current_value.kind {
-                        ValueKind::Int(ref v, ..) => match op {
-                            hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
-                            hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
-                            _ => None,
-                        }
-                        .map(|v| value::make_int(current_value.ty, v)),
-                        _ => unreachable!(),
-                    };
-                    next_value.map(|v| (target_id, v))
-                }
-                _ => None,
-            },
+        let hir = match self.hir_of(id)? {
+            HirNode::Subroutine(x) => x,
+            _ => unreachable!(),
+        };
+        debug!("emit {} with {:?}", hir.desc_full(), env);
+
+        // Non-static methods receive the object they are called on as their
+        // first argument.
+        let class = resolver::declaring_class(self.cx, id);
+        let member = class.map(|c| c.members.iter().find(|m| m.id == id).unwrap());
+        let this_class = match (class, member) {
+            (Some(class), Some(member)) if !member.is_static => Some(class),
             _ => None,
         };
-        match next {
-            Some((target_id, next_value)) => {
-                env_data.set_value(target_id, self.intern_value(next_value));
-                return Ok(self.intern_param_env(env_data));
-            }
-            None => {
-                self.emit(
-                    DiagBuilder2::error(format!(
-                        "{} is not a valid genvar iteration step",
-                        hir.desc_full()
+        let first_port = if this_class.is_some() { 1 } else { 0 };
+
+        // Determine the signature.
+        let mut sig = llhd::ir::Signature::new();
+        if this_class.is_some() {
+            sig.add_input(llhd::int_ty(64));
+        }
+        let mut ports = vec![];
+        for &port_id in &hir.ports {
+            let port = match self.hir_of(port_id)? {
+                HirNode::SubroutinePort(x) => x,
+                _ => unreachable!(),
+            };
+            let ty = self.emit_type(self.type_of(port_id, env)?, env)?;
+            sig.add_input(match port.dir {
+                ast::SubroutinePortDir::Input | ast::SubroutinePortDir::ConstRef => ty,
+                _ => llhd::pointer_ty(ty),
+            });
+            ports.push(port);
+        }
+        let acc = self.accessed_nodes(id)?;
+        let signals: Vec<_> = acc.read.union(&acc.written).cloned().collect();
+
+        // Calls to virtual methods are dispatched to an override which is
+        // only known at runtime, and which may access different signals. The
+        // signals cannot be passed along, so virtual methods may not access
+        // any.
+        if let (Some(class), Some(&node)) = (class, signals.first()) {
+            if resolver::is_virtual_method(self.cx, class.id, hir.name.value)? {
+                let span = match node {
+                    AccessedNode::Regular(id)
+                    | AccessedNode::Hier(id)
+                    | AccessedNode::GenLoop(id) => self.span(id),
+                    AccessedNode::Intf(_, signal) => self.span(signal),
+                };
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "virtual {} cannot access signals outside of its class",
+                        hir.desc_full()
                     ))
-                    .span(hir.human_span()),
+                    .span(hir.human_span())
+                    .add_note(format!("`{}` is accessed by the method", span.extract()))
+                    .span(span),
+                );
+                return Err(());
+            }
+        }
+        for &node in &signals {
+            sig.add_input(llhd::signal_ty(
+                self.emit_type(self.type_of_accessed(node, env)?, env)?,
+            ));
+        }
+        let retty = self.emit_type(self.type_of(id, env)?, env)?;
+        sig.set_return_type(retty.clone());
+
+        // Pick a function name. Functions declared in a module are prefixed
+        // with the module's name, and methods with the class's name.
+        let mut func_name = String::new();
+        let mut parent = self.parent_node_id(id);
+        if let Some(class) = class {
+            func_name.push_str(&format!("{}.", self.class_unit_name(class.id, env)?));
+            parent = None;
+        }
+        while let Some(parent_id) = parent {
+            let prefix = match self.hir_of(parent_id) {
+                Ok(HirNode::Module(x)) => Some(x.name.value),
+                Ok(HirNode::Interface(x)) => Some(x.name.value),
+                Ok(HirNode::Package(x)) => Some(x.name.value),
+                _ => None,
+            };
+            if let Some(prefix) = prefix {
+                func_name.push_str(&format!("{}.", prefix));
+                break;
+            }
+            parent = self.parent_node_id(parent_id);
+        }
+        func_name.push_str(&hir.name.value.to_string());
+        if class.is_none() && env != self.default_param_env() {
+            func_name.push_str(&format!(".param{}", env.0));
+        }
+        let name = llhd::ir::UnitName::Global(func_name);
+        self.tables
+            .subroutine_signatures
+            .insert((id, env), (name.clone(), sig.clone()));
+
+        // Pure virtual methods have no implementation. Calls to them are
+        // always dispatched to the implementation of a derived class.
+        if member.map(|m| m.kind) == Some(hir::ClassMemberKind::PureMethod) {
+            return Ok((name, sig));
+        }
+
+        // Create the function and assign names to the arguments.
+        let mut func = llhd::ir::Function::new(name.clone(), sig.clone());
+        let mut builder = llhd::ir::FunctionBuilder::new(&mut func);
+        let mut values = HashMap::new();
+        if let Some(class) = this_class {
+            let arg = builder.func.input_arg(0);
+            builder.dfg_mut().set_name(arg, "this".to_string());
+            values.insert(class.id.into(), arg);
+        }
+        for (i, port) in ports.iter().enumerate() {
+            let arg = builder.func.input_arg(first_port + i);
+            builder.dfg_mut().set_name(arg, port.name.value.to_string());
+        }
+        for (i, &node) in signals.iter().enumerate() {
+            let arg = builder.func.input_arg(first_port + ports.len() + i);
+            if let AccessedNode::Regular(id) = node {
+                if let Ok(HirNode::VarDecl(x)) = self.hir_of(id) {
+                    builder.dfg_mut().set_name(arg, x.name.value.to_string());
+                }
+            }
+            values.insert(node, arg);
+        }
+        let mut fg = UnitGenerator {
+            gen: self,
+            builder: &mut builder,
+            values: &mut values,
+            interned_consts: Default::default(),
+            interned_lvalues: Default::default(),
+            interned_rvalues: Default::default(),
+            shadows: Default::default(),
+            inout_drivers: Default::default(),
+            fork_sync: Default::default(),
+            disable_targets: Default::default(),
+            inlined_tasks: Default::default(),
+            gen_loop_values: Default::default(),
+        };
+        let entry_blk = fg.add_nameless_block();
+        fg.builder.append_to(entry_blk);
+
+        // Input arguments may be assigned to like regular variables, so copy
+        // them into one.
+        for (i, port) in ports.iter().enumerate() {
+            let arg = fg.builder.unit().input_arg(first_port + i);
+            let value = match port.dir {
+                ast::SubroutinePortDir::Input | ast::SubroutinePortDir::ConstRef => {
+                    let var = fg.builder.ins().var(arg);
+                    fg.builder
+                        .dfg_mut()
+                        .set_name(var, format!("{}.var", port.name.value));
+                    var
+                }
+                _ => arg,
+            };
+            fg.set_emitted_value(port.id, value);
+        }
+
+        // Functions may assign their return value to the function name.
+        let retvar = if hir.is_function() && !retty.is_void() {
+            let zero = fg.emit_zero_for_type(&retty);
+            let var = fg.builder.ins().var(zero);
+            fg.builder
+                .dfg_mut()
+                .set_name(var, format!("{}.ret", hir.name.value));
+            fg.set_emitted_value(id, var);
+            Some(var)
+        } else {
+            None
+        };
+
+        // Constructors that do not explicitly call `super.new` implicitly
+        // construct the base class first.
+        if let Some(class) = this_class {
+            if class.constructor().map(|m| m.id) == Some(id)
+                && !fg.calls_super_new(hir.stmts.first().cloned())?
+            {
+                let this = fg.emitted_value(class.id);
+                fg.emit_implicit_super_new(this, class.id, env)?;
+            }
+        }
+
+        // Emit the body and return.
+        for &stmt_id in &hir.stmts {
+            fg.emit_stmt(stmt_id, env)?;
+        }
+        match retvar {
+            Some(var) => {
+                let value = fg.builder.ins().ld(var);
+                fg.builder.ins().ret_value(value);
+            }
+            None => {
+                fg.builder.ins().ret();
+            }
+        }
+        trace!("{}", func.dump());
+        self.into.add_function(func);
+        Ok((name, sig))
+    }
+
+    /// Determine the name under which the units and runtime functions of a
+    /// class are emitted.
+    fn class_unit_name(&self, class_id: NodeId, env: ParamEnv) -> Result<String> {
+        let name = match self.hir_of(class_id)? {
+            HirNode::Class(x) => x.name.value,
+            _ => unreachable!(),
+        };
+        if env != self.default_param_env() {
+            Ok(format!("{}.param{}", name, env.0))
+        } else {
+            Ok(format!("{}", name))
+        }
+    }
+
+    /// Emit the implementations of the virtual methods of a class.
+    ///
+    /// The simulation runtime dispatches calls to virtual methods to the
+    /// implementation of the object's class, which is the most derived
+    /// override of each method. Emits these overrides such that they are
+    /// available to the runtime.
+    fn emit_virtual_methods(&mut self, class_id: NodeId, env: ParamEnv) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut next = Some((class_id, env));
+        while let Some((id, env)) = next {
+            let class = match self.hir_of(id)? {
+                HirNode::Class(x) => x,
+                _ => unreachable!(),
+            };
+            for member in &class.members {
+                if member.kind == hir::ClassMemberKind::Property || !seen.insert(member.name.value)
+                {
+                    continue;
+                }
+                if member.kind == hir::ClassMemberKind::Method
+                    && resolver::is_virtual_method(self.cx, id, member.name.value)?
+                {
+                    self.emit_subroutine(member.id, env)?;
+                }
+            }
+            next = resolver::class_base_type(self.cx, id, env)?;
+        }
+        Ok(())
+    }
+
+    /// Determine the type of an accessed node.
+    fn type_of_accessed(&self, node: AccessedNode, env: ParamEnv) -> Result<Type<'gcx>> {
+        match node {
+            AccessedNode::Regular(id) => self.type_of(id, env),
+            AccessedNode::Intf(binding, signal) => {
+                typeck::type_of_intf_signal(self.cx, binding, signal, env)
+            }
+            AccessedNode::Hier(id) => {
+                let hier = self.hier_name(id);
+                self.type_of(hier.target, resolver::hier_name_env(self.cx, &hier, env)?)
+            }
+            AccessedNode::GenLoop(id) => self.type_of(id, env),
+        }
+    }
+
+    /// Determine the signals of an interface instance or port.
+    ///
+    /// For ports restricted to a modport, only the signals listed in the
+    /// modport are returned, with the modport's direction. Otherwise all
+    /// signals are returned as `inout`.
+    fn interface_signals(
+        &self,
+        binding: NodeId,
+        env: ParamEnv,
+    ) -> Result<Vec<InterfaceSignal<'gcx>>> {
+        let (decl, intf_env, modport) = match self.type_of(binding, env)?.get_interface() {
+            Some(x) => x,
+            None => unreachable!("{:?} is not an interface", binding),
+        };
+        let intf = match self.hir_of(decl)? {
+            HirNode::Interface(x) => x,
+            _ => unreachable!(),
+        };
+        let modport = match modport {
+            Some(id) => match self.hir_of(id)? {
+                HirNode::Modport(x) => Some(x),
+                _ => unreachable!(),
+            },
+            None => None,
+        };
+        let mut signals = vec![];
+        for &id in intf.ports.iter().chain(intf.block.decls.iter()) {
+            let name = match self.hir_of(id)? {
+                HirNode::Port(x) => x.name,
+                HirNode::VarDecl(x) => x.name,
+                _ => unreachable!(),
+            };
+            let dir = match modport {
+                Some(modport) => match modport.find(name.value) {
+                    Some(port) => port.dir,
+                    None => continue,
+                },
+                None => ast::PortDir::Inout,
+            };
+            signals.push(InterfaceSignal {
+                id,
+                name,
+                dir,
+                ty: self.type_of(id, intf_env)?,
+            });
+        }
+        Ok(signals)
+    }
+
+    /// Record the attributes attached to a unit or a value therein.
+    fn emit_attrs(
+        &mut self,
+        attrs: &[hir::Attr],
+        env: ParamEnv,
+        unit: &llhd::ir::UnitName,
+        target: Option<Name>,
+    ) -> Result<()> {
+        for attr in attrs {
+            let value = match attr.value {
+                Some(expr_id) => Some(self.emit_attr_value(expr_id, env)?),
+                None => None,
+            };
+            self.metadata.attrs.push(AttrMetadata {
+                unit: unit.clone(),
+                target: target.map(|n| n.to_string()),
+                name: attr.name.value,
+                value,
+            });
+        }
+        Ok(())
+    }
+
+    /// Evaluate the value of an attribute to a string.
+    fn emit_attr_value(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<String> {
+        if let HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::StringConst(value),
+            ..
+        }) = self.hir_of(expr_id)?
+        {
+            return Ok(value.value.to_string());
+        }
+        match self.constant_value_of(expr_id, env)?.kind {
+            ValueKind::Int(ref v, ..) => Ok(v.to_string()),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error("attribute value must be a constant string or integer")
+                        .span(self.span(expr_id)),
                 );
                 Err(())
             }
         }
     }
+
+    /// Map a type to an LLHD type (interned).
+    fn emit_type(&mut self, ty: Type<'gcx>, env: ParamEnv) -> Result<llhd::Type> {
+        if let Some(x) = self.tables.interned_types.get(&(ty, env)) {
+            x.clone()
+        } else {
+            let x = self.emit_type_uninterned(ty, env);
+            self.tables.interned_types.insert((ty, env), x.clone());
+            x
+        }
+    }
+
+    /// Map a type to an LLHD type.
+    fn emit_type_uninterned(&mut self, ty: Type<'gcx>, env: ParamEnv) -> Result<llhd::Type> {
+        #[allow(unreachable_patterns)]
+        Ok(match *ty {
+            TypeKind::Void => llhd::void_ty(),
+            TypeKind::Bit(_) => llhd::int_ty(1),
+            TypeKind::Int(width, _) => llhd::int_ty(width),
+            TypeKind::Named(_, _, ty) => self.emit_type(ty, env)?,
+            // Unions are represented as a bit vector that holds the widest
+            // member and the tag, if any.
+            TypeKind::Struct(id) if self.struct_def(id)?.is_union() => {
+                llhd::int_ty(ty::bit_size_of_type(self.cx, ty, env)?)
+            }
+            TypeKind::Struct(id) => {
+                let fields = match self.hir_of(id)? {
+                    HirNode::Type(hir::Type {
+                        kind: hir::TypeKind::Struct(ref fields),
+                        ..
+                    }) => fields,
+                    _ => unreachable!(),
+                };
+                let mut types = vec![];
+                for &field_id in fields {
+                    types.push(self.emit_type(self.type_of(field_id, env)?, env)?);
+                }
+                llhd::struct_ty(types)
+            }
+            TypeKind::PackedArray(size, ty) => llhd::array_ty(size, self.emit_type(ty, env)?),
+            TypeKind::UnpackedArray(range, ty) => {
+                llhd::array_ty(range.size, self.emit_type(ty, env)?)
+            }
+            // Reals are represented by their IEEE 754 bit pattern.
+            TypeKind::Real => llhd::int_ty(64),
+            TypeKind::ShortReal => llhd::int_ty(32),
+            // Dynamic containers, strings, and class objects are handles into
+            // the simulation runtime.
+            TypeKind::DynamicArray(..)
+            | TypeKind::Queue(..)
+            | TypeKind::AssocArray(..)
+            | TypeKind::String
+            | TypeKind::Class { .. }
+            | TypeKind::Null => llhd::int_ty(64),
+            // Four-valued types are lowered as two-valued integers, since
+            // LLHD provides no nine-valued logic type. The `x` and `z` bits
+            // only exist in constants, where they are lowered as `0`. See
+            // `RvalueKind::Const` in `emit_mir_rvalue_uninterned`.
+            TypeKind::BitScalar { .. } => llhd::int_ty(1),
+            TypeKind::BitVector { range, .. } => llhd::int_ty(range.size),
+            _ => unimplemented!("emit type {:?}", ty),
+        })
+    }
+}
+
+/// A signal of an interface, as seen through an interface instance or port.
+struct InterfaceSignal<'gcx> {
+    /// The signal declaration within the interface.
+    id: NodeId,
+    /// The name of the signal.
+    name: Spanned<Name>,
+    /// The direction of the signal.
+    dir: ast::PortDir,
+    /// The type of the signal.
+    ty: Type<'gcx>,
+}
+
+/// A continuous driver of a net.
+struct NetDriver {
+    /// The span of the driving assignment or connection.
+    span: Span,
+    /// How the net is driven.
+    kind: NetDriverKind,
+    /// The strength levels with which a 0 and a 1 are driven.
+    strength: (usize, usize),
+}
+
+/// The ways in which a net may be driven.
+#[derive(Clone, Copy)]
+enum NetDriverKind {
+    /// An expression which always drives the net.
+    Value(NodeId),
+    /// A tri-state expression such as `oe ? d : 'z`, given as the enable
+    /// condition, whether the condition is active low, and the driven value.
+    Tristate(NodeId, bool, NodeId),
+    /// The value and the mask of driven bits with which an instance drives
+    /// the net through an `inout` port.
+    Port(llhd::ir::Value, llhd::ir::Value),
+}
+
+impl NetDriver {
+    fn new(
+        span: Span,
+        kind: NetDriverKind,
+        strength: Option<(ast::DriveStrength, ast::DriveStrength)>,
+    ) -> Self {
+        let mut levels = (6, 6);
+        for s in strength.iter().flat_map(|&(a, b)| vec![a, b]) {
+            // Strength levels as per §28.11, with `highz` being 0 and
+            // `supply` being 7.
+            match s {
+                ast::DriveStrength::Supply0 => levels.0 = 7,
+                ast::DriveStrength::Strong0 => levels.0 = 6,
+                ast::DriveStrength::Pull0 => levels.0 = 5,
+                ast::DriveStrength::Weak0 => levels.0 = 3,
+                ast::DriveStrength::HighZ0 => levels.0 = 0,
+                ast::DriveStrength::Supply1 => levels.1 = 7,
+                ast::DriveStrength::Strong1 => levels.1 = 6,
+                ast::DriveStrength::Pull1 => levels.1 = 5,
+                ast::DriveStrength::Weak1 => levels.1 = 3,
+                ast::DriveStrength::HighZ1 => levels.1 = 0,
+            }
+        }
+        NetDriver {
+            span,
+            kind,
+            strength: levels,
+        }
+    }
+
+    /// Check whether this driver always drives the net.
+    fn is_always(&self) -> bool {
+        match self.kind {
+            NetDriverKind::Value(..) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Check whether a driver of the given strengths always wins over another.
+///
+/// This is the case if it drives either value stronger than the other driver
+/// drives the opposite value.
+fn dominates((s0, s1): (usize, usize), (t0, t1): (usize, usize)) -> bool {
+    s0 > t1 && s1 > t0
 }
 
 /// A code generator for functions, processes, and entities.
@@ -467,7 +1520,7 @@ struct UnitGenerator<'a, 'gcx, C, UB> {
     /// The builder into which instructions are emitted.
     builder: &'a mut UB,
     /// The emitted LLHD values for various nodes.
-    values: &'a mut HashMap<NodeId, llhd::ir::Value>,
+    values: &'a mut HashMap<AccessedNode, llhd::ir::Value>,
     /// The constant values emitted into the unit.
     interned_consts: HashMap<Value<'gcx>, Result<llhd::ir::Value>>,
     /// The MIR lvalues emitted into the unit.
@@ -477,7 +1530,69 @@ struct UnitGenerator<'a, 'gcx, C, UB> {
     interned_rvalues: HashMap<NodeId, Result<llhd::ir::Value>>,
     /// The shadow variables introduced to handle signals which are both read
     /// and written in a process.
-    shadows: HashMap<NodeId, llhd::ir::Value>,
+    shadows: HashMap<AccessedNode, llhd::ir::Value>,
+    /// The drivers of the `inout` ports of a module, collected while emitting
+    /// its body.
+    inout_drivers: HashMap<NodeId, Vec<NetDriver>>,
+    /// The synchronization of a process with the branches of `fork`
+    /// statements.
+    fork_sync: ForkSync,
+    /// The labelled blocks being emitted, together with the basic block
+    /// following them, for use by `disable` statements.
+    disable_targets: Vec<(Name, llhd::ir::Block)>,
+    /// The tasks being inlined into a process, together with the basic block
+    /// following them, for use by `return` statements.
+    inlined_tasks: Vec<(NodeId, llhd::ir::Block)>,
+    /// The signals declared in the iterations of generate loops, given as
+    /// `(decl, env)` where `env` is the environment of the iteration.
+    gen_loop_values: HashMap<(NodeId, ParamEnv), llhd::ir::Value>,
+}
+
+/// The signals through which a process synchronizes with the branches of
+/// `fork` statements, which are emitted as separate processes.
+///
+/// All synchronization signals are single bits which are toggled to signal an
+/// event. Since a process cannot probe its outputs, it keeps track of the
+/// values it drives in variables.
+#[derive(Debug, Default)]
+struct ForkSync {
+    /// The branches spawned by the process, in order of appearance.
+    branches: Vec<(NodeId, ForkBranch)>,
+    /// The signal which aborts all spawned branches, and the variable tracking
+    /// its value.
+    kill: Option<(llhd::ir::Value, llhd::ir::Value)>,
+    /// If the process is a branch itself: the signal which aborts it, the
+    /// value of that signal when the branch was started, and the block to
+    /// jump to upon abortion.
+    abort: Option<(llhd::ir::Value, llhd::ir::Value, llhd::ir::Block)>,
+    /// The `fork` statements which may be executed again while their
+    /// branches are still running.
+    repeated: HashSet<NodeId>,
+}
+
+/// A process emitted for a procedure or a branch of a `fork` statement.
+#[derive(Debug)]
+struct EmittedProcess {
+    /// The emitted process.
+    unit: llhd::ir::ModUnit,
+    /// The branches of the `fork` statements executed by the process, and
+    /// whether they may be started repeatedly.
+    branches: Vec<(NodeId, bool)>,
+    /// The variables declared within the process which are shared with the
+    /// branches as signals.
+    shared: Vec<NodeId>,
+}
+
+/// The synchronization signals of a single branch of a `fork` statement.
+#[derive(Debug, Clone, Copy)]
+struct ForkBranch {
+    /// The signal which starts the branch, and the variable tracking its
+    /// value.
+    start: (llhd::ir::Value, llhd::ir::Value),
+    /// The signal which the branch toggles upon completion.
+    done: llhd::ir::Value,
+    /// The variable holding the value of `done` once the branch completes.
+    expect: llhd::ir::Value,
 }
 
 impl<'a, 'gcx, C, UB> Deref for UnitGenerator<'a, 'gcx, C, UB> {
@@ -499,12 +1614,33 @@ where
     C: Context<'gcx> + 'b,
     UB: UnitBuilder,
 {
-    fn emitted_value(&self, node_id: NodeId) -> llhd::ir::Value {
-        self.values[&node_id]
+    fn emitted_value(&self, node: impl Into<AccessedNode>) -> llhd::ir::Value {
+        self.values[&node.into()]
     }
 
-    fn set_emitted_value(&mut self, node_id: NodeId, value: llhd::ir::Value) {
-        self.values.insert(node_id, value);
+    fn set_emitted_value(&mut self, node: impl Into<AccessedNode>, value: llhd::ir::Value) {
+        self.values.insert(node.into(), value);
+    }
+
+    /// Determine the signal of a declaration in an iteration of a generate
+    /// loop, accessed through a field access such as `g_loop[1].x`.
+    fn gen_loop_value(&self, expr_id: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
+        let binding = resolver::resolve_gen_block_field(self.cx, expr_id)?.unwrap();
+        let iter_env = resolver::gen_block_field_env(self.cx, expr_id, env)?;
+        match self.gen_loop_values.get(&(binding, iter_env)) {
+            Some(&value) => Ok(value),
+            None => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` cannot be accessed here",
+                        self.span(expr_id).extract()
+                    ))
+                    .span(self.span(expr_id))
+                    .add_note("the generate loop is only emitted after this point"),
+                );
+                Err(())
+            }
+        }
     }
 
     /// Emit the code for the contents of a module.
@@ -514,6 +1650,7 @@ where
         env: ParamEnv,
         hir: &hir::ModuleBlock,
         name_prefix: &str,
+        scope: &str,
     ) -> Result<()> {
         // Emit declarations.
         for &decl_id in &hir.decls {
@@ -522,36 +1659,131 @@ where
                 _ => unreachable!(),
             };
             let ty = self.type_of(decl_id, env)?;
-            let init = self.emit_const(
-                match hir.init {
-                    Some(expr) => self.constant_value_of(expr, env)?,
-                    None => self.type_default_value(ty),
-                },
-                env,
-            )?;
+            let init = match hir.kind {
+                // The declaration assignment of a net is a continuous driver
+                // and is emitted together with the other assignments below.
+                hir::VarKind::Net { ty: net_ty, .. } => self.emit_net_init(ty, net_ty, env)?,
+                hir::VarKind::Var => self.emit_const(
+                    match hir.init {
+                        Some(expr) => {
+                            value::cast_value(self.cx, self.constant_value_of(expr, env)?, ty)
+                        }
+                        None => self.type_default_value(ty),
+                    },
+                    env,
+                )?,
+            };
             let value = self.builder.ins().sig(init);
             self.builder
                 .dfg_mut()
-                .set_name(value, hir.name.value.into());
-            self.values.insert(decl_id, value.into());
+                .set_name(value, scoped_name(scope, hir.name.value));
+            self.values.insert(decl_id.into(), value.into());
+            let unit = self.builder.unit().name().clone();
+            self.emit_attrs(&hir.attrs, env, &unit, Some(hir.name.value))?;
+        }
+
+        // Emit the signals of interface instances.
+        for &inst_id in &hir.insts {
+            if interface_of(self.cx, inst_id)?.is_some() {
+                self.emit_interface_inst(inst_id, env, name_prefix, scope)?;
+            }
+        }
+
+        // Emit generate blocks.
+        for &gen_id in &hir.gens {
+            let hir = match self.hir_of(gen_id)? {
+                HirNode::Gen(x) => x,
+                _ => unreachable!(),
+            };
+            match hir.kind {
+                hir::GenKind::If { .. } | hir::GenKind::Case { .. } => {
+                    if let Some(body) = resolver::selected_gen_block(self.cx, hir, env)? {
+                        self.emit_gen_block(id, env, body, name_prefix, scope, None)?;
+                    }
+                }
+                hir::GenKind::For { ref init, body, .. } => {
+                    let genvar = *init.last().unwrap();
+                    for local_env in resolver::gen_loop_iterations(self.cx, hir, env)? {
+                        let index = self.constant_value_of(genvar, local_env)?;
+                        let index = match index.get_int() {
+                            Some(v) => v.to_string(),
+                            None => unreachable!(),
+                        };
+                        self.emit_gen_block(id, local_env, body, name_prefix, scope, Some(&index))?;
+                    }
+                }
+            }
         }
 
-        // Emit assignments.
+        // Collect the continuous drivers of the nets declared in this block,
+        // which are the net declaration assignments, the assignments to the
+        // entire net, and the instances connected to it through an `inout`
+        // port. These are resolved together as per §6.6. The drivers of the
+        // module's own `inout` ports are resolved once the entire module has
+        // been emitted.
+        let mut nets = vec![];
+        let mut net_drivers = HashMap::new();
+        for &decl_id in &hir.decls {
+            let decl = match self.hir_of(decl_id)? {
+                HirNode::VarDecl(x) => x,
+                _ => unreachable!(),
+            };
+            if !decl.kind.is_net() {
+                continue;
+            }
+            let mut drivers = vec![];
+            if let Some(init) = decl.init {
+                let strength = match decl.kind {
+                    hir::VarKind::Net {
+                        strength: Some(ast::NetStrength::Drive(a, b)),
+                        ..
+                    } => Some((a, b)),
+                    _ => None,
+                };
+                let kind = self.net_driver_kind(init)?;
+                drivers.push(NetDriver::new(decl.span, kind, strength));
+            }
+            if let Some(port_drivers) = self.inout_drivers.get_mut(&decl_id) {
+                port_drivers.extend(drivers);
+                continue;
+            }
+            nets.push(decl);
+            net_drivers.insert(decl_id, drivers);
+        }
+        let mut assigns = vec![];
         for &assign_id in &hir.assigns {
             let hir = match self.hir_of(assign_id)? {
                 HirNode::Assign(x) => x,
                 _ => unreachable!(),
             };
-            let lhs = self.mir_lvalue(hir.lhs, env);
-            let rhs = self.mir_rvalue(hir.rhs, env);
-            // TODO(fschuiki): The following should happen in a lowering of the
-            // assignment to its own MIR.
-            let rhs = mir::lower::rvalue::cast_to_type(self.cx, rhs, env, lhs.ty);
-            let lhs = self.emit_mir_lvalue(lhs)?.0;
-            let rhs = self.emit_mir_rvalue(rhs)?;
-            let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
-            let one_epsilon = self.builder.ins().const_time(one_epsilon);
-            self.builder.ins().drv(lhs, rhs, one_epsilon);
+            let net = match self.hir_of(hir.lhs)? {
+                HirNode::Expr(hir::Expr {
+                    kind: hir::ExprKind::Ident(..),
+                    ..
+                }) => Some(self.resolve_node(hir.lhs, env)?),
+                _ => None,
+            };
+            let net = match net {
+                Some(id)
+                    if net_drivers.contains_key(&id) || self.inout_drivers.contains_key(&id) =>
+                {
+                    id
+                }
+                _ => {
+                    assigns.push(hir);
+                    continue;
+                }
+            };
+            let driver = NetDriver::new(hir.span, self.net_driver_kind(hir.rhs)?, hir.strength);
+            match net_drivers.get_mut(&net) {
+                Some(drivers) => drivers.push(driver),
+                None => self.inout_drivers.get_mut(&net).unwrap().push(driver),
+            }
+        }
+
+        // Emit the remaining assignments.
+        for hir in assigns {
+            self.emit_cont_assign(hir, env)?;
         }
 
         // Emit instantiations.
@@ -564,6 +1796,9 @@ where
                 HirNode::InstTarget(x) => x,
                 _ => unreachable!(),
             };
+            if self.gcx().find_interface(target_hir.name.value).is_some() {
+                continue;
+            }
             let resolved = match self.gcx().find_module(target_hir.name.value) {
                 Some(id) => id,
                 None => {
@@ -598,6 +1833,8 @@ where
             trace!("port_mapping = {:#?}", port_mapping);
             let mut inputs = Vec::new();
             let mut outputs = Vec::new();
+            let mut inout_inputs = Vec::new();
+            let mut inout_outputs = Vec::new();
             let resolved_hir = match self.hir_of(resolved)? {
                 HirNode::Module(x) => x,
                 _ => unreachable!(),
@@ -608,10 +1845,98 @@ where
                     _ => unreachable!(),
                 };
                 let mapping = port_mapping.find(port_id);
+
+                // Interface ports connect each of the flattened signals.
+                if interface_of(self.cx, port_id)?.is_some() {
+                    let binding = match mapping {
+                        Some(mapping) => self.interface_binding(mapping.0, env)?,
+                        None => {
+                            self.emit(
+                                DiagBuilder2::error(format!(
+                                    "interface {} must be connected",
+                                    port.desc_full()
+                                ))
+                                .span(hir.human_span()),
+                            );
+                            return Err(());
+                        }
+                    };
+                    for intf_sig in self.interface_signals(port_id, inst_env)? {
+                        let value = self.emitted_value(AccessedNode::Intf(binding, intf_sig.id));
+                        match intf_sig.dir {
+                            ast::PortDir::Input | ast::PortDir::Ref => inputs.push(value),
+                            ast::PortDir::Output => outputs.push(value),
+                            ast::PortDir::Inout => {
+                                inputs.push(value);
+                                outputs.push(value);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // Inout ports see the resolved value of the net they are
+                // connected to, and drive it through a value and a mask of
+                // the driven bits.
+                if port.dir == ast::PortDir::Inout {
+                    let ty = self.type_of(port_id, inst_env)?;
+                    let zero = self.type_default_value(ty);
+                    let zero = self.emit_const(zero, inst_env)?;
+                    let value = self.builder.ins().sig(zero);
+                    let enable = self.builder.ins().sig(zero);
+                    let input = match mapping {
+                        Some((expr_id, expr_env)) => {
+                            let net = self.resolve_node(expr_id, expr_env)?;
+                            let net_ty = self.type_of(net, env)?;
+                            if self.emit_type(net_ty, env)? != self.emit_type(ty, inst_env)? {
+                                self.emit(
+                                    DiagBuilder2::error(format!(
+                                        "{} of type `{}` cannot be connected to a net of type \
+                                         `{}`",
+                                        port.desc_full(),
+                                        ty,
+                                        net_ty
+                                    ))
+                                    .span(self.span(expr_id)),
+                                );
+                                return Err(());
+                            }
+                            let driver = NetDriver::new(
+                                self.span(expr_id),
+                                NetDriverKind::Port(value, enable),
+                                None,
+                            );
+                            match net_drivers.get_mut(&net) {
+                                Some(drivers) => drivers.push(driver),
+                                None => match self.inout_drivers.get_mut(&net) {
+                                    Some(drivers) => drivers.push(driver),
+                                    None => {
+                                        self.emit(
+                                            DiagBuilder2::error(format!(
+                                                "{} must be connected to a net declared in the \
+                                                 same scope",
+                                                port.desc_full()
+                                            ))
+                                            .span(self.span(expr_id)),
+                                        );
+                                        return Err(());
+                                    }
+                                },
+                            }
+                            self.emitted_value(net)
+                        }
+                        None => self.builder.ins().sig(zero),
+                    };
+                    inout_inputs.push(input);
+                    inout_outputs.push(value);
+                    inout_outputs.push(enable);
+                    continue;
+                }
+
                 let (is_input, is_output) = match port.dir {
                     ast::PortDir::Input | ast::PortDir::Ref => (true, false),
                     ast::PortDir::Output => (false, true),
-                    ast::PortDir::Inout => (true, true),
+                    ast::PortDir::Inout => unreachable!(),
                 };
                 if let Some(mapping) = mapping {
                     if is_input {
@@ -638,9 +1963,60 @@ where
                     }
                 }
             }
+
+            // Pass the signals accessed through upward references from below,
+            // and collect the ones accessed through hierarchical names
+            // reaching into the instance.
+            for import in self.module_imports(resolved)? {
+                let hier = self.hier_name(import);
+                let value = if hier.root != Some(id) {
+                    self.emitted_value(AccessedNode::Hier(import))
+                } else if hier.insts.is_empty() {
+                    self.emitted_value(hier.target)
+                } else {
+                    let local = self.intern_hier_name(
+                        None,
+                        hier.insts.clone(),
+                        hier.target,
+                        self.span(hier.id),
+                    );
+                    self.emitted_value(AccessedNode::Hier(local.id))
+                };
+                inputs.push(value);
+            }
+            let mut reaching: Vec<_> = self
+                .values
+                .iter()
+                .flat_map(|(&node, &value)| match node {
+                    AccessedNode::Hier(hier_id) => Some((self.hier_name(hier_id), value)),
+                    _ => None,
+                })
+                .filter(|(hier, _)| hier.root.is_none() && hier.insts.first() == Some(&inst_id))
+                .collect();
+            reaching.sort_by_key(|(hier, _)| hier.id);
+            let mut exports: Vec<_> = reaching
+                .into_iter()
+                .map(|(hier, value)| {
+                    let export = self.intern_hier_name(
+                        None,
+                        hier.insts[1..].to_vec(),
+                        hier.target,
+                        self.span(hier.id),
+                    );
+                    (export.id, value)
+                })
+                .collect();
+            exports.sort();
+            outputs.extend(exports.iter().map(|&(_, value)| value));
+            let exports = exports.into_iter().map(|(export, _)| export).collect();
+
+            // Pass the signals of the inout ports last.
+            inputs.extend(inout_inputs);
+            outputs.extend(inout_outputs);
+
             trace!("inputs = {:#?}", inputs);
             trace!("outputs = {:#?}", outputs);
-            let target = self.emit_module_with_env(resolved, inst_env)?;
+            let target = self.emit_module_with_exports(resolved, inst_env, exports)?;
             // let (name, sig) = self.tables.module_signatures[&(resolved, inst_env)].clone();
             let ext_unit = self.builder.add_extern(
                 self.into[target].name().clone(),
@@ -648,79 +2024,851 @@ where
             );
             self.builder.ins().inst(ext_unit, inputs, outputs);
             // self.builder.dfg_mut().set_name(inst, hir.name.value.into());
+            let unit = self.builder.unit().name().clone();
+            self.emit_attrs(&hir.attrs, env, &unit, Some(hir.name.value))?;
         }
 
-        // Emit generate blocks.
-        for &gen_id in &hir.gens {
-            let hir = match self.hir_of(gen_id)? {
-                HirNode::Gen(x) => x,
-                _ => unreachable!(),
-            };
-            #[allow(unreachable_patterns)]
-            match hir.kind {
-                hir::GenKind::If {
-                    cond,
-                    ref main_body,
-                    ref else_body,
-                } => {
-                    let k = self.constant_value_of(cond, env)?;
-                    if k.is_false() {
-                        if let Some(else_body) = else_body {
-                            self.emit_module_block(id, env, else_body, name_prefix)?;
+        // Emit the resolved drivers of each net.
+        let mut failed = false;
+        for decl in nets {
+            failed |= self
+                .emit_net_drivers(decl, &net_drivers[&decl.id], env)
+                .is_err();
+        }
+        if failed {
+            return Err(());
+        }
+
+        // Emit and instantiate procedures.
+        let name_prefix = scoped_name(name_prefix, scope);
+        for &proc_id in &hir.procs {
+            let prok = self.emit_procedure(proc_id, env, &name_prefix)?;
+            self.emit_process_inst(proc_id, prok, vec![], vec![], env, &name_prefix)?;
+        }
+
+        Ok(())
+    }
+
+    /// Emit a continuous assignment which drives its target directly.
+    fn emit_cont_assign(&mut self, hir: &hir::Assign, env: ParamEnv) -> Result<()> {
+        let lhs = self.mir_lvalue(hir.lhs, env);
+        let rhs = self.mir_rvalue(hir.rhs, env);
+        // TODO(fschuiki): The following should happen in a lowering of the
+        // assignment to its own MIR.
+        let rhs = mir::lower::rvalue::cast_to_type(self.cx, rhs, env, lhs.ty);
+        let lhs = self.emit_mir_lvalue(lhs)?.0;
+        let rhs = self.emit_mir_rvalue(rhs)?;
+        let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+        let one_epsilon = self.builder.ins().const_time(one_epsilon);
+        self.builder.ins().drv(lhs, rhs, one_epsilon);
+        Ok(())
+    }
+
+    /// Instantiate a process which accesses the nodes accessed by `id`.
+    ///
+    /// The variables the process shares with the branches of its `fork`
+    /// statements are emitted as signals. The branches are emitted and
+    /// instantiated as well, connected to the process through newly created
+    /// synchronization signals. If the process is a branch itself, its own
+    /// synchronization signals are passed in `sync_inputs` and
+    /// `sync_outputs`.
+    fn emit_process_inst(
+        &mut self,
+        id: NodeId,
+        prok: EmittedProcess,
+        sync_inputs: Vec<llhd::ir::Value>,
+        sync_outputs: Vec<llhd::ir::Value>,
+        env: ParamEnv,
+        name_prefix: &str,
+    ) -> Result<()> {
+        // Emit the shared variables, which are initialized by the process.
+        for &decl_id in &prok.shared {
+            let ty = self.type_of(decl_id, env)?;
+            let init = self.type_default_value(ty);
+            let init = self.emit_const(init, env)?;
+            let value = self.builder.ins().sig(init);
+            if let HirNode::VarDecl(decl) = self.hir_of(decl_id)? {
+                self.builder
+                    .dfg_mut()
+                    .set_name(value, decl.name.value.to_string());
+            }
+            self.values.insert(decl_id.into(), value);
+        }
+
+        let acc = self.accessed_nodes(id)?;
+        let lookup_value = |&node| match (self.values.get(&node), node) {
+            (Some(v), _) => Ok(v.clone()),
+            (None, AccessedNode::GenLoop(expr_id)) => self.gen_loop_value(expr_id, env),
+            (None, _) => {
+                let decl = match node {
+                    AccessedNode::Regular(id) | AccessedNode::Intf(_, id) => id,
+                    AccessedNode::Hier(id) => self.hier_name(id).target,
+                    AccessedNode::GenLoop(..) => unreachable!(),
+                };
+                self.emit(
+                    DiagBuilder2::bug(format!(
+                        "{} used as input/output of {}, but no value has been emitted",
+                        self.hir_of(decl).unwrap().desc_full(),
+                        self.hir_of(id).unwrap().desc_full(),
+                    ))
+                    .span(self.span(decl)),
+                );
+                panic!("no value emitted for {:?}", node);
+            }
+        };
+        let shared: Vec<AccessedNode> = prok.shared.iter().map(|&id| id.into()).collect();
+        let mut inputs: Vec<_> = acc
+            .read
+            .iter()
+            .chain(shared.iter())
+            .map(lookup_value)
+            .collect::<Result<_>>()?;
+        let mut outputs: Vec<_> = acc
+            .written
+            .iter()
+            .chain(shared.iter())
+            .map(lookup_value)
+            .collect::<Result<_>>()?;
+        inputs.extend(sync_inputs);
+        outputs.extend(sync_outputs);
+
+        // Create the signals to start the branches, to observe their
+        // completion, and to abort them.
+        let mut spawned = vec![];
+        let mut kill = None;
+        if !prok.branches.is_empty() {
+            let zero = self.builder.ins().const_int(1, 0);
+            for &(branch, repeats) in &prok.branches {
+                let start = self.builder.ins().sig(zero);
+                let done = self.builder.ins().sig(zero);
+                inputs.push(done);
+                outputs.push(start);
+                spawned.push((branch, repeats, start, done));
+            }
+            let value = self.builder.ins().sig(zero);
+            outputs.push(value);
+            kill = Some(value);
+        }
+        let ext_unit = self.builder.add_extern(
+            self.into[prok.unit].name().clone(),
+            self.into[prok.unit].sig().clone(),
+        );
+        self.builder.ins().inst(ext_unit, inputs, outputs);
+
+        // Emit and instantiate the branches.
+        for (branch, repeats, start, done) in spawned {
+            let unit = self.emit_fork_branch(branch, repeats, env, name_prefix)?;
+            self.emit_process_inst(
+                branch,
+                unit,
+                vec![start, kill.unwrap()],
+                vec![done],
+                env,
+                name_prefix,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Emit the code for the contents of a generate block.
+    ///
+    /// Blocks that form a scope extend the hierarchical name of the emitted
+    /// signals and processes with their name, followed by the `index` of the
+    /// iteration for blocks of generate loops.
+    fn emit_gen_block(
+        &mut self,
+        id: NodeId,
+        env: ParamEnv,
+        block_id: NodeId,
+        name_prefix: &str,
+        scope: &str,
+        index: Option<&str>,
+    ) -> Result<()> {
+        let hir = match self.hir_of(block_id)? {
+            HirNode::GenBlock(x) => x,
+            _ => unreachable!(),
+        };
+        let scope = match (hir.name, index) {
+            (Some(name), Some(index)) => scoped_name(scope, format!("{}[{}]", name.value, index)),
+            (Some(name), None) => scoped_name(scope, name.value),
+            (None, _) => scope.to_string(),
+        };
+        self.emit_module_block(id, env, &hir.block, name_prefix, &scope)?;
+        if index.is_some() {
+            for &decl_id in &hir.block.decls {
+                let value = self.emitted_value(decl_id);
+                self.gen_loop_values.insert((decl_id, env), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the initial value of a net.
+    ///
+    /// Pull and supply nets start out at the value they are pulled to, and
+    /// all other nets start out at the default value of their type.
+    fn emit_net_init(
+        &mut self,
+        ty: Type<'gcx>,
+        net_ty: ast::NetType,
+        env: ParamEnv,
+    ) -> Result<llhd::ir::Value> {
+        let zero = self.type_default_value(ty);
+        let zero = self.emit_const(zero, env)?;
+        match net_ty {
+            ast::NetType::Tri1 | ast::NetType::Supply1
+                if self.builder.dfg().value_type(zero).is_int() =>
+            {
+                Ok(self.builder.ins().not(zero))
+            }
+            _ => Ok(zero),
+        }
+    }
+
+    /// Determine how an expression continuously assigned to a net drives it.
+    ///
+    /// Conditional expressions with a `z` branch, such as `oe ? d : 'z`, are
+    /// tri-state drivers.
+    fn net_driver_kind(&mut self, rhs: NodeId) -> Result<NetDriverKind> {
+        let is_z = |cx: &Self, id| match cx.hir_of(id) {
+            Ok(HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::UnsizedConst('z'),
+                ..
+            })) => true,
+            Ok(HirNode::Expr(hir::Expr {
+                kind:
+                    hir::ExprKind::IntConst {
+                        ref special_bits,
+                        ref x_bits,
+                        ..
+                    },
+                ..
+            })) => special_bits.all() && x_bits.none(),
+            _ => false,
+        };
+        Ok(match self.hir_of(rhs)? {
+            HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::Ternary(cond, true_value, false_value),
+                ..
+            }) if is_z(self, false_value) => NetDriverKind::Tristate(cond, false, true_value),
+            HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::Ternary(cond, true_value, false_value),
+                ..
+            }) if is_z(self, true_value) => NetDriverKind::Tristate(cond, true, false_value),
+            _ => NetDriverKind::Value(rhs),
+        })
+    }
+
+    /// Emit the continuous drivers of a net.
+    fn emit_net_drivers(
+        &mut self,
+        decl: &hir::VarDecl,
+        drivers: &[NetDriver],
+        env: ParamEnv,
+    ) -> Result<()> {
+        let net_ty = match decl.kind {
+            hir::VarKind::Net { ty, .. } => ty,
+            _ => unreachable!(),
+        };
+        if let Some((value, _)) = self.resolve_net_drivers(decl.id, net_ty, drivers, false, env)? {
+            let sig = self.emitted_value(decl.id);
+            let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+            let delay = self.builder.ins().const_time(one_epsilon);
+            self.builder.ins().drv(sig, value, delay);
+        }
+        Ok(())
+    }
+
+    /// Resolve the continuous drivers of a net as per §6.6.
+    ///
+    /// Returns the resolved value of the net, or `None` if the net is not
+    /// driven at all. If `with_enable` is set or any of the drivers is a
+    /// tri-state driver, the net is resolved bitwise and the mask of bits
+    /// that are driven is returned as well. Since values are currently
+    /// two-valued, undriven bits resolve to the initial value of the net
+    /// instead of `z`, and conflicting drivers of equal strength cannot be
+    /// resolved to `x` and are rejected instead.
+    fn resolve_net_drivers(
+        &mut self,
+        net: NodeId,
+        net_ty: ast::NetType,
+        drivers: &[NetDriver],
+        with_enable: bool,
+        env: ParamEnv,
+    ) -> Result<Option<(llhd::ir::Value, Option<llhd::ir::Value>)>> {
+        let hir = self.hir_of(net)?;
+        let ty = self.type_of(net, env)?;
+
+        // Unresolved wires must not have more than one driver.
+        if net_ty == ast::NetType::Uwire && drivers.len() > 1 {
+            let mut d =
+                DiagBuilder2::error(format!("{} has more than one driver", hir.desc_full()))
+                    .span(hir.human_span());
+            for driver in drivers {
+                d = d.add_note("Driven here:").span(driver.span);
+            }
+            self.emit(d);
+            return Err(());
+        }
+
+        // Wired-AND and wired-OR nets combine all of their drivers.
+        let wired_op = match net_ty {
+            ast::NetType::WireAnd | ast::NetType::TriAnd => Some(mir::BinaryBitwiseOp::And),
+            ast::NetType::WireOr | ast::NetType::TriOr => Some(mir::BinaryBitwiseOp::Or),
+            _ => None,
+        };
+
+        // Pull and supply nets have an implicit driver of the value they are
+        // pulled to, which is already the initial value of the signal.
+        let implicit = match net_ty {
+            ast::NetType::Tri0 | ast::NetType::Tri1 => Some(("pull", (5, 5))),
+            ast::NetType::Supply0 | ast::NetType::Supply1 => Some(("supply", (7, 7))),
+            _ => None,
+        };
+
+        // Without tri-state drivers, the net is resolved as a whole.
+        let (always, tristate): (Vec<_>, Vec<_>) =
+            drivers.iter().partition(|driver| driver.is_always());
+        if tristate.is_empty() && !with_enable {
+            if let (Some(op), true) = (wired_op, always.len() > 1) {
+                let mut resolved = None;
+                for driver in always {
+                    let value = self.emit_net_driver(driver, ty, None, env)?.0;
+                    resolved = Some(match (resolved, op) {
+                        (None, _) => value,
+                        (Some(acc), mir::BinaryBitwiseOp::And) => {
+                            self.builder.ins().and(acc, value)
                         }
-                    } else {
-                        self.emit_module_block(id, env, main_body, name_prefix)?;
-                    }
+                        (Some(acc), _) => self.builder.ins().or(acc, value),
+                    });
                 }
-                hir::GenKind::For {
-                    ref init,
-                    cond,
-                    step,
-                    ref body,
-                } => {
-                    let mut local_env = env;
-                    for &i in init {
-                        local_env = self.execute_genvar_init(i, local_env)?;
+                return Ok(resolved.map(|value| (value, None)));
+            }
+            return match self.resolve_driver_strengths(hir, &always, implicit)? {
+                Some(driver) => Ok(Some((self.emit_net_driver(driver, ty, None, env)?.0, None))),
+                None => Ok(None),
+            };
+        }
+
+        // Otherwise resolve the net bitwise, keeping track of the bits which
+        // are driven.
+        let width = match *self.emit_type(ty, env)? {
+            llhd::IntType(w) => w,
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "tri-state drivers of {} are not supported",
+                        hir.desc_full()
+                    ))
+                    .span(hir.human_span())
+                    .add_note(format!(
+                        "Only nets of integral type can be resolved bitwise, but `{}` is not.",
+                        ty
+                    )),
+                );
+                return Err(());
+            }
+        };
+        let zeros = self.builder.ins().const_int(width, 0);
+        let ones = self.builder.ins().not(zeros);
+        if let Some(op) = wired_op {
+            let mut value = match op {
+                mir::BinaryBitwiseOp::And => ones,
+                _ => zeros,
+            };
+            let mut enable = zeros;
+            for driver in drivers {
+                let (v, en) = self.emit_net_driver(driver, ty, Some(width), env)?;
+                let en = en.unwrap();
+                value = match op {
+                    mir::BinaryBitwiseOp::And => {
+                        let disabled = self.builder.ins().not(en);
+                        let v = self.builder.ins().or(v, disabled);
+                        self.builder.ins().and(value, v)
                     }
-                    while self.constant_value_of(cond, local_env)?.is_true() {
-                        self.emit_module_block(id, local_env, body, name_prefix)?;
-                        local_env = self.execute_genvar_step(step, local_env)?;
+                    _ => {
+                        let v = self.builder.ins().and(v, en);
+                        self.builder.ins().or(value, v)
                     }
+                };
+                enable = self.builder.ins().or(enable, en);
+            }
+            let value = self.builder.ins().and(value, enable);
+            return Ok(Some((value, Some(enable))));
+        }
+
+        // The drivers which are always active form the base value of the net,
+        // on top of which the tri-state drivers are layered.
+        let base = self.resolve_driver_strengths(hir, &always, implicit)?;
+        let (mut value, mut enable) = match base {
+            Some(driver) => (self.emit_net_driver(driver, ty, None, env)?.0, ones),
+            None => (self.emit_net_init(ty, net_ty, env)?, zeros),
+        };
+        let base_strength = base
+            .map(|driver| driver.strength)
+            .or(implicit.map(|(_, s)| s));
+        let mut layered = vec![];
+        let mut failed = false;
+        for driver in tristate {
+            match base_strength {
+                Some(s) if dominates(s, driver.strength) => {
+                    let d = DiagBuilder2::warning(format!(
+                        "assignment to {} is overridden by a stronger driver",
+                        hir.desc_full()
+                    ))
+                    .span(driver.span);
+                    let d = match base {
+                        Some(w) => d.add_note("Overridden by this driver:").span(w.span),
+                        None => d
+                            .add_note(format!(
+                                "The net is driven with {} strength by its type:",
+                                implicit.unwrap().0
+                            ))
+                            .span(hir.human_span()),
+                    };
+                    self.emit(d);
+                }
+                Some(s) if !dominates(driver.strength, s) => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "tri-state driver of {} conflicts with a driver of equal strength",
+                            hir.desc_full()
+                        ))
+                        .span(driver.span)
+                        .add_note(
+                            "Resolving conflicting drivers of equal strength to `x` is not \
+                             supported.",
+                        ),
+                    );
+                    failed = true;
                 }
-                _ => return self.unimp_msg("code generation for", hir),
+                _ => layered.push(driver),
             }
         }
+        if failed {
+            return Err(());
+        }
 
-        // Emit and instantiate procedures.
-        for &proc_id in &hir.procs {
-            let prok = self.emit_procedure(proc_id, env, name_prefix)?;
-            let acc = self.accessed_nodes(proc_id)?;
-            let lookup_value = |&id| match self.values.get(&id) {
-                Some(v) => v.clone(),
-                None => {
+        // Stronger tri-state drivers take precedence over weaker ones. Drivers
+        // of equal strength are assumed to never be enabled at the same time,
+        // since the resulting `x` cannot be represented.
+        layered.sort_by_key(|driver| std::cmp::min(driver.strength.0, driver.strength.1));
+        for driver in layered {
+            let (v, en) = self.emit_net_driver(driver, ty, Some(width), env)?;
+            let en = en.unwrap();
+            let v = self.builder.ins().and(v, en);
+            let disabled = self.builder.ins().not(en);
+            let prev = self.builder.ins().and(value, disabled);
+            value = self.builder.ins().or(v, prev);
+            enable = self.builder.ins().or(enable, en);
+        }
+        Ok(Some((value, Some(enable))))
+    }
+
+    /// Find the driver of a net that wins over all other drivers by strength.
+    ///
+    /// Returns `None` if the net is not driven, or if the implicit driver of
+    /// a pull or supply net wins.
+    fn resolve_driver_strengths<'d>(
+        &mut self,
+        net: HirNode,
+        drivers: &[&'d NetDriver],
+        implicit: Option<(&str, (usize, usize))>,
+    ) -> Result<Option<&'d NetDriver>> {
+        let candidates: Vec<_> = drivers
+            .iter()
+            .map(|d| d.strength)
+            .chain(implicit.map(|(_, s)| s))
+            .collect();
+
+        // Find the driver whose value always wins over all other drivers,
+        // i.e. which drives either value stronger than the others drive the
+        // opposite value.
+        let winner = (0..candidates.len()).find(|&i| {
+            candidates
+                .iter()
+                .enumerate()
+                .all(|(j, &other)| i == j || dominates(candidates[i], other))
+        });
+        let winner = match winner {
+            Some(i) => i,
+            None if candidates.len() <= 1 => 0,
+            None => {
+                let mut d = DiagBuilder2::error(format!(
+                    "{} has multiple drivers that cannot be resolved",
+                    net.desc_full()
+                ))
+                .span(net.human_span());
+                for driver in drivers {
+                    d = d.add_note("Driven here:").span(driver.span);
+                }
+                d = d.add_note(
+                    "Resolving conflicting drivers of equal strength to `x` is not supported.",
+                );
+                self.emit(d);
+                return Err(());
+            }
+        };
+
+        // Warn about the drivers that never have an effect.
+        for (i, driver) in drivers.iter().enumerate() {
+            if i == winner {
+                continue;
+            }
+            let d = DiagBuilder2::warning(format!(
+                "assignment to {} is overridden by a stronger driver",
+                net.desc_full()
+            ))
+            .span(driver.span);
+            let d = match drivers.get(winner) {
+                Some(w) => d.add_note("Overridden by this driver:").span(w.span),
+                None => d
+                    .add_note(format!(
+                        "The net is driven with {} strength by its type:",
+                        implicit.unwrap().0
+                    ))
+                    .span(net.human_span()),
+            };
+            self.emit(d);
+        }
+        Ok(drivers.get(winner).cloned())
+    }
+
+    /// Emit the value of a net driver, cast to the type of the net.
+    ///
+    /// If the width of the net is given, also emits the mask of bits which
+    /// the driver drives.
+    fn emit_net_driver(
+        &mut self,
+        driver: &NetDriver,
+        ty: Type<'gcx>,
+        width: Option<usize>,
+        env: ParamEnv,
+    ) -> Result<(llhd::ir::Value, Option<llhd::ir::Value>)> {
+        let emit_value = |gen: &mut Self, rhs| -> Result<llhd::ir::Value> {
+            let rhs = gen.mir_rvalue(rhs, env);
+            let rhs = mir::lower::rvalue::cast_to_type(gen.cx, rhs, env, ty);
+            gen.emit_mir_rvalue(rhs)
+        };
+        let (value, enable) = match driver.kind {
+            NetDriverKind::Value(rhs) => (emit_value(self, rhs)?, None),
+            NetDriverKind::Tristate(cond, active_low, rhs) => {
+                let value = emit_value(self, rhs)?;
+                let cond = self.mir_rvalue(cond, env);
+                let cond = mir::lower::rvalue::cast_to_bool(self.cx, cond, env);
+                let cond = self.emit_mir_rvalue(cond)?;
+                (value, Some((cond, active_low)))
+            }
+            NetDriverKind::Port(value, enable) => {
+                let value = self.builder.ins().prb(value);
+                let enable = self.builder.ins().prb(enable);
+                return Ok((value, width.map(|_| enable)));
+            }
+        };
+        let width = match width {
+            Some(w) => w,
+            None => return Ok((value, None)),
+        };
+        let zeros = self.builder.ins().const_int(width, 0);
+        let ones = self.builder.ins().not(zeros);
+        let enable = match enable {
+            Some((cond, active_low)) => {
+                let choices = if active_low {
+                    vec![ones, zeros]
+                } else {
+                    vec![zeros, ones]
+                };
+                let choices = self.builder.ins().array(choices);
+                self.builder.ins().mux(choices, cond)
+            }
+            None => ones,
+        };
+        Ok((value, Some(enable)))
+    }
+
+    /// Resolve the drivers of an `inout` port within the module, and connect
+    /// them to the entity.
+    ///
+    /// The module drives the port through a value and a mask of the driven
+    /// bits. Wherever it does not drive the port itself, it sees the value
+    /// with which the port is driven from outside.
+    fn emit_inout_port(
+        &mut self,
+        target: NodeId,
+        drivers: &[NetDriver],
+        (outside, value_out, enable_out): (llhd::ir::Value, llhd::ir::Value, llhd::ir::Value),
+        env: ParamEnv,
+    ) -> Result<()> {
+        let net_ty = match self.hir_of(target)? {
+            HirNode::VarDecl(&hir::VarDecl {
+                kind: hir::VarKind::Net { ty, .. },
+                ..
+            }) => ty,
+            _ => ast::NetType::Wire,
+        };
+        let (value, enable) = match self.resolve_net_drivers(target, net_ty, drivers, true, env)? {
+            Some((value, Some(enable))) => (value, enable),
+            _ => unreachable!(),
+        };
+        let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+        let delay = self.builder.ins().const_time(one_epsilon);
+        self.builder.ins().drv(value_out, value, delay);
+        self.builder.ins().drv(enable_out, enable, delay);
+        let outside = self.builder.ins().prb(outside);
+        let driven = self.builder.ins().and(value, enable);
+        let disabled = self.builder.ins().not(enable);
+        let outside = self.builder.ins().and(outside, disabled);
+        let local = self.builder.ins().or(driven, outside);
+        let sig = self.emitted_value(target);
+        self.builder.ins().drv(sig, local, delay);
+        Ok(())
+    }
+
+    /// Connect a port given as an expression to the declarations in the
+    /// module body.
+    ///
+    /// Input ports drive the expression, and output ports are driven by it.
+    fn emit_port_connection(&mut self, port_id: NodeId, env: ParamEnv) -> Result<()> {
+        let port = match self.hir_of(port_id)? {
+            HirNode::Port(x) => x,
+            _ => unreachable!(),
+        };
+        let expr = match port.expr {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        match port.dir {
+            ast::PortDir::Input => {
+                let value = self.mir_rvalue(port_id, env);
+                let value = self.emit_mir_rvalue(value)?;
+                self.emit_port_expr_drive(expr, value, env)
+            }
+            ast::PortDir::Output => {
+                let lhs = self.mir_lvalue(port_id, env);
+                let rhs = self.mir_rvalue(expr, env);
+                let rhs = mir::lower::rvalue::cast_to_type(self.cx, rhs, env, lhs.ty);
+                let lhs = self.emit_mir_lvalue(lhs)?.0;
+                let rhs = self.emit_mir_rvalue(rhs)?;
+                let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+                let one_epsilon = self.builder.ins().const_time(one_epsilon);
+                self.builder.ins().drv(lhs, rhs, one_epsilon);
+                Ok(())
+            }
+            // Inout ports drive the net they connect to directly, see
+            // `emit_inout_port`.
+            ast::PortDir::Inout => Ok(()),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "{} cannot be connected to an expression",
+                        port.desc_full()
+                    ))
+                    .span(port.span)
+                    .add_note("only input and output ports support port expressions"),
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Drive the expression of an input port with a value.
+    ///
+    /// Concatenations are split up, with each of their elements driven by the
+    /// corresponding slice of the value.
+    fn emit_port_expr_drive(
+        &mut self,
+        expr: NodeId,
+        value: llhd::ir::Value,
+        env: ParamEnv,
+    ) -> Result<()> {
+        if let HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Concat(None, ref elems),
+            ..
+        }) = self.hir_of(expr)?
+        {
+            let mut offset = ty::bit_size_of_type(self.cx, self.type_of(expr, env)?, env)?;
+            for &elem in elems {
+                let elem_ty = self.type_of(elem, env)?;
+                let width = ty::bit_size_of_type(self.cx, elem_ty, env)?;
+                if self.emit_type(elem_ty, env)? != llhd::int_ty(width) {
                     self.emit(
-                        DiagBuilder2::bug(format!(
-                            "{} used as input/output of {}, but no value has been emitted",
-                            self.hir_of(id).unwrap().desc_full(),
-                            self.hir_of(proc_id).unwrap().desc_full(),
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be connected to a slice of an input port",
+                            elem_ty
                         ))
-                        .span(self.span(id)),
+                        .span(self.span(elem)),
                     );
-                    panic!("no value emitted for {:?}", id);
+                    return Err(());
                 }
+                offset -= width;
+                let slice = self.builder.ins().ext_slice(value, offset, width);
+                self.emit_port_expr_drive(elem, slice, env)?;
+            }
+            return Ok(());
+        }
+        let lhs = self.mir_lvalue(expr, env);
+        let lhs = self.emit_mir_lvalue(lhs)?.0;
+        let one_epsilon = llhd::ConstTime::new(num::zero(), 0, 1);
+        let one_epsilon = self.builder.ins().const_time(one_epsilon);
+        self.builder.ins().drv(lhs, value, one_epsilon);
+        Ok(())
+    }
+
+    /// Emit the signals of an interface instance.
+    ///
+    /// Each signal declared in the interface becomes a separate LLHD signal.
+    /// The ports of the interface refer to whatever is connected to them.
+    fn emit_interface_inst(
+        &mut self,
+        inst_id: NodeId,
+        env: ParamEnv,
+        name_prefix: &str,
+        scope: &str,
+    ) -> Result<()> {
+        let hir = match self.hir_of(inst_id)? {
+            HirNode::Inst(x) => x,
+            _ => unreachable!(),
+        };
+        let (decl, intf_env, _) = self.type_of(inst_id, env)?.get_interface().unwrap();
+        let intf = match self.hir_of(decl)? {
+            HirNode::Interface(x) => x,
+            _ => unreachable!(),
+        };
+        for &id in intf.block.insts.iter().chain(intf.block.gens.iter()) {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "{} in {} not supported",
+                    self.hir_of(id)?.desc_full(),
+                    intf.desc_full()
+                ))
+                .span(self.span(id))
+                .add_note(
+                    "Interfaces may only contain declarations, procedures, and continuous \
+                     assignments.",
+                ),
+            );
+            return Err(());
+        }
+
+        // Connect the interface ports.
+        let port_mapping = self.port_mapping(PortMappingSource::ModuleInst {
+            module: decl,
+            inst: inst_id,
+            env: intf_env,
+            pos: &hir.pos_ports,
+            named: &hir.named_ports,
+        })?;
+        for &port_id in intf.ports {
+            let port = match self.hir_of(port_id)? {
+                HirNode::Port(x) => x,
+                _ => unreachable!(),
             };
-            let inputs = acc.read.iter().map(lookup_value).collect();
-            let outputs = acc.written.iter().map(lookup_value).collect();
-            let ext_unit = self.builder.add_extern(
-                self.into[prok].name().clone(),
-                self.into[prok].sig().clone(),
+            let value = match port_mapping.find(port_id) {
+                Some(mapping) => self.emit_rvalue_mode(mapping.0, mapping.1, Mode::Signal)?,
+                None => {
+                    let ty = self.type_of(port_id, intf_env)?;
+                    let value = match port.default {
+                        Some(default) => self.constant_value_of(default, intf_env)?,
+                        None => self.type_default_value(ty),
+                    };
+                    let value = self.emit_const(value, intf_env)?;
+                    let value = self.builder.ins().sig(value);
+                    self.builder.dfg_mut().set_name(
+                        value,
+                        scoped_name(scope, format!("{}.{}", hir.name.value, port.name.value)),
+                    );
+                    value
+                }
+            };
+            self.values
+                .insert(AccessedNode::Intf(inst_id, port_id), value);
+        }
+
+        // Emit the signals declared in the interface.
+        for &decl_id in &intf.block.decls {
+            let decl_hir = match self.hir_of(decl_id)? {
+                HirNode::VarDecl(x) => x,
+                _ => unreachable!(),
+            };
+            let ty = self.type_of(decl_id, intf_env)?;
+            let init = self.emit_const(
+                match decl_hir.init {
+                    Some(expr) => {
+                        value::cast_value(self.cx, self.constant_value_of(expr, intf_env)?, ty)
+                    }
+                    None => self.type_default_value(ty),
+                },
+                intf_env,
+            )?;
+            let value = self.builder.ins().sig(init);
+            self.builder.dfg_mut().set_name(
+                value,
+                scoped_name(scope, format!("{}.{}", hir.name.value, decl_hir.name.value)),
             );
-            self.builder.ins().inst(ext_unit, inputs, outputs);
+            self.values
+                .insert(AccessedNode::Intf(inst_id, decl_id), value);
         }
 
+        // Emit the procedures and continuous assignments of the interface.
+        // These access the ports and signals directly rather than through the
+        // instance, so map them to the signals of this instance meanwhile.
+        let signals: Vec<NodeId> = intf
+            .ports
+            .iter()
+            .chain(intf.block.decls.iter())
+            .cloned()
+            .collect();
+        for &id in &signals {
+            let value = self.emitted_value(AccessedNode::Intf(inst_id, id));
+            self.set_emitted_value(id, value);
+        }
+        for &assign_id in &intf.block.assigns {
+            let hir = match self.hir_of(assign_id)? {
+                HirNode::Assign(x) => x,
+                _ => unreachable!(),
+            };
+            self.emit_cont_assign(hir, intf_env)?;
+        }
+        let name_prefix = scoped_name(name_prefix, scoped_name(scope, hir.name.value));
+        for &proc_id in &intf.block.procs {
+            let prok = self.emit_procedure(proc_id, intf_env, &name_prefix)?;
+            self.emit_process_inst(proc_id, prok, vec![], vec![], intf_env, &name_prefix)?;
+        }
+        for id in signals {
+            self.values.remove(&id.into());
+        }
         Ok(())
     }
 
+    /// Determine the interface instance or port an expression refers to.
+    ///
+    /// Modports of the instance, such as `bus.mst`, refer to the instance
+    /// itself. Their compatibility with the port is checked by the port's
+    /// type.
+    fn interface_binding(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<NodeId> {
+        let binding = match self.hir_of(expr_id)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Ident(..),
+                ..
+            }) => self.resolve_node(expr_id, env)?,
+            _ => match resolver::resolve_intf_modport(self.cx, expr_id)? {
+                Some((binding, _)) => binding,
+                None => expr_id,
+            },
+        };
+        if interface_of(self.cx, binding)?.is_none() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is not an interface",
+                    self.span(expr_id).extract()
+                ))
+                .span(self.span(expr_id)),
+            );
+            return Err(());
+        }
+        Ok(binding)
+    }
+
     /// Map a value to an LLHD constant (interned).
     fn emit_const(&mut self, value: Value<'gcx>, env: ParamEnv) -> Result<llhd::ir::Value> {
         if let Some(x) = self.interned_consts.get(value) {
@@ -751,17 +2899,39 @@ where
                 .builder
                 .ins()
                 .const_time(llhd::ConstTime::new(k.clone(), 0, 0))),
+            (&TypeKind::Real, &ValueKind::Real(k)) => Ok(self
+                .builder
+                .ins()
+                .const_int(64, num::BigInt::from(k.0.to_bits()))),
+            (&TypeKind::ShortReal, &ValueKind::Real(k)) => Ok(self
+                .builder
+                .ins()
+                .const_int(32, num::BigInt::from((k.0 as f32).to_bits()))),
+            (&TypeKind::String, &ValueKind::String(ref v)) => Ok(self.emit_const_string(v)),
             (&TypeKind::Bit(_), &ValueKind::Int(ref k, ..))
             | (&TypeKind::BitScalar { .. }, &ValueKind::Int(ref k, ..)) => {
                 Ok(self.builder.ins().const_int(1, k.clone()))
             }
-            (&TypeKind::PackedArray(..), &ValueKind::StructOrArray(ref v)) => {
+            (&TypeKind::PackedArray(..), &ValueKind::StructOrArray(ref v))
+            | (&TypeKind::UnpackedArray(..), &ValueKind::StructOrArray(ref v)) => {
                 let fields: Result<Vec<_>> = v
                     .iter()
                     .map(|v| self.emit_const(v, env).map(Into::into))
                     .collect();
                 Ok(self.builder.ins().array(fields?))
             }
+            (&TypeKind::DynamicArray(..), &ValueKind::StructOrArray(ref v))
+            | (&TypeKind::Queue(..), &ValueKind::StructOrArray(ref v))
+            | (&TypeKind::AssocArray(..), &ValueKind::StructOrArray(ref v))
+                if v.is_empty() =>
+            {
+                Ok(self.builder.ins().const_int(64, 0))
+            }
+            (_, &ValueKind::Null) => Ok(self.builder.ins().const_int(64, 0)),
+            (&TypeKind::Struct(..), &ValueKind::Int(ref k, ..)) => {
+                let width = ty::bit_size_of_type(self.cx, value.ty, env)?;
+                Ok(self.builder.ins().const_int(width, k.clone()))
+            }
             (&TypeKind::Struct(..), &ValueKind::StructOrArray(ref v)) => {
                 let fields: Result<Vec<_>> = v
                     .iter()
@@ -803,6 +2973,10 @@ where
                 let inner = self.emit_zero_for_type(ty);
                 self.builder.ins().sig(inner)
             }
+            llhd::PointerType(ref ty) => {
+                let inner = self.emit_zero_for_type(ty);
+                self.builder.ins().var(inner)
+            }
             llhd::ArrayType(l, ref ty) => {
                 let inner = self.emit_zero_for_type(ty);
                 self.builder.ins().array_uniform(l, inner)
@@ -865,12 +3039,26 @@ where
     /// Emit the code for an MIR rvalue.
     fn emit_mir_rvalue_uninterned(&mut self, mir: &mir::Rvalue<'gcx>) -> Result<llhd::ir::Value> {
         match mir.kind {
-            mir::RvalueKind::Var(id) => {
-                let value = self
-                    .shadows
-                    .get(&id)
-                    .cloned()
-                    .unwrap_or_else(|| self.emitted_value(id));
+            mir::RvalueKind::Var(_)
+            | mir::RvalueKind::IntfSignal(..)
+            | mir::RvalueKind::Hier(..)
+            | mir::RvalueKind::GenLoop(..) => {
+                let node = match mir.kind {
+                    mir::RvalueKind::Var(id) => AccessedNode::Regular(id),
+                    mir::RvalueKind::IntfSignal(binding, signal) => {
+                        AccessedNode::Intf(binding, signal)
+                    }
+                    mir::RvalueKind::Hier(id) => AccessedNode::Hier(id),
+                    mir::RvalueKind::GenLoop(id) => AccessedNode::GenLoop(id),
+                    _ => unreachable!(),
+                };
+                // Accesses into generate loops from the entity itself refer
+                // to the signals of the loop iteration directly.
+                let value = match (self.shadows.get(&node), self.values.get(&node), node) {
+                    (Some(&value), _, _) | (None, Some(&value), _) => value,
+                    (None, None, AccessedNode::GenLoop(id)) => self.gen_loop_value(id, mir.env)?,
+                    (None, None, _) => self.emitted_value(node),
+                };
                 Ok(match *self.llhd_type(value) {
                     llhd::SignalType(_) => {
                         let value = self.builder.ins().prb(value);
@@ -893,7 +3081,7 @@ where
             mir::RvalueKind::Port(id) => {
                 let value = self
                     .shadows
-                    .get(&id)
+                    .get(&id.into())
                     .cloned()
                     .unwrap_or_else(|| self.emitted_value(id));
                 let value = self.builder.ins().prb(value);
@@ -904,7 +3092,9 @@ where
             }
 
             mir::RvalueKind::CastValueDomain { value, .. } => {
-                // TODO(fschuiki): Turn this into an actual `iN` to `lN` cast.
+                // Both value domains map to `iN`, see `emit_type_uninterned`.
+                // Since values never carry `x` or `z` bits at runtime, the
+                // conversion is the identity in either direction.
                 self.emit_mir_rvalue(value)
             }
 
@@ -928,6 +3118,53 @@ where
                 Ok(self.builder.ins().neq(value, zero))
             }
 
+            mir::RvalueKind::CastIntToReal { sign, value } => {
+                let name = format!(
+                    "moore.{}.from_{}.i{}",
+                    mir.ty.resolve_name(),
+                    sign,
+                    value.ty.width()
+                );
+                let llty = self.emit_type(mir.ty, mir.env)?;
+                let value = self.emit_mir_rvalue(value)?;
+                Ok(self.emit_runtime_call(name, vec![value], llty))
+            }
+
+            mir::RvalueKind::CastRealToInt { truncate, value } => {
+                let name = format!(
+                    "moore.{}.{}.i{}",
+                    value.ty.resolve_name(),
+                    if truncate { "trunc" } else { "round" },
+                    mir.ty.width()
+                );
+                let llty = self.emit_type(mir.ty, mir.env)?;
+                let value = self.emit_mir_rvalue(value)?;
+                Ok(self.emit_runtime_call(name, vec![value], llty))
+            }
+
+            mir::RvalueKind::CastRealPrecision(value) => {
+                let name = format!(
+                    "moore.{}.to_{}",
+                    value.ty.resolve_name(),
+                    mir.ty.resolve_name()
+                );
+                let llty = self.emit_type(mir.ty, mir.env)?;
+                let value = self.emit_mir_rvalue(value)?;
+                Ok(self.emit_runtime_call(name, vec![value], llty))
+            }
+
+            mir::RvalueKind::CastIntToString(value) => {
+                let name = format!("moore.string.from_i{}", value.ty.width());
+                let value = self.emit_mir_rvalue(value)?;
+                Ok(self.emit_runtime_call(name, vec![value], llhd::int_ty(64)))
+            }
+
+            mir::RvalueKind::Reinterpret(value) => {
+                // Reals and unions are already represented by their bit
+                // pattern, and class objects by their handle.
+                self.emit_mir_rvalue(value)
+            }
+
             mir::RvalueKind::Truncate(target_width, value) => {
                 let llvalue = self.emit_mir_rvalue(value)?;
                 Ok(self.builder.ins().ext_slice(llvalue, 0, target_width))
@@ -968,7 +3205,27 @@ where
                 Ok(self.builder.ins().strukt(members))
             }
 
-            mir::RvalueKind::Const(k) => self.emit_const(k, mir.env),
+            mir::RvalueKind::Const(k) => {
+                // LLHD does not provide a nine-valued logic type, so the `x`
+                // and `z` bits of four-valued constants cannot be represented.
+                // They take on the value `0`, as in a two-valued conversion.
+                if let ValueKind::Int(_, ref special_bits, _) = k.kind {
+                    if special_bits.any() {
+                        self.emit(
+                            DiagBuilder2::warning(format!(
+                                "`x` and `z` bits in `{}` are lowered as `0`",
+                                mir.span.extract()
+                            ))
+                            .span(mir.span)
+                            .add_note(
+                                "Four-valued types are currently represented as two-valued \
+                                 integers.",
+                            ),
+                        );
+                    }
+                }
+                self.emit_const(k, mir.env)
+            }
 
             mir::RvalueKind::Index {
                 value,
@@ -1031,9 +3288,17 @@ where
                 let lhs = self.emit_mir_rvalue(lhs)?;
                 let rhs = self.emit_mir_rvalue(rhs)?;
                 let signed = sign.is_signed();
+                // Four-valued operands are represented as two-valued integers,
+                // such that case equality is the same as regular equality. The
+                // wildcard bits of a constant pattern are masked out by the
+                // MIR, and other operands have no wildcard bits at runtime.
                 Ok(match op {
-                    mir::IntCompOp::Eq => self.builder.ins().eq(lhs, rhs),
-                    mir::IntCompOp::Neq => self.builder.ins().neq(lhs, rhs),
+                    mir::IntCompOp::Eq | mir::IntCompOp::CaseEq | mir::IntCompOp::WildcardEq => {
+                        self.builder.ins().eq(lhs, rhs)
+                    }
+                    mir::IntCompOp::Neq | mir::IntCompOp::CaseNeq | mir::IntCompOp::WildcardNeq => {
+                        self.builder.ins().neq(lhs, rhs)
+                    }
                     mir::IntCompOp::Lt if signed => self.builder.ins().slt(lhs, rhs),
                     mir::IntCompOp::Leq if signed => self.builder.ins().sle(lhs, rhs),
                     mir::IntCompOp::Gt if signed => self.builder.ins().sgt(lhs, rhs),
@@ -1077,6 +3342,154 @@ where
                 })
             }
 
+            mir::RvalueKind::RealUnaryArith { op, arg } => {
+                // Negation only flips the sign bit.
+                let arg = self.emit_mir_rvalue(arg)?;
+                let width = self.llhd_type(arg).unwrap_int();
+                let sign_bit = num::BigInt::from(1) << (width - 1);
+                let sign_bit = self.builder.ins().const_int(width, sign_bit);
+                Ok(match op {
+                    mir::IntUnaryArithOp::Neg => self.builder.ins().xor(arg, sign_bit),
+                })
+            }
+
+            mir::RvalueKind::RealBinaryArith { op, lhs, rhs } => {
+                let name = format!(
+                    "moore.{}.{}",
+                    mir.ty.resolve_name(),
+                    match op {
+                        mir::RealBinaryArithOp::Add => "add",
+                        mir::RealBinaryArithOp::Sub => "sub",
+                        mir::RealBinaryArithOp::Mul => "mul",
+                        mir::RealBinaryArithOp::Div => "div",
+                        mir::RealBinaryArithOp::Pow => "pow",
+                    }
+                );
+                let llty = self.emit_type(mir.ty, mir.env)?;
+                let lhs = self.emit_mir_rvalue(lhs)?;
+                let rhs = self.emit_mir_rvalue(rhs)?;
+                Ok(self.emit_runtime_call(name, vec![lhs, rhs], llty))
+            }
+
+            mir::RvalueKind::RealComp { op, lhs, rhs } => {
+                let name = format!(
+                    "moore.{}.{}",
+                    lhs.ty.resolve_name(),
+                    match op {
+                        mir::IntCompOp::Eq
+                        | mir::IntCompOp::CaseEq
+                        | mir::IntCompOp::WildcardEq => "eq",
+                        mir::IntCompOp::Neq
+                        | mir::IntCompOp::CaseNeq
+                        | mir::IntCompOp::WildcardNeq => "neq",
+                        mir::IntCompOp::Lt => "lt",
+                        mir::IntCompOp::Leq => "leq",
+                        mir::IntCompOp::Gt => "gt",
+                        mir::IntCompOp::Geq => "geq",
+                    }
+                );
+                let lhs = self.emit_mir_rvalue(lhs)?;
+                let rhs = self.emit_mir_rvalue(rhs)?;
+                Ok(self.emit_runtime_call(name, vec![lhs, rhs], llhd::int_ty(1)))
+            }
+
+            mir::RvalueKind::StringComp { op, lhs, rhs } => {
+                let name = format!(
+                    "moore.string.{}",
+                    match op {
+                        mir::IntCompOp::Eq
+                        | mir::IntCompOp::CaseEq
+                        | mir::IntCompOp::WildcardEq => "eq",
+                        mir::IntCompOp::Neq
+                        | mir::IntCompOp::CaseNeq
+                        | mir::IntCompOp::WildcardNeq => "neq",
+                        mir::IntCompOp::Lt => "lt",
+                        mir::IntCompOp::Leq => "leq",
+                        mir::IntCompOp::Gt => "gt",
+                        mir::IntCompOp::Geq => "geq",
+                    }
+                );
+                let lhs = self.emit_mir_rvalue(lhs)?;
+                let rhs = self.emit_mir_rvalue(rhs)?;
+                Ok(self.emit_runtime_call(name, vec![lhs, rhs], llhd::int_ty(1)))
+            }
+
+            mir::RvalueKind::StringConcat(ref values) => {
+                let mut result = None;
+                for &value in values {
+                    let value = self.emit_mir_rvalue(value)?;
+                    result = Some(match result {
+                        Some(result) => self.emit_runtime_call(
+                            "moore.string.concat".into(),
+                            vec![result, value],
+                            llhd::int_ty(64),
+                        ),
+                        None => value,
+                    });
+                }
+                match result {
+                    Some(result) => Ok(result),
+                    None => Ok(self.builder.ins().const_int(64, 0)),
+                }
+            }
+
+            mir::RvalueKind::RealMath { func, ref args } => {
+                let name = format!("moore.real.{}", func.name());
+                let args = args
+                    .iter()
+                    .map(|&arg| self.emit_mir_rvalue(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(self.emit_runtime_call(name, args, llhd::int_ty(64)))
+            }
+
+            mir::RvalueKind::RealTime => {
+                Ok(self.emit_runtime_call("moore.realtime".into(), vec![], llhd::int_ty(64)))
+            }
+
+            mir::RvalueKind::Time => {
+                Ok(self.emit_runtime_call("moore.time".into(), vec![], llhd::int_ty(64)))
+            }
+
+            // The tasks produce no value. Return a dummy bit instead, which is
+            // never used.
+            mir::RvalueKind::Display { newline, ref items } => {
+                self.emit_format_items(items)?;
+                let newline = self.builder.ins().const_int(1, newline as usize);
+                self.emit_runtime_task("moore.display".into(), vec![newline]);
+                Ok(self.builder.ins().const_int(1, 0))
+            }
+
+            mir::RvalueKind::Severity {
+                severity,
+                finish,
+                ref items,
+            } => {
+                self.emit_format_items(items)?;
+                let level = match severity {
+                    hir::SeverityTask::Info => 0,
+                    hir::SeverityTask::Warning => 1,
+                    hir::SeverityTask::Error => 2,
+                    hir::SeverityTask::Fatal => 3,
+                };
+                let level = self.builder.ins().const_int(32, level);
+                self.emit_runtime_task("moore.severity".into(), vec![level]);
+                if let Some(finish) = finish {
+                    let finish = self.emit_mir_rvalue(finish)?;
+                    self.emit_runtime_task("moore.finish".into(), vec![finish]);
+                }
+                Ok(self.builder.ins().const_int(1, 0))
+            }
+
+            mir::RvalueKind::SimControl(control, level) => {
+                let name = match control {
+                    hir::SimControl::Finish => "moore.finish",
+                    hir::SimControl::Stop => "moore.stop",
+                };
+                let level = self.emit_mir_rvalue(level)?;
+                self.emit_runtime_task(name.into(), vec![level]);
+                Ok(self.builder.ins().const_int(1, 0))
+            }
+
             mir::RvalueKind::Concat(ref values) => {
                 let mut offset = 0;
                 let llty = self.emit_type(mir.ty, mir.env)?;
@@ -1167,7 +3580,436 @@ where
                 self.emit_mir_rvalue(result)
             }
 
-            mir::RvalueKind::Error => Err(()),
+            mir::RvalueKind::Call { target, ref args } => {
+                // Tasks which suspend execution cannot be emitted as LLHD
+                // functions. Inline them into the calling process instead.
+                if self.builder.unit().is_process() && hir::task_consumes_time(self.cx, target) {
+                    return self.emit_inlined_task(target, args, mir.env);
+                }
+                let (name, sig) = self.gen.emit_subroutine(target, mir.env)?;
+
+                let (mut values, copy_back) = self.emit_call_args(args, mir.env)?;
+
+                // Pass the signals the function or task accesses directly.
+                let acc = self.accessed_nodes(target)?;
+                for node in acc.read.union(&acc.written) {
+                    values.push(self.emitted_value(*node));
+                }
+
+                let ext_unit = self.builder.add_extern(name, sig);
+                let inst = self.builder.ins().call(ext_unit, values);
+                self.emit_copy_back(copy_back)?;
+
+                // Tasks and void functions produce no value. Return a dummy
+                // bit instead, which is never used.
+                if self.builder.dfg().has_result(inst) {
+                    Ok(self.builder.dfg().inst_result(inst))
+                } else {
+                    Ok(self.builder.ins().const_int(1, 0))
+                }
+            }
+
+            mir::RvalueKind::MethodCall {
+                target,
+                env,
+                this,
+                ref args,
+                dispatch,
+            } => {
+                let this = match this {
+                    Some(this) => Some(self.emit_mir_rvalue(this)?),
+                    None => None,
+                };
+                let (values, copy_back) = self.emit_call_args(args, mir.env)?;
+                let inst = self.emit_method_call(target, env, this, values, dispatch)?;
+                self.emit_copy_back(copy_back)?;
+
+                // Tasks and void functions produce no value. Return a dummy
+                // bit instead, which is never used.
+                if self.builder.dfg().has_result(inst) {
+                    Ok(self.builder.dfg().inst_result(inst))
+                } else {
+                    Ok(self.builder.ins().const_int(1, 0))
+                }
+            }
+
+            mir::RvalueKind::ClassNew {
+                class,
+                env,
+                ref args,
+            } => {
+                // Allocate the object and initialize its properties.
+                let name = format!("moore.class.new.{}", self.class_unit_name(class, env)?);
+                let this = self.emit_runtime_call(name, vec![], llhd::int_ty(64));
+                self.gen.emit_virtual_methods(class, env)?;
+                self.emit_class_init(this, class, env)?;
+
+                // Call the constructor, which may be inherited from a base
+                // class.
+                let ctor = match self.hir_of(class)? {
+                    HirNode::Class(x) => x.constructor(),
+                    _ => unreachable!(),
+                };
+                match ctor {
+                    Some(ctor) => {
+                        let (values, copy_back) = self.emit_call_args(args, mir.env)?;
+                        self.emit_method_call(ctor.id, env, Some(this), values, false)?;
+                        self.emit_copy_back(copy_back)?;
+                    }
+                    None => self.emit_implicit_super_new(this, class, env)?,
+                }
+                Ok(this)
+            }
+
+            mir::RvalueKind::This(class_id) => Ok(self.emitted_value(class_id)),
+
+            mir::RvalueKind::ClassProperty { this, prop, env } => {
+                let this = match this {
+                    Some(this) => Some(self.emit_mir_rvalue(this)?),
+                    None => None,
+                };
+                let ptr = self.emit_class_property(this, prop, env)?;
+                Ok(self.builder.ins().ld(ptr))
+            }
+
+            mir::RvalueKind::ContainerOp {
+                op,
+                value,
+                ref args,
+                update,
+            } => {
+                let handle = self.emit_mir_rvalue(value)?;
+                let (values, copy_back) = self.emit_call_args(args, mir.env)?;
+                let (result, updated) =
+                    self.emit_container_op(op, value.ty, handle, values, mir.env)?;
+                self.emit_copy_back(copy_back)?;
+                if let (Some(lvalue), Some(updated)) = (update, updated) {
+                    let (lv, updated) = self.emit_assign_target(lvalue, None, updated)?;
+                    self.emit_blocking_assign_llhd(lv, updated)?;
+                }
+
+                // Operations that only modify the container produce no value.
+                // Return a dummy bit instead, which is never used.
+                match result {
+                    Some(result) => Ok(result),
+                    None => Ok(self.builder.ins().const_int(1, 0)),
+                }
+            }
+
+            mir::RvalueKind::Error => Err(()),
+        }
+    }
+
+    /// Emit the arguments of a function, task, or container method call.
+    ///
+    /// Output and inout arguments are passed as a pointer to a temporary
+    /// variable, which is copied to the actual target after the call. Returns
+    /// the argument values and the temporaries to be copied back.
+    fn emit_call_args(
+        &mut self,
+        args: &[mir::CallArg<'gcx>],
+        env: ParamEnv,
+    ) -> Result<(
+        Vec<llhd::ir::Value>,
+        Vec<(&'gcx mir::Lvalue<'gcx>, llhd::ir::Value)>,
+    )> {
+        let mut values = vec![];
+        let mut copy_back = vec![];
+        for arg in args {
+            match *arg {
+                mir::CallArg::Input(value) => values.push(self.emit_mir_rvalue(value)?),
+                mir::CallArg::Output(lvalue) => {
+                    let ty = self.emit_type(lvalue.ty, env)?;
+                    let init = self.emit_zero_for_type(&ty);
+                    let var = self.builder.ins().var(init);
+                    values.push(var);
+                    copy_back.push((lvalue, var));
+                }
+                mir::CallArg::Inout(lvalue, value) => {
+                    let init = self.emit_mir_rvalue(value)?;
+                    let var = self.builder.ins().var(init);
+                    values.push(var);
+                    copy_back.push((lvalue, var));
+                }
+            }
+        }
+        Ok((values, copy_back))
+    }
+
+    /// Emit a call of a task which suspends execution.
+    ///
+    /// The body of the task is emitted into the calling process, with the
+    /// arguments bound to its ports. A `return` statement in the body branches
+    /// to the end of the inlined task.
+    fn emit_inlined_task(
+        &mut self,
+        id: NodeId,
+        args: &[mir::CallArg<'gcx>],
+        env: ParamEnv,
+    ) -> Result<llhd::ir::Value> {
+        let hir = match self.hir_of(id)? {
+            HirNode::Subroutine(x) => x,
+            _ => unreachable!(),
+        };
+        if self.inlined_tasks.iter().any(|&(task, _)| task == id) {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "recursive {} cannot suspend execution",
+                    hir.desc_full()
+                ))
+                .span(hir.human_span()),
+            );
+            return Err(());
+        }
+        debug!("inline {} with {:?}", hir.desc_full(), env);
+
+        // Input arguments may be assigned to like regular variables, so copy
+        // them into one.
+        let (values, copy_back) = self.emit_call_args(args, env)?;
+        for (&port_id, value) in hir.ports.iter().zip(values) {
+            let port = match self.hir_of(port_id)? {
+                HirNode::SubroutinePort(x) => x,
+                _ => unreachable!(),
+            };
+            let value = match port.dir {
+                ast::SubroutinePortDir::Input | ast::SubroutinePortDir::ConstRef => {
+                    let var = self.builder.ins().var(value);
+                    self.builder
+                        .dfg_mut()
+                        .set_name(var, format!("{}.{}", hir.name.value, port.name.value));
+                    var
+                }
+                _ => value,
+            };
+            self.set_emitted_value(port_id, value);
+        }
+
+        // Emit the body.
+        let exit_blk = self.add_named_block(format!("{}_exit", hir.name.value));
+        self.inlined_tasks.push((id, exit_blk));
+        for &stmt_id in &hir.stmts {
+            self.emit_stmt(stmt_id, env)?;
+        }
+        self.inlined_tasks.pop();
+        self.builder.ins().br(exit_blk);
+        self.builder.append_to(exit_blk);
+        self.emit_copy_back(copy_back)?;
+
+        // Tasks produce no value. Return a dummy bit instead, which is never
+        // used.
+        Ok(self.builder.ins().const_int(1, 0))
+    }
+
+    /// Copy the temporaries of output and inout arguments to their targets.
+    fn emit_copy_back(
+        &mut self,
+        copy_back: Vec<(&'gcx mir::Lvalue<'gcx>, llhd::ir::Value)>,
+    ) -> Result<()> {
+        for (lvalue, var) in copy_back {
+            let value = self.builder.ins().ld(var);
+            let (lvalue, value) = self.emit_assign_target(lvalue, None, value)?;
+            self.emit_blocking_assign_llhd(lvalue, value)?;
+        }
+        Ok(())
+    }
+
+    /// Emit a call of a class method.
+    ///
+    /// Virtual methods are called through the simulation runtime, which
+    /// dispatches the call to the implementation of the object's class, for
+    /// example `@moore.class.dispatch.C.f (i64 this, ...)`. The dispatch
+    /// function has the same signature as the method itself, which is the
+    /// same for all overrides since virtual methods do not access signals.
+    fn emit_method_call(
+        &mut self,
+        target: NodeId,
+        env: ParamEnv,
+        this: Option<llhd::ir::Value>,
+        mut args: Vec<llhd::ir::Value>,
+        dispatch: bool,
+    ) -> Result<llhd::ir::Inst> {
+        let (name, sig) = self.gen.emit_subroutine(target, env)?;
+        let mut values: Vec<_> = this.into_iter().collect();
+        values.append(&mut args);
+
+        // Pass the signals the method accesses directly.
+        let acc = self.accessed_nodes(target)?;
+        for node in acc.read.union(&acc.written) {
+            values.push(self.emitted_value(*node));
+        }
+
+        let name = match name {
+            llhd::ir::UnitName::Global(ref name) if dispatch => {
+                llhd::ir::UnitName::Global(format!("moore.class.dispatch.{}", name))
+            }
+            name => name,
+        };
+        let ext_unit = self.builder.add_extern(name, sig);
+        Ok(self.builder.ins().call(ext_unit, values))
+    }
+
+    /// Emit a pointer to a property of a class object.
+    ///
+    /// Objects are managed by the simulation runtime, which provides a pointer
+    /// to each property, for example `@moore.class.ref.C.x (i64 this) i32*`.
+    /// Static properties are shared by all objects and are allocated upon
+    /// first use with their initial value, for example
+    /// `@moore.class.static.C.x (i32 init) i32*`.
+    fn emit_class_property(
+        &mut self,
+        this: Option<llhd::ir::Value>,
+        prop: NodeId,
+        env: ParamEnv,
+    ) -> Result<llhd::ir::Value> {
+        let class = resolver::declaring_class(self.cx, prop).unwrap();
+        let hir = match self.hir_of(prop)? {
+            HirNode::VarDecl(x) => x,
+            _ => unreachable!(),
+        };
+        let name = format!(
+            "{}.{}",
+            self.class_unit_name(class.id, env)?,
+            hir.name.value
+        );
+        let ty = self.type_of(prop, env)?;
+        let llty = llhd::pointer_ty(self.emit_type(ty, env)?);
+        match this {
+            Some(this) => {
+                Ok(self.emit_runtime_call(format!("moore.class.ref.{}", name), vec![this], llty))
+            }
+            None => {
+                let init = match hir.init {
+                    Some(init) => {
+                        value::cast_value(self.cx, self.constant_value_of(init, env)?, ty)
+                    }
+                    None => self.type_default_value(ty),
+                };
+                let init = self.emit_const(init, env)?;
+                Ok(
+                    self.emit_runtime_call(
+                        format!("moore.class.static.{}", name),
+                        vec![init],
+                        llty,
+                    ),
+                )
+            }
+        }
+    }
+
+    /// Emit the initialization of the non-static properties of a newly
+    /// constructed object, starting with the properties of its base classes.
+    fn emit_class_init(
+        &mut self,
+        this: llhd::ir::Value,
+        class_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<()> {
+        if let Some((base_id, base_env)) = resolver::class_base_type(self.cx, class_id, env)? {
+            self.emit_class_init(this, base_id, base_env)?;
+        }
+        let class = match self.hir_of(class_id)? {
+            HirNode::Class(x) => x,
+            _ => unreachable!(),
+        };
+
+        // Initializers may refer to the object itself, so temporarily bind
+        // `this` to the new object.
+        let prev_this = self.values.insert(class_id.into(), this);
+        for member in &class.members {
+            if member.kind != hir::ClassMemberKind::Property || member.is_static {
+                continue;
+            }
+            let init = match self.hir_of(member.id)? {
+                HirNode::VarDecl(hir::VarDecl {
+                    init: Some(init), ..
+                }) => *init,
+                _ => continue,
+            };
+            let ty = self.type_of(member.id, env)?;
+            let value =
+                mir::lower::rvalue::cast_to_type(self.cx, self.mir_rvalue(init, env), env, ty);
+            let value = self.emit_mir_rvalue(value)?;
+            let ptr = self.emit_class_property(Some(this), member.id, env)?;
+            self.builder.ins().st(ptr, value);
+        }
+        match prev_this {
+            Some(prev) => self.set_emitted_value(class_id, prev),
+            None => {
+                self.values.remove(&class_id.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the construction of an object's base class, for constructors
+    /// that do not explicitly call `super.new`.
+    ///
+    /// The base class constructor is called with the arguments given in the
+    /// `extends` clause of the class. Base classes without a constructor
+    /// implicitly construct their own base class in turn.
+    fn emit_implicit_super_new(
+        &mut self,
+        this: llhd::ir::Value,
+        class_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<()> {
+        let (base_id, base_env) = match resolver::class_base_type(self.cx, class_id, env)? {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let (class, base) = match (self.hir_of(class_id)?, self.hir_of(base_id)?) {
+            (HirNode::Class(class), HirNode::Class(base)) => (class, base),
+            _ => unreachable!(),
+        };
+        let ctor = match base.constructor() {
+            Some(ctor) => ctor,
+            None => return self.emit_implicit_super_new(this, base_id, base_env),
+        };
+        let subroutine = match self.hir_of(ctor.id)? {
+            HirNode::Subroutine(x) => x,
+            _ => unreachable!(),
+        };
+        let args = match class.extends {
+            Some((_, ref args)) => args.as_slice(),
+            None => &[],
+        };
+        let mut values = vec![];
+        for (port_id, arg) in resolver::map_call_args(self.cx, class.span, subroutine, args)? {
+            let ty = self.type_of(port_id, base_env)?;
+            let value =
+                mir::lower::rvalue::cast_to_type(self.cx, self.mir_rvalue(arg, env), env, ty);
+            values.push(self.emit_mir_rvalue(value)?);
+        }
+        self.emit_method_call(ctor.id, base_env, Some(this), values, false)?;
+        Ok(())
+    }
+
+    /// Check whether a statement calls the constructor of the base class
+    /// through `super.new`.
+    fn calls_super_new(&self, stmt_id: Option<NodeId>) -> Result<bool> {
+        let expr_id = match stmt_id {
+            Some(stmt_id) => match self.hir_of(stmt_id)? {
+                HirNode::Stmt(hir::Stmt {
+                    kind: hir::StmtKind::Expr(expr_id),
+                    ..
+                }) => *expr_id,
+                _ => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+        let (target, name) = match self.hir_of(expr_id)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::MethodCall(target, name, _),
+                ..
+            }) => (*target, name.value),
+            _ => return Ok(false),
+        };
+        match self.hir_of(target)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Super,
+                ..
+            }) => Ok(&*name.as_str() == "new"),
+            _ => Ok(false),
         }
     }
 
@@ -1212,8 +4054,12 @@ where
             // them.
             mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => Ok((
                 self.emitted_value(id).clone(),
-                self.shadows.get(&id).cloned(),
+                self.shadows.get(&id.into()).cloned(),
             )),
+            mir::LvalueKind::IntfSignal(binding, signal) => {
+                let node = AccessedNode::Intf(binding, signal);
+                Ok((self.emitted_value(node), self.shadows.get(&node).cloned()))
+            }
 
             // Member accesses simply look up their inner lvalue and extract the
             // signal or pointer to the respective subfield.
@@ -1264,6 +4110,29 @@ where
                 }
             }
 
+            // Properties of class objects are accessed through a pointer
+            // provided by the simulation runtime.
+            mir::LvalueKind::ClassProperty { this, prop, env } => {
+                let this = match this {
+                    Some(this) => Some(self.emit_mir_rvalue(this)?),
+                    None => None,
+                };
+                Ok((self.emit_class_property(this, prop, env)?, None))
+            }
+
+            // Elements of dynamic containers have no storage of their own. They
+            // can only be assigned to, which `emit_assign_target` handles.
+            mir::LvalueKind::ContainerElement { current, .. } => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "element of `{}` cannot be referenced here",
+                        current.ty
+                    ))
+                    .span(mir.span),
+                );
+                Err(())
+            }
+
             // Errors from MIR lowering have already been reported. Just abort.
             mir::LvalueKind::Error => Err(()),
 
@@ -1335,13 +4204,52 @@ where
         hir: &hir::Stmt,
         env: ParamEnv,
     ) -> Result<()> {
+        // Functions and tasks are emitted as LLHD functions, which cannot
+        // suspend execution or spawn processes. Tasks which do are inlined
+        // into the calling process instead, and only end up here if called
+        // from a function.
+        if self.builder.unit().is_function() {
+            let msg = match hir.kind {
+                hir::StmtKind::Timed { .. } => {
+                    Some("timing control in functions and tasks not supported".to_string())
+                }
+                hir::StmtKind::Wait { .. }
+                | hir::StmtKind::Fork { .. }
+                | hir::StmtKind::WaitFork => Some(format!(
+                    "{} in functions and tasks not supported",
+                    hir.desc()
+                )),
+                _ => None,
+            };
+            if let Some(msg) = msg {
+                self.emit(DiagBuilder2::error(msg).span(hir.span));
+                return Err(());
+            }
+        }
+
         #[allow(unreachable_patterns)]
         match hir.kind {
             hir::StmtKind::Null => (),
+            // Assertions have no effect on the generated hardware.
+            hir::StmtKind::Assertion(_) => (),
             hir::StmtKind::Block(ref ids) => {
+                // Labelled blocks may be left early by a `disable` statement.
+                let exit_blk = match hir.label {
+                    Some(label) => {
+                        let blk = self.add_named_block(format!("{}_exit", label.value));
+                        self.disable_targets.push((label.value, blk));
+                        Some(blk)
+                    }
+                    None => None,
+                };
                 for &id in ids {
                     self.emit_stmt(id, env)?;
                 }
+                if let Some(exit_blk) = exit_blk {
+                    self.disable_targets.pop();
+                    self.builder.ins().br(exit_blk);
+                    self.builder.append_to(exit_blk);
+                }
             }
             hir::StmtKind::Assign { lhs, rhs, kind } => {
                 // TODO(fschuiki): The following should happen in a lowering of the
@@ -1349,11 +4257,17 @@ where
                 let lhs_mir = self.mir_lvalue(lhs, env);
                 let rhs_mir = self.mir_rvalue(rhs, env);
                 let rhs_mir = mir::lower::rvalue::cast_to_type(self.cx, rhs_mir, env, lhs_mir.ty);
-                let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+                // Elements of dynamic containers have no storage of their own
+                // and are only emitted once the assigned value is known.
+                let lhs_lv = match lhs_mir.kind {
+                    mir::LvalueKind::ContainerElement { .. } => None,
+                    _ => Some(self.emit_mir_lvalue(lhs_mir)?),
+                };
                 let rhs_rv = self.emit_mir_rvalue(rhs_mir)?;
 
                 match kind {
                     hir::AssignKind::Block(ast::AssignOp::Identity) => {
+                        let (lhs_lv, rhs_rv) = self.emit_assign_target(lhs_mir, lhs_lv, rhs_rv)?;
                         self.emit_blocking_assign_llhd(lhs_lv, rhs_rv)?;
                     }
                     hir::AssignKind::Block(op) => {
@@ -1381,14 +4295,17 @@ where
                                 self.emit_shift_operator(ShiftDir::Right, true, lhs_rv, rhs_rv)
                             }
                         };
+                        let (lhs_lv, value) = self.emit_assign_target(lhs_mir, lhs_lv, value)?;
                         self.emit_blocking_assign_llhd(lhs_lv, value)?;
                     }
                     hir::AssignKind::Nonblock => {
+                        let (lhs_lv, rhs_rv) = self.emit_assign_target(lhs_mir, lhs_lv, rhs_rv)?;
                         let delay = llhd::ConstTime::new(num::zero(), 1, 0);
                         let delay_const = self.builder.ins().const_time(delay);
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay_const);
                     }
                     hir::AssignKind::NonblockDelay(delay) => {
+                        let (lhs_lv, rhs_rv) = self.emit_assign_target(lhs_mir, lhs_lv, rhs_rv)?;
                         let delay = self.emit_rvalue(delay, env)?;
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay);
                     }
@@ -1407,9 +4324,7 @@ where
             } => {
                 let resume_blk = self.add_nameless_block();
                 let duration = self.emit_rvalue(expr_id, env)?.into();
-                self.builder.ins().wait_time(resume_blk, duration, vec![]);
-                self.builder.append_to(resume_blk);
-                self.emit_shadow_update();
+                self.emit_suspend(resume_blk, Some(duration), vec![]);
                 self.emit_stmt(stmt, env)?;
             }
             hir::StmtKind::Timed {
@@ -1441,9 +4356,7 @@ where
                         trigger_on.push(self.emitted_value(id).clone());
                     }
                 }
-                self.builder.ins().wait(check_blk, trigger_on);
-                self.builder.append_to(check_blk);
-                self.emit_shadow_update();
+                self.emit_suspend(check_blk, None, trigger_on);
 
                 // Check if any of the events happened and produce a single bit
                 // value that represents this.
@@ -1496,9 +4409,7 @@ where
                 for &id in &acc.read {
                     trigger_on.push(self.emitted_value(id).clone());
                 }
-                self.builder.ins().wait(trigger_blk, trigger_on);
-                self.builder.append_to(trigger_blk);
-                self.emit_shadow_update();
+                self.emit_suspend(trigger_blk, None, trigger_on);
 
                 // Emit the actual statement.
                 self.emit_stmt(stmt, env)?;
@@ -1604,6 +4515,46 @@ where
                     self.emit_stmt(stmt, env)?;
                 }
             }
+            hir::StmtKind::Return(expr) => {
+                // Returning from a task inlined into a process skips the rest
+                // of its body.
+                if let Some(&(_, exit_blk)) = self.inlined_tasks.last() {
+                    self.builder.ins().br(exit_blk);
+                    let blk = self.add_nameless_block();
+                    self.builder.append_to(blk);
+                    return Ok(());
+                }
+                if !self.builder.unit().is_function() {
+                    self.emit(
+                        DiagBuilder2::error("return statement outside of a function or task")
+                            .span(hir.span),
+                    );
+                    return Err(());
+                }
+                match expr {
+                    Some(expr) => {
+                        let mut sub_id = expr;
+                        while let Some(parent) = self.parent_node_id(sub_id) {
+                            sub_id = parent;
+                            if let HirNode::Subroutine(_) = self.hir_of(sub_id)? {
+                                break;
+                            }
+                        }
+                        let ty = self.type_of(sub_id, env)?;
+                        let mir = self.mir_rvalue(expr, env);
+                        let mir = mir::lower::rvalue::cast_to_type(self.cx, mir, env, ty);
+                        let value = self.emit_mir_rvalue(mir)?;
+                        self.builder.ins().ret_value(value);
+                    }
+                    None => {
+                        self.builder.ins().ret();
+                    }
+                }
+                // Subsequent statements are unreachable, but still need a
+                // block to be emitted into.
+                let blk = self.add_nameless_block();
+                self.builder.append_to(blk);
+            }
 
             hir::StmtKind::Case {
                 expr,
@@ -1681,6 +4632,161 @@ where
                 self.builder.append_to(final_blk);
             }
 
+            hir::StmtKind::CaseMatches {
+                expr,
+                ref ways,
+                default,
+            } => {
+                let ty = self.type_of(expr, env)?;
+                let value = self.emit_rvalue(expr, env)?;
+                let final_blk = self.add_named_block("case_exit");
+                for &(pattern, stmt) in ways {
+                    let check = self.emit_pattern_match(pattern, value, ty, env)?;
+                    let taken_blk = self.add_named_block("case_body");
+                    let untaken_blk = self.add_nameless_block();
+                    self.builder.ins().br_cond(check, untaken_blk, taken_blk);
+                    self.builder.append_to(taken_blk);
+                    self.emit_stmt(stmt, env)?;
+                    self.builder.ins().br(final_blk);
+                    self.builder.append_to(untaken_blk);
+                }
+                if let Some(default) = default {
+                    self.emit_stmt(default, env)?;
+                }
+                self.builder.ins().br(final_blk);
+                self.builder.append_to(final_blk);
+            }
+
+            // Events are toggled by every trigger.
+            hir::StmtKind::Trigger(expr) => {
+                let lhs_mir = self.mir_lvalue(expr, env);
+                let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+                let value = self.emit_rvalue(expr, env)?;
+                let value = self.builder.ins().not(value);
+                self.emit_blocking_assign_llhd(lhs_lv, value)?;
+            }
+
+            // Wait statements re-evaluate their condition whenever one of the
+            // signals it depends on changes.
+            hir::StmtKind::Wait { cond, stmt } => {
+                let check_blk = self.add_named_block("wait_check");
+                self.builder.ins().br(check_blk);
+                self.builder.append_to(check_blk);
+                let cond_value = self.emit_rvalue_bool(cond, env)?;
+                let suspend_blk = self.add_named_block("wait_suspend");
+                let done_blk = self.add_named_block("wait_done");
+                self.builder
+                    .ins()
+                    .br_cond(cond_value, suspend_blk, done_blk);
+                self.builder.append_to(suspend_blk);
+                let mut trigger_on = vec![];
+                let acc = self.accessed_nodes(cond)?;
+                for &id in &acc.read {
+                    trigger_on.push(self.emitted_value(id).clone());
+                }
+                let resume_blk = self.add_named_block("wait_resume");
+                self.emit_suspend(resume_blk, None, trigger_on);
+                self.builder.ins().br(check_blk);
+                self.builder.append_to(done_blk);
+                self.emit_stmt(stmt, env)?;
+            }
+
+            // The branches of a fork are emitted as separate processes, which
+            // are started by toggling their start signal. Declarations are
+            // executed by the process itself.
+            hir::StmtKind::Fork { ref stmts, join } => {
+                // The branches are collected from the statements of the
+                // process, which do not include the bodies of inlined tasks.
+                if !self.inlined_tasks.is_empty() {
+                    self.emit(DiagBuilder2::error("`fork` in tasks not supported").span(hir.span));
+                    return Err(());
+                }
+                let mut branches = vec![];
+                for &id in stmts {
+                    match self.fork_sync.branches.iter().find(|&&(b, _)| b == id) {
+                        Some(&(_, branch)) => branches.push(branch),
+                        None => self.emit_stmt(id, env)?,
+                    }
+                }
+
+                // A branch cannot be started while it is still running, since
+                // it is a single process. Repeated forks which do not wait
+                // for all of their branches therefore wait for the branches
+                // of their previous execution first.
+                if join != ast::JoinKind::All && self.fork_sync.repeated.contains(&hir.id) {
+                    self.emit(
+                        DiagBuilder2::warning(
+                            "branches of `fork` may still be running when it is executed again",
+                        )
+                        .span(hir.span)
+                        .add_note(
+                            "Each branch is emitted as a single process, which cannot run \
+                             multiple times concurrently. The `fork` waits for the branches \
+                             of its previous execution to complete before starting them again.",
+                        ),
+                    );
+                    self.emit_join(&branches, true);
+                }
+
+                for branch in &branches {
+                    let done = self.builder.ins().prb(branch.done);
+                    let expect = self.builder.ins().not(done);
+                    self.builder.ins().st(branch.expect, expect);
+                    self.emit_toggle(branch.start.0, branch.start.1);
+                }
+                match join {
+                    ast::JoinKind::All => self.emit_join(&branches, true),
+                    ast::JoinKind::Any => self.emit_join(&branches, false),
+                    ast::JoinKind::None => (),
+                }
+            }
+            hir::StmtKind::WaitFork => {
+                let branches: Vec<_> = self
+                    .fork_sync
+                    .branches
+                    .iter()
+                    .map(|&(_, branch)| branch)
+                    .collect();
+                self.emit_join(&branches, true);
+            }
+            hir::StmtKind::DisableFork => {
+                if let Some((kill, kill_var)) = self.fork_sync.kill {
+                    self.emit_toggle(kill, kill_var);
+                }
+            }
+
+            // Disabling an enclosing block jumps to its exit.
+            hir::StmtKind::Disable(name) => {
+                let target = self
+                    .disable_targets
+                    .iter()
+                    .rev()
+                    .find(|&&(n, _)| n == name.value)
+                    .map(|&(_, blk)| blk);
+                match target {
+                    Some(exit_blk) => {
+                        self.builder.ins().br(exit_blk);
+                        let dead_blk = self.add_nameless_block();
+                        self.builder.append_to(dead_blk);
+                    }
+                    None => {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "`disable {}` does not name an enclosing block of the same \
+                                 process",
+                                name.value
+                            ))
+                            .span(name.span)
+                            .add_note(
+                                "Only blocks enclosing the `disable` statement within the \
+                                 same process or branch can be disabled.",
+                            ),
+                        );
+                        return Err(());
+                    }
+                }
+            }
+
             _ => {
                 error!("{:#?}", hir);
                 return self.unimp_msg("code generation for", hir);
@@ -1689,6 +4795,79 @@ where
         Ok(())
     }
 
+    /// Emit the code to match a value against a pattern.
+    ///
+    /// Returns a single bit that indicates whether the value matches. Pattern
+    /// variables are bound to the corresponding part of the value as a side
+    /// effect.
+    fn emit_pattern_match(
+        &mut self,
+        pattern: NodeId,
+        value: llhd::ir::Value,
+        ty: Type<'gcx>,
+        env: ParamEnv,
+    ) -> Result<llhd::ir::Value> {
+        let hir = match self.hir_of(pattern)? {
+            HirNode::Expr(x) => x,
+            _ => unreachable!(),
+        };
+        match hir.kind {
+            // Wildcards match anything.
+            hir::ExprKind::PatternVar(None) => Ok(self.builder.ins().const_int(1, 1)),
+
+            // Pattern variables match anything and capture the value.
+            hir::ExprKind::PatternVar(Some(name)) => {
+                let var = self.builder.ins().var(value);
+                self.builder.dfg_mut().set_name(var, name.value.to_string());
+                self.set_emitted_value(pattern, var);
+                Ok(self.builder.ins().const_int(1, 1))
+            }
+
+            // Tagged union patterns check the tag and match the member against
+            // the nested pattern.
+            hir::ExprKind::Tagged(_, arg) => {
+                let (def_id, index) = resolver::resolve_tagged_member(self.cx, pattern, env)?;
+                let def = self.struct_def(def_id)?;
+                let tag_width = def.tag_width();
+                let data_width = ty::bit_size_of_type(self.cx, ty, env)? - tag_width;
+                let tag_check = if tag_width > 0 {
+                    let tag = self.builder.ins().ext_slice(value, data_width, tag_width);
+                    let expected = self.builder.ins().const_int(tag_width, index);
+                    self.builder.ins().eq(tag, expected)
+                } else {
+                    self.builder.ins().const_int(1, 1)
+                };
+                let arg = match arg {
+                    Some(arg) => arg,
+                    None => return Ok(tag_check),
+                };
+                let member_ty = self.map_to_type(def.fields[index].ty, env)?;
+                let member_width = ty::bit_size_of_type(self.cx, member_ty, env)?;
+                if member_width == 0 || !self.emit_type(member_ty, env)?.is_int() {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "union member of type `{}` cannot be matched",
+                            member_ty
+                        ))
+                        .span(self.span(arg)),
+                    );
+                    return Err(());
+                }
+                let member = self.builder.ins().ext_slice(value, 0, member_width);
+                let member_check = self.emit_pattern_match(arg, member, member_ty, env)?;
+                Ok(self.builder.ins().and(tag_check, member_check))
+            }
+
+            // All other expressions are compared against the value.
+            _ => {
+                let mir = self.mir_rvalue(pattern, env);
+                let mir = mir::lower::rvalue::cast_to_type(self.cx, mir, env, ty);
+                let expected = self.emit_mir_rvalue(mir)?;
+                Ok(self.builder.ins().eq(value, expected))
+            }
+        }
+    }
+
     /// Emit the code for a variable declaration statement, given its HIR.
     fn emit_stmt_var_decl(
         &mut self,
@@ -1697,14 +4876,25 @@ where
         env: ParamEnv,
     ) -> Result<()> {
         let ty = self.type_of(decl_id, env)?;
-        let ty = self.emit_type(ty, env)?;
         let init = match hir.init {
             Some(expr) => {
-                let k = self.constant_value_of(expr, env)?;
-                self.emit_const(k, env)?
+                let mir = self.mir_rvalue(expr, env);
+                let mir = mir::lower::rvalue::cast_to_type(self.cx, mir, env, ty);
+                self.emit_mir_rvalue(mir)?
+            }
+            None => {
+                let ty = self.emit_type(ty, env)?;
+                self.emit_zero_for_type(&ty)
             }
-            None => self.emit_zero_for_type(&ty),
         };
+
+        // Variables shared with the branches of `fork` statements are passed
+        // to the process as signals, which are initialized here.
+        if let Some(&signal) = self.values.get(&decl_id.into()) {
+            let shadow = self.shadows.get(&decl_id.into()).cloned();
+            return self.emit_blocking_assign_llhd((signal, shadow), init);
+        }
+
         let value = self.builder.ins().var(init);
         self.builder
             .dfg_mut()
@@ -1778,11 +4968,343 @@ where
         lvalue: &mir::Lvalue<'gcx>,
         rvalue: &mir::Rvalue<'gcx>,
     ) -> Result<()> {
-        let lv = self.emit_mir_lvalue(lvalue)?;
+        let lv = match lvalue.kind {
+            mir::LvalueKind::ContainerElement { .. } => None,
+            _ => Some(self.emit_mir_lvalue(lvalue)?),
+        };
         let rv = self.emit_mir_rvalue(rvalue)?;
+        let (lv, rv) = self.emit_assign_target(lvalue, lv, rv)?;
         self.emit_blocking_assign_llhd(lv, rv)
     }
 
+    /// Determine the target of an assignment of `value` to `lvalue`.
+    ///
+    /// Elements of dynamic arrays, queues, and associative arrays cannot be
+    /// assigned to directly. Instead, a modified copy of the container is
+    /// assigned to the container itself. Returns the lvalue and value that
+    /// are ultimately assigned. If `emitted` is given, the lvalue has already
+    /// been emitted and is returned as is.
+    fn emit_assign_target(
+        &mut self,
+        lvalue: &mir::Lvalue<'gcx>,
+        emitted: Option<(llhd::ir::Value, Option<llhd::ir::Value>)>,
+        value: llhd::ir::Value,
+    ) -> Result<((llhd::ir::Value, Option<llhd::ir::Value>), llhd::ir::Value)> {
+        if let Some(lv) = emitted {
+            return Ok((lv, value));
+        }
+        match lvalue.kind {
+            mir::LvalueKind::ContainerElement {
+                value: container,
+                current,
+                index,
+            } => {
+                let handle = self.emit_mir_rvalue(current)?;
+                let index = self.emit_mir_rvalue(index)?;
+                let (_, updated) = self.emit_container_op(
+                    mir::ContainerOp::Set,
+                    current.ty,
+                    handle,
+                    vec![index, value],
+                    lvalue.env,
+                )?;
+                self.emit_assign_target(container, None, updated.unwrap())
+            }
+            _ => Ok((self.emit_mir_lvalue(lvalue)?, value)),
+        }
+    }
+
+    /// Emit an operation on a dynamic array, queue, or associative array.
+    ///
+    /// These containers are represented as `i64` handles to containers
+    /// managed by the simulation runtime, where `0` is the empty container.
+    /// Containers have value semantics: operations never modify a container
+    /// in place, but produce a handle to a modified copy instead. Each
+    /// operation calls an external function whose name encodes the container
+    /// kind, the operation, and the types involved, for example
+    /// `@moore.queue.push_back.i32 (i64, i32) i64`.
+    ///
+    /// Returns the result of the operation, if any, and the handle to the
+    /// modified container, if the operation modifies it.
+    fn emit_container_op(
+        &mut self,
+        op: mir::ContainerOp,
+        ty: Type<'gcx>,
+        handle: llhd::ir::Value,
+        args: Vec<llhd::ir::Value>,
+        env: ParamEnv,
+    ) -> Result<(Option<llhd::ir::Value>, Option<llhd::ir::Value>)> {
+        use mir::ContainerOp;
+        if ty.is_string() {
+            return Ok(self.emit_string_op(op, handle, args));
+        }
+        let handle_ty = llhd::int_ty(64);
+        let int_ty = llhd::int_ty(32);
+        let elem_ty = self.emit_type(ty.get_dynamic_element().unwrap(), env)?;
+        let index_ty = self.emit_type(ty.get_dynamic_index().unwrap(), env)?;
+        let elem = elem_ty.to_string().replace(' ', "");
+        let index = index_ty.to_string().replace(' ', "");
+        let (kind, types) = match *ty.resolve_name() {
+            TypeKind::AssocArray(..) => ("assoc", format!("{}.{}", index, elem)),
+            _ => ("dyn", elem.clone()),
+        };
+        let mut operands = vec![handle];
+        operands.extend(args);
+        Ok(match op {
+            ContainerOp::New => {
+                // The size is passed first, followed by the initial contents.
+                operands.rotate_left(1);
+                let name = format!("moore.dyn.new.{}", elem);
+                (
+                    Some(self.emit_runtime_call(name, operands, handle_ty)),
+                    None,
+                )
+            }
+            ContainerOp::Size => {
+                let name = format!("moore.{}.size", kind);
+                (Some(self.emit_runtime_call(name, operands, int_ty)), None)
+            }
+            ContainerOp::Get => {
+                let name = format!("moore.{}.get.{}", kind, types);
+                (Some(self.emit_runtime_call(name, operands, elem_ty)), None)
+            }
+            ContainerOp::Set => {
+                let name = format!("moore.{}.set.{}", kind, types);
+                (
+                    None,
+                    Some(self.emit_runtime_call(name, operands, handle_ty)),
+                )
+            }
+            ContainerOp::DeleteAll => (None, Some(self.builder.ins().const_int(64, 0))),
+            ContainerOp::Delete => {
+                let name = match kind {
+                    "assoc" => format!("moore.assoc.delete.{}", index),
+                    _ => format!("moore.queue.delete"),
+                };
+                (
+                    None,
+                    Some(self.emit_runtime_call(name, operands, handle_ty)),
+                )
+            }
+            ContainerOp::Exists => {
+                let name = format!("moore.assoc.exists.{}", index);
+                (Some(self.emit_runtime_call(name, operands, int_ty)), None)
+            }
+            ContainerOp::First | ContainerOp::Last | ContainerOp::Next | ContainerOp::Prev => {
+                let which = match op {
+                    ContainerOp::First => "first",
+                    ContainerOp::Last => "last",
+                    ContainerOp::Next => "next",
+                    _ => "prev",
+                };
+                let name = format!("moore.assoc.{}.{}", which, index);
+                (Some(self.emit_runtime_call(name, operands, int_ty)), None)
+            }
+            ContainerOp::Insert => {
+                let name = format!("moore.queue.insert.{}", elem);
+                (
+                    None,
+                    Some(self.emit_runtime_call(name, operands, handle_ty)),
+                )
+            }
+            ContainerOp::PushFront | ContainerOp::PushBack => {
+                let which = match op {
+                    ContainerOp::PushFront => "push_front",
+                    _ => "push_back",
+                };
+                let name = format!("moore.queue.{}.{}", which, elem);
+                (
+                    None,
+                    Some(self.emit_runtime_call(name, operands, handle_ty)),
+                )
+            }
+            ContainerOp::PopFront | ContainerOp::PopBack => {
+                let (peek, pop) = match op {
+                    ContainerOp::PopFront => ("front", "pop_front"),
+                    _ => ("back", "pop_back"),
+                };
+                let name = format!("moore.queue.{}.{}", peek, elem);
+                let result = self.emit_runtime_call(name, operands.clone(), elem_ty);
+                let name = format!("moore.queue.{}", pop);
+                let updated = self.emit_runtime_call(name, operands, handle_ty);
+                (Some(result), Some(updated))
+            }
+            ContainerOp::Substr
+            | ContainerOp::ToUpper
+            | ContainerOp::ToLower
+            | ContainerOp::Atoi
+            | ContainerOp::Itoa => unreachable!("{:?} on `{}`", op, ty),
+        })
+    }
+
+    /// Emit an operation on a string.
+    ///
+    /// Strings are represented as `i64` handles in the same way as dynamic
+    /// containers, and each operation calls an external function such as
+    /// `@moore.string.substr (i64, i32, i32) i64`.
+    fn emit_string_op(
+        &mut self,
+        op: mir::ContainerOp,
+        handle: llhd::ir::Value,
+        args: Vec<llhd::ir::Value>,
+    ) -> (Option<llhd::ir::Value>, Option<llhd::ir::Value>) {
+        use mir::ContainerOp;
+        let handle_ty = llhd::int_ty(64);
+        let mut operands = vec![handle];
+        operands.extend(args);
+        let (name, result_ty) = match op {
+            ContainerOp::Size => ("len", llhd::int_ty(32)),
+            ContainerOp::Get => ("getc", llhd::int_ty(8)),
+            ContainerOp::Substr => ("substr", handle_ty),
+            ContainerOp::ToUpper => ("toupper", handle_ty),
+            ContainerOp::ToLower => ("tolower", handle_ty),
+            ContainerOp::Atoi => ("atoi", llhd::int_ty(32)),
+            ContainerOp::Set | ContainerOp::Itoa => {
+                let name = match op {
+                    ContainerOp::Set => "moore.string.putc",
+                    _ => "moore.string.itoa",
+                };
+                let updated = self.emit_runtime_call(name.into(), operands, handle_ty);
+                return (None, Some(updated));
+            }
+            _ => unreachable!("{:?} on string", op),
+        };
+        let name = format!("moore.string.{}", name);
+        (
+            Some(self.emit_runtime_call(name, operands, result_ty)),
+            None,
+        )
+    }
+
+    /// Emit the format items of a display or severity task.
+    ///
+    /// Each item appends to the message the runtime is assembling, which is
+    /// then printed by a subsequent call to `@moore.display` or
+    /// `@moore.severity`. Field widths and precisions are passed as `i32`,
+    /// with -1 indicating that none was given. Integers are formatted by
+    /// `@moore.fmt.int.iN (iN value, i1 signed, i32 radix, i32 width)`.
+    fn emit_format_items(&mut self, items: &[mir::FormatItem<'gcx>]) -> Result<()> {
+        for item in items {
+            let (spec, value) = match *item {
+                mir::FormatItem::Text(ref text) => {
+                    let text = self.emit_const_string(text.as_bytes());
+                    let width = self.builder.ins().const_int(32, u32::max_value());
+                    self.emit_runtime_task("moore.fmt.str".into(), vec![text, width]);
+                    continue;
+                }
+                mir::FormatItem::Scope => {
+                    self.emit_runtime_task("moore.fmt.scope".into(), vec![]);
+                    continue;
+                }
+                mir::FormatItem::Arg(spec, value) => (spec, value),
+            };
+            let width = spec.width.map(|w| w as u32).unwrap_or(u32::max_value());
+            let width = self.builder.ins().const_int(32, width);
+            let signed = value.ty.is_signed();
+            let llvalue = self.emit_mir_rvalue(value)?;
+            let (name, args) = match spec.conv {
+                hir::FormatConv::String => ("moore.fmt.str".into(), vec![llvalue, width]),
+                hir::FormatConv::Char => ("moore.fmt.char".into(), vec![llvalue, width]),
+                hir::FormatConv::Time => (
+                    format!("moore.fmt.time.i{}", value.ty.width()),
+                    vec![llvalue, width],
+                ),
+                hir::FormatConv::Exponent | hir::FormatConv::Float | hir::FormatConv::General => {
+                    let conv = match spec.conv {
+                        hir::FormatConv::Exponent => b'e',
+                        hir::FormatConv::Float => b'f',
+                        _ => b'g',
+                    };
+                    let conv = self.builder.ins().const_int(8, conv);
+                    let precision = spec.precision.map(|p| p as u32).unwrap_or(u32::max_value());
+                    let precision = self.builder.ins().const_int(32, precision);
+                    (
+                        "moore.fmt.real".into(),
+                        vec![llvalue, conv, width, precision],
+                    )
+                }
+                hir::FormatConv::Binary
+                | hir::FormatConv::Octal
+                | hir::FormatConv::Decimal
+                | hir::FormatConv::Hex => {
+                    let radix = match spec.conv {
+                        hir::FormatConv::Binary => 2,
+                        hir::FormatConv::Octal => 8,
+                        hir::FormatConv::Decimal => 10,
+                        _ => 16,
+                    };
+                    let signed = self.builder.ins().const_int(1, signed as usize);
+                    let radix = self.builder.ins().const_int(32, radix);
+                    (
+                        format!("moore.fmt.int.i{}", value.ty.width()),
+                        vec![llvalue, signed, radix, width],
+                    )
+                }
+            };
+            self.emit_runtime_task(name, args);
+        }
+        Ok(())
+    }
+
+    /// Emit a constant string.
+    ///
+    /// The empty string is represented by the null handle. Other strings are
+    /// created from their bytes by the runtime.
+    fn emit_const_string(&mut self, bytes: &[u8]) -> llhd::ir::Value {
+        if bytes.is_empty() {
+            return self.builder.ins().const_int(64, 0);
+        }
+        let width = 8 * bytes.len();
+        let value = self
+            .builder
+            .ins()
+            .const_int(width, value::string_to_int(bytes));
+        self.emit_runtime_call(
+            format!("moore.string.from_i{}", width),
+            vec![value],
+            llhd::int_ty(64),
+        )
+    }
+
+    /// Emit a call to a function provided by the simulation runtime.
+    ///
+    /// LLHD has no floating-point types, so real arithmetic and conversions
+    /// are also performed by the runtime, for example
+    /// `@moore.real.add (i64, i64) i64` or `@moore.real.round.i32 (i64) i32`.
+    fn emit_runtime_call(
+        &mut self,
+        name: String,
+        args: Vec<llhd::ir::Value>,
+        return_ty: llhd::Type,
+    ) -> llhd::ir::Value {
+        let mut sig = llhd::ir::Signature::new();
+        for &arg in &args {
+            sig.add_input(self.llhd_type(arg));
+        }
+        sig.set_return_type(return_ty);
+        let ext_unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global(name), sig);
+        let inst = self.builder.ins().call(ext_unit, args);
+        self.builder.dfg().inst_result(inst)
+    }
+
+    /// Emit a call to a task provided by the simulation runtime.
+    ///
+    /// Same as `emit_runtime_call`, but for tasks which return no value, for
+    /// example `@moore.display (i1 newline) void`.
+    fn emit_runtime_task(&mut self, name: String, args: Vec<llhd::ir::Value>) {
+        let mut sig = llhd::ir::Signature::new();
+        for &arg in &args {
+            sig.add_input(self.llhd_type(arg));
+        }
+        sig.set_return_type(llhd::void_ty());
+        let ext_unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global(name), sig);
+        self.builder.ins().call(ext_unit, args);
+    }
+
     /// Emit a blocking assignment to a variable or signal.
     fn emit_blocking_assign_llhd(
         &mut self,
@@ -1822,6 +5344,82 @@ where
             self.builder.ins().st(shadow, value);
         }
     }
+
+    /// Suspend the process until one of `signals` changes, or until `time`
+    /// has passed, and resume execution in `resume_blk`.
+    ///
+    /// Branches of `fork` statements are additionally woken up when they are
+    /// aborted.
+    fn emit_suspend(
+        &mut self,
+        resume_blk: llhd::ir::Block,
+        time: Option<llhd::ir::Value>,
+        mut signals: Vec<llhd::ir::Value>,
+    ) {
+        if let Some((kill, _, _)) = self.fork_sync.abort {
+            signals.push(kill);
+        }
+        match time {
+            Some(time) => self.builder.ins().wait_time(resume_blk, time, signals),
+            None => self.builder.ins().wait(resume_blk, signals),
+        };
+        self.builder.append_to(resume_blk);
+        self.emit_shadow_update();
+
+        // Leave the branch if it has been aborted in the meantime.
+        if let Some((kill, kill_init, abort_blk)) = self.fork_sync.abort {
+            let kill_now = self.builder.ins().prb(kill);
+            let killed = self.builder.ins().neq(kill_init, kill_now);
+            let cont_blk = self.add_nameless_block();
+            self.builder.ins().br_cond(killed, cont_blk, abort_blk);
+            self.builder.append_to(cont_blk);
+        }
+    }
+
+    /// Toggle a synchronization signal, given the variable tracking its value.
+    fn emit_toggle(&mut self, signal: llhd::ir::Value, var: llhd::ir::Value) {
+        let value = self.builder.ins().ld(var);
+        let value = self.builder.ins().not(value);
+        self.builder.ins().st(var, value);
+        let delay = llhd::ConstTime::new(num::zero(), 1, 0);
+        let delay = self.builder.ins().const_time(delay);
+        self.builder.ins().drv(signal, value, delay);
+    }
+
+    /// Wait for the completion of branches of `fork` statements.
+    ///
+    /// Waits until all branches have completed if `all` is set, or until any
+    /// of them has completed otherwise.
+    fn emit_join(&mut self, branches: &[ForkBranch], all: bool) {
+        if branches.is_empty() {
+            return;
+        }
+        let check_blk = self.add_named_block("join_check");
+        self.builder.ins().br(check_blk);
+        self.builder.append_to(check_blk);
+        let mut joined = None;
+        for branch in branches {
+            let done = self.builder.ins().prb(branch.done);
+            let expect = self.builder.ins().ld(branch.expect);
+            let finished = self.builder.ins().eq(done, expect);
+            joined = Some(match joined {
+                Some(chain) if all => self.builder.ins().and(chain, finished),
+                Some(chain) => self.builder.ins().or(chain, finished),
+                None => finished,
+            });
+        }
+        let wait_blk = self.add_named_block("join_wait");
+        let exit_blk = self.add_named_block("join_exit");
+        self.builder
+            .ins()
+            .br_cond(joined.unwrap(), wait_blk, exit_blk);
+        self.builder.append_to(wait_blk);
+        let resume_blk = self.add_named_block("join_resume");
+        let signals = branches.iter().map(|branch| branch.done).collect();
+        self.emit_suspend(resume_blk, None, signals);
+        self.builder.ins().br(check_blk);
+        self.builder.append_to(exit_blk);
+    }
 }
 
 /// An rvalue emission mode.
@@ -1852,3 +5450,120 @@ enum ShiftDir {
     Left,
     Right,
 }
+
+/// A visitor for the HIR that collects the hierarchical names used within a
+/// module, together with the module instances.
+struct HierNameCollector<'a, C> {
+    cx: &'a C,
+    names: BTreeSet<NodeId>,
+    insts: Vec<NodeId>,
+}
+
+impl<'a, 'gcx: 'a, C> hir::Visitor<'gcx> for HierNameCollector<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx hir::Expr, lvalue: bool) {
+        if let hir::ExprKind::Field(..) = expr.kind {
+            match self.cx.resolve_hier_name(expr.id) {
+                Ok(Some(hier)) => {
+                    match self.cx.hir_of(hier.target) {
+                        Ok(HirNode::ValueParam(..)) => (),
+                        _ if hier.is_local() => (),
+                        _ => {
+                            self.names.insert(hier.id);
+                        }
+                    }
+                    return;
+                }
+                Ok(None) => (),
+                Err(()) => return,
+            }
+        }
+        hir::walk_expr(self, expr, lvalue);
+    }
+
+    fn visit_inst(&mut self, inst: &'gcx hir::Inst<'gcx>) {
+        if let Ok(Some(_)) = resolver::inst_module(self.cx, inst.id) {
+            self.insts.push(inst.id);
+        }
+        hir::walk_inst(self, inst);
+    }
+}
+
+/// A visitor for the HIR that collects the branches of the `fork` statements
+/// executed by a process.
+///
+/// Branches of nested `fork` statements are executed by the process of the
+/// enclosing branch, and are therefore not collected. Declarations within a
+/// `fork` are executed by the process itself. A `fork` is repeated if the
+/// process repeats, or if it is located within a loop.
+struct ForkBranchCollector<'a, C> {
+    cx: &'a C,
+    /// Whether the statements are currently executed repeatedly.
+    repeats: bool,
+    /// The collected branches, and whether they may be started repeatedly.
+    branches: Vec<(NodeId, bool)>,
+    /// The `fork` statements which are executed repeatedly.
+    repeated_forks: HashSet<NodeId>,
+}
+
+impl<'a, C> ForkBranchCollector<'a, C> {
+    fn new(cx: &'a C, repeats: bool) -> Self {
+        ForkBranchCollector {
+            cx,
+            repeats,
+            branches: vec![],
+            repeated_forks: Default::default(),
+        }
+    }
+}
+
+impl<'a, 'gcx: 'a, C> hir::Visitor<'gcx> for ForkBranchCollector<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx hir::Stmt) {
+        match stmt.kind {
+            hir::StmtKind::Fork { ref stmts, .. } => {
+                if self.repeats {
+                    self.repeated_forks.insert(stmt.id);
+                }
+                for &id in stmts {
+                    match self.cx.hir_of(id) {
+                        Ok(HirNode::Stmt(hir::Stmt {
+                            kind: hir::StmtKind::InlineGroup { .. },
+                            ..
+                        })) => (),
+                        _ => self.branches.push((id, self.repeats)),
+                    }
+                }
+            }
+            hir::StmtKind::Loop { .. } => {
+                let repeats = std::mem::replace(&mut self.repeats, true);
+                hir::walk_stmt(self, stmt);
+                self.repeats = repeats;
+            }
+            _ => hir::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// Prefix a name with the hierarchical scope it is declared in, if any.
+fn scoped_name(scope: &str, name: impl std::fmt::Display) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
@@ -29,7 +29,7 @@ use crate::{
     common::{arenas::Alloc, arenas::TypedArena, Session},
     crate_prelude::*,
     hir::{self, AccessTable, HirNode},
-    resolver::StructDef,
+    resolver::{CallMapping, ClassCall, HierName, IntfSignal, StructDef},
     ty::{Type, TypeKind},
     typeck::TypeContext,
     value::{Value, ValueData, ValueKind},
@@ -55,12 +55,18 @@ pub struct GlobalContext<'gcx> {
     ast_map: AstMap<'gcx>,
     /// The modules in the AST.
     modules: RefCell<HashMap<Name, NodeId>>,
+    /// The interfaces in the AST.
+    interfaces: RefCell<HashMap<Name, NodeId>>,
     /// The packages in the AST.
     packages: RefCell<HashMap<Name, NodeId>>,
+    /// The bind directives in the AST, by target module.
+    binds: RefCell<HashMap<Name, Vec<&'gcx ast::BindDirective>>>,
     /// A mapping from node ids to spans for diagnostics.
     node_id_to_span: RefCell<HashMap<NodeId, Span>>,
     /// The tables.
     tables: GlobalTables<'gcx>,
+    /// The random number generator used by `randomize()`.
+    pub(crate) randomizer: RefCell<crate::rand::Randomizer>,
 }
 
 impl<'gcx> GlobalContext<'gcx> {
@@ -72,9 +78,12 @@ impl<'gcx> GlobalContext<'gcx> {
             runtime: Default::default(),
             ast_map: Default::default(),
             modules: Default::default(),
+            interfaces: Default::default(),
             packages: Default::default(),
+            binds: Default::default(),
             node_id_to_span: Default::default(),
             tables: Default::default(),
+            randomizer: RefCell::new(crate::rand::Randomizer::new(sess.opts.seed)),
         }
     }
 
@@ -83,23 +92,93 @@ impl<'gcx> GlobalContext<'gcx> {
     /// Use the `find_global_item` function afterwards to look up the id of
     /// modules that were added.
     pub fn add_root_nodes(&self, ast: impl Iterator<Item = &'gcx ast::Root>) {
+        let mut binds = vec![];
         for root in ast {
+            // Items outside of any module, interface, or package form the
+            // `$unit` scope of the compilation unit. Modules and interfaces
+            // are nested within it such that they can see its names.
+            let has_unit_items = root.items.iter().any(|item| match *item {
+                ast::Item::Item(_) | ast::Item::Class(_) => true,
+                _ => false,
+            });
+            let unit = if has_unit_items {
+                Some(self.map_ast(AstNode::Unit(root)))
+            } else {
+                None
+            };
             for item in &root.items {
                 match *item {
                     ast::Item::Module(ref m) => {
                         let id = self.map_ast(AstNode::Module(m));
+                        if let Some(unit) = unit {
+                            self.set_parent(id, unit);
+                        }
                         self.modules.borrow_mut().insert(m.name, id);
                     }
+                    ast::Item::Interface(ref i) => {
+                        let id = self.map_ast(AstNode::Interface(i));
+                        if let Some(unit) = unit {
+                            self.set_parent(id, unit);
+                        }
+                        self.interfaces.borrow_mut().insert(i.name, id);
+                    }
                     ast::Item::Package(ref p) => {
                         let id = self.map_ast(AstNode::Package(p));
                         self.packages.borrow_mut().insert(p.name, id);
                     }
-                    _ => {
-                        let _: Result<()> = self.unimp(item);
+                    ast::Item::Bind(ref b) => match b.target_module() {
+                        Some(name) => {
+                            self.binds
+                                .borrow_mut()
+                                .entry(name)
+                                .or_insert_with(Vec::new)
+                                .push(b);
+                            binds.push((name, b));
+                        }
+                        // Instances of a module share its HIR and LLHD unit,
+                        // so they cannot differ in the instances bound into
+                        // them.
+                        None => {
+                            let mut d = DiagBuilder2::error(
+                                "bind directives targeting specific instances are not supported",
+                            )
+                            .span(b.human_span());
+                            if !b.target_insts.is_empty() {
+                                d = d.add_note(format!(
+                                    "remove the instance list to bind into all instances of `{}`",
+                                    b.target[0].name
+                                ));
+                            }
+                            self.emit(d);
+                        }
+                    },
+                    ast::Item::Item(_) | ast::Item::Class(_) => (),
+                    // Programs, checkers, and configurations are parsed, but
+                    // elaboration is limited to modules, interfaces, and
+                    // packages.
+                    ast::Item::Program(_) | ast::Item::Checker(_) | ast::Item::Config(_) => {
+                        self.emit(
+                            DiagBuilder2::error(format!("{} not supported", item.desc_full()))
+                                .span(item.human_span())
+                                .add_note(
+                                    "Programs, checkers, and configurations are only parsed. \
+                                     Only modules, interfaces, and packages can be elaborated.",
+                                ),
+                        );
                     }
                 }
             }
         }
+
+        // Make sure the bind directives refer to modules that exist.
+        for (name, bind) in binds {
+            if self.find_module(name).is_none() {
+                self.emit(
+                    DiagBuilder2::error(format!("bind target `{}` is not a module", name))
+                        .span(bind.human_span()),
+                );
+            }
+        }
     }
 
     /// Find a module in the AST.
@@ -112,6 +191,20 @@ impl<'gcx> GlobalContext<'gcx> {
         self.modules.borrow().clone().into_iter()
     }
 
+    /// Find an interface in the AST.
+    pub fn find_interface(&self, name: Name) -> Option<NodeId> {
+        self.interfaces.borrow().get(&name).cloned()
+    }
+
+    /// Find the bind directives which add instances to a module.
+    pub fn find_binds(&self, module: Name) -> Vec<&'gcx ast::BindDirective> {
+        self.binds
+            .borrow()
+            .get(&module)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// Find a package in the AST.
     pub fn find_package(&self, name: Name) -> Option<NodeId> {
         self.packages.borrow().get(&name).cloned()
@@ -209,6 +302,8 @@ pub struct GlobalTables<'t> {
     interned_types: RefCell<HashSet<Type<'t>>>,
     interned_values: RefCell<HashSet<Value<'t>>>,
     lowering_hints: RefCell<HashMap<NodeId, hir::Hint>>,
+    interned_hier_names: RefCell<HashMap<(Option<NodeId>, Vec<NodeId>, NodeId), Arc<HierName>>>,
+    hier_names: RefCell<HashMap<NodeId, Arc<HierName>>>,
 }
 
 /// The fundamental compiler context.
@@ -382,6 +477,20 @@ pub trait BaseContext<'gcx>: salsa::Database + DiagEmitter {
         self.intern_type(TypeKind::Int(width, ty::Domain::TwoValued))
     }
 
+    /// Make a 2-value unsigned bit vector type, e.g. `bit [31:0]`.
+    fn mkty_bit_vector(&self, width: usize) -> Type<'gcx> {
+        self.intern_type(TypeKind::BitVector {
+            domain: ty::Domain::TwoValued,
+            sign: ty::Sign::Unsigned,
+            range: ty::Range {
+                size: width,
+                dir: ty::RangeDir::Down,
+                offset: 0isize,
+            },
+            dubbed: false,
+        })
+    }
+
     /// Make a 4-value integer type.
     fn mkty_integer(&self, width: usize) -> Type<'gcx> {
         self.intern_type(TypeKind::Int(width, ty::Domain::FourValued))
@@ -397,6 +506,26 @@ pub trait BaseContext<'gcx>: salsa::Database + DiagEmitter {
         self.intern_type(TypeKind::PackedArray(size, elem_ty))
     }
 
+    /// Make an unpacked array type.
+    fn mkty_unpacked_array(&self, range: ty::Range, elem_ty: Type<'gcx>) -> Type<'gcx> {
+        self.intern_type(TypeKind::UnpackedArray(range, elem_ty))
+    }
+
+    /// Make a dynamic array type.
+    fn mkty_dynamic_array(&self, elem_ty: Type<'gcx>) -> Type<'gcx> {
+        self.intern_type(TypeKind::DynamicArray(elem_ty))
+    }
+
+    /// Make a queue type.
+    fn mkty_queue(&self, elem_ty: Type<'gcx>, max: Option<usize>) -> Type<'gcx> {
+        self.intern_type(TypeKind::Queue(elem_ty, max))
+    }
+
+    /// Make an associative array type.
+    fn mkty_assoc_array(&self, elem_ty: Type<'gcx>, index_ty: Option<Type<'gcx>>) -> Type<'gcx> {
+        self.intern_type(TypeKind::AssocArray(elem_ty, index_ty))
+    }
+
     /// Internalize a parameter environment.
     fn intern_param_env(&self, env: ParamEnvData<'gcx>) -> ParamEnv {
         if let Some(&x) = self.tables().interned_param_envs.borrow().get(&env) {
@@ -540,6 +669,45 @@ pub trait BaseContext<'gcx>: salsa::Database + DiagEmitter {
         self.tables().lowering_hints.borrow().get(&node_id).cloned()
     }
 
+    /// Intern a hierarchical name.
+    ///
+    /// Paths with the same root, instances, and target map to the same
+    /// [`HierName`] and thus the same node id. The `span` of the first use of
+    /// a path is associated with its node id.
+    fn intern_hier_name(
+        &self,
+        root: Option<NodeId>,
+        insts: Vec<NodeId>,
+        target: NodeId,
+        span: Span,
+    ) -> Arc<HierName> {
+        let key = (root, insts, target);
+        if let Some(x) = self.tables().interned_hier_names.borrow().get(&key) {
+            return x.clone();
+        }
+        let (root, insts, target) = key.clone();
+        let hier = Arc::new(HierName {
+            id: self.alloc_id(span),
+            root,
+            insts,
+            target,
+        });
+        self.tables()
+            .interned_hier_names
+            .borrow_mut()
+            .insert(key, hier.clone());
+        self.tables()
+            .hier_names
+            .borrow_mut()
+            .insert(hier.id, hier.clone());
+        hier
+    }
+
+    /// Get the [`HierName`] associated with a node id.
+    fn hier_name(&self, id: NodeId) -> Arc<HierName> {
+        self.tables().hier_names.borrow()[&id].clone()
+    }
+
     /// Compute the constant value of a node and make sure it is an integer.
     fn constant_int_value_of(&self, node_id: NodeId, env: ParamEnv) -> Result<&'gcx num::BigInt> {
         match self.gcx().constant_value_of(node_id, env)?.kind {
@@ -705,6 +873,49 @@ pub(super) mod queries {
                 use fn resolver::resolve_field_access;
             }
 
+            /// Resolve a field access expression to an interface signal.
+            ///
+            /// Returns `None` if the expression does not access a signal
+            /// through an interface instance or port.
+            fn resolve_intf_signal(node_id: NodeId) -> Result<Option<IntfSignal>> {
+                type ResolveIntfSignalQuery;
+                use fn resolver::resolve_intf_signal;
+            }
+
+            /// Resolve a field access expression to a hierarchical name.
+            ///
+            /// Returns `None` if the expression is not a hierarchical name.
+            fn resolve_hier_name(node_id: NodeId) -> Result<Option<Arc<HierName>>> {
+                type ResolveHierNameQuery;
+                use fn resolver::resolve_hier_name;
+            }
+
+            /// Resolve the callee and arguments of a function or task call.
+            fn resolve_call(node_id: NodeId) -> Result<Arc<CallMapping>> {
+                type ResolveCallQuery;
+                use fn resolver::resolve_call;
+            }
+
+            /// Resolve a field access expression to a property of a class
+            /// object.
+            ///
+            /// Returns the property and the parameter environment of the class
+            /// that declares it, or `None` if the expression does not access a
+            /// class object.
+            fn resolve_class_property(node_id: NodeId, env: ParamEnv) -> Result<Option<(NodeId, ParamEnv)>> {
+                type ResolveClassPropertyQuery;
+                use fn resolver::resolve_class_property;
+            }
+
+            /// Resolve a method call on a class object, or a `new` expression.
+            ///
+            /// Returns `None` for method calls on other things, such as
+            /// queues or strings.
+            fn resolve_class_call(node_id: NodeId, env: ParamEnv) -> Result<Option<Arc<ClassCall>>> {
+                type ResolveClassCallQuery;
+                use fn resolver::resolve_class_call;
+            }
+
             /// Lower an expression to an lvalue in the MIR.
             fn mir_lvalue(
                 expr_id: NodeId,
@@ -751,6 +962,11 @@ pub(super) mod queries {
                 fn port_mapping() for PortMappingQuery<'gcx>;
                 fn struct_def() for StructDefQuery<'gcx>;
                 fn resolve_field_access() for ResolveFieldAccessQuery<'gcx>;
+                fn resolve_intf_signal() for ResolveIntfSignalQuery<'gcx>;
+                fn resolve_hier_name() for ResolveHierNameQuery<'gcx>;
+                fn resolve_call() for ResolveCallQuery<'gcx>;
+                fn resolve_class_property() for ResolveClassPropertyQuery<'gcx>;
+                fn resolve_class_call() for ResolveClassCallQuery<'gcx>;
                 fn mir_lvalue() for MirLvalueQuery<'gcx>;
                 fn mir_rvalue() for MirRvalueQuery<'gcx>;
             }
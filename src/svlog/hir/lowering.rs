@@ -5,6 +5,7 @@
 use crate::{ast_map::AstNode, crate_prelude::*, hir::HirNode};
 use bit_vec::BitVec;
 use num::BigInt;
+use std::collections::HashMap;
 
 /// A hint about how a node should be lowered to HIR.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -21,6 +22,8 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
     #[allow(unreachable_patterns)]
     match ast {
         AstNode::Module(m) => lower_module(cx, node_id, m),
+        AstNode::Interface(i) => lower_interface(cx, node_id, i),
+        AstNode::Modport(modport, _) => lower_modport(cx, node_id, modport),
         AstNode::Port(p) => lower_port(cx, node_id, p),
         AstNode::Type(ty) => lower_type(cx, node_id, ty),
         AstNode::TypeOrExpr(&ast::TypeOrExpr::Type(ref ty)) => lower_type(cx, node_id, ty),
@@ -37,32 +40,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
         // }
         AstNode::Expr(expr) => lower_expr(cx, node_id, expr),
         AstNode::InstTarget(ast) => {
-            let mut named_params = vec![];
-            let mut pos_params = vec![];
-            let mut is_pos = true;
-            for param in &ast.params {
-                let value_id = cx.map_ast_with_parent(AstNode::TypeOrExpr(&param.expr), node_id);
-                if let Some(name) = param.name {
-                    is_pos = false;
-                    named_params.push((
-                        param.span,
-                        Spanned::new(name.name, name.span),
-                        Some(value_id),
-                    ));
-                } else {
-                    if !is_pos {
-                        cx.emit(
-                            DiagBuilder2::warning("positional parameters must appear before named")
-                                .span(param.span)
-                                .add_note(format!(
-                                    "assuming this refers to argument #{}",
-                                    pos_params.len() + 1
-                                )),
-                        );
-                    }
-                    pos_params.push((param.span, Some(value_id)));
-                }
-            }
+            let (pos_params, named_params) = lower_param_assignments(cx, node_id, &ast.params);
             let hir = hir::InstTarget {
                 id: node_id,
                 name: Spanned::new(ast.target.name, ast.target.span),
@@ -111,10 +89,15 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                     }
                 }
             }
+            let attrs = match cx.ast_of(target_id)? {
+                AstNode::InstTarget(target) => lower_attrs(cx, node_id, &target.attrs),
+                _ => unreachable!(),
+            };
             let hir = hir::Inst {
                 id: node_id,
                 name: Spanned::new(inst.name.name, inst.name.span),
                 span: inst.span,
+                attrs,
                 target: target_id,
                 named_ports,
                 pos_ports,
@@ -155,7 +138,10 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                 id: node_id,
                 name: Spanned::new(name.name, name.name_span),
                 span: Span::union(name.span, decl.span),
+                attrs: lower_attrs(cx, node_id, &decl.attrs),
+                kind: hir::VarKind::Var,
                 ty: ty,
+                dims: lower_unpacked_dims(cx, node_id, &name.dims)?,
                 init: name
                     .init
                     .as_ref()
@@ -164,12 +150,55 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             Ok(HirNode::VarDecl(cx.arena().alloc_hir(hir)))
         }
         AstNode::NetDecl(name, decl, ty) => {
-            // TODO(fschuiki): Map this to something different than a variable.
             let hir = hir::VarDecl {
                 id: node_id,
                 name: Spanned::new(name.name, name.name_span),
                 span: Span::union(name.span, decl.span),
+                attrs: lower_attrs(cx, node_id, &decl.attrs),
+                kind: hir::VarKind::Net {
+                    ty: decl.net_type,
+                    kind: decl.kind,
+                    strength: decl.strength,
+                },
+                ty: ty,
+                dims: lower_unpacked_dims(cx, node_id, &name.dims)?,
+                init: name
+                    .init
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+            };
+            Ok(HirNode::VarDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::PortDecl(name, decl, ty) => {
+            let hir = hir::VarDecl {
+                id: node_id,
+                name: Spanned::new(name.name, name.name_span),
+                span: Span::union(name.span, decl.span),
+                attrs: vec![],
+                // Ports default to nets, except for outputs with an explicit
+                // data type. See IEEE 1800-2017 §23.2.2.3. This matters for
+                // `inout` ports, which must connect to a net, and for ports
+                // driven by more than one continuous assignment.
+                kind: match decl.net_type {
+                    Some(ty) => hir::VarKind::Net {
+                        ty,
+                        kind: ast::NetKind::None,
+                        strength: None,
+                    },
+                    None if !decl.var
+                        && (decl.dir != ast::PortDir::Output
+                            || decl.ty.data == ast::ImplicitType) =>
+                    {
+                        hir::VarKind::Net {
+                            ty: ast::NetType::Wire,
+                            kind: ast::NetKind::None,
+                            strength: None,
+                        }
+                    }
+                    None => hir::VarKind::Var,
+                },
                 ty: ty,
+                dims: lower_unpacked_dims(cx, node_id, &name.dims)?,
                 init: name
                     .init
                     .as_ref()
@@ -181,6 +210,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             let hir = hir::Proc {
                 id: node_id,
                 span: prok.span,
+                attrs: lower_attrs(cx, node_id, &prok.attrs),
                 kind: prok.kind,
                 stmt: cx.map_ast_with_parent(AstNode::Stmt(&prok.stmt), node_id),
             };
@@ -340,9 +370,97 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                         kind,
                     }
                 }
-                ast::AssertionStmt { .. } => {
-                    warn!("ignoring unsupported assertion `{}`", stmt.span.extract());
-                    hir::StmtKind::Null
+                ast::CaseStmt {
+                    ref expr,
+                    mode: ast::CaseMode::Pattern,
+                    ref items,
+                    ..
+                } => {
+                    let expr = cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+                    let mut ways = vec![];
+                    let mut default = None;
+                    for item in items {
+                        match *item {
+                            ast::CaseItem::Default(ref stmt) => {
+                                if default.is_none() {
+                                    default =
+                                        Some(cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id));
+                                } else {
+                                    cx.emit(
+                                        DiagBuilder2::error("multiple default cases")
+                                            .span(stmt.human_span()),
+                                    );
+                                }
+                            }
+                            ast::CaseItem::Expr(ref exprs, ref stmt) => {
+                                if exprs.len() != 1 {
+                                    cx.emit(
+                                        DiagBuilder2::error(
+                                            "each item of a `case matches` statement must have \
+                                             exactly one pattern",
+                                        )
+                                        .span(stmt.human_span()),
+                                    );
+                                    continue;
+                                }
+                                let pattern =
+                                    cx.map_ast_with_parent(AstNode::Expr(&exprs[0]), node_id);
+                                // Nest the statement within the pattern variable
+                                // such that it can refer to it by name.
+                                let scope = match cx.hir_of(pattern)? {
+                                    HirNode::Expr(hir::Expr {
+                                        kind: hir::ExprKind::Tagged(_, Some(inner)),
+                                        ..
+                                    }) => *inner,
+                                    _ => pattern,
+                                };
+                                ways.push((
+                                    pattern,
+                                    cx.map_ast_with_parent(AstNode::Stmt(stmt), scope),
+                                ));
+                            }
+                        }
+                    }
+                    hir::StmtKind::CaseMatches {
+                        expr,
+                        ways,
+                        default,
+                    }
+                }
+                ast::AssertionStmt(ref assertion) => hir::StmtKind::Assertion(
+                    cx.map_ast_with_parent(AstNode::Assertion(assertion), node_id),
+                ),
+                ast::ReturnStmt(ref expr) => hir::StmtKind::Return(
+                    expr.as_ref()
+                        .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                ),
+                ast::ParallelBlock(ref stmts, join) => {
+                    // Declarations are visible to the subsequent statements,
+                    // but the branches are independent of each other.
+                    let mut next_rib = node_id;
+                    hir::StmtKind::Fork {
+                        stmts: stmts
+                            .iter()
+                            .map(|stmt| {
+                                let id = cx.map_ast_with_parent(AstNode::Stmt(stmt), next_rib);
+                                if let ast::VarDeclStmt(_) = stmt.data {
+                                    next_rib = id;
+                                }
+                                id
+                            })
+                            .collect(),
+                        join,
+                    }
+                }
+                ast::WaitExprStmt(ref cond, ref inner_stmt) => hir::StmtKind::Wait {
+                    cond: cx.map_ast_with_parent(AstNode::Expr(cond), node_id),
+                    stmt: cx.map_ast_with_parent(AstNode::Stmt(inner_stmt), node_id),
+                },
+                ast::WaitForkStmt => hir::StmtKind::WaitFork,
+                ast::DisableForkStmt => hir::StmtKind::DisableFork,
+                ast::DisableStmt(name) => hir::StmtKind::Disable(Spanned::new(name, stmt.span)),
+                ast::EventTriggerStmt(ref expr) => {
+                    hir::StmtKind::Trigger(cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
                 }
                 _ => {
                     error!("{:#?}", stmt);
@@ -353,6 +471,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                 id: node_id,
                 label: stmt.label.map(|n| Spanned::new(n, stmt.span)), // this is horrible...
                 span: stmt.span,
+                attrs: lower_attrs(cx, node_id, &stmt.attrs),
                 kind: kind,
             };
             Ok(HirNode::Stmt(cx.arena().alloc_hir(hir)))
@@ -367,13 +486,14 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::EventExpr(cx.arena().alloc_hir(hir)))
         }
-        AstNode::GenIf(gen) => {
+        AstNode::GenIf(gen, index) => {
             let cond = cx.map_ast_with_parent(AstNode::Expr(&gen.cond), node_id);
-            let main_body = lower_module_block(cx, node_id, &gen.main_block.items)?;
-            let else_body = match gen.else_block {
-                Some(ref else_block) => Some(lower_module_block(cx, node_id, &else_block.items)?),
-                None => None,
-            };
+            let main_body =
+                cx.map_ast_with_parent(AstNode::GenBlock(&gen.main_block, index), node_id);
+            let else_body = gen
+                .else_block
+                .as_ref()
+                .map(|block| cx.map_ast_with_parent(AstNode::GenBlock(block, index), node_id));
             let hir = hir::Gen {
                 id: node_id,
                 span: gen.span(),
@@ -385,12 +505,12 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::Gen(cx.arena().alloc_hir(hir)))
         }
-        AstNode::GenFor(gen) => {
+        AstNode::GenFor(gen, index) => {
             let init = alloc_genvar_init(cx, &gen.init, node_id)?;
             let rib = *init.last().unwrap();
             let cond = cx.map_ast_with_parent(AstNode::Expr(&gen.cond), rib);
             let step = cx.map_ast_with_parent(AstNode::Expr(&gen.step), rib);
-            let body = lower_module_block(cx, rib, &gen.block.items)?;
+            let body = cx.map_ast_with_parent(AstNode::GenBlock(&gen.block, index), rib);
             let hir = hir::Gen {
                 id: node_id,
                 span: gen.span(),
@@ -403,6 +523,104 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::Gen(cx.arena().alloc_hir(hir)))
         }
+        AstNode::GenCase(gen, index) => {
+            let expr = cx.map_ast_with_parent(AstNode::Expr(&gen.expr), node_id);
+            let mut ways = vec![];
+            let mut default = None;
+            for item in &gen.items {
+                match *item {
+                    ast::GenerateCaseItem::Expr(ref exprs, ref block) => {
+                        let exprs = exprs
+                            .iter()
+                            .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
+                            .collect();
+                        let body = cx.map_ast_with_parent(AstNode::GenBlock(block, index), node_id);
+                        ways.push((exprs, body));
+                    }
+                    ast::GenerateCaseItem::Default(ref block) => {
+                        if default.is_some() {
+                            cx.emit(
+                                DiagBuilder2::error(
+                                    "case-generate statement has multiple default items",
+                                )
+                                .span(block.span()),
+                            );
+                            return Err(());
+                        }
+                        default =
+                            Some(cx.map_ast_with_parent(AstNode::GenBlock(block, index), node_id));
+                    }
+                }
+            }
+            let hir = hir::Gen {
+                id: node_id,
+                span: gen.span(),
+                kind: hir::GenKind::Case {
+                    expr,
+                    ways,
+                    default,
+                },
+            };
+            Ok(HirNode::Gen(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::GenBlock(block, index) => {
+            // A block that consists of nothing but another conditional
+            // generate statement does not form a scope of its own. The nested
+            // statement inherits the index such that its blocks are named as
+            // if they were part of the outer statement.
+            let nested = match block.items.as_slice() {
+                [ast::HierarchyItem::GenerateIf(ref gen)] if block.label.is_none() => {
+                    Some(AstNode::GenIf(gen, index))
+                }
+                [ast::HierarchyItem::GenerateCase(ref gen)] if block.label.is_none() => {
+                    Some(AstNode::GenCase(gen, index))
+                }
+                _ => None,
+            };
+            let (name, block_hir) = match nested {
+                Some(nested) => {
+                    let id = cx.map_ast_with_parent(nested, node_id);
+                    let block_hir = hir::ModuleBlock {
+                        insts: vec![],
+                        decls: vec![],
+                        procs: vec![],
+                        gens: vec![id],
+                        params: vec![],
+                        assigns: vec![],
+                        defparams: vec![],
+                        assertions: vec![],
+                        subroutines: vec![],
+                        classes: vec![],
+                        last_rib: id,
+                    };
+                    (None, block_hir)
+                }
+                None => {
+                    let name = match block.label {
+                        Some(label) => label,
+                        None => Name::from(format!("genblk{}", index).as_str()),
+                    };
+                    let block_hir = lower_module_block(cx, node_id, &block.items, None, false)?;
+                    if let Some(&id) = block_hir.defparams.first() {
+                        cx.emit(
+                            DiagBuilder2::error(
+                                "defparam statements within generate blocks are not supported",
+                            )
+                            .span(cx.span(id)),
+                        );
+                        return Err(());
+                    }
+                    (Some(Spanned::new(name, block.span())), block_hir)
+                }
+            };
+            let hir = hir::GenBlock {
+                id: node_id,
+                span: block.span(),
+                name,
+                block: block_hir,
+            };
+            Ok(HirNode::GenBlock(cx.arena().alloc_hir(hir)))
+        }
         AstNode::GenvarDecl(decl) => {
             let hir = hir::GenvarDecl {
                 id: node_id,
@@ -427,21 +645,35 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::Typedef(cx.arena().alloc_hir(hir)))
         }
-        AstNode::ContAssign(_, lhs, rhs) => {
+        AstNode::ContAssign(assign, lhs, rhs) => {
             let hir = hir::Assign {
                 id: node_id,
                 span: Span::union(lhs.span(), rhs.span()),
+                attrs: lower_attrs(cx, node_id, &assign.attrs),
+                strength: assign.strength,
                 lhs: cx.map_ast_with_parent(AstNode::Expr(lhs), node_id),
                 rhs: cx.map_ast_with_parent(AstNode::Expr(rhs), node_id),
             };
             Ok(HirNode::Assign(cx.arena().alloc_hir(hir)))
         }
+        AstNode::Defparam(_, target, value) => {
+            let hir = hir::Defparam {
+                id: node_id,
+                span: Span::union(target.span(), value.span()),
+                target: cx.map_ast_with_parent(AstNode::Expr(target), node_id),
+                value: cx.map_ast_with_parent(AstNode::Expr(value), node_id),
+            };
+            Ok(HirNode::Defparam(cx.arena().alloc_hir(hir)))
+        }
         AstNode::StructMember(name, decl, ty) => {
             let hir = hir::VarDecl {
                 id: node_id,
                 name: Spanned::new(name.name, name.name_span),
                 span: Span::union(name.span, decl.span),
+                attrs: vec![],
+                kind: hir::VarKind::Var,
                 ty: ty,
+                dims: lower_unpacked_dims(cx, node_id, &name.dims)?,
                 init: name
                     .init
                     .as_ref()
@@ -450,6 +682,7 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             Ok(HirNode::VarDecl(cx.arena().alloc_hir(hir)))
         }
         AstNode::Package(p) => lower_package(cx, node_id, p),
+        AstNode::Unit(u) => lower_unit(cx, node_id, u),
         AstNode::EnumVariant(var, decl, index) => {
             let hir = hir::EnumVariant {
                 id: node_id,
@@ -464,6 +697,97 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::EnumVariant(cx.arena().alloc_hir(hir)))
         }
+        AstNode::Assertion(assertion) => lower_assertion(cx, node_id, assertion),
+        AstNode::SeqDecl(decl) => {
+            let (ports, rib) = alloc_seq_ports(cx, &decl.ports, node_id);
+            let hir = hir::SeqDecl {
+                id: node_id,
+                name: Spanned::new(decl.name.name, decl.name.span),
+                span: decl.span,
+                ports,
+                seq: lower_seq_expr(cx, &decl.seq, rib),
+            };
+            Ok(HirNode::SeqDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::PropDecl(decl) => {
+            let (ports, rib) = alloc_seq_ports(cx, &decl.ports, node_id);
+            let hir = hir::PropDecl {
+                id: node_id,
+                name: Spanned::new(decl.name.name, decl.name.span),
+                span: decl.span,
+                ports,
+                prop: lower_prop_spec(cx, &decl.prop, rib),
+            };
+            Ok(HirNode::PropDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::SeqPort(port) => {
+            let parent = cx.parent_node_id(node_id).unwrap();
+            let hir = hir::SeqPort {
+                id: node_id,
+                name: Spanned::new(port.name.name, port.name.span),
+                span: port.span,
+                ty: match port.ty {
+                    ast::SeqPortType::Implicit => hir::SeqPortType::Implicit,
+                    ast::SeqPortType::Untyped => hir::SeqPortType::Untyped,
+                    ast::SeqPortType::Sequence => hir::SeqPortType::Sequence,
+                    ast::SeqPortType::Property => hir::SeqPortType::Property,
+                    ast::SeqPortType::Data(ref ty) => {
+                        hir::SeqPortType::Data(cx.map_ast_with_parent(AstNode::Type(ty), parent))
+                    }
+                },
+                default: port
+                    .default
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
+            };
+            Ok(HirNode::SeqPort(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::Subroutine(decl) => {
+            lower_subroutine(cx, node_id, decl.span, &decl.prototype, &decl.items)
+        }
+        AstNode::SubroutineProto(proto) => lower_subroutine(cx, node_id, proto.span, proto, &[]),
+        AstNode::Class(decl) => lower_class(cx, node_id, decl),
+        AstNode::Constraint(decl) => lower_constraint(cx, node_id, decl),
+        AstNode::ForeachIndex(ident) => {
+            let hir = hir::GenvarDecl {
+                id: node_id,
+                span: ident.span,
+                name: Spanned::new(ident.name, ident.span),
+                init: None,
+            };
+            Ok(HirNode::GenvarDecl(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::SubroutinePort(port, dir, ty) => {
+            let parent = cx.parent_node_id(node_id).unwrap();
+            let name = port.name.as_ref().unwrap();
+            let hir = hir::SubroutinePort {
+                id: node_id,
+                name: Spanned::new(name.name.name, name.name.span),
+                span: port.span,
+                dir,
+                ty,
+                default: name
+                    .expr
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
+            };
+            Ok(HirNode::SubroutinePort(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::SubroutinePortDecl(name, decl, ty) => {
+            let parent = cx.parent_node_id(node_id).unwrap();
+            let hir = hir::SubroutinePort {
+                id: node_id,
+                name: Spanned::new(name.name, name.name_span),
+                span: Span::union(name.span, decl.span),
+                dir: decl.dir,
+                ty,
+                default: name
+                    .init
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
+            };
+            Ok(HirNode::SubroutinePort(cx.arena().alloc_hir(hir)))
+        }
         AstNode::Import(import) => unreachable!("import should never be lowered: {:#?}", import),
         _ => {
             error!("{:#?}", ast);
@@ -491,25 +815,24 @@ fn lower_module<'gcx>(
 
     // Allocate ports.
     let mut ports = Vec::new();
-    for port in &ast.ports {
-        match *port {
-            ast::Port::Named { .. } => {
-                let id = cx.map_ast(AstNode::Port(port));
-                cx.set_parent(id, next_rib);
-                next_rib = id;
-                ports.push(id);
-            }
-            _ => return cx.unimp(port),
-        }
-    }
+    let (rib, nonansi) = alloc_ports(cx, &ast.ports, next_rib, &mut ports)?;
+    next_rib = rib;
 
-    // Allocate items.
-    let block = lower_module_block(cx, next_rib, &ast.items)?;
+    // Allocate items, including the instances bound into this module.
+    let binds = cx.gcx().find_binds(ast.name);
+    let block = lower_module_block(
+        cx,
+        next_rib,
+        &ast.items,
+        binds.into_iter().map(|bind| &bind.inst),
+        nonansi,
+    )?;
 
     let hir = hir::Module {
         id: node_id,
         name: Spanned::new(ast.name, ast.name_span),
         span: ast.span,
+        attrs: lower_attrs(cx, node_id, &ast.attrs),
         ports: cx.arena().alloc_ids(ports),
         params: cx.arena().alloc_ids(params),
         block,
@@ -517,11 +840,247 @@ fn lower_module<'gcx>(
     Ok(HirNode::Module(cx.arena().alloc_hir(hir)))
 }
 
+/// Lower an interface to HIR.
+///
+/// This allocates node IDs to everything in the interface and registers AST
+/// nodes for each ID. The modports are allocated last, such that they can
+/// refer to all signals declared in the interface.
+fn lower_interface<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::IntfDecl,
+) -> Result<HirNode<'gcx>> {
+    let mut next_rib = node_id;
+
+    // Allocate parameters.
+    let mut params = Vec::new();
+    for param in &ast.params {
+        next_rib = alloc_param_decl(cx, param, next_rib, &mut params);
+    }
+
+    // Allocate ports.
+    let mut ports = Vec::new();
+    let (rib, nonansi) = alloc_ports(cx, &ast.ports, next_rib, &mut ports)?;
+    next_rib = rib;
+    if nonansi {
+        cx.emit(
+            DiagBuilder2::error("non-ANSI port lists are not supported for interfaces")
+                .span(ast.ports[0].span())
+                .add_note("declare the ports with a direction or type in the port list"),
+        );
+        return Err(());
+    }
+
+    // Allocate items.
+    let block = lower_module_block(
+        cx,
+        next_rib,
+        ast.items.iter().filter(|item| match item {
+            ast::HierarchyItem::ModportDecl(..) => false,
+            _ => true,
+        }),
+        std::iter::empty(),
+        false,
+    )?;
+    next_rib = block.last_rib;
+
+    // Allocate modports.
+    let mut modports = Vec::new();
+    for item in &ast.items {
+        if let ast::HierarchyItem::ModportDecl(ref decl) = *item {
+            for modport in &decl.items {
+                next_rib = cx.map_ast_with_parent(AstNode::Modport(modport, node_id), next_rib);
+                modports.push(next_rib);
+            }
+        }
+    }
+
+    let hir = hir::Interface {
+        id: node_id,
+        name: Spanned::new(ast.name, ast.name_span),
+        span: ast.span,
+        attrs: lower_attrs(cx, node_id, &ast.attrs),
+        ports: cx.arena().alloc_ids(ports),
+        params: cx.arena().alloc_ids(params),
+        block,
+        modports,
+        last_rib: next_rib,
+    };
+    Ok(HirNode::Interface(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower a modport to HIR.
+fn lower_modport<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::ModportItem,
+) -> Result<HirNode<'gcx>> {
+    let mut ports = Vec::new();
+    for port in &ast.ports {
+        match *port {
+            ast::ModportPort::Simple { dir, ref port, .. } => {
+                for port in port {
+                    if port.expr.is_some() {
+                        cx.emit(
+                            DiagBuilder2::error("modport expressions not supported")
+                                .span(port.span),
+                        );
+                        return Err(());
+                    }
+                    ports.push(hir::ModportPort {
+                        name: Spanned::new(port.name.name, port.name.span),
+                        span: port.span,
+                        dir,
+                    });
+                }
+            }
+        }
+    }
+    let hir = hir::Modport {
+        id: node_id,
+        name: Spanned::new(ast.name.name, ast.name.span),
+        span: ast.span,
+        ports,
+    };
+    Ok(HirNode::Modport(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower the attribute instances attached to a node.
+///
+/// The attribute values are allocated as expressions with `node_id` as their
+/// parent.
+fn lower_attrs<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    attrs: &'gcx [ast::AttrInst],
+) -> Vec<hir::Attr> {
+    attrs
+        .iter()
+        .flat_map(|inst| inst.specs.iter())
+        .map(|spec| hir::Attr {
+            name: Spanned::new(spec.name.name, spec.name.span),
+            span: spec.span,
+            value: spec
+                .value
+                .as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        })
+        .collect()
+}
+
+/// Lower the arguments of a function, task, or method call.
+/// Lower the parameter assignments of an instantiation or specialized class
+/// type, such as `#(8, .T(bit))`.
+fn lower_param_assignments<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    params: &'gcx [ast::ParamAssignment],
+) -> (Vec<hir::PosParam>, Vec<hir::NamedParam>) {
+    let mut named_params = vec![];
+    let mut pos_params = vec![];
+    let mut is_pos = true;
+    for param in params {
+        let value_id = cx.map_ast_with_parent(AstNode::TypeOrExpr(&param.expr), node_id);
+        if let Some(name) = param.name {
+            is_pos = false;
+            named_params.push((
+                param.span,
+                Spanned::new(name.name, name.span),
+                Some(value_id),
+            ));
+        } else {
+            if !is_pos {
+                cx.emit(
+                    DiagBuilder2::warning("positional parameters must appear before named")
+                        .span(param.span)
+                        .add_note(format!(
+                            "assuming this refers to argument #{}",
+                            pos_params.len() + 1
+                        )),
+                );
+            }
+            pos_params.push((param.span, Some(value_id)));
+        }
+    }
+    (pos_params, named_params)
+}
+
+fn lower_call_args<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    args: &'gcx [ast::CallArg],
+) -> Vec<hir::CallArg> {
+    args.iter()
+        .map(|arg| hir::CallArg {
+            span: arg.span,
+            name: arg.name.map(|name| Spanned::new(name, arg.name_span)),
+            expr: arg
+                .expr
+                .as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        })
+        .collect()
+}
+
+/// Lower the unpacked dimensions of a declaration.
+///
+/// The dimension expressions are allocated with `node_id` as their parent.
+fn lower_unpacked_dims<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    dims: &'gcx [ast::TypeDim],
+) -> Result<Vec<hir::UnpackedDim>> {
+    dims.iter()
+        .map(|dim| match *dim {
+            ast::TypeDim::Expr(ref size) => Ok(hir::UnpackedDim::Size(
+                cx.map_ast_with_parent(AstNode::Expr(size), node_id),
+            )),
+            ast::TypeDim::Range(ref lhs, ref rhs) => Ok(hir::UnpackedDim::Range(
+                cx.map_ast_with_parent(AstNode::Expr(lhs), node_id),
+                cx.map_ast_with_parent(AstNode::Expr(rhs), node_id),
+            )),
+            ast::TypeDim::Unsized => Ok(hir::UnpackedDim::Unsized),
+            ast::TypeDim::Queue(ref max) => {
+                Ok(hir::UnpackedDim::Queue(max.as_ref().map(|max| {
+                    cx.map_ast_with_parent(AstNode::Expr(max), node_id)
+                })))
+            }
+            ast::TypeDim::Associative(ref index) => {
+                Ok(hir::UnpackedDim::Associative(index.as_ref().map(|index| {
+                    cx.map_ast_with_parent(AstNode::Type(index), node_id)
+                })))
+            }
+        })
+        .collect()
+}
+
+/// Lower the items of a module, interface, or generate block.
+///
+/// Port declarations are only accepted if `port_decls` is set, which is the
+/// case for the body of a module with a non-ANSI port list.
 fn lower_module_block<'gcx>(
     cx: &impl Context<'gcx>,
     parent_rib: NodeId,
     items: impl IntoIterator<Item = &'gcx ast::HierarchyItem>,
+    binds: impl IntoIterator<Item = &'gcx ast::Inst>,
+    port_decls: bool,
 ) -> Result<hir::ModuleBlock> {
+    let items: Vec<_> = items.into_iter().collect();
+
+    // Variable and net declarations provide the type of a port with the same
+    // name, in which case the port declaration only adds the direction.
+    let mut declared = HashMap::new();
+    for item in &items {
+        let names = match **item {
+            ast::HierarchyItem::VarDecl(ref decl) => &decl.names,
+            ast::HierarchyItem::NetDecl(ref decl) => &decl.names,
+            _ => continue,
+        };
+        for name in names {
+            declared.insert(name.name, name.name_span);
+        }
+    }
+
     let mut next_rib = parent_rib;
     let mut insts = Vec::new();
     let mut decls = Vec::new();
@@ -529,22 +1088,14 @@ fn lower_module_block<'gcx>(
     let mut gens = Vec::new();
     let mut params = Vec::new();
     let mut assigns = Vec::new();
+    let mut defparams = Vec::new();
+    let mut assertions = Vec::new();
+    let mut subroutines = Vec::new();
+    let mut classes = Vec::new();
     for item in items {
         match *item {
             ast::HierarchyItem::Inst(ref inst) => {
-                let target_id = cx.map_ast_with_parent(AstNode::InstTarget(inst), next_rib);
-                next_rib = target_id;
-                trace!(
-                    "instantiation target `{}` => {:?}",
-                    inst.target.name,
-                    target_id
-                );
-                for inst in &inst.names {
-                    let inst_id = cx.map_ast_with_parent(AstNode::Inst(inst, target_id), next_rib);
-                    trace!("instantiation `{}` => {:?}", inst.name.name, inst_id);
-                    next_rib = inst_id;
-                    insts.push(inst_id);
-                }
+                next_rib = alloc_inst(cx, inst, next_rib, &mut insts);
             }
             ast::HierarchyItem::VarDecl(ref decl) => {
                 next_rib = alloc_var_decl(cx, decl, next_rib, &mut decls);
@@ -558,12 +1109,17 @@ fn lower_module_block<'gcx>(
                 procs.push(id);
             }
             ast::HierarchyItem::GenerateIf(ref gen) => {
-                let id = cx.map_ast_with_parent(AstNode::GenIf(gen), next_rib);
+                let id = cx.map_ast_with_parent(AstNode::GenIf(gen, gens.len() + 1), next_rib);
                 next_rib = id;
                 gens.push(id);
             }
             ast::HierarchyItem::GenerateFor(ref gen) => {
-                let id = cx.map_ast_with_parent(AstNode::GenFor(gen), next_rib);
+                let id = cx.map_ast_with_parent(AstNode::GenFor(gen, gens.len() + 1), next_rib);
+                next_rib = id;
+                gens.push(id);
+            }
+            ast::HierarchyItem::GenerateCase(ref gen) => {
+                let id = cx.map_ast_with_parent(AstNode::GenCase(gen, gens.len() + 1), next_rib);
                 next_rib = id;
                 gens.push(id);
             }
@@ -582,26 +1138,721 @@ fn lower_module_block<'gcx>(
                     assigns.push(id);
                 }
             }
+            ast::HierarchyItem::Defparam(ref decl) => {
+                for &(ref target, ref value) in &decl.assignments {
+                    defparams.push(
+                        cx.map_ast_with_parent(AstNode::Defparam(decl, target, value), next_rib),
+                    );
+                }
+            }
             ast::HierarchyItem::ImportDecl(ref decl) => {
                 for item in &decl.items {
                     let id = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
                     next_rib = id;
                 }
             }
-            // _ => return cx.unimp_msg("lowering of", item),
-            _ => warn!("skipping unsupported {:?}", item),
-        }
-    }
-    Ok(hir::ModuleBlock {
-        insts,
-        decls,
-        procs,
-        gens,
-        params,
-        assigns,
+            ast::HierarchyItem::ExportDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::error("export declarations may only appear in packages")
+                        .span(decl.span),
+                );
+            }
+            ast::HierarchyItem::Assertion(ref assertion) => {
+                assertions.push(cx.map_ast_with_parent(AstNode::Assertion(assertion), next_rib));
+            }
+            ast::HierarchyItem::SeqDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::SeqDecl(decl), next_rib);
+                next_rib = id;
+            }
+            ast::HierarchyItem::PropDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::PropDecl(decl), next_rib);
+                next_rib = id;
+            }
+            ast::HierarchyItem::SubroutineDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::Subroutine(decl), next_rib);
+                next_rib = id;
+                subroutines.push(id);
+            }
+            ast::HierarchyItem::ClassDecl(ref decl) => {
+                classes.push(cx.map_ast_with_parent(AstNode::Class(decl), next_rib));
+            }
+            ast::HierarchyItem::PortDecl(ref decl) => {
+                if !port_decls {
+                    cx.emit(
+                        DiagBuilder2::error(
+                            "port declarations are only allowed if a non-ANSI port list is used",
+                        )
+                        .span(decl.span),
+                    );
+                    return Err(());
+                }
+                next_rib = alloc_port_decl(cx, decl, &declared, next_rib, &mut decls)?;
+            }
+            // _ => return cx.unimp_msg("lowering of", item),
+            _ => warn!("skipping unsupported {:?}", item),
+        }
+    }
+    // Instances added by bind directives come last, such that their port
+    // connections see all declarations in the block.
+    for inst in binds {
+        next_rib = alloc_inst(cx, inst, next_rib, &mut insts);
+    }
+    Ok(hir::ModuleBlock {
+        insts,
+        decls,
+        procs,
+        gens,
+        params,
+        assigns,
+        defparams,
+        assertions,
+        subroutines,
+        classes,
+        last_rib: next_rib,
     })
 }
 
+/// Lower a function or task to HIR.
+///
+/// The arguments and the statements in the body are chained up as ribs, such
+/// that each statement sees the arguments and the declarations preceding it.
+fn lower_subroutine<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    span: Span,
+    proto: &'gcx ast::SubroutinePrototype,
+    items: &'gcx [ast::SubroutineItem],
+) -> Result<HirNode<'gcx>> {
+    let retty = proto
+        .retty
+        .as_ref()
+        .map(|ty| cx.map_ast_with_parent(AstNode::Type(ty), node_id));
+    let mut next_rib = node_id;
+    let mut ports = Vec::new();
+    let mut stmts = Vec::new();
+
+    // Arguments without a direction and data type inherit them from the
+    // preceding argument. The first argument defaults to `input logic`.
+    let mut dir = ast::SubroutinePortDir::Input;
+    let mut ty = None;
+    for port in &proto.args {
+        if port.name.is_none() {
+            cx.emit(DiagBuilder2::error("argument has no name").span(port.span));
+            return Err(());
+        }
+        let inherit = port.dir.is_none()
+            && port.ty.data == ast::ImplicitType
+            && port.ty.sign == ast::TypeSign::None
+            && port.ty.dims.is_empty();
+        let port_ty = match ty {
+            Some(ty) if inherit => ty,
+            _ => cx.map_ast_with_parent(AstNode::Type(&port.ty), next_rib),
+        };
+        ty = Some(port_ty);
+        dir = port.dir.unwrap_or(dir);
+        next_rib = cx.map_ast_with_parent(AstNode::SubroutinePort(port, dir, port_ty), next_rib);
+        ports.push(next_rib);
+    }
+
+    // Lower the body, which may contain additional argument declarations.
+    for item in items {
+        match *item {
+            ast::SubroutineItem::PortDecl(ref decl) => {
+                let ty = cx.map_ast_with_parent(AstNode::Type(&decl.ty), next_rib);
+                for name in &decl.names {
+                    next_rib = cx
+                        .map_ast_with_parent(AstNode::SubroutinePortDecl(name, decl, ty), next_rib);
+                    ports.push(next_rib);
+                }
+            }
+            ast::SubroutineItem::Stmt(ref stmt) => {
+                next_rib = cx.map_ast_with_parent(AstNode::Stmt(stmt), next_rib);
+                stmts.push(next_rib);
+            }
+        }
+    }
+
+    let hir = hir::Subroutine {
+        id: node_id,
+        name: Spanned::new(proto.name.name, proto.name.span),
+        span,
+        kind: proto.kind,
+        lifetime: proto.lifetime.clone(),
+        retty,
+        ports,
+        stmts,
+    };
+    Ok(HirNode::Subroutine(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower a class to HIR.
+///
+/// The parameters are chained up as ribs below the class, followed by the
+/// base class type, such that the latter cannot see the members of the class.
+/// The properties and methods are allocated directly below the class, which
+/// makes all members visible to each other regardless of their order.
+fn lower_class<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::ClassDecl,
+) -> Result<HirNode<'gcx>> {
+    let mut next_rib = node_id;
+    let mut params = Vec::new();
+    for param in &ast.params {
+        next_rib = alloc_param_decl(cx, param, next_rib, &mut params);
+    }
+    if params.is_empty() {
+        next_rib = cx.parent_node_id(node_id).unwrap();
+    }
+    let extends = ast.extends.as_ref().map(|(ty, args)| {
+        let ty_id = cx.map_ast_with_parent(AstNode::Type(ty), next_rib);
+        (ty_id, lower_call_args(cx, node_id, args))
+    });
+
+    let mut members = Vec::new();
+    let mut constraints = Vec::new();
+    for item in &ast.items {
+        let mut is_static = false;
+        let mut virt = false;
+        let mut visibility = hir::ClassVisibility::Public;
+        let mut rand = None;
+        for &(qualifier, _) in &item.qualifiers {
+            match qualifier {
+                ast::ClassItemQualifier::Static => is_static = true,
+                ast::ClassItemQualifier::Virtual => virt = true,
+                ast::ClassItemQualifier::Protected => visibility = hir::ClassVisibility::Protected,
+                ast::ClassItemQualifier::Local => visibility = hir::ClassVisibility::Local,
+                ast::ClassItemQualifier::Rand => rand = Some(ast::RandomQualifier::Rand),
+                ast::ClassItemQualifier::Randc => rand = Some(ast::RandomQualifier::Randc),
+                ast::ClassItemQualifier::Pure | ast::ClassItemQualifier::Const => (),
+            }
+        }
+        let member = |id, name, kind| hir::ClassMember {
+            id,
+            name,
+            kind,
+            is_static,
+            virt,
+            visibility,
+            rand,
+        };
+        match item.data {
+            ast::ClassItemData::Property(ref decl) => {
+                let ty = cx.map_ast_with_parent(AstNode::Type(&decl.ty), node_id);
+                for name in &decl.names {
+                    let id = cx.map_ast_with_parent(AstNode::VarDecl(name, decl, ty), node_id);
+                    members.push(member(
+                        id,
+                        Spanned::new(name.name, name.name_span),
+                        hir::ClassMemberKind::Property,
+                    ));
+                }
+            }
+            ast::ClassItemData::SubroutineDecl(ref decl) => {
+                let id = cx.map_ast_with_parent(AstNode::Subroutine(decl), node_id);
+                let name = &decl.prototype.name;
+                members.push(member(
+                    id,
+                    Spanned::new(name.name, name.span),
+                    hir::ClassMemberKind::Method,
+                ));
+            }
+            ast::ClassItemData::ExternSubroutine(ref proto)
+                if item
+                    .qualifiers
+                    .iter()
+                    .any(|&(q, _)| q == ast::ClassItemQualifier::Pure) =>
+            {
+                let id = cx.map_ast_with_parent(AstNode::SubroutineProto(proto), node_id);
+                members.push(member(
+                    id,
+                    Spanned::new(proto.name.name, proto.name.span),
+                    hir::ClassMemberKind::PureMethod,
+                ));
+            }
+            ast::ClassItemData::Constraint(ref decl) => {
+                constraints.push(cx.map_ast_with_parent(AstNode::Constraint(decl), node_id));
+            }
+            ast::ClassItemData::Null => (),
+            ref data => {
+                let what = match *data {
+                    ast::ClassItemData::ExternSubroutine(..) => "extern methods",
+                    ast::ClassItemData::ClassDecl => "nested classes",
+                    ast::ClassItemData::CovergroupDecl => "covergroups",
+                    _ => "parameters in the class body",
+                };
+                cx.emit(DiagBuilder2::error(format!("{} are not supported", what)).span(item.span));
+                return Err(());
+            }
+        }
+    }
+
+    let hir = hir::Class {
+        id: node_id,
+        name: Spanned::new(ast.name.name, ast.name.span),
+        span: ast.span,
+        virt: ast.virt,
+        params,
+        extends,
+        members,
+        constraints,
+    };
+    Ok(HirNode::Class(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower a constraint block of a class to HIR.
+fn lower_constraint<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::Constraint,
+) -> Result<HirNode<'gcx>> {
+    if ast.kind != ast::ConstraintKind::Decl {
+        cx.emit(
+            DiagBuilder2::error("constraint prototypes are not supported").span(ast.human_span()),
+        );
+        return Err(());
+    }
+    let hir = hir::Constraint {
+        id: node_id,
+        name: Spanned::new(ast.name, ast.name_span),
+        span: ast.span,
+        items: lower_constraint_items(cx, node_id, &ast.items),
+    };
+    Ok(HirNode::Constraint(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower the items of a constraint block or an inline `with` constraint.
+///
+/// Constraint sets `{ ... }` are flattened into the surrounding items. The
+/// expressions are allocated with `node_id` as their parent.
+fn lower_constraint_items<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    items: &'gcx [ast::ConstraintItem],
+) -> Vec<hir::ConstraintItem> {
+    let mut lowered = Vec::new();
+    for item in items {
+        lower_constraint_item(cx, node_id, item, &mut lowered);
+    }
+    lowered
+}
+
+fn lower_constraint_item<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    item: &'gcx ast::ConstraintItem,
+    into: &mut Vec<hir::ConstraintItem>,
+) {
+    let expr = |expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+    let nested = |item: &'gcx ast::ConstraintItem| {
+        lower_constraint_items(cx, node_id, std::slice::from_ref(item))
+    };
+    into.push(match item.data {
+        ast::ConstraintItemData::Set(ref items) => {
+            for item in items {
+                lower_constraint_item(cx, node_id, item, into);
+            }
+            return;
+        }
+        ast::ConstraintItemData::If(ref cond, ref main, ref other) => hir::ConstraintItem::If(
+            expr(cond),
+            nested(main),
+            other.as_ref().map(|x| nested(x)).unwrap_or_default(),
+        ),
+        ast::ConstraintItemData::Implication(ref cond, ref item) => {
+            hir::ConstraintItem::If(expr(cond), nested(item), vec![])
+        }
+        ast::ConstraintItemData::Foreach(ref array, ref vars, ref item) => {
+            // The index variables are visible in the body of the loop.
+            let array = expr(array);
+            let mut next_rib = node_id;
+            let vars = vars
+                .iter()
+                .map(|var| {
+                    var.as_ref().map(|var| {
+                        next_rib = cx.map_ast_with_parent(AstNode::ForeachIndex(var), next_rib);
+                        next_rib
+                    })
+                })
+                .collect();
+            hir::ConstraintItem::Foreach(
+                array,
+                vars,
+                lower_constraint_items(cx, next_rib, std::slice::from_ref(item)),
+            )
+        }
+        ast::ConstraintItemData::Dist(ref value, ref items) => hir::ConstraintItem::Dist(
+            expr(value),
+            items
+                .iter()
+                .map(|item| {
+                    Spanned::new(
+                        hir::DistItem {
+                            range: lower_value_range(cx, node_id, &item.range).value,
+                            weight: item.weight.as_ref().map(|(_, weight)| expr(weight)),
+                            per_range: item.weight.as_ref().map(|w| w.0)
+                                == Some(ast::DistWeight::PerRange),
+                        },
+                        item.span,
+                    )
+                })
+                .collect(),
+        ),
+        ast::ConstraintItemData::Solve(ref before, ref after) => hir::ConstraintItem::Solve(
+            before.iter().map(expr).collect(),
+            after.iter().map(expr).collect(),
+        ),
+        ast::ConstraintItemData::Expr(ref value) => hir::ConstraintItem::Expr(expr(value)),
+    });
+}
+
+/// Lower a single value or a range `[lo:hi]` of an `inside` or `dist`
+/// expression.
+fn lower_value_range<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    range: &'gcx ast::ValueRange,
+) -> Spanned<hir::InsideRange> {
+    match *range {
+        ast::ValueRange::Single(ref expr) => Spanned::new(
+            hir::InsideRange::Single(cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+            expr.span,
+        ),
+        ast::ValueRange::Range {
+            ref lo,
+            ref hi,
+            span,
+        } => Spanned::new(
+            hir::InsideRange::Range(
+                cx.map_ast_with_parent(AstNode::Expr(lo), node_id),
+                cx.map_ast_with_parent(AstNode::Expr(hi), node_id),
+            ),
+            span,
+        ),
+    }
+}
+
+/// Check whether an expression names the `randomize` function, as in
+/// `randomize`, `std::randomize`, or `obj.randomize`.
+fn is_randomize_callee(expr: &ast::Expr) -> bool {
+    let name = match expr.data {
+        ast::IdentExpr(name) => name,
+        ast::ScopeExpr(ref scope, name) => match scope.data {
+            ast::IdentExpr(scope) if &*scope.name.as_str() == "std" => name,
+            _ => return false,
+        },
+        ast::MemberExpr { name, .. } => name,
+        _ => return false,
+    };
+    &*name.name.as_str() == "randomize"
+}
+
+/// Lower a call to `randomize()`.
+///
+/// The randomized object and the arguments are allocated with `parent` as
+/// their parent, such that they do not see the names introduced by a `with`
+/// clause.
+fn lower_randomize<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    parent: NodeId,
+    callee: &'gcx ast::Expr,
+    args: &'gcx [ast::CallArg],
+    constraints: Vec<hir::ConstraintItem>,
+) -> Result<hir::ExprKind> {
+    let target = match callee.data {
+        ast::MemberExpr { ref expr, .. } => {
+            hir::RandomizeTarget::Object(cx.map_ast_with_parent(AstNode::Expr(expr), parent))
+        }
+        ast::ScopeExpr(..) => hir::RandomizeTarget::Std,
+        _ => {
+            // Within a class, a plain `randomize()` refers to the object's
+            // own method.
+            let mut next = cx.parent_node_id(node_id);
+            let mut target = hir::RandomizeTarget::Std;
+            while let Some(id) = next {
+                if let AstNode::Class(_) = cx.ast_of(id)? {
+                    target = hir::RandomizeTarget::This;
+                    break;
+                }
+                next = cx.parent_node_id(id);
+            }
+            target
+        }
+    };
+    if target != hir::RandomizeTarget::Std && !args.is_empty() {
+        cx.emit(
+            DiagBuilder2::error(
+                "passing variables to the `randomize()` method of an object is not supported",
+            )
+            .span(args[0].span),
+        );
+        return Err(());
+    }
+    let mut vars = Vec::new();
+    for arg in args {
+        match arg.expr {
+            Some(ref expr) if arg.name.is_none() => {
+                vars.push(cx.map_ast_with_parent(AstNode::Expr(expr), parent));
+            }
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error("`randomize()` expects the variables to be randomized")
+                        .span(arg.span),
+                );
+                return Err(());
+            }
+        }
+    }
+    Ok(hir::ExprKind::Randomize(target, vars, constraints))
+}
+
+/// Lower an immediate, deferred, or concurrent assertion to HIR.
+fn lower_assertion<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::Assertion,
+) -> Result<HirNode<'gcx>> {
+    let parent = cx.parent_node_id(node_id).unwrap();
+    let (kind, data, (pass_stmt, fail_stmt)) = match ast.data {
+        ast::AssertionData::Immediate(ref blocking)
+        | ast::AssertionData::Deferred(ref blocking) => {
+            let deferred = match ast.data {
+                ast::AssertionData::Deferred(..) => true,
+                _ => false,
+            };
+            let (kind, expr, actions) = match *blocking {
+                ast::BlockingAssertion::Assert(ref expr, ref action) => (
+                    hir::AssertionKind::Assert,
+                    expr,
+                    lower_assertion_action(cx, action, node_id),
+                ),
+                ast::BlockingAssertion::Assume(ref expr, ref action) => (
+                    hir::AssertionKind::Assume,
+                    expr,
+                    lower_assertion_action(cx, action, node_id),
+                ),
+                ast::BlockingAssertion::Cover(ref expr, ref stmt) => (
+                    hir::AssertionKind::Cover,
+                    expr,
+                    (
+                        Some(cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id)),
+                        None,
+                    ),
+                ),
+            };
+            let expr = cx.map_ast_with_parent(AstNode::Expr(expr), parent);
+            (
+                kind,
+                hir::AssertionData::Immediate { expr, deferred },
+                actions,
+            )
+        }
+        ast::AssertionData::Concurrent(ref concurrent) => {
+            use crate::syntax::ast::ConcurrentAssertion::*;
+            let (kind, spec, actions) = match *concurrent {
+                AssertProperty(ref spec, ref action) => (
+                    hir::AssertionKind::Assert,
+                    spec,
+                    lower_assertion_action(cx, action, node_id),
+                ),
+                AssumeProperty(ref spec, ref action) => (
+                    hir::AssertionKind::Assume,
+                    spec,
+                    lower_assertion_action(cx, action, node_id),
+                ),
+                CoverProperty(ref spec, ref stmt) | CoverSequence(ref spec, ref stmt) => (
+                    hir::AssertionKind::Cover,
+                    spec,
+                    (
+                        Some(cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id)),
+                        None,
+                    ),
+                ),
+                ExpectProperty(ref spec, ref action) => (
+                    hir::AssertionKind::Expect,
+                    spec,
+                    lower_assertion_action(cx, action, node_id),
+                ),
+                RestrictProperty(ref spec) => (hir::AssertionKind::Restrict, spec, (None, None)),
+            };
+            (
+                kind,
+                hir::AssertionData::Concurrent(lower_prop_spec(cx, spec, parent)),
+                actions,
+            )
+        }
+    };
+    let hir = hir::Assertion {
+        id: node_id,
+        label: ast.label.map(|(name, span)| Spanned::new(name, span)),
+        span: ast.span,
+        kind,
+        data,
+        pass_stmt,
+        fail_stmt,
+    };
+    Ok(HirNode::Assertion(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower the action block of an assertion to its pass and fail statements.
+fn lower_assertion_action<'gcx>(
+    cx: &impl Context<'gcx>,
+    action: &'gcx ast::AssertionActionBlock,
+    parent: NodeId,
+) -> (Option<NodeId>, Option<NodeId>) {
+    let stmt = |stmt| cx.map_ast_with_parent(AstNode::Stmt(stmt), parent);
+    match *action {
+        ast::AssertionActionBlock::Positive(ref pass) => (Some(stmt(pass)), None),
+        ast::AssertionActionBlock::Negative(ref fail) => (None, Some(stmt(fail))),
+        ast::AssertionActionBlock::Both(ref pass, ref fail) => (Some(stmt(pass)), Some(stmt(fail))),
+    }
+}
+
+/// Allocate node IDs for the formal arguments of a sequence or property.
+///
+/// Returns the IDs of the arguments, and the rib in which the body of the
+/// declaration shall be lowered.
+fn alloc_seq_ports<'gcx>(
+    cx: &impl Context<'gcx>,
+    ports: &'gcx [ast::SeqPort],
+    parent: NodeId,
+) -> (Vec<NodeId>, NodeId) {
+    let mut next_rib = parent;
+    let ids = ports
+        .iter()
+        .map(|port| {
+            next_rib = cx.map_ast_with_parent(AstNode::SeqPort(port), next_rib);
+            next_rib
+        })
+        .collect();
+    (ids, next_rib)
+}
+
+fn lower_prop_spec<'gcx>(
+    cx: &impl Context<'gcx>,
+    spec: &'gcx ast::PropSpec,
+    parent: NodeId,
+) -> hir::PropSpec {
+    hir::PropSpec {
+        span: spec.span,
+        clock: spec
+            .clock
+            .as_ref()
+            .map(|clock| cx.map_ast_with_parent(AstNode::EventExpr(clock), parent)),
+        disable: spec
+            .disable
+            .as_ref()
+            .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
+        prop: lower_prop_expr(cx, &spec.prop, parent),
+    }
+}
+
+fn lower_prop_expr<'gcx>(
+    cx: &impl Context<'gcx>,
+    prop: &'gcx ast::PropExpr,
+    parent: NodeId,
+) -> hir::PropExpr {
+    let boxed = |prop| Box::new(lower_prop_expr(cx, prop, parent));
+    let kind = match prop.data {
+        ast::PropExprData::SeqOp(op, ref seq) => {
+            hir::PropExprKind::Seq(op, lower_seq_expr(cx, seq, parent))
+        }
+        ast::PropExprData::SeqBinOp(op, seq_op, ref lhs, ref rhs) => {
+            hir::PropExprKind::SeqBinOp(op, seq_op, lower_seq_expr(cx, lhs, parent), boxed(rhs))
+        }
+        ast::PropExprData::Not(ref arg) => hir::PropExprKind::Not(boxed(arg)),
+        ast::PropExprData::BinOp(op, ref lhs, ref rhs) => {
+            hir::PropExprKind::BinOp(op, boxed(lhs), boxed(rhs))
+        }
+        ast::PropExprData::Clocked(ref clock, ref arg) => hir::PropExprKind::Clocked(
+            cx.map_ast_with_parent(AstNode::EventExpr(clock), parent),
+            boxed(arg),
+        ),
+    };
+    hir::PropExpr {
+        span: prop.span,
+        kind,
+    }
+}
+
+fn lower_seq_expr<'gcx>(
+    cx: &impl Context<'gcx>,
+    seq: &'gcx ast::SeqExpr,
+    parent: NodeId,
+) -> hir::SeqExpr {
+    let expr = |expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent);
+    let boxed = |seq| Box::new(lower_seq_expr(cx, seq, parent));
+    let kind = match seq.data {
+        ast::SeqExprData::Expr(ref arg, None) => hir::SeqExprKind::Expr(expr(arg)),
+        ast::SeqExprData::Expr(ref arg, Some(ref rep)) => hir::SeqExprKind::Repeat(
+            Box::new(hir::SeqExpr {
+                span: arg.span,
+                kind: hir::SeqExprKind::Expr(expr(arg)),
+            }),
+            lower_seq_rep(cx, rep, parent),
+        ),
+        ast::SeqExprData::Repeat(ref arg, ref rep) => {
+            hir::SeqExprKind::Repeat(boxed(arg), lower_seq_rep(cx, rep, parent))
+        }
+        ast::SeqExprData::Delay(ref lhs, ref delay, ref rhs) => hir::SeqExprKind::Delay(
+            lhs.as_ref().map(|lhs| boxed(lhs)),
+            match *delay {
+                ast::SeqDelay::Range(ref range) => {
+                    hir::SeqDelay::Range(lower_seq_range(cx, range, parent))
+                }
+                ast::SeqDelay::Star => hir::SeqDelay::Star,
+                ast::SeqDelay::Plus => hir::SeqDelay::Plus,
+            },
+            boxed(rhs),
+        ),
+        ast::SeqExprData::BinOp(op, ref lhs, ref rhs) => {
+            hir::SeqExprKind::BinOp(op, boxed(lhs), boxed(rhs))
+        }
+        ast::SeqExprData::Throughout(ref cond, ref arg) => {
+            hir::SeqExprKind::Throughout(expr(cond), boxed(arg))
+        }
+        ast::SeqExprData::Clocked(ref clock, ref arg) => hir::SeqExprKind::Clocked(
+            cx.map_ast_with_parent(AstNode::EventExpr(clock), parent),
+            boxed(arg),
+        ),
+    };
+    hir::SeqExpr {
+        span: seq.span,
+        kind,
+    }
+}
+
+fn lower_seq_rep<'gcx>(
+    cx: &impl Context<'gcx>,
+    rep: &'gcx ast::SeqRep,
+    parent: NodeId,
+) -> hir::SeqRep {
+    match *rep {
+        ast::SeqRep::Consec(ref range) => hir::SeqRep::Consec(lower_seq_range(cx, range, parent)),
+        ast::SeqRep::ConsecStar => hir::SeqRep::ConsecStar,
+        ast::SeqRep::ConsecPlus => hir::SeqRep::ConsecPlus,
+        ast::SeqRep::Nonconsec(ref range) => {
+            hir::SeqRep::Nonconsec(lower_seq_range(cx, range, parent))
+        }
+        ast::SeqRep::Goto(ref range) => hir::SeqRep::Goto(lower_seq_range(cx, range, parent)),
+    }
+}
+
+fn lower_seq_range<'gcx>(
+    cx: &impl Context<'gcx>,
+    range: &'gcx ast::SeqRange,
+    parent: NodeId,
+) -> hir::SeqRange {
+    let expr = |expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent);
+    match *range {
+        ast::SeqRange::Single(ref count) => hir::SeqRange::Single(expr(count)),
+        ast::SeqRange::Range(ref lo, ref hi) => {
+            hir::SeqRange::Range(expr(lo), hi.as_ref().map(|hi| expr(hi)))
+        }
+    }
+}
+
 fn lower_port<'gcx>(
     cx: &impl Context<'gcx>,
     node_id: NodeId,
@@ -612,25 +1863,260 @@ fn lower_port<'gcx>(
         ast::Port::Named {
             span,
             name,
-            dir,
-            ref ty,
             ref expr,
             ..
-        } => hir::Port {
-            id: node_id,
-            name: Spanned::new(name.name, name.span),
-            span: span,
-            dir: dir.expect("port missing direction"),
-            ty: cx.map_ast_with_parent(AstNode::Type(ty), parent),
-            default: expr
-                .as_ref()
-                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
-        },
+        } => {
+            let (dir, ty) = ansi_port_dir_and_type(cx, node_id, ast)?;
+            hir::Port {
+                id: node_id,
+                name: Spanned::new(name.name, name.span),
+                span: span,
+                dir,
+                ty: Some(cx.map_ast_with_parent(AstNode::Type(ty), parent)),
+                default: expr
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), parent)),
+                expr: None,
+            }
+        }
+        ast::Port::Explicit {
+            span,
+            dir,
+            name,
+            ref expr,
+        } => {
+            let expr = match *expr {
+                Some(ref expr) => expr,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} is not connected to anything in the module",
+                            ast.desc_full()
+                        ))
+                        .span(span)
+                        .add_note("ports without an expression, e.g. `.a()`, are not supported"),
+                    );
+                    return Err(());
+                }
+            };
+            lower_port_expr(
+                cx,
+                node_id,
+                span,
+                Spanned::new(name.name, name.span),
+                dir,
+                expr,
+            )?
+        }
+        ast::Port::Implicit(ref expr) => {
+            // Ports such as `a[3:0]` or `{a,b}` have no name. Name them after
+            // their position in the port list for the generated code.
+            let name = match ast.name() {
+                Some(name) => Spanned::new(name.name, name.span),
+                None => {
+                    let (_, ports, _) = port_scope(cx, node_id)?;
+                    let index = ports.iter().position(|p| std::ptr::eq(p, ast)).unwrap();
+                    Spanned::new(Name::from(format!("port{}", index).as_str()), expr.span)
+                }
+            };
+            lower_port_expr(cx, node_id, expr.span, name, None, expr)?
+        }
         _ => return cx.unimp(ast),
     };
     Ok(HirNode::Port(cx.arena().alloc_hir(hir)))
 }
 
+/// Determine the direction and type of a port in an ANSI port list.
+///
+/// Ports without a direction inherit it from the preceding port, with the
+/// first port defaulting to `inout`. Ports which additionally have no kind and
+/// data type inherit the type of the preceding port as well. See IEEE
+/// 1800-2017 §23.2.2.3. Treating every port without a direction as `inout`
+/// would turn `y` in `input logic [7:0] x, y` into a bidirectional port.
+fn ansi_port_dir_and_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    port: &'gcx ast::Port,
+) -> Result<(ast::PortDir, &'gcx ast::Type)> {
+    let (_, ports, _) = port_scope(cx, node_id)?;
+    let index = ports.iter().position(|p| std::ptr::eq(p, port)).unwrap();
+    let mut dir = ast::PortDir::Inout;
+    let mut ty = None;
+    for port in &ports[..=index] {
+        match *port {
+            ast::Port::Named {
+                dir: port_dir,
+                kind,
+                ty: ref port_ty,
+                ..
+            } => {
+                let inherit = port_dir.is_none()
+                    && kind.is_none()
+                    && port_ty.data == ast::ImplicitType
+                    && port_ty.sign == ast::TypeSign::None
+                    && port_ty.dims.is_empty();
+                dir = port_dir.unwrap_or(dir);
+                if !inherit || ty.is_none() {
+                    ty = Some(port_ty);
+                }
+            }
+            ast::Port::Explicit { dir: port_dir, .. } => {
+                dir = port_dir.unwrap_or(dir);
+                ty = None;
+            }
+            _ => ty = None,
+        }
+    }
+    Ok((dir, ty.unwrap()))
+}
+
+/// Find the module or interface a port belongs to.
+///
+/// Returns the node ID of the module or interface, together with its port list
+/// and body items.
+fn port_scope<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<(NodeId, &'gcx [ast::Port], &'gcx [ast::HierarchyItem])> {
+    let mut id = node_id;
+    loop {
+        match cx.ast_of(id)? {
+            AstNode::Module(m) => return Ok((id, &m.ports, &m.items)),
+            AstNode::Interface(i) => return Ok((id, &i.ports, &i.items)),
+            _ => (),
+        }
+        id = cx.parent_node_id(id).unwrap();
+    }
+}
+
+/// Lower a port that connects to an expression over the declarations in the
+/// module body, e.g. `.a(b)`, `a[3:0]`, or `{a,b}`.
+///
+/// Ports without an explicit direction take it from the port declarations of
+/// the signals in the expression.
+fn lower_port_expr<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    span: Span,
+    name: Spanned<Name>,
+    dir: Option<ast::PortDir>,
+    expr: &'gcx ast::Expr,
+) -> Result<hir::Port> {
+    let (module_id, ports, items) = port_scope(cx, node_id)?;
+    let mut idents = Vec::new();
+    collect_port_expr_idents(cx, expr, &mut idents)?;
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None if ports[0].is_nonansi() => {
+            let mut dir: Option<(ast::PortDir, Span)> = None;
+            for ident in idents {
+                let decl = items
+                    .iter()
+                    .flat_map(|item| match *item {
+                        ast::HierarchyItem::PortDecl(ref decl) => Some(decl),
+                        _ => None,
+                    })
+                    .find(|decl| decl.names.iter().any(|n| n.name == ident.name));
+                let decl = match decl {
+                    Some(decl) => decl,
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "missing port declaration for `{}`",
+                                ident.name
+                            ))
+                            .span(ident.span)
+                            .add_note(format!(
+                                "`{}` appears in the port list, which is non-ANSI; declare it \
+                                 in the module body, e.g. `input {};`",
+                                ident.name, ident.name
+                            )),
+                        );
+                        return Err(());
+                    }
+                };
+                match dir {
+                    Some((prev, prev_span)) if prev != decl.dir => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "port `{}` connects to declarations with different directions",
+                                name.value
+                            ))
+                            .span(decl.span)
+                            .add_note("conflicting port declaration was here:")
+                            .span(prev_span),
+                        );
+                        return Err(());
+                    }
+                    Some(_) => (),
+                    None => dir = Some((decl.dir, decl.span)),
+                }
+            }
+            dir.map(|(dir, _)| dir).unwrap_or(ast::PortDir::Inout)
+        }
+        None => ast::PortDir::Inout,
+    };
+
+    // The expression refers to the declarations in the module body.
+    let rib = match cx.hir_of(module_id)? {
+        HirNode::Module(m) => m.block.last_rib,
+        HirNode::Interface(i) => i.block.last_rib,
+        _ => unreachable!(),
+    };
+    Ok(hir::Port {
+        id: node_id,
+        name,
+        span,
+        dir,
+        ty: None,
+        default: None,
+        expr: Some(cx.map_ast_with_parent(AstNode::Expr(expr), rib)),
+    })
+}
+
+/// Collect the identifiers in a port expression.
+///
+/// Emits an error if the expression contains anything but identifiers, member
+/// and index accesses, and concatenations.
+fn collect_port_expr_idents<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx ast::Expr,
+    into: &mut Vec<ast::Identifier>,
+) -> Result<()> {
+    match expr.data {
+        ast::IdentExpr(ident) => into.push(ident),
+        ast::IndexExpr { ref indexee, .. } => collect_port_expr_idents(cx, indexee, into)?,
+        ast::MemberExpr { ref expr, .. } => collect_port_expr_idents(cx, expr, into)?,
+        ast::ConcatExpr {
+            repeat: Some(ref repeat),
+            ..
+        } => {
+            cx.emit(
+                DiagBuilder2::error("concatenation in port list cannot have a repeat count")
+                    .span(repeat.span),
+            );
+            return Err(());
+        }
+        ast::ConcatExpr { ref exprs, .. } => {
+            for expr in exprs {
+                collect_port_expr_idents(cx, expr, into)?;
+            }
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(
+                    "port expression can only contain identifiers, member accesses, index \
+                     accesses, and concatenations",
+                )
+                .span(expr.span),
+            );
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
 fn lower_type<'gcx>(
     cx: &impl Context<'gcx>,
     node_id: NodeId,
@@ -648,7 +2134,10 @@ fn lower_type<'gcx>(
         ast::IntegerType => hir::TypeKind::Builtin(hir::BuiltinType::Integer),
         ast::LongIntType => hir::TypeKind::Builtin(hir::BuiltinType::LongInt),
         ast::StringType => hir::TypeKind::Builtin(hir::BuiltinType::String),
+        ast::EventType => hir::TypeKind::Builtin(hir::BuiltinType::Event),
         ast::TimeType => hir::TypeKind::Builtin(hir::BuiltinType::Time),
+        ast::RealType | ast::RealtimeType => hir::TypeKind::Builtin(hir::BuiltinType::Real),
+        ast::ShortRealType => hir::TypeKind::Builtin(hir::BuiltinType::ShortReal),
         ast::NamedType(name) => hir::TypeKind::Named(Spanned::new(name.name, name.span)),
         ast::StructType { ref members, .. } => {
             let mut fields = vec![];
@@ -658,11 +2147,15 @@ fn lower_type<'gcx>(
             }
             hir::TypeKind::Struct(fields)
         }
-        ast::ScopedType {
-            ref ty,
-            member: false,
-            name,
-        } => hir::TypeKind::Scope(
+        ast::SpecializedType(ref ty, ref params) => {
+            let (pos_params, named_params) = lower_param_assignments(cx, node_id, params);
+            hir::TypeKind::Specialized(
+                cx.map_ast_with_parent(AstNode::Type(ty.as_ref()), node_id),
+                pos_params,
+                named_params,
+            )
+        }
+        ast::ScopedType { ref ty, name, .. } => hir::TypeKind::Scope(
             cx.map_ast_with_parent(AstNode::Type(ty.as_ref()), node_id),
             Spanned::new(name.name, name.span),
         ),
@@ -806,16 +2299,30 @@ fn lower_expr<'gcx>(
                 )).span(expr.span).add_note(format!("constant is {} bits wide, but the value `{}{}` needs {} bits to not be truncated", size, base, value, size_needed)));
             }
 
-            // Identify the special bits (x and z) in the input.
+            // Identify the special bits (x and z) in the input. The bits are
+            // aligned to the width of the constant, with a leading x or z digit
+            // extended towards the MSB. See "5.7.1 Integer literal constants".
             // TODO(fschuiki): Decimal literals are not handled properly.
-            let bit_iter = value_str.chars().flat_map(|c| {
-                std::iter::repeat(c).take(match base {
-                    'h' => 4,
-                    'o' => 3,
-                    'b' => 1,
-                    _ => 0,
-                })
-            });
+            let digits: Vec<char> = value_str.chars().filter(|&c| c != '_').collect();
+            let digit_bits = match base {
+                'h' => 4,
+                'o' => 3,
+                'b' => 1,
+                _ => 0,
+            };
+            let num_bits = digits.len() * digit_bits;
+            let fill = match digits.first() {
+                Some(&c) if "xXzZ?".contains(c) => c,
+                _ => '0',
+            };
+            let bit_iter = std::iter::repeat(fill)
+                .take(size.saturating_sub(num_bits))
+                .chain(
+                    digits
+                        .iter()
+                        .flat_map(|&c| std::iter::repeat(c).take(digit_bits))
+                        .skip(num_bits.saturating_sub(size)),
+                );
             let special_bits: BitVec = bit_iter
                 .clone()
                 .map(|c| match c {
@@ -858,11 +2365,24 @@ fn lower_expr<'gcx>(
             hir::ExprKind::TimeConst(value)
         }
 
+        ast::LiteralExpr(Lit::Number(int, Some(frac))) => {
+            hir::ExprKind::RealConst(parse_real_number(cx, expr.span, int, Some(frac), None)?)
+        }
+
+        ast::LiteralExpr(Lit::Real(int, frac, exp)) => {
+            hir::ExprKind::RealConst(parse_real_number(cx, expr.span, int, frac, Some(exp))?)
+        }
+
         ast::LiteralExpr(Lit::Str(value)) => {
             hir::ExprKind::StringConst(Spanned::new(value, expr.span))
         }
 
         ast::IdentExpr(ident) => hir::ExprKind::Ident(Spanned::new(ident.name, ident.span)),
+        // System functions without arguments may be called without
+        // parentheses, e.g. `$realtime` or `$finish`.
+        ast::SysIdentExpr(ident) => {
+            hir::ExprKind::Builtin(lower_system_call(cx, node_id, expr, ident, &[])?)
+        }
         ast::UnaryExpr {
             op,
             expr: ref arg,
@@ -915,6 +2435,10 @@ fn lower_expr<'gcx>(
                 Op::Pow => hir::BinaryOp::Pow,
                 Op::LogicEq => hir::BinaryOp::Eq,
                 Op::LogicNeq => hir::BinaryOp::Neq,
+                Op::CaseEq => hir::BinaryOp::CaseEq,
+                Op::CaseNeq => hir::BinaryOp::CaseNeq,
+                Op::WildcardEq => hir::BinaryOp::WildcardEq,
+                Op::WildcardNeq => hir::BinaryOp::WildcardNeq,
                 Op::Lt => hir::BinaryOp::Lt,
                 Op::Leq => hir::BinaryOp::Leq,
                 Op::Gt => hir::BinaryOp::Gt,
@@ -966,45 +2490,64 @@ fn lower_expr<'gcx>(
             };
             hir::ExprKind::Index(indexee, mode)
         }
+        ast::CallExpr(ref callee, ref args) if is_randomize_callee(callee) => {
+            lower_randomize(cx, node_id, node_id, callee, args, vec![])?
+        }
+        ast::RandomizeWithExpr(ref call, ref items) => {
+            let (callee, args) = match call.data {
+                ast::CallExpr(ref callee, ref args) => (callee.as_ref(), args.as_slice()),
+                _ => (call.as_ref(), &[][..]),
+            };
+            if !is_randomize_callee(callee) {
+                cx.emit(
+                    DiagBuilder2::error(
+                        "inline constraints can only be applied to a call to `randomize()`",
+                    )
+                    .span(call.span),
+                );
+                return Err(());
+            }
+            let parent = cx.parent_node_id(node_id).unwrap();
+            let constraints = lower_constraint_items(cx, node_id, items);
+            lower_randomize(cx, node_id, parent, callee, args, constraints)?
+        }
         ast::CallExpr(ref callee, ref args) => match callee.data {
             ast::SysIdentExpr(ident) => {
-                let map_unary = || {
-                    Ok(match args.as_slice() {
-                        [ast::CallArg {
-                            expr: Some(ref arg),
-                            ..
-                        }] => cx.map_ast_with_parent(AstNode::Expr(arg), node_id),
-                        _ => {
-                            cx.emit(
-                                DiagBuilder2::error(format!("`{}` takes one argument", ident.name))
-                                    .span(expr.human_span()),
-                            );
-                            return Err(());
-                        }
-                    })
-                };
-                hir::ExprKind::Builtin(match &*ident.name.as_str() {
-                    "clog2" => hir::BuiltinCall::Clog2(map_unary()?),
-                    "bits" => hir::BuiltinCall::Bits(map_unary()?),
-                    "signed" => hir::BuiltinCall::Signed(map_unary()?),
-                    "unsigned" => hir::BuiltinCall::Unsigned(map_unary()?),
-                    _ => {
-                        cx.emit(
-                            DiagBuilder2::warning(format!(
-                                "`${}` not supported; ignored",
-                                ident.name
-                            ))
-                            .span(expr.human_span()),
-                        );
-                        hir::BuiltinCall::Unsupported
-                    }
-                })
+                hir::ExprKind::Builtin(lower_system_call(cx, node_id, expr, ident, args)?)
             }
+            ast::IdentExpr(..) | ast::ScopeExpr(..) => hir::ExprKind::Call(
+                cx.map_ast_with_parent(AstNode::Expr(callee), node_id),
+                lower_call_args(cx, node_id, args),
+            ),
+            ast::MemberExpr { ref expr, name } => hir::ExprKind::MethodCall(
+                cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
+                Spanned::new(name.name, name.span),
+                lower_call_args(cx, node_id, args),
+            ),
             _ => {
                 error!("{:#?}", callee);
                 return cx.unimp_msg("lowering of call to", callee.as_ref());
             }
         },
+        ast::TaggedUnionExpr { member, ref expr } => hir::ExprKind::Tagged(
+            Spanned::new(member.name, member.span),
+            expr.as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        ),
+        ast::PatternVarExpr(name) => {
+            hir::ExprKind::PatternVar(name.map(|n| Spanned::new(n.name, n.span)))
+        }
+        ast::ConstructorCallExpr(ref args) => {
+            hir::ExprKind::New(lower_call_args(cx, node_id, args))
+        }
+        ast::ThisExpr => hir::ExprKind::This,
+        ast::SuperExpr => hir::ExprKind::Super,
+        ast::NullExpr => hir::ExprKind::Null,
+        ast::ArrayNewExpr(ref size, ref init) => hir::ExprKind::ArrayNew(
+            cx.map_ast_with_parent(AstNode::Expr(size), node_id),
+            init.as_ref()
+                .map(|init| cx.map_ast_with_parent(AstNode::Expr(init), node_id)),
+        ),
         ast::TernaryExpr {
             ref cond,
             ref true_expr,
@@ -1130,21 +2673,7 @@ fn lower_expr<'gcx>(
             cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
             ranges
                 .iter()
-                .map(|vr| match vr {
-                    ast::ValueRange::Single(expr) => Spanned::new(
-                        hir::InsideRange::Single(
-                            cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
-                        ),
-                        expr.span,
-                    ),
-                    ast::ValueRange::Range { lo, hi, span } => Spanned::new(
-                        hir::InsideRange::Range(
-                            cx.map_ast_with_parent(AstNode::Expr(lo), node_id),
-                            cx.map_ast_with_parent(AstNode::Expr(hi), node_id),
-                        ),
-                        *span,
-                    ),
-                })
+                .map(|vr| lower_value_range(cx, node_id, vr))
                 .collect(),
         ),
         _ => {
@@ -1160,6 +2689,358 @@ fn lower_expr<'gcx>(
     Ok(HirNode::Expr(cx.arena().alloc_hir(hir)))
 }
 
+/// Lower a call to a system function or task, e.g. `$clog2(x)`.
+///
+/// System functions and tasks without arguments may be called without
+/// parentheses, in which case `args` is empty.
+fn lower_system_call<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    expr: &'gcx ast::Expr,
+    ident: ast::Identifier,
+    args: &'gcx [ast::CallArg],
+) -> Result<hir::BuiltinCall> {
+    let map_unary = || {
+        Ok(match args {
+            [ast::CallArg {
+                expr: Some(ref arg),
+                ..
+            }] => cx.map_ast_with_parent(AstNode::Expr(arg), node_id),
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error(format!("`{}` takes one argument", ident.name))
+                        .span(expr.human_span()),
+                );
+                return Err(());
+            }
+        })
+    };
+    let map_optional = || match args {
+        [] => Ok(None),
+        [ast::CallArg {
+            expr: Some(ref arg),
+            ..
+        }] => Ok(Some(cx.map_ast_with_parent(AstNode::Expr(arg), node_id))),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("`${}` takes at most one argument", ident.name))
+                    .span(expr.human_span()),
+            );
+            Err(())
+        }
+    };
+    let map_real_math = |func: hir::RealMath| {
+        let mut args = args.iter().map(|arg| {
+            arg.expr
+                .as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
+        });
+        match (args.next(), args.next(), args.next()) {
+            (Some(Some(x)), None, None) if !func.is_binary() => {
+                Ok(hir::BuiltinCall::RealMath(func, x, None))
+            }
+            (Some(Some(x)), Some(Some(y)), None) if func.is_binary() => {
+                Ok(hir::BuiltinCall::RealMath(func, x, Some(y)))
+            }
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`${}` takes {} argument{}",
+                        ident.name,
+                        if func.is_binary() { "two" } else { "one" },
+                        if func.is_binary() { "s" } else { "" }
+                    ))
+                    .span(expr.human_span()),
+                );
+                Err(())
+            }
+        }
+    };
+    let map_display = |task, radix| -> Result<_> {
+        Ok(hir::BuiltinCall::Display(
+            task,
+            lower_format_args(cx, node_id, args, radix)?,
+        ))
+    };
+    let map_severity = |severity, finish, args| -> Result<_> {
+        Ok(hir::BuiltinCall::Severity(
+            severity,
+            finish,
+            lower_format_args(cx, node_id, args, hir::FormatConv::Decimal)?,
+        ))
+    };
+    let map_array_query = |func| {
+        let mut args = args.iter().map(|arg| {
+            arg.expr
+                .as_ref()
+                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
+        });
+        let takes_dim = match func {
+            hir::ArrayQuery::Dimensions | hir::ArrayQuery::UnpackedDimensions => false,
+            _ => true,
+        };
+        match (args.next(), args.next(), args.next()) {
+            (Some(Some(arg)), None, None) => Ok(hir::BuiltinCall::ArrayQuery(func, arg, None)),
+            (Some(Some(arg)), Some(Some(dim)), None) if takes_dim => {
+                Ok(hir::BuiltinCall::ArrayQuery(func, arg, Some(dim)))
+            }
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`${}` takes {} argument{}",
+                        ident.name,
+                        if takes_dim { "one or two" } else { "one" },
+                        if takes_dim { "s" } else { "" }
+                    ))
+                    .span(expr.human_span()),
+                );
+                Err(())
+            }
+        }
+    };
+    Ok(match &*ident.name.as_str() {
+        "clog2" => hir::BuiltinCall::Clog2(map_unary()?),
+        "bits" => hir::BuiltinCall::Bits(map_unary()?),
+        "signed" => hir::BuiltinCall::Signed(map_unary()?),
+        "unsigned" => hir::BuiltinCall::Unsigned(map_unary()?),
+        "left" => map_array_query(hir::ArrayQuery::Left)?,
+        "right" => map_array_query(hir::ArrayQuery::Right)?,
+        "low" => map_array_query(hir::ArrayQuery::Low)?,
+        "high" => map_array_query(hir::ArrayQuery::High)?,
+        "increment" => map_array_query(hir::ArrayQuery::Increment)?,
+        "size" => map_array_query(hir::ArrayQuery::Size)?,
+        "dimensions" => map_array_query(hir::ArrayQuery::Dimensions)?,
+        "unpacked_dimensions" => map_array_query(hir::ArrayQuery::UnpackedDimensions)?,
+        "rtoi" => hir::BuiltinCall::RealConv(hir::RealConv::RealToInt, map_unary()?),
+        "itor" => hir::BuiltinCall::RealConv(hir::RealConv::IntToReal, map_unary()?),
+        "realtobits" => hir::BuiltinCall::RealConv(hir::RealConv::RealToBits, map_unary()?),
+        "bitstoreal" => hir::BuiltinCall::RealConv(hir::RealConv::BitsToReal, map_unary()?),
+        "shortrealtobits" => {
+            hir::BuiltinCall::RealConv(hir::RealConv::ShortRealToBits, map_unary()?)
+        }
+        "bitstoshortreal" => {
+            hir::BuiltinCall::RealConv(hir::RealConv::BitsToShortReal, map_unary()?)
+        }
+        "countones" => hir::BuiltinCall::BitQuery(hir::BitQuery::CountOnes, map_unary()?),
+        "onehot" => hir::BuiltinCall::BitQuery(hir::BitQuery::OneHot, map_unary()?),
+        "onehot0" => hir::BuiltinCall::BitQuery(hir::BitQuery::OneHot0, map_unary()?),
+        "isunknown" => hir::BuiltinCall::BitQuery(hir::BitQuery::IsUnknown, map_unary()?),
+        "ln" => map_real_math(hir::RealMath::Ln)?,
+        "log10" => map_real_math(hir::RealMath::Log10)?,
+        "exp" => map_real_math(hir::RealMath::Exp)?,
+        "sqrt" => map_real_math(hir::RealMath::Sqrt)?,
+        "pow" => map_real_math(hir::RealMath::Pow)?,
+        "floor" => map_real_math(hir::RealMath::Floor)?,
+        "ceil" => map_real_math(hir::RealMath::Ceil)?,
+        "sin" => map_real_math(hir::RealMath::Sin)?,
+        "cos" => map_real_math(hir::RealMath::Cos)?,
+        "tan" => map_real_math(hir::RealMath::Tan)?,
+        "asin" => map_real_math(hir::RealMath::Asin)?,
+        "acos" => map_real_math(hir::RealMath::Acos)?,
+        "atan" => map_real_math(hir::RealMath::Atan)?,
+        "atan2" => map_real_math(hir::RealMath::Atan2)?,
+        "hypot" => map_real_math(hir::RealMath::Hypot)?,
+        "sinh" => map_real_math(hir::RealMath::Sinh)?,
+        "cosh" => map_real_math(hir::RealMath::Cosh)?,
+        "tanh" => map_real_math(hir::RealMath::Tanh)?,
+        "asinh" => map_real_math(hir::RealMath::Asinh)?,
+        "acosh" => map_real_math(hir::RealMath::Acosh)?,
+        "atanh" => map_real_math(hir::RealMath::Atanh)?,
+        "realtime" if args.is_empty() => hir::BuiltinCall::RealTime,
+        "time" if args.is_empty() => hir::BuiltinCall::Time,
+        "stime" if args.is_empty() => hir::BuiltinCall::STime,
+        "display" => map_display(hir::DisplayTask::Display, hir::FormatConv::Decimal)?,
+        "displayb" => map_display(hir::DisplayTask::Display, hir::FormatConv::Binary)?,
+        "displayo" => map_display(hir::DisplayTask::Display, hir::FormatConv::Octal)?,
+        "displayh" => map_display(hir::DisplayTask::Display, hir::FormatConv::Hex)?,
+        "write" => map_display(hir::DisplayTask::Write, hir::FormatConv::Decimal)?,
+        "writeb" => map_display(hir::DisplayTask::Write, hir::FormatConv::Binary)?,
+        "writeo" => map_display(hir::DisplayTask::Write, hir::FormatConv::Octal)?,
+        "writeh" => map_display(hir::DisplayTask::Write, hir::FormatConv::Hex)?,
+        "info" => map_severity(hir::SeverityTask::Info, None, args)?,
+        "warning" => map_severity(hir::SeverityTask::Warning, None, args)?,
+        "error" => map_severity(hir::SeverityTask::Error, None, args)?,
+        "fatal" => {
+            // The optional first argument of `$fatal` is the finish number,
+            // which is told apart from the message by not being a string.
+            match args.split_first() {
+                Some((
+                    ast::CallArg {
+                        expr: Some(ref finish),
+                        ..
+                    },
+                    rest,
+                )) if !is_string_literal(finish) => {
+                    let finish = cx.map_ast_with_parent(AstNode::Expr(finish), node_id);
+                    map_severity(hir::SeverityTask::Fatal, Some(finish), rest)?
+                }
+                _ => map_severity(hir::SeverityTask::Fatal, None, args)?,
+            }
+        }
+        "finish" => hir::BuiltinCall::SimControl(hir::SimControl::Finish, map_optional()?),
+        "stop" => hir::BuiltinCall::SimControl(hir::SimControl::Stop, map_optional()?),
+        _ => {
+            cx.emit(
+                DiagBuilder2::warning(format!("`${}` not supported; ignored", ident.name))
+                    .span(expr.human_span()),
+            );
+            hir::BuiltinCall::Unsupported
+        }
+    })
+}
+
+/// Check whether an expression is a string literal.
+fn is_string_literal(expr: &ast::Expr) -> bool {
+    match expr.data {
+        ast::LiteralExpr(crate::syntax::token::Lit::Str(..)) => true,
+        _ => false,
+    }
+}
+
+/// Lower the arguments of a display or severity task to format items.
+///
+/// String literal arguments are format strings, whose format specifications
+/// consume the subsequent arguments. All other arguments are formatted in the
+/// task's default `radix`, and empty arguments produce a single space. See
+/// IEEE 1800-2017 §21.2.1.
+fn lower_format_args<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    args: &'gcx [ast::CallArg],
+    radix: hir::FormatConv,
+) -> Result<Vec<hir::FormatItem>> {
+    use crate::syntax::token::Lit;
+
+    fn flush(text: &mut String, items: &mut Vec<hir::FormatItem>) {
+        if !text.is_empty() {
+            items.push(hir::FormatItem::Text(std::mem::replace(
+                text,
+                String::new(),
+            )));
+        }
+    }
+
+    fn parse_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+        let mut number = None;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+            number = Some(number.unwrap_or(0) * 10 + digit as usize);
+            chars.next();
+        }
+        number
+    }
+
+    let mut items = vec![];
+    let mut text = String::new();
+    let mut failed = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let expr = match arg.expr {
+            Some(ref expr) => expr,
+            None => {
+                text.push(' ');
+                continue;
+            }
+        };
+        let format = match expr.data {
+            ast::LiteralExpr(Lit::Str(format)) => format.as_str(),
+            _ => {
+                flush(&mut text, &mut items);
+                let spec = hir::FormatSpec {
+                    conv: radix,
+                    width: None,
+                    precision: None,
+                    implicit: true,
+                };
+                let arg = cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+                items.push(hir::FormatItem::Arg(spec, arg));
+                continue;
+            }
+        };
+
+        // Parse the format string.
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            let width = parse_number(&mut chars);
+            let precision = if chars.peek() == Some(&'.') {
+                chars.next();
+                Some(parse_number(&mut chars).unwrap_or(0))
+            } else {
+                None
+            };
+            let conv = match chars.next() {
+                Some('%') => {
+                    text.push('%');
+                    continue;
+                }
+                Some('m') | Some('M') => {
+                    flush(&mut text, &mut items);
+                    items.push(hir::FormatItem::Scope);
+                    continue;
+                }
+                Some('b') | Some('B') => hir::FormatConv::Binary,
+                Some('o') | Some('O') => hir::FormatConv::Octal,
+                Some('d') | Some('D') => hir::FormatConv::Decimal,
+                Some('h') | Some('H') | Some('x') | Some('X') => hir::FormatConv::Hex,
+                Some('c') | Some('C') => hir::FormatConv::Char,
+                Some('s') | Some('S') => hir::FormatConv::String,
+                Some('t') | Some('T') => hir::FormatConv::Time,
+                Some('e') | Some('E') => hir::FormatConv::Exponent,
+                Some('f') | Some('F') => hir::FormatConv::Float,
+                Some('g') | Some('G') => hir::FormatConv::General,
+                Some(c) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("format specification `%{}` not supported", c))
+                            .span(expr.span),
+                    );
+                    failed = true;
+                    continue;
+                }
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error("format string ends with an incomplete specification")
+                            .span(expr.span),
+                    );
+                    failed = true;
+                    continue;
+                }
+            };
+            let spec = hir::FormatSpec {
+                conv,
+                width,
+                precision,
+                implicit: false,
+            };
+            match args.next() {
+                Some(ast::CallArg {
+                    expr: Some(ref arg),
+                    ..
+                }) => {
+                    flush(&mut text, &mut items);
+                    let arg = cx.map_ast_with_parent(AstNode::Expr(arg), node_id);
+                    items.push(hir::FormatItem::Arg(spec, arg));
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("missing argument for `{}`", spec))
+                            .span(expr.span),
+                    );
+                    failed = true;
+                }
+            }
+        }
+    }
+    flush(&mut text, &mut items);
+    if failed {
+        Err(())
+    } else {
+        Ok(items)
+    }
+}
+
 /// Parse a fixed point number into a [`BigRational`].
 ///
 /// The fractional part of the number is optional, such that this function may
@@ -1194,6 +3075,36 @@ fn parse_fixed_point_number<'gcx>(
     }
 }
 
+/// Parse a real number literal such as `1.5e-3` into a floating-point value.
+fn parse_real_number<'gcx>(
+    cx: &impl Context<'gcx>,
+    span: Span,
+    int: Name,
+    frac: Option<Name>,
+    exp: Option<Name>,
+) -> Result<value::Real> {
+    let mut s = int.to_string();
+    if let Some(frac) = frac {
+        s.push('.');
+        s.push_str(&frac.as_str());
+    }
+    if let Some(exp) = exp {
+        s.push('e');
+        s.push_str(&exp.as_str());
+    }
+    match s.parse() {
+        Ok(v) => Ok(value::Real(v)),
+        Err(e) => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` is not a real number literal", s))
+                    .span(span)
+                    .add_note(format!("{}", e)),
+            );
+            Err(())
+        }
+    }
+}
+
 fn lower_event_expr<'gcx>(
     cx: &impl Context<'gcx>,
     expr: &'gcx ast::EventExpr,
@@ -1305,6 +3216,29 @@ fn alloc_var_decl<'gcx>(
     next_rib
 }
 
+/// Allocate node IDs for an instantiation.
+fn alloc_inst<'gcx>(
+    cx: &impl Context<'gcx>,
+    inst: &'gcx ast::Inst,
+    mut next_rib: NodeId,
+    into: &mut Vec<NodeId>,
+) -> NodeId {
+    let target_id = cx.map_ast_with_parent(AstNode::InstTarget(inst), next_rib);
+    next_rib = target_id;
+    trace!(
+        "instantiation target `{}` => {:?}",
+        inst.target.name,
+        target_id
+    );
+    for inst in &inst.names {
+        let inst_id = cx.map_ast_with_parent(AstNode::Inst(inst, target_id), next_rib);
+        trace!("instantiation `{}` => {:?}", inst.name.name, inst_id);
+        next_rib = inst_id;
+        into.push(inst_id);
+    }
+    next_rib
+}
+
 /// Allocate node IDs for a net declaration.
 fn alloc_net_decl<'gcx>(
     cx: &impl Context<'gcx>,
@@ -1322,6 +3256,93 @@ fn alloc_net_decl<'gcx>(
     next_rib
 }
 
+/// Allocate node IDs for the ports of a module or interface.
+///
+/// Returns the next rib and whether the port list is non-ANSI, in which case
+/// the directions and types of the ports are given by port declarations in
+/// the body.
+fn alloc_ports<'gcx>(
+    cx: &impl Context<'gcx>,
+    ports: &'gcx [ast::Port],
+    mut next_rib: NodeId,
+    into: &mut Vec<NodeId>,
+) -> Result<(NodeId, bool)> {
+    let nonansi = ports.first().map(|port| port.is_nonansi()).unwrap_or(false);
+    for port in ports {
+        match *port {
+            ast::Port::Intf { .. } => return cx.unimp(port),
+            ast::Port::Implicit(..) if !nonansi => {
+                cx.emit(
+                    DiagBuilder2::error(format!("{} in ANSI port list", port.desc_full()))
+                        .span(port.span())
+                        .add_note("use an explicit port such as `.name(expr)` instead"),
+                );
+                return Err(());
+            }
+            _ if nonansi && !port.is_nonansi() => {
+                cx.emit(
+                    DiagBuilder2::error(format!("{} in non-ANSI port list", port.desc_full()))
+                        .span(port.span())
+                        .add_note(
+                            "the first port has no direction or type, which makes the port list \
+                             non-ANSI; declare the port in the module body instead",
+                        ),
+                );
+                return Err(());
+            }
+            _ => (),
+        }
+        let id = cx.map_ast(AstNode::Port(port));
+        cx.set_parent(id, next_rib);
+        next_rib = id;
+        into.push(id);
+    }
+    Ok((next_rib, nonansi))
+}
+
+/// Allocate node IDs for a port declaration in a non-ANSI module body.
+///
+/// Names which are also declared as a variable or net in `declared` are
+/// skipped, since that declaration already introduces the signal.
+fn alloc_port_decl<'gcx>(
+    cx: &impl Context<'gcx>,
+    decl: &'gcx ast::PortDecl,
+    declared: &HashMap<Name, Span>,
+    mut next_rib: NodeId,
+    into: &mut Vec<NodeId>,
+) -> Result<NodeId> {
+    // A port declaration with a data type or net type is complete and cannot
+    // be redeclared.
+    let complete = decl.var || decl.net_type.is_some() || decl.ty.data != ast::ImplicitType;
+    let mut type_id = None;
+    for name in &decl.names {
+        if let Some(&span) = declared.get(&name.name) {
+            if complete {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "port `{}` is completely declared and cannot be redeclared",
+                        name.name
+                    ))
+                    .span(span)
+                    .add_note("port declaration with a type was here:")
+                    .span(name.name_span),
+                );
+                return Err(());
+            }
+            continue;
+        }
+        let ty = *type_id.get_or_insert_with(|| {
+            let id = cx.map_ast_with_parent(AstNode::Type(&decl.ty), next_rib);
+            next_rib = id;
+            id
+        });
+        let decl_id = cx.map_ast_with_parent(AstNode::PortDecl(name, decl, ty), next_rib);
+        next_rib = decl_id;
+        into.push(decl_id);
+    }
+    Ok(next_rib)
+}
+
 /// Allocate node IDs for a struct member.
 fn alloc_struct_member<'gcx>(
     cx: &impl Context<'gcx>,
@@ -1348,12 +3369,69 @@ fn lower_package<'gcx>(
     cx: &impl Context<'gcx>,
     node_id: NodeId,
     ast: &'gcx ast::PackageDecl,
+) -> Result<HirNode<'gcx>> {
+    lower_package_items(
+        cx,
+        node_id,
+        Spanned::new(ast.name, ast.name_span),
+        ast.span,
+        ast.items.iter(),
+        None,
+    )
+}
+
+/// Lower the `$unit` scope of a compilation unit.
+///
+/// The items that appear outside of any module, interface, or package are
+/// treated as if they were declared in an implicit package called `$unit`.
+fn lower_unit<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    ast: &'gcx ast::Root,
+) -> Result<HirNode<'gcx>> {
+    let span = ast.span();
+    lower_package_items(
+        cx,
+        node_id,
+        Spanned::new(
+            moore_common::name::get_name_table().intern("$unit", true),
+            span,
+        ),
+        span,
+        ast.items.iter().flat_map(|item| match *item {
+            ast::Item::Item(ref item) => Some(item),
+            _ => None,
+        }),
+        ast.items.iter().flat_map(|item| match *item {
+            ast::Item::Class(ref decl) => Some(decl),
+            _ => None,
+        }),
+    )
+}
+
+/// Lower the items of a package or the `$unit` scope.
+///
+/// Classes are not chained up as ribs, but are made visible throughout the
+/// entire package. The classes in `unit_classes` are the ones declared
+/// outside of any module, interface, or package.
+fn lower_package_items<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    name: Spanned<Name>,
+    span: Span,
+    items: impl Iterator<Item = &'gcx ast::HierarchyItem>,
+    unit_classes: impl IntoIterator<Item = &'gcx ast::ClassDecl>,
 ) -> Result<HirNode<'gcx>> {
     let mut next_rib = node_id;
     let mut names = Vec::new();
     let mut decls = Vec::new();
     let mut params = Vec::new();
-    for item in &ast.items {
+    let mut exports = Vec::new();
+    let mut classes = Vec::new();
+    for decl in unit_classes {
+        classes.push(cx.map_ast_with_parent(AstNode::Class(decl), node_id));
+    }
+    for item in items {
         match *item {
             ast::HierarchyItem::VarDecl(ref decl) => {
                 next_rib = alloc_var_decl(cx, decl, next_rib, &mut decls);
@@ -1365,10 +3443,28 @@ fn lower_package<'gcx>(
                 next_rib = cx.map_ast_with_parent(AstNode::Typedef(def), next_rib);
                 names.push((Spanned::new(def.name.name, def.name.span), next_rib));
             }
-            ast::HierarchyItem::SubroutineDecl(ref decl) => warn!(
-                "ignoring unsupported subroutine `{}`",
-                decl.prototype.name.name
-            ),
+            ast::HierarchyItem::SubroutineDecl(ref decl) => {
+                next_rib = cx.map_ast_with_parent(AstNode::Subroutine(decl), next_rib);
+                let name = &decl.prototype.name;
+                names.push((Spanned::new(name.name, name.span), next_rib));
+            }
+            ast::HierarchyItem::ImportDecl(ref decl) => {
+                for item in &decl.items {
+                    next_rib = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
+                }
+            }
+            ast::HierarchyItem::ClassDecl(ref decl) => {
+                classes.push(cx.map_ast_with_parent(AstNode::Class(decl), next_rib));
+            }
+            ast::HierarchyItem::ExportDecl(ref decl) => {
+                for item in &decl.items {
+                    exports.push(hir::Export {
+                        span: item.span,
+                        pkg: item.pkg.map(|n| Spanned::new(n.name, n.span)),
+                        name: item.name.map(|n| Spanned::new(n.name, n.span)),
+                    });
+                }
+            }
             _ => {
                 cx.emit(
                     DiagBuilder2::error(format!("{} cannot appear in a package", item.desc_full()))
@@ -1381,11 +3477,13 @@ fn lower_package<'gcx>(
 
     let hir = hir::Package {
         id: node_id,
-        name: Spanned::new(ast.name, ast.name_span),
-        span: ast.span,
+        name,
+        span,
         names,
         decls,
         params,
+        exports,
+        classes,
         last_rib: next_rib,
     };
     Ok(HirNode::Package(cx.arena().alloc_hir(hir)))
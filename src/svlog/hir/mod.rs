@@ -21,6 +21,8 @@ make_arenas!(
     /// An arena to allocate HIR nodes into.
     pub struct Arena<'hir> {
         modules: Module<'hir>,
+        interfaces: Interface<'hir>,
+        modports: Modport,
         ports: Port,
         types: Type,
         exprs: Expr,
@@ -33,11 +35,21 @@ make_arenas!(
         stmts: Stmt,
         event_exprs: EventExpr,
         gens: Gen,
+        gen_blocks: GenBlock,
         genvar_decls: GenvarDecl,
         typedefs: Typedef,
         assigns: Assign,
+        defparams: Defparam,
         packages: Package,
         enum_variants: EnumVariant,
+        assertions: Assertion,
+        seq_decls: SeqDecl,
+        prop_decls: PropDecl,
+        seq_ports: SeqPort,
+        subroutines: Subroutine,
+        subroutine_ports: SubroutinePort,
+        classes: Class,
+        constraints: Constraint,
     }
 );
 
@@ -53,6 +65,10 @@ pub(crate) fn accessed_nodes<'gcx>(
             read: Default::default(),
             written: Default::default(),
         },
+        subroutines: match cx.hir_of(node_id)? {
+            HirNode::Subroutine(..) => vec![node_id],
+            _ => vec![],
+        },
     };
     k.visit_node_with_id(node_id, false);
     Ok(Arc::new(k.table))
@@ -64,15 +80,39 @@ pub struct AccessTable {
     /// The node for which the analysis was performed.
     pub node_id: NodeId,
     /// All nodes being read.
-    pub read: BTreeSet<NodeId>,
+    pub read: BTreeSet<AccessedNode>,
     /// All nodes being written.
-    pub written: BTreeSet<NodeId>,
+    pub written: BTreeSet<AccessedNode>,
+}
+
+/// A node accessed by a process or expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessedNode {
+    /// A variable, net, or port.
+    Regular(NodeId),
+    /// A signal of an interface, given as `(binding, signal)`, where `binding`
+    /// is the interface instance or port through which it is accessed.
+    Intf(NodeId, NodeId),
+    /// A declaration in another module, given as the id of the hierarchical
+    /// name through which it is accessed.
+    Hier(NodeId),
+    /// A declaration in an iteration of a generate loop, given as the id of
+    /// the field access through which it is accessed, such as `g_loop[1].x`.
+    GenLoop(NodeId),
+}
+
+impl From<NodeId> for AccessedNode {
+    fn from(id: NodeId) -> AccessedNode {
+        AccessedNode::Regular(id)
+    }
 }
 
 /// A visitor for the HIR that populates an access table.
 struct AccessTableCollector<'a, C> {
     cx: &'a C,
     table: AccessTable,
+    /// The functions and tasks whose bodies have been visited.
+    subroutines: Vec<NodeId>,
 }
 
 impl<'a, 'gcx: 'a, C> Visitor<'gcx> for AccessTableCollector<'a, C>
@@ -89,15 +129,119 @@ where
             ExprKind::Ident(name) => match self.cx.resolve_upwards_or_error(name, expr.id) {
                 Ok(binding) => {
                     if self.is_binding_interesting(binding) {
-                        if lvalue {
-                            self.table.written.insert(binding);
-                        } else {
-                            self.table.read.insert(binding);
-                        }
+                        self.insert(binding.into(), lvalue);
                     }
                 }
                 Err(()) => (),
             },
+            ExprKind::Field(..) => {
+                match self.cx.resolve_intf_signal(expr.id) {
+                    Ok(Some(sig)) => {
+                        if !self.is_local(sig.binding) {
+                            self.insert(AccessedNode::Intf(sig.binding, sig.signal), lvalue);
+                        }
+                        return;
+                    }
+                    Ok(None) => (),
+                    Err(()) => return,
+                }
+                match resolver::resolve_gen_block_field(self.cx, expr.id) {
+                    Ok(Some(binding)) => {
+                        if self.is_binding_interesting(binding) {
+                            match resolver::is_gen_loop_field(self.cx, expr.id) {
+                                Ok(true) => self.insert(AccessedNode::GenLoop(expr.id), lvalue),
+                                Ok(false) => self.insert(binding.into(), lvalue),
+                                Err(()) => (),
+                            }
+                        }
+                        return;
+                    }
+                    Ok(None) => (),
+                    Err(()) => return,
+                }
+                match self.cx.resolve_hier_name(expr.id) {
+                    Ok(Some(hier)) => {
+                        if hier.is_local() {
+                            if self.is_binding_interesting(hier.target) {
+                                self.insert(hier.target.into(), lvalue);
+                            }
+                        } else if !lvalue {
+                            match self.cx.hir_of(hier.target) {
+                                Ok(HirNode::ValueParam(..)) => (),
+                                _ => self.insert(AccessedNode::Hier(hier.id), false),
+                            }
+                        }
+                        return;
+                    }
+                    Ok(None) => (),
+                    Err(()) => return,
+                }
+            }
+            // Calls access their arguments according to the direction of the
+            // corresponding port, as well as everything the called function
+            // or task accesses itself.
+            ExprKind::Call(..) => {
+                if let Ok(mapping) = self.cx.resolve_call(expr.id) {
+                    self.visit_call_mapping(&mapping);
+                }
+                return;
+            }
+            // Constructors access their arguments like calls.
+            ExprKind::New(..) => {
+                let env = self.cx.default_param_env();
+                if let Ok(Some(call)) = self.cx.resolve_class_call(expr.id, env) {
+                    if let Some(ref mapping) = call.mapping {
+                        self.visit_call_mapping(mapping);
+                    }
+                }
+                return;
+            }
+            // Assigning to an element of a dynamic array, queue, or
+            // associative array also reads the container it modifies.
+            ExprKind::Index(target, _) if lvalue => {
+                let env = self.cx.default_param_env();
+                match self.cx.type_of(target, env) {
+                    Ok(ty) if ty.is_dynamic() => self.visit_node_with_id(target, false),
+                    _ => (),
+                }
+            }
+            // Methods of class objects access their arguments like calls.
+            // Container methods read their target, and modify it if they
+            // update the container. The associative array traversal methods
+            // additionally write to their argument.
+            ExprKind::MethodCall(target, name, ref args) => {
+                let env = self.cx.default_param_env();
+                match self.cx.resolve_class_call(expr.id, env) {
+                    Ok(Some(call)) => {
+                        self.visit_node_with_id(target, false);
+                        if let Some(ref mapping) = call.mapping {
+                            self.visit_call_mapping(mapping);
+                        }
+                        return;
+                    }
+                    Ok(None) => (),
+                    Err(()) => return,
+                }
+                let (updates, writes_args) = match &*name.value.as_str() {
+                    "delete" | "insert" | "push_front" | "push_back" | "pop_front" | "pop_back"
+                    | "putc" | "itoa" => (true, false),
+                    "first" | "last" | "next" | "prev" => (false, true),
+                    _ => (false, false),
+                };
+                self.visit_node_with_id(target, false);
+                if updates {
+                    self.visit_node_with_id(target, true);
+                }
+                for arg in args {
+                    if let Some(expr) = arg.expr {
+                        self.visit_node_with_id(expr, false);
+                        if writes_args {
+                            self.visit_node_with_id(expr, true);
+                        }
+                    }
+                }
+                return;
+            }
             _ => (),
         }
         walk_expr(self, expr, lvalue)
@@ -108,8 +252,60 @@ impl<'a, 'gcx: 'a, C> AccessTableCollector<'a, C>
 where
     C: Context<'gcx>,
 {
+    /// Visit the arguments of a call according to the direction of the
+    /// corresponding port, as well as the body of the called function or
+    /// task.
+    fn visit_call_mapping(&mut self, mapping: &resolver::CallMapping) {
+        for &(port_id, arg_id) in &mapping.args {
+            let (read, written) = match self.cx.hir_of(port_id) {
+                Ok(HirNode::SubroutinePort(port)) => match port.dir {
+                    ast::SubroutinePortDir::Input | ast::SubroutinePortDir::ConstRef => {
+                        (true, false)
+                    }
+                    ast::SubroutinePortDir::Output => (false, true),
+                    ast::SubroutinePortDir::Inout | ast::SubroutinePortDir::Ref => (true, true),
+                },
+                _ => (true, false),
+            };
+            if read {
+                self.visit_node_with_id(arg_id, false);
+            }
+            if written {
+                self.visit_node_with_id(arg_id, true);
+            }
+        }
+        let sub = mapping.subroutine;
+        if !self.subroutines.contains(&sub) {
+            self.subroutines.push(sub);
+            self.visit_node_with_id(sub, false);
+        }
+    }
+
+    fn insert(&mut self, node: AccessedNode, lvalue: bool) {
+        if lvalue {
+            self.table.written.insert(node);
+        } else {
+            self.table.read.insert(node);
+        }
+    }
+
+    /// Check if a node is declared within the analyzed node or one of the
+    /// functions and tasks it calls.
+    fn is_local(&self, node: NodeId) -> bool {
+        if self
+            .subroutines
+            .iter()
+            .any(|&sub| resolver::is_within_subroutine(self.cx, sub, node))
+        {
+            return true;
+        }
+        !self.subroutines.contains(&self.table.node_id)
+            && self.cx.is_parent_of(self.table.node_id, node)
+    }
+
     fn is_binding_interesting(&self, binding: NodeId) -> bool {
-        if self.cx.is_parent_of(self.table.node_id, binding) {
+        // Class properties are stored in objects rather than signals.
+        if self.is_local(binding) || resolver::declaring_class(self.cx, binding).is_some() {
             return false;
         }
         match self.cx.hir_of(binding) {
@@ -118,10 +314,74 @@ where
             Ok(HirNode::GenvarDecl(..)) => return false,
             Ok(HirNode::EnumVariant(..)) => return false,
             Ok(HirNode::Package(..)) => return false,
+            Ok(HirNode::SeqDecl(..)) => return false,
+            Ok(HirNode::PropDecl(..)) => return false,
             Ok(HirNode::Module(..)) => return false,
+            Ok(HirNode::Interface(..)) => return false,
+            Ok(HirNode::Modport(..)) => return false,
+            Ok(HirNode::Inst(..)) => return false,
+            Ok(HirNode::Subroutine(..)) => return false,
+            Ok(HirNode::SubroutinePort(..)) => return false,
+            Ok(HirNode::Class(..)) => return false,
             Err(_) => return false,
             _ => (),
         }
         true
     }
 }
+
+/// Check whether a task may suspend execution.
+///
+/// This is the case if the task contains a timing control, `wait`, or `fork`
+/// statement, or calls another task which does.
+pub(crate) fn task_consumes_time<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> bool {
+    let mut k = TimingCollector {
+        cx,
+        subroutines: vec![node_id],
+        consumes_time: false,
+    };
+    k.visit_node_with_id(node_id, false);
+    k.consumes_time
+}
+
+/// A visitor for the HIR that looks for statements which suspend execution.
+struct TimingCollector<'a, C> {
+    cx: &'a C,
+    /// The tasks whose bodies have been visited.
+    subroutines: Vec<NodeId>,
+    /// Whether any such statement was found.
+    consumes_time: bool,
+}
+
+impl<'a, 'gcx: 'a, C> Visitor<'gcx> for TimingCollector<'a, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+    fn context(&self) -> &C {
+        self.cx
+    }
+
+    fn visit_stmt(&mut self, stmt: &'gcx Stmt) {
+        match stmt.kind {
+            StmtKind::Timed { .. }
+            | StmtKind::Wait { .. }
+            | StmtKind::Fork { .. }
+            | StmtKind::WaitFork => self.consumes_time = true,
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx Expr, lvalue: bool) {
+        if let ExprKind::Call(..) = expr.kind {
+            if let Ok(mapping) = self.cx.resolve_call(expr.id) {
+                let sub = mapping.subroutine;
+                if !self.subroutines.contains(&sub) {
+                    self.subroutines.push(sub);
+                    self.visit_node_with_id(sub, false);
+                }
+            }
+        }
+        walk_expr(self, expr, lvalue)
+    }
+}
@@ -10,6 +10,8 @@ use num::{BigInt, BigRational};
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum HirNode<'hir> {
     Module(&'hir Module<'hir>),
+    Interface(&'hir Interface<'hir>),
+    Modport(&'hir Modport),
     Port(&'hir Port),
     Type(&'hir Type),
     Expr(&'hir Expr),
@@ -22,17 +24,29 @@ pub enum HirNode<'hir> {
     Stmt(&'hir Stmt),
     EventExpr(&'hir EventExpr),
     Gen(&'hir Gen),
+    GenBlock(&'hir GenBlock),
     GenvarDecl(&'hir GenvarDecl),
     Typedef(&'hir Typedef),
     Assign(&'hir Assign),
+    Defparam(&'hir Defparam),
     Package(&'hir Package),
     EnumVariant(&'hir EnumVariant),
+    Assertion(&'hir Assertion),
+    SeqDecl(&'hir SeqDecl),
+    PropDecl(&'hir PropDecl),
+    SeqPort(&'hir SeqPort),
+    Subroutine(&'hir Subroutine),
+    SubroutinePort(&'hir SubroutinePort),
+    Class(&'hir Class),
+    Constraint(&'hir Constraint),
 }
 
 impl<'hir> HasSpan for HirNode<'hir> {
     fn span(&self) -> Span {
         match *self {
             HirNode::Module(x) => x.span(),
+            HirNode::Interface(x) => x.span(),
+            HirNode::Modport(x) => x.span(),
             HirNode::Port(x) => x.span(),
             HirNode::Type(x) => x.span(),
             HirNode::Expr(x) => x.span(),
@@ -45,17 +59,29 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Stmt(x) => x.span(),
             HirNode::EventExpr(x) => x.span(),
             HirNode::Gen(x) => x.span(),
+            HirNode::GenBlock(x) => x.span(),
             HirNode::GenvarDecl(x) => x.span(),
             HirNode::Typedef(x) => x.span(),
             HirNode::Assign(x) => x.span(),
+            HirNode::Defparam(x) => x.span(),
             HirNode::Package(x) => x.span(),
             HirNode::EnumVariant(x) => x.span(),
+            HirNode::Assertion(x) => x.span(),
+            HirNode::SeqDecl(x) => x.span(),
+            HirNode::PropDecl(x) => x.span(),
+            HirNode::SeqPort(x) => x.span(),
+            HirNode::Subroutine(x) => x.span(),
+            HirNode::SubroutinePort(x) => x.span(),
+            HirNode::Class(x) => x.span(),
+            HirNode::Constraint(x) => x.span(),
         }
     }
 
     fn human_span(&self) -> Span {
         match *self {
             HirNode::Module(x) => x.human_span(),
+            HirNode::Interface(x) => x.human_span(),
+            HirNode::Modport(x) => x.human_span(),
             HirNode::Port(x) => x.human_span(),
             HirNode::Type(x) => x.human_span(),
             HirNode::Expr(x) => x.human_span(),
@@ -68,11 +94,21 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Stmt(x) => x.human_span(),
             HirNode::EventExpr(x) => x.human_span(),
             HirNode::Gen(x) => x.human_span(),
+            HirNode::GenBlock(x) => x.human_span(),
             HirNode::GenvarDecl(x) => x.human_span(),
             HirNode::Typedef(x) => x.human_span(),
             HirNode::Assign(x) => x.human_span(),
+            HirNode::Defparam(x) => x.human_span(),
             HirNode::Package(x) => x.human_span(),
             HirNode::EnumVariant(x) => x.human_span(),
+            HirNode::Assertion(x) => x.human_span(),
+            HirNode::SeqDecl(x) => x.human_span(),
+            HirNode::PropDecl(x) => x.human_span(),
+            HirNode::SeqPort(x) => x.human_span(),
+            HirNode::Subroutine(x) => x.human_span(),
+            HirNode::SubroutinePort(x) => x.human_span(),
+            HirNode::Class(x) => x.human_span(),
+            HirNode::Constraint(x) => x.human_span(),
         }
     }
 }
@@ -81,6 +117,8 @@ impl<'hir> HasDesc for HirNode<'hir> {
     fn desc(&self) -> &'static str {
         match *self {
             HirNode::Module(x) => x.desc(),
+            HirNode::Interface(x) => x.desc(),
+            HirNode::Modport(x) => x.desc(),
             HirNode::Port(x) => x.desc(),
             HirNode::Type(x) => x.desc(),
             HirNode::Expr(x) => x.desc(),
@@ -93,17 +131,29 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Stmt(x) => x.desc(),
             HirNode::EventExpr(x) => x.desc(),
             HirNode::Gen(x) => x.desc(),
+            HirNode::GenBlock(x) => x.desc(),
             HirNode::GenvarDecl(x) => x.desc(),
             HirNode::Typedef(x) => x.desc(),
             HirNode::Assign(x) => x.desc(),
+            HirNode::Defparam(x) => x.desc(),
             HirNode::Package(x) => x.desc(),
             HirNode::EnumVariant(x) => x.desc(),
+            HirNode::Assertion(x) => x.desc(),
+            HirNode::SeqDecl(x) => x.desc(),
+            HirNode::PropDecl(x) => x.desc(),
+            HirNode::SeqPort(x) => x.desc(),
+            HirNode::Subroutine(x) => x.desc(),
+            HirNode::SubroutinePort(x) => x.desc(),
+            HirNode::Class(x) => x.desc(),
+            HirNode::Constraint(x) => x.desc(),
         }
     }
 
     fn desc_full(&self) -> String {
         match *self {
             HirNode::Module(x) => x.desc_full(),
+            HirNode::Interface(x) => x.desc_full(),
+            HirNode::Modport(x) => x.desc_full(),
             HirNode::Port(x) => x.desc_full(),
             HirNode::Type(x) => x.desc_full(),
             HirNode::Expr(x) => x.desc_full(),
@@ -116,11 +166,21 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Stmt(x) => x.desc_full(),
             HirNode::EventExpr(x) => x.desc_full(),
             HirNode::Gen(x) => x.desc_full(),
+            HirNode::GenBlock(x) => x.desc_full(),
             HirNode::GenvarDecl(x) => x.desc_full(),
             HirNode::Typedef(x) => x.desc_full(),
             HirNode::Assign(x) => x.desc_full(),
+            HirNode::Defparam(x) => x.desc_full(),
             HirNode::Package(x) => x.desc_full(),
             HirNode::EnumVariant(x) => x.desc_full(),
+            HirNode::Assertion(x) => x.desc_full(),
+            HirNode::SeqDecl(x) => x.desc_full(),
+            HirNode::PropDecl(x) => x.desc_full(),
+            HirNode::SeqPort(x) => x.desc_full(),
+            HirNode::Subroutine(x) => x.desc_full(),
+            HirNode::SubroutinePort(x) => x.desc_full(),
+            HirNode::Class(x) => x.desc_full(),
+            HirNode::Constraint(x) => x.desc_full(),
         }
     }
 }
@@ -131,6 +191,8 @@ pub struct Module<'hir> {
     pub id: NodeId,
     pub name: Spanned<Name>,
     pub span: Span,
+    /// The attributes attached to the module.
+    pub attrs: Vec<Attr>,
     /// The ports of the module.
     pub ports: &'hir [NodeId],
     /// The parameters of the module.
@@ -159,6 +221,91 @@ impl HasDesc for Module<'_> {
     }
 }
 
+/// An interface.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Interface<'hir> {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The attributes attached to the interface.
+    pub attrs: Vec<Attr>,
+    /// The ports of the interface.
+    pub ports: &'hir [NodeId],
+    /// The parameters of the interface.
+    pub params: &'hir [NodeId],
+    /// The contents of the interface.
+    pub block: ModuleBlock,
+    /// The modports declared in the interface.
+    pub modports: Vec<NodeId>,
+    /// The bottom of the name scope tree.
+    pub last_rib: NodeId,
+}
+
+impl HasSpan for Interface<'_> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Interface<'_> {
+    fn desc(&self) -> &'static str {
+        "interface"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("interface `{}`", self.name.value)
+    }
+}
+
+/// A modport of an interface.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Modport {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The signals exposed by the modport.
+    pub ports: Vec<ModportPort>,
+}
+
+impl Modport {
+    /// Find the signal with a given name.
+    pub fn find(&self, name: Name) -> Option<&ModportPort> {
+        self.ports.iter().find(|p| p.name.value == name)
+    }
+}
+
+impl HasSpan for Modport {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Modport {
+    fn desc(&self) -> &'static str {
+        "modport"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("modport `{}`", self.name.value)
+    }
+}
+
+/// A signal exposed by a modport, together with its direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModportPort {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub dir: ast::PortDir,
+}
+
 /// The contents of a module.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModuleBlock {
@@ -174,6 +321,16 @@ pub struct ModuleBlock {
     pub params: Vec<NodeId>,
     /// The continuous assignments in the module.
     pub assigns: Vec<NodeId>,
+    /// The parameter overrides in the module.
+    pub defparams: Vec<NodeId>,
+    /// The concurrent assertions in the module.
+    pub assertions: Vec<NodeId>,
+    /// The functions and tasks declared in the module.
+    pub subroutines: Vec<NodeId>,
+    /// The classes declared in the module.
+    pub classes: Vec<NodeId>,
+    /// The bottom of the name scope tree.
+    pub last_rib: NodeId,
 }
 
 /// An instantiation target.
@@ -225,6 +382,8 @@ pub struct Inst<'hir> {
     pub id: NodeId,
     pub name: Spanned<Name>,
     pub span: Span,
+    /// The attributes attached to the instantiation.
+    pub attrs: Vec<Attr>,
     /// The target of the instantiation.
     pub target: NodeId,
     /// The positional port connections.
@@ -366,8 +525,12 @@ pub struct Port {
     pub name: Spanned<Name>,
     pub span: Span,
     pub dir: ast::PortDir,
-    pub ty: NodeId,
+    /// The type of the port, or `None` if the port is given as an expression.
+    pub ty: Option<NodeId>,
     pub default: Option<NodeId>,
+    /// The expression that connects the port to the declarations in the
+    /// module body, as given in a non-ANSI or explicit port, e.g. `.a(b)`.
+    pub expr: Option<NodeId>,
 }
 
 impl HasSpan for Port {
@@ -498,6 +661,9 @@ pub enum TypeKind {
     /// Each element in the vector refers to a `EnumVariant`. The optional field
     /// indicates the representation type.
     Enum(Vec<(Spanned<Name>, NodeId)>, Option<NodeId>),
+    /// A parameterized class type such as `foo#(8, .T(bit))`, given as
+    /// `(base, pos_params, named_params)`.
+    Specialized(NodeId, Vec<PosParam>, Vec<NamedParam>),
 }
 
 impl HasDesc for TypeKind {
@@ -540,6 +706,9 @@ pub enum BuiltinType {
     LongInt,
     Time,
     String,
+    Real,
+    ShortReal,
+    Event,
 }
 
 /// An expression.
@@ -562,6 +731,7 @@ impl HasDesc for Expr {
         match self.kind {
             ExprKind::IntConst { .. } => "integer constant",
             ExprKind::TimeConst(_) => "time constant",
+            ExprKind::RealConst(_) => "real constant",
             ExprKind::Ident(_) => "identifier",
             _ => "expression",
         }
@@ -572,11 +742,13 @@ impl HasDesc for Expr {
         match self.kind {
             ExprKind::IntConst { value: ref k, .. } => format!("{} `{}`", self.desc(), k),
             ExprKind::TimeConst(ref k) => format!("{} `{}`", self.desc(), k),
+            ExprKind::RealConst(k) => format!("{} `{}`", self.desc(), k.0),
             ExprKind::Ident(n) => format!("`{}`", n.value),
             ExprKind::PositionalPattern(..) => format!("positional pattern"),
             ExprKind::NamedPattern(..) => format!("named pattern"),
             ExprKind::RepeatPattern(..) => format!("repeat pattern"),
             ExprKind::EmptyPattern => format!("empty pattern"),
+            ExprKind::PatternVar(Some(n)) => format!("pattern variable `{}`", n.value),
             _ => format!("{} `{}`", self.desc(), self.span().extract()),
         }
     }
@@ -600,6 +772,8 @@ pub enum ExprKind {
     UnsizedConst(char),
     /// A time constant literal.
     TimeConst(BigRational),
+    /// A real constant literal.
+    RealConst(value::Real),
     /// A string constant literal.
     StringConst(Spanned<Name>),
     /// An identifier.
@@ -632,6 +806,27 @@ pub enum ExprKind {
     Cast(NodeId, NodeId),
     /// An inside expression such as `a inside {b, c}`.
     Inside(NodeId, Vec<Spanned<InsideRange>>),
+    /// A function or task call such as `foo(a, .b(c))`.
+    Call(NodeId, Vec<CallArg>),
+    /// A method call such as `q.push_back(a)`.
+    MethodCall(NodeId, Spanned<Name>, Vec<CallArg>),
+    /// A dynamic array constructor such as `new[n]` or `new[n](a)`.
+    ArrayNew(NodeId, Option<NodeId>),
+    /// A tagged union expression or pattern such as `tagged Valid 42`.
+    Tagged(Spanned<Name>, Option<NodeId>),
+    /// A pattern variable such as `.v`, or the wildcard pattern `.*`.
+    PatternVar(Option<Spanned<Name>>),
+    /// A class constructor call such as `new` or `new(a, b)`.
+    New(Vec<CallArg>),
+    /// The `this` handle.
+    This,
+    /// The `super` handle.
+    Super,
+    /// The `null` handle.
+    Null,
+    /// A call to `randomize()`, given as the randomized object, the variables
+    /// passed as arguments, and the inline constraints of a `with` clause.
+    Randomize(RandomizeTarget, Vec<NodeId>, Vec<ConstraintItem>),
 }
 
 /// The different unary operators.
@@ -707,6 +902,14 @@ pub enum BinaryOp {
     Eq,
     /// The inequality operator `x != y`.
     Neq,
+    /// The case equality operator `x === y`.
+    CaseEq,
+    /// The case inequality operator `x !== y`.
+    CaseNeq,
+    /// The wildcard equality operator `x ==? y`.
+    WildcardEq,
+    /// The wildcard inequality operator `x !=? y`.
+    WildcardNeq,
     /// The less-than operator `x < y`.
     Lt,
     /// The less-than-or-equal operator `x <= y`.
@@ -752,6 +955,10 @@ impl HasDesc for BinaryOp {
             BinaryOp::Pow => "`**` operator",
             BinaryOp::Eq => "`==` operator",
             BinaryOp::Neq => "`!=` operator",
+            BinaryOp::CaseEq => "`===` operator",
+            BinaryOp::CaseNeq => "`!==` operator",
+            BinaryOp::WildcardEq => "`==?` operator",
+            BinaryOp::WildcardNeq => "`!=?` operator",
             BinaryOp::Lt => "`<` operator",
             BinaryOp::Leq => "`<=` operator",
             BinaryOp::Gt => "`>` operator",
@@ -781,8 +988,18 @@ pub enum IndexMode {
     Many(ast::RangeMode, NodeId, NodeId),
 }
 
+/// An argument of a function or task call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallArg {
+    pub span: Span,
+    /// The name of the port, for named arguments such as `.foo(x)`.
+    pub name: Option<Spanned<Name>>,
+    /// The argument expression, or `None` if the argument was left empty.
+    pub expr: Option<NodeId>,
+}
+
 /// The different builtin function calls that are supported.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BuiltinCall {
     /// An unsupported builtin. Will yield constant 0.
     Unsupported,
@@ -794,6 +1011,345 @@ pub enum BuiltinCall {
     Signed(NodeId),
     /// A call to the convert-to-unsigned function `$unsigned(x)`.
     Unsigned(NodeId),
+    /// A call to an array query function, e.g. `$left(x)` or `$size(x, 2)`.
+    ArrayQuery(ArrayQuery, NodeId, Option<NodeId>),
+    /// A call to a real conversion function, e.g. `$rtoi(x)`.
+    RealConv(RealConv, NodeId),
+    /// A call to a bit vector function, e.g. `$countones(x)`.
+    BitQuery(BitQuery, NodeId),
+    /// A call to a real math function, e.g. `$ln(x)` or `$pow(x, y)`.
+    RealMath(RealMath, NodeId, Option<NodeId>),
+    /// A call to the simulation time function `$realtime`.
+    RealTime,
+    /// A call to the simulation time function `$time`.
+    Time,
+    /// A call to the simulation time function `$stime`.
+    STime,
+    /// A call to a display task, e.g. `$display("%d", x)`.
+    Display(DisplayTask, Vec<FormatItem>),
+    /// A call to a severity task, e.g. `$error("%d", x)`. Carries the finish
+    /// number of a `$fatal`, if one was provided.
+    Severity(SeverityTask, Option<NodeId>, Vec<FormatItem>),
+    /// A call to a simulation control task, e.g. `$finish(1)`.
+    SimControl(SimControl, Option<NodeId>),
+}
+
+/// The different display tasks.
+///
+/// See IEEE 1800-2017 §21.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayTask {
+    /// `$display`, which terminates the output with a newline.
+    Display,
+    /// `$write`
+    Write,
+}
+
+/// The different severity tasks.
+///
+/// See IEEE 1800-2017 §20.10.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeverityTask {
+    /// `$info(...)`
+    Info,
+    /// `$warning(...)`
+    Warning,
+    /// `$error(...)`
+    Error,
+    /// `$fatal(finish_number, ...)`
+    Fatal,
+}
+
+/// The different simulation control tasks.
+///
+/// See IEEE 1800-2017 §20.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimControl {
+    /// `$finish(n)`
+    Finish,
+    /// `$stop(n)`
+    Stop,
+}
+
+/// A piece of the output of a display or severity task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatItem {
+    /// Literal text.
+    Text(String),
+    /// An argument formatted according to a format specification.
+    Arg(FormatSpec, NodeId),
+    /// The hierarchical name of the current scope, i.e. `%m`.
+    Scope,
+}
+
+/// A format specification, e.g. the `%0d` in `$display("%0d", x)`.
+///
+/// See IEEE 1800-2017 §21.2.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatSpec {
+    pub conv: FormatConv,
+    /// The field width, e.g. the `4` in `%4d`.
+    pub width: Option<usize>,
+    /// The precision, e.g. the `2` in `%.2f`.
+    pub precision: Option<usize>,
+    /// Whether the specification is implied by an argument without a format
+    /// string, e.g. the `x` in `$display(x)`. In this case strings and reals
+    /// are formatted as such rather than in the task's default radix.
+    pub implicit: bool,
+}
+
+/// The different format conversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatConv {
+    /// `%b`
+    Binary,
+    /// `%o`
+    Octal,
+    /// `%d`
+    Decimal,
+    /// `%h` or `%x`
+    Hex,
+    /// `%c`
+    Char,
+    /// `%s`
+    String,
+    /// `%t`
+    Time,
+    /// `%e`
+    Exponent,
+    /// `%f`
+    Float,
+    /// `%g`
+    General,
+}
+
+impl FormatConv {
+    /// Check whether the conversion formats an integral value.
+    pub fn is_integral(self) -> bool {
+        match self {
+            FormatConv::Binary
+            | FormatConv::Octal
+            | FormatConv::Decimal
+            | FormatConv::Hex
+            | FormatConv::Char
+            | FormatConv::Time => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether the conversion formats a real value.
+    pub fn is_real(self) -> bool {
+        match self {
+            FormatConv::Exponent | FormatConv::Float | FormatConv::General => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "%")?;
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        let c = match self.conv {
+            FormatConv::Binary => 'b',
+            FormatConv::Octal => 'o',
+            FormatConv::Decimal => 'd',
+            FormatConv::Hex => 'h',
+            FormatConv::Char => 'c',
+            FormatConv::String => 's',
+            FormatConv::Time => 't',
+            FormatConv::Exponent => 'e',
+            FormatConv::Float => 'f',
+            FormatConv::General => 'g',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+/// The different array query functions.
+///
+/// See IEEE 1800-2017 §20.7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayQuery {
+    /// `$left(x, dim)`
+    Left,
+    /// `$right(x, dim)`
+    Right,
+    /// `$low(x, dim)`
+    Low,
+    /// `$high(x, dim)`
+    High,
+    /// `$increment(x, dim)`
+    Increment,
+    /// `$size(x, dim)`
+    Size,
+    /// `$dimensions(x)`
+    Dimensions,
+    /// `$unpacked_dimensions(x)`
+    UnpackedDimensions,
+}
+
+/// The different real conversion functions.
+///
+/// See IEEE 1800-2017 §20.5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RealConv {
+    /// `$rtoi(x)`, which truncates towards zero.
+    RealToInt,
+    /// `$itor(x)`
+    IntToReal,
+    /// `$realtobits(x)`
+    RealToBits,
+    /// `$bitstoreal(x)`
+    BitsToReal,
+    /// `$shortrealtobits(x)`
+    ShortRealToBits,
+    /// `$bitstoshortreal(x)`
+    BitsToShortReal,
+}
+
+/// The different bit vector functions.
+///
+/// See IEEE 1800-2017 §20.9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitQuery {
+    /// `$countones(x)`
+    CountOnes,
+    /// `$onehot(x)`, which checks that exactly one bit is set.
+    OneHot,
+    /// `$onehot0(x)`, which checks that at most one bit is set.
+    OneHot0,
+    /// `$isunknown(x)`, which checks whether any bit is x or z.
+    IsUnknown,
+}
+
+/// The different real math functions.
+///
+/// See IEEE 1800-2017 §20.8.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum RealMath {
+    Ln,
+    Log10,
+    Exp,
+    Sqrt,
+    Pow,
+    Floor,
+    Ceil,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Hypot,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
+}
+
+impl RealMath {
+    /// Check whether the function takes two arguments, e.g. `$pow(x, y)`.
+    pub fn is_binary(self) -> bool {
+        match self {
+            RealMath::Pow | RealMath::Atan2 | RealMath::Hypot => true,
+            _ => false,
+        }
+    }
+
+    /// Get the name of the function, without the leading `$`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RealMath::Ln => "ln",
+            RealMath::Log10 => "log10",
+            RealMath::Exp => "exp",
+            RealMath::Sqrt => "sqrt",
+            RealMath::Pow => "pow",
+            RealMath::Floor => "floor",
+            RealMath::Ceil => "ceil",
+            RealMath::Sin => "sin",
+            RealMath::Cos => "cos",
+            RealMath::Tan => "tan",
+            RealMath::Asin => "asin",
+            RealMath::Acos => "acos",
+            RealMath::Atan => "atan",
+            RealMath::Atan2 => "atan2",
+            RealMath::Hypot => "hypot",
+            RealMath::Sinh => "sinh",
+            RealMath::Cosh => "cosh",
+            RealMath::Tanh => "tanh",
+            RealMath::Asinh => "asinh",
+            RealMath::Acosh => "acosh",
+            RealMath::Atanh => "atanh",
+        }
+    }
+
+    /// Evaluate the function.
+    ///
+    /// The second argument is ignored by unary functions.
+    pub fn apply(self, x: f64, y: f64) -> f64 {
+        match self {
+            RealMath::Ln => x.ln(),
+            RealMath::Log10 => x.log10(),
+            RealMath::Exp => x.exp(),
+            RealMath::Sqrt => x.sqrt(),
+            RealMath::Pow => x.powf(y),
+            RealMath::Floor => x.floor(),
+            RealMath::Ceil => x.ceil(),
+            RealMath::Sin => x.sin(),
+            RealMath::Cos => x.cos(),
+            RealMath::Tan => x.tan(),
+            RealMath::Asin => x.asin(),
+            RealMath::Acos => x.acos(),
+            RealMath::Atan => x.atan(),
+            RealMath::Atan2 => x.atan2(y),
+            RealMath::Hypot => x.hypot(y),
+            RealMath::Sinh => x.sinh(),
+            RealMath::Cosh => x.cosh(),
+            RealMath::Tanh => x.tanh(),
+            RealMath::Asinh => x.asinh(),
+            RealMath::Acosh => x.acosh(),
+            RealMath::Atanh => x.atanh(),
+        }
+    }
+}
+
+/// An attribute, e.g. the `keep` in `(* keep *)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attr {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The optional value assigned to the attribute.
+    pub value: Option<NodeId>,
+}
+
+impl HasSpan for Attr {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Attr {
+    fn desc(&self) -> &'static str {
+        "attribute"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("attribute `{}`", self.name.value)
+    }
 }
 
 /// A variable declaration.
@@ -802,10 +1358,58 @@ pub struct VarDecl {
     pub id: NodeId,
     pub name: Spanned<Name>,
     pub span: Span,
+    pub attrs: Vec<Attr>,
+    /// Whether this declares a variable or a net.
+    pub kind: VarKind,
     pub ty: NodeId,
+    /// The unpacked dimensions following the variable name.
+    pub dims: Vec<UnpackedDim>,
     pub init: Option<NodeId>,
 }
 
+/// Whether a declaration introduces a variable or a net.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarKind {
+    /// A variable.
+    Var,
+    /// A net, such as `wire` or `supply0`.
+    Net {
+        /// The net type.
+        ty: ast::NetType,
+        /// Whether the net is `vectored` or `scalared`.
+        kind: ast::NetKind,
+        /// The optional drive or charge strength of the net.
+        strength: Option<ast::NetStrength>,
+    },
+}
+
+impl VarKind {
+    /// Check whether this is a net.
+    pub fn is_net(&self) -> bool {
+        match *self {
+            VarKind::Net { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// An unpacked array dimension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnpackedDim {
+    /// A dimension given by its size, e.g. `[8]`, equivalent to `[0:7]`.
+    Size(NodeId),
+    /// A dimension given as a range, e.g. `[0:7]`.
+    Range(NodeId, NodeId),
+    /// A dynamic array dimension, e.g. `[]`.
+    Unsized,
+    /// A queue dimension with an optional maximum index, e.g. `[$]` or
+    /// `[$:15]`.
+    Queue(Option<NodeId>),
+    /// An associative array dimension with an optional index type, e.g.
+    /// `[string]` or `[*]`.
+    Associative(Option<NodeId>),
+}
+
 impl HasSpan for VarDecl {
     fn span(&self) -> Span {
         self.span
@@ -818,11 +1422,17 @@ impl HasSpan for VarDecl {
 
 impl HasDesc for VarDecl {
     fn desc(&self) -> &'static str {
-        "variable declaration"
+        match self.kind {
+            VarKind::Var => "variable declaration",
+            VarKind::Net { .. } => "net declaration",
+        }
     }
 
     fn desc_full(&self) -> String {
-        format!("variable `{}`", self.name.value)
+        match self.kind {
+            VarKind::Var => format!("variable `{}`", self.name.value),
+            VarKind::Net { .. } => format!("net `{}`", self.name.value),
+        }
     }
 }
 
@@ -831,6 +1441,7 @@ impl HasDesc for VarDecl {
 pub struct Proc {
     pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<Attr>,
     pub kind: ast::ProcedureKind,
     pub stmt: NodeId,
 }
@@ -860,6 +1471,7 @@ pub struct Stmt {
     pub id: NodeId,
     pub label: Option<Spanned<Name>>,
     pub span: Span,
+    pub attrs: Vec<Attr>,
     pub kind: StmtKind,
 }
 
@@ -880,6 +1492,12 @@ impl HasDesc for Stmt {
             StmtKind::Null => "null statement",
             StmtKind::Block(_) => "block",
             StmtKind::Assign { .. } => "assign statement",
+            StmtKind::Fork { .. } => "parallel block",
+            StmtKind::Wait { .. } => "wait statement",
+            StmtKind::WaitFork => "`wait fork` statement",
+            StmtKind::DisableFork => "`disable fork` statement",
+            StmtKind::Disable(..) => "disable statement",
+            StmtKind::Trigger(..) => "event trigger",
             _ => "statement",
         }
     }
@@ -931,6 +1549,37 @@ pub enum StmtKind {
         default: Option<NodeId>,
         kind: ast::CaseKind,
     },
+    /// A pattern matching case statement, i.e. `case (expr) matches`.
+    ///
+    /// Each way is given as `(pattern, stmt)`.
+    CaseMatches {
+        expr: NodeId,
+        ways: Vec<(NodeId, NodeId)>,
+        default: Option<NodeId>,
+    },
+    /// An assertion statement.
+    Assertion(NodeId),
+    /// A return statement.
+    Return(Option<NodeId>),
+    /// A parallel block, i.e. `fork ... join`, `join_any`, or `join_none`.
+    Fork {
+        stmts: Vec<NodeId>,
+        join: ast::JoinKind,
+    },
+    /// A wait statement.
+    ///
+    /// ```text
+    /// wait (<cond>) <stmt>
+    /// ```
+    Wait { cond: NodeId, stmt: NodeId },
+    /// A `wait fork` statement.
+    WaitFork,
+    /// A `disable fork` statement.
+    DisableFork,
+    /// A `disable` statement, naming the block to be disabled.
+    Disable(Spanned<Name>),
+    /// An event trigger, i.e. `-> e`.
+    Trigger(NodeId),
 }
 
 /// The different forms an assignment can take.
@@ -1022,23 +1671,83 @@ impl HasDesc for Gen {
 }
 
 /// The different forms a generate statement can take.
+///
+/// The bodies refer to `GenBlock` nodes.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GenKind {
     /// An if-generate statement.
     If {
         cond: NodeId,
-        main_body: ModuleBlock,
-        else_body: Option<ModuleBlock>,
+        main_body: NodeId,
+        else_body: Option<NodeId>,
     },
     /// A for-generate statement.
     For {
         init: Vec<NodeId>,
         cond: NodeId,
         step: NodeId,
-        body: ModuleBlock,
+        body: NodeId,
+    },
+    /// A case-generate statement. Each way is given as `(exprs, body)`.
+    Case {
+        expr: NodeId,
+        ways: Vec<(Vec<NodeId>, NodeId)>,
+        default: Option<NodeId>,
     },
 }
 
+impl GenKind {
+    /// Get the blocks of the generate statement.
+    pub fn blocks(&self) -> Vec<NodeId> {
+        match *self {
+            GenKind::If {
+                main_body,
+                else_body,
+                ..
+            } => std::iter::once(main_body).chain(else_body).collect(),
+            GenKind::For { body, .. } => vec![body],
+            GenKind::Case {
+                ref ways, default, ..
+            } => ways.iter().map(|&(_, body)| body).chain(default).collect(),
+        }
+    }
+}
+
+/// A block of a generate statement.
+///
+/// The block forms a named scope within the enclosing module, which is either
+/// given explicitly as a label or assigned implicitly as `genblk<N>`, with `N`
+/// being the position of the generate statement within its scope. Blocks that
+/// consist of nothing but a nested if- or case-generate statement, such as the
+/// `else if` branch of an if-generate statement, do not form a scope of their
+/// own and have no name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenBlock {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Option<Spanned<Name>>,
+    pub block: ModuleBlock,
+}
+
+impl HasSpan for GenBlock {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for GenBlock {
+    fn desc(&self) -> &'static str {
+        "generate block"
+    }
+
+    fn desc_full(&self) -> String {
+        match self.name {
+            Some(name) => format!("generate block `{}`", name.value),
+            None => self.desc().into(),
+        }
+    }
+}
+
 /// A genvar declaration.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GenvarDecl {
@@ -1102,6 +1811,9 @@ impl HasDesc for Typedef {
 pub struct Assign {
     pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<Attr>,
+    /// The optional drive strengths, e.g. `(weak0, strong1)`.
+    pub strength: Option<(ast::DriveStrength, ast::DriveStrength)>,
     pub lhs: NodeId,
     pub rhs: NodeId,
 }
@@ -1118,6 +1830,29 @@ impl HasDesc for Assign {
     }
 }
 
+/// A parameter override, such as `defparam u_core.WIDTH = 8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defparam {
+    pub id: NodeId,
+    pub span: Span,
+    /// The hierarchical name of the overridden parameter.
+    pub target: NodeId,
+    /// The value assigned to the parameter.
+    pub value: NodeId,
+}
+
+impl HasSpan for Defparam {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for Defparam {
+    fn desc(&self) -> &'static str {
+        "defparam"
+    }
+}
+
 /// A package.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Package {
@@ -1130,6 +1865,10 @@ pub struct Package {
     pub decls: Vec<NodeId>,
     /// The parameter declarations in the package.
     pub params: Vec<NodeId>,
+    /// The names imported into this package that are exported again.
+    pub exports: Vec<Export>,
+    /// The classes declared in the package.
+    pub classes: Vec<NodeId>,
     /// The bottom of the name scope tree.
     pub last_rib: NodeId,
 }
@@ -1154,6 +1893,19 @@ impl HasDesc for Package {
     }
 }
 
+/// A package export.
+///
+/// Corresponds to `export pkg::name`, `export pkg::*`, or `export *::*`, where
+/// the `*` are represented as `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Export {
+    pub span: Span,
+    /// The package whose imported names are exported.
+    pub pkg: Option<Spanned<Name>>,
+    /// The imported name that is exported.
+    pub name: Option<Spanned<Name>>,
+}
+
 /// A single variant of an enum.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EnumVariant {
@@ -1188,6 +1940,264 @@ impl HasDesc for EnumVariant {
     }
 }
 
+/// An immediate, deferred, or concurrent assertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assertion {
+    pub id: NodeId,
+    pub label: Option<Spanned<Name>>,
+    pub span: Span,
+    pub kind: AssertionKind,
+    pub data: AssertionData,
+    /// The statement executed when the assertion succeeds.
+    pub pass_stmt: Option<NodeId>,
+    /// The statement executed when the assertion fails.
+    pub fail_stmt: Option<NodeId>,
+}
+
+impl HasSpan for Assertion {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.label.map(|l| l.span).unwrap_or(self.span)
+    }
+}
+
+impl HasDesc for Assertion {
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            AssertionKind::Assert => "assertion",
+            AssertionKind::Assume => "assumption",
+            AssertionKind::Cover => "cover statement",
+            AssertionKind::Expect => "expect statement",
+            AssertionKind::Restrict => "restriction",
+        }
+    }
+
+    fn desc_full(&self) -> String {
+        match self.label {
+            Some(label) => format!("{} `{}`", self.desc(), label.value),
+            None => self.desc().into(),
+        }
+    }
+}
+
+/// The different verification directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind {
+    Assert,
+    Assume,
+    Cover,
+    Expect,
+    Restrict,
+}
+
+/// What an assertion checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssertionData {
+    /// An immediate or deferred assertion of an expression.
+    Immediate { expr: NodeId, deferred: bool },
+    /// A concurrent assertion of a property or sequence.
+    Concurrent(PropSpec),
+}
+
+/// A property specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropSpec {
+    pub span: Span,
+    /// The clocking event, an `EventExpr`.
+    pub clock: Option<NodeId>,
+    /// The `disable iff` condition.
+    pub disable: Option<NodeId>,
+    pub prop: PropExpr,
+}
+
+/// A property expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropExpr {
+    pub span: Span,
+    pub kind: PropExprKind,
+}
+
+/// The different forms a property expression can take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropExprKind {
+    /// A sequence, e.g. `a ##1 b` or `strong(a ##1 b)`.
+    Seq(ast::PropSeqOp, SeqExpr),
+    /// An implication or followed-by, e.g. `a |-> b`.
+    SeqBinOp(ast::PropSeqBinOp, ast::PropSeqOp, SeqExpr, Box<PropExpr>),
+    /// A negation, e.g. `not a`.
+    Not(Box<PropExpr>),
+    /// A binary operator, e.g. `a until b`.
+    BinOp(ast::PropBinOp, Box<PropExpr>, Box<PropExpr>),
+    /// A clocked property. The clocking event is an `EventExpr`.
+    Clocked(NodeId, Box<PropExpr>),
+}
+
+/// A sequence expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqExpr {
+    pub span: Span,
+    pub kind: SeqExprKind,
+}
+
+/// The different forms a sequence expression can take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeqExprKind {
+    /// A boolean expression or a reference to a named sequence.
+    Expr(NodeId),
+    /// A repetition, e.g. `a[*2]`.
+    Repeat(Box<SeqExpr>, SeqRep),
+    /// A cycle delay, e.g. `a ##1 b` or `##1 b`.
+    Delay(Option<Box<SeqExpr>>, SeqDelay, Box<SeqExpr>),
+    /// A binary operator, e.g. `a intersect b`.
+    BinOp(ast::SeqBinOp, Box<SeqExpr>, Box<SeqExpr>),
+    /// An expression that holds throughout a sequence.
+    Throughout(NodeId, Box<SeqExpr>),
+    /// A clocked sequence. The clocking event is an `EventExpr`.
+    Clocked(NodeId, Box<SeqExpr>),
+}
+
+/// A sequence repetition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqRep {
+    /// `[* range]`
+    Consec(SeqRange),
+    /// `[*]`
+    ConsecStar,
+    /// `[+]`
+    ConsecPlus,
+    /// `[= range]`
+    Nonconsec(SeqRange),
+    /// `[-> range]`
+    Goto(SeqRange),
+}
+
+/// A cycle delay in a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqDelay {
+    /// `##n` or `##[m:n]`
+    Range(SeqRange),
+    /// `##[*]`
+    Star,
+    /// `##[+]`
+    Plus,
+}
+
+/// A number of cycles in a sequence delay or repetition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqRange {
+    /// Exactly `n` cycles.
+    Single(NodeId),
+    /// Between `m` and `n` cycles, where `None` indicates no upper bound.
+    Range(NodeId, Option<NodeId>),
+}
+
+/// A named sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqDecl {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The formal arguments, each a `SeqPort`.
+    pub ports: Vec<NodeId>,
+    pub seq: SeqExpr,
+}
+
+impl HasSpan for SeqDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for SeqDecl {
+    fn desc(&self) -> &'static str {
+        "sequence"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("sequence `{}`", self.name.value)
+    }
+}
+
+/// A named property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropDecl {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The formal arguments, each a `SeqPort`.
+    pub ports: Vec<NodeId>,
+    pub prop: PropSpec,
+}
+
+impl HasSpan for PropDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for PropDecl {
+    fn desc(&self) -> &'static str {
+        "property"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("property `{}`", self.name.value)
+    }
+}
+
+/// A formal argument of a named sequence or property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqPort {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub ty: SeqPortType,
+    /// The default actual argument.
+    pub default: Option<NodeId>,
+}
+
+impl HasSpan for SeqPort {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for SeqPort {
+    fn desc(&self) -> &'static str {
+        "sequence argument"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("sequence argument `{}`", self.name.value)
+    }
+}
+
+/// The type of a sequence or property argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqPortType {
+    Implicit,
+    Untyped,
+    Sequence,
+    Property,
+    /// A data type.
+    Data(NodeId),
+}
+
 /// A named pattern mapping.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PatternMapping {
@@ -1205,3 +2215,242 @@ pub enum InsideRange {
     Single(NodeId),
     Range(NodeId, NodeId),
 }
+
+/// The object whose random variables are assigned by `randomize()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizeTarget {
+    /// `std::randomize(a, b)`, which assigns the variables passed as
+    /// arguments.
+    Std,
+    /// `randomize()` called within a class method.
+    This,
+    /// `obj.randomize()`.
+    Object(NodeId),
+}
+
+/// A function or task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subroutine {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub kind: ast::SubroutineKind,
+    pub lifetime: Option<ast::Lifetime>,
+    /// The return type of a function. `None` for tasks.
+    pub retty: Option<NodeId>,
+    /// The formal arguments, each a `SubroutinePort`.
+    pub ports: Vec<NodeId>,
+    /// The statements in the body.
+    pub stmts: Vec<NodeId>,
+}
+
+impl Subroutine {
+    /// Check whether this is a function, as opposed to a task.
+    pub fn is_function(&self) -> bool {
+        self.kind == ast::SubroutineKind::Func
+    }
+}
+
+impl HasSpan for Subroutine {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Subroutine {
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            ast::SubroutineKind::Func => "function",
+            ast::SubroutineKind::Task => "task",
+        }
+    }
+
+    fn desc_full(&self) -> String {
+        format!("{} `{}`", self.desc(), self.name.value)
+    }
+}
+
+/// A formal argument of a function or task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubroutinePort {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub dir: ast::SubroutinePortDir,
+    pub ty: NodeId,
+    /// The default actual argument.
+    pub default: Option<NodeId>,
+}
+
+impl HasSpan for SubroutinePort {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for SubroutinePort {
+    fn desc(&self) -> &'static str {
+        "argument"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("argument `{}`", self.name.value)
+    }
+}
+
+/// A class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// Whether this is a `virtual class`.
+    pub virt: bool,
+    /// The parameters of the class.
+    pub params: Vec<NodeId>,
+    /// The base class type and the arguments passed to its constructor, for
+    /// `extends base(a, b)`.
+    pub extends: Option<(NodeId, Vec<CallArg>)>,
+    /// The properties and methods of the class.
+    pub members: Vec<ClassMember>,
+    /// The constraint blocks of the class.
+    pub constraints: Vec<NodeId>,
+}
+
+impl Class {
+    /// Find a member by name.
+    pub fn member(&self, name: Name) -> Option<&ClassMember> {
+        self.members.iter().find(|m| m.name.value == name)
+    }
+
+    /// Find the constructor `new`.
+    pub fn constructor(&self) -> Option<&ClassMember> {
+        self.member(moore_common::name::get_name_table().intern("new", true))
+    }
+}
+
+impl HasSpan for Class {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Class {
+    fn desc(&self) -> &'static str {
+        "class"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("class `{}`", self.name.value)
+    }
+}
+
+/// A property or method of a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassMember {
+    /// The `VarDecl` of a property, or the `Subroutine` of a method.
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub kind: ClassMemberKind,
+    /// Whether the member is `static`.
+    pub is_static: bool,
+    /// Whether the member is `virtual`, or `pure virtual` if it is a method
+    /// without a body.
+    pub virt: bool,
+    /// Whether the member is `local` or `protected`.
+    pub visibility: ClassVisibility,
+    /// Whether the property is `rand` or `randc`.
+    pub rand: Option<ast::RandomQualifier>,
+}
+
+/// Whether a class member is a property or a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassMemberKind {
+    /// A property such as `int x;`.
+    Property,
+    /// A function or task.
+    Method,
+    /// A `pure virtual` function or task without a body.
+    PureMethod,
+}
+
+/// The visibility of a class member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassVisibility {
+    /// Accessible from anywhere.
+    Public,
+    /// Only accessible from within the class and its subclasses.
+    Protected,
+    /// Only accessible from within the class.
+    Local,
+}
+
+/// A constraint block of a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub id: NodeId,
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub items: Vec<ConstraintItem>,
+}
+
+impl HasSpan for Constraint {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for Constraint {
+    fn desc(&self) -> &'static str {
+        "constraint"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("constraint `{}`", self.name.value)
+    }
+}
+
+/// An item in a constraint block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintItem {
+    /// An expression that must be true, such as `a < b`.
+    Expr(NodeId),
+    /// A weighted distribution such as `a dist {0 := 1, [1:3] :/ 2}`.
+    Dist(NodeId, Vec<Spanned<DistItem>>),
+    /// A conditional constraint such as `if (a) b == 1; else b == 2;`. An
+    /// implication `a -> b == 1` has no else branch.
+    If(NodeId, Vec<ConstraintItem>, Vec<ConstraintItem>),
+    /// An iterative constraint such as `foreach (a[i]) a[i] < i;`, given as
+    /// the array and its index variables. Each index variable is a
+    /// `GenvarDecl`.
+    Foreach(NodeId, Vec<Option<NodeId>>, Vec<ConstraintItem>),
+    /// A variable ordering such as `solve a before b;`.
+    Solve(Vec<NodeId>, Vec<NodeId>),
+}
+
+/// A value or range of a distribution, together with its weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistItem {
+    pub range: InsideRange,
+    /// The weight of the item. Defaults to 1 if omitted.
+    pub weight: Option<NodeId>,
+    /// Whether the weight is divided among the values of a range (`:/`), as
+    /// opposed to being assigned to each value (`:=`).
+    pub per_range: bool,
+}
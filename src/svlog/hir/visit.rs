@@ -29,6 +29,7 @@ pub trait Visitor<'a>: Sized {
     fn visit_node(&mut self, node: HirNode<'a>, lvalue: bool) {
         match node {
             HirNode::Module(x) => self.visit_module(x),
+            HirNode::Interface(x) => self.visit_interface(x),
             HirNode::Proc(x) => self.visit_proc(x),
             HirNode::Stmt(x) => self.visit_stmt(x),
             HirNode::Expr(x) => self.visit_expr(x, lvalue),
@@ -36,6 +37,13 @@ pub trait Visitor<'a>: Sized {
             HirNode::Typedef(x) => self.visit_typedef(x),
             HirNode::VarDecl(x) => self.visit_var_decl(x),
             HirNode::Assign(x) => self.visit_assign(x),
+            HirNode::Gen(x) => self.visit_gen(x),
+            HirNode::GenBlock(x) => self.visit_gen_block(x),
+            HirNode::Inst(x) => self.visit_inst(x),
+            HirNode::Assertion(x) => self.visit_assertion(x),
+            HirNode::Subroutine(x) => self.visit_subroutine(x),
+            HirNode::SubroutinePort(x) => self.visit_subroutine_port(x),
+            HirNode::Constraint(x) => self.visit_constraint(x),
             _ => (),
         }
     }
@@ -48,6 +56,10 @@ pub trait Visitor<'a>: Sized {
         walk_module(self, module)
     }
 
+    fn visit_interface(&mut self, intf: &'a Interface) {
+        walk_interface(self, intf)
+    }
+
     fn visit_proc(&mut self, prok: &'a Proc) {
         walk_proc(self, prok)
     }
@@ -83,6 +95,34 @@ pub trait Visitor<'a>: Sized {
     fn visit_assign(&mut self, assign: &'a Assign) {
         walk_assign(self, assign);
     }
+
+    fn visit_gen(&mut self, gen: &'a Gen) {
+        walk_gen(self, gen);
+    }
+
+    fn visit_gen_block(&mut self, block: &'a GenBlock) {
+        walk_module_block(self, &block.block);
+    }
+
+    fn visit_inst(&mut self, inst: &'a Inst<'a>) {
+        walk_inst(self, inst);
+    }
+
+    fn visit_assertion(&mut self, assertion: &'a Assertion) {
+        walk_assertion(self, assertion);
+    }
+
+    fn visit_subroutine(&mut self, subroutine: &'a Subroutine) {
+        walk_subroutine(self, subroutine);
+    }
+
+    fn visit_subroutine_port(&mut self, port: &'a SubroutinePort) {
+        walk_subroutine_port(self, port);
+    }
+
+    fn visit_constraint(&mut self, constraint: &'a Constraint) {
+        walk_constraint_items(self, &constraint.items);
+    }
 }
 
 /// Walk the contents of a module.
@@ -96,6 +136,17 @@ pub fn walk_module<'a>(visitor: &mut impl Visitor<'a>, module: &'a Module) {
     walk_module_block(visitor, &module.block);
 }
 
+/// Walk the contents of an interface.
+pub fn walk_interface<'a>(visitor: &mut impl Visitor<'a>, intf: &'a Interface) {
+    for &id in intf.ports {
+        visitor.visit_node_with_id(id, false);
+    }
+    for &id in intf.params {
+        visitor.visit_node_with_id(id, false);
+    }
+    walk_module_block(visitor, &intf.block);
+}
+
 /// Walk the contents of a module block.
 pub fn walk_module_block<'a>(visitor: &mut impl Visitor<'a>, blk: &'a ModuleBlock) {
     for &id in &blk.insts {
@@ -116,6 +167,12 @@ pub fn walk_module_block<'a>(visitor: &mut impl Visitor<'a>, blk: &'a ModuleBloc
     for &id in &blk.assigns {
         visitor.visit_node_with_id(id, false);
     }
+    for &id in &blk.assertions {
+        visitor.visit_node_with_id(id, false);
+    }
+    for &id in &blk.subroutines {
+        visitor.visit_node_with_id(id, false);
+    }
 }
 
 /// Walk the contents of a procedure.
@@ -189,6 +246,49 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
                 visitor.visit_node_with_id(default, false);
             }
         }
+        StmtKind::CaseMatches {
+            expr,
+            ref ways,
+            default,
+        } => {
+            visitor.visit_node_with_id(expr, false);
+            for &(pattern, stmt) in ways {
+                visitor.visit_node_with_id(pattern, false);
+                visitor.visit_node_with_id(stmt, false);
+            }
+            if let Some(default) = default {
+                visitor.visit_node_with_id(default, false);
+            }
+        }
+        StmtKind::Assertion(assertion) => visitor.visit_node_with_id(assertion, false),
+        StmtKind::Fork { ref stmts, .. } => {
+            for &stmt in stmts {
+                visitor.visit_node_with_id(stmt, false);
+            }
+        }
+        StmtKind::Wait { cond, stmt } => {
+            visitor.visit_node_with_id(cond, false);
+            visitor.visit_node_with_id(stmt, false);
+        }
+        StmtKind::WaitFork | StmtKind::DisableFork | StmtKind::Disable(..) => (),
+        StmtKind::Trigger(expr) => {
+            visitor.visit_node_with_id(expr, false);
+            visitor.visit_node_with_id(expr, true);
+        }
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                visitor.visit_node_with_id(expr, false);
+            }
+        }
+    }
+}
+
+/// Walk the arguments of a display or severity task.
+fn walk_format_items<'a>(visitor: &mut impl Visitor<'a>, items: &'a [FormatItem]) {
+    for item in items {
+        if let FormatItem::Arg(_, arg) = *item {
+            visitor.visit_node_with_id(arg, false);
+        }
     }
 }
 
@@ -196,9 +296,13 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
 pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: bool) {
     match expr.kind {
         ExprKind::Builtin(BuiltinCall::Unsupported)
+        | ExprKind::Builtin(BuiltinCall::RealTime)
+        | ExprKind::Builtin(BuiltinCall::Time)
+        | ExprKind::Builtin(BuiltinCall::STime)
         | ExprKind::IntConst { .. }
         | ExprKind::UnsizedConst(_)
         | ExprKind::TimeConst(_)
+        | ExprKind::RealConst(_)
         | ExprKind::StringConst(_) => (),
         ExprKind::Ident(x) => {
             visitor.visit_ident(x);
@@ -228,9 +332,37 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
         ExprKind::Builtin(BuiltinCall::Clog2(arg))
         | ExprKind::Builtin(BuiltinCall::Bits(arg))
         | ExprKind::Builtin(BuiltinCall::Signed(arg))
-        | ExprKind::Builtin(BuiltinCall::Unsigned(arg)) => {
+        | ExprKind::Builtin(BuiltinCall::Unsigned(arg))
+        | ExprKind::Builtin(BuiltinCall::RealConv(_, arg))
+        | ExprKind::Builtin(BuiltinCall::BitQuery(_, arg)) => {
             visitor.visit_node_with_id(arg, false);
         }
+        ExprKind::Builtin(BuiltinCall::RealMath(_, x, y)) => {
+            visitor.visit_node_with_id(x, false);
+            if let Some(y) = y {
+                visitor.visit_node_with_id(y, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::ArrayQuery(_, arg, dim)) => {
+            visitor.visit_node_with_id(arg, false);
+            if let Some(dim) = dim {
+                visitor.visit_node_with_id(dim, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::Display(_, ref items)) => {
+            walk_format_items(visitor, items);
+        }
+        ExprKind::Builtin(BuiltinCall::Severity(_, finish, ref items)) => {
+            if let Some(finish) = finish {
+                visitor.visit_node_with_id(finish, false);
+            }
+            walk_format_items(visitor, items);
+        }
+        ExprKind::Builtin(BuiltinCall::SimControl(_, arg)) => {
+            if let Some(arg) = arg {
+                visitor.visit_node_with_id(arg, false);
+            }
+        }
         ExprKind::Ternary(cond, true_expr, false_expr) => {
             visitor.visit_node_with_id(cond, false);
             visitor.visit_node_with_id(true_expr, lvalue);
@@ -285,6 +417,110 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
                 }
             }
         }
+        ExprKind::Call(callee, ref args) => {
+            visitor.visit_node_with_id(callee, false);
+            for arg in args {
+                if let Some(expr) = arg.expr {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
+        ExprKind::MethodCall(target, _, ref args) => {
+            visitor.visit_node_with_id(target, lvalue);
+            for arg in args {
+                if let Some(expr) = arg.expr {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
+        ExprKind::ArrayNew(size, init) => {
+            visitor.visit_node_with_id(size, false);
+            if let Some(init) = init {
+                visitor.visit_node_with_id(init, false);
+            }
+        }
+        ExprKind::Tagged(_, expr) => {
+            if let Some(expr) = expr {
+                visitor.visit_node_with_id(expr, false);
+            }
+        }
+        ExprKind::PatternVar(_) => (),
+        ExprKind::New(ref args) => {
+            for arg in args {
+                if let Some(expr) = arg.expr {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
+        ExprKind::This | ExprKind::Super | ExprKind::Null => (),
+        ExprKind::Randomize(target, ref args, ref constraints) => {
+            if let RandomizeTarget::Object(target) = target {
+                visitor.visit_node_with_id(target, true);
+            }
+            for &arg in args {
+                visitor.visit_node_with_id(arg, true);
+            }
+            walk_constraint_items(visitor, constraints);
+        }
+    }
+}
+
+/// Walk the items of a constraint block.
+pub fn walk_constraint_items<'a>(visitor: &mut impl Visitor<'a>, items: &'a [ConstraintItem]) {
+    for item in items {
+        match *item {
+            ConstraintItem::Expr(expr) => visitor.visit_node_with_id(expr, false),
+            ConstraintItem::Dist(expr, ref dist) => {
+                visitor.visit_node_with_id(expr, false);
+                for d in dist {
+                    match d.value.range {
+                        InsideRange::Single(expr) => visitor.visit_node_with_id(expr, false),
+                        InsideRange::Range(lo, hi) => {
+                            visitor.visit_node_with_id(lo, false);
+                            visitor.visit_node_with_id(hi, false);
+                        }
+                    }
+                    if let Some(weight) = d.value.weight {
+                        visitor.visit_node_with_id(weight, false);
+                    }
+                }
+            }
+            ConstraintItem::If(cond, ref main, ref other) => {
+                visitor.visit_node_with_id(cond, false);
+                walk_constraint_items(visitor, main);
+                walk_constraint_items(visitor, other);
+            }
+            ConstraintItem::Foreach(array, _, ref body) => {
+                visitor.visit_node_with_id(array, false);
+                walk_constraint_items(visitor, body);
+            }
+            ConstraintItem::Solve(ref before, ref after) => {
+                for &expr in before.iter().chain(after.iter()) {
+                    visitor.visit_node_with_id(expr, false);
+                }
+            }
+        }
+    }
+}
+
+/// Walk the contents of a function or task.
+pub fn walk_subroutine<'a>(visitor: &mut impl Visitor<'a>, subroutine: &'a Subroutine) {
+    if let Some(retty) = subroutine.retty {
+        visitor.visit_node_with_id(retty, false);
+    }
+    for &id in &subroutine.ports {
+        visitor.visit_node_with_id(id, false);
+    }
+    for &id in &subroutine.stmts {
+        visitor.visit_node_with_id(id, false);
+    }
+}
+
+/// Walk the contents of a function or task argument.
+pub fn walk_subroutine_port<'a>(visitor: &mut impl Visitor<'a>, port: &'a SubroutinePort) {
+    visitor.visit_node_with_id(port.ty, false);
+    if let Some(default) = port.default {
+        visitor.visit_node_with_id(default, false);
     }
 }
 
@@ -320,6 +556,18 @@ pub fn walk_typedef<'a>(visitor: &mut impl Visitor<'a>, typedef: &'a Typedef) {
 /// Walk the contents of a variable declaration.
 pub fn walk_var_decl<'a>(visitor: &mut impl Visitor<'a>, decl: &'a VarDecl) {
     visitor.visit_node_with_id(decl.ty, false);
+    for dim in &decl.dims {
+        match *dim {
+            UnpackedDim::Size(size) => visitor.visit_node_with_id(size, false),
+            UnpackedDim::Range(lhs, rhs) => {
+                visitor.visit_node_with_id(lhs, false);
+                visitor.visit_node_with_id(rhs, false);
+            }
+            UnpackedDim::Unsized | UnpackedDim::Queue(None) | UnpackedDim::Associative(None) => (),
+            UnpackedDim::Queue(Some(max)) => visitor.visit_node_with_id(max, false),
+            UnpackedDim::Associative(Some(index)) => visitor.visit_node_with_id(index, false),
+        }
+    }
     if let Some(init) = decl.init {
         visitor.visit_node_with_id(init, false);
     }
@@ -330,3 +578,142 @@ pub fn walk_assign<'a>(visitor: &mut impl Visitor<'a>, assign: &'a Assign) {
     visitor.visit_node_with_id(assign.lhs, true);
     visitor.visit_node_with_id(assign.rhs, false);
 }
+
+/// Walk the contents of a generate statement.
+pub fn walk_gen<'a>(visitor: &mut impl Visitor<'a>, gen: &'a Gen) {
+    match gen.kind {
+        GenKind::If { cond, .. } => visitor.visit_node_with_id(cond, false),
+        GenKind::For {
+            ref init,
+            cond,
+            step,
+            ..
+        } => {
+            for &id in init {
+                visitor.visit_node_with_id(id, false);
+            }
+            visitor.visit_node_with_id(cond, false);
+            visitor.visit_node_with_id(step, false);
+        }
+        GenKind::Case { expr, ref ways, .. } => {
+            visitor.visit_node_with_id(expr, false);
+            for &(ref exprs, _) in ways {
+                for &id in exprs {
+                    visitor.visit_node_with_id(id, false);
+                }
+            }
+        }
+    }
+    for id in gen.kind.blocks() {
+        visitor.visit_node_with_id(id, false);
+    }
+}
+
+/// Walk the port connections of an instantiation.
+pub fn walk_inst<'a>(visitor: &mut impl Visitor<'a>, inst: &'a Inst<'a>) {
+    for &(_, expr) in &inst.pos_ports {
+        if let Some(expr) = expr {
+            visitor.visit_node_with_id(expr, false);
+        }
+    }
+    for &(_, _, expr) in &inst.named_ports {
+        if let Some(expr) = expr {
+            visitor.visit_node_with_id(expr, false);
+        }
+    }
+}
+
+/// Walk the contents of an assertion.
+pub fn walk_assertion<'a>(visitor: &mut impl Visitor<'a>, assertion: &'a Assertion) {
+    match assertion.data {
+        AssertionData::Immediate { expr, .. } => visitor.visit_node_with_id(expr, false),
+        AssertionData::Concurrent(ref spec) => walk_prop_spec(visitor, spec),
+    }
+    if let Some(stmt) = assertion.pass_stmt {
+        visitor.visit_node_with_id(stmt, false);
+    }
+    if let Some(stmt) = assertion.fail_stmt {
+        visitor.visit_node_with_id(stmt, false);
+    }
+}
+
+/// Walk the contents of a property specification.
+pub fn walk_prop_spec<'a>(visitor: &mut impl Visitor<'a>, spec: &'a PropSpec) {
+    if let Some(clock) = spec.clock {
+        visitor.visit_node_with_id(clock, false);
+    }
+    if let Some(disable) = spec.disable {
+        visitor.visit_node_with_id(disable, false);
+    }
+    walk_prop_expr(visitor, &spec.prop);
+}
+
+/// Walk the contents of a property expression.
+pub fn walk_prop_expr<'a>(visitor: &mut impl Visitor<'a>, prop: &'a PropExpr) {
+    match prop.kind {
+        PropExprKind::Seq(_, ref seq) => walk_seq_expr(visitor, seq),
+        PropExprKind::SeqBinOp(_, _, ref lhs, ref rhs) => {
+            walk_seq_expr(visitor, lhs);
+            walk_prop_expr(visitor, rhs);
+        }
+        PropExprKind::Not(ref arg) => walk_prop_expr(visitor, arg),
+        PropExprKind::BinOp(_, ref lhs, ref rhs) => {
+            walk_prop_expr(visitor, lhs);
+            walk_prop_expr(visitor, rhs);
+        }
+        PropExprKind::Clocked(clock, ref arg) => {
+            visitor.visit_node_with_id(clock, false);
+            walk_prop_expr(visitor, arg);
+        }
+    }
+}
+
+/// Walk the contents of a sequence expression.
+pub fn walk_seq_expr<'a>(visitor: &mut impl Visitor<'a>, seq: &'a SeqExpr) {
+    match seq.kind {
+        SeqExprKind::Expr(expr) => visitor.visit_node_with_id(expr, false),
+        SeqExprKind::Repeat(ref arg, rep) => {
+            walk_seq_expr(visitor, arg);
+            match rep {
+                SeqRep::Consec(range) | SeqRep::Nonconsec(range) | SeqRep::Goto(range) => {
+                    walk_seq_range(visitor, range)
+                }
+                SeqRep::ConsecStar | SeqRep::ConsecPlus => (),
+            }
+        }
+        SeqExprKind::Delay(ref lhs, delay, ref rhs) => {
+            if let Some(ref lhs) = *lhs {
+                walk_seq_expr(visitor, lhs);
+            }
+            if let SeqDelay::Range(range) = delay {
+                walk_seq_range(visitor, range);
+            }
+            walk_seq_expr(visitor, rhs);
+        }
+        SeqExprKind::BinOp(_, ref lhs, ref rhs) => {
+            walk_seq_expr(visitor, lhs);
+            walk_seq_expr(visitor, rhs);
+        }
+        SeqExprKind::Throughout(cond, ref arg) => {
+            visitor.visit_node_with_id(cond, false);
+            walk_seq_expr(visitor, arg);
+        }
+        SeqExprKind::Clocked(clock, ref arg) => {
+            visitor.visit_node_with_id(clock, false);
+            walk_seq_expr(visitor, arg);
+        }
+    }
+}
+
+/// Walk the bounds of a sequence delay or repetition.
+pub fn walk_seq_range<'a>(visitor: &mut impl Visitor<'a>, range: SeqRange) {
+    match range {
+        SeqRange::Single(count) => visitor.visit_node_with_id(count, false),
+        SeqRange::Range(lo, hi) => {
+            visitor.visit_node_with_id(lo, false);
+            if let Some(hi) = hi {
+                visitor.visit_node_with_id(hi, false);
+            }
+        }
+    }
+}
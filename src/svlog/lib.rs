@@ -24,13 +24,14 @@ pub mod hir;
 pub mod mir;
 mod param_env;
 mod port_mapping;
+pub mod rand;
 mod resolver;
 pub mod ty;
 pub mod typeck;
 pub mod value;
 
 pub use crate::{
-    codegen::CodeGenerator,
+    codegen::{AttrMetadata, CodeGenerator, Metadata},
     context::*,
     param_env::{NodeEnvId, ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvSource},
     port_mapping::{PortMapping, PortMappingSource},
@@ -5,8 +5,13 @@
 use crate::{
     crate_prelude::*,
     hir::HirNode,
-    mir::{lower::rvalue::compute_indexing, lvalue::*},
-    ty::Type,
+    mir::{
+        lower::rvalue::{
+            cast_to_type, compute_indexing, compute_packed_member, lower_property_by_name,
+        },
+        lvalue::*,
+    },
+    ty::{self, Type},
     ParamEnv,
 };
 
@@ -84,6 +89,8 @@ fn try_lower_expr<'gcx>(
         HirNode::Expr(x) => x,
         HirNode::VarDecl(decl) => return Ok(builder.build(ty, LvalueKind::Var(decl.id))),
         HirNode::Port(port) => return Ok(builder.build(ty, LvalueKind::Port(port.id))),
+        HirNode::SubroutinePort(port) => return Ok(builder.build(ty, LvalueKind::Var(port.id))),
+        HirNode::Subroutine(sub) => return Ok(builder.build(ty, LvalueKind::Var(sub.id))),
         x => unreachable!("lvalue for {:#?}", x),
     };
 
@@ -93,15 +100,72 @@ fn try_lower_expr<'gcx>(
         // the resolved node to an MIR node.
         hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
             let binding = builder.cx.resolve_node(expr_id, builder.env)?;
+            if let Some((this, prop, env)) =
+                lower_property_by_name(builder.cx, expr_id, binding, builder.env)?
+            {
+                return Ok(builder.build(ty, LvalueKind::ClassProperty { this, prop, env }));
+            }
             match builder.cx.hir_of(binding)? {
                 HirNode::VarDecl(..) | HirNode::Port(..) => {
                     return try_lower_expr(builder, binding);
                 }
+                HirNode::SubroutinePort(port) => {
+                    if port.dir == ast::SubroutinePortDir::ConstRef {
+                        builder.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "{} cannot be assigned to; it is a `const ref`",
+                                port.desc_full()
+                            ))
+                            .span(builder.span),
+                        );
+                        return Err(());
+                    }
+                    return try_lower_expr(builder, binding);
+                }
+                // Within a function, its name refers to the return value.
+                HirNode::Subroutine(..)
+                    if resolver::is_within_subroutine(builder.cx, binding, expr_id) =>
+                {
+                    return try_lower_expr(builder, binding);
+                }
                 _ => (),
             }
         }
 
         hir::ExprKind::Index(target, mode) => {
+            // Elements of dynamic arrays, queues, and associative arrays are
+            // assigned by modifying the entire container.
+            let target_ty = builder.cx.type_of(target, builder.env)?;
+            if target_ty.is_dynamic() {
+                let index = match mode {
+                    hir::IndexMode::One(index) => index,
+                    hir::IndexMode::Many(..) => {
+                        builder.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "slices of `{}` cannot be assigned to",
+                                target_ty
+                            ))
+                            .span(builder.span),
+                        );
+                        return Err(());
+                    }
+                };
+                let index = cast_to_type(
+                    builder.cx,
+                    builder.cx.mir_rvalue(index, builder.env),
+                    builder.env,
+                    target_ty.get_dynamic_index().unwrap(),
+                );
+                return Ok(builder.build(
+                    ty,
+                    LvalueKind::ContainerElement {
+                        value: builder.cx.mir_lvalue(target, builder.env),
+                        current: builder.cx.mir_rvalue(target, builder.env),
+                        index,
+                    },
+                ));
+            }
+
             // Compute the indexing parameters.
             let (base, length) = compute_indexing(builder.cx, builder.expr, builder.env, mode)?;
 
@@ -129,10 +193,101 @@ fn try_lower_expr<'gcx>(
             ));
         }
 
-        hir::ExprKind::Field(target, _) => {
-            let value = builder.cx.mir_lvalue(target, builder.env);
-            let (_, field, _) = builder.cx.resolve_field_access(expr_id, builder.env)?;
-            return Ok(builder.build(ty, LvalueKind::Member { value, field }));
+        hir::ExprKind::Field(target, name) => {
+            if let Some(sig) = builder.cx.resolve_intf_signal(expr_id)? {
+                // Signals which are not accessible through the modport a
+                // port is connected through have no type.
+                if ty.is_error() {
+                    return Err(());
+                }
+                if sig.dir == Some(ast::PortDir::Input) {
+                    builder.cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be assigned to; it is an input of the modport",
+                            name
+                        ))
+                        .span(builder.span),
+                    );
+                    return Err(());
+                }
+                return Ok(builder.build(ty, LvalueKind::IntfSignal(sig.binding, sig.signal)));
+            }
+            if let Some(binding) = resolver::resolve_gen_block_field(builder.cx, expr_id)? {
+                if resolver::is_gen_loop_field(builder.cx, expr_id)? {
+                    builder.cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be assigned to; iterations of generate loops may only \
+                             be read",
+                            builder.span.extract()
+                        ))
+                        .span(builder.span),
+                    );
+                    return Err(());
+                }
+                resolver::check_gen_block_instantiated(
+                    builder.cx,
+                    binding,
+                    builder.span,
+                    builder.env,
+                )?;
+                match builder.cx.hir_of(binding)? {
+                    HirNode::VarDecl(..) => return try_lower_expr(builder, binding),
+                    _ => (),
+                }
+            } else if let Some(hier) = builder.cx.resolve_hier_name(expr_id)? {
+                match builder.cx.hir_of(hier.target)? {
+                    HirNode::VarDecl(..) | HirNode::Port(..) if hier.is_local() => {
+                        return try_lower_expr(builder, hier.target)
+                    }
+                    _ => {
+                        builder.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` cannot be assigned to; hierarchical names may only be read",
+                                builder.span.extract()
+                            ))
+                            .span(builder.span),
+                        );
+                        return Err(());
+                    }
+                }
+            } else if let Some((prop, env)) =
+                builder.cx.resolve_class_property(expr_id, builder.env)?
+            {
+                let this = match resolver::declaring_class(builder.cx, prop)
+                    .and_then(|class| class.members.iter().find(|m| m.id == prop))
+                {
+                    Some(member) if member.is_static => None,
+                    _ => Some(builder.cx.mir_rvalue(target, builder.env)),
+                };
+                return Ok(builder.build(ty, LvalueKind::ClassProperty { this, prop, env }));
+            } else {
+                let value = builder.cx.mir_lvalue(target, builder.env);
+                let (def_id, field, _) = builder.cx.resolve_field_access(expr_id, builder.env)?;
+                // Members of unions, and of structs that are nested in a union
+                // and therefore stored as bits, are assigned as a slice of the
+                // bits.
+                let def = builder.cx.struct_def(def_id)?;
+                if def.is_union() || !value.ty.is_struct() {
+                    let mut offset = 0;
+                    if !def.is_union() {
+                        for field in &def.fields[field + 1..] {
+                            let field_ty = builder.cx.map_to_type(field.ty, builder.env)?;
+                            offset += ty::bit_size_of_type(builder.cx, field_ty, builder.env)?;
+                        }
+                    }
+                    let (base, sbvt) =
+                        compute_packed_member(builder.cx, expr_id, builder.env, ty, offset)?;
+                    return Ok(builder.build(
+                        sbvt,
+                        LvalueKind::Index {
+                            value,
+                            base,
+                            length: sbvt.width(),
+                        },
+                    ));
+                }
+                return Ok(builder.build(ty, LvalueKind::Member { value, field }));
+            }
         }
 
         _ => (),
@@ -97,6 +97,12 @@ fn try_lower_expr<'gcx>(
         HirNode::Port(port) => {
             return Ok(builder.build(builder.cx.type_of(expr_id, env)?, RvalueKind::Port(port.id)))
         }
+        HirNode::SubroutinePort(port) => {
+            return Ok(builder.build(builder.cx.type_of(expr_id, env)?, RvalueKind::Var(port.id)))
+        }
+        HirNode::Subroutine(sub) => {
+            return Ok(builder.build(builder.cx.type_of(expr_id, env)?, RvalueKind::Var(sub.id)))
+        }
         HirNode::EnumVariant(..) => {
             let k = builder.cx.constant_value_of(expr_id, env)?;
             return Ok(builder.build(k.ty, RvalueKind::Const(k)));
@@ -111,23 +117,60 @@ fn try_lower_expr<'gcx>(
     // Determine the expression type and match on the various forms.
     let ty = builder.cx.type_of(expr_id, env)?;
     match hir.kind {
+        // Queries of the first dimension of a dynamic array depend on its size
+        // at runtime.
+        hir::ExprKind::Builtin(hir::BuiltinCall::ArrayQuery(func, arg, dim))
+            if func != hir::ArrayQuery::Dimensions
+                && func != hir::ArrayQuery::UnpackedDimensions
+                && cx
+                    .type_of(arg, env)
+                    .map(|ty| ty.is_dynamic() && !ty.is_string())
+                    .unwrap_or(false) =>
+        {
+            lower_dynamic_array_query(&builder, func, arg, dim)
+        }
+
         hir::ExprKind::IntConst { .. }
         | hir::ExprKind::UnsizedConst(..)
         | hir::ExprKind::TimeConst(_)
+        | hir::ExprKind::RealConst(_)
+        | hir::ExprKind::StringConst(_)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_)) => {
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayQuery(..)) => {
             let k = builder.cx.constant_value_of(expr_id, env)?;
             Ok(builder.build(k.ty, RvalueKind::Const(k)))
         }
         hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
             let binding = builder.cx.resolve_node(expr_id, env)?;
+            if let Some((this, prop, prop_env)) = lower_property_by_name(cx, expr_id, binding, env)?
+            {
+                return Ok(builder.build(
+                    ty,
+                    RvalueKind::ClassProperty {
+                        this,
+                        prop,
+                        env: prop_env,
+                    },
+                ));
+            }
             match builder.cx.hir_of(binding)? {
                 HirNode::VarDecl(..)
                 | HirNode::Port(..)
                 | HirNode::EnumVariant(..)
                 | HirNode::ValueParam(..)
-                | HirNode::GenvarDecl(..) => try_lower_expr(builder, binding),
+                | HirNode::GenvarDecl(..)
+                | HirNode::SubroutinePort(..) => try_lower_expr(builder, binding),
+                // Within a function, its name refers to the return value.
+                HirNode::Subroutine(..) if resolver::is_within_subroutine(cx, binding, expr_id) => {
+                    try_lower_expr(builder, binding)
+                }
+                // Pattern variables are bound by the `case matches` statement.
+                HirNode::Expr(hir::Expr {
+                    kind: hir::ExprKind::PatternVar(..),
+                    ..
+                }) => Ok(builder.build(ty, RvalueKind::Var(binding))),
                 x => {
                     builder.cx.emit(
                         DiagBuilder2::error(format!(
@@ -179,6 +222,30 @@ fn try_lower_expr<'gcx>(
                 Err(())
             }
         }
+        hir::ExprKind::PositionalPattern(..) | hir::ExprKind::RepeatPattern(..)
+            if ty.is_array() =>
+        {
+            let elem_ty = ty.get_array_element().unwrap();
+            let values = resolver::resolve_pattern(cx, expr_id, env)?
+                .into_iter()
+                .map(|(i, expr)| (i, lower_expr_and_cast(cx, expr, env, elem_ty)))
+                .collect();
+            Ok(builder.build(ty, RvalueKind::ConstructArray(values)))
+        }
+        hir::ExprKind::Concat(repeat, ref exprs) if ty.is_string() => {
+            // Cast each expression to a string and repeat the entire sequence
+            // if needed.
+            let exprs: Vec<_> = exprs
+                .iter()
+                .map(|&expr| lower_expr_and_cast(cx, expr, env, ty))
+                .collect();
+            let count = match repeat {
+                Some(repeat) => cx.constant_int_value_of(repeat, env)?.to_usize().unwrap(),
+                None => 1,
+            };
+            let values = (0..count).flat_map(|_| exprs.iter().cloned()).collect();
+            Ok(builder.build(ty, RvalueKind::StringConcat(values)))
+        }
         hir::ExprKind::Concat(repeat, ref exprs) => {
             // Compute the SBVT for each expression and lower it to MIR,
             // implicitly casting to the SBVT.
@@ -256,6 +323,81 @@ fn try_lower_expr<'gcx>(
         hir::ExprKind::Builtin(hir::BuiltinCall::Unsigned(id)) => {
             Ok(lower_expr_and_cast_sign(&builder, id, ty::Sign::Unsigned))
         }
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealConv(conv, arg)) => {
+            Ok(lower_real_conv(&builder, ty, conv, arg))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::BitQuery(query, arg)) => {
+            lower_bit_query(&builder, ty, query, arg)
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealMath(func, x, y)) => {
+            let args = std::iter::once(x)
+                .chain(y)
+                .map(|arg| lower_expr_and_cast(cx, arg, env, &ty::REAL_TYPE))
+                .collect();
+            Ok(builder.build(ty, RvalueKind::RealMath { func, args }))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealTime) => {
+            Ok(builder.build(ty, RvalueKind::RealTime))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Time) => Ok(builder.build(ty, RvalueKind::Time)),
+        hir::ExprKind::Builtin(hir::BuiltinCall::STime) => {
+            let time = builder.build(cx.mkty_bit_vector(64), RvalueKind::Time);
+            Ok(builder.build(ty, RvalueKind::Truncate(32, time)))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Display(task, ref items)) => Ok(builder.build(
+            ty,
+            RvalueKind::Display {
+                newline: task == hir::DisplayTask::Display,
+                items: lower_format_items(&builder, items)?,
+            },
+        )),
+        hir::ExprKind::Builtin(hir::BuiltinCall::Severity(severity, finish, ref items)) => {
+            let finish = match severity {
+                hir::SeverityTask::Fatal => Some(lower_finish_number(&builder, finish)),
+                _ => None,
+            };
+            Ok(builder.build(
+                ty,
+                RvalueKind::Severity {
+                    severity,
+                    finish,
+                    items: lower_format_items(&builder, items)?,
+                },
+            ))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::SimControl(control, arg)) => Ok(builder.build(
+            ty,
+            RvalueKind::SimControl(control, lower_finish_number(&builder, arg)),
+        )),
+
+        hir::ExprKind::Index(target, mode)
+            if cx
+                .type_of(target, env)
+                .map(|ty| ty.is_dynamic())
+                .unwrap_or(false) =>
+        {
+            let target_ty = cx.type_of(target, env)?;
+            let index = match mode {
+                hir::IndexMode::One(index) => index,
+                hir::IndexMode::Many(..) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("slices of `{}` not supported", target_ty))
+                            .span(span),
+                    );
+                    return Err(());
+                }
+            };
+            let index = lower_expr_and_cast(cx, index, env, target_ty.get_dynamic_index().unwrap());
+            Ok(builder.build(
+                ty,
+                RvalueKind::ContainerOp {
+                    op: ContainerOp::Get,
+                    value: cx.mir_rvalue(target, env),
+                    args: vec![CallArg::Input(index)],
+                    update: None,
+                },
+            ))
+        }
 
         hir::ExprKind::Index(target, mode) => {
             let (base, length) = compute_indexing(cx, builder.expr, env, mode)?;
@@ -299,8 +441,71 @@ fn try_lower_expr<'gcx>(
         }
 
         hir::ExprKind::Field(target, _) => {
+            if let Some(sig) = cx.resolve_intf_signal(expr_id)? {
+                // Signals which are not accessible through the modport a
+                // port is connected through have no type.
+                if ty.is_error() {
+                    return Err(());
+                }
+                return Ok(builder.build(ty, RvalueKind::IntfSignal(sig.binding, sig.signal)));
+            }
+            if let Some(binding) = resolver::resolve_gen_block_field(cx, expr_id)? {
+                let block_env = resolver::gen_block_field_env(cx, expr_id, env)?;
+                resolver::check_gen_block_instantiated(cx, binding, span, block_env)?;
+                let in_loop = resolver::is_gen_loop_field(cx, expr_id)?;
+                return match cx.hir_of(binding)? {
+                    HirNode::ValueParam(..) if in_loop => {
+                        let k = cx.constant_value_of(expr_id, env)?;
+                        Ok(builder.build(k.ty, RvalueKind::Const(k)))
+                    }
+                    HirNode::VarDecl(..) if in_loop => {
+                        Ok(builder.build(ty, RvalueKind::GenLoop(expr_id)))
+                    }
+                    HirNode::VarDecl(..) | HirNode::ValueParam(..) => {
+                        try_lower_expr(builder, binding)
+                    }
+                    x => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "{} cannot be used in expression",
+                                x.desc_full()
+                            ))
+                            .span(span),
+                        );
+                        Err(())
+                    }
+                };
+            }
+            if let Some(hier) = cx.resolve_hier_name(expr_id)? {
+                return match cx.hir_of(hier.target)? {
+                    HirNode::ValueParam(..) => {
+                        let k = cx.constant_value_of(expr_id, env)?;
+                        Ok(builder.build(k.ty, RvalueKind::Const(k)))
+                    }
+                    _ if hier.is_local() => try_lower_expr(builder, hier.target),
+                    _ => Ok(builder.build(ty, RvalueKind::Hier(hier.id))),
+                };
+            }
+            if let Some((prop, prop_env)) = cx.resolve_class_property(expr_id, env)? {
+                let this = if is_static_member(cx, prop) {
+                    None
+                } else {
+                    Some(cx.mir_rvalue(target, env))
+                };
+                return Ok(builder.build(
+                    ty,
+                    RvalueKind::ClassProperty {
+                        this,
+                        prop,
+                        env: prop_env,
+                    },
+                ));
+            }
             let value = cx.mir_rvalue(target, env);
-            let (_, field, _) = cx.resolve_field_access(expr_id, env)?;
+            let (def_id, field, _) = cx.resolve_field_access(expr_id, env)?;
+            if cx.struct_def(def_id)?.is_union() {
+                return Ok(lower_union_member(builder, value, ty));
+            }
             Ok(builder.build(ty, RvalueKind::Member { value, field }))
         }
 
@@ -309,6 +514,206 @@ fn try_lower_expr<'gcx>(
             Ok(lower_expr_and_cast(cx, expr, env, ty))
         }
 
+        hir::ExprKind::Call(..) => {
+            let mapping = cx.resolve_call(expr_id)?;
+
+            // Methods called by name operate on the object the enclosing
+            // method was called on, unless they are static.
+            if let Some(class) = resolver::declaring_class(cx, mapping.subroutine) {
+                let method = class
+                    .members
+                    .iter()
+                    .find(|m| m.id == mapping.subroutine)
+                    .unwrap();
+                resolver::check_member_access(cx, class, method, expr_id, span)?;
+                let within = resolver::enclosing_class(cx, expr_id);
+                let (this, method_env) = match within {
+                    Some((within, _)) if method.is_static => (
+                        None,
+                        resolver::class_member(cx, within.id, env, method.name.value)?
+                            .map(|(_, _, env)| env)
+                            .unwrap_or(env),
+                    ),
+                    None if method.is_static => (None, env),
+                    _ => {
+                        let this = lower_implicit_this(&builder, method.name.value)?;
+                        let (this_class, this_env) = this.ty.get_class().unwrap();
+                        let method_env =
+                            resolver::class_member(cx, this_class, this_env, method.name.value)?
+                                .map(|(_, _, env)| env)
+                                .unwrap_or(this_env);
+                        (Some(this), method_env)
+                    }
+                };
+                let args = mapping
+                    .args
+                    .iter()
+                    .map(|&(port_id, arg)| lower_call_arg(&builder.with(arg), port_id, method_env))
+                    .collect::<Result<Vec<_>>>()?;
+                let dispatch =
+                    this.is_some() && resolver::is_virtual_method(cx, class.id, method.name.value)?;
+                return Ok(builder.build(
+                    ty,
+                    RvalueKind::MethodCall {
+                        target: mapping.subroutine,
+                        env: method_env,
+                        this,
+                        args,
+                        dispatch,
+                    },
+                ));
+            }
+
+            let args = mapping
+                .args
+                .iter()
+                .map(|&(port_id, arg)| lower_call_arg(&builder.with(arg), port_id, env))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(builder.build(
+                ty,
+                RvalueKind::Call {
+                    target: mapping.subroutine,
+                    args,
+                },
+            ))
+        }
+
+        hir::ExprKind::MethodCall(target, ..) => {
+            if let Some(call) = cx.resolve_class_call(expr_id, env)? {
+                let mapping = call.mapping.as_ref().unwrap();
+                let args = mapping
+                    .args
+                    .iter()
+                    .map(|&(port_id, arg)| lower_call_arg(&builder.with(arg), port_id, call.env))
+                    .collect::<Result<Vec<_>>>()?;
+                let (this, dispatch) = if is_static_member(cx, mapping.subroutine) {
+                    (None, false)
+                } else {
+                    // Methods called through `super` are never dispatched,
+                    // since they explicitly refer to the base class.
+                    let via_super = match cx.hir_of(target)? {
+                        HirNode::Expr(hir::Expr {
+                            kind: hir::ExprKind::Super,
+                            ..
+                        }) => true,
+                        _ => false,
+                    };
+                    let name = cx.hir_of(mapping.subroutine)?;
+                    let name = match name {
+                        HirNode::Subroutine(x) => x.name.value,
+                        _ => unreachable!(),
+                    };
+                    (
+                        Some(cx.mir_rvalue(target, env)),
+                        !via_super && resolver::is_virtual_method(cx, call.class, name)?,
+                    )
+                };
+                return Ok(builder.build(
+                    ty,
+                    RvalueKind::MethodCall {
+                        target: mapping.subroutine,
+                        env: call.env,
+                        this,
+                        args,
+                        dispatch,
+                    },
+                ));
+            }
+            let (op, args) = resolver::resolve_container_method(cx, expr_id, env)?;
+            let target_ty = cx.type_of(target, env)?;
+            let args = args
+                .into_iter()
+                .zip(op.arg_types(target_ty))
+                .map(|(arg, arg_ty)| lower_container_arg(&builder.with(arg), op, arg_ty))
+                .collect::<Result<Vec<_>>>()?;
+            let update = if op.is_update() {
+                Some(cx.mir_lvalue(target, env))
+            } else {
+                None
+            };
+            Ok(builder.build(
+                ty,
+                RvalueKind::ContainerOp {
+                    op,
+                    value: cx.mir_rvalue(target, env),
+                    args,
+                    update,
+                },
+            ))
+        }
+
+        hir::ExprKind::ArrayNew(size, init) => {
+            match *ty.resolve_name() {
+                TypeKind::DynamicArray(..) => (),
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`new[...]` cannot construct a value of type `{}`",
+                            ty
+                        ))
+                        .span(span),
+                    );
+                    return Err(());
+                }
+            }
+            let value = match init {
+                Some(init) => lower_expr_and_cast(cx, init, env, ty),
+                None => builder.build(ty, RvalueKind::Const(cx.type_default_value(ty))),
+            };
+            let size = lower_expr_and_cast(cx, size, env, &ty::INT_TYPE);
+            Ok(builder.build(
+                ty,
+                RvalueKind::ContainerOp {
+                    op: ContainerOp::New,
+                    value,
+                    args: vec![CallArg::Input(size)],
+                    update: None,
+                },
+            ))
+        }
+
+        hir::ExprKind::New(_) => {
+            let call = cx.resolve_class_call(expr_id, env)?.unwrap();
+            let args = match call.mapping {
+                Some(ref mapping) => mapping
+                    .args
+                    .iter()
+                    .map(|&(port_id, arg)| lower_call_arg(&builder.with(arg), port_id, call.env))
+                    .collect::<Result<Vec<_>>>()?,
+                None => vec![],
+            };
+            Ok(builder.build(
+                ty,
+                RvalueKind::ClassNew {
+                    class: call.class,
+                    env: call.env,
+                    args,
+                },
+            ))
+        }
+
+        // `this` and `super` both refer to the object the enclosing method was
+        // called on, and only differ in their type.
+        hir::ExprKind::This | hir::ExprKind::Super => {
+            let (class, _) = resolver::enclosing_class(cx, expr_id).unwrap();
+            Ok(builder.build(ty, RvalueKind::This(class.id)))
+        }
+
+        hir::ExprKind::Null => Ok(builder.build(ty, RvalueKind::Const(cx.type_default_value(ty)))),
+
+        hir::ExprKind::Tagged(_, arg) => lower_tagged(&builder, ty, arg),
+
+        hir::ExprKind::PatternVar(..) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} may only appear in the pattern of a `case matches` statement",
+                    hir.desc_full()
+                ))
+                .span(span),
+            );
+            Err(())
+        }
+
         hir::ExprKind::Inside(expr, ref ranges) => {
             // By default nothing matches.
             let mut check = builder.build(
@@ -324,11 +729,13 @@ fn try_lower_expr<'gcx>(
             for r in ranges {
                 let arg = match r.value {
                     hir::InsideRange::Single(expr) => {
-                        // Check if the value matches the LHS.
+                        // Check if the value matches the LHS. The x and z bits
+                        // in the value act as wildcards. See "11.4.13 Set
+                        // membership operator".
                         let expr_rv = cx.mir_rvalue(expr, env);
                         make_int_comparison(
                             &builder.with(expr),
-                            IntCompOp::Eq,
+                            IntCompOp::WildcardEq,
                             comp_ty,
                             lhs,
                             expr_rv,
@@ -368,6 +775,18 @@ fn try_lower_expr<'gcx>(
             Ok(check)
         }
 
+        hir::ExprKind::Randomize(..) => {
+            cx.emit(
+                DiagBuilder2::error("`randomize()` is not supported in simulation")
+                    .span(span)
+                    .add_note(
+                        "`randomize()` can only be called in constant functions, which are \
+                         evaluated during elaboration",
+                    ),
+            );
+            Err(())
+        }
+
         _ => {
             error!("{:#?}", hir);
             cx.unimp_msg("lowering to mir rvalue of", hir)
@@ -375,6 +794,213 @@ fn try_lower_expr<'gcx>(
     }
 }
 
+/// Lower an argument of a function or task call.
+///
+/// Inputs are cast to the type of the port, which is declared in the parameter
+/// environment `port_env`. Values written back to the caller through output,
+/// inout, and ref ports are not cast, so their type must match the port.
+fn lower_call_arg<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    port_id: NodeId,
+    port_env: ParamEnv,
+) -> Result<CallArg<'gcx>> {
+    let cx = builder.cx;
+    let env = builder.env;
+    let port = match cx.hir_of(port_id)? {
+        HirNode::SubroutinePort(x) => x,
+        _ => unreachable!(),
+    };
+    let ty = cx.type_of(port_id, port_env)?;
+    let input = || lower_expr_and_cast(cx, builder.expr, env, ty);
+    let output = || {
+        let lv = cx.mir_lvalue(builder.expr, env);
+        if lv.ty.is_error() {
+            return Err(());
+        }
+        let compatible = lv.ty == ty
+            || match (
+                map_to_simple_bit_type(cx, lv.ty, env),
+                map_to_simple_bit_type(cx, ty, env),
+            ) {
+                (Some(a), Some(b)) => !lv.ty.is_struct() && !ty.is_struct() && a == b,
+                _ => false,
+            };
+        if !compatible {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` cannot be connected to {} of type `{}`",
+                    builder.span.extract(),
+                    port.desc_full(),
+                    ty
+                ))
+                .span(builder.span)
+                .add_note(format!(
+                    "`{}` has type `{}`, but output arguments require the types to match",
+                    builder.span.extract(),
+                    lv.ty
+                )),
+            );
+            return Err(());
+        }
+        Ok(lv)
+    };
+    Ok(match port.dir {
+        ast::SubroutinePortDir::Input | ast::SubroutinePortDir::ConstRef => CallArg::Input(input()),
+        ast::SubroutinePortDir::Output => CallArg::Output(output()?),
+        ast::SubroutinePortDir::Inout | ast::SubroutinePortDir::Ref => {
+            CallArg::Inout(output()?, input())
+        }
+    })
+}
+
+/// Check whether a class property or method is static.
+fn is_static_member<'gcx>(cx: &impl Context<'gcx>, member_id: NodeId) -> bool {
+    resolver::declaring_class(cx, member_id)
+        .and_then(|class| class.members.iter().find(|m| m.id == member_id))
+        .map(|m| m.is_static)
+        .unwrap_or(false)
+}
+
+/// Lower the implicit `this` object of a class member accessed by name.
+fn lower_implicit_this<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    name: Name,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    match resolver::enclosing_class(builder.cx, builder.expr) {
+        Some((class, member)) if !member.is_static => {
+            let ty = builder.cx.map_to_type(class.id, builder.env)?;
+            Ok(builder.build(ty, RvalueKind::This(class.id)))
+        }
+        _ => {
+            builder.cx.emit(
+                DiagBuilder2::error(format!(
+                    "non-static member `{}` can only be accessed through an object",
+                    name
+                ))
+                .span(builder.span),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Determine the object and property a name refers to, if it is a class
+/// property, such as `x` or `C::x` within a method.
+///
+/// Returns the object, or `None` for static properties, together with the
+/// property and the parameter environment of the class that declares it.
+pub(crate) fn lower_property_by_name<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr_id: NodeId,
+    binding: NodeId,
+    env: ParamEnv,
+) -> Result<Option<(Option<&'gcx Rvalue<'gcx>>, NodeId, ParamEnv)>> {
+    let (class, member) = match (cx.hir_of(binding)?, resolver::declaring_class(cx, binding)) {
+        (HirNode::VarDecl(_), Some(class)) => (
+            class,
+            class.members.iter().find(|m| m.id == binding).unwrap(),
+        ),
+        _ => return Ok(None),
+    };
+    resolver::check_member_access(cx, class, member, expr_id, cx.span(expr_id))?;
+    let builder = Builder {
+        cx,
+        span: cx.span(expr_id),
+        expr: expr_id,
+        env,
+    };
+    let this = if member.is_static {
+        None
+    } else {
+        Some(lower_implicit_this(&builder, member.name.value)?)
+    };
+    let prop_env = match resolver::enclosing_class(cx, expr_id) {
+        Some((within, _)) => resolver::class_member(cx, within.id, env, member.name.value)?
+            .map(|(_, _, env)| env)
+            .unwrap_or(env),
+        None => env,
+    };
+    Ok(Some((this, binding, prop_env)))
+}
+
+/// Lower an argument of a method call on a dynamic array, queue, or associative
+/// array.
+///
+/// The iteration methods `first`, `last`, `next`, and `prev` write the index
+/// back to their argument, which must therefore be of the index type.
+fn lower_container_arg<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    op: ContainerOp,
+    ty: Type<'gcx>,
+) -> Result<CallArg<'gcx>> {
+    let cx = builder.cx;
+    let env = builder.env;
+    let input = || lower_expr_and_cast(cx, builder.expr, env, ty);
+    let output = || {
+        let lv = cx.mir_lvalue(builder.expr, env);
+        if lv.ty.is_error() {
+            return Err(());
+        }
+        if !ty::identical(lv.ty, ty) {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` must be of type `{}`",
+                    builder.span.extract(),
+                    ty
+                ))
+                .span(builder.span)
+                .add_note(format!(
+                    "`{}` has type `{}`, but the index is written back to it",
+                    builder.span.extract(),
+                    lv.ty
+                )),
+            );
+            return Err(());
+        }
+        Ok(lv)
+    };
+    Ok(match op {
+        ContainerOp::First | ContainerOp::Last => CallArg::Output(output()?),
+        ContainerOp::Next | ContainerOp::Prev => CallArg::Inout(output()?, input()),
+        _ => CallArg::Input(input()),
+    })
+}
+
+/// Compute the bits occupied by a member of a packed struct or union.
+///
+/// All members of a union overlay the same bits, starting at the LSB, whereas
+/// struct members start at `offset`. Returns the base of the member's slice and
+/// its simple bit vector type, which also determines the length of the slice.
+pub(crate) fn compute_packed_member<'gcx>(
+    cx: &impl Context<'gcx>,
+    origin: NodeId,
+    env: ParamEnv,
+    member_ty: Type<'gcx>,
+    offset: usize,
+) -> Result<(&'gcx Rvalue<'gcx>, Type<'gcx>)> {
+    let builder = Builder {
+        cx,
+        span: cx.span(origin),
+        expr: origin,
+        env,
+    };
+    let sbvt = match map_to_simple_bit_vector_type(cx, member_ty, env) {
+        Some(x) if x.width() > 0 => x,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("member of type `{}` cannot be accessed", member_ty))
+                    .span(builder.span),
+            );
+            return Err(());
+        }
+    };
+    let base = builder.build(
+        &ty::INT_TYPE,
+        RvalueKind::Const(cx.intern_value(value::make_int(&ty::INT_TYPE, offset.into()))),
+    );
+    Ok((base, sbvt))
+}
+
 /// Compute the base and length of an indexing operation.
 ///
 /// Determine the index of the LSB and the width of the selection. Note that
@@ -391,7 +1017,7 @@ pub(crate) fn compute_indexing<'gcx>(
         expr: origin,
         env,
     };
-    Ok(match mode {
+    let (base, length) = match mode {
         hir::IndexMode::One(index) => (cx.mir_rvalue(index, env), 0),
         hir::IndexMode::Many(ast::RangeMode::RelativeUp, base, delta) => (
             cx.mir_rvalue(base, env),
@@ -433,9 +1059,299 @@ pub(crate) fn compute_indexing<'gcx>(
                 .unwrap();
             (base, length)
         }
+    };
+
+    // Unpacked arrays may have a range that does not start at zero, e.g.
+    // `[1:16]`. Rebase the index such that it addresses the array elements.
+    let offset = match cx.hir_of(origin)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Index(target, _),
+            ..
+        }) => match *cx.type_of(*target, env)?.resolve_name() {
+            TypeKind::UnpackedArray(range, _) => range.offset,
+            _ => 0,
+        },
+        _ => 0,
+    };
+    if offset == 0 {
+        return Ok((base, length));
+    }
+    let base_ty = base.ty.resolve_name();
+    let offset_rvalue = builder.build(
+        base_ty,
+        RvalueKind::Const(cx.intern_value(value::make_int(base_ty, BigInt::from(offset).abs()))),
+    );
+    let base = builder.build(
+        base_ty,
+        RvalueKind::IntBinaryArith {
+            op: if offset > 0 {
+                IntBinaryArithOp::Sub
+            } else {
+                IntBinaryArithOp::Add
+            },
+            sign: base_ty.get_sign().unwrap(),
+            domain: base_ty.get_value_domain().unwrap(),
+            lhs: base,
+            rhs: offset_rvalue,
+        },
+    );
+    Ok((base, length))
+}
+
+/// Lower an array query on the first dimension of a dynamic array, queue, or
+/// associative array.
+///
+/// Dynamic arrays and queues are addressed from 0 to `size - 1`. Associative
+/// arrays only support `$size`.
+fn lower_dynamic_array_query<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    func: hir::ArrayQuery,
+    arg: NodeId,
+    dim: Option<NodeId>,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let env = builder.env;
+    let ty = cx.type_of(arg, env)?;
+    if let Some(dim) = dim {
+        if !cx.constant_int_value_of(dim, env)?.is_one() {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "only the first dimension of `{}` can be queried",
+                    ty
+                ))
+                .span(builder.span),
+            );
+            return Err(());
+        }
+    }
+    let is_assoc = match *ty.resolve_name() {
+        TypeKind::AssocArray(..) => true,
+        _ => false,
+    };
+    if is_assoc && func != hir::ArrayQuery::Size {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`{}` cannot be applied to associative array `{}`",
+                builder.span.extract(),
+                ty
+            ))
+            .span(builder.span),
+        );
+        return Err(());
+    }
+
+    let int = |value: isize| {
+        builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::Const(cx.intern_value(value::make_int(&ty::INT_TYPE, BigInt::from(value)))),
+        )
+    };
+    let size = || {
+        builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::ContainerOp {
+                op: ContainerOp::Size,
+                value: cx.mir_rvalue(arg, env),
+                args: vec![],
+                update: None,
+            },
+        )
+    };
+    Ok(match func {
+        hir::ArrayQuery::Size => size(),
+        hir::ArrayQuery::Left | hir::ArrayQuery::Low => int(0),
+        hir::ArrayQuery::Increment => int(-1),
+        hir::ArrayQuery::Right | hir::ArrayQuery::High => builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::IntBinaryArith {
+                op: IntBinaryArithOp::Sub,
+                sign: ty::Sign::Signed,
+                domain: ty::Domain::TwoValued,
+                lhs: size(),
+                rhs: int(1),
+            },
+        ),
+        hir::ArrayQuery::Dimensions | hir::ArrayQuery::UnpackedDimensions => unreachable!(),
     })
 }
 
+/// Lower a bit vector function, e.g. `$countones(x)`.
+///
+/// The ones are counted by summing up the individual bits of the argument.
+fn lower_bit_query<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: Type<'gcx>,
+    query: hir::BitQuery,
+    arg: NodeId,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let env = builder.env;
+    let int = |value: usize| {
+        builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::Const(cx.intern_value(value::make_int(&ty::INT_TYPE, BigInt::from(value)))),
+        )
+    };
+
+    // Determine the bit vector to operate on.
+    let arg_ty = cx.type_of(arg, env)?;
+    let sbvt = match map_to_simple_bit_vector_type(cx, arg_ty, env) {
+        Some(ty) => ty,
+        None => {
+            let span = cx.span(arg);
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` is not a bit vector", span.extract()))
+                    .span(span)
+                    .add_note(format!("`{}` has type `{}`", span.extract(), arg_ty)),
+            );
+            return Err(());
+        }
+    };
+
+    // The generated code only represents the values 0 and 1, such that no
+    // bit is ever unknown.
+    if query == hir::BitQuery::IsUnknown {
+        return Ok(builder.build(
+            ty,
+            RvalueKind::Const(cx.intern_value(value::make_int(ty, BigInt::zero()))),
+        ));
+    }
+
+    // Count the ones.
+    let value = lower_expr_and_cast(cx, arg, env, sbvt);
+    let bit_ty = sbvt.get_value_domain().unwrap().bit_type();
+    let mut count = int(0);
+    for i in 0..sbvt.width() {
+        let bit = builder.build(
+            bit_ty,
+            RvalueKind::Index {
+                value,
+                base: int(i),
+                length: 0,
+            },
+        );
+        count = builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::IntBinaryArith {
+                op: IntBinaryArithOp::Add,
+                sign: ty::Sign::Signed,
+                domain: ty::Domain::TwoValued,
+                lhs: count,
+                rhs: lower_implicit_cast(builder, bit, &ty::INT_TYPE),
+            },
+        );
+    }
+
+    // Compare the count if needed.
+    let result = match query {
+        hir::BitQuery::CountOnes => return Ok(count),
+        hir::BitQuery::OneHot => IntCompOp::Eq,
+        hir::BitQuery::OneHot0 => IntCompOp::Leq,
+        hir::BitQuery::IsUnknown => unreachable!(),
+    };
+    let result = make_int_comparison(builder, result, &ty::INT_TYPE, count, int(1));
+    Ok(lower_implicit_cast(builder, result, ty))
+}
+
+/// Lower the format items of a display or severity task.
+///
+/// Checks that each argument can be formatted according to its format
+/// specification, and casts it to the type the specification operates on.
+fn lower_format_items<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    items: &[hir::FormatItem],
+) -> Result<Vec<FormatItem<'gcx>>> {
+    let cx = builder.cx;
+    let mut failed = false;
+    let mut lowered = vec![];
+    for item in items {
+        let (mut spec, arg) = match *item {
+            hir::FormatItem::Text(ref text) => {
+                lowered.push(FormatItem::Text(text.clone()));
+                continue;
+            }
+            hir::FormatItem::Scope => {
+                lowered.push(FormatItem::Scope);
+                continue;
+            }
+            hir::FormatItem::Arg(spec, arg) => (spec, arg),
+        };
+        let value = cx.mir_rvalue(arg, builder.env);
+        if value.kind.is_error() {
+            failed = true;
+            continue;
+        }
+
+        // Arguments without a format string are printed according to their
+        // type, unless they are integral.
+        let ty = value.ty.resolve_name();
+        if spec.implicit && ty.is_string() {
+            spec.conv = hir::FormatConv::String;
+        } else if spec.implicit && ty.is_real() {
+            spec.conv = hir::FormatConv::General;
+        }
+
+        // Determine the type the conversion operates on.
+        let sbvt = map_to_simple_bit_vector_type(cx, ty, builder.env);
+        let target = match spec.conv {
+            hir::FormatConv::String if ty.is_string() || sbvt.is_some() => Some(&ty::STRING_TYPE),
+            hir::FormatConv::Char if sbvt.is_some() => Some(&ty::BYTE_TYPE),
+            conv if conv.is_real() && (ty.is_real() || sbvt.is_some()) => Some(&ty::REAL_TYPE),
+            conv if conv.is_integral() && ty.is_real() => Some(&ty::LONGINT_TYPE),
+            conv if conv.is_integral() => sbvt,
+            _ => None,
+        };
+        match target {
+            Some(target) => {
+                let value = lower_implicit_cast(&builder.with(arg), value, target);
+                lowered.push(FormatItem::Arg(spec, value));
+            }
+            None => {
+                let span = cx.span(arg);
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` cannot be formatted with `{}`",
+                        span.extract(),
+                        spec
+                    ))
+                    .span(span)
+                    .add_note(format!(
+                        "`{}` has type `{}`",
+                        span.extract(),
+                        value.ty
+                    )),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(lowered)
+    }
+}
+
+/// Lower the finish number of a `$finish`, `$stop`, or `$fatal`.
+///
+/// The number defaults to 1 if it is omitted.
+fn lower_finish_number<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    arg: Option<NodeId>,
+) -> &'gcx Rvalue<'gcx> {
+    match arg {
+        Some(arg) => lower_expr_and_cast(builder.cx, arg, builder.env, &ty::INT_TYPE),
+        None => builder.build(
+            &ty::INT_TYPE,
+            RvalueKind::Const(
+                builder
+                    .cx
+                    .intern_value(value::make_int(&ty::INT_TYPE, BigInt::one())),
+            ),
+        ),
+    }
+}
+
 /// Lower an HIR expression and implicitly cast to a target type.
 fn lower_expr_and_cast<'gcx>(
     cx: &impl Context<'gcx>,
@@ -491,6 +1407,19 @@ fn lower_implicit_cast<'gcx>(
     // Strip away all named types.
     let from_raw = from.resolve_name();
     let to_raw = to.resolve_name();
+
+    // Class handles may be assigned to handles of a base class, and `null` to
+    // any class handle. The handle itself remains unchanged.
+    let upcast = match (from_raw.get_class(), to_raw.get_class()) {
+        (Some(from_class), Some(to_class)) => {
+            resolver::is_derived_class_type(builder.cx, from_class, to_class).unwrap_or(false)
+        }
+        (None, Some(_)) => from_raw.is_null(),
+        _ => false,
+    };
+    if upcast {
+        return builder.build(to, RvalueKind::Reinterpret(value));
+    }
     trace!(
         "implicitly casting `{}` from {} to {}",
         value.span.extract(),
@@ -504,6 +1433,75 @@ fn lower_implicit_cast<'gcx>(
     let from_size = from_sbvt.map(|ty| ty.width());
     let to_size = to_sbvt.map(|ty| ty.width());
 
+    // Try a conversion to, from, or between real types. Constants are
+    // converted right away.
+    if let RvalueKind::Const(k) = value.kind {
+        if from_raw.is_real() || to_raw.is_real() {
+            let k = value::cast_value(builder.cx, k, to_raw);
+            if ty::identical(k.ty, to_raw) {
+                return builder.build(to, RvalueKind::Const(k));
+            }
+        }
+    }
+    let real = if from_raw.is_real() && to_raw.is_real() {
+        Some(builder.build(to_raw, RvalueKind::CastRealPrecision(value)))
+    } else if to_raw.is_real() && from_sbvt.is_some() {
+        let sign = from_raw.get_sign().unwrap_or(ty::Sign::Unsigned);
+        let value = lower_implicit_cast(
+            builder,
+            value,
+            from_sbvt.unwrap().change_sign(builder.cx, sign),
+        );
+        Some(builder.build(to_raw, RvalueKind::CastIntToReal { sign, value }))
+    } else if from_raw.is_real() && to_sbvt.is_some() {
+        Some(builder.build(
+            to_sbvt.unwrap(),
+            RvalueKind::CastRealToInt {
+                truncate: false,
+                value,
+            },
+        ))
+    } else {
+        None
+    };
+    if let Some(inner) = real {
+        if verbose {
+            builder.cx.emit(
+                DiagBuilder2::note("implicit cast: real conversion")
+                    .span(builder.span)
+                    .add_note(format!(
+                        "from `{}` to `{}`; eventually `{}`",
+                        from_raw, inner.ty, to
+                    )),
+            );
+        }
+        return lower_implicit_cast(builder, inner, to);
+    }
+
+    // Try a conversion from an integral type to a string. Constants such as
+    // string literals are converted right away.
+    if to_raw.is_string() && from_sbvt.is_some() {
+        let value = lower_implicit_cast(builder, value, from_sbvt.unwrap());
+        let inner = match value.kind {
+            RvalueKind::Const(k) => builder.build(
+                to_raw,
+                RvalueKind::Const(value::cast_value(builder.cx, k, to_raw)),
+            ),
+            _ => builder.build(to_raw, RvalueKind::CastIntToString(value)),
+        };
+        if verbose {
+            builder.cx.emit(
+                DiagBuilder2::note("implicit cast: integral to string")
+                    .span(builder.span)
+                    .add_note(format!(
+                        "from `{}` to `{}`; eventually `{}`",
+                        from_raw, inner.ty, to
+                    )),
+            );
+        }
+        return lower_implicit_cast(builder, inner, to);
+    }
+
     if from_size.is_some() && to_size.is_some() && from_size != to_size {
         let from_sbvt = from_sbvt.unwrap();
         let value = lower_implicit_cast(builder, value, from_sbvt);
@@ -675,6 +1673,30 @@ fn lower_implicit_cast<'gcx>(
         return lower_implicit_cast(builder, inner, to);
     }
 
+    // Try union packing and unpacking. Unions are already stored as a bit
+    // vector, so this is merely a reinterpretation of the bits.
+    let reinterpreted = if is_union(builder.cx, from_raw) {
+        from_sbvt.map(|ty| builder.build(ty, RvalueKind::Reinterpret(value)))
+    } else if is_union(builder.cx, to_raw) && to_sbvt.is_some() {
+        let value = lower_implicit_cast(builder, value, to_sbvt.unwrap());
+        Some(builder.build(to, RvalueKind::Reinterpret(value)))
+    } else {
+        None
+    };
+    if let Some(inner) = reinterpreted {
+        if verbose {
+            builder.cx.emit(
+                DiagBuilder2::note("implicit cast: union reinterpretation")
+                    .span(builder.span)
+                    .add_note(format!(
+                        "from `{}` to `{}`; eventually `{}`",
+                        from_raw, inner.ty, to
+                    )),
+            );
+        }
+        return lower_implicit_cast(builder, inner, to);
+    }
+
     // Try struct/array packing.
     let packed = if from_raw.is_struct() {
         Some(pack_struct(builder, value))
@@ -722,13 +1744,21 @@ fn lower_implicit_cast<'gcx>(
     // Complain and abort.
     error!("failed implicit cast from {:?} to {:?}", from, to);
     info!("failed implicit cast from {:?}", value);
-    builder.cx.emit(
-        DiagBuilder2::error(format!(
-            "type `{}` required, but expression has type `{}`",
-            to, from
-        ))
-        .span(value.span),
-    );
+    let mut d = DiagBuilder2::error(format!(
+        "type `{}` required, but expression has type `{}`",
+        to, from
+    ))
+    .span(value.span);
+    if let (Some((from_class, _)), Some((to_class, _))) = (from_raw.get_class(), to_raw.get_class())
+    {
+        if from_class == to_class {
+            d = d.add_note(format!(
+                "`{}` is specialized with different parameters on either side",
+                to_raw
+            ));
+        }
+    }
+    builder.cx.emit(d);
     builder.error()
 }
 
@@ -785,6 +1815,110 @@ fn pack_struct<'gcx>(
     }
 }
 
+/// Construct a tagged union.
+///
+/// The tag occupies the upper bits of the union, and the member value is
+/// zero-extended to fill the remaining bits.
+fn lower_tagged<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: Type<'gcx>,
+    arg: Option<NodeId>,
+) -> Result<&'gcx Rvalue<'gcx>> {
+    let cx = builder.cx;
+    let (def_id, index) = resolver::resolve_tagged_member(cx, builder.expr, builder.env)?;
+    let def = cx.struct_def(def_id)?;
+    let member = &def.fields[index];
+    let member_ty = cx.map_to_type(member.ty, builder.env)?;
+    let tag_width = def.tag_width();
+    let data_width = ty::bit_size_of_type(cx, ty, builder.env)? - tag_width;
+    let make_bits = |width| {
+        cx.intern_type(TypeKind::BitVector {
+            domain: ty::Domain::FourValued,
+            sign: ty::Sign::Unsigned,
+            range: ty::Range {
+                size: width,
+                dir: ty::RangeDir::Down,
+                offset: 0isize,
+            },
+            dubbed: false,
+        })
+    };
+
+    // Lower the member value, which is absent for `void` members.
+    let is_void = member_ty.resolve_name().is_void();
+    let data = match arg {
+        Some(arg) if !is_void => {
+            let value = lower_expr_and_cast(cx, arg, builder.env, member_ty);
+            let value = lower_implicit_cast(builder, value, make_bits(data_width));
+            Some(value)
+        }
+        None if is_void => None,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "member `{}` {} a value",
+                    member.name,
+                    if is_void {
+                        "is `void` and cannot have"
+                    } else {
+                        "requires"
+                    }
+                ))
+                .span(builder.span)
+                .add_note("The member is declared here:")
+                .span(member.name.span),
+            );
+            return Err(());
+        }
+    };
+
+    // Concatenate the tag and the member value.
+    let mut fields = vec![];
+    if tag_width > 0 {
+        let tag_ty = make_bits(tag_width);
+        fields.push(builder.build(
+            tag_ty,
+            RvalueKind::Const(cx.intern_value(value::make_int(tag_ty, index.into()))),
+        ));
+    }
+    if data_width > 0 {
+        fields.push(data.unwrap_or_else(|| {
+            let data_ty = make_bits(data_width);
+            builder.build(
+                data_ty,
+                RvalueKind::Const(cx.intern_value(value::make_int(data_ty, Zero::zero()))),
+            )
+        }));
+    }
+    let bits = builder.build(
+        make_bits(tag_width + data_width),
+        RvalueKind::Concat(fields),
+    );
+    Ok(builder.build(ty, RvalueKind::Reinterpret(bits)))
+}
+
+/// Extract a member from a union.
+fn lower_union_member<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    value: &'gcx Rvalue<'gcx>,
+    member_ty: Type<'gcx>,
+) -> &'gcx Rvalue<'gcx> {
+    let (base, sbvt) =
+        match compute_packed_member(builder.cx, builder.expr, builder.env, member_ty, 0) {
+            Ok(x) => x,
+            Err(()) => return builder.error(),
+        };
+    let bits = builder.build(
+        sbvt,
+        RvalueKind::Index {
+            value,
+            base,
+            length: sbvt.width(),
+        },
+    );
+    lower_implicit_cast(builder, bits, member_ty)
+}
+
 /// Pack an array as a simple bit vector.
 fn pack_array<'gcx>(
     builder: &Builder<'_, impl Context<'gcx>>,
@@ -853,8 +1987,11 @@ fn unpack_struct<'gcx>(
         Err(()) => return builder.error(),
     };
 
-    // Unpack each of the fields.
-    let mut offset = 0;
+    // Unpack each of the fields. The first field occupies the MSBs.
+    let mut offset = match ty::bit_size_of_type(builder.cx, ty, builder.env) {
+        Ok(w) => w,
+        Err(()) => return builder.error(),
+    };
     let mut unpacked_fields = vec![];
     for field in &def.fields {
         let field_ty = match builder.cx.map_to_type(field.ty, builder.env) {
@@ -879,6 +2016,7 @@ fn unpack_struct<'gcx>(
             }
         };
         let w = sbvt.width();
+        offset -= w;
         let i = builder.build(
             &ty::INT_TYPE,
             RvalueKind::Const(
@@ -897,7 +2035,6 @@ fn unpack_struct<'gcx>(
         );
         let field_value = lower_implicit_cast(builder, field_value, field_ty);
         unpacked_fields.push(field_value);
-        offset += w;
     }
 
     // Construct the struct.
@@ -967,8 +2104,9 @@ fn lower_array_pattern<'gcx>(
     mapping: &[(PatternMapping, NodeId)],
     ty: Type<'gcx>,
 ) -> &'gcx Rvalue<'gcx> {
-    let (length, offset, elem_ty) = match *ty {
+    let (length, offset, elem_ty) = match *ty.resolve_name() {
         TypeKind::PackedArray(w, t) => (w, 0isize, t),
+        TypeKind::UnpackedArray(r, t) => (r.size, r.offset, t),
         TypeKind::BitScalar { domain, .. } => (1, 0isize, domain.bit_type()),
         TypeKind::BitVector { domain, range, .. } => (range.size, range.offset, domain.bit_type()),
         _ => unreachable!("array pattern with invalid input type"),
@@ -1075,9 +2213,11 @@ fn lower_array_pattern<'gcx>(
         }
     }
 
-    match *ty {
+    match *ty.resolve_name() {
         _ if failed => builder.error(),
-        TypeKind::PackedArray(..) => builder.build(ty, RvalueKind::ConstructArray(values)),
+        TypeKind::PackedArray(..) | TypeKind::UnpackedArray(..) => {
+            builder.build(ty, RvalueKind::ConstructArray(values))
+        }
         TypeKind::BitScalar { .. } => {
             assert_eq!(values.len(), 1);
             values[&0]
@@ -1254,6 +2394,14 @@ fn lower_struct_pattern<'gcx>(
     }
 }
 
+/// Check whether a type is a union.
+fn is_union<'gcx>(cx: &impl Context<'gcx>, ty: Type<'gcx>) -> bool {
+    match ty.get_struct_def() {
+        Some(id) => cx.struct_def(id).map(|def| def.is_union()).unwrap_or(false),
+        None => false,
+    }
+}
+
 /// Try to convert a type to its equivalent simple bit vector type.
 ///
 /// All *integral* data types have an equivalent *simple bit vector type*. These
@@ -1271,7 +2419,19 @@ fn map_to_simple_bit_type<'gcx>(
     env: ParamEnv,
 ) -> Option<Type<'gcx>> {
     let bits = match *ty {
-        TypeKind::Error | TypeKind::Void | TypeKind::Time => return None,
+        TypeKind::Error
+        | TypeKind::Void
+        | TypeKind::Time
+        | TypeKind::Real
+        | TypeKind::ShortReal
+        | TypeKind::String
+        | TypeKind::Interface { .. }
+        | TypeKind::Class { .. }
+        | TypeKind::Null
+        | TypeKind::UnpackedArray(..)
+        | TypeKind::DynamicArray(..)
+        | TypeKind::Queue(..)
+        | TypeKind::AssocArray(..) => return None,
         TypeKind::Named(_, _, ty) => return map_to_simple_bit_type(cx, ty, env),
         TypeKind::BitVector { .. } => return Some(ty),
         TypeKind::BitScalar { .. } => return Some(ty),
@@ -1325,6 +2485,9 @@ fn lower_unary<'gcx>(
 ) -> &'gcx Rvalue<'gcx> {
     // Determine the category of the operation.
     match op {
+        hir::UnaryOp::Pos | hir::UnaryOp::Neg if ty.is_real() => {
+            lower_real_unary_arith(builder, ty, op, arg)
+        }
         hir::UnaryOp::Pos | hir::UnaryOp::Neg => lower_int_unary_arith(builder, ty, op, arg),
         hir::UnaryOp::BitNot => lower_unary_bitwise(builder, ty, op, arg),
         hir::UnaryOp::LogicNot => lower_unary_logic(builder, op, arg),
@@ -1350,6 +2513,15 @@ fn lower_binary<'gcx>(
     rhs: NodeId,
 ) -> &'gcx Rvalue<'gcx> {
     match op {
+        hir::BinaryOp::Add
+        | hir::BinaryOp::Sub
+        | hir::BinaryOp::Mul
+        | hir::BinaryOp::Div
+        | hir::BinaryOp::Pow
+            if ty.is_real() =>
+        {
+            lower_real_binary_arith(builder, ty, op, lhs, rhs)
+        }
         hir::BinaryOp::Add
         | hir::BinaryOp::Sub
         | hir::BinaryOp::Mul
@@ -1358,6 +2530,10 @@ fn lower_binary<'gcx>(
         | hir::BinaryOp::Pow => lower_int_binary_arith(builder, ty, op, lhs, rhs),
         hir::BinaryOp::Eq
         | hir::BinaryOp::Neq
+        | hir::BinaryOp::CaseEq
+        | hir::BinaryOp::CaseNeq
+        | hir::BinaryOp::WildcardEq
+        | hir::BinaryOp::WildcardNeq
         | hir::BinaryOp::Lt
         | hir::BinaryOp::Leq
         | hir::BinaryOp::Gt
@@ -1473,6 +2649,87 @@ fn lower_int_binary_arith<'gcx>(
     )
 }
 
+/// Map a real unary arithmetic operator to MIR.
+fn lower_real_unary_arith<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: Type<'gcx>,
+    op: hir::UnaryOp,
+    arg: NodeId,
+) -> &'gcx Rvalue<'gcx> {
+    let ty = ty.resolve_name();
+    let arg = lower_expr_and_cast(builder.cx, arg, builder.env, ty);
+    let op = match op {
+        hir::UnaryOp::Pos => return arg,
+        hir::UnaryOp::Neg => IntUnaryArithOp::Neg,
+        _ => unreachable!("{:?} is not a real unary arithmetic operator", op),
+    };
+    builder.build(ty, RvalueKind::RealUnaryArith { op, arg })
+}
+
+/// Map a real binary arithmetic operator to MIR.
+fn lower_real_binary_arith<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: Type<'gcx>,
+    op: hir::BinaryOp,
+    lhs: NodeId,
+    rhs: NodeId,
+) -> &'gcx Rvalue<'gcx> {
+    let ty = ty.resolve_name();
+    let lhs = lower_expr_and_cast(builder.cx, lhs, builder.env, ty);
+    let rhs = lower_expr_and_cast(builder.cx, rhs, builder.env, ty);
+    let op = match op {
+        hir::BinaryOp::Add => RealBinaryArithOp::Add,
+        hir::BinaryOp::Sub => RealBinaryArithOp::Sub,
+        hir::BinaryOp::Mul => RealBinaryArithOp::Mul,
+        hir::BinaryOp::Div => RealBinaryArithOp::Div,
+        hir::BinaryOp::Pow => RealBinaryArithOp::Pow,
+        _ => unreachable!("{:?} is not a real binary arithmetic operator", op),
+    };
+    builder.build(ty, RvalueKind::RealBinaryArith { op, lhs, rhs })
+}
+
+/// Map a real conversion function to MIR.
+///
+/// See "20.5 Conversion functions".
+fn lower_real_conv<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    ty: Type<'gcx>,
+    conv: hir::RealConv,
+    arg: NodeId,
+) -> &'gcx Rvalue<'gcx> {
+    let cx = builder.cx;
+    let env = builder.env;
+    match conv {
+        hir::RealConv::RealToInt => {
+            let value = lower_expr_and_cast(cx, arg, env, &ty::REAL_TYPE);
+            builder.build(
+                ty,
+                RvalueKind::CastRealToInt {
+                    truncate: true,
+                    value,
+                },
+            )
+        }
+        hir::RealConv::IntToReal => lower_expr_and_cast(cx, arg, env, ty),
+        hir::RealConv::RealToBits => {
+            let value = lower_expr_and_cast(cx, arg, env, &ty::REAL_TYPE);
+            builder.build(ty, RvalueKind::Reinterpret(value))
+        }
+        hir::RealConv::ShortRealToBits => {
+            let value = lower_expr_and_cast(cx, arg, env, &ty::SHORTREAL_TYPE);
+            builder.build(ty, RvalueKind::Reinterpret(value))
+        }
+        hir::RealConv::BitsToReal => {
+            let value = lower_expr_and_cast(cx, arg, env, cx.mkty_bit_vector(64));
+            builder.build(ty, RvalueKind::Reinterpret(value))
+        }
+        hir::RealConv::BitsToShortReal => {
+            let value = lower_expr_and_cast(cx, arg, env, cx.mkty_bit_vector(32));
+            builder.build(ty, RvalueKind::Reinterpret(value))
+        }
+    }
+}
+
 /// Map an integer comparison operator to MIR.
 fn lower_int_comparison<'gcx>(
     builder: &Builder<'_, impl Context<'gcx>>,
@@ -1490,6 +2747,10 @@ fn lower_int_comparison<'gcx>(
     let op = match op {
         hir::BinaryOp::Eq => IntCompOp::Eq,
         hir::BinaryOp::Neq => IntCompOp::Neq,
+        hir::BinaryOp::CaseEq => IntCompOp::CaseEq,
+        hir::BinaryOp::CaseNeq => IntCompOp::CaseNeq,
+        hir::BinaryOp::WildcardEq => IntCompOp::WildcardEq,
+        hir::BinaryOp::WildcardNeq => IntCompOp::WildcardNeq,
         hir::BinaryOp::Lt => IntCompOp::Lt,
         hir::BinaryOp::Leq => IntCompOp::Leq,
         hir::BinaryOp::Gt => IntCompOp::Gt,
@@ -1497,8 +2758,90 @@ fn lower_int_comparison<'gcx>(
         _ => unreachable!("{:?} is not an integer binary comparison operator", op),
     };
 
-    // Assemble the node.
-    make_int_comparison(builder, op, ty, lhs, rhs)
+    // Class handles can only be checked for equality.
+    if ty.is_class() || ty.is_null() {
+        return match op {
+            IntCompOp::Eq | IntCompOp::CaseEq => {
+                make_handle_comparison(builder, IntCompOp::Eq, ty, lhs, rhs)
+            }
+            IntCompOp::Neq | IntCompOp::CaseNeq => {
+                make_handle_comparison(builder, IntCompOp::Neq, ty, lhs, rhs)
+            }
+            _ => {
+                builder.cx.emit(
+                    DiagBuilder2::error(format!(
+                        "class handles of type `{}` can only be compared for equality",
+                        ty
+                    ))
+                    .span(builder.span),
+                );
+                builder.error()
+            }
+        };
+    }
+
+    // Reals and strings have no x and z bits, such that case equality is the
+    // same as regular equality. Wildcard equality is only defined for
+    // integral operands. See "11.4.6 Wildcard equality operators".
+    if ty.is_real() || ty.is_string() {
+        match op {
+            IntCompOp::CaseEq => {
+                return lower_int_comparison_of(builder, IntCompOp::Eq, ty, lhs, rhs)
+            }
+            IntCompOp::CaseNeq => {
+                return lower_int_comparison_of(builder, IntCompOp::Neq, ty, lhs, rhs)
+            }
+            IntCompOp::WildcardEq | IntCompOp::WildcardNeq => {
+                builder.cx.emit(
+                    DiagBuilder2::error(format!(
+                        "wildcard equality cannot be applied to operands of type `{}`",
+                        ty
+                    ))
+                    .span(builder.span),
+                );
+                return builder.error();
+            }
+            _ => (),
+        }
+    }
+    lower_int_comparison_of(builder, op, ty, lhs, rhs)
+}
+
+/// Map an integer comparison operator to MIR, given its lowered operands.
+fn lower_int_comparison_of<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    op: IntCompOp,
+    ty: Type<'gcx>,
+    lhs: &'gcx Rvalue<'gcx>,
+    rhs: &'gcx Rvalue<'gcx>,
+) -> &'gcx Rvalue<'gcx> {
+    if ty.is_real() {
+        make_real_comparison(builder, op, ty, lhs, rhs)
+    } else if ty.is_string() {
+        let lhs = lower_implicit_cast(builder, lhs, ty);
+        let rhs = lower_implicit_cast(builder, rhs, ty);
+        builder.build(&ty::BIT_TYPE, RvalueKind::StringComp { op, lhs, rhs })
+    } else {
+        make_int_comparison(builder, op, ty, lhs, rhs)
+    }
+}
+
+/// Compare two class handles.
+///
+/// The handles are compared as the 64 bit integers that represent them.
+fn make_handle_comparison<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    op: IntCompOp,
+    ty: Type<'gcx>,
+    lhs: &'gcx Rvalue<'gcx>,
+    rhs: &'gcx Rvalue<'gcx>,
+) -> &'gcx Rvalue<'gcx> {
+    let handle_ty = builder.cx.mkty_bit_vector(64);
+    let lhs = lower_implicit_cast(builder, lhs, ty);
+    let rhs = lower_implicit_cast(builder, rhs, ty);
+    let lhs = builder.build(handle_ty, RvalueKind::Reinterpret(lhs));
+    let rhs = builder.build(handle_ty, RvalueKind::Reinterpret(rhs));
+    make_int_comparison(builder, op, handle_ty, lhs, rhs)
 }
 
 /// Map an integer comparison operator to MIR.
@@ -1509,9 +2852,52 @@ fn make_int_comparison<'gcx>(
     lhs: &'gcx Rvalue<'gcx>,
     rhs: &'gcx Rvalue<'gcx>,
 ) -> &'gcx Rvalue<'gcx> {
+    // The x and z bits of a constant right-hand side of a wildcard comparison
+    // match any bit. Mask them out on both sides.
+    let (mask, rhs) = match (op, &rhs.kind) {
+        (IntCompOp::WildcardEq, &RvalueKind::Const(k))
+        | (IntCompOp::WildcardNeq, &RvalueKind::Const(k)) => match wildcard_mask(k, ty.width()) {
+            Some(mask) => {
+                // The wildcard bits are fully handled by the mask.
+                let value = match k.kind {
+                    ValueKind::Int(ref v, ..) => v.clone(),
+                    _ => unreachable!(),
+                };
+                let k = builder
+                    .cx
+                    .intern_value(value::make_int(k.ty.resolve_name(), value));
+                (Some(mask), builder.build(rhs.ty, RvalueKind::Const(k)))
+            }
+            None => (None, rhs),
+        },
+        _ => (None, rhs),
+    };
+
     // Cast the operands to the operator type.
-    let lhs = lower_implicit_cast(builder, lhs, ty);
-    let rhs = lower_implicit_cast(builder, rhs, ty);
+    let mut lhs = lower_implicit_cast(builder, lhs, ty);
+    let mut rhs = lower_implicit_cast(builder, rhs, ty);
+    if let Some(mask) = mask {
+        let mask = builder.build(
+            ty,
+            RvalueKind::Const(
+                builder
+                    .cx
+                    .intern_value(value::make_int(ty.resolve_name(), mask)),
+            ),
+        );
+        let apply = |value| {
+            builder.build(
+                ty,
+                RvalueKind::BinaryBitwise {
+                    op: BinaryBitwiseOp::And,
+                    lhs: value,
+                    rhs: mask,
+                },
+            )
+        };
+        lhs = apply(lhs);
+        rhs = apply(rhs);
+    }
 
     // Assemble the node.
     builder.build(
@@ -1526,6 +2912,39 @@ fn make_int_comparison<'gcx>(
     )
 }
 
+/// Compute the mask of the non-wildcard bits of a constant.
+///
+/// Returns `None` if the constant has no x or z bits.
+fn wildcard_mask(value: value::Value, width: usize) -> Option<BigInt> {
+    let special_bits = match value.kind {
+        ValueKind::Int(_, ref special_bits, _) if special_bits.any() => special_bits,
+        _ => return None,
+    };
+    let mut mask = BigInt::zero();
+    for i in (0..width).rev() {
+        mask <<= 1;
+        let special = i < special_bits.len() && special_bits[special_bits.len() - 1 - i];
+        if !special {
+            mask |= BigInt::one();
+        }
+    }
+    Some(mask)
+}
+
+/// Map a real comparison operator to MIR.
+fn make_real_comparison<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    op: IntCompOp,
+    ty: Type<'gcx>,
+    lhs: &'gcx Rvalue<'gcx>,
+    rhs: &'gcx Rvalue<'gcx>,
+) -> &'gcx Rvalue<'gcx> {
+    let ty = ty.resolve_name();
+    let lhs = lower_implicit_cast(builder, lhs, ty);
+    let rhs = lower_implicit_cast(builder, rhs, ty);
+    builder.build(&ty::BIT_TYPE, RvalueKind::RealComp { op, lhs, rhs })
+}
+
 /// Map an integer shift operator to MIR.
 fn lower_shift<'gcx>(
     builder: &Builder<'_, impl Context<'gcx>>,
@@ -1809,6 +3228,25 @@ fn implicit_cast_to_bool<'gcx>(
     builder: &Builder<'_, impl Context<'gcx>>,
     value: &'gcx Rvalue<'gcx>,
 ) -> &'gcx Rvalue<'gcx> {
+    // Reals are true if they are non-zero.
+    if value.ty.is_real() {
+        let ty = value.ty.resolve_name();
+        let zero = builder.build(
+            ty,
+            RvalueKind::Const(builder.cx.intern_value(value::make_real(ty, 0.0))),
+        );
+        return make_real_comparison(builder, IntCompOp::Neq, ty, value, zero);
+    }
+
+    // Class handles are true if they are not `null`.
+    if value.ty.is_class() {
+        let null = builder.build(
+            &ty::NULL_TYPE,
+            RvalueKind::Const(builder.cx.type_default_value(&ty::NULL_TYPE)),
+        );
+        return make_handle_comparison(builder, IntCompOp::Neq, value.ty, value, null);
+    }
+
     // Map the value to a simple bit type.
     let sbvt = match map_to_simple_bit_type(builder.cx, value.ty, builder.env) {
         Some(sbvt) => sbvt,
@@ -1912,9 +3350,28 @@ fn lower_int_incdec<'gcx>(
                 rhs: one,
             },
         )
+    } else if lv.ty.is_real() {
+        // Real values add/subtract one as well.
+        let ty = lv.ty.resolve_name();
+        let op = match op {
+            hir::UnaryOp::PreInc | hir::UnaryOp::PostInc => RealBinaryArithOp::Add,
+            hir::UnaryOp::PreDec | hir::UnaryOp::PostDec => RealBinaryArithOp::Sub,
+            _ => unreachable!(),
+        };
+        let one = builder.build(
+            ty,
+            RvalueKind::Const(builder.cx.intern_value(value::make_real(ty, 1.0))),
+        );
+        builder.build(
+            ty,
+            RvalueKind::RealBinaryArith {
+                op,
+                lhs: rv,
+                rhs: one,
+            },
+        )
     } else {
         // Everything else we cannot do.
-        // TODO(fschuiki): Technically `real` supports this operator as well...
         builder.cx.emit(
             DiagBuilder2::error(format!("`{}` cannot be incremented/decremented", lv.ty))
                 .span(builder.span),
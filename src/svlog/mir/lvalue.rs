@@ -37,6 +37,8 @@ pub enum LvalueKind<'a> {
     Var(NodeId),
     /// A reference to a port declaration.
     Port(NodeId),
+    /// A reference to an interface signal, given as `(binding, signal)`.
+    IntfSignal(NodeId, NodeId),
     /// A bit- or part-select.
     Index {
         value: &'a Lvalue<'a>,
@@ -45,6 +47,23 @@ pub enum LvalueKind<'a> {
     },
     /// A struct field access.
     Member { value: &'a Lvalue<'a>, field: usize },
+    /// An element of a dynamic array, queue, or associative array.
+    ///
+    /// Such elements cannot be referred to directly. Instead, assigning to
+    /// them replaces the entire container `value`, whose current value is
+    /// `current`, with a modified copy.
+    ContainerElement {
+        value: &'a Lvalue<'a>,
+        current: &'a Rvalue<'a>,
+        index: &'a Rvalue<'a>,
+    },
+    /// A property of a class object, declared in the class's parameter
+    /// environment `env`. Static properties have no `this` object.
+    ClassProperty {
+        this: Option<&'a Rvalue<'a>>,
+        prop: NodeId,
+        env: ParamEnv,
+    },
     /// An error occurred during lowering.
     Error,
 }
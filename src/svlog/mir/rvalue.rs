@@ -58,6 +58,27 @@ pub enum RvalueKind<'a> {
     CastSign(ty::Sign, &'a Rvalue<'a>),
     /// A cast from a simple bit type to a boolean.
     CastToBool(&'a Rvalue<'a>),
+    /// A cast from a simple bit vector type to a real type. E.g. `int` to
+    /// `real`.
+    CastIntToReal {
+        sign: ty::Sign,
+        value: &'a Rvalue<'a>,
+    },
+    /// A cast from a real type to a simple bit vector type. E.g. `real` to
+    /// `int`. Rounds to the nearest integer, or truncates towards zero if
+    /// `truncate` is set.
+    CastRealToInt {
+        truncate: bool,
+        value: &'a Rvalue<'a>,
+    },
+    /// A cast between the real types. E.g. `real` to `shortreal`.
+    CastRealPrecision(&'a Rvalue<'a>),
+    /// A cast from a simple bit vector type to a string. E.g. a string
+    /// literal to `string`.
+    CastIntToString(&'a Rvalue<'a>),
+    /// Reinterpret the bits of a value as a different type of the same size.
+    /// E.g. `real` to `bit [63:0]` for `$realtobits`.
+    Reinterpret(&'a Rvalue<'a>),
     /// Shrink the width of a vector type. E.g. `bit [31:0]` to `bit [7:0]`.
     Truncate(usize, &'a Rvalue<'a>),
     /// Increase the width of a vector by zero extension. E.g. `bit [7:0]` to
@@ -112,6 +133,55 @@ pub enum RvalueKind<'a> {
         lhs: &'a Rvalue<'a>,
         rhs: &'a Rvalue<'a>,
     },
+    /// A real unary arithmetic operator.
+    RealUnaryArith {
+        op: IntUnaryArithOp,
+        arg: &'a Rvalue<'a>,
+    },
+    /// A real binary arithmetic operator.
+    RealBinaryArith {
+        op: RealBinaryArithOp,
+        lhs: &'a Rvalue<'a>,
+        rhs: &'a Rvalue<'a>,
+    },
+    /// A real comparison operator.
+    RealComp {
+        op: IntCompOp,
+        lhs: &'a Rvalue<'a>,
+        rhs: &'a Rvalue<'a>,
+    },
+    /// A real math function, e.g. `$ln(x)` or `$pow(x, y)`.
+    RealMath {
+        func: hir::RealMath,
+        args: Vec<&'a Rvalue<'a>>,
+    },
+    /// The current simulation time as a real, i.e. `$realtime`.
+    RealTime,
+    /// The current simulation time as an integer, i.e. `$time`.
+    Time,
+    /// A display task, i.e. `$display` or `$write`.
+    Display {
+        newline: bool,
+        items: Vec<FormatItem<'a>>,
+    },
+    /// A severity task, e.g. `$error`. A `$fatal` additionally carries the
+    /// finish number passed to `$finish` after the message has been printed.
+    Severity {
+        severity: hir::SeverityTask,
+        finish: Option<&'a Rvalue<'a>>,
+        items: Vec<FormatItem<'a>>,
+    },
+    /// A simulation control task, i.e. `$finish` or `$stop`, with its
+    /// diagnostics level.
+    SimControl(hir::SimControl, &'a Rvalue<'a>),
+    /// A string comparison operator.
+    StringComp {
+        op: IntCompOp,
+        lhs: &'a Rvalue<'a>,
+        rhs: &'a Rvalue<'a>,
+    },
+    /// Concatenate multiple strings.
+    StringConcat(Vec<&'a Rvalue<'a>>),
     /// Concatenate multiple values.
     ///
     /// The values are cast to and treated as packed bit vectors, and the result
@@ -126,6 +196,14 @@ pub enum RvalueKind<'a> {
     Var(NodeId),
     /// A reference to a port declaration.
     Port(NodeId),
+    /// A reference to an interface signal, given as `(binding, signal)`.
+    IntfSignal(NodeId, NodeId),
+    /// A reference to a declaration in another module, given as the id of the
+    /// hierarchical name through which it is accessed.
+    Hier(NodeId),
+    /// A reference to a declaration in an iteration of a generate loop, given
+    /// as the id of the field access through which it is accessed.
+    GenLoop(NodeId),
     /// A bit- or part-select.
     Index {
         value: &'a Rvalue<'a>,
@@ -161,10 +239,81 @@ pub enum RvalueKind<'a> {
         /// the rvalue, but may be different (e.g. for the `i++` or `i--`).
         result: &'a Rvalue<'a>,
     },
+    /// A function or task call.
+    Call {
+        target: NodeId,
+        args: Vec<CallArg<'a>>,
+    },
+    /// A call to a method of a class, which is declared in the class's
+    /// parameter environment `env`.
+    ///
+    /// Static methods are called without a `this` object. Virtual methods are
+    /// dispatched at runtime to the implementation of the object's class.
+    MethodCall {
+        target: NodeId,
+        env: ParamEnv,
+        this: Option<&'a Rvalue<'a>>,
+        args: Vec<CallArg<'a>>,
+        dispatch: bool,
+    },
+    /// The construction of an object of the class `class`, which is
+    /// specialized by the parameter environment `env`. The arguments are
+    /// passed to the class's constructor, if it has one.
+    ClassNew {
+        class: NodeId,
+        env: ParamEnv,
+        args: Vec<CallArg<'a>>,
+    },
+    /// The object on which the method of a class was called.
+    This(NodeId),
+    /// A property of a class object, declared in the class's parameter
+    /// environment `env`. Static properties have no `this` object.
+    ClassProperty {
+        this: Option<&'a Rvalue<'a>>,
+        prop: NodeId,
+        env: ParamEnv,
+    },
+    /// An operation on a dynamic array, queue, associative array, or string.
+    ///
+    /// The operation is applied to the container `value`. Operations that
+    /// modify the container, such as `push_back`, write the modified container
+    /// to `update`.
+    ContainerOp {
+        op: ContainerOp,
+        value: &'a Rvalue<'a>,
+        args: Vec<CallArg<'a>>,
+        update: Option<&'a Lvalue<'a>>,
+    },
     /// An error occurred during lowering.
     Error,
 }
 
+/// An argument of a function or task call.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CallArg<'a> {
+    /// An input argument, passed by value.
+    Input(&'a Rvalue<'a>),
+    /// An output argument, written back to the lvalue when the call returns.
+    Output(&'a Lvalue<'a>),
+    /// An inout or ref argument, passed in by value and written back to the
+    /// lvalue when the call returns.
+    Inout(&'a Lvalue<'a>, &'a Rvalue<'a>),
+}
+
+/// A piece of the output of a display or severity task.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FormatItem<'a> {
+    /// Literal text.
+    Text(String),
+    /// A value formatted according to a format specification.
+    ///
+    /// Integral conversions are applied to simple bit vectors, `%s` to
+    /// strings, and the real conversions to reals.
+    Arg(hir::FormatSpec, &'a Rvalue<'a>),
+    /// The hierarchical name of the current scope, i.e. `%m`.
+    Scope,
+}
+
 impl<'a> RvalueKind<'a> {
     /// Check whether the rvalue represents a lowering error tombstone.
     pub fn is_error(&self) -> bool {
@@ -175,6 +324,127 @@ impl<'a> RvalueKind<'a> {
     }
 }
 
+/// The operations on dynamic arrays, queues, associative arrays, and strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerOp {
+    /// `new[n]`, optionally initialized from another array.
+    New,
+    /// `x.size()`, `x.num()`, or `x.len()`
+    Size,
+    /// `x[i]` or `x.getc(i)`
+    Get,
+    /// `x[i] = v` or `x.putc(i, v)`
+    Set,
+    /// `x.delete()`
+    DeleteAll,
+    /// `x.delete(i)`
+    Delete,
+    /// `x.exists(i)`
+    Exists,
+    /// `x.first(i)`
+    First,
+    /// `x.last(i)`
+    Last,
+    /// `x.next(i)`
+    Next,
+    /// `x.prev(i)`
+    Prev,
+    /// `x.insert(i, v)`
+    Insert,
+    /// `x.push_front(v)`
+    PushFront,
+    /// `x.push_back(v)`
+    PushBack,
+    /// `x.pop_front()`
+    PopFront,
+    /// `x.pop_back()`
+    PopBack,
+    /// `x.substr(i, j)`
+    Substr,
+    /// `x.toupper()`
+    ToUpper,
+    /// `x.tolower()`
+    ToLower,
+    /// `x.atoi()`
+    Atoi,
+    /// `x.itoa(v)`
+    Itoa,
+}
+
+impl ContainerOp {
+    /// Check whether the operation modifies the container.
+    pub fn is_update(self) -> bool {
+        match self {
+            ContainerOp::Set
+            | ContainerOp::DeleteAll
+            | ContainerOp::Delete
+            | ContainerOp::Insert
+            | ContainerOp::PushFront
+            | ContainerOp::PushBack
+            | ContainerOp::PopFront
+            | ContainerOp::PopBack
+            | ContainerOp::Itoa => true,
+            _ => false,
+        }
+    }
+
+    /// Determine the types of the operation's arguments, given the type of
+    /// the container.
+    pub fn arg_types<'t>(self, ty: Type<'t>) -> Vec<Type<'t>> {
+        let elem = ty.get_dynamic_element().unwrap_or(&ty::ERROR_TYPE);
+        let index = ty.get_dynamic_index().unwrap_or(&ty::ERROR_TYPE);
+        match self {
+            ContainerOp::New => vec![&ty::INT_TYPE],
+            ContainerOp::Size
+            | ContainerOp::DeleteAll
+            | ContainerOp::PopFront
+            | ContainerOp::PopBack
+            | ContainerOp::ToUpper
+            | ContainerOp::ToLower
+            | ContainerOp::Atoi => vec![],
+            ContainerOp::Get
+            | ContainerOp::Delete
+            | ContainerOp::Exists
+            | ContainerOp::First
+            | ContainerOp::Last
+            | ContainerOp::Next
+            | ContainerOp::Prev => vec![index],
+            ContainerOp::Set | ContainerOp::Insert => vec![index, elem],
+            ContainerOp::PushFront | ContainerOp::PushBack => vec![elem],
+            ContainerOp::Substr => vec![index, index],
+            ContainerOp::Itoa => vec![&ty::INTEGER_TYPE],
+        }
+    }
+
+    /// Determine the type of the operation's result, given the type of the
+    /// container.
+    pub fn result_type<'t>(self, ty: Type<'t>) -> Type<'t> {
+        match self {
+            ContainerOp::New
+            | ContainerOp::Substr
+            | ContainerOp::ToUpper
+            | ContainerOp::ToLower => ty,
+            ContainerOp::Atoi => &ty::INTEGER_TYPE,
+            ContainerOp::Size
+            | ContainerOp::Exists
+            | ContainerOp::First
+            | ContainerOp::Last
+            | ContainerOp::Next
+            | ContainerOp::Prev => &ty::INT_TYPE,
+            ContainerOp::Get | ContainerOp::PopFront | ContainerOp::PopBack => {
+                ty.get_dynamic_element().unwrap_or(&ty::ERROR_TYPE)
+            }
+            ContainerOp::Set
+            | ContainerOp::DeleteAll
+            | ContainerOp::Delete
+            | ContainerOp::Insert
+            | ContainerOp::PushFront
+            | ContainerOp::PushBack
+            | ContainerOp::Itoa => &ty::VOID_TYPE,
+        }
+    }
+}
+
 /// The unary bitwise operators.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(missing_docs)]
@@ -210,12 +480,30 @@ pub enum IntBinaryArithOp {
     Pow,
 }
 
+/// The real binary arithmetic operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum RealBinaryArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
 /// The integer comparison operators.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(missing_docs)]
 pub enum IntCompOp {
     Eq,
     Neq,
+    /// Case equality, which also considers x and z bits.
+    CaseEq,
+    CaseNeq,
+    /// Wildcard equality, where x and z bits in the right-hand side match any
+    /// bit in the left-hand side.
+    WildcardEq,
+    WildcardNeq,
     Lt,
     Leq,
     Gt,
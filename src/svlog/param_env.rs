@@ -6,9 +6,12 @@ use crate::{
     ast_map::AstNode,
     crate_prelude::*,
     hir::{HirNode, NamedParam, PosParam},
+    resolver::{enclosing_module, interface_of, HierName},
     ty::Type,
     value::Value,
+    PortMappingSource,
 };
+use std::sync::Arc;
 
 /// A parameter environment.
 ///
@@ -25,6 +28,10 @@ pub type NodeEnvId = (NodeId, ParamEnv);
 pub struct ParamEnvData<'t> {
     values: Vec<(NodeId, ParamEnvBinding<Value<'t>>)>,
     types: Vec<(NodeId, ParamEnvBinding<Type<'t>>)>,
+    /// The defparam overrides that apply further down in the hierarchy, given
+    /// as `(insts, param, value)`, where `insts` is the path of instances
+    /// leading to the module declaring `param`.
+    defparams: Vec<(Vec<NodeId>, NodeId, NodeEnvId)>,
 }
 
 impl<'t> ParamEnvData<'t> {
@@ -49,6 +56,18 @@ impl<'t> ParamEnvData<'t> {
         self.values.retain(|&(n, _)| n != node_id);
         self.values.push((node_id, ParamEnvBinding::Direct(value)));
     }
+
+    /// Add the bindings of another environment, overriding existing ones.
+    pub fn extend(&mut self, other: &ParamEnvData<'t>) {
+        for &(id, binding) in &other.values {
+            self.values.retain(|&(n, _)| n != id);
+            self.values.push((id, binding));
+        }
+        for &(id, binding) in &other.types {
+            self.types.retain(|&(n, _)| n != id);
+            self.types.push((id, binding));
+        }
+    }
 }
 
 /// A binding in a parameter environment.
@@ -70,6 +89,12 @@ pub enum ParamEnvSource<'hir> {
         pos: &'hir [PosParam],
         named: &'hir [NamedParam],
     },
+    ClassSpecialization {
+        class: NodeId,
+        env: ParamEnv,
+        pos: &'hir [PosParam],
+        named: &'hir [NamedParam],
+    },
 }
 
 pub(crate) fn compute<'gcx>(
@@ -78,112 +103,295 @@ pub(crate) fn compute<'gcx>(
 ) -> Result<ParamEnv> {
     match src {
         ParamEnvSource::ModuleInst {
-            module,
+            module: module_id,
             inst,
             env,
             pos,
             named,
         } => {
-            let module = match cx.hir_of(module)? {
-                HirNode::Module(m) => m,
-                _ => panic!("expected module"),
+            let (module, module_ports, params, block) = match cx.hir_of(module_id)? {
+                hir @ HirNode::Module(m) => (hir, m.ports, m.params, &m.block),
+                hir @ HirNode::Interface(i) => (hir, i.ports, i.params, &i.block),
+                _ => panic!("expected module or interface"),
             };
 
             // Collect a list of module parameters.
-            let module_params: Vec<_> = module
-                .params
+            let module_params: Vec<_> = params
                 .iter()
                 .cloned()
-                .chain(module.block.params.iter().cloned())
+                .chain(block.params.iter().cloned())
                 .collect();
 
             // Associate the positional and named assignments with the actual
             // parameters of the module.
-            let param_iter = pos
-                .iter()
-                .enumerate()
-                .map(
-                    |(index, &(span, assign_id))| match module_params.get(index) {
-                        Some(&param_id) => Ok((param_id, (assign_id, env))),
-                        None => {
-                            cx.emit(
-                                DiagBuilder2::error(format!(
-                                    "{} only has {} parameter(s)",
-                                    module.desc_full(),
-                                    module_params.len()
-                                ))
-                                .span(span),
-                            );
-                            Err(())
-                        }
-                    },
-                )
-                .chain(named.iter().map(|&(_span, name, assign_id)| {
-                    let names: Vec<_> = module_params
-                        .iter()
-                        .flat_map(|&id| match cx.ast_of(id) {
-                            Ok(AstNode::TypeParam(_, p)) => Some((p.name.name, id)),
-                            Ok(AstNode::ValueParam(_, p)) => Some((p.name.name, id)),
-                            Ok(_) => unreachable!(),
-                            Err(()) => None,
-                        })
-                        .collect();
-                    match names
-                        .iter()
-                        .find(|&(param_name, _)| *param_name == name.value)
-                    {
-                        Some(&(_, param_id)) => Ok((param_id, (assign_id, env))),
-                        None => {
-                            cx.emit(
-                                DiagBuilder2::error(format!(
-                                    "no parameter `{}` in {}",
-                                    name,
-                                    module.desc_full(),
-                                ))
-                                .span(name.span)
-                                .add_note(format!(
-                                    "declared parameters are {}",
-                                    names
-                                        .iter()
-                                        .map(|&(n, _)| format!("`{}`", n))
-                                        .collect::<Vec<_>>()
-                                        .join(", ")
-                                )),
-                            );
-                            Err(())
-                        }
+            let (mut types, mut values) =
+                assign_params(cx, module, &module_params, env, pos, named)?;
+
+            // Bind interface ports to the interface instances they are
+            // connected to, such that they see the instances' parameters.
+            let mut intf_ports = vec![];
+            for &port_id in module_ports {
+                if interface_of(cx, port_id)?.is_some() {
+                    intf_ports.push(port_id);
+                }
+            }
+            if !intf_ports.is_empty() {
+                let inst_hir = match cx.hir_of(inst)? {
+                    HirNode::Inst(x) => x,
+                    _ => unreachable!(),
+                };
+                let mapping = cx.port_mapping(PortMappingSource::ModuleInst {
+                    module: module_id,
+                    inst,
+                    env,
+                    pos: &inst_hir.pos_ports,
+                    named: &inst_hir.named_ports,
+                })?;
+                for port_id in intf_ports {
+                    if let Some(conn) = mapping.find(port_id) {
+                        types.push((port_id, ParamEnvBinding::Indirect(conn)));
                     }
-                }));
-            let param_iter = param_iter
-                .collect::<Vec<_>>()
-                .into_iter()
-                .collect::<Result<Vec<_>>>()?
-                .into_iter();
-
-            // Split up type and value parameters.
-            let mut types = vec![];
-            let mut values = vec![];
-            for (param_id, assign_id) in param_iter {
-                let assign_id = match assign_id {
-                    (Some(i), n) => (i, n),
-                    _ => continue,
+                }
+            }
+
+            // Parameters overridden by defparam statements take precedence
+            // over the parameter assignments of the instance. Overrides
+            // issued further up in the hierarchy take precedence over the
+            // ones in the instantiating module (IEEE 1800-2017 §23.10.1).
+            let mut overrides = vec![];
+            if let Some(parent) = enclosing_module(cx, inst) {
+                let block = match cx.hir_of(parent)? {
+                    HirNode::Module(m) => &m.block,
+                    _ => unreachable!(),
                 };
-                match cx.ast_of(param_id)? {
-                    AstNode::TypeParam(..) => {
-                        cx.set_lowering_hint(assign_id.0, hir::Hint::Type);
-                        types.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+                for &defparam_id in &block.defparams {
+                    let defparam = match cx.hir_of(defparam_id)? {
+                        HirNode::Defparam(x) => x,
+                        _ => unreachable!(),
+                    };
+                    let hier = resolve_defparam(cx, defparam)?;
+                    overrides.push((hier.insts.clone(), hier.target, (defparam.value, env)));
+                }
+            }
+            overrides.extend(cx.param_env_data(env).defparams.iter().cloned());
+            let mut defparams = vec![];
+            for (insts, param_id, value) in overrides {
+                match insts.split_first() {
+                    Some((&first, [])) if first == inst => {
+                        values.retain(|&(id, _)| id != param_id);
+                        values.push((param_id, ParamEnvBinding::Indirect(value)));
                     }
-                    AstNode::ValueParam(..) => {
-                        cx.set_lowering_hint(assign_id.0, hir::Hint::Expr);
-                        values.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+                    Some((&first, rest)) if first == inst => {
+                        defparams.push((rest.to_vec(), param_id, value));
                     }
-                    _ => unreachable!(),
+                    _ => (),
                 }
             }
 
-            let env = cx.intern_param_env(ParamEnvData { types, values });
+            let env = cx.intern_param_env(ParamEnvData {
+                types,
+                values,
+                defparams,
+            });
             cx.add_param_env_context(env, inst);
             Ok(env)
         }
+        ParamEnvSource::ClassSpecialization {
+            class: class_id,
+            env,
+            pos,
+            named,
+        } => {
+            let class = cx.hir_of(class_id)?;
+            let params = match class {
+                HirNode::Class(x) => &x.params,
+                _ => panic!("expected class"),
+            };
+
+            // A class nested in a module or another class may still refer to
+            // the parameters of its surroundings, so start out with the outer
+            // environment and replace the bindings of the class parameters.
+            // Classes in a package or the compilation unit see no parameters
+            // other than their own.
+            let nested = match cx.parent_node_id(class_id).map(|id| cx.ast_of(id)) {
+                Some(Ok(AstNode::Package(_))) | Some(Ok(AstNode::Unit(_))) | None => false,
+                _ => true,
+            };
+            let mut data = if nested {
+                (*cx.param_env_data(env)).clone()
+            } else {
+                ParamEnvData::default()
+            };
+            data.types.retain(|(id, _)| !params.contains(id));
+            data.values.retain(|(id, _)| !params.contains(id));
+
+            // Bind the parameters directly to their types and values, such
+            // that identical specializations share the same environment, no
+            // matter where they appear.
+            let (types, values) = assign_params(cx, class, params, env, pos, named)?;
+            let mut assigned = data.clone();
+            assigned.types.extend(types.iter().cloned());
+            assigned.values.extend(values.iter().cloned());
+            let assigned = cx.intern_param_env(assigned);
+            for (id, _) in types {
+                let ty = cx.map_to_type(id, assigned)?;
+                data.types.push((id, ParamEnvBinding::Direct(ty)));
+            }
+            for (id, _) in values {
+                let value = cx.constant_value_of(id, assigned)?;
+                data.values.push((id, ParamEnvBinding::Direct(value)));
+            }
+            Ok(cx.intern_param_env(data))
+        }
+    }
+}
+
+/// Associate positional and named parameter assignments with the parameters
+/// of a module, interface, or class.
+///
+/// Returns the bindings of the type and value parameters, respectively.
+fn assign_params<'gcx>(
+    cx: &impl Context<'gcx>,
+    decl: HirNode<'gcx>,
+    decl_params: &[NodeId],
+    env: ParamEnv,
+    pos: &[PosParam],
+    named: &[NamedParam],
+) -> Result<(
+    Vec<(NodeId, ParamEnvBinding<Type<'gcx>>)>,
+    Vec<(NodeId, ParamEnvBinding<Value<'gcx>>)>,
+)> {
+    let param_iter = pos
+        .iter()
+        .enumerate()
+        .map(|(index, &(span, assign_id))| match decl_params.get(index) {
+            Some(&param_id) => Ok((param_id, (assign_id, env))),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} only has {} parameter(s)",
+                        decl.desc_full(),
+                        decl_params.len()
+                    ))
+                    .span(span),
+                );
+                Err(())
+            }
+        })
+        .chain(named.iter().map(|&(_span, name, assign_id)| {
+            let names: Vec<_> = decl_params
+                .iter()
+                .flat_map(|&id| match cx.ast_of(id) {
+                    Ok(AstNode::TypeParam(_, p)) => Some((p.name.name, id)),
+                    Ok(AstNode::ValueParam(_, p)) => Some((p.name.name, id)),
+                    Ok(_) => unreachable!(),
+                    Err(()) => None,
+                })
+                .collect();
+            match names
+                .iter()
+                .find(|&(param_name, _)| *param_name == name.value)
+            {
+                Some(&(_, param_id)) => Ok((param_id, (assign_id, env))),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "no parameter `{}` in {}",
+                            name,
+                            decl.desc_full(),
+                        ))
+                        .span(name.span)
+                        .add_note(format!(
+                            "declared parameters are {}",
+                            names
+                                .iter()
+                                .map(|&(n, _)| format!("`{}`", n))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )),
+                    );
+                    Err(())
+                }
+            }
+        }));
+    let param_iter = param_iter
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?
+        .into_iter();
+
+    // Split up type and value parameters.
+    let mut types = vec![];
+    let mut values = vec![];
+    for (param_id, assign_id) in param_iter {
+        let assign_id = match assign_id {
+            (Some(i), n) => (i, n),
+            _ => continue,
+        };
+        match cx.ast_of(param_id)? {
+            AstNode::TypeParam(..) => {
+                cx.set_lowering_hint(assign_id.0, hir::Hint::Type);
+                types.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+            }
+            AstNode::ValueParam(..) => {
+                cx.set_lowering_hint(assign_id.0, hir::Hint::Expr);
+                values.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok((types, values))
+}
+
+/// Resolve the parameter overridden by a defparam statement.
+fn resolve_defparam<'gcx>(
+    cx: &impl Context<'gcx>,
+    defparam: &hir::Defparam,
+) -> Result<Arc<HierName>> {
+    let hier = match cx.resolve_hier_name(defparam.target)? {
+        Some(hier) if !hier.insts.is_empty() => hier,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is not a parameter of a module instance",
+                    cx.span(defparam.target).extract()
+                ))
+                .span(cx.span(defparam.target)),
+            );
+            return Err(());
+        }
+    };
+    if hier.root.is_some() {
+        cx.emit(
+            DiagBuilder2::error(
+                "defparam statements can only override parameters below the enclosing module",
+            )
+            .span(cx.span(defparam.target)),
+        );
+        return Err(());
+    }
+    match cx.hir_of(hier.target)? {
+        HirNode::ValueParam(param) if !param.local => Ok(hier),
+        HirNode::ValueParam(param) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is a localparam and cannot be overridden",
+                    param.name
+                ))
+                .span(cx.span(defparam.target))
+                .add_note(format!("{} declared here:", param.desc_full()))
+                .span(param.human_span()),
+            );
+            Err(())
+        }
+        hir => {
+            cx.emit(
+                DiagBuilder2::error(format!("{} cannot be overridden", hir.desc_full()))
+                    .span(cx.span(defparam.target))
+                    .add_note(format!("{} declared here:", hir.desc_full()))
+                    .span(hir.human_span()),
+            );
+            Err(())
+        }
     }
 }
@@ -48,9 +48,10 @@ pub(crate) fn compute<'gcx>(
             pos,
             named,
         } => {
-            let module = match cx.hir_of(module)? {
-                HirNode::Module(m) => m,
-                _ => panic!("expected module"),
+            let (module, module_ports) = match cx.hir_of(module)? {
+                hir @ HirNode::Module(m) => (hir, m.ports),
+                hir @ HirNode::Interface(i) => (hir, i.ports),
+                _ => panic!("expected module or interface"),
             };
 
             // Associate the positional and named assignments with the actual
@@ -59,14 +60,14 @@ pub(crate) fn compute<'gcx>(
                 .iter()
                 .enumerate()
                 .map(
-                    |(index, &(span, assign_id))| match module.ports.get(index) {
+                    |(index, &(span, assign_id))| match module_ports.get(index) {
                         Some(&port_id) => Ok((port_id, (assign_id, env))),
                         None => {
                             cx.emit(
                                 DiagBuilder2::error(format!(
                                     "{} only has {} ports(s)",
                                     module.desc_full(),
-                                    module.ports.len()
+                                    module_ports.len()
                                 ))
                                 .span(span),
                             );
@@ -75,13 +76,10 @@ pub(crate) fn compute<'gcx>(
                     },
                 )
                 .chain(named.iter().map(|&(_span, name, assign_id)| {
-                    let names: Vec<_> = module
-                        .ports
+                    let names: Vec<_> = module_ports
                         .iter()
                         .flat_map(|&id| match cx.ast_of(id) {
-                            Ok(AstNode::Port(&ast::Port::Named { name, .. })) => {
-                                Some((name.name, id))
-                            }
+                            Ok(AstNode::Port(port)) => port.name().map(|name| (name.name, id)),
                             Ok(_) => unreachable!(),
                             Err(()) => None,
                         })
@@ -120,6 +118,48 @@ pub(crate) fn compute<'gcx>(
                 })
                 .collect::<Result<Vec<_>>>()?;
 
+            // Inout ports must be connected to a net, which they drive
+            // together with the other drivers of the net.
+            let mut failed = false;
+            for &(port_id, (assign_id, env)) in &ports {
+                let port = match cx.hir_of(port_id)? {
+                    HirNode::Port(p) => p,
+                    _ => continue,
+                };
+                if port.dir != ast::PortDir::Inout
+                    || crate::resolver::interface_of(cx, port_id)?.is_some()
+                {
+                    continue;
+                }
+                let is_net = match cx.hir_of(assign_id)? {
+                    HirNode::Expr(hir::Expr {
+                        kind: hir::ExprKind::Ident(..),
+                        ..
+                    }) => match cx.hir_of(cx.resolve_node(assign_id, env)?)? {
+                        HirNode::VarDecl(decl) => decl.kind.is_net(),
+                        HirNode::Port(p) => p.dir == ast::PortDir::Inout,
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                if !is_net {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} must be connected to a net",
+                            port.desc_full()
+                        ))
+                        .span(cx.span(assign_id))
+                        .add_note(
+                            "inout ports can only be connected to an entire net or inout port",
+                        ),
+                    );
+                    failed = true;
+                }
+            }
+            if failed {
+                return Err(());
+            }
+
             Ok(Arc::new(PortMapping(ports)))
         }
     }
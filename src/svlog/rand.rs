@@ -0,0 +1,1043 @@
+// Copyright (c) 2016-2019 Fabian Schuiki
+
+//! Constrained randomization
+//!
+//! This module implements the solver behind `randomize()`. It assigns random
+//! values to the `rand` and `randc` properties of a class object, or to the
+//! variables passed to `std::randomize()`, such that all constraints hold.
+//! The solver covers integral variables and fixed-size unpacked arrays
+//! thereof. Constraint expressions are evaluated as constant expressions, with
+//! the variables bound to scratch values on top of the parameter environment.
+
+use crate::crate_prelude::*;
+use crate::{
+    hir::{HirNode, Visitor},
+    ty::{Type, TypeKind},
+    value::{self, ScratchValues, Value},
+    ParamEnv,
+};
+use num::{BigInt, One, ToPrimitive, Zero};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The largest domain whose values are enumerated exhaustively.
+const ENUM_LIMIT: usize = 4096;
+
+/// The widest `randc` variable that is supported.
+const RANDC_WIDTH_LIMIT: usize = 16;
+
+/// The number of constraint evaluations after which the search for a solution
+/// is abandoned.
+const SEARCH_BUDGET: usize = 200_000;
+
+/// A source of random numbers for `randomize()`.
+///
+/// Also keeps track of the values already assumed by `randc` variables in the
+/// current cycle.
+#[derive(Debug, Clone)]
+pub struct Randomizer {
+    state: u64,
+    cycles: HashMap<(NodeId, usize), HashSet<BigInt>>,
+}
+
+impl Randomizer {
+    /// Create a new randomizer from a seed.
+    pub fn new(seed: u64) -> Self {
+        // Scramble the seed such that similar seeds produce unrelated
+        // sequences, and make sure the state is never zero.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Randomizer {
+            state: (z ^ (z >> 31)) | 1,
+            cycles: HashMap::new(),
+        }
+    }
+
+    /// Create a new randomizer seeded from this one.
+    ///
+    /// The `randc` cycles are not shared with the new randomizer.
+    pub fn fork(&mut self) -> Self {
+        Randomizer::new(self.next_u64())
+    }
+
+    /// Generate the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Generate a random number in the range `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Generate a random integer in the range `[lo, hi]`.
+    fn between(&mut self, lo: &BigInt, hi: &BigInt) -> BigInt {
+        let size: BigInt = hi - lo + 1;
+        let mut r = BigInt::zero();
+        for _ in 0..size.bits() / 64 + 2 {
+            r = (r << 64) + BigInt::from(self.next_u64());
+        }
+        lo + r % size
+    }
+
+    /// Shuffle a slice.
+    fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            values.swap(i, j);
+        }
+    }
+}
+
+/// Randomize an object of a class.
+///
+/// The properties that are not `rand` or `randc` assume their initial value.
+/// Returns the new values of the random properties, or `None` if the
+/// constraints of the class cannot be satisfied.
+pub fn randomize<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    env: ParamEnv,
+    rng: &mut Randomizer,
+) -> Result<Option<Vec<(NodeId, Value<'gcx>)>>> {
+    randomize_object(cx, class_id, env, &[], None, cx.span(class_id), rng)
+}
+
+/// Randomize an object of a class, given the current values of its
+/// properties.
+///
+/// Properties missing from `state` assume their initial value. The optional
+/// inline constraints are evaluated in the given environment, which is
+/// extended with the parameters of the class.
+pub(crate) fn randomize_object<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    env: ParamEnv,
+    state: &[(NodeId, Value<'gcx>)],
+    inline: Option<(&'gcx [hir::ConstraintItem], ParamEnv)>,
+    span: Span,
+    rng: &mut Randomizer,
+) -> Result<Option<Vec<(NodeId, Value<'gcx>)>>> {
+    let mut chain = vec![];
+    let mut next = Some((class_id, env));
+    while let Some((id, env)) = next {
+        let class = match cx.hir_of(id)? {
+            HirNode::Class(x) => x,
+            _ => unreachable!(),
+        };
+        chain.push((class, env));
+        next = resolver::class_base_type(cx, id, env)?;
+    }
+
+    // Properties without a random qualifier keep their value, and are bound
+    // when the constraints are evaluated.
+    let mut solver = Solver::new(cx, span);
+    for &(class, env) in &chain {
+        for member in &class.members {
+            if member.kind != hir::ClassMemberKind::Property {
+                continue;
+            }
+            if let Some(qualifier) = member.rand {
+                solver.add_var(member.id, env, qualifier == ast::RandomQualifier::Randc)?;
+                continue;
+            }
+            let value = match state.iter().find(|&&(id, _)| id == member.id) {
+                Some(&(_, value)) => value,
+                None => value::property_init_value(cx, member.id, env)?,
+            };
+            solver.fixed.insert(member.id, value);
+        }
+    }
+
+    // Constraints override the ones with the same name in a base class.
+    let mut seen = HashSet::new();
+    for &(class, env) in &chain {
+        for &id in &class.constraints {
+            let constraint = match cx.hir_of(id)? {
+                HirNode::Constraint(x) => x,
+                _ => unreachable!(),
+            };
+            if seen.insert(constraint.name.value) {
+                solver.add_constraints(&constraint.items, env)?;
+            }
+        }
+    }
+    if let Some((items, outer)) = inline {
+        let mut env_data = cx.param_env_data(outer).clone();
+        env_data.extend(cx.param_env_data(env));
+        solver.add_constraints(items, cx.intern_param_env(env_data))?;
+    }
+    solver.solve(rng)
+}
+
+/// Randomize a list of variables, as done by `std::randomize()`.
+///
+/// The variables and constraints are evaluated in `env`. Returns the new
+/// values of the variables, or `None` if the constraints cannot be
+/// satisfied.
+pub fn randomize_vars<'gcx>(
+    cx: &impl Context<'gcx>,
+    vars: &[NodeId],
+    constraints: &'gcx [hir::ConstraintItem],
+    env: ParamEnv,
+    span: Span,
+    rng: &mut Randomizer,
+) -> Result<Option<Vec<(NodeId, Value<'gcx>)>>> {
+    let mut solver = Solver::new(cx, span);
+    for &var in vars {
+        solver.add_var(var, env, false)?;
+    }
+    solver.add_constraints(constraints, env)?;
+    solver.solve(rng)
+}
+
+/// Determine the smallest and largest value of an integral type.
+fn integral_domain(ty: Type) -> Option<(BigInt, BigInt)> {
+    match *ty.resolve_name() {
+        TypeKind::Bit(..)
+        | TypeKind::Int(..)
+        | TypeKind::BitScalar { .. }
+        | TypeKind::BitVector { .. } => (),
+        _ => return None,
+    }
+    let width = ty.width();
+    Some(if ty.is_signed() && width > 0 {
+        let half = BigInt::one() << (width - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << width) - 1)
+    })
+}
+
+/// A variable to be randomized.
+struct Var<'gcx> {
+    id: NodeId,
+    ty: Type<'gcx>,
+    /// The range of a fixed-size array, whose elements are randomized
+    /// individually.
+    range: Option<ty::Range>,
+    cyclic: bool,
+    /// The slots of the variable, one per array element.
+    slots: std::ops::Range<usize>,
+}
+
+/// A single integral value to be randomized.
+struct Slot<'gcx> {
+    var: usize,
+    ty: Type<'gcx>,
+    lo: BigInt,
+    hi: BigInt,
+    /// The constants the constraints compare this slot against.
+    landmarks: BTreeSet<BigInt>,
+    /// The distribution that determines the weights of the values.
+    dist: Option<usize>,
+}
+
+/// A constraint on the slots.
+struct Check<'gcx> {
+    kind: CheckKind<'gcx>,
+    /// The conditions under which the constraint applies, together with the
+    /// value they must have.
+    guards: Vec<(NodeId, bool)>,
+    /// The environment in which the constraint is evaluated, including the
+    /// index variables of enclosing `foreach` constraints.
+    env: ParamEnv,
+    /// The slots the constraint depends on.
+    deps: BTreeSet<usize>,
+}
+
+enum CheckKind<'gcx> {
+    /// An expression that must be true.
+    Expr(NodeId),
+    /// An expression that must lie within one of the items of a distribution.
+    Dist(NodeId, &'gcx [Spanned<hir::DistItem>]),
+}
+
+impl<'gcx> CheckKind<'gcx> {
+    fn expr(&self) -> NodeId {
+        match *self {
+            CheckKind::Expr(id) | CheckKind::Dist(id, _) => id,
+        }
+    }
+}
+
+struct Solver<'a, 'gcx, C> {
+    cx: &'a C,
+    span: Span,
+    vars: Vec<Var<'gcx>>,
+    slots: Vec<Slot<'gcx>>,
+    checks: Vec<Check<'gcx>>,
+    /// The values of the variables which are not randomized.
+    fixed: ScratchValues<'gcx>,
+    /// The `solve a before b` orderings, as pairs of variable indices.
+    order: Vec<(usize, usize)>,
+    budget: usize,
+}
+
+impl<'a, 'gcx, C: Context<'gcx>> Solver<'a, 'gcx, C> {
+    fn new(cx: &'a C, span: Span) -> Self {
+        Solver {
+            cx,
+            span,
+            vars: vec![],
+            slots: vec![],
+            checks: vec![],
+            fixed: HashMap::new(),
+            order: vec![],
+            budget: SEARCH_BUDGET,
+        }
+    }
+
+    /// Add a variable to be randomized.
+    fn add_var(&mut self, id: NodeId, env: ParamEnv, cyclic: bool) -> Result<()> {
+        let cx = self.cx;
+        let ty = cx.type_of(id, env)?;
+        let (range, elem_ty) = match *ty.resolve_name() {
+            TypeKind::UnpackedArray(range, elem) => (Some(range), elem),
+            _ => (None, ty),
+        };
+        let (lo, hi) = match integral_domain(elem_ty) {
+            Some(x) => x,
+            None => {
+                let hir = cx.hir_of(id)?;
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} cannot be randomized; only integral variables and fixed-size \
+                         arrays thereof are supported",
+                        hir.desc_full()
+                    ))
+                    .span(hir.human_span())
+                    .add_note(format!(
+                        "{} has type `{}`",
+                        hir.desc_full(),
+                        ty
+                    )),
+                );
+                return Err(());
+            }
+        };
+        if cyclic && elem_ty.width() > RANDC_WIDTH_LIMIT {
+            let hir = cx.hir_of(id)?;
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`randc` {} is wider than {} bits",
+                    hir.desc_full(),
+                    RANDC_WIDTH_LIMIT
+                ))
+                .span(hir.human_span()),
+            );
+            return Err(());
+        }
+        let first = self.slots.len();
+        for _ in 0..range.map(|r| r.size).unwrap_or(1) {
+            self.slots.push(Slot {
+                var: self.vars.len(),
+                ty: elem_ty.resolve_name(),
+                lo: lo.clone(),
+                hi: hi.clone(),
+                landmarks: BTreeSet::new(),
+                dist: None,
+            });
+        }
+        self.vars.push(Var {
+            id,
+            ty: ty.resolve_name(),
+            range,
+            cyclic,
+            slots: first..self.slots.len(),
+        });
+        Ok(())
+    }
+
+    /// Find the variable a node refers to.
+    fn var_of(&self, id: NodeId) -> Option<usize> {
+        self.vars.iter().position(|v| v.id == id)
+    }
+
+    /// Add a list of constraints, evaluated in `env`.
+    fn add_constraints(&mut self, items: &'gcx [hir::ConstraintItem], env: ParamEnv) -> Result<()> {
+        let mut failed = false;
+        for item in items {
+            failed |= self.add_constraint(item, &[], env).is_err();
+        }
+        match failed {
+            true => Err(()),
+            false => Ok(()),
+        }
+    }
+
+    fn add_constraint(
+        &mut self,
+        item: &'gcx hir::ConstraintItem,
+        guards: &[(NodeId, bool)],
+        env: ParamEnv,
+    ) -> Result<()> {
+        let cx = self.cx;
+        match *item {
+            hir::ConstraintItem::Expr(expr) => self.add_check(CheckKind::Expr(expr), guards, env),
+            hir::ConstraintItem::Dist(expr, ref items) => {
+                self.add_check(CheckKind::Dist(expr, items), guards, env)
+            }
+            hir::ConstraintItem::If(cond, ref main, ref other) => {
+                self.need_integral(cond, env)?;
+                let mut inner = guards.to_vec();
+                inner.push((cond, true));
+                for item in main {
+                    self.add_constraint(item, &inner, env)?;
+                }
+                inner.last_mut().unwrap().1 = false;
+                for item in other {
+                    self.add_constraint(item, &inner, env)?;
+                }
+                Ok(())
+            }
+            hir::ConstraintItem::Foreach(array, ref indices, ref body) => {
+                let ty = cx.type_of(array, env)?;
+                let dims = ty.dims();
+                if ty.is_dynamic() || indices.len() > ty.num_unpacked_dims() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`foreach` constraint requires a fixed-size array with at least \
+                             {} unpacked dimensions",
+                            indices.len()
+                        ))
+                        .span(cx.span(array))
+                        .add_note(format!(
+                            "`{}` has type `{}`",
+                            cx.span(array).extract(),
+                            ty
+                        )),
+                    );
+                    return Err(());
+                }
+                // Expand the loop for every combination of indices.
+                let mut envs = vec![env];
+                for (&index, dim) in indices.iter().zip(dims.iter()) {
+                    let index = match index {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let mut next = vec![];
+                    for &env in &envs {
+                        for i in 0..dim.size {
+                            let value =
+                                value::make_int(&ty::INT_TYPE, (dim.offset + i as isize).into());
+                            let mut env_data = cx.param_env_data(env).clone();
+                            env_data.set_value(index, cx.intern_value(value));
+                            next.push(cx.intern_param_env(env_data));
+                        }
+                    }
+                    envs = next;
+                }
+                for env in envs {
+                    for item in body {
+                        self.add_constraint(item, guards, env)?;
+                    }
+                }
+                Ok(())
+            }
+            hir::ConstraintItem::Solve(ref before, ref after) => {
+                let before = self.solve_vars(before, env)?;
+                let after = self.solve_vars(after, env)?;
+                for &b in &before {
+                    for &a in &after {
+                        self.order.push((b, a));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolve the variables of a `solve ... before ...` constraint.
+    fn solve_vars(&self, exprs: &[NodeId], env: ParamEnv) -> Result<Vec<usize>> {
+        let cx = self.cx;
+        exprs
+            .iter()
+            .map(|&expr| {
+                let binding = match cx.hir_of(expr)? {
+                    HirNode::Expr(hir::Expr {
+                        kind: hir::ExprKind::Ident(..),
+                        ..
+                    }) => Some(cx.resolve_node(expr, env)?),
+                    _ => None,
+                };
+                match binding.and_then(|id| self.var_of(id)) {
+                    Some(var) => Ok(var),
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` is not a random variable",
+                                cx.span(expr).extract()
+                            ))
+                            .span(cx.span(expr))
+                            .add_note("`solve ... before` may only order random variables"),
+                        );
+                        Err(())
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Make sure an expression of a constraint is integral.
+    fn need_integral(&self, expr: NodeId, env: ParamEnv) -> Result<()> {
+        let ty = self.cx.type_of(expr, env)?;
+        if integral_domain(ty).is_some() {
+            return Ok(());
+        }
+        self.cx.emit(
+            DiagBuilder2::error(format!(
+                "constraint `{}` must be of an integral type",
+                self.cx.span(expr).extract()
+            ))
+            .span(self.cx.span(expr))
+            .add_note(format!("expression has type `{}`", ty)),
+        );
+        Err(())
+    }
+
+    fn add_check(
+        &mut self,
+        kind: CheckKind<'gcx>,
+        guards: &[(NodeId, bool)],
+        env: ParamEnv,
+    ) -> Result<()> {
+        self.need_integral(kind.expr(), env)?;
+        let mut collector = DepCollector {
+            solver: self,
+            env,
+            deps: BTreeSet::new(),
+            landmarks: BTreeSet::new(),
+            failed: false,
+        };
+        collector.visit_node_with_id(kind.expr(), false);
+        for &(cond, _) in guards {
+            collector.visit_node_with_id(cond, false);
+        }
+        if let CheckKind::Dist(_, items) = kind {
+            for item in items {
+                match item.value.range {
+                    hir::InsideRange::Single(x) => collector.visit_node_with_id(x, false),
+                    hir::InsideRange::Range(lo, hi) => {
+                        collector.visit_node_with_id(lo, false);
+                        collector.visit_node_with_id(hi, false);
+                    }
+                }
+                if let Some(weight) = item.value.weight {
+                    collector.visit_node_with_id(weight, false);
+                }
+            }
+        }
+        let DepCollector {
+            deps,
+            landmarks,
+            failed,
+            ..
+        } = collector;
+        if failed {
+            return Err(());
+        }
+
+        // Constants in the constraint are likely boundaries of the values that
+        // satisfy it.
+        for &slot in &deps {
+            self.slots[slot].landmarks.extend(landmarks.iter().cloned());
+        }
+
+        // An unconditional distribution over a single variable determines the
+        // weights of its values.
+        if let CheckKind::Dist(expr, _) = kind {
+            let var = match self.cx.hir_of(expr)? {
+                HirNode::Expr(hir::Expr {
+                    kind: hir::ExprKind::Ident(..),
+                    ..
+                }) => self.var_of(self.cx.resolve_node(expr, env)?),
+                _ => None,
+            };
+            if let Some(var) = var {
+                let slots = &self.vars[var].slots;
+                if guards.is_empty() && slots.len() == 1 && deps.len() == 1 {
+                    self.slots[slots.start].dist = Some(self.checks.len());
+                }
+            }
+        }
+
+        self.checks.push(Check {
+            kind,
+            guards: guards.to_vec(),
+            env,
+            deps,
+        });
+        Ok(())
+    }
+
+    /// Find values for all slots that satisfy the constraints.
+    fn solve(mut self, rng: &mut Randomizer) -> Result<Option<Vec<(NodeId, Value<'gcx>)>>> {
+        let order = self.slot_order()?;
+
+        // Check each constraint as soon as the last slot it depends on has
+        // been assigned.
+        let mut position = vec![0; self.slots.len()];
+        for (i, &slot) in order.iter().enumerate() {
+            position[slot] = i;
+        }
+        let mut at = vec![vec![]; order.len()];
+        let mut values = vec![None; self.slots.len()];
+        for (i, check) in self.checks.iter().enumerate() {
+            match check.deps.iter().map(|&s| position[s]).max() {
+                Some(pos) => at[pos].push(i),
+                None if !self.satisfied(i, &values)? => return Ok(self.unsatisfiable()),
+                None => (),
+            }
+        }
+        if !self.search(0, &order, &at, &mut values, rng)? {
+            return Ok(self.unsatisfiable());
+        }
+
+        // Record the values of `randc` variables in the current cycle.
+        for var in &self.vars {
+            if !var.cyclic {
+                continue;
+            }
+            for (index, slot) in var.slots.clone().enumerate() {
+                let size = (&self.slots[slot].hi - &self.slots[slot].lo + BigInt::one())
+                    .to_usize()
+                    .unwrap();
+                let used = rng.cycles.entry((var.id, index)).or_default();
+                if used.len() >= size {
+                    used.clear();
+                }
+                used.insert(values[slot].clone().unwrap());
+            }
+        }
+
+        let values = values.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        Ok(Some(
+            self.vars
+                .iter()
+                .map(|var| {
+                    (
+                        var.id,
+                        self.var_value(var, |slot| Some(&values[slot])).unwrap(),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    /// Emit a warning about unsatisfiable constraints.
+    fn unsatisfiable<T>(&self) -> Option<T> {
+        let mut d = DiagBuilder2::warning("constraints cannot be satisfied").span(self.span);
+        if self.budget == 0 {
+            d = d.add_note(format!(
+                "The search for a solution was abandoned after {} steps.",
+                SEARCH_BUDGET
+            ));
+        }
+        self.cx.emit(d);
+        None
+    }
+
+    /// Determine the order in which the slots are assigned.
+    ///
+    /// Variables are ordered according to the `solve ... before`
+    /// constraints, with `randc` variables solved first. Variables are
+    /// otherwise solved in the order of their declaration.
+    fn slot_order(&self) -> Result<Vec<usize>> {
+        let mut pending: Vec<usize> = (0..self.vars.len()).collect();
+        let mut done = vec![false; self.vars.len()];
+        let mut order = vec![];
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .cloned()
+                .filter(|&v| self.order.iter().all(|&(b, a)| a != v || done[b] || b == v))
+                .min_by_key(|&v| (!self.vars[v].cyclic, v));
+            let var = match ready {
+                Some(v) => v,
+                None => {
+                    self.cx.emit(
+                        DiagBuilder2::error("`solve ... before` constraints form a cycle")
+                            .span(self.span)
+                            .add_note(format!(
+                                "Involved variables: {}",
+                                pending
+                                    .iter()
+                                    .map(|&v| format!(
+                                        "`{}`",
+                                        self.cx.span(self.vars[v].id).extract()
+                                    ))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )),
+                    );
+                    return Err(());
+                }
+            };
+            pending.retain(|&v| v != var);
+            done[var] = true;
+            order.extend(self.vars[var].slots.clone());
+        }
+        Ok(order)
+    }
+
+    /// Assign the slots from `pos` onwards.
+    fn search(
+        &mut self,
+        pos: usize,
+        order: &[usize],
+        at: &[Vec<usize>],
+        values: &mut Vec<Option<BigInt>>,
+        rng: &mut Randomizer,
+    ) -> Result<bool> {
+        let slot = match order.get(pos) {
+            Some(&x) => x,
+            None => return Ok(true),
+        };
+        for value in self.candidates(slot, values, rng)? {
+            values[slot] = Some(value);
+            let mut ok = true;
+            for &check in &at[pos] {
+                if self.budget == 0 {
+                    values[slot] = None;
+                    return Ok(false);
+                }
+                self.budget -= 1;
+                if !self.satisfied(check, values)? {
+                    ok = false;
+                    break;
+                }
+            }
+            if ok && self.search(pos + 1, order, at, values, rng)? {
+                return Ok(true);
+            }
+        }
+        values[slot] = None;
+        Ok(false)
+    }
+
+    /// Determine the values to try for a slot, in the order they are tried.
+    fn candidates(
+        &self,
+        slot: usize,
+        values: &[Option<BigInt>],
+        rng: &mut Randomizer,
+    ) -> Result<Vec<BigInt>> {
+        let s = &self.slots[slot];
+        let var = &self.vars[s.var];
+        let size = (&s.hi - &s.lo + BigInt::one())
+            .to_usize()
+            .unwrap_or(usize::max_value());
+        let dist = match s.dist {
+            Some(check) => self.dist_weights(check, values)?,
+            None => vec![],
+        };
+        let weight = |v: &BigInt| -> f64 {
+            dist.iter()
+                .filter(|(lo, hi, _)| lo <= v && v <= hi)
+                .map(|&(_, _, w)| w)
+                .sum()
+        };
+
+        // Small domains are enumerated exhaustively. Values of `randc`
+        // variables that were already assumed in the current cycle are
+        // skipped.
+        if size <= ENUM_LIMIT || var.cyclic {
+            let index = slot - var.slots.start;
+            let used = rng
+                .cycles
+                .get(&(var.id, index))
+                .filter(|used| used.len() < size)
+                .cloned()
+                .unwrap_or_default();
+            let mut all = vec![];
+            let mut v = s.lo.clone();
+            while v <= s.hi {
+                if !used.contains(&v) {
+                    all.push(v.clone());
+                }
+                v += 1;
+            }
+            if dist.is_empty() {
+                rng.shuffle(&mut all);
+                return Ok(all);
+            }
+            // Weighted random order, using exponentially distributed keys.
+            let mut keyed: Vec<(f64, BigInt)> = all
+                .into_iter()
+                .map(|v| (weight(&v), v))
+                .filter(|&(w, _)| w > 0.0)
+                .map(|(w, v)| (-(1.0 - rng.unit()).ln() / w, v))
+                .collect();
+            keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            return Ok(keyed.into_iter().map(|(_, v)| v).collect());
+        }
+
+        // Large domains are sampled. Values drawn from the distribution come
+        // first, followed by uniformly drawn values, and finally values around
+        // the constants the constraints refer to.
+        let mut cands = vec![];
+        let total: f64 = dist.iter().map(|d| d.2 * range_size(&d.0, &d.1)).sum();
+        if total > 0.0 {
+            for _ in 0..64 {
+                let mut pick = rng.unit() * total;
+                for &(ref lo, ref hi, w) in &dist {
+                    pick -= w * range_size(lo, hi);
+                    if pick <= 0.0 {
+                        let lo = lo.max(&s.lo);
+                        let hi = hi.min(&s.hi);
+                        if lo <= hi {
+                            cands.push(rng.between(lo, hi));
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        for _ in 0..32 {
+            cands.push(rng.between(&s.lo, &s.hi));
+        }
+        let mut marks = BTreeSet::new();
+        marks.insert(s.lo.clone());
+        marks.insert(s.hi.clone());
+        marks.insert(BigInt::zero());
+        for mark in &s.landmarks {
+            marks.insert(mark - 1);
+            marks.insert(mark.clone());
+            marks.insert(mark + 1);
+        }
+        let marks: Vec<_> = marks
+            .into_iter()
+            .filter(|v| &s.lo <= v && v <= &s.hi)
+            .collect();
+        let mut near = marks.clone();
+        for pair in marks.windows(2) {
+            if &pair[1] - &pair[0] > BigInt::one() {
+                near.push(rng.between(&(&pair[0] + 1), &(&pair[1] - 1)));
+            }
+        }
+        rng.shuffle(&mut near);
+        cands.extend(near);
+        let mut seen = HashSet::new();
+        cands.retain(|v| seen.insert(v.clone()));
+        Ok(cands)
+    }
+
+    /// Evaluate the ranges and per-value weights of a distribution.
+    fn dist_weights(
+        &self,
+        check: usize,
+        values: &[Option<BigInt>],
+    ) -> Result<Vec<(BigInt, BigInt, f64)>> {
+        let cx = self.cx;
+        let items = match self.checks[check].kind {
+            CheckKind::Dist(_, items) => items,
+            _ => unreachable!(),
+        };
+        let env = self.checks[check].env;
+        let scratch = self.scratch(values);
+        let int = |id| -> Result<BigInt> {
+            Ok(value::scratch_value_of(cx, id, env, &scratch)?
+                .get_int()
+                .cloned()
+                .unwrap_or_default())
+        };
+        let mut weights = vec![];
+        for item in items {
+            let (lo, hi) = match item.value.range {
+                hir::InsideRange::Single(x) => {
+                    let v = int(x)?;
+                    (v.clone(), v)
+                }
+                hir::InsideRange::Range(lo, hi) => (int(lo)?, int(hi)?),
+            };
+            let mut weight = match item.value.weight {
+                Some(w) => int(w)?.to_f64().unwrap_or(0.0).max(0.0),
+                None => 1.0,
+            };
+            if item.value.per_range {
+                weight /= range_size(&lo, &hi);
+            }
+            weights.push((lo, hi, weight));
+        }
+        Ok(weights)
+    }
+
+    /// Check whether a constraint holds.
+    fn satisfied(&self, check: usize, values: &[Option<BigInt>]) -> Result<bool> {
+        let cx = self.cx;
+        let check = &self.checks[check];
+        let env = check.env;
+        let scratch = self.scratch(values);
+        let eval = |id| value::scratch_value_of(cx, id, env, &scratch);
+        for &(cond, expected) in &check.guards {
+            if eval(cond)?.is_true() != expected {
+                return Ok(true);
+            }
+        }
+        match check.kind {
+            CheckKind::Expr(expr) => Ok(eval(expr)?.is_true()),
+            CheckKind::Dist(expr, items) => {
+                let value = eval(expr)?.get_int().cloned().unwrap_or_default();
+                let int =
+                    |id| -> Result<BigInt> { Ok(eval(id)?.get_int().cloned().unwrap_or_default()) };
+                for item in items {
+                    if let Some(weight) = item.value.weight {
+                        if int(weight)? <= BigInt::zero() {
+                            continue;
+                        }
+                    }
+                    let inside = match item.value.range {
+                        hir::InsideRange::Single(x) => value == int(x)?,
+                        hir::InsideRange::Range(lo, hi) => int(lo)? <= value && value <= int(hi)?,
+                    };
+                    if inside {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// Bind the fixed variables and the assigned slots to scratch values.
+    fn scratch(&self, values: &[Option<BigInt>]) -> ScratchValues<'gcx> {
+        let mut scratch = self.fixed.clone();
+        for var in &self.vars {
+            if let Some(value) = self.var_value(var, |slot| values[slot].as_ref()) {
+                scratch.insert(var.id, value);
+            }
+        }
+        scratch
+    }
+
+    /// Assemble the value of a variable from its slots.
+    ///
+    /// Unassigned array elements are zero. Returns `None` if no slot of the
+    /// variable has been assigned.
+    fn var_value<'v>(
+        &self,
+        var: &Var<'gcx>,
+        value_of: impl Fn(usize) -> Option<&'v BigInt>,
+    ) -> Option<Value<'gcx>> {
+        let cx = self.cx;
+        if var.slots.clone().all(|slot| value_of(slot).is_none()) {
+            return None;
+        }
+        let mut elements = var.slots.clone().map(|slot| {
+            let value = value_of(slot).cloned().unwrap_or_default();
+            cx.intern_value(value::make_int(self.slots[slot].ty, value))
+        });
+        Some(match var.range {
+            Some(_) => cx.intern_value(value::make_array(var.ty, elements.collect())),
+            None => elements.next().unwrap(),
+        })
+    }
+}
+
+/// The number of values in a range.
+fn range_size(lo: &BigInt, hi: &BigInt) -> f64 {
+    (hi - lo + BigInt::one()).to_f64().unwrap_or(0.0).max(1.0)
+}
+
+/// A visitor that determines the slots a constraint depends on.
+struct DepCollector<'a, 'b, 'gcx, C> {
+    solver: &'b Solver<'a, 'gcx, C>,
+    env: ParamEnv,
+    deps: BTreeSet<usize>,
+    landmarks: BTreeSet<BigInt>,
+    failed: bool,
+}
+
+impl<'a, 'b, 'gcx, C> DepCollector<'a, 'b, 'gcx, C>
+where
+    C: Context<'gcx>,
+{
+    /// Determine the slot of an array element accessed with an index that
+    /// does not depend on any random variable.
+    fn constant_element(&self, target: NodeId, index: NodeId) -> Result<Option<usize>> {
+        let cx = self.solver.cx;
+        let var = match cx.hir_of(target)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Ident(..),
+                ..
+            }) => self.solver.var_of(cx.resolve_node(target, self.env)?),
+            _ => None,
+        };
+        let var = match var.map(|v| &self.solver.vars[v]) {
+            Some(var) if var.range.is_some() => var,
+            _ => return Ok(None),
+        };
+        let mut inner = DepCollector {
+            solver: self.solver,
+            env: self.env,
+            deps: BTreeSet::new(),
+            landmarks: BTreeSet::new(),
+            failed: false,
+        };
+        inner.visit_node_with_id(index, false);
+        if inner.failed || !inner.deps.is_empty() {
+            return Ok(None);
+        }
+        let index = value::scratch_value_of(cx, index, self.env, &self.solver.fixed)?
+            .get_int()
+            .cloned()
+            .unwrap_or_default();
+        let pos = (index - BigInt::from(var.range.unwrap().offset)).to_usize();
+        Ok(pos
+            .filter(|&pos| pos < var.slots.len())
+            .map(|pos| var.slots.start + pos))
+    }
+}
+
+impl<'a, 'b, 'gcx, C> Visitor<'gcx> for DepCollector<'a, 'b, 'gcx, C>
+where
+    C: Context<'gcx>,
+{
+    type Context = C;
+
+    fn context(&self) -> &C {
+        self.solver.cx
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx hir::Expr, lvalue: bool) {
+        let cx = self.solver.cx;
+        match expr.kind {
+            hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
+                match cx.resolve_node(expr.id, self.env) {
+                    Ok(binding) => {
+                        if let Some(var) = self.solver.var_of(binding) {
+                            self.deps.extend(self.solver.vars[var].slots.clone());
+                        }
+                    }
+                    Err(()) => self.failed = true,
+                }
+            }
+            hir::ExprKind::Index(target, hir::IndexMode::One(index)) => {
+                match self.constant_element(target, index) {
+                    Ok(Some(slot)) => {
+                        self.deps.insert(slot);
+                        return;
+                    }
+                    Ok(None) => (),
+                    Err(()) => {
+                        self.failed = true;
+                        return;
+                    }
+                }
+            }
+            hir::ExprKind::IntConst { ref value, .. } => {
+                self.landmarks.insert(value.clone());
+                self.landmarks.insert(-value);
+            }
+            _ => (),
+        }
+        hir::walk_expr(self, expr, lvalue);
+    }
+}
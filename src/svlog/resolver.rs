@@ -10,9 +10,11 @@ use crate::{
     common::{SessionContext, Verbosity},
     crate_prelude::*,
     hir::HirNode,
-    ty::TypeKind,
+    ty::{RangeDir, TypeKind},
+    value::{self, ValueKind},
     ParamEnv,
 };
+use num::ToPrimitive;
 use std::{
     collections::{BTreeSet, HashMap},
     sync::Arc,
@@ -49,6 +51,13 @@ pub enum RibKind {
     Module(HashMap<Name, NodeId>),
     /// An enum type declaration.
     Enum(HashMap<Name, NodeId>),
+    /// An explicit import of a name from a package, e.g. `import pkg::name`.
+    Import(Spanned<Name>, NodeId),
+    /// A wildcard import of a package, e.g. `import pkg::*`.
+    ///
+    /// The names of the package only become visible if the scope containing
+    /// the import does not declare them itself. See `resolve_upwards`.
+    Wildcard(NodeId),
 }
 
 impl Rib {
@@ -62,7 +71,7 @@ impl RibKind {
     /// Look up a name.
     pub fn get(&self, name: Name) -> Option<NodeId> {
         match *self {
-            RibKind::Normal(n, id) if n.value == name => Some(id),
+            RibKind::Normal(n, id) | RibKind::Import(n, id) if n.value == name => Some(id),
             RibKind::Module(ref defs) | RibKind::Enum(ref defs) => defs.get(&name).cloned(),
             _ => None,
         }
@@ -78,16 +87,79 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
     trace!("local_rib for {} ({:?})", ast.desc_full(), node_id);
     let mut parent = None;
     let mut kind = match ast {
-        AstNode::TypeParam(_, decl) => Some(RibKind::Normal(
-            Spanned::new(decl.name.name, decl.name.span),
+        AstNode::TypeParam(_, decl) => {
+            parent = class_param_parent(cx, node_id)?;
+            Some(RibKind::Normal(
+                Spanned::new(decl.name.name, decl.name.span),
+                node_id,
+            ))
+        }
+        AstNode::ValueParam(_, decl) => {
+            parent = class_param_parent(cx, node_id)?;
+            Some(RibKind::Normal(
+                Spanned::new(decl.name.name, decl.name.span),
+                node_id,
+            ))
+        }
+        AstNode::Module(_) | AstNode::Interface(_) => {
+            // Functions and tasks may be called before they are declared, so
+            // make them visible throughout the entire module.
+            let block = match cx.hir_of(node_id)? {
+                HirNode::Module(x) => &x.block,
+                HirNode::Interface(x) => &x.block,
+                _ => unreachable!(),
+            };
+            // Modules within a compilation unit see all names of its `$unit`
+            // scope.
+            if let Some(unit_id) = cx.parent_node_id(node_id) {
+                if let HirNode::Package(unit) = cx.hir_of(unit_id)? {
+                    parent = Some(unit.last_rib);
+                }
+            }
+            let mut defs = HashMap::new();
+            for &id in &block.subroutines {
+                if let AstNode::Subroutine(decl) = cx.ast_of(id)? {
+                    defs.insert(decl.prototype.name.name, id);
+                }
+            }
+            for &id in &block.classes {
+                if let AstNode::Class(decl) = cx.ast_of(id)? {
+                    defs.insert(decl.name.name, id);
+                }
+            }
+            Some(RibKind::Module(defs))
+        }
+        // Classes see their own members and the ones inherited from their
+        // base class, with the former taking precedence. The members are
+        // placed above the parameters of the class.
+        AstNode::Class(_) => {
+            let class = match cx.hir_of(node_id)? {
+                HirNode::Class(x) => x,
+                _ => unreachable!(),
+            };
+            parent = class.params.last().cloned();
+            let mut defs = HashMap::new();
+            if let Some(base) = class_base(cx, node_id)? {
+                if let RibKind::Module(ref inherited) = cx.local_rib(base)?.kind {
+                    defs.extend(inherited);
+                }
+            }
+            for member in &class.members {
+                defs.insert(member.name.value, member.id);
+            }
+            Some(RibKind::Module(defs))
+        }
+        AstNode::Modport(modport, _) => Some(RibKind::Normal(
+            Spanned::new(modport.name.name, modport.name.span),
             node_id,
         )),
-        AstNode::ValueParam(_, decl) => Some(RibKind::Normal(
-            Spanned::new(decl.name.name, decl.name.span),
+        AstNode::Inst(inst, _) => Some(RibKind::Normal(
+            Spanned::new(inst.name.name, inst.name.span),
             node_id,
         )),
-        AstNode::Module(_) => Some(RibKind::Module(HashMap::new())),
-        AstNode::VarDecl(decl, _, _) | AstNode::NetDecl(decl, _, _) => Some(RibKind::Normal(
+        AstNode::VarDecl(decl, _, _)
+        | AstNode::NetDecl(decl, _, _)
+        | AstNode::PortDecl(decl, _, _) => Some(RibKind::Normal(
             Spanned::new(decl.name, decl.name_span),
             node_id,
         )),
@@ -111,7 +183,60 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
             }
             _ => None,
         },
-        AstNode::Package(_) => Some(RibKind::Module(HashMap::new())),
+        AstNode::Expr(&ast::Expr {
+            data: ast::PatternVarExpr(Some(name)),
+            ..
+        }) => Some(RibKind::Normal(Spanned::new(name.name, name.span), node_id)),
+        AstNode::SeqDecl(decl) => Some(RibKind::Normal(
+            Spanned::new(decl.name.name, decl.name.span),
+            node_id,
+        )),
+        AstNode::PropDecl(decl) => Some(RibKind::Normal(
+            Spanned::new(decl.name.name, decl.name.span),
+            node_id,
+        )),
+        AstNode::SeqPort(port) => Some(RibKind::Normal(
+            Spanned::new(port.name.name, port.name.span),
+            node_id,
+        )),
+        AstNode::Subroutine(decl) => Some(RibKind::Normal(
+            Spanned::new(decl.prototype.name.name, decl.prototype.name.span),
+            node_id,
+        )),
+        AstNode::SubroutineProto(proto) => Some(RibKind::Normal(
+            Spanned::new(proto.name.name, proto.name.span),
+            node_id,
+        )),
+        AstNode::SubroutinePort(port, _, _) => port
+            .name
+            .as_ref()
+            .map(|name| RibKind::Normal(Spanned::new(name.name.name, name.name.span), node_id)),
+        AstNode::SubroutinePortDecl(name, _, _) => Some(RibKind::Normal(
+            Spanned::new(name.name, name.name_span),
+            node_id,
+        )),
+        // Classes are visible throughout the entire package.
+        AstNode::Package(_) | AstNode::Unit(_) => {
+            let pkg = match cx.hir_of(node_id)? {
+                HirNode::Package(x) => x,
+                _ => unreachable!(),
+            };
+            let mut defs = HashMap::new();
+            for &id in &pkg.classes {
+                if let AstNode::Class(decl) = cx.ast_of(id)? {
+                    defs.insert(decl.name.name, id);
+                }
+            }
+            Some(RibKind::Module(defs))
+        }
+        // Generate statements make the names of their blocks visible, and the
+        // blocks themselves form a scope.
+        AstNode::GenIf(..) | AstNode::GenFor(..) | AstNode::GenCase(..) => {
+            let mut defs = HashMap::new();
+            collect_gen_block_names(cx, node_id, &mut defs)?;
+            Some(RibKind::Module(defs))
+        }
+        AstNode::GenBlock(..) => Some(RibKind::Module(HashMap::new())),
         AstNode::Type(_) => {
             let hir = match cx.hir_of(node_id)? {
                 HirNode::Type(x) => x,
@@ -119,9 +244,50 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
             };
             local_rib_kind_for_type(cx, &hir.kind)
         }
-        AstNode::Import(_import) => {
-            unimplemented!("import statement local rib");
+        AstNode::Import(import) => {
+            let pkg = match cx.gcx().find_package(import.pkg.name) {
+                Some(pkg) => pkg,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("unknown package `{}`", import.pkg.name))
+                            .span(import.pkg.span),
+                    );
+                    return Err(());
+                }
+            };
+            match import.name {
+                Some(name) => {
+                    let name = Spanned::new(name.name, name.span);
+                    Some(RibKind::Import(
+                        name,
+                        cx.resolve_downwards_or_error(name, pkg)?,
+                    ))
+                }
+                None => Some(RibKind::Wildcard(pkg)),
+            }
         }
+        AstNode::ForeachIndex(ident) => Some(RibKind::Normal(
+            Spanned::new(ident.name, ident.span),
+            node_id,
+        )),
+        // The inline constraints of `obj.randomize() with {...}` see the
+        // members of the object's class before the surrounding scope.
+        AstNode::Expr(&ast::Expr {
+            data: ast::RandomizeWithExpr(..),
+            ..
+        }) => match cx.hir_of(node_id)? {
+            HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::Randomize(hir::RandomizeTarget::Object(target), ..),
+                ..
+            }) => match cx.type_of(target, cx.default_param_env())?.get_class() {
+                Some((class_id, _)) => match cx.local_rib(class_id)?.kind {
+                    RibKind::Module(ref defs) => Some(RibKind::Module(defs.clone())),
+                    _ => None,
+                },
+                None => None,
+            },
+            _ => None,
+        },
         _ => None,
     };
     if kind.is_none() {
@@ -166,146 +332,1798 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
                 .add_note("Parent is here:".to_string())
                 .span(cx.span(parent));
         }
-        cx.emit(d);
+        cx.emit(d);
+    }
+    Ok(cx.arena().alloc_rib(rib))
+}
+
+/// Determine the parent rib of a class parameter.
+///
+/// The first parameter of a class skips the class itself, such that the
+/// parameters and the base class type cannot see the members of the class.
+fn class_param_parent<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<Option<NodeId>> {
+    let parent = match cx.parent_node_id(node_id) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    Ok(match cx.ast_of(parent)? {
+        AstNode::Class(_) => cx.parent_node_id(parent),
+        _ => None,
+    })
+}
+
+/// Determine the base class of a class, if it has one.
+pub(crate) fn class_base<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+) -> Result<Option<NodeId>> {
+    let class = match cx.hir_of(class_id)? {
+        HirNode::Class(x) => x,
+        _ => unreachable!(),
+    };
+    let ty = match class.extends {
+        Some((ty, _)) => ty,
+        None => return Ok(None),
+    };
+    let base = cx.resolve_node(ty, cx.default_param_env())?;
+    match cx.hir_of(base)? {
+        HirNode::Class(_) if base != class_id => Ok(Some(base)),
+        hir => {
+            cx.emit(
+                DiagBuilder2::error(format!("{} cannot be extended", hir.desc_full()))
+                    .span(cx.span(ty))
+                    .add_note("only classes other than the class itself can be extended"),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Determine the base class of a class, together with its parameter
+/// environment.
+///
+/// The `env` is the parameter environment of the derived class.
+pub(crate) fn class_base_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    env: ParamEnv,
+) -> Result<Option<(NodeId, ParamEnv)>> {
+    let class = match cx.hir_of(class_id)? {
+        HirNode::Class(x) => x,
+        _ => unreachable!(),
+    };
+    match (&class.extends, class_base(cx, class_id)?) {
+        (&Some((ty, _)), Some(_)) => Ok(cx.map_to_type(ty, env)?.get_class()),
+        _ => Ok(None),
+    }
+}
+
+/// Find a property or method of a class, including the inherited ones.
+///
+/// Returns the class that declares the member, the member itself, and the
+/// parameter environment of the declaring class.
+pub(crate) fn class_member<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    env: ParamEnv,
+    name: Name,
+) -> Result<Option<(&'gcx hir::Class, &'gcx hir::ClassMember, ParamEnv)>> {
+    let mut next = Some((class_id, env));
+    while let Some((id, env)) = next {
+        let class = match cx.hir_of(id)? {
+            HirNode::Class(x) => x,
+            _ => unreachable!(),
+        };
+        if let Some(member) = class.member(name) {
+            return Ok(Some((class, member, env)));
+        }
+        next = class_base_type(cx, id, env)?;
+    }
+    Ok(None)
+}
+
+/// Determine the class that declares a property or method, if any.
+pub(crate) fn declaring_class<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Option<&'gcx hir::Class> {
+    let parent = cx.parent_node_id(node_id)?;
+    match cx.ast_of(parent) {
+        Ok(AstNode::Class(_)) => (),
+        _ => return None,
+    }
+    match cx.hir_of(parent) {
+        Ok(HirNode::Class(class)) if class.members.iter().any(|m| m.id == node_id) => Some(class),
+        _ => None,
+    }
+}
+
+/// Determine the class method or property that contains a node, if any.
+pub(crate) fn enclosing_class<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Option<(&'gcx hir::Class, &'gcx hir::ClassMember)> {
+    let mut next = cx.parent_node_id(node_id);
+    while let Some(id) = next {
+        if let Some(class) = declaring_class(cx, id) {
+            let member = class.members.iter().find(|m| m.id == id).unwrap();
+            return Some((class, member));
+        }
+        next = cx.parent_node_id(id);
+    }
+    None
+}
+
+/// Check whether one class is derived from another, or is the same class.
+pub(crate) fn is_derived_class<'gcx>(
+    cx: &impl Context<'gcx>,
+    derived: NodeId,
+    base: NodeId,
+) -> Result<bool> {
+    let mut next = Some(derived);
+    while let Some(id) = next {
+        if id == base {
+            return Ok(true);
+        }
+        next = class_base(cx, id)?;
+    }
+    Ok(false)
+}
+
+/// Check whether a class type is derived from another, or is the same type.
+///
+/// Unlike `is_derived_class`, this also requires the parameters of the base
+/// class to match, such that different specializations of a class are not
+/// compatible with each other.
+pub(crate) fn is_derived_class_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    derived: (NodeId, ParamEnv),
+    base: (NodeId, ParamEnv),
+) -> Result<bool> {
+    let mut next = Some(derived);
+    while let Some((id, env)) = next {
+        if id == base.0 {
+            return same_specialization(cx, id, env, base.1);
+        }
+        next = class_base_type(cx, id, env)?;
+    }
+    Ok(false)
+}
+
+/// Check whether two environments assign the same types and values to the
+/// parameters of a class.
+fn same_specialization<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    a: ParamEnv,
+    b: ParamEnv,
+) -> Result<bool> {
+    if a == b {
+        return Ok(true);
+    }
+    let class = match cx.hir_of(class_id)? {
+        HirNode::Class(x) => x,
+        _ => unreachable!(),
+    };
+    for &param in &class.params {
+        let same = match cx.hir_of(param)? {
+            HirNode::TypeParam(_) => {
+                ty::identical(cx.map_to_type(param, a)?, cx.map_to_type(param, b)?)
+            }
+            _ => cx.constant_value_of(param, a)? == cx.constant_value_of(param, b)?,
+        };
+        if !same {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Check whether a method is virtual, either by being declared as such or by
+/// overriding a virtual method of a base class.
+pub(crate) fn is_virtual_method<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    name: Name,
+) -> Result<bool> {
+    let mut next = Some(class_id);
+    while let Some(id) = next {
+        let class = match cx.hir_of(id)? {
+            HirNode::Class(x) => x,
+            _ => unreachable!(),
+        };
+        if class.member(name).map(|m| m.virt).unwrap_or(false) {
+            return Ok(true);
+        }
+        next = class_base(cx, id)?;
+    }
+    Ok(false)
+}
+
+/// Check that a class member may be accessed from a node.
+///
+/// Local members are only accessible from within the declaring class, and
+/// protected members additionally from within derived classes.
+pub(crate) fn check_member_access<'gcx>(
+    cx: &impl Context<'gcx>,
+    class: &'gcx hir::Class,
+    member: &'gcx hir::ClassMember,
+    from: NodeId,
+    span: Span,
+) -> Result<()> {
+    let within = enclosing_class(cx, from).map(|(c, _)| c.id);
+    let allowed = match member.visibility {
+        hir::ClassVisibility::Public => true,
+        hir::ClassVisibility::Local => within == Some(class.id),
+        hir::ClassVisibility::Protected => match within {
+            Some(within) => is_derived_class(cx, within, class.id)?,
+            None => false,
+        },
+    };
+    if allowed {
+        return Ok(());
+    }
+    let (what, note) = match member.visibility {
+        hir::ClassVisibility::Local => ("local", "within the class itself"),
+        _ => ("protected", "within the class and its subclasses"),
+    };
+    cx.emit(
+        DiagBuilder2::error(format!(
+            "`{}` is a {} member of {}",
+            member.name,
+            what,
+            class.desc_full()
+        ))
+        .span(span)
+        .add_note(format!("`{}` can only be accessed {}", member.name, note))
+        .add_note(format!("`{}` declared here:", member.name))
+        .span(member.name.span),
+    );
+    Err(())
+}
+
+/// Resolve a field access expression to a property of a class object.
+///
+/// Returns the property together with the parameter environment of the class
+/// that declares it, or `None` if the expression does not access a class
+/// object.
+pub(crate) fn resolve_class_property<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<Option<(NodeId, ParamEnv)>> {
+    let hir = match cx.hir_of(node_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    let (target, name) = match hir.kind {
+        hir::ExprKind::Field(target, name) => (target, name),
+        _ => unreachable!(),
+    };
+    let (class_id, class_env) = match cx.type_of(target, env)?.get_class() {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    match class_member(cx, class_id, class_env, name.value)? {
+        Some((class, member, member_env)) if member.kind == hir::ClassMemberKind::Property => {
+            check_member_access(cx, class, member, node_id, name.span)?;
+            Ok(Some((member.id, member_env)))
+        }
+        Some((class, member, _)) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is a method of {} and must be called",
+                    name,
+                    class.desc_full()
+                ))
+                .span(name.span)
+                .add_note(format!("`{}` declared here:", member.name))
+                .span(member.name.span),
+            );
+            Err(())
+        }
+        None => {
+            let class = cx.hir_of(class_id)?;
+            cx.emit(
+                DiagBuilder2::error(format!("{} has no member `{}`", class.desc_full(), name))
+                    .span(name.span)
+                    .add_note(format!("{} declared here:", class.desc_full()))
+                    .span(class.human_span()),
+            );
+            Err(())
+        }
+    }
+}
+
+/// A call of a class method, or the construction of a class object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassCall {
+    /// The class that declares the method, or the class being constructed.
+    pub class: NodeId,
+    /// The parameter environment of `class`.
+    pub env: ParamEnv,
+    /// The method being called and its arguments. Classes without a
+    /// constructor are constructed without a call.
+    pub mapping: Option<CallMapping>,
+}
+
+/// Resolve a method call on a class object, or a `new` expression.
+///
+/// Returns `None` if the expression is a method call on something other than
+/// a class object, such as a queue or string.
+pub(crate) fn resolve_class_call<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<Option<Arc<ClassCall>>> {
+    let hir = match cx.hir_of(node_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    let (class_id, class_env, name, args) = match hir.kind {
+        // Constructors build an object of the class required by the context.
+        hir::ExprKind::New(ref args) => {
+            let ty = cx.need_type_context(node_id, env).ty();
+            let (class_id, class_env) = match ty.get_class() {
+                Some(x) => x,
+                None if ty.is_error() => return Err(()),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`new` cannot construct a value of type `{}`",
+                            ty
+                        ))
+                        .span(hir.span),
+                    );
+                    return Err(());
+                }
+            };
+            let class = match cx.hir_of(class_id)? {
+                HirNode::Class(x) => x,
+                _ => unreachable!(),
+            };
+            if class.virt {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} is virtual and cannot be constructed",
+                        class.desc_full()
+                    ))
+                    .span(hir.span)
+                    .add_note(format!("{} declared here:", class.desc_full()))
+                    .span(class.human_span()),
+                );
+                return Err(());
+            }
+            // Classes without a constructor of their own are constructed by
+            // an implicit `new()`, even if their base class has one.
+            let name = moore_common::name::get_name_table().intern("new", true);
+            if class.constructor().is_none() {
+                if let Some(arg) = args.first() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} has no constructor that takes arguments",
+                            class.desc_full()
+                        ))
+                        .span(arg.span),
+                    );
+                    return Err(());
+                }
+                return Ok(Some(Arc::new(ClassCall {
+                    class: class_id,
+                    env: class_env,
+                    mapping: None,
+                })));
+            }
+            (class_id, class_env, Spanned::new(name, hir.span), args)
+        }
+        hir::ExprKind::MethodCall(target, name, ref args) => {
+            match cx.type_of(target, env)?.get_class() {
+                Some((class_id, class_env)) => (class_id, class_env, name, args),
+                None => return Ok(None),
+            }
+        }
+        _ => unreachable!(),
+    };
+
+    // Find the method and match up the arguments.
+    let (class, member, member_env) = match class_member(cx, class_id, class_env, name.value)? {
+        Some((class, member, member_env)) if member.kind != hir::ClassMemberKind::Property => {
+            (class, member, member_env)
+        }
+        Some((class, member, _)) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is a property of {} and cannot be called",
+                    name,
+                    class.desc_full()
+                ))
+                .span(name.span)
+                .add_note(format!("`{}` declared here:", member.name))
+                .span(member.name.span),
+            );
+            return Err(());
+        }
+        None => {
+            let class = cx.hir_of(class_id)?;
+            cx.emit(
+                DiagBuilder2::error(format!("{} has no method `{}`", class.desc_full(), name))
+                    .span(name.span)
+                    .add_note(format!("{} declared here:", class.desc_full()))
+                    .span(class.human_span()),
+            );
+            return Err(());
+        }
+    };
+    check_member_access(cx, class, member, node_id, name.span)?;
+    let subroutine = match cx.hir_of(member.id)? {
+        HirNode::Subroutine(x) => x,
+        _ => unreachable!(),
+    };
+    Ok(Some(Arc::new(ClassCall {
+        class: class.id,
+        env: member_env,
+        mapping: Some(CallMapping {
+            subroutine: subroutine.id,
+            args: map_call_args(cx, hir.span, subroutine, args)?,
+        }),
+    })))
+}
+
+/// Collect the names of the blocks of a generate statement.
+///
+/// Blocks that do not form a scope of their own contribute the names of the
+/// blocks of their nested generate statement.
+fn collect_gen_block_names<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    into: &mut HashMap<Name, NodeId>,
+) -> Result<()> {
+    let gen = match cx.hir_of(node_id)? {
+        HirNode::Gen(x) => x,
+        _ => unreachable!(),
+    };
+    for block_id in gen.kind.blocks() {
+        let block = match cx.hir_of(block_id)? {
+            HirNode::GenBlock(x) => x,
+            _ => unreachable!(),
+        };
+        match block.name {
+            Some(name) => {
+                into.entry(name.value).or_insert(block_id);
+            }
+            None => {
+                for &id in &block.block.gens {
+                    collect_gen_block_names(cx, id, into)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn local_rib_kind_for_type<'gcx>(cx: &impl Context<'gcx>, kind: &hir::TypeKind) -> Option<RibKind> {
+    trace!("creating local rib for type {:#?}", kind);
+    match kind {
+        hir::TypeKind::PackedArray(inner, ..) => local_rib_kind_for_type(cx, inner.as_ref()),
+        hir::TypeKind::Enum(ref variants, _) => Some(RibKind::Enum(
+            variants
+                .iter()
+                .map(|(name, id)| (name.value, *id))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Determine the hierarchical rib of a node.
+///
+/// This will return a rib containing the hierarchical names exposed by a node.
+pub(crate) fn hierarchical_rib<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<&'gcx Rib> {
+    let hir = cx.hir_of(node_id)?;
+    let mut names = HashMap::new();
+    let mut imports = vec![];
+    let mut wildcards = vec![];
+    let mut rib_id = match hir {
+        HirNode::Package(pkg) => Some(pkg.last_rib),
+        HirNode::Module(module) => Some(module.block.last_rib),
+        HirNode::Interface(intf) => Some(intf.last_rib),
+        HirNode::GenBlock(block) => Some(block.block.last_rib),
+        // The members of a class, including the inherited ones, are accessed
+        // as `C::x`.
+        HirNode::Class(_) => {
+            if let RibKind::Module(ref defs) = cx.local_rib(node_id)?.kind {
+                names.extend(defs);
+            }
+            None
+        }
+        _ => panic!("{} has no hierarchical rib", hir.desc_full()),
+    };
+    while let Some(id) = rib_id {
+        if id == node_id {
+            break;
+        }
+        let rib = cx.local_rib(id)?;
+        match rib.kind {
+            RibKind::Normal(name, def) => {
+                names.insert(name.value, def);
+            }
+            RibKind::Module(ref defs) => names.extend(defs),
+            RibKind::Enum(ref defs) => names.extend(defs),
+            // Imported names are only visible from outside if they are
+            // exported again.
+            RibKind::Import(name, def) => {
+                if let AstNode::Import(import) = cx.ast_of(rib.node)? {
+                    imports.push((import.pkg.name, name.value, def));
+                }
+            }
+            RibKind::Wildcard(pkg) => wildcards.push(pkg),
+        }
+        rib_id = rib.parent;
+    }
+    if let HirNode::Package(pkg) = hir {
+        for &id in &pkg.classes {
+            if let AstNode::Class(decl) = cx.ast_of(id)? {
+                names.insert(decl.name.name, id);
+            }
+        }
+        for export in &pkg.exports {
+            add_exported_names(cx, export, &imports, &wildcards, &mut names)?;
+        }
+    }
+    let rib = Rib {
+        node: node_id,
+        parent: None,
+        kind: RibKind::Module(names),
+    };
+    Ok(cx.arena().alloc_rib(rib))
+}
+
+/// Add the names exported by a package export to a hierarchical rib.
+///
+/// Exporting a wildcard-imported package makes all of its names visible, not
+/// just the ones that are referenced within the exporting package.
+fn add_exported_names<'gcx>(
+    cx: &impl Context<'gcx>,
+    export: &hir::Export,
+    imports: &[(Name, Name, NodeId)],
+    wildcards: &[NodeId],
+    names: &mut HashMap<Name, NodeId>,
+) -> Result<()> {
+    let pkg = match export.pkg {
+        Some(name) => match cx.gcx().find_package(name.value) {
+            Some(pkg) => Some(pkg),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!("unknown package `{}`", name.value))
+                        .span(name.span),
+                );
+                return Err(());
+            }
+        },
+        None => None,
+    };
+    let pkg_name = export.pkg.map(|n| n.value);
+    let wildcards = wildcards
+        .iter()
+        .cloned()
+        .filter(|&id| pkg.is_none() || pkg == Some(id));
+    match export.name {
+        Some(name) => {
+            let explicit = imports
+                .iter()
+                .find(|&&(p, n, _)| Some(p) == pkg_name && n == name.value)
+                .map(|&(_, _, def)| def);
+            let def = match explicit {
+                Some(def) => Some(def),
+                None => {
+                    let mut found = None;
+                    for id in wildcards {
+                        found = found.or(cx.resolve_downwards(name.value, id)?);
+                    }
+                    found
+                }
+            };
+            match def {
+                Some(def) => {
+                    names.entry(name.value).or_insert(def);
+                }
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not imported from package `{}`",
+                            name.value,
+                            pkg_name.unwrap()
+                        ))
+                        .span(export.span),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        None => {
+            for &(p, n, def) in imports {
+                if pkg_name.is_none() || pkg_name == Some(p) {
+                    names.entry(n).or_insert(def);
+                }
+            }
+            for id in wildcards {
+                if let RibKind::Module(ref defs) = cx.hierarchical_rib(id)?.kind {
+                    for (&n, &def) in defs {
+                        names.entry(n).or_insert(def);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a name upwards through the ribs.
+///
+/// This is equivalent to performing regular scoped namespace lookup.
+pub(crate) fn resolve_upwards<'gcx>(
+    cx: &impl Context<'gcx>,
+    name: Name,
+    start_at: NodeId,
+) -> Result<Option<NodeId>> {
+    if cx.sess().has_verbosity(Verbosity::NAMES) {
+        cx.emit(DiagBuilder2::note(format!("resolving `{}`", name)).span(cx.span(start_at)));
+    }
+    let mut next_id = Some(start_at);
+    let mut wildcards = vec![];
+    while let Some(rib_id) = next_id {
+        if cx.sess().has_verbosity(Verbosity::NAMES) {
+            cx.emit(DiagBuilder2::note(format!("resolving `{}` here", name)).span(cx.span(rib_id)));
+        }
+        let rib = cx.local_rib(rib_id)?;
+        if let id @ Some(_) = rib.get(name) {
+            return Ok(id);
+        }
+        if let RibKind::Wildcard(pkg) = rib.kind {
+            wildcards.push(pkg);
+        }
+        // Names imported through a wildcard are only visible if the scope
+        // that contains the import does not declare them itself. Therefore
+        // only consider them once we leave that scope.
+        if !wildcards.is_empty() && is_scope_boundary(cx, rib.node)? {
+            if let id @ Some(_) = resolve_wildcards(cx, name, start_at, &wildcards)? {
+                return Ok(id);
+            }
+            wildcards.clear();
+        }
+        next_id = rib.parent;
+    }
+    if let id @ Some(_) = resolve_wildcards(cx, name, start_at, &wildcards)? {
+        return Ok(id);
+    }
+    if let m @ Some(_) = cx.gcx().find_module(name) {
+        return Ok(m);
+    }
+    if let p @ Some(_) = cx.gcx().find_package(name) {
+        return Ok(p);
+    }
+    if let i @ Some(_) = cx.gcx().find_interface(name) {
+        return Ok(i);
+    }
+    Ok(None)
+}
+
+/// Check whether a rib marks the top of a scope.
+fn is_scope_boundary<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<bool> {
+    Ok(match cx.ast_of(node_id)? {
+        AstNode::Module(..)
+        | AstNode::Interface(..)
+        | AstNode::Package(..)
+        | AstNode::Unit(..)
+        | AstNode::GenBlock(..)
+        | AstNode::Class(..) => true,
+        _ => false,
+    })
+}
+
+/// Look up a name in a list of wildcard-imported packages.
+///
+/// Emits an error if the name is visible through more than one package.
+fn resolve_wildcards<'gcx>(
+    cx: &impl Context<'gcx>,
+    name: Name,
+    start_at: NodeId,
+    wildcards: &[NodeId],
+) -> Result<Option<NodeId>> {
+    let mut found: Option<(NodeId, NodeId)> = None;
+    for &pkg in wildcards.iter().rev() {
+        let id = match cx.resolve_downwards(name, pkg)? {
+            Some(id) => id,
+            None => continue,
+        };
+        match found {
+            Some((other_pkg, other_id)) if other_id != id => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is ambiguous; it is imported from both {} and {}",
+                        name,
+                        cx.ast_of(other_pkg)?.desc_full(),
+                        cx.ast_of(pkg)?.desc_full(),
+                    ))
+                    .span(cx.span(start_at)),
+                );
+                return Err(());
+            }
+            Some(_) => (),
+            None => found = Some((pkg, id)),
+        }
+    }
+    Ok(found.map(|(_, id)| id))
+}
+
+/// Resolve a name downwards.
+///
+/// This is equivalent to performing a hierarchical name lookup.
+pub(crate) fn resolve_downwards<'gcx>(
+    cx: &impl Context<'gcx>,
+    name: Name,
+    start_at: NodeId,
+) -> Result<Option<NodeId>> {
+    let rib = cx.hierarchical_rib(start_at)?;
+    Ok(match rib.get(name) {
+        Some(x) => Some(x),
+        None => {
+            debug!("name `{}` not found in {:#?}", name, rib);
+            None
+        }
+    })
+}
+
+/// Resolve a node to its target.
+pub(crate) fn resolve_node<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<NodeId> {
+    let hir = cx.hir_of(node_id)?;
+    match hir {
+        HirNode::Expr(expr) => match expr.kind {
+            hir::ExprKind::Ident(ident) => return cx.resolve_upwards_or_error(ident, node_id),
+            hir::ExprKind::Scope(scope_id, name) => {
+                let within = cx.resolve_node(scope_id, env)?;
+                return cx.resolve_downwards_or_error(name, within);
+            }
+            _ => (),
+        },
+        HirNode::Type(ty) => match ty.kind {
+            hir::TypeKind::Named(name) => return cx.resolve_upwards_or_error(name, node_id),
+            hir::TypeKind::Scope(scope_id, name) => {
+                let within = cx.resolve_node(scope_id, env)?;
+                return cx.resolve_downwards_or_error(name, within);
+            }
+            hir::TypeKind::Specialized(base, ..) => return cx.resolve_node(base, env),
+            _ => (),
+        },
+        _ => (),
+    }
+    error!("{:#?}", hir);
+    cx.emit(DiagBuilder2::bug("cannot resolve node").span(hir.human_span()));
+    Err(())
+}
+
+/// The actual arguments of a function or task call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallMapping {
+    /// The function or task being called.
+    pub subroutine: NodeId,
+    /// The argument assigned to each port of the subroutine, given as `(port,
+    /// expr)` in declaration order. Ports that are not assigned in the call
+    /// map to their default expression.
+    pub args: Vec<(NodeId, NodeId)>,
+}
+
+/// Check if a node is located within the body of a function or task.
+///
+/// Declarations following a function or task have it as an ancestor in the
+/// scope tree, such that simply checking for a parent relationship is not
+/// sufficient. Nodes allocated while lowering the body, such as the type of a
+/// local variable declared before any arguments, are not listed in the
+/// subroutine and are recognized by their span instead.
+pub(crate) fn is_within_subroutine<'gcx>(
+    cx: &impl Context<'gcx>,
+    subroutine_id: NodeId,
+    node_id: NodeId,
+) -> bool {
+    let sub = match cx.hir_of(subroutine_id) {
+        Ok(HirNode::Subroutine(x)) => x,
+        _ => return false,
+    };
+    let mut prev = node_id;
+    while let Some(id) = cx.parent_node_id(prev) {
+        if id == subroutine_id {
+            let span = cx.span(prev);
+            return sub.retty == Some(prev)
+                || sub.ports.contains(&prev)
+                || sub.stmts.contains(&prev)
+                || (span.source == sub.span.source
+                    && span.begin >= sub.span.begin
+                    && span.end <= sub.span.end);
+        }
+        prev = id;
+    }
+    false
+}
+
+/// Resolve a method call on a dynamic array, queue, associative array, or
+/// string.
+///
+/// Returns the operation the method corresponds to, together with the
+/// argument expressions.
+pub(crate) fn resolve_container_method<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<(mir::ContainerOp, Vec<NodeId>)> {
+    use mir::ContainerOp;
+    let hir = match cx.hir_of(node_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    let (target, name, args) = match hir.kind {
+        hir::ExprKind::MethodCall(target, name, ref args) => (target, name, args),
+        _ => unreachable!(),
+    };
+    let ty = cx.type_of(target, env)?;
+    let op = match (ty.resolve_name(), &*name.value.as_str(), args.len()) {
+        (TypeKind::String, "len", 0) => Some(ContainerOp::Size),
+        (TypeKind::String, "getc", 1) => Some(ContainerOp::Get),
+        (TypeKind::String, "putc", 2) => Some(ContainerOp::Set),
+        (TypeKind::String, "substr", 2) => Some(ContainerOp::Substr),
+        (TypeKind::String, "toupper", 0) => Some(ContainerOp::ToUpper),
+        (TypeKind::String, "tolower", 0) => Some(ContainerOp::ToLower),
+        (TypeKind::String, "atoi", 0) => Some(ContainerOp::Atoi),
+        (TypeKind::String, "itoa", 1) => Some(ContainerOp::Itoa),
+        (TypeKind::String, ..) => None,
+        (_, "size", 0) if ty.is_dynamic() => Some(ContainerOp::Size),
+        (_, "delete", 0) if ty.is_dynamic() => Some(ContainerOp::DeleteAll),
+        (TypeKind::Queue(..), "delete", 1) | (TypeKind::AssocArray(..), "delete", 1) => {
+            Some(ContainerOp::Delete)
+        }
+        (TypeKind::Queue(..), "insert", 2) => Some(ContainerOp::Insert),
+        (TypeKind::Queue(..), "push_front", 1) => Some(ContainerOp::PushFront),
+        (TypeKind::Queue(..), "push_back", 1) => Some(ContainerOp::PushBack),
+        (TypeKind::Queue(..), "pop_front", 0) => Some(ContainerOp::PopFront),
+        (TypeKind::Queue(..), "pop_back", 0) => Some(ContainerOp::PopBack),
+        (TypeKind::AssocArray(..), "num", 0) => Some(ContainerOp::Size),
+        (TypeKind::AssocArray(..), "exists", 1) => Some(ContainerOp::Exists),
+        (TypeKind::AssocArray(..), "first", 1) => Some(ContainerOp::First),
+        (TypeKind::AssocArray(..), "last", 1) => Some(ContainerOp::Last),
+        (TypeKind::AssocArray(..), "next", 1) => Some(ContainerOp::Next),
+        (TypeKind::AssocArray(..), "prev", 1) => Some(ContainerOp::Prev),
+        _ => None,
+    };
+    let op = match op {
+        Some(op) => op,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` has no method `{}` taking {} arguments",
+                    ty,
+                    name,
+                    args.len()
+                ))
+                .span(name.span),
+            );
+            return Err(());
+        }
+    };
+    let args = args
+        .iter()
+        .map(|arg| match (arg.name, arg.expr) {
+            (None, Some(expr)) => Ok(expr),
+            _ => {
+                cx.emit(
+                    DiagBuilder2::error(format!("method `{}` requires positional arguments", name))
+                        .span(arg.span),
+                );
+                Err(())
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((op, args))
+}
+
+/// Resolve the callee of a call expression and match its arguments up with
+/// the ports of the function or task.
+pub(crate) fn resolve_call<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Arc<CallMapping>> {
+    let hir = match cx.hir_of(node_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    let (callee, args) = match hir.kind {
+        hir::ExprKind::Call(callee, ref args) => (callee, args),
+        _ => unreachable!(),
+    };
+    let target = cx.resolve_node(callee, cx.default_param_env())?;
+    let subroutine = match cx.hir_of(target)? {
+        HirNode::Subroutine(x) => x,
+        target => {
+            cx.emit(
+                DiagBuilder2::error(format!("{} is not a function or task", target.desc_full()))
+                    .span(cx.span(callee))
+                    .add_note(format!("{} declared here:", target.desc_full()))
+                    .span(target.human_span()),
+            );
+            return Err(());
+        }
+    };
+    Ok(Arc::new(CallMapping {
+        subroutine: subroutine.id,
+        args: map_call_args(cx, hir.span, subroutine, args)?,
+    }))
+}
+
+/// Match the arguments of a call up with the ports of a function or task.
+///
+/// Returns the argument assigned to each port, given as `(port, expr)` in
+/// declaration order.
+pub(crate) fn map_call_args<'gcx>(
+    cx: &impl Context<'gcx>,
+    span: Span,
+    subroutine: &'gcx hir::Subroutine,
+    args: &[hir::CallArg],
+) -> Result<Vec<(NodeId, NodeId)>> {
+    let ports = subroutine
+        .ports
+        .iter()
+        .map(|&id| match cx.hir_of(id)? {
+            HirNode::SubroutinePort(x) => Ok(x),
+            _ => unreachable!(),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Assign the positional and named arguments to ports.
+    let mut failed = false;
+    let mut assigned = vec![None; ports.len()];
+    let mut seen_named = false;
+    for (index, arg) in args.iter().enumerate() {
+        let index = match arg.name {
+            Some(name) => {
+                seen_named = true;
+                match ports.iter().position(|p| p.name.value == name.value) {
+                    Some(index) => index,
+                    None => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "{} has no argument `{}`",
+                                subroutine.desc_full(),
+                                name
+                            ))
+                            .span(name.span)
+                            .add_note(format!("{} declared here:", subroutine.desc_full()))
+                            .span(subroutine.human_span()),
+                        );
+                        failed = true;
+                        continue;
+                    }
+                }
+            }
+            None if seen_named => {
+                cx.emit(
+                    DiagBuilder2::error("positional arguments must come before named arguments")
+                        .span(arg.span),
+                );
+                failed = true;
+                continue;
+            }
+            None if index >= ports.len() => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} takes {} arguments, but {} were given",
+                        subroutine.desc_full(),
+                        ports.len(),
+                        args.len()
+                    ))
+                    .span(arg.span)
+                    .add_note(format!("{} declared here:", subroutine.desc_full()))
+                    .span(subroutine.human_span()),
+                );
+                failed = true;
+                break;
+            }
+            None => index,
+        };
+        if assigned[index].is_some() {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} assigned multiple times",
+                    ports[index].desc_full()
+                ))
+                .span(arg.span),
+            );
+            failed = true;
+            continue;
+        }
+        assigned[index] = Some(arg.expr);
+    }
+
+    // Fall back to the default for ports which were not assigned.
+    let mut mapped = vec![];
+    for (port, expr) in ports.into_iter().zip(assigned) {
+        match expr.and_then(|x| x).or(port.default) {
+            Some(expr) => mapped.push((port.id, expr)),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} not assigned in call to {}",
+                        port.desc_full(),
+                        subroutine.desc_full()
+                    ))
+                    .span(span)
+                    .add_note(format!("{} declared here:", port.desc_full()))
+                    .span(port.human_span()),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        return Err(());
+    }
+    Ok(mapped)
+}
+
+/// Determine the interface a node refers to, if any.
+///
+/// Returns the interface and modport if the node is an instance of an
+/// interface, or a port whose type names an interface or one of its modports.
+pub(crate) fn interface_of<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Option<(NodeId, Option<NodeId>)>> {
+    let binding = match cx.hir_of(node_id)? {
+        HirNode::Inst(inst) => {
+            let name = match cx.hir_of(inst.target)? {
+                HirNode::InstTarget(x) => x.name.value,
+                _ => unreachable!(),
+            };
+            return Ok(cx.gcx().find_interface(name).map(|id| (id, None)));
+        }
+        HirNode::Port(hir::Port { ty: Some(ty), .. }) => match cx.hir_of(*ty)? {
+            HirNode::Type(hir::Type {
+                kind: hir::TypeKind::Named(..),
+                ..
+            })
+            | HirNode::Type(hir::Type {
+                kind: hir::TypeKind::Scope(..),
+                ..
+            }) => cx.resolve_node(*ty, cx.default_param_env())?,
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(match cx.ast_of(binding)? {
+        AstNode::Interface(..) => Some((binding, None)),
+        AstNode::Modport(_, intf) => Some((intf, Some(binding))),
+        _ => None,
+    })
+}
+
+/// A signal accessed through an interface instance or port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntfSignal {
+    /// The interface instance or port through which the signal is accessed.
+    pub binding: NodeId,
+    /// The interface declaration.
+    pub intf: NodeId,
+    /// The signal declaration within the interface.
+    pub signal: NodeId,
+    /// The direction imposed by the modport, if any.
+    pub dir: Option<ast::PortDir>,
+}
+
+/// Resolve a field access expression to an interface signal.
+///
+/// Returns `None` if the expression does not access a signal of an interface.
+pub(crate) fn resolve_intf_signal<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Option<IntfSignal>> {
+    let (target_id, name) = match cx.hir_of(node_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target_id, name),
+            ..
+        }) => (*target_id, *name),
+        _ => return Ok(None),
+    };
+    let binding = match cx.hir_of(target_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        }) => cx.resolve_node(target_id, cx.default_param_env())?,
+        _ => return Ok(None),
+    };
+    let (intf, modport) = match interface_of(cx, binding)? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let signal = cx.resolve_downwards_or_error(name, intf)?;
+    match cx.hir_of(signal)? {
+        HirNode::VarDecl(..) | HirNode::Port(..) => (),
+        HirNode::Modport(..) => return Ok(None),
+        hir => {
+            cx.emit(
+                DiagBuilder2::error(format!("{} is not a signal", hir.desc_full()))
+                    .span(name.span)
+                    .add_note(format!("{} declared here:", hir.desc_full()))
+                    .span(hir.human_span()),
+            );
+            return Err(());
+        }
+    }
+    let dir = match modport {
+        Some(modport) => {
+            let modport = match cx.hir_of(modport)? {
+                HirNode::Modport(x) => x,
+                _ => unreachable!(),
+            };
+            match modport.find(name.value) {
+                Some(port) => Some(port.dir),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not accessible through {}",
+                            name,
+                            modport.desc_full()
+                        ))
+                        .span(name.span)
+                        .add_note(format!("{} declared here:", modport.desc_full()))
+                        .span(modport.human_span()),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        None => None,
+    };
+    Ok(Some(IntfSignal {
+        binding,
+        intf,
+        signal,
+        dir,
+    }))
+}
+
+/// Resolve a field access expression to a modport of an interface instance,
+/// such as `bus.mst` (IEEE 1800-2017 §25.5).
+///
+/// Returns the interface instance or port and the modport, or `None` if the
+/// expression does not name a modport.
+pub(crate) fn resolve_intf_modport<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Option<(NodeId, NodeId)>> {
+    let (target_id, name) = match cx.hir_of(node_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target_id, name),
+            ..
+        }) => (*target_id, *name),
+        _ => return Ok(None),
+    };
+    let binding = match cx.hir_of(target_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        }) => cx.resolve_node(target_id, cx.default_param_env())?,
+        _ => return Ok(None),
+    };
+    let intf = match interface_of(cx, binding)? {
+        Some((intf, _)) => intf,
+        None => return Ok(None),
+    };
+    let modport = cx.resolve_downwards_or_error(name, intf)?;
+    match cx.hir_of(modport)? {
+        HirNode::Modport(..) => Ok(Some((binding, modport))),
+        _ => Ok(None),
+    }
+}
+
+/// Resolve a field access expression to a declaration within a generate
+/// block, such as `genblk1.x`, `g_outer.g_inner.x`, or `g_loop[1].x`.
+///
+/// Returns `None` if the expression does not access a generate block.
+pub(crate) fn resolve_gen_block_field<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Option<NodeId>> {
+    let (target_id, name) = match cx.hir_of(node_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target_id, name),
+            ..
+        }) => (*target_id, *name),
+        _ => return Ok(None),
+    };
+    match resolve_gen_block(cx, target_id)? {
+        Some(within) => cx.resolve_downwards_or_error(name, within).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Resolve an expression to a generate block, such as `genblk1`,
+/// `g_outer.g_inner`, or `g_loop[1]`.
+///
+/// The blocks of generate loops must be indexed to select an iteration.
+/// Returns `None` if the expression does not refer to a generate block.
+fn resolve_gen_block<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<Option<NodeId>> {
+    let (target_id, index) = match cx.hir_of(node_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Index(target_id, ref mode),
+            ..
+        }) => (*target_id, Some(mode)),
+        _ => (node_id, None),
+    };
+    let block_id = match cx.hir_of(target_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        }) => cx.resolve_node(target_id, cx.default_param_env())?,
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(..),
+            ..
+        }) => match resolve_gen_block_field(cx, target_id)? {
+            Some(x) => x,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    let block = match cx.hir_of(block_id)? {
+        HirNode::GenBlock(..) => cx.hir_of(block_id)?,
+        _ => return Ok(None),
+    };
+    match (gen_loop_of(cx, block_id)?, index) {
+        (None, None) | (Some(_), Some(hir::IndexMode::One(_))) => Ok(Some(block_id)),
+        (None, Some(_)) => {
+            cx.emit(
+                DiagBuilder2::error(format!("{} cannot be indexed", block.desc_full()))
+                    .span(cx.span(node_id))
+                    .add_note("only the blocks of generate loops have multiple iterations"),
+            );
+            Err(())
+        }
+        (Some(_), None) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} is the block of a generate loop; an index is required to select \
+                     an iteration",
+                    block.desc_full()
+                ))
+                .span(cx.span(node_id)),
+            );
+            Err(())
+        }
+        (Some(_), Some(hir::IndexMode::Many(..))) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "multiple iterations of {} cannot be selected at once",
+                    block.desc_full()
+                ))
+                .span(cx.span(node_id)),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Determine the generate loop a generate block belongs to.
+///
+/// The blocks of generate loops are parented to the loop's genvar rather than
+/// the generate statement itself. Returns `None` for the blocks of if- and
+/// case-generate statements.
+fn gen_loop_of<'gcx>(cx: &impl Context<'gcx>, block_id: NodeId) -> Result<Option<NodeId>> {
+    let mut id = cx.parent_node_id(block_id).unwrap();
+    while let HirNode::GenvarDecl(..) = cx.hir_of(id)? {
+        id = cx.parent_node_id(id).unwrap();
+    }
+    match cx.hir_of(id)? {
+        HirNode::Gen(hir::Gen {
+            kind: hir::GenKind::For { .. },
+            ..
+        }) => Ok(Some(id)),
+        _ => Ok(None),
+    }
+}
+
+/// Check whether a field access expression accesses a declaration within an
+/// iteration of a generate loop, such as `g_loop[1].x`.
+pub(crate) fn is_gen_loop_field<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<bool> {
+    let mut id = node_id;
+    while let HirNode::Expr(hir::Expr {
+        kind: hir::ExprKind::Field(target_id, _),
+        ..
+    }) = cx.hir_of(id)?
+    {
+        id = *target_id;
+        if let HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Index(..),
+            ..
+        }) = cx.hir_of(id)?
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Determine the parameter environment of the declaration a field access into
+/// a generate block resolves to.
+///
+/// Accesses into an iteration of a generate loop, such as `g_loop[1].x`, see
+/// the genvar set to the value of that iteration. All other accesses see the
+/// environment `env` of the scope where the expression is used.
+pub(crate) fn gen_block_field_env<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    let target_id = match cx.hir_of(node_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target_id, _),
+            ..
+        }) => *target_id,
+        _ => return Ok(env),
+    };
+    let (inner_id, index) = match cx.hir_of(target_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Index(inner_id, hir::IndexMode::One(index)),
+            ..
+        }) => (*inner_id, *index),
+        _ => return gen_block_field_env(cx, target_id, env),
+    };
+    let block_env = gen_block_field_env(cx, inner_id, env)?;
+    let block_id = resolve_gen_block(cx, target_id)?.unwrap();
+    let gen_id = gen_loop_of(cx, block_id)?.unwrap();
+    let gen = match cx.hir_of(gen_id)? {
+        HirNode::Gen(x) => x,
+        _ => unreachable!(),
+    };
+    let init = match gen.kind {
+        hir::GenKind::For { ref init, .. } => init,
+        _ => unreachable!(),
+    };
+
+    // The iterations of a loop are only known outside of it, where the genvar
+    // is not yet set. Declarations following the loop also have it as an
+    // ancestor, but not through its genvars.
+    let mut id = node_id;
+    while let Some(parent) = cx.parent_node_id(id) {
+        if parent == gen_id && init.contains(&id) {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be accessed from within the loop",
+                    cx.hir_of(block_id)?.desc_full()
+                ))
+                .span(cx.span(node_id)),
+            );
+            return Err(());
+        }
+        id = parent;
+    }
+
+    // Find the iteration where the genvar equals the index.
+    let index_value = cx.constant_value_of(index, env)?;
+    let genvar = *init.last().unwrap();
+    for iter_env in gen_loop_iterations(cx, gen, block_env)? {
+        if cx.constant_value_of(genvar, iter_env)?.get_int() == index_value.get_int() {
+            return Ok(iter_env);
+        }
+    }
+    cx.emit(
+        DiagBuilder2::error(format!(
+            "{} has no iteration with index `{}`",
+            cx.hir_of(block_id)?.desc_full(),
+            cx.span(index).extract()
+        ))
+        .span(cx.span(index)),
+    );
+    Err(())
+}
+
+/// Determine the parameter environments of the iterations of a generate loop.
+///
+/// Each iteration sees the genvar set to its value, starting out with the
+/// environment `env` of the scope containing the loop.
+pub(crate) fn gen_loop_iterations<'gcx>(
+    cx: &impl Context<'gcx>,
+    gen: &hir::Gen,
+    env: ParamEnv,
+) -> Result<Vec<ParamEnv>> {
+    let (init, cond, step) = match gen.kind {
+        hir::GenKind::For {
+            ref init,
+            cond,
+            step,
+            ..
+        } => (init, cond, step),
+        _ => unreachable!("only generate loops have iterations"),
+    };
+    for &id in init {
+        match cx.hir_of(id)? {
+            HirNode::GenvarDecl(_) => (),
+            _ => unreachable!(),
+        }
     }
-    Ok(cx.arena().alloc_rib(rib))
+    let mut iterations = vec![];
+    let mut env = env;
+    while cx.constant_value_of(cond, env)?.is_true() {
+        iterations.push(env);
+        env = genvar_step(cx, step, env)?;
+    }
+    Ok(iterations)
 }
 
-fn local_rib_kind_for_type<'gcx>(cx: &impl Context<'gcx>, kind: &hir::TypeKind) -> Option<RibKind> {
-    trace!("creating local rib for type {:#?}", kind);
-    match kind {
-        hir::TypeKind::PackedArray(inner, ..) => local_rib_kind_for_type(cx, inner.as_ref()),
-        hir::TypeKind::Enum(ref variants, _) => Some(RibKind::Enum(
-            variants
-                .iter()
-                .map(|(name, id)| (name.value, *id))
-                .collect(),
-        )),
+/// Execute the iteration step of a generate loop.
+fn genvar_step<'gcx>(cx: &impl Context<'gcx>, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
+    let hir = cx.hir_of(id)?;
+    let next = match hir {
+        HirNode::Expr(expr) => match expr.kind {
+            hir::ExprKind::Unary(op, target_id) => {
+                let target_id = cx.resolve_node(target_id, env)?;
+                let current_value = cx.constant_value_of(target_id, env)?;
+                let next_value = match current_value.kind {
+                    ValueKind::Int(ref v, ..) => match op {
+                        hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
+                        hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
+                        _ => None,
+                    }
+                    .map(|v| value::make_int(current_value.ty, v)),
+                    _ => unreachable!(),
+                };
+                next_value.map(|v| (target_id, v))
+            }
+            _ => None,
+        },
         _ => None,
+    };
+    match next {
+        Some((target_id, next_value)) => {
+            let mut env_data = cx.param_env_data(env).clone();
+            env_data.set_value(target_id, cx.intern_value(next_value));
+            Ok(cx.intern_param_env(env_data))
+        }
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} is not a valid genvar iteration step",
+                    hir.desc_full()
+                ))
+                .span(hir.human_span()),
+            );
+            Err(())
+        }
     }
 }
 
-/// Determine the hierarchical rib of a node.
+/// Determine which block of an if- or case-generate statement is instantiated.
 ///
-/// This will return a rib containing the hierarchical names exposed by a node.
-pub(crate) fn hierarchical_rib<'gcx>(
+/// Returns `None` if no block is instantiated, e.g. if there is no `else`
+/// branch or `default` item.
+pub(crate) fn selected_gen_block<'gcx>(
     cx: &impl Context<'gcx>,
-    node_id: NodeId,
-) -> Result<&'gcx Rib> {
-    let hir = cx.hir_of(node_id)?;
-    let mut names = HashMap::new();
-    let mut rib_id = match hir {
-        HirNode::Package(pkg) => Some(pkg.last_rib),
-        _ => panic!("{} has no hierarchical rib", hir.desc_full()),
-    };
-    while let Some(id) = rib_id {
-        let rib = cx.local_rib(id)?;
-        match rib.kind {
-            RibKind::Normal(name, def) => {
-                names.insert(name.value, def);
+    gen: &hir::Gen,
+    env: ParamEnv,
+) -> Result<Option<NodeId>> {
+    match gen.kind {
+        hir::GenKind::If {
+            cond,
+            main_body,
+            else_body,
+        } => {
+            if cx.constant_value_of(cond, env)?.is_false() {
+                Ok(else_body)
+            } else {
+                Ok(Some(main_body))
             }
-            RibKind::Module(ref defs) => names.extend(defs),
-            RibKind::Enum(ref defs) => names.extend(defs),
         }
-        rib_id = rib.parent;
-        if rib_id == Some(node_id) {
-            rib_id = None;
+        hir::GenKind::Case {
+            expr,
+            ref ways,
+            default,
+        } => {
+            let k = cx.constant_value_of(expr, env)?;
+            for &(ref exprs, body) in ways {
+                for &way_expr in exprs {
+                    let v = cx.constant_value_of(way_expr, env)?;
+                    let matches = match (k.get_int(), v.get_int()) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => k.kind == v.kind,
+                    };
+                    if matches {
+                        return Ok(Some(body));
+                    }
+                }
+            }
+            Ok(default)
         }
+        hir::GenKind::For { .. } => unreachable!("generate loops have no selected block"),
     }
-    let rib = Rib {
-        node: node_id,
-        parent: None,
-        kind: RibKind::Module(names),
-    };
-    Ok(cx.arena().alloc_rib(rib))
 }
 
-/// Resolve a name upwards through the ribs.
-///
-/// This is equivalent to performing regular scoped namespace lookup.
-pub(crate) fn resolve_upwards<'gcx>(
+/// Check that a declaration is not located within a block of an if- or
+/// case-generate statement that is not instantiated.
+pub(crate) fn check_gen_block_instantiated<'gcx>(
     cx: &impl Context<'gcx>,
-    name: Name,
-    start_at: NodeId,
-) -> Result<Option<NodeId>> {
-    if cx.sess().has_verbosity(Verbosity::NAMES) {
-        cx.emit(DiagBuilder2::note(format!("resolving `{}`", name)).span(cx.span(start_at)));
+    node_id: NodeId,
+    span: Span,
+    env: ParamEnv,
+) -> Result<()> {
+    let mut id = node_id;
+    while let Some(parent) = cx.parent_node_id(id) {
+        id = parent;
+        match cx.ast_of(id)? {
+            AstNode::GenBlock(..) => (),
+            AstNode::Module(..) | AstNode::Interface(..) => break,
+            _ => continue,
+        }
+        let gen_id = cx.parent_node_id(id).unwrap();
+        let gen = match cx.hir_of(gen_id)? {
+            HirNode::Gen(x) => x,
+            _ => continue,
+        };
+        if selected_gen_block(cx, gen, env)? != Some(id) {
+            let block = cx.hir_of(id)?;
+            cx.emit(
+                DiagBuilder2::error(format!("{} is not instantiated", block.desc_full()))
+                    .span(span)
+                    .add_note(format!(
+                        "{} declared here:",
+                        cx.hir_of(node_id)?.desc_full()
+                    ))
+                    .span(cx.span(node_id)),
+            );
+            return Err(());
+        }
     }
-    let mut next_id = Some(start_at);
-    while let Some(rib_id) = next_id {
-        if cx.sess().has_verbosity(Verbosity::NAMES) {
-            cx.emit(DiagBuilder2::note(format!("resolving `{}` here", name)).span(cx.span(rib_id)));
+    Ok(())
+}
+
+/// A declaration accessed through a hierarchical name, such as
+/// `u_core.state` or `top.u_core.u_fifo.count`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HierName {
+    /// The node id uniquely identifying the path.
+    pub id: NodeId,
+    /// The module named at the start of an upward reference, such as `top`
+    /// in `top.u_core.state`. This is `None` for downward references, which
+    /// start at an instance visible in the current scope.
+    pub root: Option<NodeId>,
+    /// The module instances along the path, outermost first.
+    pub insts: Vec<NodeId>,
+    /// The variable, net, port, or parameter the path refers to.
+    pub target: NodeId,
+}
+
+impl HierName {
+    /// Check whether the name refers to a declaration in the module where the
+    /// name is used, such as `top.x` within module `top`.
+    pub fn is_local(&self) -> bool {
+        self.root.is_none() && self.insts.is_empty()
+    }
+}
+
+/// Resolve a field access expression to a hierarchical name.
+///
+/// The path must start with a module instance visible in the current scope,
+/// or with the name of a module for upward references (IEEE 1800-2017
+/// §23.8). All subsequent names but the last must refer to module instances.
+/// Upward references that start at the module enclosing the expression are
+/// treated as downward references.
+///
+/// Returns `None` if the expression is not a hierarchical name.
+pub(crate) fn resolve_hier_name<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+) -> Result<Option<Arc<HierName>>> {
+    // Flatten the chain of field accesses.
+    let mut names = vec![];
+    let mut id = node_id;
+    loop {
+        match cx.hir_of(id)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Field(target, name),
+                ..
+            }) => {
+                names.push(*name);
+                id = *target;
+            }
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Ident(..),
+                ..
+            }) => break,
+            _ => return Ok(None),
         }
-        let rib = cx.local_rib(rib_id)?;
-        if let id @ Some(_) = rib.get(name) {
-            return Ok(id);
+    }
+    if names.is_empty() {
+        return Ok(None);
+    }
+    names.reverse();
+
+    // Determine where the path starts.
+    let binding = cx.resolve_node(id, cx.default_param_env())?;
+    let (mut root, mut module, mut insts) = match cx.hir_of(binding)? {
+        HirNode::Module(..) => (Some(binding), binding, vec![]),
+        HirNode::Inst(..) => match inst_module(cx, binding)? {
+            Some(module) => (None, module, vec![binding]),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    if root.is_some() && root == enclosing_module(cx, node_id) {
+        root = None;
+    }
+
+    // Descend through the instances.
+    let (leaf, path) = names.split_last().unwrap();
+    for &name in path {
+        let inst = cx.resolve_downwards_or_error(name, module)?;
+        let child = match cx.hir_of(inst)? {
+            HirNode::Inst(..) => inst_module(cx, inst)?,
+            _ => None,
+        };
+        match child {
+            Some(child) => {
+                insts.push(inst);
+                module = child;
+            }
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} is not a module instance",
+                        cx.hir_of(inst)?.desc_full()
+                    ))
+                    .span(name.span)
+                    .add_note("hierarchical names may only descend through module instances"),
+                );
+                return Err(());
+            }
         }
-        next_id = rib.parent;
     }
-    if let m @ Some(_) = cx.gcx().find_module(name) {
-        return Ok(m);
+    let target = cx.resolve_downwards_or_error(*leaf, module)?;
+    match cx.hir_of(target)? {
+        HirNode::VarDecl(..) | HirNode::Port(..) | HirNode::ValueParam(..) => (),
+        hir => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be accessed through a hierarchical name",
+                    hir.desc_full()
+                ))
+                .span(leaf.span)
+                .add_note(format!("{} declared here:", hir.desc_full()))
+                .span(hir.human_span()),
+            );
+            return Err(());
+        }
     }
-    if let p @ Some(_) = cx.gcx().find_package(name) {
-        return Ok(p);
+    Ok(Some(cx.intern_hier_name(
+        root,
+        insts,
+        target,
+        cx.span(node_id),
+    )))
+}
+
+/// Determine the parameter environment of the module a hierarchical name
+/// points into.
+///
+/// Downward references start out in the environment `env` of the scope where
+/// the name is used. Upward references start out in the default environment
+/// of the module they name.
+pub(crate) fn hier_name_env<'gcx>(
+    cx: &impl Context<'gcx>,
+    hier: &HierName,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    let mut env = match hier.root {
+        Some(_) => cx.default_param_env(),
+        None => env,
+    };
+    for &inst in &hier.insts {
+        env = inst_param_env(cx, inst, env)?;
     }
-    Ok(None)
+    Ok(env)
 }
 
-/// Resolve a name downwards.
+/// Determine the module instantiated by an instance.
 ///
-/// This is equivalent to performing a hierarchical name lookup.
-pub(crate) fn resolve_downwards<'gcx>(
+/// Returns `None` if the instance refers to an interface or unknown module.
+pub(crate) fn inst_module<'gcx>(
     cx: &impl Context<'gcx>,
-    name: Name,
-    start_at: NodeId,
+    inst_id: NodeId,
 ) -> Result<Option<NodeId>> {
-    let rib = cx.hierarchical_rib(start_at)?;
-    Ok(match rib.get(name) {
-        Some(x) => Some(x),
-        None => {
-            debug!("name `{}` not found in {:#?}", name, rib);
-            None
-        }
-    })
+    let inst = match cx.hir_of(inst_id)? {
+        HirNode::Inst(x) => x,
+        _ => unreachable!(),
+    };
+    let name = match cx.hir_of(inst.target)? {
+        HirNode::InstTarget(x) => x.name.value,
+        _ => unreachable!(),
+    };
+    Ok(cx.gcx().find_module(name))
 }
 
-/// Resolve a node to its target.
-pub(crate) fn resolve_node<'gcx>(
+/// Determine the parameter environment of a module instance, given the
+/// environment `env` of the instantiating scope.
+pub(crate) fn inst_param_env<'gcx>(
     cx: &impl Context<'gcx>,
-    node_id: NodeId,
+    inst_id: NodeId,
     env: ParamEnv,
-) -> Result<NodeId> {
-    let hir = cx.hir_of(node_id)?;
-    match hir {
-        HirNode::Expr(expr) => match expr.kind {
-            hir::ExprKind::Ident(ident) => return cx.resolve_upwards_or_error(ident, node_id),
-            hir::ExprKind::Scope(scope_id, name) => {
-                let within = cx.resolve_node(scope_id, env)?;
-                return cx.resolve_downwards_or_error(name, within);
-            }
-            _ => (),
-        },
-        HirNode::Type(ty) => match ty.kind {
-            hir::TypeKind::Named(name) => return cx.resolve_upwards_or_error(name, node_id),
-            hir::TypeKind::Scope(scope_id, name) => {
-                let within = cx.resolve_node(scope_id, env)?;
-                return cx.resolve_downwards_or_error(name, within);
-            }
-            _ => (),
-        },
-        _ => (),
+) -> Result<ParamEnv> {
+    let inst = match cx.hir_of(inst_id)? {
+        HirNode::Inst(x) => x,
+        _ => unreachable!(),
+    };
+    let target = match cx.hir_of(inst.target)? {
+        HirNode::InstTarget(x) => x,
+        _ => unreachable!(),
+    };
+    let module = match inst_module(cx, inst_id)? {
+        Some(x) => x,
+        None => unreachable!("{:?} does not instantiate a module", inst_id),
+    };
+    cx.param_env(crate::ParamEnvSource::ModuleInst {
+        module,
+        inst: inst_id,
+        env,
+        pos: &target.pos_params,
+        named: &target.named_params,
+    })
+}
+
+/// Find the module a node is located in.
+pub(crate) fn enclosing_module<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Option<NodeId> {
+    let mut id = node_id;
+    loop {
+        match cx.ast_of(id) {
+            Ok(AstNode::Module(..)) => return Some(id),
+            _ => id = cx.parent_node_id(id)?,
+        }
     }
-    error!("{:#?}", hir);
-    cx.emit(DiagBuilder2::bug("cannot resolve node").span(hir.human_span()));
-    Err(())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StructDef {
+    pub kind: ast::StructKind,
     pub packed: bool,
     pub fields: Vec<StructField>,
 }
 
+impl StructDef {
+    /// Check whether this is a union, tagged or not.
+    pub fn is_union(&self) -> bool {
+        self.kind != ast::StructKind::Struct
+    }
+
+    /// Check whether this is a tagged union.
+    pub fn is_tagged(&self) -> bool {
+        self.kind == ast::StructKind::TaggedUnion
+    }
+
+    /// The number of bits used to store the tag of a tagged union.
+    ///
+    /// The tag is placed above the member bits and is zero bits wide for
+    /// untagged unions and structs.
+    pub fn tag_width(&self) -> usize {
+        if !self.is_tagged() {
+            return 0;
+        }
+        let mut width = 0;
+        while (1 << width) < self.fields.len() {
+            width += 1;
+        }
+        width
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StructField {
     pub name: Spanned<Name>,
@@ -338,8 +2156,16 @@ pub(crate) fn struct_def<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resu
             _ => None,
         })
         .collect();
+    let (kind, packed) = match cx.ast_of(node_id)? {
+        AstNode::Type(&ast::Type {
+            data: ast::StructType { kind, packed, .. },
+            ..
+        }) => (kind, packed),
+        _ => (ast::StructKind::Struct, true),
+    };
     Ok(Arc::new(StructDef {
-        packed: true,
+        kind,
+        packed,
         fields,
     }))
 }
@@ -398,6 +2224,52 @@ pub(crate) fn resolve_field_access<'gcx>(
     Ok((struct_def, index, struct_fields[index]))
 }
 
+/// Resolve the member of a tagged union expression.
+///
+/// Returns the tagged union definition and the index of the member.
+pub(crate) fn resolve_tagged_member<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<(NodeId, usize)> {
+    let hir = match cx.hir_of(node_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    let name = match hir.kind {
+        hir::ExprKind::Tagged(name, _) => name,
+        _ => unreachable!(),
+    };
+    let ty = cx.type_of(node_id, env)?;
+    if ty.is_error() {
+        return Err(());
+    }
+    let def_id = match ty.get_struct_def() {
+        Some(id) if cx.struct_def(id)?.is_tagged() => id,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` is not a tagged union", ty))
+                    .span(hir.span())
+                    .add_note(format!("{} requires a tagged union type", hir.desc_full())),
+            );
+            return Err(());
+        }
+    };
+    let def = cx.struct_def(def_id)?;
+    match def.fields.iter().position(|f| f.name.value == name.value) {
+        Some(index) => Ok((def_id, index)),
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` has no member `{}`", ty, name))
+                    .span(name.span())
+                    .add_note("The union is defined here:")
+                    .span(cx.span(def_id)),
+            );
+            Err(())
+        }
+    }
+}
+
 /// Resolve the fields in an assignment pattern.
 pub(crate) fn resolve_pattern<'gcx>(
     cx: &impl Context<'gcx>,
@@ -548,18 +2420,72 @@ pub(crate) fn resolve_pattern<'gcx>(
             }
             return Ok(assigned);
         }
-        &TypeKind::Named(_, _, &TypeKind::PackedArray(length, elem_ty))
-        | &TypeKind::PackedArray(length, elem_ty) => {
-            trace!("array pattern: {} elements of type {:?}", length, elem_ty);
-            cx.emit(
-                DiagBuilder2::error(format!(
-                    "`{}` cannot be accessed by a {}",
-                    ty,
-                    hir.desc_full()
-                ))
-                .span(hir.span),
+        _ if ty.is_array() => {
+            let range = ty.get_range().unwrap();
+            trace!(
+                "array pattern: {} elements of type {:?}",
+                range.size,
+                ty.get_array_element()
             );
-            return Err(());
+            let exprs = match hir.kind {
+                hir::ExprKind::PositionalPattern(ref exprs) => exprs.clone(),
+                hir::ExprKind::RepeatPattern(count, ref exprs) => {
+                    let count = match cx.constant_int_value_of(count, env)?.to_usize() {
+                        Some(c) => c,
+                        None => {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "repetition count of {} is out of bounds",
+                                    hir.desc_full()
+                                ))
+                                .span(cx.span(count)),
+                            );
+                            return Err(());
+                        }
+                    };
+                    exprs
+                        .iter()
+                        .cloned()
+                        .cycle()
+                        .take(exprs.len() * count)
+                        .collect()
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be accessed by a {}",
+                            ty,
+                            hir.desc_full()
+                        ))
+                        .span(hir.span),
+                    );
+                    return Err(());
+                }
+            };
+            if exprs.len() != range.size {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} has {} elements, but `{}` has {}",
+                        hir.desc_full(),
+                        exprs.len(),
+                        ty,
+                        range.size
+                    ))
+                    .span(hir.span),
+                );
+                return Err(());
+            }
+
+            // The first element of the pattern corresponds to the left bound
+            // of the array's range.
+            return Ok(exprs
+                .into_iter()
+                .enumerate()
+                .map(|(i, expr_id)| match range.dir {
+                    RangeDir::Up => (i, expr_id),
+                    RangeDir::Down => (range.size - 1 - i, expr_id),
+                })
+                .collect());
         }
         _ => {
             cx.emit(
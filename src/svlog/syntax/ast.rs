@@ -4,7 +4,7 @@
 
 use super::token::{Lit, Op};
 use moore_common::name::Name;
-use moore_common::source::{Span, Spanned};
+use moore_common::source::{Span, Spanned, INVALID_SPAN};
 use moore_common::util::{HasDesc, HasSpan};
 use std::fmt;
 
@@ -57,6 +57,23 @@ pub struct Root {
     pub items: Vec<Item>,
 }
 
+impl HasSpan for Root {
+    fn span(&self) -> Span {
+        let mut spans = self.items.iter().map(|item| item.span());
+        let mut span = spans.next().unwrap_or(INVALID_SPAN);
+        for sp in spans {
+            span.expand(sp);
+        }
+        span
+    }
+}
+
+impl HasDesc for Root {
+    fn desc(&self) -> &'static str {
+        "compilation unit"
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum Item {
     Module(ModDecl),
@@ -64,9 +81,10 @@ pub enum Item {
     Package(PackageDecl),
     Class(ClassDecl),
     Item(HierarchyItem),
-    // Program(ProgramDecl),
-    // Bind(BindDirective),
-    // Config(ConfigDecl),
+    Program(ProgramDecl),
+    Checker(CheckerDecl),
+    Bind(BindDirective),
+    Config(ConfigDecl),
 }
 
 impl HasSpan for Item {
@@ -77,13 +95,22 @@ impl HasSpan for Item {
             Item::Package(ref decl) => decl.span,
             Item::Class(ref decl) => decl.span,
             Item::Item(ref item) => item.span(),
+            Item::Program(ref decl) => decl.span,
+            Item::Checker(ref decl) => decl.span,
+            Item::Bind(ref decl) => decl.span,
+            Item::Config(ref decl) => decl.span,
         }
     }
 
     fn human_span(&self) -> Span {
         match *self {
             Item::Module(ref decl) => decl.human_span(),
+            Item::Interface(ref decl) => decl.human_span(),
             Item::Item(ref item) => item.human_span(),
+            Item::Program(ref decl) => decl.human_span(),
+            Item::Checker(ref decl) => decl.human_span(),
+            Item::Bind(ref decl) => decl.human_span(),
+            Item::Config(ref decl) => decl.human_span(),
             _ => self.span(),
         }
     }
@@ -93,17 +120,26 @@ impl HasDesc for Item {
     fn desc(&self) -> &'static str {
         match *self {
             Item::Module(ref decl) => decl.desc(),
-            Item::Interface(ref decl) => "interface declaration",
+            Item::Interface(ref decl) => decl.desc(),
             Item::Package(ref decl) => "package declaration",
             Item::Class(ref decl) => "class declaration",
             Item::Item(ref item) => item.desc(),
+            Item::Program(ref decl) => decl.desc(),
+            Item::Checker(ref decl) => decl.desc(),
+            Item::Bind(ref decl) => decl.desc(),
+            Item::Config(ref decl) => decl.desc(),
         }
     }
 
     fn desc_full(&self) -> String {
         match *self {
             Item::Module(ref decl) => decl.desc_full(),
+            Item::Interface(ref decl) => decl.desc_full(),
             Item::Item(ref item) => item.desc_full(),
+            Item::Program(ref decl) => decl.desc_full(),
+            Item::Checker(ref decl) => decl.desc_full(),
+            Item::Bind(ref decl) => decl.desc_full(),
+            Item::Config(ref decl) => decl.desc_full(),
             _ => self.desc().into(),
         }
     }
@@ -119,6 +155,7 @@ impl Item {
 pub struct ModDecl {
     pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub lifetime: Lifetime, // default static
     pub name: Name,
     pub name_span: Span,
@@ -151,6 +188,7 @@ impl HasDesc for ModDecl {
 pub struct IntfDecl {
     pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub lifetime: Lifetime, // default static
     pub name: Name,
     pub name_span: Span,
@@ -159,10 +197,31 @@ pub struct IntfDecl {
     pub items: Vec<HierarchyItem>,
 }
 
+impl HasSpan for IntfDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl HasDesc for IntfDecl {
+    fn desc(&self) -> &'static str {
+        "interface declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("interface `{}`", self.name)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct PackageDecl {
     pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub lifetime: Lifetime,
     pub name: Name,
     pub name_span: Span,
@@ -190,6 +249,202 @@ impl HasDesc for PackageDecl {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ProgramDecl {
+    pub id: NodeId,
+    pub span: Span,
+    pub attrs: Vec<AttrInst>,
+    pub lifetime: Lifetime, // default static
+    pub name: Name,
+    pub name_span: Span,
+    pub params: Vec<ParamDecl>,
+    pub ports: Vec<Port>,
+    pub items: Vec<HierarchyItem>,
+}
+
+impl HasSpan for ProgramDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl HasDesc for ProgramDecl {
+    fn desc(&self) -> &'static str {
+        "program declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("program `{}`", self.name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct CheckerDecl {
+    pub id: NodeId,
+    pub span: Span,
+    pub attrs: Vec<AttrInst>,
+    pub name: Name,
+    pub name_span: Span,
+    pub ports: Vec<Port>,
+    pub items: Vec<HierarchyItem>,
+}
+
+impl HasSpan for CheckerDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl HasDesc for CheckerDecl {
+    fn desc(&self) -> &'static str {
+        "checker declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("checker `{}`", self.name)
+    }
+}
+
+/// A bind directive.
+///
+/// ```text
+/// "bind" target_scope [":" target_instance {"," target_instance}] instantiation
+/// "bind" target_instance instantiation
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct BindDirective {
+    pub id: NodeId,
+    pub span: Span,
+    /// The target module or interface, or the hierarchical name of the target
+    /// instance.
+    pub target: Vec<Identifier>,
+    /// The instances of the target module the directive is limited to.
+    pub target_insts: Vec<Vec<Identifier>>,
+    /// The instantiation to be added to the target.
+    pub inst: Inst,
+}
+
+impl BindDirective {
+    /// Get the name of the target module, unless the directive targets a
+    /// specific instance.
+    pub fn target_module(&self) -> Option<Name> {
+        match self.target.as_slice() {
+            [name] if self.target_insts.is_empty() => Some(name.name),
+            _ => None,
+        }
+    }
+}
+
+impl HasSpan for BindDirective {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.target
+            .iter()
+            .fold(self.target[0].span, |sp, id| Span::union(sp, id.span))
+    }
+}
+
+impl HasDesc for BindDirective {
+    fn desc(&self) -> &'static str {
+        "bind directive"
+    }
+
+    fn desc_full(&self) -> String {
+        let target: Vec<_> = self.target.iter().map(|id| id.name.to_string()).collect();
+        format!("bind directive for `{}`", target.join("."))
+    }
+}
+
+/// A configuration.
+///
+/// ```text
+/// "config" name ";" {localparam ";"} "design" {[lib "."] cell} ";" {rule} "endconfig"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ConfigDecl {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Name,
+    pub name_span: Span,
+    pub params: Vec<ParamDecl>,
+    pub design: Vec<ConfigCell>,
+    pub rules: Vec<ConfigRule>,
+}
+
+impl HasSpan for ConfigDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl HasDesc for ConfigDecl {
+    fn desc(&self) -> &'static str {
+        "config declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("config `{}`", self.name)
+    }
+}
+
+/// A cell reference in a configuration, optionally qualified with a library.
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ConfigCell {
+    pub span: Span,
+    pub lib: Option<Identifier>,
+    pub cell: Identifier,
+}
+
+/// A rule in a configuration.
+///
+/// ```text
+/// "default" liblist_clause ";"
+/// "instance" hierarchical_name (liblist_clause | use_clause) ";"
+/// "cell" [lib "."] cell (liblist_clause | use_clause) ";"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ConfigRule {
+    pub span: Span,
+    pub clause: ConfigClause,
+    pub action: ConfigAction,
+}
+
+/// The part of the design a configuration rule applies to.
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum ConfigClause {
+    Default,
+    Instance(Vec<Identifier>),
+    Cell(ConfigCell),
+}
+
+/// What a configuration rule does.
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum ConfigAction {
+    /// Search the given libraries, in order.
+    Liblist(Vec<Identifier>),
+    /// Use a specific cell, parameter values, or configuration.
+    Use {
+        cell: Option<ConfigCell>,
+        params: Vec<ParamAssignment>,
+        config: bool,
+    },
+}
+
 /// Lifetime specifier for variables, tasks, and functions. Defaults to static.
 #[derive(Debug, PartialEq, Eq, Clone, RustcEncodable, RustcDecodable)]
 pub enum Lifetime {
@@ -212,7 +467,10 @@ pub struct Timeunit {
 #[derive(Debug, PartialEq, Eq, Clone, RustcEncodable, RustcDecodable)]
 pub enum HierarchyItem {
     Dummy,
+    /// An item that could not be parsed.
+    Error(Span),
     ImportDecl(ImportDecl),
+    ExportDecl(ExportDecl),
     LocalparamDecl(()),
     ParameterDecl(()),
     ParamDecl(ParamDecl),
@@ -223,12 +481,15 @@ pub enum HierarchyItem {
     Procedure(Procedure),
     SubroutineDecl(SubroutineDecl),
     ContAssign(ContAssign),
+    Defparam(Defparam),
     GenvarDecl(Vec<GenvarDecl>),
     GenerateRegion(Span, Vec<HierarchyItem>),
     GenerateFor(GenerateFor),
     GenerateIf(GenerateIf),
     GenerateCase(GenerateCase),
     Assertion(Assertion),
+    SeqDecl(SeqDecl),
+    PropDecl(PropDecl),
     NetDecl(NetDecl),
     VarDecl(VarDecl),
     Inst(Inst),
@@ -238,16 +499,27 @@ impl HasSpan for HierarchyItem {
     fn span(&self) -> Span {
         match *self {
             HierarchyItem::ImportDecl(ref decl) => decl.span,
+            HierarchyItem::ExportDecl(ref decl) => decl.span,
             HierarchyItem::ParamDecl(ref decl) => decl.span,
+            HierarchyItem::Typedef(ref decl) => decl.span,
+            HierarchyItem::ContAssign(ref assign) => assign.span,
+            HierarchyItem::GenerateRegion(span, _) => span,
+            HierarchyItem::GenerateFor(ref gen) => gen.span,
+            HierarchyItem::GenerateIf(ref gen) => gen.span,
+            HierarchyItem::GenerateCase(ref gen) => gen.span,
             HierarchyItem::ModportDecl(ref decl) => decl.span,
             HierarchyItem::ClassDecl(ref decl) => decl.span,
             HierarchyItem::PortDecl(ref decl) => decl.span,
             HierarchyItem::Procedure(ref prc) => prc.span,
             HierarchyItem::SubroutineDecl(ref decl) => decl.span,
             HierarchyItem::Assertion(ref assertion) => assertion.span,
+            HierarchyItem::SeqDecl(ref decl) => decl.span,
+            HierarchyItem::PropDecl(ref decl) => decl.span,
+            HierarchyItem::Defparam(ref decl) => decl.span,
             HierarchyItem::NetDecl(ref decl) => decl.span,
             HierarchyItem::VarDecl(ref decl) => decl.span,
             HierarchyItem::Inst(ref inst) => inst.span,
+            HierarchyItem::Error(span) => span,
             _ => unimplemented!(), // TODO remove this and have the compiler complain
         }
     }
@@ -257,6 +529,7 @@ impl HasDesc for HierarchyItem {
     fn desc(&self) -> &'static str {
         match *self {
             HierarchyItem::ImportDecl(ref decl) => "import declaration",
+            HierarchyItem::ExportDecl(ref decl) => "export declaration",
             HierarchyItem::ParamDecl(ref decl) => "parameter declaration",
             HierarchyItem::ModportDecl(ref decl) => "modport declaration",
             HierarchyItem::ClassDecl(ref decl) => "class declaration",
@@ -264,9 +537,13 @@ impl HasDesc for HierarchyItem {
             HierarchyItem::Procedure(ref prc) => "procedure declaration",
             HierarchyItem::SubroutineDecl(ref decl) => "subroutine declaration",
             HierarchyItem::Assertion(ref assertion) => "assertion",
+            HierarchyItem::SeqDecl(ref decl) => "sequence declaration",
+            HierarchyItem::PropDecl(ref decl) => "property declaration",
+            HierarchyItem::Defparam(ref decl) => "defparam statement",
             HierarchyItem::NetDecl(ref decl) => "net declaration",
             HierarchyItem::VarDecl(ref decl) => "variable declaration",
             HierarchyItem::Inst(ref inst) => "instantiation",
+            HierarchyItem::Error(_) => "erroneous item",
             _ => unimplemented!(), // TODO remove this and have the compiler complain
         }
     }
@@ -364,9 +641,9 @@ pub enum TypeSign {
 pub enum TypeDim {
     Expr(Expr),
     Range(Expr, Expr),
-    Queue,
+    Queue(Option<Expr>),
     Unsized,
-    Associative,
+    Associative(Option<Type>),
 }
 
 impl HasDesc for TypeDim {
@@ -380,9 +657,11 @@ impl HasDesc for TypeDim {
             TypeDim::Range(ref lhs, ref rhs) => {
                 format!("`[{}:{}]`", lhs.span().extract(), rhs.span().extract())
             }
-            TypeDim::Queue => format!("`[$]`"),
+            TypeDim::Queue(None) => format!("`[$]`"),
+            TypeDim::Queue(Some(ref max)) => format!("`[$:{}]`", max.span().extract()),
             TypeDim::Unsized => format!("`[]`"),
-            TypeDim::Associative => format!("associative type dimension"),
+            TypeDim::Associative(None) => format!("`[*]`"),
+            TypeDim::Associative(Some(ref ty)) => format!("`[{}]`", ty.span().extract()),
         }
     }
 }
@@ -501,6 +780,53 @@ impl HasDesc for Port {
     }
 }
 
+impl Port {
+    /// Check whether this is a non-ANSI style port.
+    ///
+    /// Non-ANSI ports only name the port and optionally the expression it
+    /// connects to, e.g. `a`, `.a(b)`, or `{a,b}`. Their direction and type
+    /// are given by port declarations in the module body. The first port of a
+    /// port list determines whether the entire list is ANSI or non-ANSI.
+    pub fn is_nonansi(&self) -> bool {
+        match *self {
+            Port::Intf { .. } => false,
+            Port::Explicit { dir, .. } => dir.is_none(),
+            Port::Named {
+                dir,
+                kind,
+                ref ty,
+                ref expr,
+                ..
+            } => {
+                dir.is_none()
+                    && kind.is_none()
+                    && ty.data == ImplicitType
+                    && ty.sign == TypeSign::None
+                    && ty.dims.is_empty()
+                    && expr.is_none()
+            }
+            Port::Implicit(..) => true,
+        }
+    }
+
+    /// Get the name by which the port can be connected.
+    ///
+    /// Implicit ports only have a name if their expression is a single
+    /// identifier, e.g. `a` as opposed to `a[3:0]` or `{a,b}`.
+    pub fn name(&self) -> Option<Identifier> {
+        match *self {
+            Port::Intf { name, .. } | Port::Explicit { name, .. } | Port::Named { name, .. } => {
+                Some(name)
+            }
+            Port::Implicit(Expr {
+                data: IdentExpr(name),
+                ..
+            }) => Some(name),
+            Port::Implicit(..) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct PortDecl {
     pub span: Span,
@@ -544,6 +870,7 @@ pub enum NetType {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct Procedure {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub kind: ProcedureKind,
     pub stmt: Stmt,
 }
@@ -573,6 +900,7 @@ pub enum ProcedureKind {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct Stmt {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub label: Option<Name>,
     pub data: StmtData,
 }
@@ -592,6 +920,8 @@ impl HasDesc for Stmt {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum StmtData {
     NullStmt,
+    /// A statement that could not be parsed.
+    ErrorStmt,
     SequentialBlock(Vec<Stmt>),
     ParallelBlock(Vec<Stmt>, JoinKind),
     IfStmt {
@@ -637,12 +967,15 @@ pub enum StmtData {
     WaitForkStmt,
     DisableForkStmt,
     DisableStmt(Name),
+    /// An event trigger such as `-> e;`.
+    EventTriggerStmt(Expr),
 }
 
 impl Stmt {
     pub fn new_null(span: Span) -> Stmt {
         Stmt {
             span: span,
+            attrs: vec![],
             label: None,
             data: NullStmt,
         }
@@ -731,6 +1064,7 @@ pub enum AssignOp {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct VarDecl {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub konst: bool,
     pub var: bool,
     pub lifetime: Option<Lifetime>,
@@ -833,6 +1167,8 @@ impl HasDesc for Expr {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum ExprData {
     DummyExpr,
+    /// An expression that could not be parsed.
+    ErrorExpr,
     LiteralExpr(Lit),
     IdentExpr(Identifier),
     SysIdentExpr(Identifier),
@@ -867,6 +1203,12 @@ pub enum ExprData {
     ClassNewExpr(Option<Box<Expr>>),
     ArrayNewExpr(Box<Expr>, Option<Box<Expr>>),
     EmptyQueueExpr,
+    /// The `this` handle of the enclosing class.
+    ThisExpr,
+    /// The `super` handle of the enclosing class.
+    SuperExpr,
+    /// The `null` class handle.
+    NullExpr,
     StreamConcatExpr {
         slice: Option<StreamConcatSlice>,
         exprs: Vec<StreamExpr>,
@@ -891,8 +1233,18 @@ pub enum ExprData {
     },
     PatternExpr(Vec<PatternField>),
     InsideExpr(Box<Expr>, Vec<ValueRange>),
+    /// A call to `randomize` with inline constraints, such as
+    /// `obj.randomize() with { a < b; }`.
+    RandomizeWithExpr(Box<Expr>, Vec<ConstraintItem>),
     CastExpr(Type, Box<Expr>),
     CastSizeExpr(Box<Expr>, Box<Expr>),
+    /// A tagged union expression or pattern such as `tagged Valid 42`.
+    TaggedUnionExpr {
+        member: Identifier,
+        expr: Option<Box<Expr>>,
+    },
+    /// A pattern variable `.name`, or the wildcard pattern `.*`.
+    PatternVarExpr(Option<Identifier>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -946,6 +1298,49 @@ pub enum RangeMode {
     RelativeUp,
 }
 
+/// An attribute instance.
+///
+/// ```text
+/// "(*" attr_spec {"," attr_spec} "*)"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct AttrInst {
+    pub span: Span,
+    pub specs: Vec<AttrSpec>,
+}
+
+/// A single attribute within an attribute instance.
+///
+/// ```text
+/// ident ["=" const_expr]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct AttrSpec {
+    pub span: Span,
+    pub name: Identifier,
+    pub value: Option<Expr>,
+}
+
+impl HasSpan for AttrSpec {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for AttrSpec {
+    fn desc(&self) -> &'static str {
+        "attribute"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("attribute `{}`", self.name.name)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct Identifier {
     pub id: NodeId,
@@ -1027,6 +1422,26 @@ pub struct ClassDecl {
     pub items: Vec<ClassItem>,
 }
 
+impl HasSpan for ClassDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for ClassDecl {
+    fn desc(&self) -> &'static str {
+        "class"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("class `{}`", self.name.name)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct ClassItem {
     pub span: Span,
@@ -1048,7 +1463,7 @@ pub enum ClassItemQualifier {
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum ClassItemData {
-    Property,
+    Property(VarDecl),
     SubroutineDecl(SubroutineDecl),
     ExternSubroutine(SubroutinePrototype),
     Constraint(Constraint),
@@ -1103,6 +1518,26 @@ pub struct Constraint {
     pub items: Vec<ConstraintItem>,
 }
 
+impl HasSpan for Constraint {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl HasDesc for Constraint {
+    fn desc(&self) -> &'static str {
+        "constraint"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("constraint `{}`", self.name)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum ConstraintKind {
     Decl,
@@ -1119,11 +1554,39 @@ pub struct ConstraintItem {
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum ConstraintItemData {
-    If,
-    Foreach,
+    /// A set of constraints enclosed in braces, such as `{ a < b; c > d; }`.
+    Set(Vec<ConstraintItem>),
+    /// A conditional constraint `if (expr) ... else ...`.
+    If(Expr, Box<ConstraintItem>, Option<Box<ConstraintItem>>),
+    /// An implication `expr -> ...`.
+    Implication(Expr, Box<ConstraintItem>),
+    /// An iteration over the elements of an array, such as
+    /// `foreach (a[i]) ...`.
+    Foreach(Expr, Vec<Option<Identifier>>, Box<ConstraintItem>),
+    /// A distribution `expr dist { ... }`.
+    Dist(Expr, Vec<DistItem>),
+    /// A variable ordering `solve a, b before c, d`.
+    Solve(Vec<Expr>, Vec<Expr>),
     Expr(Expr),
 }
 
+/// A value or range of a distribution, together with its weight.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct DistItem {
+    pub span: Span,
+    pub range: ValueRange,
+    pub weight: Option<(DistWeight, Expr)>,
+}
+
+/// How the weight of a distribution item is applied to its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum DistWeight {
+    /// The weight `:=` applies to each value in the range.
+    PerValue,
+    /// The weight `:/` is divided among the values in the range.
+    PerRange,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct SubroutineDecl {
     pub span: Span,
@@ -1131,6 +1594,29 @@ pub struct SubroutineDecl {
     pub items: Vec<SubroutineItem>,
 }
 
+impl HasSpan for SubroutineDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.prototype.name.span
+    }
+}
+
+impl HasDesc for SubroutineDecl {
+    fn desc(&self) -> &'static str {
+        match self.prototype.kind {
+            SubroutineKind::Func => "function",
+            SubroutineKind::Task => "task",
+        }
+    }
+
+    fn desc_full(&self) -> String {
+        format!("{} `{}`", self.desc(), self.prototype.name.name)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct SubroutinePrototype {
     pub span: Span,
@@ -1138,6 +1624,21 @@ pub struct SubroutinePrototype {
     pub lifetime: Option<Lifetime>,
     pub name: Identifier,
     pub args: Vec<SubroutinePort>,
+    /// The return type of a function. `None` for tasks and constructors.
+    pub retty: Option<Type>,
+}
+
+impl HasDesc for SubroutinePrototype {
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            SubroutineKind::Func => "function",
+            SubroutineKind::Task => "task",
+        }
+    }
+
+    fn desc_full(&self) -> String {
+        format!("{} `{}`", self.desc(), self.name.name)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -1189,6 +1690,7 @@ pub enum SubroutinePortDir {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct NetDecl {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub net_type: NetType,
     pub strength: Option<NetStrength>,
     pub kind: NetKind,
@@ -1286,6 +1788,31 @@ impl HasDesc for ImportItem {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ExportDecl {
+    pub span: Span,
+    pub items: Vec<ExportItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ExportItem {
+    pub span: Span,
+    pub pkg: Option<Identifier>,  // None means `export *::*`
+    pub name: Option<Identifier>, // None means `export pkg::*`
+}
+
+impl HasSpan for ExportItem {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for ExportItem {
+    fn desc(&self) -> &'static str {
+        "export"
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct Assertion {
     pub span: Span,
@@ -1293,6 +1820,18 @@ pub struct Assertion {
     pub data: AssertionData,
 }
 
+impl HasSpan for Assertion {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for Assertion {
+    fn desc(&self) -> &'static str {
+        "assertion"
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum AssertionData {
     Immediate(BlockingAssertion),
@@ -1312,7 +1851,7 @@ pub enum ConcurrentAssertion {
     AssertProperty(PropSpec, AssertionActionBlock),
     AssumeProperty(PropSpec, AssertionActionBlock),
     CoverProperty(PropSpec, Stmt),
-    CoverSequence,
+    CoverSequence(PropSpec, Stmt),
     ExpectProperty(PropSpec, AssertionActionBlock),
     RestrictProperty(PropSpec),
 }
@@ -1333,6 +1872,10 @@ pub struct SeqExpr {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum SeqExprData {
     Expr(Expr, Option<SeqRep>),
+    /// A repeated sequence, e.g. `(a ##1 b)[*2]`.
+    Repeat(Box<SeqExpr>, SeqRep),
+    /// A cycle delay, e.g. `a ##[1:3] b` or `##1 b`.
+    Delay(Option<Box<SeqExpr>>, SeqDelay, Box<SeqExpr>),
     BinOp(SeqBinOp, Box<SeqExpr>, Box<SeqExpr>),
     Throughout(Expr, Box<SeqExpr>),
     Clocked(EventExpr, Box<SeqExpr>),
@@ -1340,11 +1883,28 @@ pub enum SeqExprData {
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum SeqRep {
-    Consec(Expr),    // [* expr]
-    ConsecStar,      // [*]
-    ConsecPlus,      // [+]
-    Nonconsec(Expr), // [= expr]
-    Goto(Expr),      // [-> expr]
+    Consec(SeqRange),    // [* range]
+    ConsecStar,          // [*]
+    ConsecPlus,          // [+]
+    Nonconsec(SeqRange), // [= range]
+    Goto(SeqRange),      // [-> range]
+}
+
+/// A cycle delay in a sequence.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum SeqDelay {
+    Range(SeqRange), // ##n, ##(expr), ##[range]
+    Star,            // ##[*]
+    Plus,            // ##[+]
+}
+
+/// A number of cycles in a sequence delay or repetition.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum SeqRange {
+    /// Exactly `n` cycles.
+    Single(Expr),
+    /// Between `m` and `n` cycles, where `$` indicates no upper bound.
+    Range(Expr, Option<Expr>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -1355,8 +1915,18 @@ pub enum SeqBinOp {
     Within,
 }
 
+/// A property specification.
+///
+/// ```text
+/// [clocking_event] ["disable" "iff" "(" expr ")"] property_expr
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
-pub struct PropSpec;
+pub struct PropSpec {
+    pub span: Span,
+    pub clock: Option<EventExpr>,
+    pub disable: Option<Expr>,
+    pub prop: PropExpr,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct PropExpr {
@@ -1404,9 +1974,115 @@ pub enum PropBinOp {
     SeqFollowNol,
 }
 
+/// A named sequence declaration.
+///
+/// ```text
+/// "sequence" name ["(" {port} ")"] ";" sequence_expr [";"] "endsequence" [":" name]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct SeqDecl {
+    pub span: Span,
+    pub name: Identifier,
+    pub ports: Vec<SeqPort>,
+    pub seq: SeqExpr,
+}
+
+impl HasSpan for SeqDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for SeqDecl {
+    fn desc(&self) -> &'static str {
+        "sequence declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("sequence `{}`", self.name.name)
+    }
+}
+
+/// A named property declaration.
+///
+/// ```text
+/// "property" name ["(" {port} ")"] ";" property_spec [";"] "endproperty" [":" name]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct PropDecl {
+    pub span: Span,
+    pub name: Identifier,
+    pub ports: Vec<SeqPort>,
+    pub prop: PropSpec,
+}
+
+impl HasSpan for PropDecl {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for PropDecl {
+    fn desc(&self) -> &'static str {
+        "property declaration"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("property `{}`", self.name.name)
+    }
+}
+
+/// A formal argument of a sequence or property declaration.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct SeqPort {
+    pub span: Span,
+    pub ty: SeqPortType,
+    pub name: Identifier,
+    pub default: Option<Expr>,
+}
+
+impl HasSpan for SeqPort {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for SeqPort {
+    fn desc(&self) -> &'static str {
+        "sequence argument"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("sequence argument `{}`", self.name.name)
+    }
+}
+
+/// The type of a sequence or property argument.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum SeqPortType {
+    Implicit,
+    Untyped,
+    Sequence,
+    Property,
+    Data(Type),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct Inst {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     /// The name of the module to instantiate.
     pub target: Identifier,
     /// The parameters in the module to be assigned.
@@ -1476,9 +2152,46 @@ pub struct ModportItem {
     pub ports: Vec<ModportPort>,
 }
 
+impl HasSpan for ModportItem {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.name.span
+    }
+}
+
+impl HasDesc for ModportItem {
+    fn desc(&self) -> &'static str {
+        "modport"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("modport `{}`", self.name.name)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub enum ModportPort {
-    Port,
+    /// A group of simple ports with a common direction, e.g. `input a, b`.
+    Simple {
+        span: Span,
+        dir: PortDir,
+        port: Vec<ModportSimplePort>,
+    },
+}
+
+/// A simple port in a modport.
+///
+/// ```text
+/// ident | "." ident "(" [expr] ")"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ModportSimplePort {
+    pub span: Span,
+    pub name: Identifier,
+    pub expr: Option<Expr>,
 }
 
 /// A parameter or localparam declaration.
@@ -1589,6 +2302,7 @@ impl HasDesc for ParamValueDecl {
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct ContAssign {
     pub span: Span,
+    pub attrs: Vec<AttrInst>,
     pub strength: Option<(DriveStrength, DriveStrength)>,
     pub delay: Option<Expr>,
     pub delay_control: Option<DelayControl>,
@@ -1607,6 +2321,30 @@ impl HasDesc for ContAssign {
     }
 }
 
+/// A parameter override statement.
+///
+/// ```text
+/// "defparam" hierarchical_parameter_identifier "=" constant_mintypmax_expression
+///     {"," hierarchical_parameter_identifier "=" constant_mintypmax_expression} ";"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Defparam {
+    pub span: Span,
+    pub assignments: Vec<(Expr, Expr)>,
+}
+
+impl HasSpan for Defparam {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for Defparam {
+    fn desc(&self) -> &'static str {
+        "defparam statement"
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct GenerateFor {
     pub span: Span,
@@ -1650,7 +2388,28 @@ impl HasDesc for GenerateIf {
 
 #[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct GenerateCase {
-    // TODO
+    pub span: Span,
+    pub expr: Expr,
+    pub items: Vec<GenerateCaseItem>,
+}
+
+impl HasSpan for GenerateCase {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for GenerateCase {
+    fn desc(&self) -> &'static str {
+        "case-generate statement"
+    }
+}
+
+/// A single item of a case-generate statement.
+#[derive(Debug, Clone, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum GenerateCaseItem {
+    Default(GenerateBlock),
+    Expr(Vec<Expr>, GenerateBlock),
 }
 
 /// A body of a generate construct. May contains hierarchy items or more
@@ -1670,7 +2429,14 @@ impl HasSpan for GenerateBlock {
 
 impl HasDesc for GenerateBlock {
     fn desc(&self) -> &'static str {
-        "generate statement"
+        "generate block"
+    }
+
+    fn desc_full(&self) -> String {
+        match self.label {
+            Some(label) => format!("generate block `{}`", label),
+            None => self.desc().into(),
+        }
     }
 }
 
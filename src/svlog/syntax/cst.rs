@@ -0,0 +1,471 @@
+// Copyright (c) 2016-2019 Fabian Schuiki
+
+//! A lossless concrete syntax tree.
+//!
+//! The lexer discards whitespace and comments, and the AST only retains the
+//! spans of the nodes. This module provides an opt-in concrete syntax tree
+//! (CST) which keeps every byte of a source file: Each token carries the
+//! whitespace, newlines, and comments around it as leading and trailing
+//! trivia, and the tokens are grouped into nodes along the boundaries of the
+//! AST. Concatenating the text of all tokens and trivia reproduces the original
+//! source file exactly.
+//!
+//! Parts of the source that do not end up as tokens in the lexer's output,
+//! such as compiler directives, macro invocations, and code disabled by `ifdef
+//! or translate_off pragmas, are recorded as `Skipped` trivia. Tokens that do
+//! not originate from the source file itself, such as the contents of included
+//! files and macro bodies, are not part of the tree.
+//!
+//! # Example
+//! ```ignore
+//! let tokens = lexer::Lexer::new(preproc).collect::<Result<Vec<_>, _>>()?;
+//! let cst = cst::Cst::new(source, tokens, Some(&ast));
+//! assert_eq!(cst.to_string(), source.get_content().extract(0, len));
+//! ```
+
+use crate::ast::{self, Item, Root};
+use crate::cat::{Cat, CatTokenKind};
+use crate::lexer::TokenAndSpan;
+use crate::token::Token;
+use moore_common::source::*;
+use std::fmt;
+
+/// A concrete syntax tree of a source file.
+#[derive(Debug, Clone)]
+pub struct Cst {
+    /// The source file.
+    pub source: Source,
+    /// The root node of the tree.
+    pub root: Node,
+}
+
+/// A node in the concrete syntax tree.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// The span covered by the node's tokens, excluding trivia.
+    pub span: Span,
+    pub children: Vec<Element>,
+}
+
+/// The different kinds of nodes in the concrete syntax tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+    Module,
+    Interface,
+    Package,
+    Class,
+    Program,
+    Checker,
+    Config,
+    Port,
+    /// A hierarchy item, e.g. a declaration, instantiation, or procedure.
+    Item,
+    Stmt,
+}
+
+/// An element of the concrete syntax tree; either a node or a token.
+#[derive(Debug, Clone)]
+pub enum Element {
+    Node(Node),
+    Token(CstToken),
+}
+
+/// A token in the concrete syntax tree, together with its trivia.
+#[derive(Debug, Clone)]
+pub struct CstToken {
+    pub kind: Token,
+    pub span: Span,
+    /// The trivia preceding the token.
+    pub leading: Vec<Trivia>,
+    /// The trivia following the token, up to and including the end of the
+    /// line.
+    pub trailing: Vec<Trivia>,
+}
+
+/// A piece of source text which is not part of any token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+/// The different kinds of trivia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    Comment,
+    /// Source text consumed by the preprocessor, e.g. compiler directives and
+    /// macro invocations.
+    Skipped,
+}
+
+impl Cst {
+    /// Build the concrete syntax tree of a source file.
+    ///
+    /// The `tokens` are the output of the lexer for `source`. If the AST parsed
+    /// from the same tokens is provided, the tokens are grouped into nodes
+    /// along its boundaries. Otherwise the root node contains all tokens.
+    pub fn new<I>(source: Source, tokens: I, ast: Option<&Root>) -> Cst
+    where
+        I: IntoIterator<Item = TokenAndSpan>,
+    {
+        let content = source.get_content();
+        let len = content.bytes().len();
+
+        // Keep only the tokens that were lexed from the source file itself,
+        // in order, and attach the text in between as trivia.
+        let mut cursor = 0;
+        let mut flat = vec![];
+        let mut pending = vec![];
+        for (kind, span) in tokens {
+            if kind == Token::Eof || span.source != source || span.begin < cursor {
+                continue;
+            }
+            let gap = lex_trivia(source, cursor, span.begin);
+            attach_trivia(&mut flat, &mut pending, gap);
+            flat.push(CstToken {
+                kind,
+                span,
+                leading: std::mem::replace(&mut pending, vec![]),
+                trailing: vec![],
+            });
+            cursor = span.end;
+        }
+
+        // Attach the remaining text to an end-of-file token.
+        let gap = lex_trivia(source, cursor, len);
+        attach_trivia(&mut flat, &mut pending, gap);
+        flat.push(CstToken {
+            kind: Token::Eof,
+            span: Span::new(source, len, len),
+            leading: pending,
+            trailing: vec![],
+        });
+
+        // Collect the node boundaries from the AST.
+        let mut bounds = vec![];
+        if let Some(root) = ast {
+            collect_root(root, &mut bounds);
+        }
+        bounds.retain(|&(_, span)| span.source == source);
+        bounds.sort_by(|a, b| (a.1.begin, b.1.end).cmp(&(b.1.begin, a.1.end)));
+
+        Cst {
+            source,
+            root: build_tree(Span::new(source, 0, len), flat, bounds),
+        }
+    }
+}
+
+impl Node {
+    /// Iterate over all tokens in this node and its children.
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        let mut v = vec![];
+        self.collect_tokens(&mut v);
+        v
+    }
+
+    fn collect_tokens<'a>(&'a self, into: &mut Vec<&'a CstToken>) {
+        for child in &self.children {
+            match *child {
+                Element::Node(ref n) => n.collect_tokens(into),
+                Element::Token(ref t) => into.push(t),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            match *child {
+                Element::Node(ref n) => write!(f, "{}", n)?,
+                Element::Token(ref t) => write!(f, "{}", t)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CstToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for t in &self.leading {
+            write!(f, "{}", t.span.extract())?;
+        }
+        write!(f, "{}", self.span.extract())?;
+        for t in &self.trailing {
+            write!(f, "{}", t.span.extract())?;
+        }
+        Ok(())
+    }
+}
+
+/// Categorize the source text between two tokens into trivia.
+fn lex_trivia(source: Source, begin: usize, end: usize) -> Vec<Trivia> {
+    if begin >= end {
+        return vec![];
+    }
+    let text = source.extract(begin, end);
+    let mut trivia: Vec<Trivia> = vec![];
+    for tkn in Cat::new(Box::new(text.char_indices())) {
+        let kind = match tkn.0 {
+            CatTokenKind::Whitespace => TriviaKind::Whitespace,
+            CatTokenKind::Newline => TriviaKind::Newline,
+            CatTokenKind::Comment => TriviaKind::Comment,
+            _ => TriviaKind::Skipped,
+        };
+        let span = Span::new(source, begin + tkn.1, begin + tkn.2);
+        match trivia.last_mut() {
+            Some(last) if last.kind == TriviaKind::Skipped && kind == TriviaKind::Skipped => {
+                last.span.expand(span);
+            }
+            _ => trivia.push(Trivia { kind, span }),
+        }
+    }
+    trivia
+}
+
+/// Distribute trivia between the previous token and the next one.
+///
+/// Everything up to and including the first newline is trailing trivia of the
+/// previous token. The rest is leading trivia of the next token.
+fn attach_trivia(tokens: &mut Vec<CstToken>, pending: &mut Vec<Trivia>, gap: Vec<Trivia>) {
+    let mut gap = gap.into_iter();
+    if let Some(prev) = tokens.last_mut() {
+        for t in &mut gap {
+            prev.trailing.push(t);
+            if t.kind == TriviaKind::Newline {
+                break;
+            }
+        }
+    }
+    pending.extend(gap);
+}
+
+/// Arrange a flat list of tokens into a tree of nodes.
+///
+/// The `bounds` must be sorted by their beginning, with outer nodes before
+/// inner ones. A token is part of a node if it begins within the node's span.
+/// Nodes that overlap without being nested are cut short at the end of their
+/// parent.
+fn build_tree(span: Span, tokens: Vec<CstToken>, bounds: Vec<(NodeKind, Span)>) -> Node {
+    let mut stack = vec![Node {
+        kind: NodeKind::Root,
+        span,
+        children: vec![],
+    }];
+    let mut bounds = bounds.into_iter().peekable();
+    let close = |stack: &mut Vec<Node>| {
+        let node = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(Element::Node(node));
+    };
+    for token in tokens {
+        let pos = token.span.begin;
+
+        // Close the nodes that end before this token.
+        while stack.len() > 1 && pos >= stack.last().unwrap().span.end {
+            close(&mut stack);
+        }
+
+        // Open the nodes that begin at or before this token.
+        while let Some(&(kind, span)) = bounds.peek() {
+            if span.begin > pos {
+                break;
+            }
+            bounds.next();
+            if span.end <= pos {
+                continue;
+            }
+            while stack.len() > 1 && span.begin >= stack.last().unwrap().span.end {
+                close(&mut stack);
+            }
+            let end = std::cmp::min(span.end, stack.last().unwrap().span.end);
+            stack.push(Node {
+                kind,
+                span: Span::new(span.source, span.begin, end),
+                children: vec![],
+            });
+        }
+
+        stack
+            .last_mut()
+            .unwrap()
+            .children
+            .push(Element::Token(token));
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap()
+}
+
+fn collect_root(root: &Root, into: &mut Vec<(NodeKind, Span)>) {
+    for item in &root.items {
+        match *item {
+            Item::Module(ref x) => {
+                into.push((NodeKind::Module, x.span));
+                collect_ports(&x.ports, into);
+                collect_items(&x.items, into);
+            }
+            Item::Interface(ref x) => {
+                into.push((NodeKind::Interface, x.span));
+                collect_ports(&x.ports, into);
+                collect_items(&x.items, into);
+            }
+            Item::Package(ref x) => {
+                into.push((NodeKind::Package, x.span));
+                collect_items(&x.items, into);
+            }
+            Item::Class(ref x) => into.push((NodeKind::Class, x.span)),
+            Item::Item(ref x) => collect_items(Some(x), into),
+            Item::Program(ref x) => {
+                into.push((NodeKind::Program, x.span));
+                collect_ports(&x.ports, into);
+                collect_items(&x.items, into);
+            }
+            Item::Checker(ref x) => {
+                into.push((NodeKind::Checker, x.span));
+                collect_ports(&x.ports, into);
+                collect_items(&x.items, into);
+            }
+            Item::Bind(ref x) => into.push((NodeKind::Item, x.span)),
+            Item::Config(ref x) => into.push((NodeKind::Config, x.span)),
+        }
+    }
+}
+
+fn collect_ports(ports: &[ast::Port], into: &mut Vec<(NodeKind, Span)>) {
+    use moore_common::util::HasSpan;
+    for port in ports {
+        into.push((NodeKind::Port, port.span()));
+    }
+}
+
+fn collect_items<'a>(
+    items: impl IntoIterator<Item = &'a ast::HierarchyItem>,
+    into: &mut Vec<(NodeKind, Span)>,
+) {
+    use crate::ast::HierarchyItem::*;
+    for item in items {
+        let span = match *item {
+            ImportDecl(ref x) => x.span,
+            ParamDecl(ref x) => x.span,
+            ModportDecl(ref x) => x.span,
+            ClassDecl(ref x) => x.span,
+            Typedef(ref x) => x.span,
+            PortDecl(ref x) => x.span,
+            SubroutineDecl(ref x) => x.span,
+            ContAssign(ref x) => x.span,
+            Assertion(ref x) => x.span,
+            SeqDecl(ref x) => x.span,
+            PropDecl(ref x) => x.span,
+            NetDecl(ref x) => x.span,
+            VarDecl(ref x) => x.span,
+            Inst(ref x) => x.span,
+            GenerateFor(ref x) => x.span,
+            GenerateIf(ref x) => x.span,
+            GenerateRegion(span, _) => span,
+            Error(span) => span,
+            Procedure(ref x) => {
+                into.push((NodeKind::Item, x.span));
+                collect_stmt(&x.stmt, into);
+                continue;
+            }
+            _ => continue,
+        };
+        into.push((NodeKind::Item, span));
+        if let GenerateRegion(_, ref items) = *item {
+            collect_items(items, into);
+        }
+    }
+}
+
+fn collect_stmt(stmt: &ast::Stmt, into: &mut Vec<(NodeKind, Span)>) {
+    into.push((NodeKind::Stmt, stmt.span));
+    match stmt.data {
+        ast::SequentialBlock(ref stmts) | ast::ParallelBlock(ref stmts, _) => {
+            for stmt in stmts {
+                collect_stmt(stmt, into);
+            }
+        }
+        ast::IfStmt {
+            ref main_stmt,
+            ref else_stmt,
+            ..
+        } => {
+            collect_stmt(main_stmt, into);
+            if let Some(ref else_stmt) = *else_stmt {
+                collect_stmt(else_stmt, into);
+            }
+        }
+        ast::TimedStmt(_, ref stmt) => collect_stmt(stmt, into),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::preproc::Preprocessor;
+
+    fn cst(input: &str) -> Cst {
+        let source = get_source_manager().add_anonymous(input);
+        let tokens: Vec<_> = Lexer::new(Preprocessor::new(source, &[], &[]))
+            .map(|x| x.unwrap())
+            .collect();
+        let ast = crate::parser::parse(Lexer::new(Preprocessor::new(source, &[], &[]))).unwrap();
+        Cst::new(source, tokens, Some(&ast))
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = "// Header\n`define FOO 42\n\nmodule foo ( input  bit a ); // ports\n    /* block\n comment */ int x = `FOO;\n  `ifdef BAR\n    int y;\n  `endif\n    (* keep *) always_comb begin\n        x = a; // trailing\n    end\nendmodule : foo\n\n";
+        assert_eq!(cst(input).to_string(), input);
+    }
+
+    #[test]
+    fn nodes_and_trivia() {
+        let cst = cst("module foo; // c\n  int x;\nendmodule\n");
+        let module = match cst.root.children[0] {
+            Element::Node(ref n) => n,
+            _ => panic!("expected node"),
+        };
+        assert_eq!(module.kind, NodeKind::Module);
+        let semi = module.tokens()[2];
+        assert_eq!(semi.kind, Token::Semicolon);
+        let kinds: Vec<_> = semi.trailing.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TriviaKind::Whitespace,
+                TriviaKind::Comment,
+                TriviaKind::Newline
+            ]
+        );
+        let item = module
+            .children
+            .iter()
+            .filter_map(|c| match *c {
+                Element::Node(ref n) => Some(n),
+                _ => None,
+            })
+            .next()
+            .unwrap();
+        assert_eq!(item.kind, NodeKind::Item);
+        assert_eq!(item.tokens()[0].leading[0].kind, TriviaKind::Whitespace);
+        assert_eq!(
+            module.tokens().last().unwrap().kind,
+            Token::Keyword(crate::token::Kw::Endmodule)
+        );
+    }
+}
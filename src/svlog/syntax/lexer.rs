@@ -17,6 +17,8 @@ pub type TokenAndSpan = (Token, Span);
 pub struct Lexer<'a> {
     input: Preprocessor<'a>,
     peek: [CatTokenAndSpan; 4],
+    /// Whether we are currently inside a `(* ... *)` attribute instance.
+    in_attr: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -24,6 +26,7 @@ impl<'a> Lexer<'a> {
         Lexer {
             input: input,
             peek: [(CatTokenKind::Eof, INVALID_SPAN); 4],
+            in_attr: false,
         }
     }
 
@@ -55,6 +58,30 @@ impl<'a> Lexer<'a> {
         loop {
             self.skip_noise()?;
 
+            // IEEE 1800-2009 5.12 Attributes. The `(*` is only treated as the
+            // start of an attribute if it is not immediately closed, to allow
+            // for the `@(*)` event control. The `*)` only closes an attribute
+            // if one has been opened before.
+            match (self.peek[0].0, self.peek[1].0) {
+                (CatTokenKind::Symbol('('), CatTokenKind::Symbol('*'))
+                    if !self.in_attr && self.peek[2].0 != CatTokenKind::Symbol(')') =>
+                {
+                    let sp = Span::union(self.peek[0].1, self.peek[1].1);
+                    self.bump()?;
+                    self.bump()?;
+                    self.in_attr = true;
+                    return Ok((OpenDelim(Attr), sp));
+                }
+                (CatTokenKind::Symbol('*'), CatTokenKind::Symbol(')')) if self.in_attr => {
+                    let sp = Span::union(self.peek[0].1, self.peek[1].1);
+                    self.bump()?;
+                    self.bump()?;
+                    self.in_attr = false;
+                    return Ok((CloseDelim(Attr), sp));
+                }
+                _ => (),
+            }
+
             // Match 4-character symbols
             if let (
                 CatTokenKind::Symbol(c0),
@@ -319,6 +346,9 @@ impl<'a> Lexer<'a> {
                         self.bump()?; // eat the unit
                         return Ok((Literal(Time(value, frac, unit)), sp));
                     }
+                    if let Some(exp) = self.try_exponent(&mut sp)? {
+                        return Ok((Literal(Real(value, frac, exp)), sp));
+                    }
                     if self.peek[0].0 == CatTokenKind::Text {
                         return Err(DiagBuilder2::fatal(format!(
                             "number literal `{}` may not directly be followed by letters `{}`",
@@ -367,9 +397,9 @@ impl<'a> Lexer<'a> {
                                         span.expand(sp);
                                         s.push('"');
                                     }
-                                    (CatTokenKind::Text, sp) => {
+                                    (CatTokenKind::Text, sp) | (CatTokenKind::Digits, sp) => {
                                         span.expand(sp);
-                                        s.push_str(&sp.extract());
+                                        unescape_into(&mut s, &sp.extract());
                                     }
                                     _ => {
                                         return Err(DiagBuilder2::fatal(
@@ -441,24 +471,6 @@ impl<'a> Lexer<'a> {
                         }
                     }
                 }
-                // SystemVerilog Attributes
-                (CatTokenKind::Symbol('('), CatTokenKind::Symbol('*'))
-                    if self.peek[2].0 != CatTokenKind::Symbol(')') =>
-                {
-                    self.bump()?;
-                    self.bump()?;
-                    loop {
-                        match (self.peek[0].0, self.peek[1].0) {
-                            (CatTokenKind::Eof, _) => break,
-                            (CatTokenKind::Symbol('*'), CatTokenKind::Symbol(')')) => {
-                                self.bump()?;
-                                self.bump()?;
-                                break;
-                            }
-                            _ => self.bump()?,
-                        }
-                    }
-                }
                 _ => (),
             }
             match self.peek[0].0 {
@@ -631,6 +643,44 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// Try to parse the exponent of a real number, e.g. the `e-3` in `1.5e-3`.
+    ///
+    /// Returns the exponent including its sign, if any.
+    fn try_exponent(&mut self, span: &mut Span) -> DiagResult2<Option<Name>> {
+        if self.peek[0].0 != CatTokenKind::Text {
+            return Ok(None);
+        }
+        let text = self.peek[0].1.extract();
+        if !text.starts_with('e') && !text.starts_with('E') {
+            return Ok(None);
+        }
+        let mut s = String::new();
+        if text.len() == 1 {
+            // A signed exponent such as `e-3`, where the sign is a separate
+            // symbol.
+            let sign = match (self.peek[1].0, self.peek[2].0) {
+                (CatTokenKind::Symbol(c @ '+'), CatTokenKind::Digits)
+                | (CatTokenKind::Symbol(c @ '-'), CatTokenKind::Digits) => c,
+                _ => return Ok(None),
+            };
+            span.expand(self.peek[0].1);
+            self.bump()?; // eat the `e`
+            s.push(sign);
+            span.expand(self.peek[0].1);
+            self.bump()?; // eat the sign
+        } else if text[1..].starts_with(|c: char| c.is_ascii_digit())
+            && text[1..].chars().all(|c| c.is_ascii_digit() || c == '_')
+        {
+            s.extend(text[1..].chars().filter(|&c| c != '_'));
+            span.expand(self.peek[0].1);
+            self.bump()?; // eat the `e` and the leading digits
+        } else {
+            return Ok(None);
+        }
+        self.eat_number_body_into(&mut s, span, false)?;
+        Ok(Some(get_name_table().intern(&s, true)))
+    }
+
     /// Try to parse the next text token as a time unit.
     fn try_time_unit(&mut self) -> Option<TimeUnit> {
         if self.peek[0].0 == CatTokenKind::Text {
@@ -649,6 +699,41 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Resolve an escape sequence in a string literal.
+///
+/// The `text` is the token following the backslash, of which only the leading
+/// characters are part of the escape sequence. See "5.9.1 Special characters
+/// in strings".
+fn unescape_into(into: &mut String, text: &str) {
+    let (escaped, len) = match text.chars().next() {
+        Some('n') => ('\n', 1),
+        Some('t') => ('\t', 1),
+        Some('v') => ('\x0b', 1),
+        Some('f') => ('\x0c', 1),
+        Some('a') => ('\x07', 1),
+        Some('x') => {
+            let digits = text[1..]
+                .chars()
+                .take(2)
+                .take_while(|c| c.is_ascii_hexdigit())
+                .count();
+            match u8::from_str_radix(&text[1..1 + digits], 16) {
+                Ok(v) => (v as char, 1 + digits),
+                Err(_) => ('x', 1),
+            }
+        }
+        Some(c) if c.is_digit(8) => {
+            let digits = text.chars().take(3).take_while(|c| c.is_digit(8)).count();
+            let v = u32::from_str_radix(&text[..digits], 8).unwrap();
+            ((v as u8) as char, digits)
+        }
+        Some(c) => (c, c.len_utf8()),
+        None => return,
+    };
+    into.push(escaped);
+    into.push_str(&text[len..]);
+}
+
 impl<'a> Iterator for Lexer<'a> {
     type Item = DiagResult2<TokenAndSpan>;
 
@@ -831,6 +916,19 @@ mod tests {
         );
     }
 
+    /// According to IEEE 1800-2009 5.9.1
+    #[test]
+    fn string_literal_escapes() {
+        check(
+            r#""a\tb\nc" "\x41\102C" "\\\"""#,
+            &[
+                Literal(Str(name("a\tb\nc"))),
+                Literal(Str(name("ABC"))),
+                Literal(Str(name("\\\""))),
+            ],
+        );
+    }
+
     #[test]
     fn time_literal() {
         check(
@@ -856,4 +954,17 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn real_literal() {
+        check(
+            "1e3 1.5E-3 2e+4 7e1_0",
+            &[
+                Literal(Real(name("1"), None, name("3"))),
+                Literal(Real(name("1"), Some(name("5")), name("-3"))),
+                Literal(Real(name("2"), None, name("+4"))),
+                Literal(Real(name("7"), None, name("10"))),
+            ],
+        );
+    }
 }
@@ -8,6 +8,7 @@ extern crate log;
 
 pub mod ast;
 pub mod cat;
+pub mod cst;
 pub mod lexer;
 pub mod parser;
 pub mod preproc;
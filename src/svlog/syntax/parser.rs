@@ -12,7 +12,7 @@ use crate::lexer::{Lexer, TokenAndSpan};
 use crate::token::*;
 use moore_common::{errors::*, name::*, source::*, util::HasSpan};
 use std;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 // The problem with data_declaration and data_type_or_implicit:
 //
@@ -38,6 +38,21 @@ trait AbstractParser {
     fn add_diag(&mut self, diag: DiagBuilder2);
     fn severity(&self) -> Severity;
 
+    /// Take the attribute instances that precede the next token.
+    fn take_attrs(&mut self) -> Vec<AttrInst> {
+        Vec::new()
+    }
+
+    /// Check whether the parser should recover from errors by producing error
+    /// nodes in the AST.
+    ///
+    /// This is never the case while speculatively trying one of several
+    /// alternatives, since error nodes would make a failed alternative look
+    /// like a successful one.
+    fn is_tolerant(&self) -> bool {
+        false
+    }
+
     fn try_eat_ident(&mut self) -> Option<(Name, Span)> {
         match self.peek(0) {
             (Ident(name), span) => {
@@ -148,6 +163,43 @@ trait AbstractParser {
     //  }
     // }
 
+    /// Skip to the end of the current item or statement after an error.
+    ///
+    /// Consumes everything up to and including the next `;` or `begin`/`end`
+    /// block at the current nesting level, and stops before any of the
+    /// `terminators`. Unlike `recover_balanced`, a closing delimiter without a
+    /// matching opening one is not an error; it is assumed to belong to an
+    /// enclosing construct and is left untouched.
+    fn recover_item(&mut self, terminators: &[Token]) {
+        let mut stack = Vec::new();
+        loop {
+            let tkn = self.peek(0).0;
+            if stack.is_empty() {
+                if tkn == Eof || terminators.contains(&tkn) {
+                    return;
+                }
+                if tkn == Semicolon {
+                    self.skip();
+                    return;
+                }
+            }
+            match tkn {
+                OpenDelim(x) => stack.push(x),
+                CloseDelim(x) => match stack.pop() {
+                    None => return,
+                    Some(Bgend) if x == Bgend && stack.is_empty() => {
+                        self.skip();
+                        return;
+                    }
+                    _ => (),
+                },
+                Eof => return,
+                _ => (),
+            }
+            self.skip();
+        }
+    }
+
     fn recover_balanced(&mut self, terminators: &[Token], eat_terminator: bool) {
         // println!("recovering (balanced) to {:?}", terminators);
         let mut stack = Vec::new();
@@ -224,6 +276,13 @@ struct Parser<'a> {
     last_span: Span,
     severity: Severity,
     consumed: usize,
+    /// The attribute instances encountered in the token stream, keyed by the
+    /// index of the token they precede.
+    attrs: HashMap<usize, Vec<AttrInst>>,
+    /// Whether diagnostics are printed as they are emitted.
+    print_diags: bool,
+    /// Whether to recover from errors by producing error nodes.
+    tolerant: bool,
 }
 
 impl<'a> AbstractParser for Parser<'a> {
@@ -262,8 +321,10 @@ impl<'a> AbstractParser for Parser<'a> {
     }
 
     fn add_diag(&mut self, diag: DiagBuilder2) {
-        eprintln!("");
-        eprintln!("{}", diag);
+        if self.print_diags {
+            eprintln!("");
+            eprintln!("{}", diag);
+        }
 
         // Keep track of the worst diagnostic severity we've encountered, such
         // that parsing can be aborted accordingly.
@@ -276,6 +337,15 @@ impl<'a> AbstractParser for Parser<'a> {
     fn severity(&self) -> Severity {
         self.severity
     }
+
+    fn take_attrs(&mut self) -> Vec<AttrInst> {
+        self.ensure_queue_filled(0);
+        self.attrs.remove(&self.consumed).unwrap_or_default()
+    }
+
+    fn is_tolerant(&self) -> bool {
+        self.tolerant
+    }
 }
 
 impl<'a> Parser<'a> {
@@ -287,6 +357,9 @@ impl<'a> Parser<'a> {
             last_span: INVALID_SPAN,
             severity: Severity::Note,
             consumed: 0,
+            attrs: HashMap::new(),
+            print_diags: true,
+            tolerant: false,
         }
     }
 
@@ -297,11 +370,110 @@ impl<'a> Parser<'a> {
         while self.queue.len() <= min_tokens {
             match self.input.next_token() {
                 Ok((Eof, sp)) => self.queue.push_back((Eof, sp)),
+                Ok((OpenDelim(Attr), sp)) => self.capture_attr(sp),
                 Ok(tkn) => self.queue.push_back(tkn),
                 Err(x) => self.add_diag(x),
             }
         }
     }
+
+    /// Capture an attribute instance from the input stream.
+    ///
+    /// Attributes may appear in front of almost any construct. Rather than
+    /// dealing with them at every point in the grammar, the tokens of the
+    /// attribute are removed from the token stream and parsed separately. The
+    /// result is associated with the next token in the stream, from where the
+    /// parse functions that care about attributes can pick it up via
+    /// `take_attrs`.
+    fn capture_attr(&mut self, open_span: Span) {
+        let mut tokens = vec![];
+        let close_span = loop {
+            match self.input.next_token() {
+                Ok((CloseDelim(Attr), sp)) => break sp,
+                Ok((Eof, sp)) => {
+                    self.add_diag(
+                        DiagBuilder2::error("attribute is missing a closing `*)`").span(open_span),
+                    );
+                    self.queue.push_back((Eof, sp));
+                    return;
+                }
+                Ok(tkn) => tokens.push(tkn),
+                Err(x) => self.add_diag(x),
+            }
+        };
+        let span = Span::union(open_span, close_span);
+        tokens.push((Eof, close_span));
+        let mut sub = TokenParser::new(tokens);
+        let result = parse_attr_inst(&mut sub, span);
+        for diag in sub.diagnostics {
+            self.add_diag(diag);
+        }
+        if let Ok(attr) = result {
+            let index = self.consumed + self.queue.len();
+            self.attrs.entry(index).or_insert_with(Vec::new).push(attr);
+        }
+    }
+}
+
+/// A parser operating on a fixed sequence of tokens.
+///
+/// The sequence must be terminated by an `Eof` token. Used to parse the
+/// attribute instances captured out of the main token stream.
+struct TokenParser {
+    tokens: Vec<TokenAndSpan>,
+    pos: usize,
+    diagnostics: Vec<DiagBuilder2>,
+    last_span: Span,
+    severity: Severity,
+}
+
+impl TokenParser {
+    fn new(tokens: Vec<TokenAndSpan>) -> TokenParser {
+        TokenParser {
+            tokens,
+            pos: 0,
+            diagnostics: Vec::new(),
+            last_span: INVALID_SPAN,
+            severity: Severity::Note,
+        }
+    }
+}
+
+impl AbstractParser for TokenParser {
+    fn peek(&mut self, offset: usize) -> TokenAndSpan {
+        let index = std::cmp::min(self.pos + offset, self.tokens.len() - 1);
+        self.tokens[index]
+    }
+
+    fn bump(&mut self) {
+        if self.pos < self.tokens.len() - 1 {
+            self.last_span = self.tokens[self.pos].1;
+            self.pos += 1;
+        }
+    }
+
+    fn skip(&mut self) {
+        self.bump()
+    }
+
+    fn consumed(&self) -> usize {
+        self.pos
+    }
+
+    fn last_span(&self) -> Span {
+        self.last_span
+    }
+
+    fn add_diag(&mut self, diag: DiagBuilder2) {
+        if diag.get_severity() > self.severity {
+            self.severity = diag.get_severity();
+        }
+        self.diagnostics.push(diag);
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
 }
 
 /// Parses the opening delimiter, calls the `inner` function, and parses the
@@ -552,6 +724,10 @@ impl<M: FnMut(&mut dyn AbstractParser) -> bool, R: FnMut(&mut dyn AbstractParser
     }
 }
 
+/// Parse a SystemVerilog source text.
+///
+/// Diagnostics are printed as they are emitted. Fails if any errors were
+/// reported; use `parse_tolerant` to obtain a partial AST in that case.
 pub fn parse(input: Lexer) -> Result<Root, ()> {
     let mut p = Parser::new(input);
     let root = parse_source_text(&mut p);
@@ -562,6 +738,21 @@ pub fn parse(input: Lexer) -> Result<Root, ()> {
     }
 }
 
+/// Parse a SystemVerilog source text, recovering from errors.
+///
+/// Always returns the best-effort AST, together with the diagnostics emitted
+/// during parsing, which are not printed. Parts of the input that could not be
+/// parsed are represented as `HierarchyItem::Error`, `ErrorStmt`, or
+/// `ErrorExpr` nodes. Parsing stops at the first fatal error, in which case
+/// the AST only covers the input up to that point.
+pub fn parse_tolerant(input: Lexer) -> (Root, Vec<DiagBuilder2>) {
+    let mut p = Parser::new(input);
+    p.print_diags = false;
+    p.tolerant = true;
+    let root = parse_source_text(&mut p);
+    (root, p.diagnostics)
+}
+
 fn parse_source_text(p: &mut Parser) -> Root {
     let mut root = Root {
         timeunits: Timeunit {
@@ -579,15 +770,49 @@ fn parse_source_text(p: &mut Parser) -> Root {
 
     // Parse the descriptions in the source text.
     while !p.is_fatal() && p.peek(0).0 != Eof {
+        let span = p.peek(0).1;
+        let consumed = p.consumed();
         match parse_item(p) {
             Ok(item) => root.items.push(item),
+            Err(()) if p.is_tolerant() => {
+                // parse_item handles recovery for most items. Make sure we
+                // make progress in any case.
+                if p.consumed() == consumed {
+                    p.recover_item(&[]);
+                }
+                let span = Span::union(span, p.last_span());
+                root.items.push(ast::Item::Item(HierarchyItem::Error(span)));
+            }
             Err(()) => (), // parse_item handles recovery, so no need to do anything here
         }
     }
 
+    // Attributes in front of constructs that do not pick them up, e.g. ports
+    // or expressions, are left over.
+    let mut attrs: Vec<_> = p.attrs.drain().collect();
+    attrs.sort_by_key(|&(index, _)| index);
+    for (_, attrs) in attrs {
+        warn_ignored_attrs(p, &attrs);
+    }
+
     root
 }
 
+/// Warn about attribute instances which are not attached to any node.
+fn warn_ignored_attrs(p: &mut dyn AbstractParser, attrs: &[AttrInst]) {
+    for attr in attrs {
+        p.add_diag(
+            DiagBuilder2::warning("attribute ignored")
+                .span(attr.span)
+                .add_note(
+                    "Attributes are only kept on modules, interfaces, packages, programs, \
+                     checkers, declarations, instances, procedures, continuous assignments, \
+                     and statements.",
+                ),
+        );
+    }
+}
+
 fn parse_time_units(p: &mut dyn AbstractParser) -> ReportedResult<Timeunit> {
     let mut unit = None;
     let mut prec = None;
@@ -628,14 +853,43 @@ fn parse_time_literal(p: &mut dyn AbstractParser) -> ReportedResult<Spanned<Lit>
     }
 }
 
+/// Parse the contents of an attribute instance, i.e. everything between the
+/// `(*` and `*)`.
+///
+/// ```text
+/// attr_spec {"," attr_spec}
+/// attr_spec: ident ["=" const_expr]
+/// ```
+fn parse_attr_inst(p: &mut dyn AbstractParser, span: Span) -> ReportedResult<AttrInst> {
+    let specs = comma_list_nonempty(p, Eof, "attribute", |p| {
+        let name = parse_identifier(p, "attribute name")?;
+        let mut span = name.span;
+        let value = if p.try_eat(Operator(Op::Assign)) {
+            let expr = parse_expr(p)?;
+            span.expand(expr.span);
+            Some(expr)
+        } else {
+            None
+        };
+        Ok(AttrSpec { span, name, value })
+    })?;
+    Ok(AttrInst { span, specs })
+}
+
 fn parse_item(p: &mut Parser) -> ReportedResult<ast::Item> {
     let (tkn, sp) = p.peek(0);
     match tkn {
         Keyword(Kw::Module) => parse_module_decl(p).map(|d| ast::Item::Module(d)),
         Keyword(Kw::Interface) => parse_interface_decl(p).map(|d| ast::Item::Interface(d)),
         Keyword(Kw::Package) => parse_package_decl(p).map(|d| ast::Item::Package(d)),
-        // Keyword(Kw::Program) => parse_program_decl(p).map(|d| ast::Item::Program(d)),
+        Keyword(Kw::Program) => parse_program_decl(p).map(|d| ast::Item::Program(d)),
+        Keyword(Kw::Checker) => parse_checker_decl(p).map(|d| ast::Item::Checker(d)),
+        Keyword(Kw::Bind) => parse_bind_directive(p).map(|d| ast::Item::Bind(d)),
+        Keyword(Kw::Config) => parse_config_decl(p).map(|d| ast::Item::Config(d)),
         Keyword(Kw::Class) => parse_class_decl(p).map(|d| ast::Item::Class(d)),
+        Keyword(Kw::Virtual) if p.peek(1).0 == Keyword(Kw::Class) => {
+            parse_class_decl(p).map(|d| ast::Item::Class(d))
+        }
         Keyword(Kw::Import) => {
             parse_import_decl(p).map(|i| ast::Item::Item(HierarchyItem::ImportDecl(i)))
         }
@@ -677,6 +931,7 @@ fn as_lifetime(tkn: Token) -> Option<Lifetime> {
 }
 
 fn parse_interface_decl(p: &mut Parser) -> ReportedResult<IntfDecl> {
+    let attrs = p.take_attrs();
     let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Interface))?;
     let result = recovered(p, Keyword(Kw::Endinterface), |p| {
@@ -726,13 +981,14 @@ fn parse_interface_decl(p: &mut Parser) -> ReportedResult<IntfDecl> {
             if p.try_eat(Semicolon) {
                 continue;
             }
-            items.push(parse_hierarchy_item(p)?);
+            items.push(parse_hierarchy_item_or_error(p, Keyword(Kw::Endinterface))?);
         }
 
         span.expand(p.last_span());
         Ok(IntfDecl {
             id: DUMMY_NODE_ID,
             span: span,
+            attrs: attrs.clone(),
             lifetime: lifetime,
             name: name,
             name_span: name_sp,
@@ -742,7 +998,10 @@ fn parse_interface_decl(p: &mut Parser) -> ReportedResult<IntfDecl> {
         })
     });
     p.require_reported(Keyword(Kw::Endinterface))?;
-    result
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
 }
 
 fn parse_parameter_port_list(p: &mut dyn AbstractParser) -> ReportedResult<Vec<ParamDecl>> {
@@ -871,6 +1130,7 @@ fn parse_constant_expr(p: &mut dyn AbstractParser) -> ReportedResult<()> {
 /// Parse a module declaration, assuming that the leading `module` keyword has
 /// already been consumed.
 fn parse_module_decl(p: &mut Parser) -> ReportedResult<ModDecl> {
+    let attrs = p.take_attrs();
     let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Module))?;
     let result = recovered(p, Keyword(Kw::Endmodule), |p| {
@@ -918,13 +1178,14 @@ fn parse_module_decl(p: &mut Parser) -> ReportedResult<ModDecl> {
             if p.try_eat(Semicolon) {
                 continue;
             }
-            items.push(parse_hierarchy_item(p)?);
+            items.push(parse_hierarchy_item_or_error(p, Keyword(Kw::Endmodule))?);
         }
 
         span.expand(p.last_span());
         Ok(ModDecl {
             id: DUMMY_NODE_ID,
             span: span,
+            attrs: attrs.clone(),
             lifetime: lifetime,
             name: name,
             name_span: name_sp,
@@ -938,10 +1199,14 @@ fn parse_module_decl(p: &mut Parser) -> ReportedResult<ModDecl> {
     if p.try_eat(Colon) {
         p.eat_ident("module name")?;
     }
-    result
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
 }
 
 fn parse_package_decl(p: &mut dyn AbstractParser) -> ReportedResult<PackageDecl> {
+    let attrs = p.take_attrs();
     let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Package))?;
     let result = recovered(p, Keyword(Kw::Endpackage), |p| {
@@ -970,13 +1235,14 @@ fn parse_package_decl(p: &mut dyn AbstractParser) -> ReportedResult<PackageDecl>
             if p.try_eat(Semicolon) {
                 continue;
             }
-            items.push(parse_hierarchy_item(p)?);
+            items.push(parse_hierarchy_item_or_error(p, Keyword(Kw::Endpackage))?);
         }
 
         span.expand(p.last_span());
         Ok(PackageDecl {
             id: DUMMY_NODE_ID,
             span: span,
+            attrs: attrs.clone(),
             lifetime: lifetime,
             name: name,
             name_span: name_span,
@@ -988,72 +1254,418 @@ fn parse_package_decl(p: &mut dyn AbstractParser) -> ReportedResult<PackageDecl>
     if p.try_eat(Colon) {
         p.eat_ident("package name")?;
     }
-    result
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
 }
 
-fn parse_program_decl(p: &mut dyn AbstractParser) -> ReportedResult<()> {
+fn parse_program_decl(p: &mut Parser) -> ReportedResult<ProgramDecl> {
+    let attrs = p.take_attrs();
+    let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Program))?;
     let result = recovered(p, Keyword(Kw::Endprogram), |p| {
-        let q = p.peek(0).1;
-        p.add_diag(DiagBuilder2::error("Don't know how to parse program declarations").span(q));
-        Err(())
+        // Eat the optional lifetime.
+        let lifetime = match as_lifetime(p.peek(0).0) {
+            Some(l) => {
+                p.bump();
+                l
+            }
+            None => Lifetime::Static,
+        };
+
+        // Eat the program name.
+        let (name, name_sp) = p.eat_ident("program name")?;
+
+        // Eat the optional parameter port list.
+        let params = if p.try_eat(Hashtag) {
+            parse_parameter_port_list(p)?
+        } else {
+            Vec::new()
+        };
+
+        // Eat the optional list of ports.
+        let ports = if p.try_eat(OpenDelim(Paren)) {
+            parse_port_list(p)?
+        } else {
+            Vec::new()
+        };
+        p.require_reported(Semicolon)?;
+
+        // Parse the program items.
+        let mut items = Vec::new();
+        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endprogram) && p.peek(0).0 != Eof {
+            if p.try_eat(Semicolon) {
+                continue;
+            }
+            items.push(parse_hierarchy_item_or_error(p, Keyword(Kw::Endprogram))?);
+        }
+
+        span.expand(p.last_span());
+        Ok(ProgramDecl {
+            id: DUMMY_NODE_ID,
+            span: span,
+            attrs: attrs.clone(),
+            lifetime: lifetime,
+            name: name,
+            name_span: name_sp,
+            params: params,
+            ports: ports,
+            items: items,
+        })
     });
     p.require_reported(Keyword(Kw::Endprogram))?;
-    result
+    if p.try_eat(Colon) {
+        p.eat_ident("program name")?;
+    }
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
 }
 
-fn parse_hierarchy_item(p: &mut dyn AbstractParser) -> ReportedResult<HierarchyItem> {
-    // Consume optional leading label.
-    if p.is_ident() && p.peek(1).0 == Colon {
-        p.bump();
-        p.bump();
-    }
+fn parse_checker_decl(p: &mut Parser) -> ReportedResult<CheckerDecl> {
+    let attrs = p.take_attrs();
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Checker))?;
+    let result = recovered(p, Keyword(Kw::Endchecker), |p| {
+        // Eat the checker name and the optional list of ports.
+        let (name, name_sp) = p.eat_ident("checker name")?;
+        let ports = if p.try_eat(OpenDelim(Paren)) {
+            parse_port_list(p)?
+        } else {
+            Vec::new()
+        };
+        p.require_reported(Semicolon)?;
 
-    // First attempt the simple cases where a keyword reliably identifies the
-    // following item.
-    match p.peek(0).0 {
-        // Keyword(Kw::Localparam) => return parse_localparam_decl(p).map(|x| HierarchyItem::LocalparamDecl(x)),
-        // Keyword(Kw::Parameter)  => return parse_parameter_decl(p).map(|x| HierarchyItem::ParameterDecl(x)),
-        Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
-            let decl = parse_param_decl(p, false)?;
-            p.require_reported(Semicolon)?;
-            return Ok(HierarchyItem::ParamDecl(decl));
-        }
-        Keyword(Kw::Modport) => {
-            return parse_modport_decl(p).map(|x| HierarchyItem::ModportDecl(x))
+        // Parse the checker items.
+        let mut items = Vec::new();
+        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endchecker) && p.peek(0).0 != Eof {
+            if p.try_eat(Semicolon) {
+                continue;
+            }
+            items.push(parse_hierarchy_item_or_error(p, Keyword(Kw::Endchecker))?);
         }
-        Keyword(Kw::Class) => return parse_class_decl(p).map(|x| HierarchyItem::ClassDecl(x)),
-        Keyword(Kw::Typedef) => return parse_typedef(p).map(|x| HierarchyItem::Typedef(x)),
-        Keyword(Kw::Import) => return parse_import_decl(p).map(|x| HierarchyItem::ImportDecl(x)),
 
-        // Structured procedures as per IEEE 1800-2009 section 9.2
-        Keyword(Kw::Initial) => {
-            return parse_procedure(p, ProcedureKind::Initial).map(|x| HierarchyItem::Procedure(x));
-        }
-        Keyword(Kw::Always) => {
-            return parse_procedure(p, ProcedureKind::Always).map(|x| HierarchyItem::Procedure(x));
-        }
-        Keyword(Kw::AlwaysComb) => {
-            return parse_procedure(p, ProcedureKind::AlwaysComb)
-                .map(|x| HierarchyItem::Procedure(x));
-        }
-        Keyword(Kw::AlwaysLatch) => {
-            return parse_procedure(p, ProcedureKind::AlwaysLatch)
-                .map(|x| HierarchyItem::Procedure(x));
+        span.expand(p.last_span());
+        Ok(CheckerDecl {
+            id: DUMMY_NODE_ID,
+            span: span,
+            attrs: attrs.clone(),
+            name: name,
+            name_span: name_sp,
+            ports: ports,
+            items: items,
+        })
+    });
+    p.require_reported(Keyword(Kw::Endchecker))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("checker name")?;
+    }
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
+}
+
+/// Parse a bind directive.
+///
+/// ```text
+/// "bind" hierarchical_name [":" hierarchical_name {"," hierarchical_name}] instantiation
+/// ```
+fn parse_bind_directive(p: &mut dyn AbstractParser) -> ReportedResult<BindDirective> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Bind))?;
+    let target = parse_hierarchical_name(p, "bind target")?;
+    let mut target_insts = Vec::new();
+    if p.try_eat(Colon) {
+        loop {
+            target_insts.push(parse_hierarchical_name(p, "bind target instance")?);
+            if !p.try_eat(Comma) {
+                break;
+            }
         }
-        Keyword(Kw::AlwaysFf) => {
-            return parse_procedure(p, ProcedureKind::AlwaysFf)
-                .map(|x| HierarchyItem::Procedure(x));
+    }
+    let inst = parse_inst(p)?;
+    span.expand(p.last_span());
+    Ok(BindDirective {
+        id: DUMMY_NODE_ID,
+        span: span,
+        target: target,
+        target_insts: target_insts,
+        inst: inst,
+    })
+}
+
+/// Parse a sequence of identifiers separated by periods.
+fn parse_hierarchical_name(
+    p: &mut dyn AbstractParser,
+    msg: &str,
+) -> ReportedResult<Vec<ast::Identifier>> {
+    let mut names = vec![parse_identifier(p, msg)?];
+    while p.try_eat(Period) {
+        names.push(parse_identifier(p, msg)?);
+    }
+    Ok(names)
+}
+
+/// Parse a configuration.
+///
+/// ```text
+/// "config" name ";" {localparam ";"} "design" {cell} ";" {rule} "endconfig" [":" name]
+/// ```
+fn parse_config_decl(p: &mut dyn AbstractParser) -> ReportedResult<ConfigDecl> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Config))?;
+    let result = recovered(p, Keyword(Kw::Endconfig), |p| {
+        let (name, name_span) = p.eat_ident("config name")?;
+        p.require_reported(Semicolon)?;
+
+        // Parse the local parameters.
+        let mut params = Vec::new();
+        while p.peek(0).0 == Keyword(Kw::Localparam) {
+            params.push(parse_param_decl(p, false)?);
+            p.require_reported(Semicolon)?;
         }
-        Keyword(Kw::Final) => {
-            return parse_procedure(p, ProcedureKind::Final).map(|x| HierarchyItem::Procedure(x));
+
+        // Parse the design statement.
+        p.require_reported(Keyword(Kw::Design))?;
+        let mut design = Vec::new();
+        while p.is_ident() {
+            design.push(parse_config_cell(p)?);
         }
-        Keyword(Kw::Function) | Keyword(Kw::Task) => {
-            return parse_subroutine_decl(p).map(|x| HierarchyItem::SubroutineDecl(x));
+        p.require_reported(Semicolon)?;
+
+        // Parse the rules.
+        let mut rules = Vec::new();
+        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endconfig) && p.peek(0).0 != Eof {
+            rules.push(parse_config_rule(p)?);
         }
 
-        // Port declarations
-        Keyword(Kw::Inout) | Keyword(Kw::Input) | Keyword(Kw::Output) | Keyword(Kw::Ref) => {
+        span.expand(p.last_span());
+        Ok(ConfigDecl {
+            id: DUMMY_NODE_ID,
+            span: span,
+            name: name,
+            name_span: name_span,
+            params: params,
+            design: design,
+            rules: rules,
+        })
+    });
+    p.require_reported(Keyword(Kw::Endconfig))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("config name")?;
+    }
+    result.map(|mut x| {
+        x.span.expand(p.last_span());
+        x
+    })
+}
+
+/// Parse a cell name in a configuration, e.g. `lib.cell` or `cell`.
+fn parse_config_cell(p: &mut dyn AbstractParser) -> ReportedResult<ConfigCell> {
+    let mut span = p.peek(0).1;
+    let first = parse_identifier(p, "cell name")?;
+    let (lib, cell) = if p.try_eat(Period) {
+        (Some(first), parse_identifier(p, "cell name")?)
+    } else {
+        (None, first)
+    };
+    span.expand(p.last_span());
+    Ok(ConfigCell {
+        span: span,
+        lib: lib,
+        cell: cell,
+    })
+}
+
+/// Parse a configuration rule.
+///
+/// ```text
+/// ("default" | "instance" hierarchical_name | "cell" cell) (liblist | use) ";"
+/// liblist: "liblist" {name}
+/// use: "use" [cell] [named_param_assignment {"," named_param_assignment}] [":" "config"]
+/// ```
+fn parse_config_rule(p: &mut dyn AbstractParser) -> ReportedResult<ConfigRule> {
+    let mut span = p.peek(0).1;
+    let clause = match p.peek(0).0 {
+        Keyword(Kw::Default) => {
+            p.bump();
+            ConfigClause::Default
+        }
+        Keyword(Kw::Instance) => {
+            p.bump();
+            ConfigClause::Instance(parse_hierarchical_name(p, "instance name")?)
+        }
+        Keyword(Kw::Cell) => {
+            p.bump();
+            ConfigClause::Cell(parse_config_cell(p)?)
+        }
+        tkn => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `default`, `instance`, or `cell` rule, instead got `{}`",
+                    tkn
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+    let action = match p.peek(0) {
+        (Keyword(Kw::Liblist), _) => {
+            p.bump();
+            let mut libs = Vec::new();
+            while p.is_ident() {
+                libs.push(parse_identifier(p, "library name")?);
+            }
+            ConfigAction::Liblist(libs)
+        }
+        (Keyword(Kw::Use), _) => {
+            p.bump();
+            let cell = if p.is_ident() {
+                Some(parse_config_cell(p)?)
+            } else {
+                None
+            };
+            let mut params = Vec::new();
+            if p.peek(0).0 == Period {
+                loop {
+                    params.push(parse_parameter_assignment(p)?);
+                    if !p.try_eat(Comma) {
+                        break;
+                    }
+                }
+            }
+            let config = if p.try_eat(Colon) {
+                p.require_reported(Keyword(Kw::Config))?;
+                true
+            } else {
+                false
+            };
+            ConfigAction::Use {
+                cell: cell,
+                params: params,
+                config: config,
+            }
+        }
+        (tkn, sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `liblist` or `use` clause, instead got `{}`",
+                    tkn
+                ))
+                .span(sp),
+            );
+            return Err(());
+        }
+    };
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ConfigRule {
+        span: span,
+        clause: clause,
+        action: action,
+    })
+}
+
+fn parse_hierarchy_item(p: &mut dyn AbstractParser) -> ReportedResult<HierarchyItem> {
+    // Attach the preceding attribute instances to the item. This happens
+    // outside of the actual item parsing, since some items are parsed
+    // speculatively.
+    let attrs = p.take_attrs();
+    let mut item = parse_hierarchy_item_data(p)?;
+    match item {
+        HierarchyItem::Procedure(ref mut x) => x.attrs = attrs,
+        HierarchyItem::ContAssign(ref mut x) => x.attrs = attrs,
+        HierarchyItem::NetDecl(ref mut x) => x.attrs = attrs,
+        HierarchyItem::VarDecl(ref mut x) => x.attrs = attrs,
+        HierarchyItem::Inst(ref mut x) => x.attrs = attrs,
+        _ => warn_ignored_attrs(p, &attrs),
+    }
+    Ok(item)
+}
+
+/// Parse a hierarchy item. In tolerant mode, recover from errors by skipping
+/// to the end of the item, but not past `term`, and produce an error item.
+fn parse_hierarchy_item_or_error(
+    p: &mut dyn AbstractParser,
+    term: Token,
+) -> ReportedResult<HierarchyItem> {
+    let span = p.peek(0).1;
+    let consumed = p.consumed();
+    match parse_hierarchy_item(p) {
+        Err(()) if p.is_tolerant() && !p.is_fatal() => {
+            p.recover_item(&[term]);
+            if p.consumed() == consumed {
+                return Err(());
+            }
+            Ok(HierarchyItem::Error(Span::union(span, p.last_span())))
+        }
+        x => x,
+    }
+}
+
+fn parse_hierarchy_item_data(p: &mut dyn AbstractParser) -> ReportedResult<HierarchyItem> {
+    // Consume optional leading label.
+    let label = if p.is_ident() && p.peek(1).0 == Colon {
+        let (name, span) = p.eat_ident("label")?;
+        p.bump();
+        Some((name, span))
+    } else {
+        None
+    };
+
+    // First attempt the simple cases where a keyword reliably identifies the
+    // following item.
+    match p.peek(0).0 {
+        // Keyword(Kw::Localparam) => return parse_localparam_decl(p).map(|x| HierarchyItem::LocalparamDecl(x)),
+        // Keyword(Kw::Parameter)  => return parse_parameter_decl(p).map(|x| HierarchyItem::ParameterDecl(x)),
+        Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
+            let decl = parse_param_decl(p, false)?;
+            p.require_reported(Semicolon)?;
+            return Ok(HierarchyItem::ParamDecl(decl));
+        }
+        Keyword(Kw::Modport) => {
+            return parse_modport_decl(p).map(|x| HierarchyItem::ModportDecl(x))
+        }
+        Keyword(Kw::Class) => return parse_class_decl(p).map(|x| HierarchyItem::ClassDecl(x)),
+        Keyword(Kw::Virtual) if p.peek(1).0 == Keyword(Kw::Class) => {
+            return parse_class_decl(p).map(|x| HierarchyItem::ClassDecl(x))
+        }
+        Keyword(Kw::Typedef) => return parse_typedef(p).map(|x| HierarchyItem::Typedef(x)),
+        Keyword(Kw::Import) => return parse_import_decl(p).map(|x| HierarchyItem::ImportDecl(x)),
+        Keyword(Kw::Export) => return parse_export_decl(p).map(|x| HierarchyItem::ExportDecl(x)),
+
+        // Structured procedures as per IEEE 1800-2009 section 9.2
+        Keyword(Kw::Initial) => {
+            return parse_procedure(p, ProcedureKind::Initial).map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::Always) => {
+            return parse_procedure(p, ProcedureKind::Always).map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::AlwaysComb) => {
+            return parse_procedure(p, ProcedureKind::AlwaysComb)
+                .map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::AlwaysLatch) => {
+            return parse_procedure(p, ProcedureKind::AlwaysLatch)
+                .map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::AlwaysFf) => {
+            return parse_procedure(p, ProcedureKind::AlwaysFf)
+                .map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::Final) => {
+            return parse_procedure(p, ProcedureKind::Final).map(|x| HierarchyItem::Procedure(x));
+        }
+        Keyword(Kw::Function) | Keyword(Kw::Task) => {
+            return parse_subroutine_decl(p).map(|x| HierarchyItem::SubroutineDecl(x));
+        }
+
+        // Port declarations
+        Keyword(Kw::Inout) | Keyword(Kw::Input) | Keyword(Kw::Output) | Keyword(Kw::Ref) => {
             return parse_port_decl(p).map(|x| HierarchyItem::PortDecl(x));
         }
 
@@ -1062,6 +1674,9 @@ fn parse_hierarchy_item(p: &mut dyn AbstractParser) -> ReportedResult<HierarchyI
             return parse_continuous_assign(p).map(|x| HierarchyItem::ContAssign(x));
         }
 
+        // Parameter override
+        Keyword(Kw::Defparam) => return parse_defparam(p).map(|x| HierarchyItem::Defparam(x)),
+
         // Genvar declaration
         Keyword(Kw::Genvar) => {
             p.bump();
@@ -1088,7 +1703,14 @@ fn parse_hierarchy_item(p: &mut dyn AbstractParser) -> ReportedResult<HierarchyI
         | Keyword(Kw::Assume)
         | Keyword(Kw::Cover)
         | Keyword(Kw::Expect)
-        | Keyword(Kw::Restrict) => return parse_assertion(p).map(|x| HierarchyItem::Assertion(x)),
+        | Keyword(Kw::Restrict) => {
+            return parse_assertion(p).map(|mut x| {
+                x.label = label;
+                HierarchyItem::Assertion(x)
+            })
+        }
+        Keyword(Kw::Sequence) => return parse_seq_decl(p).map(|x| HierarchyItem::SeqDecl(x)),
+        Keyword(Kw::Property) => return parse_prop_decl(p).map(|x| HierarchyItem::PropDecl(x)),
         Semicolon => {
             p.bump();
             return Ok(HierarchyItem::Dummy);
@@ -1357,14 +1979,31 @@ fn parse_modport_port_decl(p: &mut dyn AbstractParser) -> ReportedResult<ast::Mo
     // keywords.
     if let Some(dir) = as_port_direction(tkn) {
         p.bump();
+        let mut port = vec![];
         loop {
+            let mut port_span = p.peek(0).1;
             if p.try_eat(Period) {
                 let name = parse_identifier(p, "port name")?;
-                p.require_reported(OpenDelim(Paren))?;
-                let expr = parse_expr(p)?;
-                p.require_reported(CloseDelim(Paren))?;
+                let expr = flanked(p, Paren, |p| {
+                    Ok(if p.peek(0).0 == CloseDelim(Paren) {
+                        None
+                    } else {
+                        Some(parse_expr(p)?)
+                    })
+                })?;
+                port_span.expand(p.last_span());
+                port.push(ast::ModportSimplePort {
+                    span: port_span,
+                    name,
+                    expr,
+                });
             } else {
                 let name = parse_identifier(p, "port name")?;
+                port.push(ast::ModportSimplePort {
+                    span: port_span,
+                    name,
+                    expr: None,
+                });
             }
 
             // Decide whether we should continue iterating and thus consuming
@@ -1382,7 +2021,9 @@ fn parse_modport_port_decl(p: &mut dyn AbstractParser) -> ReportedResult<ast::Mo
                 _ => break,
             }
         }
-        return Ok(ast::ModportPort::Port);
+        let mut span = span;
+        span.expand(p.last_span());
+        return Ok(ast::ModportPort::Simple { span, dir, port });
     }
 
     // TODO: Parse modport_tf_port.
@@ -1821,22 +2462,30 @@ fn try_dimension(p: &mut dyn AbstractParser) -> ReportedResult<Option<(TypeDim,
     let mut span = p.last_span();
 
     let dim = match p.peek(0).0 {
-        CloseDelim(Brack) => {
-            p.bump();
-            TypeDim::Unsized
-        }
+        // The closing bracket is consumed below.
+        CloseDelim(Brack) => TypeDim::Unsized,
         Operator(Op::Mul) => {
             p.bump();
-            TypeDim::Associative
+            TypeDim::Associative(None)
         }
         Dollar => {
             p.bump();
-            if p.try_eat(Colon) {
+            let max = if p.try_eat(Colon) {
                 Some(parse_expr(p)?)
             } else {
                 None
             };
-            TypeDim::Queue
+            TypeDim::Queue(max)
+        }
+        // Associative arrays may be indexed by a type, e.g. `[string]`. Only
+        // consider this if the dimension is not a valid expression, such that
+        // `[N]` with `N` being a parameter remains a fixed-size dimension.
+        _ if {
+            let mut bp = BranchParser::new(p);
+            parse_expr(&mut bp).is_err()
+        } =>
+        {
+            TypeDim::Associative(Some(parse_explicit_type(p)?))
         }
         _ => {
             // What's left must either be a single constant expression, or a range
@@ -1946,6 +2595,32 @@ fn parse_expr(p: &mut dyn AbstractParser) -> ReportedResult<Expr> {
     parse_expr_prec(p, Precedence::Min)
 }
 
+/// Parse an expression. In tolerant mode, recover from errors by skipping to
+/// one of the `terminators`, but not past it, and produce an error expression.
+fn parse_expr_or_error(
+    p: &mut dyn AbstractParser,
+    precedence: Precedence,
+    terminators: &[Token],
+) -> ReportedResult<Expr> {
+    let span = p.peek(0).1;
+    let consumed = p.consumed();
+    match parse_expr_prec(p, precedence) {
+        Ok(x) => Ok(x),
+        Err(()) if p.is_tolerant() && !p.is_fatal() => {
+            p.recover_balanced(terminators, false);
+            Ok(Expr {
+                span: if p.consumed() == consumed {
+                    span
+                } else {
+                    Span::union(span, p.last_span())
+                },
+                data: ErrorExpr,
+            })
+        }
+        Err(()) => Err(()),
+    }
+}
+
 fn parse_expr_prec(p: &mut dyn AbstractParser, precedence: Precedence) -> ReportedResult<Expr> {
     // TODO: Keep track of the location here and pass that to the
     // parse_expr_first and parse_expr_suffix calls further down. This will
@@ -1977,6 +2652,17 @@ fn parse_expr_prec(p: &mut dyn AbstractParser, precedence: Precedence) -> Report
                     data: ConstructorCallExpr(args),
                 });
             } else {
+                // A bare `new` without any arguments is a constructor call,
+                // whereas `new <expr>` creates a shallow copy of an object.
+                match p.peek(0).0 {
+                    Semicolon | Comma | CloseDelim(_) => {
+                        return Ok(Expr {
+                            span: span,
+                            data: ConstructorCallExpr(vec![]),
+                        });
+                    }
+                    _ => (),
+                }
                 let expr = parse_expr(p)?;
                 span.expand(p.last_span());
                 return Ok(Expr {
@@ -2026,7 +2712,7 @@ fn parse_expr_suffix(
     let (tkn, sp) = p.peek(0);
     match tkn {
         // Index: "[" range_expression "]"
-        OpenDelim(Brack) if precedence <= Precedence::Postfix => {
+        OpenDelim(Brack) if precedence <= Precedence::Postfix && !is_seqrep(p) => {
             p.bump();
             let expr = match parse_range_expr(p) {
                 Ok(x) => x,
@@ -2059,7 +2745,12 @@ fn parse_expr_suffix(
         // expr "." ident
         Period if precedence <= Precedence::Scope => {
             p.bump();
-            let (name, name_span) = p.eat_ident("member name")?;
+            // Accept `new` as a member name to support `super.new(...)`.
+            let (name, name_span) = if p.try_eat(Keyword(Kw::New)) {
+                (get_name_table().intern("new", true), p.last_span())
+            } else {
+                p.eat_ident("member name")?
+            };
             let expr = Expr {
                 span: Span::union(prefix.span, p.last_span()),
                 data: MemberExpr {
@@ -2134,20 +2825,7 @@ fn parse_expr_suffix(
         Keyword(Kw::Inside) if precedence <= Precedence::Relational => {
             p.bump();
             let set = flanked(p, Brace, |p| {
-                comma_list_nonempty(p, CloseDelim(Brace), "range", |p| {
-                    if p.peek(0).0 == OpenDelim(Brack) {
-                        p.require_reported(OpenDelim(Brack))?;
-                        let mut sp = p.last_span();
-                        let lo = parse_expr(p)?;
-                        p.require_reported(Colon)?;
-                        let hi = parse_expr(p)?;
-                        p.require_reported(CloseDelim(Brack))?;
-                        sp.expand(p.last_span());
-                        Ok(ValueRange::Range { lo, hi, span: sp })
-                    } else {
-                        Ok(ValueRange::Single(parse_expr(p)?))
-                    }
-                })
+                comma_list_nonempty(p, CloseDelim(Brace), "range", parse_value_range)
             })?;
             let expr = Expr {
                 span: Span::union(prefix.span, p.last_span()),
@@ -2156,6 +2834,21 @@ fn parse_expr_suffix(
             return parse_expr_suffix(p, expr, precedence);
         }
 
+        // expr "with" "{" {constraint_item} "}"
+        Keyword(Kw::With)
+            if precedence <= Precedence::Postfix && p.peek(1).0 == OpenDelim(Brace) =>
+        {
+            p.bump();
+            let items = flanked(p, Brace, |p| {
+                repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+            })?;
+            let expr = Expr {
+                span: Span::union(prefix.span, p.last_span()),
+                data: RandomizeWithExpr(Box::new(prefix), items),
+            };
+            return parse_expr_suffix(p, expr, precedence);
+        }
+
         // expr "'" "(" expr ")"
         Apostrophe if precedence <= Precedence::Postfix => {
             p.bump();
@@ -2239,9 +2932,19 @@ fn parse_expr_first(p: &mut dyn AbstractParser, precedence: Precedence) -> Repor
             });
         }
 
-        (Keyword(Kw::Tagged), sp) => {
-            p.add_diag(DiagBuilder2::error("Tagged union expressions not implemented").span(sp));
-            return Err(());
+        (Keyword(Kw::Tagged), _) => {
+            p.bump();
+            let member = parse_identifier(p, "union member name")?;
+            let expr = match p.peek(0).0 {
+                Period => Some(Box::new(parse_pattern_var(p)?)),
+                Semicolon | Colon | Comma | CloseDelim(_) | Eof => None,
+                Keyword(kw) if kw != Kw::Tagged => None,
+                _ => Some(Box::new(parse_expr_prec(p, Precedence::Unary)?)),
+            };
+            return Ok(Expr {
+                span: Span::union(first, p.last_span()),
+                data: TaggedUnionExpr { member, expr },
+            });
         }
 
         _ => (),
@@ -2301,6 +3004,29 @@ fn parse_primary_expr(p: &mut dyn AbstractParser) -> ReportedResult<Expr> {
             });
         }
 
+        // Class handles
+        Keyword(Kw::This) => {
+            p.bump();
+            return Ok(Expr {
+                span: sp,
+                data: ThisExpr,
+            });
+        }
+        Keyword(Kw::Super) => {
+            p.bump();
+            return Ok(Expr {
+                span: sp,
+                data: SuperExpr,
+            });
+        }
+        Keyword(Kw::Null) => {
+            p.bump();
+            return Ok(Expr {
+                span: sp,
+                data: NullExpr,
+            });
+        }
+
         // Concatenation and empty queue
         OpenDelim(Brace) => {
             p.bump();
@@ -2735,9 +3461,58 @@ fn parse_port_list(p: &mut dyn AbstractParser) -> ReportedResult<Vec<Port>> {
     }
 
     p.require_reported(CloseDelim(Paren))?;
+
+    // In a non-ANSI port list, a port such as `a` or `a[3:0]` refers to (a
+    // part-select of) the port declared in the module body, rather than
+    // declaring a variable or unpacked array. Convert these into the
+    // corresponding port expressions.
+    if v.first().map(|port| port.is_nonansi()).unwrap_or(false) {
+        for port in &mut v {
+            let expr = match *port {
+                Port::Named { name, ref dims, .. } if port.is_nonansi() => {
+                    port_select_expr(name, dims)
+                }
+                _ => continue,
+            };
+            *port = Port::Implicit(expr);
+        }
+    }
     Ok(v)
 }
 
+/// Convert a port name followed by dimensions, e.g. `a[7:4]`, into the
+/// equivalent index expression.
+fn port_select_expr(name: Identifier, dims: &[TypeDim]) -> Expr {
+    let mut expr = Expr {
+        span: name.span,
+        data: IdentExpr(name),
+    };
+    for dim in dims {
+        let index = match *dim {
+            TypeDim::Expr(ref index) => index.clone(),
+            TypeDim::Range(ref lhs, ref rhs) => Expr {
+                span: Span::union(lhs.span, rhs.span),
+                data: RangeExpr {
+                    mode: RangeMode::Absolute,
+                    lhs: Box::new(lhs.clone()),
+                    rhs: Box::new(rhs.clone()),
+                },
+            },
+            // Other dimensions cannot be expressed as an index and are left
+            // for the port lowering to reject.
+            _ => continue,
+        };
+        expr = Expr {
+            span: Span::union(expr.span, index.span),
+            data: IndexExpr {
+                indexee: Box::new(expr),
+                index: Box::new(index),
+            },
+        };
+    }
+    expr
+}
+
 /// Parse one port in a module or interface port list. The `prev` argument shall
 /// be a reference to the previously parsed port, or `None` if this is the first
 /// port in the list. This is required since ports inherit certain information
@@ -3081,6 +3856,7 @@ fn parse_procedure(p: &mut dyn AbstractParser, kind: ProcedureKind) -> ReportedR
     span.expand(p.last_span());
     Ok(Procedure {
         span: span,
+        attrs: vec![],
         kind: kind,
         stmt: stmt,
     })
@@ -3167,6 +3943,7 @@ fn parse_subroutine_prototype(p: &mut dyn AbstractParser) -> ReportedResult<Subr
         lifetime: lifetime,
         name: name,
         args: args,
+        retty: retty,
     })
 }
 
@@ -3200,7 +3977,13 @@ fn parse_subroutine_prototype_tail(
             let mut pp = ParallelParser::new();
             pp.add("explicit type", |p| {
                 let ty = parse_explicit_type(p)?;
-                Ok((ty, tail(p)?))
+                let name = tail(p)?;
+                // A lone identifier is the name of a port with implicit type,
+                // as in `output int a, b`, rather than a named type.
+                match (&ty.data, &name) {
+                    (&ast::NamedType(_), &None) if ty.dims.is_empty() => Err(()),
+                    _ => Ok((ty, name)),
+                }
             });
             pp.add("implicit type", |p| {
                 let ty = parse_implicit_type(p)?;
@@ -3341,28 +4124,50 @@ fn parse_subroutine_item(p: &mut dyn AbstractParser) -> ReportedResult<Subroutin
 }
 
 fn parse_stmt(p: &mut dyn AbstractParser) -> ReportedResult<Stmt> {
+    let attrs = p.take_attrs();
     let mut span = p.peek(0).1;
+    let consumed = p.consumed();
 
     // Null statements simply consist of a semicolon.
     if p.try_eat(Semicolon) {
-        return Ok(Stmt::new_null(span));
+        let mut stmt = Stmt::new_null(span);
+        stmt.attrs = attrs;
+        return Ok(stmt);
     }
 
     // Consume the optional statement label.
+    let mut label_span = None;
     let mut label = if p.is_ident() && p.peek(1).0 == Colon {
-        let (n, _) = p.eat_ident("statement label")?;
+        let (n, sp) = p.eat_ident("statement label")?;
         p.bump(); // eat the colon
+        label_span = Some(sp);
         Some(n)
     } else {
         None
     };
 
-    // Parse the actual statement item.
-    let data = parse_stmt_data(p, &mut label)?;
+    // Parse the actual statement item. In tolerant mode, recover from errors
+    // by skipping to the end of the statement.
+    let data = match parse_stmt_data(p, &mut label) {
+        Ok(AssertionStmt(mut x)) => {
+            x.label = label.and_then(|n| label_span.map(|sp| (n, sp)));
+            AssertionStmt(x)
+        }
+        Ok(x) => x,
+        Err(()) if p.is_tolerant() && !p.is_fatal() => {
+            p.recover_item(&[]);
+            if p.consumed() == consumed {
+                return Err(());
+            }
+            ErrorStmt
+        }
+        Err(()) => return Err(()),
+    };
     span.expand(p.last_span());
 
     Ok(Stmt {
         span: span,
+        attrs: attrs,
         label: label,
         data: data,
     })
@@ -3478,35 +4283,7 @@ fn parse_stmt_data(
         }
         Keyword(Kw::Foreach) => {
             p.bump();
-            let (expr, vars) = flanked(p, Paren, |p| {
-                let expr = parse_expr_prec(p, Precedence::Scope)?;
-                let vars = flanked(p, Brack, |p| {
-                    let mut v = Vec::new();
-                    while p.peek(0).0 != Eof && p.peek(0).0 != CloseDelim(Brack) {
-                        if p.peek(0).0 != Comma {
-                            v.push(Some(parse_identifier(p, "loop variable name")?));
-                        } else {
-                            v.push(None)
-                        }
-                        match p.peek(0) {
-                            (Comma, _) => p.bump(),
-                            (CloseDelim(Brack), _) => (),
-                            (tkn, sp) => {
-                                p.add_diag(
-                                    DiagBuilder2::error(format!(
-                                        "expected , or ] after loop variable; found {} instead",
-                                        tkn
-                                    ))
-                                    .span(sp),
-                                );
-                                return Err(());
-                            }
-                        }
-                    }
-                    Ok(v)
-                })?;
-                Ok((expr, vars))
-            })?;
+            let (expr, vars) = flanked(p, Paren, parse_foreach_header)?;
             let stmt = Box::new(parse_stmt(p)?);
             ForeachStmt(expr, vars, stmt)
         }
@@ -3597,9 +4374,27 @@ fn parse_stmt_data(
             }
         }
 
+        // Event triggers
+        Operator(Op::LogicImpl) => {
+            p.bump();
+            let expr = parse_expr(p)?;
+            p.require_reported(Semicolon)?;
+            EventTriggerStmt(expr)
+        }
+
         // Everything else needs special treatment as things such as variable
         // declarations look very similar to other expressions.
         _ => {
+            // In tolerant mode, statements that start out as an assignment
+            // are parsed as one even if the right-hand side is broken, such
+            // that the assignment survives with an error expression.
+            let assign = p.is_tolerant() && {
+                let mut bp = BranchParser::new(p);
+                parse_expr_prec(&mut bp, Precedence::Postfix).is_ok() && {
+                    let tkn = bp.peek(0).0;
+                    as_assign_operator(tkn).is_some() || tkn == Operator(Op::Leq)
+                }
+            };
             let result = {
                 let mut pp = ParallelParser::new();
                 pp.add("variable declaration", |p| {
@@ -3607,12 +4402,26 @@ fn parse_stmt_data(
                 });
                 pp.add("assign statement", |p| parse_assign_stmt(p));
                 pp.add("expression statement", |p| parse_expr_stmt(p));
-                pp.finish(p, "statement")
+                if assign {
+                    let mut bp = BranchParser::new(p);
+                    match pp.finish(&mut bp, "statement") {
+                        Ok(x) => {
+                            bp.commit();
+                            Ok(x)
+                        }
+                        Err(()) => parse_assign_stmt(p),
+                    }
+                } else {
+                    pp.finish(p, "statement")
+                }
             };
             match result {
                 Ok(x) => x,
                 Err(_) => {
-                    p.recover_balanced(&[Semicolon], true);
+                    // In tolerant mode the caller recovers.
+                    if !p.is_tolerant() {
+                        p.recover_balanced(&[Semicolon], true);
+                    }
                     return Err(());
                 }
             }
@@ -3729,6 +4538,7 @@ fn parse_continuous_assign(p: &mut dyn AbstractParser) -> ReportedResult<ContAss
     span.expand(p.last_span());
     Ok(ContAssign {
         span: span,
+        attrs: vec![],
         strength: strength,
         delay: None,
         delay_control: delay_control,
@@ -3736,6 +4546,15 @@ fn parse_continuous_assign(p: &mut dyn AbstractParser) -> ReportedResult<ContAss
     })
 }
 
+fn parse_defparam(p: &mut dyn AbstractParser) -> ReportedResult<Defparam> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Defparam))?;
+    let assignments = comma_list_nonempty(p, Semicolon, "parameter override", parse_assignment)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(Defparam { span, assignments })
+}
+
 fn parse_if_or_case(
     p: &mut dyn AbstractParser,
     up: Option<UniquePriority>,
@@ -3880,7 +4699,7 @@ fn parse_case(
 fn parse_if(p: &mut dyn AbstractParser, up: Option<UniquePriority>) -> ReportedResult<StmtData> {
     // Parse the condition expression surrounded by parenthesis.
     p.require_reported(OpenDelim(Paren))?;
-    let cond = match parse_expr(p) {
+    let cond = match parse_expr_or_error(p, Precedence::Min, &[CloseDelim(Paren)]) {
         Ok(x) => x,
         Err(()) => {
             p.recover_balanced(&[CloseDelim(Paren)], true);
@@ -3928,7 +4747,7 @@ fn try_delay_control(p: &mut dyn AbstractParser) -> ReportedResult<Option<DelayC
         }
 
         // Literals
-        Literal(Number(..)) | Literal(Time(..)) | Ident(..) => {
+        Literal(Number(..)) | Literal(Real(..)) | Literal(Time(..)) | Ident(..) => {
             parse_expr_first(p, Precedence::Max)?
         }
 
@@ -4000,7 +4819,7 @@ fn try_cycle_delay(p: &mut dyn AbstractParser) -> ReportedResult<Option<CycleDel
 fn parse_assignment(p: &mut dyn AbstractParser) -> ReportedResult<(Expr, Expr)> {
     let lhs = parse_expr_prec(p, Precedence::Postfix)?;
     p.require_reported(Operator(Op::Assign))?;
-    let rhs = parse_expr_prec(p, Precedence::Assignment)?;
+    let rhs = parse_expr_or_error(p, Precedence::Assignment, &[Comma, Semicolon])?;
     Ok((lhs, rhs))
 }
 
@@ -4013,7 +4832,7 @@ fn parse_assign_stmt(p: &mut dyn AbstractParser) -> ReportedResult<StmtData> {
     // expression is followed by an assignment operator.
     if let Some(op) = as_assign_operator(tkn) {
         p.bump();
-        let rhs = parse_expr(p)?;
+        let rhs = parse_expr_or_error(p, Precedence::Min, &[Semicolon])?;
         p.require_reported(Semicolon)?;
         return Ok(BlockingAssignStmt {
             lhs: expr,
@@ -4031,7 +4850,7 @@ fn parse_assign_stmt(p: &mut dyn AbstractParser) -> ReportedResult<StmtData> {
         let event_control = /*try_event_control(p)?*/ None;
 
         // Parse the right-hand side of the assignment.
-        let rhs = parse_expr(p)?;
+        let rhs = parse_expr_or_error(p, Precedence::Min, &[Semicolon])?;
         p.require_reported(Semicolon)?;
 
         return Ok(NonblockingAssignStmt {
@@ -4307,18 +5126,47 @@ fn parse_generate_if(p: &mut dyn AbstractParser) -> ReportedResult<GenerateIf> {
     })
 }
 
+/// Parse a case-generate construct.
+/// ```text
+/// "case" "(" expr ")" {case_generate_item} "endcase"
+/// case_generate_item: expr {"," expr} ":" generate_block
+/// case_generate_item: "default" [":"] generate_block
+/// ```
 fn parse_generate_case(p: &mut dyn AbstractParser) -> ReportedResult<GenerateCase> {
     let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Case))?;
-    let q = p.last_span();
-    p.add_diag(DiagBuilder2::error("Don't know how to parse case-generate statements").span(q));
-    Err(())
-}
+    let expr = flanked(p, Paren, parse_expr)?;
 
-fn parse_generate_block(p: &mut dyn AbstractParser) -> ReportedResult<GenerateBlock> {
-    let mut span = p.peek(0).1;
-
-    // Parse the optional block label.
+    // Parse the case items.
+    let mut items = Vec::new();
+    while p.peek(0).0 != Keyword(Kw::Endcase) && p.peek(0).0 != Eof {
+        if p.try_eat(Keyword(Kw::Default)) {
+            p.try_eat(Colon);
+            let block = parse_generate_block(p)?;
+            items.push(GenerateCaseItem::Default(block));
+        } else {
+            let mut exprs = vec![parse_expr(p)?];
+            while p.try_eat(Comma) {
+                exprs.push(parse_expr(p)?);
+            }
+            p.require_reported(Colon)?;
+            let block = parse_generate_block(p)?;
+            items.push(GenerateCaseItem::Expr(exprs, block));
+        }
+    }
+    p.require_reported(Keyword(Kw::Endcase))?;
+    span.expand(p.last_span());
+    Ok(GenerateCase {
+        span: span,
+        expr: expr,
+        items: items,
+    })
+}
+
+fn parse_generate_block(p: &mut dyn AbstractParser) -> ReportedResult<GenerateBlock> {
+    let mut span = p.peek(0).1;
+
+    // Parse the optional block label.
     let mut label = if p.is_ident() && p.peek(1).0 == Colon {
         let (n, _) = p.eat_ident("generate block label")?;
         p.require_reported(Colon)?;
@@ -4521,10 +5369,28 @@ fn parse_class_item(p: &mut dyn AbstractParser) -> ReportedResult<ClassItem> {
 
     // Parse the optional class item qualifiers.
     let qualifiers = parse_class_item_qualifiers(p)?;
+    let konst = qualifiers
+        .iter()
+        .any(|&(q, _)| q == ClassItemQualifier::Const);
+
+    // Parse "pure virtual" task and function prototypes, which have no body.
+    if qualifiers
+        .iter()
+        .any(|&(q, _)| q == ClassItemQualifier::Pure)
+    {
+        let proto = parse_subroutine_prototype(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span: span,
+            qualifiers: qualifiers,
+            data: ClassItemData::ExternSubroutine(proto),
+        });
+    }
 
     let data = {
         let mut pp = ParallelParser::new();
         pp.add("class property", |p| {
+            let mut span = p.peek(0).1;
             let ty = parse_data_type(p)?;
             let names = comma_list_nonempty(
                 p,
@@ -4533,7 +5399,16 @@ fn parse_class_item(p: &mut dyn AbstractParser) -> ReportedResult<ClassItem> {
                 parse_variable_decl_assignment,
             )?;
             p.require_reported(Semicolon)?;
-            Ok(ClassItemData::Property)
+            span.expand(p.last_span());
+            Ok(ClassItemData::Property(VarDecl {
+                span: span,
+                attrs: vec![],
+                konst: konst,
+                var: false,
+                lifetime: None,
+                ty: ty,
+                names: names,
+            }))
         });
         pp.add("class function or task", |p| {
             parse_subroutine_decl(p).map(|d| ClassItemData::SubroutineDecl(d))
@@ -4574,17 +5449,6 @@ fn parse_class_item_qualifiers(
     Ok(v)
 }
 
-fn parse_class_method(p: &mut dyn AbstractParser) -> ReportedResult<ClassItem> {
-    println!("Parsing class method");
-    Err(())
-}
-
-fn parse_class_property(p: &mut dyn AbstractParser) -> ReportedResult<ClassItem> {
-    println!("Parsing class property");
-    p.try_eat(Keyword(Kw::Rand));
-    Err(())
-}
-
 fn parse_constraint(p: &mut dyn AbstractParser) -> ReportedResult<Constraint> {
     let mut span = p.peek(0).1;
 
@@ -4611,12 +5475,12 @@ fn parse_constraint(p: &mut dyn AbstractParser) -> ReportedResult<Constraint> {
     // Parse the constraint name.
     let (name, name_span) = p.eat_ident("constraint name")?;
 
-    let items = if p.try_eat(Semicolon) {
+    let (kind, items) = if p.try_eat(Semicolon) {
         let kind = match kind {
             ConstraintKind::Decl => ConstraintKind::Proto,
             x => x,
         };
-        Vec::new()
+        (kind, Vec::new())
     } else {
         // Make sure that no "extern" or "pure" keyword was used, as these are
         // only valid for prototypes.
@@ -4627,9 +5491,10 @@ fn parse_constraint(p: &mut dyn AbstractParser) -> ReportedResult<Constraint> {
             );
             return Err(());
         }
-        flanked(p, Brace, |p| {
+        let items = flanked(p, Brace, |p| {
             repeat_until(p, CloseDelim(Brace), parse_constraint_item)
-        })?
+        })?;
+        (kind, items)
     };
     span.expand(p.last_span());
 
@@ -4654,27 +5519,138 @@ fn parse_constraint_item(p: &mut dyn AbstractParser) -> ReportedResult<Constrain
 }
 
 fn parse_constraint_item_data(p: &mut dyn AbstractParser) -> ReportedResult<ConstraintItemData> {
-    // Handle the trivial cases that start with a keyword first.
+    // Handle the cases that start with a keyword or brace first.
+    if p.peek(0).0 == OpenDelim(Brace) {
+        let items = flanked(p, Brace, |p| {
+            repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+        })?;
+        return Ok(ConstraintItemData::Set(items));
+    }
+
     if p.try_eat(Keyword(Kw::If)) {
-        let q = p.last_span();
-        p.add_diag(DiagBuilder2::error("Don't know how to parse `if` constraint items").span(q));
-        return Err(());
+        let cond = flanked(p, Paren, parse_expr)?;
+        let main = Box::new(parse_constraint_item(p)?);
+        let other = if p.try_eat(Keyword(Kw::Else)) {
+            Some(Box::new(parse_constraint_item(p)?))
+        } else {
+            None
+        };
+        return Ok(ConstraintItemData::If(cond, main, other));
     }
 
     if p.try_eat(Keyword(Kw::Foreach)) {
-        let q = p.last_span();
-        p.add_diag(
-            DiagBuilder2::error("Don't know how to parse `foreach` constraint items").span(q),
-        );
-        return Err(());
+        let (expr, vars) = flanked(p, Paren, parse_foreach_header)?;
+        let item = Box::new(parse_constraint_item(p)?);
+        return Ok(ConstraintItemData::Foreach(expr, vars, item));
+    }
+
+    if p.try_eat(Keyword(Kw::Solve)) {
+        let before = comma_list_nonempty(p, Keyword(Kw::Before), "variable", parse_expr)?;
+        p.require_reported(Keyword(Kw::Before))?;
+        let after = comma_list_nonempty(p, Semicolon, "variable", parse_expr)?;
+        p.require_reported(Semicolon)?;
+        return Ok(ConstraintItemData::Solve(before, after));
     }
 
-    // If we arrive here, the item starts with an expression.
-    let expr = parse_expr(p)?;
+    // If we arrive here, the item starts with an expression. Stop before any
+    // implication, since its right hand side is a constraint rather than an
+    // expression.
+    let expr = parse_expr_prec(p, Precedence::Implication)?;
+    if p.try_eat(Operator(Op::LogicImpl)) {
+        let item = Box::new(parse_constraint_item(p)?);
+        return Ok(ConstraintItemData::Implication(expr, item));
+    }
+    if p.try_eat(Keyword(Kw::Dist)) {
+        let items = flanked(p, Brace, |p| {
+            comma_list_nonempty(p, CloseDelim(Brace), "distribution item", parse_dist_item)
+        })?;
+        p.require_reported(Semicolon)?;
+        return Ok(ConstraintItemData::Dist(expr, items));
+    }
     p.require_reported(Semicolon)?;
     Ok(ConstraintItemData::Expr(expr))
 }
 
+/// Parse the array and index variables of a `foreach` loop, such as
+/// `a[i, , j]`.
+fn parse_foreach_header(
+    p: &mut dyn AbstractParser,
+) -> ReportedResult<(Expr, Vec<Option<Identifier>>)> {
+    let expr = parse_expr_prec(p, Precedence::Scope)?;
+    let vars = flanked(p, Brack, |p| {
+        let mut v = Vec::new();
+        while p.peek(0).0 != Eof && p.peek(0).0 != CloseDelim(Brack) {
+            if p.peek(0).0 != Comma {
+                v.push(Some(parse_identifier(p, "loop variable name")?));
+            } else {
+                v.push(None)
+            }
+            match p.peek(0) {
+                (Comma, _) => p.bump(),
+                (CloseDelim(Brack), _) => (),
+                (tkn, sp) => {
+                    p.add_diag(
+                        DiagBuilder2::error(format!(
+                            "expected , or ] after loop variable; found {} instead",
+                            tkn
+                        ))
+                        .span(sp),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        Ok(v)
+    })?;
+    Ok((expr, vars))
+}
+
+/// Parse an item of a distribution, such as `[1:5] :/ 2`.
+fn parse_dist_item(p: &mut dyn AbstractParser) -> ReportedResult<DistItem> {
+    let mut span = p.peek(0).1;
+    let range = parse_value_range(p)?;
+    let weight = if p.try_eat(Colon) {
+        let kind = match p.peek(0).0 {
+            Operator(Op::Assign) => DistWeight::PerValue,
+            Operator(Op::Div) => DistWeight::PerRange,
+            _ => {
+                let q = p.peek(0).1;
+                p.add_diag(
+                    DiagBuilder2::error("expected `:=` or `:/` after distribution range").span(q),
+                );
+                return Err(());
+            }
+        };
+        p.bump();
+        Some((kind, parse_expr(p)?))
+    } else {
+        None
+    };
+    span.expand(p.last_span());
+    Ok(DistItem {
+        span,
+        range,
+        weight,
+    })
+}
+
+/// Parse a single value or a range `[lo:hi]`, as used in `inside` and `dist`
+/// expressions.
+fn parse_value_range(p: &mut dyn AbstractParser) -> ReportedResult<ValueRange> {
+    if p.peek(0).0 == OpenDelim(Brack) {
+        p.require_reported(OpenDelim(Brack))?;
+        let mut sp = p.last_span();
+        let lo = parse_expr(p)?;
+        p.require_reported(Colon)?;
+        let hi = parse_expr(p)?;
+        p.require_reported(CloseDelim(Brack))?;
+        sp.expand(p.last_span());
+        Ok(ValueRange::Range { lo, hi, span: sp })
+    } else {
+        Ok(ValueRange::Single(parse_expr(p)?))
+    }
+}
+
 struct ParallelParser<'a, R: Clone> {
     branches: Vec<(
         String,
@@ -5027,6 +6003,7 @@ fn parse_net_decl(p: &mut dyn AbstractParser) -> ReportedResult<NetDecl> {
     span.expand(p.last_span());
     Ok(NetDecl {
         span: span,
+        attrs: vec![],
         net_type: net_type,
         strength: strength,
         kind: kind,
@@ -5043,6 +6020,7 @@ fn try_drive_strength(
         p.bump();
         p.require_reported(Comma)?;
         if let Some(b) = as_drive_strength(p.peek(0).0) {
+            p.bump();
             Ok(Some((a, b)))
         } else {
             let q = p.peek(0).1;
@@ -5176,6 +6154,101 @@ fn parse_import_decl(p: &mut dyn AbstractParser) -> ReportedResult<ImportDecl> {
     })
 }
 
+/// Parse an export declaration.
+///
+/// ```text
+/// "export" "*" "::" "*" ";"
+/// "export" package_ident "::" "*" ";"
+/// "export" package_ident "::" ident ";"
+/// "export" string [ident "="] ("function"|"task") ident ";"
+/// ```
+fn parse_export_decl(p: &mut dyn AbstractParser) -> ReportedResult<ExportDecl> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Export))?;
+
+    // Handle the DPI export case.
+    if let Literal(Lit::Str(..)) = p.peek(0).0 {
+        p.bump();
+        if p.peek(1).0 == Operator(Op::Assign) {
+            p.eat_ident("C identifier")?;
+            p.require_reported(Operator(Op::Assign))?;
+        }
+        if !p.try_eat(Keyword(Kw::Function)) {
+            p.require_reported(Keyword(Kw::Task))?;
+        }
+        p.eat_ident("function or task name")?;
+        p.require_reported(Semicolon)?;
+        span.expand(p.last_span());
+        p.add_diag(DiagBuilder2::warning("unsupported DPI export").span(span));
+        return Ok(ExportDecl {
+            span: span,
+            items: vec![],
+        });
+    }
+
+    let items = comma_list_nonempty(p, Semicolon, "export item", |p| {
+        let mut span = p.peek(0).1;
+        let pkg = if p.try_eat(Operator(Op::Mul)) {
+            None
+        } else {
+            Some(parse_identifier(p, "package name")?)
+        };
+        p.require_reported(Namespace)?;
+        let (tkn, sp) = p.peek(0);
+        let name = match tkn {
+            Operator(Op::Mul) => {
+                p.bump();
+                None
+            }
+            Ident(n) | EscIdent(n) if pkg.is_some() => {
+                p.bump();
+                Some(ast::Identifier {
+                    id: DUMMY_NODE_ID,
+                    span: sp,
+                    name: n,
+                })
+            }
+            _ => {
+                p.add_diag(
+                    DiagBuilder2::error(if pkg.is_some() {
+                        "Expected identifier or * after :: in export declaration"
+                    } else {
+                        "Expected * after *:: in export declaration"
+                    })
+                    .span(sp),
+                );
+                return Err(());
+            }
+        };
+        span.expand(p.last_span());
+        Ok(ExportItem { span, pkg, name })
+    })?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ExportDecl { span, items })
+}
+
+/// Parse a pattern variable.
+///
+/// ```text
+/// "." ident
+/// "." "*"
+/// ```
+fn parse_pattern_var(p: &mut dyn AbstractParser) -> ReportedResult<Expr> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Period)?;
+    let name = if p.try_eat(Operator(Op::Mul)) {
+        None
+    } else {
+        Some(parse_identifier(p, "pattern variable name")?)
+    };
+    span.expand(p.last_span());
+    Ok(Expr {
+        span,
+        data: PatternVarExpr(name),
+    })
+}
+
 fn parse_assertion(p: &mut dyn AbstractParser) -> ReportedResult<Assertion> {
     let mut span = p.peek(0).1;
 
@@ -5223,9 +6296,9 @@ fn parse_assertion(p: &mut dyn AbstractParser) -> ReportedResult<Assertion> {
         Keyword(Kw::Cover) if is_sequence => {
             p.bump();
             p.bump();
-            p.add_diag(DiagBuilder2::error("Don't know how to parse cover sequences").span(span));
-            return Err(());
-            // AssertionData::Concurrent(ConcurrentAssertion::CoverSequence)
+            let prop = flanked(p, Paren, parse_property_spec)?;
+            let stmt = parse_stmt(p)?;
+            AssertionData::Concurrent(ConcurrentAssertion::CoverSequence(prop, stmt))
         }
 
         // `expect`
@@ -5334,28 +6407,52 @@ fn parse_assertion_action_block(
 fn parse_property_spec(p: &mut dyn AbstractParser) -> ReportedResult<PropSpec> {
     let mut span = p.peek(0).1;
 
-    // TODO: Actually parse this stuff, rather than just chicken out.
-    p.recover_balanced(&[CloseDelim(Paren)], false);
-    return Ok(PropSpec);
+    // Parse the optional clocking event.
+    let clock = if p.peek(0).0 == At {
+        Some(parse_clocking_event(p)?)
+    } else {
+        None
+    };
 
-    // // Parse the optional event expression.
-    // let event = if p.try_eat(At) {
-    //     Some(parse_event_expr(p, EventPrecedence::Min)?)
-    // } else {
-    //     None
-    // };
+    // Parse the optional "disable iff" clause.
+    let disable = if p.try_eat(Keyword(Kw::Disable)) {
+        p.require_reported(Keyword(Kw::Iff))?;
+        Some(flanked(p, Paren, parse_expr)?)
+    } else {
+        None
+    };
 
-    // // Parse the optional "disable iff" clause.
-    // let disable = if p.try_eat(Keyword(Kw::Disable)) {
-    //     p.require_reported(Keyword(Kw::Iff))?;
-    //     Some(flanked(p, Paren, parse_expr)?)
-    // } else {
-    //     None
-    // };
+    // Parse the property expression.
+    let prop = parse_propexpr(p)?;
+    span.expand(p.last_span());
+    Ok(PropSpec {
+        span: span,
+        clock: clock,
+        disable: disable,
+        prop: prop,
+    })
+}
 
-    // // Parse the property expression.
-    // let prop = parse_propexpr(p)?;
-    // Ok(PropSpec)
+/// Parse a clocking event.
+///
+/// ```text
+/// "@" ident
+/// "@" "(" event_expr ")"
+/// ```
+fn parse_clocking_event(p: &mut dyn AbstractParser) -> ReportedResult<EventExpr> {
+    p.require_reported(At)?;
+    if p.peek(0).0 == OpenDelim(Paren) {
+        return parse_event_expr(p, EventPrecedence::Max);
+    }
+    let ident = parse_identifier(p, "clocking event")?;
+    Ok(EventExpr::Edge {
+        span: ident.span,
+        edge: EdgeIdent::Implicit,
+        value: Expr {
+            span: ident.span,
+            data: IdentExpr(ident),
+        },
+    })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -5377,6 +6474,17 @@ enum PropSeqPrecedence {
     Max,
 }
 
+// Sequence and property expressions share most of their syntax, and it is
+// often unclear which of the two is being parsed until an operator comes along
+// that is only defined for one of them, e.g.:
+//
+// (foo) |=> bar
+// ^^^^^ sequence or property?
+//
+// Therefore both are parsed as property expressions, with sequences wrapped in
+// a `PropExprData::SeqOp(PropSeqOp::None, ..)`. Operators that require a
+// sequence unwrap their operands again via `expect_seq`.
+
 fn parse_propexpr(p: &mut dyn AbstractParser) -> ReportedResult<PropExpr> {
     parse_propexpr_prec(p, PropSeqPrecedence::Min)
 }
@@ -5385,105 +6493,97 @@ fn parse_propexpr_prec(
     p: &mut dyn AbstractParser,
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<PropExpr> {
-    let mut span = p.peek(0).1;
-
-    // To parse property expressions we need a parallel parser. For certain
-    // cases it is unclear if a parenthesized expression is a sequence or a
-    // property expression, e.g.:
-    //
-    // (foo) |=> bar
-    // ^^^^^ sequence or property?
-    //
-    // Both sequences and property expressions support parenthesis. However the
-    // |=> operator is only defined for sequences on the left hand side. If the
-    // parenthesis are parsed as a property and foo as a sequence, the above
-    // code fails to parse since the sequence on the left has effectively become
-    // a property. If the parenthesis are parsed as a sequence, all is well. To
-    // resolve these kinds of issues, we need a parallel parser.
-    let mut pp = ParallelParser::new();
-    pp.add_greedy("sequence expression", move |p| {
-        parse_propexpr_seq(p, precedence)
-    });
-    pp.add_greedy("property expression", move |p| {
-        parse_propexpr_nonseq(p, precedence)
-    });
-    let data = pp.finish(p, "sequence or primary property expression")?;
-
-    span.expand(p.last_span());
-    let expr = PropExpr {
-        span: span,
-        data: data,
-    };
-    parse_propexpr_suffix(p, expr, precedence)
+    let prefix = parse_propexpr_primary(p)?;
+    parse_propexpr_suffix(p, prefix, precedence)
 }
 
-fn parse_propexpr_nonseq(
-    p: &mut dyn AbstractParser,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<PropExprData> {
-    // Handle the trivial case of expressions introduced by a symbol or keyword.
-    match p.peek(0).0 {
-        // Parenthesized property expression.
-        OpenDelim(Paren) => return flanked(p, Paren, parse_propexpr).map(|pe| pe.data),
-
+fn parse_propexpr_primary(p: &mut dyn AbstractParser) -> ReportedResult<PropExpr> {
+    let mut span = p.peek(0).1;
+    let data = match p.peek(0).0 {
         // "not" operator
         Keyword(Kw::Not) => {
             p.bump();
             let expr = parse_propexpr_prec(p, PropSeqPrecedence::NotNexttime)?;
-            return Ok(PropExprData::Not(Box::new(expr)));
-        }
-
-        // Clocking event
-        At => {
-            p.bump();
-            let ev = parse_event_expr(p, EventPrecedence::Min)?;
-            let expr = parse_propexpr(p)?;
-            return Ok(PropExprData::Clocked(ev, Box::new(expr)));
-        }
-
-        _ => {
-            let q = p.peek(0).1;
-            p.add_diag(DiagBuilder2::error("Expected primary property expression").span(q));
-            return Err(());
+            PropExprData::Not(Box::new(expr))
         }
-    }
-}
 
-fn parse_propexpr_seq(
-    p: &mut dyn AbstractParser,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<PropExprData> {
-    // Consume a strong, weak, or regular sequence operator.
-    let (seqop, seqexpr) = match p.peek(0).0 {
+        // Strong and weak sequences
         Keyword(Kw::Strong) => {
             p.bump();
-            (PropSeqOp::Strong, flanked(p, Paren, parse_seqexpr)?)
+            PropExprData::SeqOp(PropSeqOp::Strong, flanked(p, Paren, parse_seqexpr)?)
         }
         Keyword(Kw::Weak) => {
             p.bump();
-            (PropSeqOp::Weak, flanked(p, Paren, parse_seqexpr)?)
+            PropExprData::SeqOp(PropSeqOp::Weak, flanked(p, Paren, parse_seqexpr)?)
         }
-        _ => (PropSeqOp::None, parse_seqexpr_prec(p, precedence)?),
-    };
 
-    // Handle the operators that have a sequence expression on their left hand
-    // side.
-    if precedence <= PropSeqPrecedence::ImplFollow {
-        if let Some(op) = match p.peek(0).0 {
-            Operator(Op::SeqImplOl) => Some(PropSeqBinOp::ImplOverlap),
-            Operator(Op::SeqImplNol) => Some(PropSeqBinOp::ImplNonoverlap),
-            Operator(Op::SeqFollowOl) => Some(PropSeqBinOp::FollowOverlap),
-            Operator(Op::SeqFollowNol) => Some(PropSeqBinOp::FollowNonoverlap),
-            _ => None,
-        } {
-            p.bump();
-            let expr = parse_propexpr_prec(p, PropSeqPrecedence::ImplFollow)?;
-            return Ok(PropExprData::SeqBinOp(op, seqop, seqexpr, Box::new(expr)));
+        // Clocking event, which applies to everything that follows.
+        At => {
+            let event = parse_clocking_event(p)?;
+            let expr = parse_propexpr(p)?;
+            span.expand(p.last_span());
+            match prop_to_seq(expr) {
+                Ok(seq) => PropExprData::SeqOp(
+                    PropSeqOp::None,
+                    SeqExpr {
+                        span: span,
+                        data: SeqExprData::Clocked(event, Box::new(seq)),
+                    },
+                ),
+                Err(expr) => PropExprData::Clocked(event, Box::new(expr)),
+            }
         }
-    }
 
-    // Otherwise this is just a simple sequence operator.
-    Ok(PropExprData::SeqOp(seqop, seqexpr))
+        // Leading cycle delay, e.g. `##1 a`.
+        DoubleHashtag => {
+            let delay = parse_seq_delay(p)?;
+            let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::CycleDelay)?;
+            span.expand(p.last_span());
+            PropExprData::SeqOp(
+                PropSeqOp::None,
+                SeqExpr {
+                    span: span,
+                    data: SeqExprData::Delay(None, delay, Box::new(rhs)),
+                },
+            )
+        }
+
+        // Parenthesized expressions are either a plain expression such as
+        // `(a + b) == c`, or a parenthesized sequence or property.
+        OpenDelim(Paren) => match r#try(p, parse_expr) {
+            Some(expr) => PropExprData::SeqOp(PropSeqOp::None, expr_to_seq(expr)),
+            None => flanked(p, Paren, parse_propexpr)?.data,
+        },
+
+        // Everything else is an expression.
+        _ => PropExprData::SeqOp(PropSeqOp::None, expr_to_seq(parse_expr(p)?)),
+    };
+    span.expand(p.last_span());
+    let mut expr = PropExpr {
+        span: span,
+        data: data,
+    };
+
+    // Parse any trailing repetitions.
+    while p.peek(0).0 == OpenDelim(Brack) && is_seqrep(p) {
+        let seq = expect_seq(p, expr, "repetition")?;
+        let rep = flanked(p, Brack, parse_seqrep)?;
+        let data = match seq.data {
+            SeqExprData::Expr(expr, None) => SeqExprData::Expr(expr, Some(rep)),
+            data => SeqExprData::Repeat(
+                Box::new(SeqExpr {
+                    span: seq.span,
+                    data: data,
+                }),
+                rep,
+            ),
+        };
+        expr = seq_to_prop(SeqExpr {
+            span: Span::union(seq.span, p.last_span()),
+            data: data,
+        });
+    }
+    Ok(expr)
 }
 
 fn parse_propexpr_suffix(
@@ -5491,29 +6591,124 @@ fn parse_propexpr_suffix(
     prefix: PropExpr,
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<PropExpr> {
-    // Handle the binary operators that have a property expression on both their
-    // left and right hand side.
-    if let Some((op, prec, rassoc)) = match p.peek(0).0 {
-        Keyword(Kw::Or) => Some((PropBinOp::Or, PropSeqPrecedence::Or, false)),
-        Keyword(Kw::And) => Some((PropBinOp::And, PropSeqPrecedence::And, false)),
-        Keyword(Kw::Until) => Some((PropBinOp::Until, PropSeqPrecedence::Until, true)),
-        Keyword(Kw::SUntil) => Some((PropBinOp::SUntil, PropSeqPrecedence::Until, true)),
-        Keyword(Kw::UntilWith) => Some((PropBinOp::UntilWith, PropSeqPrecedence::Until, true)),
-        Keyword(Kw::SUntilWith) => Some((PropBinOp::SUntilWith, PropSeqPrecedence::Until, true)),
-        Keyword(Kw::Implies) => Some((PropBinOp::Impl, PropSeqPrecedence::Until, true)),
-        Keyword(Kw::Iff) => Some((PropBinOp::Iff, PropSeqPrecedence::Iff, true)),
-        _ => None,
-    } {
-        if precedence < prec || (rassoc && precedence == prec) {
-            p.bump();
-            let rhs = parse_propexpr_prec(p, prec)?;
+    let tkn = p.peek(0).0;
+
+    // Handle the cycle delay, which has a sequence on either side.
+    if tkn == DoubleHashtag && precedence < PropSeqPrecedence::CycleDelay {
+        let lhs = expect_seq(p, prefix, "`##`")?;
+        let delay = parse_seq_delay(p)?;
+        let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::CycleDelay)?;
+        let seq = SeqExpr {
+            span: Span::union(lhs.span, rhs.span),
+            data: SeqExprData::Delay(Some(Box::new(lhs)), delay, Box::new(rhs)),
+        };
+        return parse_propexpr_suffix(p, seq_to_prop(seq), precedence);
+    }
+
+    // Handle the "throughout" operator, which has an expression on its left
+    // hand side.
+    if tkn == Keyword(Kw::Throughout) && precedence <= PropSeqPrecedence::Throughout {
+        let lhs = match expect_seq(p, prefix, "`throughout`")? {
+            SeqExpr {
+                data: SeqExprData::Expr(expr, None),
+                ..
+            } => expr,
+            seq => {
+                p.add_diag(
+                    DiagBuilder2::error("expected expression on the left of `throughout`")
+                        .span(seq.span),
+                );
+                return Err(());
+            }
+        };
+        p.bump();
+        let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::Throughout)?;
+        let seq = SeqExpr {
+            span: Span::union(lhs.span, rhs.span),
+            data: SeqExprData::Throughout(lhs, Box::new(rhs)),
+        };
+        return parse_propexpr_suffix(p, seq_to_prop(seq), precedence);
+    }
+
+    // Handle the binary operators that only apply to sequences.
+    if let Some((op, prec)) = match tkn {
+        Keyword(Kw::Within) => Some((SeqBinOp::Within, PropSeqPrecedence::Within)),
+        Keyword(Kw::Intersect) => Some((SeqBinOp::Intersect, PropSeqPrecedence::Intersect)),
+        _ => None,
+    } {
+        if precedence < prec {
+            let lhs = expect_seq(p, prefix, &format!("`{}`", tkn))?;
+            p.bump();
+            let rhs = parse_seqexpr_prec(p, prec)?;
+            let seq = SeqExpr {
+                span: Span::union(lhs.span, rhs.span),
+                data: SeqExprData::BinOp(op, Box::new(lhs), Box::new(rhs)),
+            };
+            return parse_propexpr_suffix(p, seq_to_prop(seq), precedence);
+        }
+    }
+
+    // Handle the operators that have a sequence on their left hand side and a
+    // property on their right hand side.
+    if let Some(op) = match tkn {
+        Operator(Op::SeqImplOl) => Some(PropSeqBinOp::ImplOverlap),
+        Operator(Op::SeqImplNol) => Some(PropSeqBinOp::ImplNonoverlap),
+        Operator(Op::SeqFollowOl) => Some(PropSeqBinOp::FollowOverlap),
+        Operator(Op::SeqFollowNol) => Some(PropSeqBinOp::FollowNonoverlap),
+        _ => None,
+    } {
+        if precedence <= PropSeqPrecedence::ImplFollow {
+            let lhs = expect_seq(p, prefix, &format!("`{}`", tkn))?;
+            p.bump();
+            let rhs = parse_propexpr_prec(p, PropSeqPrecedence::ImplFollow)?;
             return Ok(PropExpr {
-                span: Span::union(prefix.span, rhs.span),
-                data: PropExprData::BinOp(op, Box::new(prefix), Box::new(rhs)),
+                span: Span::union(lhs.span, rhs.span),
+                data: PropExprData::SeqBinOp(op, PropSeqOp::None, lhs, Box::new(rhs)),
             });
         }
     }
 
+    // Handle the binary operators that have a property expression on both their
+    // left and right hand side. The "and" and "or" operators produce a sequence
+    // if both operands are sequences.
+    if let Some((op, prec, rassoc)) = match tkn {
+        Keyword(Kw::Or) => Some((PropBinOp::Or, PropSeqPrecedence::Or, false)),
+        Keyword(Kw::And) => Some((PropBinOp::And, PropSeqPrecedence::And, false)),
+        Keyword(Kw::Until) => Some((PropBinOp::Until, PropSeqPrecedence::Until, true)),
+        Keyword(Kw::SUntil) => Some((PropBinOp::SUntil, PropSeqPrecedence::Until, true)),
+        Keyword(Kw::UntilWith) => Some((PropBinOp::UntilWith, PropSeqPrecedence::Until, true)),
+        Keyword(Kw::SUntilWith) => Some((PropBinOp::SUntilWith, PropSeqPrecedence::Until, true)),
+        Keyword(Kw::Implies) => Some((PropBinOp::Impl, PropSeqPrecedence::Until, true)),
+        Keyword(Kw::Iff) => Some((PropBinOp::Iff, PropSeqPrecedence::Iff, true)),
+        _ => None,
+    } {
+        if precedence < prec || (rassoc && precedence == prec) {
+            p.bump();
+            let rhs = parse_propexpr_prec(p, prec)?;
+            let span = Span::union(prefix.span, rhs.span);
+            let seq_op = match op {
+                PropBinOp::Or => Some(SeqBinOp::Or),
+                PropBinOp::And => Some(SeqBinOp::And),
+                _ => None,
+            };
+            let expr = match (seq_op, prop_to_seq(prefix), prop_to_seq(rhs)) {
+                (Some(op), Ok(lhs), Ok(rhs)) => seq_to_prop(SeqExpr {
+                    span: span,
+                    data: SeqExprData::BinOp(op, Box::new(lhs), Box::new(rhs)),
+                }),
+                (_, lhs, rhs) => PropExpr {
+                    span: span,
+                    data: PropExprData::BinOp(
+                        op,
+                        Box::new(lhs.map(seq_to_prop).unwrap_or_else(|x| x)),
+                        Box::new(rhs.map(seq_to_prop).unwrap_or_else(|x| x)),
+                    ),
+                },
+            };
+            return parse_propexpr_suffix(p, expr, precedence);
+        }
+    }
+
     Ok(prefix)
 }
 
@@ -5525,78 +6720,121 @@ fn parse_seqexpr_prec(
     p: &mut dyn AbstractParser,
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<SeqExpr> {
-    let mut span = p.peek(0).1;
+    let expr = parse_propexpr_prec(p, precedence)?;
+    expect_seq(p, expr, "sequence")
+}
 
-    // See parse_propexpr_prec for an explanation of why we need a parallel
-    // parser here.
-    let mut pp = ParallelParser::new();
-    pp.add_greedy("expression", move |p| parse_seqexpr_expr(p, precedence));
-    pp.add_greedy("sequence", move |p| parse_seqexpr_nonexpr(p, precedence));
-    let data = pp.finish(p, "sequence or primary property expression")?;
+/// Unwrap the sequence in a property expression, or emit a diagnostic if the
+/// expression is not a sequence.
+fn expect_seq(
+    p: &mut dyn AbstractParser,
+    expr: PropExpr,
+    context: &str,
+) -> ReportedResult<SeqExpr> {
+    match prop_to_seq(expr) {
+        Ok(seq) => Ok(seq),
+        Err(expr) => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected sequence for {}, found property expression",
+                    context
+                ))
+                .span(expr.span),
+            );
+            Err(())
+        }
+    }
+}
 
-    span.expand(p.last_span());
-    let expr = SeqExpr {
-        span: span,
-        data: data,
-    };
-    parse_seqexpr_suffix(p, expr, precedence)
+/// Unwrap the sequence in a property expression, if the expression is just a
+/// sequence.
+fn prop_to_seq(expr: PropExpr) -> Result<SeqExpr, PropExpr> {
+    match expr.data {
+        PropExprData::SeqOp(PropSeqOp::None, seq) => Ok(seq),
+        data => Err(PropExpr {
+            span: expr.span,
+            data: data,
+        }),
+    }
 }
 
-fn parse_seqexpr_expr(
-    p: &mut dyn AbstractParser,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<SeqExprData> {
-    // TODO: Handle all the non-trivial cases.
-    let q = p.peek(0).1;
-    p.add_diag(
-        DiagBuilder2::error(
-            "Don't know how to parse sequence expression that don't start with an expression",
-        )
-        .span(q),
-    );
-    Err(())
+fn seq_to_prop(seq: SeqExpr) -> PropExpr {
+    PropExpr {
+        span: seq.span,
+        data: PropExprData::SeqOp(PropSeqOp::None, seq),
+    }
 }
 
-fn parse_seqexpr_nonexpr(
-    p: &mut dyn AbstractParser,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<SeqExprData> {
-    // If we arrive here, the only possibility left is that this sequence starts
-    // with and expression or distribution.
-    let expr = parse_expr(p)?;
-
-    // Handle the case of the "throughout" operator that has an expression on
-    // its left hand side.
-    if precedence <= PropSeqPrecedence::Throughout && p.try_eat(Keyword(Kw::Throughout)) {
-        let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::Throughout)?;
-        return Ok(SeqExprData::Throughout(expr, Box::new(rhs)));
+fn expr_to_seq(expr: Expr) -> SeqExpr {
+    SeqExpr {
+        span: expr.span,
+        data: SeqExprData::Expr(expr, None),
     }
+}
 
-    // Parse the optional repetition.
-    let rep = try_flanked(p, Brack, parse_seqrep)?;
+/// Check whether the next tokens start a sequence repetition, i.e. `[*`, `[=`,
+/// `[->`, or `[+]`.
+fn is_seqrep(p: &mut dyn AbstractParser) -> bool {
+    match p.peek(1).0 {
+        Operator(Op::Mul) | Operator(Op::Assign) | Operator(Op::LogicImpl) => true,
+        Operator(Op::Add) => p.peek(2).0 == CloseDelim(Brack),
+        _ => false,
+    }
+}
 
-    Ok(SeqExprData::Expr(expr, rep))
+/// Parse a cycle delay.
+///
+/// ```text
+/// "##" (number | ident | "(" expr ")" | "[" range "]" | "[*]" | "[+]")
+/// ```
+fn parse_seq_delay(p: &mut dyn AbstractParser) -> ReportedResult<SeqDelay> {
+    p.require_reported(DoubleHashtag)?;
+    match p.peek(0).0 {
+        OpenDelim(Brack) => flanked(p, Brack, |p| match p.peek(0).0 {
+            Operator(Op::Mul) => {
+                p.bump();
+                Ok(SeqDelay::Star)
+            }
+            Operator(Op::Add) => {
+                p.bump();
+                Ok(SeqDelay::Plus)
+            }
+            _ => Ok(SeqDelay::Range(parse_seq_range(p)?)),
+        }),
+        OpenDelim(Paren) => Ok(SeqDelay::Range(SeqRange::Single(flanked(
+            p, Paren, parse_expr,
+        )?))),
+        _ => Ok(SeqDelay::Range(SeqRange::Single(parse_expr_prec(
+            p,
+            Precedence::Max,
+        )?))),
+    }
 }
 
-fn parse_seqexpr_suffix(
-    p: &mut dyn AbstractParser,
-    prefix: SeqExpr,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<SeqExpr> {
-    // TODO: Handle all the binary operators.
-    Ok(prefix)
+/// Parse a cycle range, e.g. `2`, `1:3`, or `1:$`.
+fn parse_seq_range(p: &mut dyn AbstractParser) -> ReportedResult<SeqRange> {
+    let lo = parse_expr(p)?;
+    if !p.try_eat(Colon) {
+        return Ok(SeqRange::Single(lo));
+    }
+    let hi = if p.try_eat(Dollar) {
+        None
+    } else {
+        Some(parse_expr(p)?)
+    };
+    Ok(SeqRange::Range(lo, hi))
 }
 
 fn parse_seqrep(p: &mut dyn AbstractParser) -> ReportedResult<SeqRep> {
     match p.peek(0).0 {
         // [*]
-        // [* expr]
+        // [* range]
         Operator(Op::Mul) => {
             p.bump();
             if p.peek(0).0 == CloseDelim(Brack) {
                 Ok(SeqRep::ConsecStar)
             } else {
-                Ok(SeqRep::Consec(parse_expr(p)?))
+                Ok(SeqRep::Consec(parse_seq_range(p)?))
             }
         }
 
@@ -5606,16 +6844,16 @@ fn parse_seqrep(p: &mut dyn AbstractParser) -> ReportedResult<SeqRep> {
             Ok(SeqRep::ConsecPlus)
         }
 
-        // [= expr]
+        // [= range]
         Operator(Op::Assign) => {
             p.bump();
-            Ok(SeqRep::Nonconsec(parse_expr(p)?))
+            Ok(SeqRep::Nonconsec(parse_seq_range(p)?))
         }
 
-        // [-> expr]
+        // [-> range]
         Operator(Op::LogicImpl) => {
             p.bump();
-            Ok(SeqRep::Goto(parse_expr(p)?))
+            Ok(SeqRep::Goto(parse_seq_range(p)?))
         }
 
         _ => {
@@ -5631,6 +6869,108 @@ fn parse_seqrep(p: &mut dyn AbstractParser) -> ReportedResult<SeqRep> {
     }
 }
 
+/// Parse a named sequence declaration.
+fn parse_seq_decl(p: &mut dyn AbstractParser) -> ReportedResult<SeqDecl> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Sequence))?;
+    let result = recovered(p, Keyword(Kw::Endsequence), |p| {
+        let name = parse_identifier(p, "sequence name")?;
+        let ports = parse_seq_ports(p)?;
+        p.require_reported(Semicolon)?;
+        let seq = parse_seqexpr(p)?;
+        p.try_eat(Semicolon);
+        Ok((name, ports, seq))
+    });
+    p.require_reported(Keyword(Kw::Endsequence))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("sequence name")?;
+    }
+    let (name, ports, seq) = result?;
+    span.expand(p.last_span());
+    Ok(SeqDecl {
+        span: span,
+        name: name,
+        ports: ports,
+        seq: seq,
+    })
+}
+
+/// Parse a named property declaration.
+fn parse_prop_decl(p: &mut dyn AbstractParser) -> ReportedResult<PropDecl> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Property))?;
+    let result = recovered(p, Keyword(Kw::Endproperty), |p| {
+        let name = parse_identifier(p, "property name")?;
+        let ports = parse_seq_ports(p)?;
+        p.require_reported(Semicolon)?;
+        let prop = parse_property_spec(p)?;
+        p.try_eat(Semicolon);
+        Ok((name, ports, prop))
+    });
+    p.require_reported(Keyword(Kw::Endproperty))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("property name")?;
+    }
+    let (name, ports, prop) = result?;
+    span.expand(p.last_span());
+    Ok(PropDecl {
+        span: span,
+        name: name,
+        ports: ports,
+        prop: prop,
+    })
+}
+
+/// Parse the optional list of formal arguments of a sequence or property.
+///
+/// ```text
+/// ["(" [port {"," port}] ")"]
+/// port: ["untyped" | "sequence" | "property" | data_type] ident ["=" expr]
+/// ```
+fn parse_seq_ports(p: &mut dyn AbstractParser) -> ReportedResult<Vec<SeqPort>> {
+    if p.peek(0).0 != OpenDelim(Paren) {
+        return Ok(Vec::new());
+    }
+    flanked(p, Paren, |p| {
+        comma_list(p, CloseDelim(Paren), "argument", |p| {
+            let mut span = p.peek(0).1;
+            let ty = match p.peek(0).0 {
+                Keyword(Kw::Untyped) => {
+                    p.bump();
+                    SeqPortType::Untyped
+                }
+                Keyword(Kw::Sequence) => {
+                    p.bump();
+                    SeqPortType::Sequence
+                }
+                Keyword(Kw::Property) => {
+                    p.bump();
+                    SeqPortType::Property
+                }
+                _ => match p.peek(1).0 {
+                    Comma | CloseDelim(Paren) | Operator(Op::Assign) if p.is_ident() => {
+                        SeqPortType::Implicit
+                    }
+                    _ => SeqPortType::Data(parse_explicit_type(p)?),
+                },
+            };
+            let name = parse_identifier(p, "argument name")?;
+            let default = if p.try_eat(Operator(Op::Assign)) {
+                Some(parse_expr(p)?)
+            } else {
+                None
+            };
+            span.expand(p.last_span());
+            Ok(SeqPort {
+                span: span,
+                ty: ty,
+                name: name,
+                default: default,
+            })
+        })
+    })
+}
+
 fn parse_inst(p: &mut dyn AbstractParser) -> ReportedResult<ast::Inst> {
     let mut span = p.peek(0).1;
 
@@ -5664,6 +7004,7 @@ fn parse_inst(p: &mut dyn AbstractParser) -> ReportedResult<ast::Inst> {
     span.expand(p.last_span());
     Ok(ast::Inst {
         span: span,
+        attrs: vec![],
         target: target,
         params: params,
         names: names,
@@ -5715,6 +7056,7 @@ fn parse_var_decl(p: &mut dyn AbstractParser) -> ReportedResult<ast::VarDecl> {
     span.expand(p.last_span());
     Ok(ast::VarDecl {
         span: span,
+        attrs: vec![],
         konst: konst,
         var: var,
         lifetime: lifetime,
@@ -5834,6 +7176,16 @@ fn parse_param_decl(
             pp.finish(p, "explicit or implicit type")
         })?;
         p.anticipate(&[Semicolon, Comma, CloseDelim(Paren)])?;
+
+        // Declarations without a type of their own share the type of the
+        // preceding declaration, e.g. `parameter int A = 1, B = 2`.
+        let mut decls = decls;
+        for i in 1..decls.len() {
+            let ty = &decls[i].ty;
+            if ty.data == ImplicitType && ty.sign == TypeSign::None && ty.dims.is_empty() {
+                decls[i].ty = decls[i - 1].ty.clone();
+            }
+        }
         ast::ParamKind::Value(decls)
     };
 
@@ -5855,7 +7207,7 @@ mod tests {
     use crate::preproc::*;
     use moore_common::source::*;
 
-    fn parse(input: &str) {
+    fn parse(input: &str) -> crate::ast::Root {
         use std::cell::Cell;
         thread_local!(static INDEX: Cell<usize> = Cell::new(0));
         let sm = get_source_manager();
@@ -5867,7 +7219,15 @@ mod tests {
         let source = sm.add(&format!("test_{}.sv", idx), input);
         let pp = Preprocessor::new(source, &[], &[]);
         let lexer = Lexer::new(pp);
-        super::parse(lexer).unwrap();
+        super::parse(lexer).unwrap()
+    }
+
+    fn parse_tolerant(input: &str) -> (crate::ast::Root, usize) {
+        let source = get_source_manager().add_anonymous(input);
+        let pp = Preprocessor::new(source, &[], &[]);
+        let lexer = Lexer::new(pp);
+        let (root, diags) = super::parse_tolerant(lexer);
+        (root, diags.len())
     }
 
     #[test]
@@ -5887,4 +7247,726 @@ mod tests {
     fn intf_header() {
         // parse("interface Foo ();")
     }
+
+    #[test]
+    fn attrs() {
+        use crate::ast::*;
+        let root = parse(
+            "(* top *) module Foo; (* keep, ram_style = \"block\" *) logic a; always @(*) (* full_case *) a = 1; endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        assert_eq!(m.attrs.len(), 1);
+        assert_eq!(&*m.attrs[0].specs[0].name.name.as_str(), "top");
+        match m.items[0] {
+            HierarchyItem::VarDecl(ref d) => {
+                assert_eq!(d.attrs[0].specs.len(), 2);
+                assert!(d.attrs[0].specs[0].value.is_none());
+                assert!(d.attrs[0].specs[1].value.is_some());
+            }
+            _ => panic!("expected variable declaration"),
+        }
+        match m.items[1] {
+            HierarchyItem::Procedure(ref p) => {
+                assert!(p.attrs.is_empty());
+                match p.stmt.data {
+                    TimedStmt(_, ref stmt) => assert_eq!(stmt.attrs.len(), 1),
+                    _ => panic!("expected timed statement"),
+                }
+            }
+            _ => panic!("expected procedure"),
+        }
+    }
+
+    #[test]
+    fn ignored_attrs() {
+        let (root, num_diags) = parse_tolerant(
+            "module Foo ((* keep *) input a, output b); assign b = a + (* foo *) a; endmodule",
+        );
+        assert_eq!(root.items.len(), 1);
+        assert_eq!(num_diags, 2);
+        let (_, num_diags) = parse_tolerant("module Foo; (* keep *) logic a; endmodule");
+        assert_eq!(num_diags, 0);
+    }
+
+    #[test]
+    fn program_checker_bind_config() {
+        use crate::ast::*;
+        let root = parse(
+            "program P; initial; endprogram : P
+            checker C (input logic clk); endchecker
+            bind top.u0 C c0 (.clk(clk));
+            bind dut : u1, u2 C c1 (.clk(clk));
+            config cfg; design rtl.top; default liblist rtl gate; instance top.u0 use gate.dut : config; cell ram liblist rtl; endconfig : cfg",
+        );
+        assert_eq!(root.items.len(), 5);
+        match root.items[0] {
+            Item::Program(ref p) => assert_eq!(p.items.len(), 1),
+            _ => panic!("expected program"),
+        }
+        match root.items[1] {
+            Item::Checker(ref c) => assert_eq!(c.ports.len(), 1),
+            _ => panic!("expected checker"),
+        }
+        match root.items[2] {
+            Item::Bind(ref b) => {
+                assert_eq!(b.target.len(), 2);
+                assert_eq!(b.target_module(), None);
+                assert_eq!(b.inst.names.len(), 1);
+            }
+            _ => panic!("expected bind directive"),
+        }
+        match root.items[3] {
+            Item::Bind(ref b) => {
+                assert_eq!(b.target.len(), 1);
+                assert_eq!(b.target_insts.len(), 2);
+            }
+            _ => panic!("expected bind directive"),
+        }
+        match root.items[4] {
+            Item::Config(ref c) => {
+                assert_eq!(c.design.len(), 1);
+                assert_eq!(c.rules.len(), 3);
+                assert_eq!(c.rules[0].clause, ConfigClause::Default);
+                match c.rules[1].action {
+                    ConfigAction::Use {
+                        ref cell, config, ..
+                    } => {
+                        assert!(config);
+                        assert!(cell.as_ref().unwrap().lib.is_some());
+                    }
+                    _ => panic!("expected use clause"),
+                }
+            }
+            _ => panic!("expected config"),
+        }
+    }
+
+    #[test]
+    fn sequences_and_properties() {
+        use crate::ast::*;
+        let root = parse(
+            "module Foo;
+                sequence s1(a, logic b = 1'b0); @(posedge clk) a ##[1:$] b[*2]; endsequence
+                property p1; @(posedge clk) disable iff (rst) s1(x, y) |=> (c throughout d ##2 e) within f; endproperty : p1
+                a1: assert property (@(posedge clk) (a + b) == c |-> ##1 d[->1] and e until f);
+                cover sequence (@(posedge clk) a ##1 b);
+                initial a2: assert property (p1) else $error;
+            endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        match m.items[0] {
+            HierarchyItem::SeqDecl(ref d) => {
+                assert_eq!(d.ports.len(), 2);
+                assert!(d.ports[1].default.is_some());
+                let inner = match d.seq.data {
+                    SeqExprData::Clocked(_, ref inner) => inner,
+                    _ => panic!("expected clocked sequence"),
+                };
+                match inner.data {
+                    SeqExprData::Delay(
+                        Some(_),
+                        SeqDelay::Range(SeqRange::Range(_, None)),
+                        ref rhs,
+                    ) => match rhs.data {
+                        SeqExprData::Expr(_, Some(SeqRep::Consec(_))) => (),
+                        _ => panic!("expected repetition"),
+                    },
+                    _ => panic!("expected cycle delay"),
+                }
+            }
+            _ => panic!("expected sequence declaration"),
+        }
+        match m.items[1] {
+            HierarchyItem::PropDecl(ref d) => {
+                assert!(d.prop.clock.is_some());
+                assert!(d.prop.disable.is_some());
+                match d.prop.prop.data {
+                    PropExprData::SeqBinOp(PropSeqBinOp::ImplNonoverlap, _, _, ref rhs) => {
+                        match rhs.data {
+                            PropExprData::SeqOp(_, ref seq) => match seq.data {
+                                SeqExprData::BinOp(SeqBinOp::Within, ref lhs, _) => {
+                                    match lhs.data {
+                                        SeqExprData::Throughout(..) => (),
+                                        _ => panic!("expected throughout"),
+                                    }
+                                }
+                                _ => panic!("expected within"),
+                            },
+                            _ => panic!("expected sequence"),
+                        }
+                    }
+                    _ => panic!("expected non-overlapping implication"),
+                }
+            }
+            _ => panic!("expected property declaration"),
+        }
+        match m.items[2] {
+            HierarchyItem::Assertion(ref a) => {
+                assert_eq!(&*a.label.unwrap().0.as_str(), "a1");
+                match a.data {
+                    AssertionData::Concurrent(ConcurrentAssertion::AssertProperty(ref spec, _)) => {
+                        assert!(spec.clock.is_some());
+                        match spec.prop.data {
+                            PropExprData::SeqBinOp(PropSeqBinOp::ImplOverlap, _, _, ref rhs) => {
+                                match rhs.data {
+                                    PropExprData::BinOp(PropBinOp::Until, ..) => (),
+                                    _ => panic!("expected until"),
+                                }
+                            }
+                            _ => panic!("expected overlapping implication"),
+                        }
+                    }
+                    _ => panic!("expected assert property"),
+                }
+            }
+            _ => panic!("expected assertion"),
+        }
+        match m.items[3] {
+            HierarchyItem::Assertion(Assertion {
+                data: AssertionData::Concurrent(ConcurrentAssertion::CoverSequence(..)),
+                ..
+            }) => (),
+            _ => panic!("expected cover sequence"),
+        }
+        match m.items[4] {
+            HierarchyItem::Procedure(ref p) => match p.stmt.data {
+                AssertionStmt(ref a) => assert!(a.label.is_some()),
+                _ => panic!("expected assertion statement"),
+            },
+            _ => panic!("expected procedure"),
+        }
+    }
+
+    #[test]
+    fn modport_ports() {
+        use crate::ast::*;
+        let root = parse(
+            "interface I; logic a, b, c; modport m (input a, b, output .x(c), .y()); endinterface",
+        );
+        let i = match root.items[0] {
+            Item::Interface(ref i) => i,
+            _ => panic!("expected interface"),
+        };
+        let item = match i.items[1] {
+            HierarchyItem::ModportDecl(ref d) => &d.items[0],
+            _ => panic!("expected modport declaration"),
+        };
+        assert_eq!(&*item.name.name.as_str(), "m");
+        assert_eq!(item.ports.len(), 2);
+        match item.ports[0] {
+            ModportPort::Simple {
+                dir: PortDir::Input,
+                ref port,
+                ..
+            } => {
+                assert_eq!(port.len(), 2);
+                assert_eq!(&*port[1].name.name.as_str(), "b");
+                assert!(port[1].expr.is_none());
+            }
+            _ => panic!("expected input ports"),
+        }
+        match item.ports[1] {
+            ModportPort::Simple {
+                dir: PortDir::Output,
+                ref port,
+                ..
+            } => {
+                assert_eq!(port.len(), 2);
+                assert!(port[0].expr.is_some());
+                assert!(port[1].expr.is_none());
+            }
+            _ => panic!("expected output ports"),
+        }
+    }
+
+    #[test]
+    fn dynamic_dims() {
+        use crate::ast::*;
+        let root = parse(
+            "module M; int a[], b[$], c[$:7], d[*], e[string], f[N]; localparam N = 4; endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        let names = match m.items[0] {
+            HierarchyItem::VarDecl(ref d) => &d.names,
+            _ => panic!("expected variable declaration"),
+        };
+        assert_eq!(names.len(), 6);
+        let dims: Vec<_> = names.iter().map(|n| &n.dims[0]).collect();
+        match *dims[0] {
+            TypeDim::Unsized => (),
+            _ => panic!("expected unsized dimension"),
+        }
+        match *dims[1] {
+            TypeDim::Queue(None) => (),
+            _ => panic!("expected unbounded queue dimension"),
+        }
+        match *dims[2] {
+            TypeDim::Queue(Some(_)) => (),
+            _ => panic!("expected bounded queue dimension"),
+        }
+        match *dims[3] {
+            TypeDim::Associative(None) => (),
+            _ => panic!("expected wildcard associative dimension"),
+        }
+        match *dims[4] {
+            TypeDim::Associative(Some(_)) => (),
+            _ => panic!("expected associative dimension"),
+        }
+        match *dims[5] {
+            TypeDim::Expr(_) => (),
+            _ => panic!("expected fixed-size dimension"),
+        }
+    }
+
+    #[test]
+    fn param_list_shares_type() {
+        use crate::ast::*;
+        let root = parse("module M; localparam int A = 1, B = 2.5, logic C = 0; endmodule");
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        let decls = match m.items[0] {
+            HierarchyItem::ParamDecl(ParamDecl {
+                kind: ParamKind::Value(ref decls),
+                ..
+            }) => decls,
+            _ => panic!("expected parameter declaration"),
+        };
+        assert_eq!(decls.len(), 3);
+        assert_eq!(decls[0].ty.data, IntType);
+        assert_eq!(decls[1].ty.data, IntType);
+        assert_eq!(decls[2].ty.data, LogicType);
+    }
+
+    #[test]
+    fn generate_case() {
+        use crate::ast::*;
+        let root = parse(
+            "module M; case (N) 0, 1: x a(); 2: begin : g_two end default: begin end endcase endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        let gen = match m.items[0] {
+            HierarchyItem::GenerateCase(ref gen) => gen,
+            _ => panic!("expected case-generate statement"),
+        };
+        assert_eq!(gen.items.len(), 3);
+        match gen.items[0] {
+            GenerateCaseItem::Expr(ref exprs, ref block) => {
+                assert_eq!(exprs.len(), 2);
+                assert_eq!(block.items.len(), 1);
+            }
+            _ => panic!("expected case item"),
+        }
+        match gen.items[1] {
+            GenerateCaseItem::Expr(_, ref block) => {
+                assert_eq!(
+                    block.label.map(|n| n.to_string()),
+                    Some("g_two".to_string())
+                );
+            }
+            _ => panic!("expected case item"),
+        }
+        match gen.items[2] {
+            GenerateCaseItem::Default(..) => (),
+            _ => panic!("expected default item"),
+        }
+    }
+
+    #[test]
+    fn nonansi_port_list() {
+        use crate::ast::*;
+        let root = parse("module M(a, b[3:0], {c, d}, .e(f)); endmodule");
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        assert_eq!(m.ports.len(), 4);
+        assert!(m.ports.iter().all(|port| port.is_nonansi()));
+        match m.ports[0] {
+            Port::Implicit(Expr {
+                data: IdentExpr(..),
+                ..
+            }) => (),
+            _ => panic!("expected identifier port"),
+        }
+        match m.ports[1] {
+            Port::Implicit(Expr {
+                data: IndexExpr { ref index, .. },
+                ..
+            }) => match index.data {
+                RangeExpr {
+                    mode: RangeMode::Absolute,
+                    ..
+                } => (),
+                _ => panic!("expected range index"),
+            },
+            _ => panic!("expected part-select port"),
+        }
+        match m.ports[2] {
+            Port::Implicit(Expr {
+                data: ConcatExpr { .. },
+                ..
+            }) => (),
+            _ => panic!("expected concatenation port"),
+        }
+        match m.ports[3] {
+            Port::Explicit { .. } => (),
+            _ => panic!("expected explicit port"),
+        }
+    }
+
+    #[test]
+    fn export_decl() {
+        use crate::ast::*;
+        let root = parse("package P; import Q::*; export Q::*, Q::x; export *::*; endpackage");
+        let p = match root.items[0] {
+            Item::Package(ref p) => p,
+            _ => panic!("expected package"),
+        };
+        let items = match (&p.items[1], &p.items[2]) {
+            (HierarchyItem::ExportDecl(a), HierarchyItem::ExportDecl(b)) => (a, b),
+            _ => panic!("expected export declarations"),
+        };
+        assert_eq!(items.0.items.len(), 2);
+        assert!(items.0.items[0].pkg.is_some() && items.0.items[0].name.is_none());
+        assert!(items.0.items[1].name.is_some());
+        assert!(items.1.items[0].pkg.is_none() && items.1.items[0].name.is_none());
+    }
+
+    #[test]
+    fn net_strengths() {
+        use crate::ast::*;
+        let root = parse(
+            "module M; wire (weak0, pull1) a = b; trireg (small) c; \
+             assign (supply1, strong0) a = d; endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        match m.items[0] {
+            HierarchyItem::NetDecl(ref d) => assert_eq!(
+                d.strength,
+                Some(NetStrength::Drive(
+                    DriveStrength::Weak0,
+                    DriveStrength::Pull1
+                ))
+            ),
+            _ => panic!("expected net declaration"),
+        }
+        match m.items[1] {
+            HierarchyItem::NetDecl(ref d) => {
+                assert_eq!(d.strength, Some(NetStrength::Charge(ChargeStrength::Small)))
+            }
+            _ => panic!("expected net declaration"),
+        }
+        match m.items[2] {
+            HierarchyItem::ContAssign(ref a) => assert_eq!(
+                a.strength,
+                Some((DriveStrength::Supply1, DriveStrength::Strong0))
+            ),
+            _ => panic!("expected continuous assignment"),
+        }
+    }
+
+    #[test]
+    fn tagged_union_expr() {
+        use crate::ast::*;
+        let root = parse(
+            "module M; initial begin v = tagged Valid 42; v = tagged Invalid; \
+             case (v) matches tagged Valid .x: ; tagged Invalid: ; tagged Valid .*: ; \
+             default: ; endcase end endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        let body = match m.items[0] {
+            HierarchyItem::Procedure(ref p) => match p.stmt.data {
+                SequentialBlock(ref stmts) => stmts,
+                _ => panic!("expected sequential block"),
+            },
+            _ => panic!("expected procedure"),
+        };
+        match body[0].data {
+            BlockingAssignStmt { ref rhs, .. } => match rhs.data {
+                TaggedUnionExpr {
+                    ref member,
+                    expr: Some(_),
+                } => assert_eq!(&*member.name.as_str(), "Valid"),
+                _ => panic!("expected tagged union expression"),
+            },
+            _ => panic!("expected assignment"),
+        }
+        match body[1].data {
+            BlockingAssignStmt { ref rhs, .. } => match rhs.data {
+                TaggedUnionExpr { expr: None, .. } => (),
+                _ => panic!("expected tagged union expression without value"),
+            },
+            _ => panic!("expected assignment"),
+        }
+        match body[2].data {
+            CaseStmt {
+                mode: CaseMode::Pattern,
+                ref items,
+                ..
+            } => {
+                assert_eq!(items.len(), 4);
+                match items[0] {
+                    CaseItem::Expr(ref exprs, _) => match exprs[0].data {
+                        TaggedUnionExpr {
+                            expr: Some(ref var),
+                            ..
+                        } => match var.data {
+                            PatternVarExpr(Some(_)) => (),
+                            _ => panic!("expected pattern variable"),
+                        },
+                        _ => panic!("expected tagged pattern"),
+                    },
+                    _ => panic!("expected pattern item"),
+                }
+            }
+            _ => panic!("expected case matches statement"),
+        }
+    }
+
+    #[test]
+    fn defparam() {
+        use crate::ast::*;
+        let root = parse("module M; defparam u.W = 8, u.v.D = W + 1; endmodule");
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        match m.items[0] {
+            HierarchyItem::Defparam(ref decl) => {
+                assert_eq!(decl.assignments.len(), 2);
+                match decl.assignments[1].0.data {
+                    MemberExpr { ref expr, .. } => match expr.data {
+                        MemberExpr { .. } => (),
+                        _ => panic!("expected nested member access"),
+                    },
+                    _ => panic!("expected member access"),
+                }
+            }
+            _ => panic!("expected defparam"),
+        }
+    }
+
+    #[test]
+    fn tolerant() {
+        use crate::ast::*;
+        let (root, num_diags) = parse_tolerant(
+            "module Foo; logic a; logic b c; initial begin a = ; if (+) a = 1; a = 2; end endmodule module Bar; endmodule",
+        );
+        assert!(num_diags > 0);
+        assert_eq!(root.items.len(), 2);
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        assert_eq!(m.items.len(), 3);
+        match m.items[1] {
+            HierarchyItem::Error(_) => (),
+            _ => panic!("expected error item"),
+        }
+        let stmts = match m.items[2] {
+            HierarchyItem::Procedure(Procedure {
+                stmt:
+                    Stmt {
+                        data: SequentialBlock(ref stmts),
+                        ..
+                    },
+                ..
+            }) => stmts,
+            _ => panic!("expected procedure with block"),
+        };
+        assert_eq!(stmts.len(), 3);
+        match stmts[0].data {
+            BlockingAssignStmt { ref rhs, .. } => assert_eq!(rhs.data, ErrorExpr),
+            _ => panic!("expected assignment"),
+        }
+        match stmts[1].data {
+            IfStmt { ref cond, .. } => assert_eq!(cond.data, ErrorExpr),
+            _ => panic!("expected if statement"),
+        }
+        match stmts[2].data {
+            BlockingAssignStmt { .. } => (),
+            _ => panic!("expected assignment"),
+        }
+    }
+
+    #[test]
+    fn tolerant_exprs() {
+        use crate::ast::*;
+        let (root, num_diags) = parse_tolerant(
+            "module Foo; assign a = , b = 1; always @* begin a <= b + ; b = (a; end endmodule",
+        );
+        assert!(num_diags > 0);
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        match m.items[0] {
+            HierarchyItem::ContAssign(ref a) => {
+                assert_eq!(a.assignments.len(), 2);
+                assert_eq!(a.assignments[0].1.data, ErrorExpr);
+            }
+            _ => panic!("expected continuous assignment"),
+        }
+        let stmts = match m.items[1] {
+            HierarchyItem::Procedure(Procedure {
+                stmt:
+                    Stmt {
+                        data: TimedStmt(_, ref stmt),
+                        ..
+                    },
+                ..
+            }) => match stmt.data {
+                SequentialBlock(ref stmts) => stmts,
+                _ => panic!("expected block"),
+            },
+            _ => panic!("expected procedure"),
+        };
+        assert_eq!(stmts.len(), 2);
+        match stmts[0].data {
+            NonblockingAssignStmt { ref rhs, .. } => assert_eq!(rhs.data, ErrorExpr),
+            _ => panic!("expected non-blocking assignment"),
+        }
+        match stmts[1].data {
+            BlockingAssignStmt { ref rhs, .. } => assert_eq!(rhs.data, ErrorExpr),
+            _ => panic!("expected assignment"),
+        }
+    }
+
+    #[test]
+    fn class_decl() {
+        use crate::ast::*;
+        let root = parse(
+            "virtual class A; static int n; local rand bit [3:0] x, y; \
+             pure virtual function int get(); \
+             function new(); n = n + 1; endfunction endclass \
+             class B extends A; function new(); super.new(); this.x = 1; endfunction \
+             virtual function int get(); return x; endfunction endclass \
+             module M; A a = null; initial begin a = new; end endmodule",
+        );
+        let a = match root.items[0] {
+            Item::Class(ref c) => c,
+            _ => panic!("expected class"),
+        };
+        assert!(a.virt);
+        assert_eq!(a.items.len(), 4);
+        match a.items[1].data {
+            ClassItemData::Property(ref d) => assert_eq!(d.names.len(), 2),
+            _ => panic!("expected class property"),
+        }
+        match a.items[2].data {
+            ClassItemData::ExternSubroutine(_) => (),
+            _ => panic!("expected pure virtual prototype"),
+        }
+        match root.items[1] {
+            Item::Class(ref c) => assert!(c.extends.is_some()),
+            _ => panic!("expected class"),
+        }
+    }
+
+    #[test]
+    fn constraints() {
+        use crate::ast::*;
+        let root = parse(
+            "class A; rand int x, y; randc bit [3:0] z; rand int q [4]; \
+             constraint c { x inside {[0:9], 12}; x < y; } \
+             constraint d { if (x > 2) y == 1; else { y == 2; } x == 0 -> z != 3; } \
+             constraint e { z dist { 0 := 2, [1:7] :/ 5, 9 }; solve x, y before z; } \
+             constraint f { foreach (q[i]) q[i] < i; } endclass \
+             module M; initial begin int v; void'(std::randomize(v) with { v > 3; }); end endmodule",
+        );
+        let a = match root.items[0] {
+            Item::Class(ref c) => c,
+            _ => panic!("expected class"),
+        };
+        let items = |i: usize| match a.items[i].data {
+            ClassItemData::Constraint(ref c) => c.items.iter().map(|i| &i.data).collect::<Vec<_>>(),
+            _ => panic!("expected constraint"),
+        };
+        match items(4)[..] {
+            [ConstraintItemData::If(_, _, Some(_)), ConstraintItemData::Implication(..)] => (),
+            ref x => panic!("unexpected items {:?}", x),
+        }
+        match items(5)[..] {
+            [ConstraintItemData::Dist(_, ref d), ConstraintItemData::Solve(ref b, ref a)] => {
+                assert_eq!(d.len(), 3);
+                assert_eq!(
+                    d[1].weight.as_ref().map(|w| w.0),
+                    Some(DistWeight::PerRange)
+                );
+                assert!(d[2].weight.is_none());
+                assert_eq!((b.len(), a.len()), (2, 1));
+            }
+            ref x => panic!("unexpected items {:?}", x),
+        }
+        match items(6)[..] {
+            [ConstraintItemData::Foreach(_, ref v, _)] => assert_eq!(v.len(), 1),
+            ref x => panic!("unexpected items {:?}", x),
+        }
+    }
+
+    #[test]
+    fn process_control() {
+        use crate::ast::*;
+        let root = parse(
+            "module M; event e; initial begin : outer \
+             fork : f a = 1; begin wait (a) -> e; end join_any \
+             disable fork; wait fork; disable outer; end endmodule",
+        );
+        let m = match root.items[0] {
+            Item::Module(ref m) => m,
+            _ => panic!("expected module"),
+        };
+        let stmts = match m.items[1] {
+            HierarchyItem::Procedure(Procedure {
+                stmt:
+                    Stmt {
+                        data: SequentialBlock(ref stmts),
+                        ..
+                    },
+                ..
+            }) => stmts,
+            _ => panic!("expected procedure"),
+        };
+        let branches = match stmts[0].data {
+            ParallelBlock(ref branches, JoinKind::Any) => branches,
+            ref x => panic!("expected fork, got {:?}", x),
+        };
+        assert_eq!(stmts[0].label.map(|n| n.to_string()), Some("f".to_string()));
+        match branches[1].data {
+            SequentialBlock(ref inner) => match inner[0].data {
+                WaitExprStmt(_, ref stmt) => match stmt.data {
+                    EventTriggerStmt(_) => (),
+                    ref x => panic!("expected event trigger, got {:?}", x),
+                },
+                ref x => panic!("expected wait statement, got {:?}", x),
+            },
+            ref x => panic!("expected block, got {:?}", x),
+        }
+        match (&stmts[1].data, &stmts[2].data, &stmts[3].data) {
+            (DisableForkStmt, WaitForkStmt, DisableStmt(name)) => {
+                assert_eq!(name.to_string(), "outer")
+            }
+            x => panic!("unexpected statements {:?}", x),
+        }
+    }
 }
@@ -37,6 +37,11 @@ pub struct Preprocessor<'a> {
     defcond_stack: Vec<Defcond>,
     /// Currently enabled directives.
     dirs: Directives,
+    /// The tool prefixes recognized in `translate_off` and `translate_on`
+    /// comment pragmas, e.g. `synopsys` in `// synopsys translate_off`.
+    translate_pragmas: Vec<String>,
+    /// Whether we are inside a region disabled by a `translate_off` pragma.
+    translate_off: bool,
 }
 
 impl<'a> Preprocessor<'a> {
@@ -86,9 +91,29 @@ impl<'a> Preprocessor<'a> {
             include_paths: include_paths,
             defcond_stack: Vec::new(),
             dirs: Default::default(),
+            translate_pragmas: DEFAULT_TRANSLATE_PRAGMAS
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            translate_off: false,
         }
     }
 
+    /// Configure the tool prefixes recognized in `translate_off` and
+    /// `translate_on` comment pragmas.
+    ///
+    /// A comment whose first word is one of these prefixes and whose second
+    /// word is `translate_off` disables all subsequent text, until a matching
+    /// `translate_on` comment is encountered. Passing an empty list disables
+    /// pragma handling entirely.
+    pub fn set_translate_pragmas<I, S>(&mut self, prefixes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.translate_pragmas = prefixes.into_iter().map(Into::into).collect();
+    }
+
     /// Advance to the next token in the input stream.
     fn bump(&mut self) {
         self.token = self.macro_stack.pop();
@@ -865,12 +890,42 @@ impl<'a> Preprocessor<'a> {
     /// whether a preceeding `ifdef, `ifndef, `else, or `elsif directive have
     /// disabled the subsequent code.
     fn is_inactive(&self) -> bool {
+        if self.translate_off {
+            return true;
+        }
         match self.defcond_stack.last() {
             Some(&Defcond::Enabled) | None => false,
             _ => true,
         }
     }
 
+    /// Check whether a comment is a `translate_off` or `translate_on` pragma,
+    /// and update the translation state accordingly.
+    fn handle_comment_pragma(&mut self, span: Span) {
+        // Pragmas inside inactive define conditionals are ignored.
+        if !self.translate_off && self.is_inactive() {
+            return;
+        }
+        let text = span.extract();
+        let text = text
+            .trim_start_matches("//")
+            .trim_start_matches("/*")
+            .trim_end_matches("*/");
+        let mut words = text.split_whitespace();
+        let prefix = match words.next() {
+            Some(w) => w,
+            None => return,
+        };
+        if !self.translate_pragmas.iter().any(|p| p == prefix) {
+            return;
+        }
+        match words.next() {
+            Some("translate_off") => self.translate_off = true,
+            Some("translate_on") => self.translate_off = false,
+            _ => (),
+        }
+    }
+
     fn try_eat_name(&mut self) -> Option<(String, Span)> {
         // Eat the first token of the name, which may either be a letter or an
         // underscore.
@@ -897,6 +952,10 @@ impl<'a> Preprocessor<'a> {
     }
 }
 
+/// The tool prefixes recognized in `translate_off` and `translate_on` comment
+/// pragmas by default.
+pub const DEFAULT_TRANSLATE_PRAGMAS: &[&str] = &["synopsys", "synthesis", "pragma"];
+
 impl<'a> Iterator for Preprocessor<'a> {
     type Item = DiagResult2<TokenAndSpan>;
 
@@ -947,10 +1006,22 @@ impl<'a> Iterator for Preprocessor<'a> {
                         .span(sp_backtick)));
                     }
                 }
+                Some((Comment, sp)) => {
+                    // Comments may carry `translate_off` and `translate_on`
+                    // pragmas. The pragma comments themselves are emitted
+                    // as long as they are not inside a disabled region.
+                    let was_inactive = self.is_inactive();
+                    self.handle_comment_pragma(sp);
+                    let tkn = self.token.map(|x| Ok(x));
+                    self.bump();
+                    if !was_inactive || !self.is_inactive() {
+                        return tkn;
+                    }
+                }
                 _ => {
                     // All tokens other than preprocessor directives are
                     // emitted, unless we're currently inside a disabled define
-                    // conditional.
+                    // conditional or translate_off region.
                     if self.is_inactive() {
                         self.bump();
                     } else {
@@ -1215,4 +1286,21 @@ mod tests {
     fn macro_name_with_digits_and_underscores() {
         check_str("`define AXI_BUS21_SV 42\n`AXI_BUS21_SV", "42");
     }
+
+    #[test]
+    fn translate_off_region() {
+        check_str(
+            "a\n// synopsys translate_off\nb\n// synopsys translate_on\nc",
+            "a\n// synopsys translate_off// synopsys translate_on\nc",
+        );
+    }
+
+    #[test]
+    fn translate_pragmas_configurable() {
+        let mut pp = preproc("a /* foo translate_off */ b /* foo translate_on */ c");
+        pp.set_translate_pragmas(vec!["foo"]);
+        let actual: String = pp.map(|x| x.unwrap().1.extract()).collect();
+        assert_eq!(actual, "a /* foo translate_off *//* foo translate_on */ c");
+        check_str("a // foo translate_off\nb", "a // foo translate_off\nb");
+    }
 }
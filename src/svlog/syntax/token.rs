@@ -79,10 +79,12 @@ impl Token {
             OpenDelim(Brack) => "[",
             OpenDelim(Brace) => "{",
             OpenDelim(Bgend) => "begin",
+            OpenDelim(Attr) => "(*",
             CloseDelim(Paren) => ")",
             CloseDelim(Brack) => "]",
             CloseDelim(Brace) => "}",
             CloseDelim(Bgend) => "end",
+            CloseDelim(Attr) => "*)",
 
             Keyword(kw) => kw.as_str(),
 
@@ -114,6 +116,8 @@ pub enum DelimToken {
     Brace,
     /// A `begin` or `end`
     Bgend,
+    /// An attribute instance `(*` or `*)`
+    Attr,
 }
 
 /// Abstract literals such as strings.
@@ -125,6 +129,9 @@ pub enum Lit {
     UnbasedUnsized(char),
     /// A number given as integer and optional fractional part.
     Number(Name, Option<Name>),
+    /// A real number in scientific notation, given as integer part, optional
+    /// fractional part, and exponent, e.g. `1.5e-3`.
+    Real(Name, Option<Name>, Name),
     /// A time literal given as integer part, fractional part, and unit.
     Time(Name, Option<Name>, TimeUnit),
 }
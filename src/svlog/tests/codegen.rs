@@ -17,6 +17,21 @@ fn compile_module(name: &str, code: &str) -> String {
     module_to_string(&cg.finalize())
 }
 
+/// Compile a module in a piece of verilog code and check whether any errors
+/// were reported.
+fn compile_module_fails(name: &str, code: &str) -> bool {
+    simple_logger::init().is_ok();
+    let sess = Session::new();
+    let store = GlobalArenas::default();
+    let ast = parse(code);
+    let cx = GlobalContext::new(&sess, &store);
+    cx.add_root_nodes(ast.iter());
+    let m = cx.find_module(name.into()).unwrap();
+    let mut cg = CodeGenerator::new(&cx);
+    let result = cg.emit_module(m.into());
+    result.is_err() || sess.failed()
+}
+
 #[test]
 fn empty_module() {
     assert_eq!(
@@ -51,3 +66,481 @@ fn empty_instances() {
         "entity @bar () -> () {\n}\n\nentity @foo () -> () {\n    inst @bar () -> ()\n}"
     );
 }
+
+#[test]
+fn attributes_as_metadata() {
+    simple_logger::init().is_ok();
+    let sess = Session::new();
+    let store = GlobalArenas::default();
+    let ast = parse(
+        "
+        (* top *)
+        module foo;
+            (* keep, ram_style = \"block\", depth = 4 *) bit a;
+        endmodule
+        ",
+    );
+    let cx = GlobalContext::new(&sess, &store);
+    cx.add_root_nodes(ast.iter());
+    let m = cx.find_module("foo".into()).unwrap();
+    let mut cg = CodeGenerator::new(&cx);
+    cg.emit_module(m.into()).unwrap();
+    let (_, metadata) = cg.finalize_with_metadata();
+    let unit = llhd::ir::UnitName::global("foo");
+    let attrs = |value| {
+        metadata
+            .attrs_of(&unit, value)
+            .map(|a| (a.name.to_string(), a.value.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(attrs(None), vec![("top".to_string(), None)]);
+    assert_eq!(
+        attrs(Some("a")),
+        vec![
+            ("keep".to_string(), None),
+            ("ram_style".to_string(), Some("block".to_string())),
+            ("depth".to_string(), Some("4".to_string())),
+        ]
+    );
+    assert_eq!(
+        metadata.to_string(),
+        "@foo top\n@foo %a keep\n@foo %a ram_style = \"block\"\n@foo %a depth = \"4\"\n"
+    );
+}
+
+#[test]
+fn bound_instances() {
+    assert_eq!(
+        compile_module(
+            "foo",
+            "
+            module foo;
+            endmodule
+
+            module bar;
+            endmodule
+
+            bind foo bar b();
+            "
+        )
+        .trim(),
+        "entity @bar () -> () {\n}\n\nentity @foo () -> () {\n    inst @bar () -> ()\n}"
+    );
+}
+
+#[test]
+fn unsupported_binds_and_items() {
+    assert!(!compile_module_fails(
+        "foo",
+        "
+        module foo;
+            bar u0();
+        endmodule
+        module bar;
+        endmodule
+        module baz;
+        endmodule
+        bind bar baz b();
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        module foo;
+            bar u0();
+        endmodule
+        module bar;
+        endmodule
+        module baz;
+        endmodule
+        bind foo.u0 baz b();
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        module foo;
+            bar u0();
+        endmodule
+        module bar;
+        endmodule
+        module baz;
+        endmodule
+        bind bar : u0 baz b();
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "module foo; endmodule program P; endprogram"
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "module foo; endmodule checker C; endchecker"
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "module foo; endmodule config cfg; design foo; endconfig"
+    ));
+}
+
+#[test]
+fn flattened_interface_ports() {
+    assert_eq!(
+        compile_module(
+            "foo",
+            "
+            interface bus;
+                bit req;
+                bit gnt;
+                modport mst (input gnt, output req);
+            endinterface
+
+            module bar (bus.mst b);
+                assign b.req = b.gnt;
+            endmodule
+
+            module foo;
+                bus b();
+                bar x(b);
+            endmodule
+            "
+        )
+        .trim(),
+        "entity @bar.param1 (i1$ %b.gnt) -> (i1$ %b.req) {\n    %b.gnt1 = prb i1$ %b.gnt\n    %0 = const time 0s 1e\n    drv i1$ %b.req, %b.gnt1, %0\n}\n\nentity @foo () -> () {\n    %0 = const i1 0\n    %b.req = sig i1 %0\n    %1 = const i1 0\n    %b.gnt = sig i1 %1\n    inst @bar.param1 (i1$ %b.gnt) -> (i1$ %b.req)\n}"
+    );
+}
+
+#[test]
+fn modport_connections() {
+    assert!(compile_module(
+        "foo",
+        "
+        interface bus;
+            bit req;
+            bit gnt;
+            modport mst (input gnt, output req);
+        endinterface
+
+        module bar (bus b);
+            assign b.req = b.gnt;
+        endmodule
+
+        module foo;
+            bus b();
+            bar x(b.mst);
+        endmodule
+        "
+    )
+    .contains("entity @bar.param1 (i1$ %b.gnt) -> (i1$ %b.req) {"));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        interface bus;
+            bit req;
+            bit gnt;
+            modport mst (input gnt, output req);
+            modport slv (input req, output gnt);
+        endinterface
+
+        module bar (bus.mst b);
+        endmodule
+
+        module foo;
+            bus b();
+            bar x(b.slv);
+        endmodule
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        interface bus;
+            bit req;
+            bit gnt;
+            bit err;
+            modport mst (input gnt, output req);
+        endinterface
+
+        module bar (bus b, output bit q);
+            assign q = b.err;
+        endmodule
+
+        module foo;
+            bus b();
+            bit q;
+            bar x(b.mst, q);
+        endmodule
+        "
+    ));
+}
+
+#[test]
+fn interface_contents() {
+    let llhd = compile_module(
+        "foo",
+        "
+        interface intf;
+            bit a, b;
+            assign b = a;
+            initial a = 1;
+        endinterface
+        module foo;
+            intf i0();
+            intf i1();
+        endmodule
+        ",
+    );
+    assert!(llhd.contains("drv i1$ %i0.b, "));
+    assert!(llhd.contains("drv i1$ %i1.b, "));
+    assert!(llhd.contains("inst %foo.i0.initial."));
+    assert!(llhd.contains("inst %foo.i1.initial."));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        module bar;
+        endmodule
+        interface intf;
+            bar u0();
+        endinterface
+        module foo;
+            intf i0();
+        endmodule
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        interface intf;
+            if (1) begin : g
+                bit c;
+            end
+        endinterface
+        module foo;
+            intf i0();
+        endmodule
+        "
+    ));
+}
+
+#[test]
+fn function_calls() {
+    assert_eq!(
+        compile_module(
+            "foo",
+            "
+            module foo (input bit [7:0] a, output bit [7:0] b);
+                function bit [7:0] incr(bit [7:0] x);
+                    return x + 1;
+                endfunction
+                assign b = incr(a);
+            endmodule
+            "
+        )
+        .trim(),
+        "func @foo.incr (i8 %x) i8 {\n0:\n    %x.var = var i8 %x\n    %1 = const i8 0\n    %incr.ret = var i8 %1\n    %2 = const i32 0\n    %x1 = ld i8* %x.var\n    %3 = inss i32 %2, i8 %x1, 0, 8\n    %4 = const i32 1\n    %5 = add i32 %3, %4\n    %6 = exts i8, i32 %5, 0, 8\n    ret i8 %6\n7:\n    %8 = ld i8* %incr.ret\n    ret i8 %8\n}\n\nentity @foo (i8$ %a) -> (i8$ %b) {\n    %a1 = prb i8$ %a\n    %0 = call i8 @foo.incr (i8 %a1)\n    %1 = const time 0s 1e\n    drv i8$ %b, %0, %1\n}"
+    );
+}
+
+#[test]
+fn tasks_suspending_execution() {
+    let llhd = compile_module(
+        "foo",
+        "
+        module foo (input bit clk, output int n);
+            task automatic tick(output int count);
+                @(posedge clk) count = 1;
+                if (count > 0) return;
+                count = 2;
+            endtask
+            initial tick(n);
+        endmodule
+        ",
+    );
+    assert!(!llhd.contains("@foo.tick"));
+    assert!(llhd.contains("tick_exit"));
+    assert!(llhd.contains("wait %"));
+
+    assert!(compile_module_fails(
+        "foo",
+        "
+        module foo (input bit clk);
+            task automatic tick(int n);
+                @(posedge clk);
+                if (n > 0) tick(n - 1);
+            endtask
+            initial tick(4);
+        endmodule
+        ",
+    ));
+}
+
+#[test]
+fn unpacked_array_index() {
+    assert_eq!(
+        compile_module(
+            "foo",
+            "
+            module foo (input bit [1:0] a, output bit [7:0] b);
+                bit [7:0] mem [1:2] = '{8'h12, 8'h34};
+                assign b = mem[a];
+            endmodule
+            "
+        )
+        .trim(),
+        "entity @foo (i2$ %a) -> (i8$ %b) {\n    %0 = const i8 18\n    %1 = const i8 52\n    %2 = [i8 %0, %1]\n    %mem = sig [2 x i8] %2\n    %mem1 = prb [2 x i8]$ %mem\n    %a1 = prb i2$ %a\n    %3 = const i2 1\n    %4 = sub i2 %a1, %3\n    %5 = const i8 0\n    %6 = [2 x i8 %5]\n    %7 = shr [2 x i8] %mem1, [2 x i8] %6, i2 %4\n    %8 = extf i8, [2 x i8] %7, 0\n    %9 = const time 0s 1e\n    drv i8$ %b, %8, %9\n}"
+    );
+}
+
+#[test]
+fn class_specializations() {
+    let llhd = compile_module(
+        "foo",
+        "
+        class Box #(type T = int, int N = 2);
+            static int count;
+            T value;
+        endclass
+        module bar;
+            Box#(byte, 4) c;
+            initial c = new;
+        endmodule
+        module foo;
+            Box#(byte, 4) a;
+            Box#(byte, 2 + 2) b;
+            bar x();
+            initial begin
+                a = new;
+                b = a;
+            end
+        endmodule
+        ",
+    );
+    let news: std::collections::HashSet<_> = llhd
+        .split_whitespace()
+        .filter(|w| w.starts_with("@moore.class.new."))
+        .collect();
+    assert_eq!(news.len(), 1, "{}", llhd);
+    assert!(compile_module_fails(
+        "foo",
+        "
+        class Box #(type T = int, int N = 2);
+            static int count;
+            T value;
+        endclass
+        module foo;
+            Box#(byte, 4) a;
+            Box#(int, 2) b;
+            initial b = a;
+        endmodule
+        "
+    ));
+}
+
+#[test]
+fn virtual_methods_accessing_signals() {
+    assert!(!compile_module_fails(
+        "foo",
+        "
+        module foo;
+            bit [7:0] level;
+            class Probe;
+                function int get();
+                    return level;
+                endfunction
+            endclass
+            Probe p;
+            int a;
+            initial begin
+                p = new;
+                a = p.get();
+            end
+        endmodule
+        "
+    ));
+    assert!(compile_module_fails(
+        "foo",
+        "
+        module foo;
+            bit [7:0] level;
+            class Probe;
+                virtual function int get();
+                    return level;
+                endfunction
+            endclass
+            Probe p;
+            int a;
+            initial begin
+                p = new;
+                a = p.get();
+            end
+        endmodule
+        "
+    ));
+}
+
+#[test]
+fn real_declaration_initializers() {
+    let llhd = compile_module(
+        "foo",
+        "
+        module foo;
+            int i = 2.5;
+            real r = 3;
+        endmodule
+        ",
+    );
+    assert!(
+        llhd.contains("%0 = const i32 3\n    %i = sig i32 %0\n"),
+        "{}",
+        llhd
+    );
+    assert!(
+        llhd.contains("%1 = const i64 4613937818241073152\n    %r = sig i64 %1\n"),
+        "{}",
+        llhd
+    );
+}
+
+#[test]
+fn ansi_port_inheritance() {
+    let llhd = compile_module(
+        "foo",
+        "
+        module foo (
+            input logic [7:0] x, y,
+            output logic [7:0] z
+        );
+            assign z = x & y;
+        endmodule
+        ",
+    );
+    assert_eq!(
+        llhd.lines().next(),
+        Some("entity @foo (i8$ %x, i8$ %y) -> (i8$ %z) {")
+    );
+}
+
+#[test]
+fn port_declarations_default_to_nets() {
+    // `io` is a net and can be driven bidirectionally, while `q` has a data
+    // type and is a variable which may be assigned procedurally.
+    let llhd = compile_module(
+        "foo",
+        "
+        module foo (oe, d, io, q);
+            input oe, d;
+            inout io;
+            output logic q;
+            assign io = oe ? d : 1'bz;
+            always_comb q = io;
+        endmodule
+        ",
+    );
+    assert!(
+        llhd.contains(
+            "entity @foo (i1$ %oe, i1$ %d, i1$ %io.i) -> (i1$ %q, i1$ %io.o, i1$ %io.oe) {"
+        ),
+        "{}",
+        llhd
+    );
+}
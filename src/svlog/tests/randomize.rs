@@ -0,0 +1,144 @@
+// Copyright (c) 2016-2019 Fabian Schuiki
+
+mod common;
+use crate::common::*;
+use num::ToPrimitive;
+use std::collections::HashSet;
+
+/// Randomize an object of a class in a package a number of times, and return
+/// the values of its random properties, by name.
+fn randomize_class(code: &str, class: &str, times: usize) -> Vec<Vec<(String, i64)>> {
+    simple_logger::init().is_ok();
+    let sess = Session::new();
+    let store = GlobalArenas::default();
+    let ast = parse(code);
+    let cx = GlobalContext::new(&sess, &store);
+    cx.add_root_nodes(ast.iter());
+    let pkg = cx.find_package("pkg".into()).unwrap();
+    let class = cx.resolve_downwards(class.into(), pkg).unwrap().unwrap();
+    let mut rng = rand::Randomizer::new(42);
+    (0..times)
+        .map(|_| {
+            rand::randomize(&cx, class, cx.default_param_env(), &mut rng)
+                .unwrap()
+                .expect("constraints should be satisfiable")
+                .into_iter()
+                .map(|(id, value)| {
+                    let name = cx.span(id).extract();
+                    (name, value.get_int().unwrap().to_i64().unwrap())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn get(values: &[(String, i64)], name: &str) -> i64 {
+    values.iter().find(|(n, _)| n == name).unwrap().1
+}
+
+#[test]
+fn ranges_and_implications() {
+    let results = randomize_class(
+        "
+        package pkg;
+            class Frame;
+                rand bit [7:0] kind;
+                rand int size;
+                constraint c_kind { kind inside {1, 2, [10:12]}; }
+                constraint c_size {
+                    size > 0;
+                    kind == 1 -> size < 8;
+                    if (kind > 2) size inside {[100:200]}; else size >= 64;
+                }
+            endclass
+        endpackage
+        ",
+        "Frame",
+        50,
+    );
+    for values in &results {
+        let kind = get(values, "kind");
+        let size = get(values, "size");
+        assert!([1, 2, 10, 11, 12].contains(&kind), "kind = {}", kind);
+        assert!(size > 0);
+        assert!(kind != 1, "kind == 1 contradicts size >= 64");
+        if kind > 2 {
+            assert!(size >= 100 && size <= 200, "size = {}", size);
+        } else {
+            assert!(size >= 64, "size = {}", size);
+        }
+    }
+    let kinds: HashSet<_> = results.iter().map(|v| get(v, "kind")).collect();
+    assert!(kinds.len() > 1);
+}
+
+#[test]
+fn randc_cycles_through_all_values() {
+    let results = randomize_class(
+        "
+        package pkg;
+            class Token;
+                randc bit [2:0] id;
+                constraint c_id { id != 5; }
+            endclass
+        endpackage
+        ",
+        "Token",
+        7,
+    );
+    let ids: HashSet<_> = results.iter().map(|v| get(v, "id")).collect();
+    assert_eq!(ids.len(), 7);
+    assert!(!ids.contains(&5));
+}
+
+#[test]
+fn dist_weights() {
+    let results = randomize_class(
+        "
+        package pkg;
+            class Op;
+                rand bit [3:0] code;
+                constraint c_code { code dist { 0 := 0, 1 := 9, [2:3] :/ 1 }; }
+            endclass
+        endpackage
+        ",
+        "Op",
+        200,
+    );
+    let ones = results.iter().filter(|v| get(v, "code") == 1).count();
+    for values in &results {
+        let code = get(values, "code");
+        assert!(code >= 1 && code <= 3, "code = {}", code);
+    }
+    assert!(ones > 150, "only {} of 200 codes are 1", ones);
+}
+
+#[test]
+fn fixed_properties_and_functions() {
+    let results = randomize_class(
+        "
+        package pkg;
+            function int twice(int v);
+                return 2 * v;
+            endfunction
+            class Scaled;
+                int limit = 10;
+                bit [7:0] table_ [4] = '{1, 2, 3, 4};
+                int pick = 2;
+                rand bit [3:0] x;
+                rand bit [7:0] y;
+                constraint c_x { x >= 0; x < limit; }
+                constraint c_y { y == twice(x) + table_[pick]; }
+            endclass
+        endpackage
+        ",
+        "Scaled",
+        50,
+    );
+    for values in &results {
+        let x = get(values, "x");
+        let y = get(values, "y");
+        assert!(x < 10, "x = {}", x);
+        assert_eq!(y, 2 * x + 3);
+    }
+}
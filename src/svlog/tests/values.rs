@@ -0,0 +1,60 @@
+// Copyright (c) 2016-2019 Fabian Schuiki
+
+mod common;
+use crate::common::*;
+use num::ToPrimitive;
+
+/// Evaluate the parameters of a module in a piece of verilog code, by name.
+fn eval_params(code: &str, names: &[&str]) -> Vec<i64> {
+    simple_logger::init().is_ok();
+    let sess = Session::new();
+    let store = GlobalArenas::default();
+    let ast = parse(code);
+    let cx = GlobalContext::new(&sess, &store);
+    cx.add_root_nodes(ast.iter());
+    let m = cx.find_module("M".into()).unwrap();
+    names
+        .iter()
+        .map(|&name| {
+            let id = cx.resolve_downwards(name.into(), m).unwrap().unwrap();
+            let value = cx.constant_value_of(id, cx.default_param_env()).unwrap();
+            value.get_int().unwrap().to_i64().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn equality_of_negative_constants() {
+    let values = eval_params(
+        "
+        module M;
+            localparam bit A = -3 == 1;
+            localparam bit B = -1 == 1;
+            localparam bit C = -1 == -1;
+            localparam bit D = -3 != 1;
+            localparam bit E = -1 === 1;
+            localparam bit F = -5 == 5;
+        endmodule
+        ",
+        &["A", "B", "C", "D", "E", "F"],
+    );
+    assert_eq!(values, vec![0, 0, 1, 1, 0, 0]);
+}
+
+#[test]
+fn equality_of_mixed_width_and_sign() {
+    let values = eval_params(
+        "
+        module M;
+            localparam bit A = 4'b1111 == -1;
+            localparam bit B = 32'hffff_ffff == -1;
+            localparam bit C = 4'sb1111 == 8'sb1111_1111;
+            localparam bit D = 4'b1111 == 8'sb1111_1111;
+            localparam bit E = 8'd255 !== -1;
+            localparam bit F = 4'sb1000 ==? -8;
+        endmodule
+        ",
+        &["A", "B", "C", "D", "E", "F"],
+    );
+    assert_eq!(values, vec![0, 1, 1, 0, 1, 1]);
+}
@@ -17,6 +17,12 @@ pub enum TypeKind<'t> {
     Void,
     /// The `time` type.
     Time,
+    /// The `real` and `realtime` types.
+    Real,
+    /// The `shortreal` type.
+    ShortReal,
+    /// The `string` type.
+    String,
     /// A single bit type.
     Bit(Domain),
     /// An integer type.
@@ -31,6 +37,20 @@ pub enum TypeKind<'t> {
     Struct(NodeId),
     /// A packed array type.
     PackedArray(usize, Type<'t>),
+    /// An unpacked array type.
+    ///
+    /// The range determines how the array is indexed. Element `i` of the
+    /// array is accessed as `a[offset + i]`, regardless of the direction of
+    /// the range.
+    UnpackedArray(Range, Type<'t>),
+    /// A dynamic array type, e.g. `int x[]`.
+    DynamicArray(Type<'t>),
+    /// A queue type with an optional maximum index, e.g. `int x[$]` or
+    /// `int x[$:15]`.
+    Queue(Type<'t>, Option<usize>),
+    /// An associative array type with an optional index type, e.g.
+    /// `int x[string]` or `int x[*]`.
+    AssocArray(Type<'t>, Option<Type<'t>>),
     /// A single bit type.
     BitScalar { domain: Domain, sign: Sign },
     /// A simple bit vector type (SBVT).
@@ -43,6 +63,25 @@ pub enum TypeKind<'t> {
         range: Range,
         dubbed: bool,
     },
+    /// An interface instance, optionally restricted to a modport.
+    ///
+    /// The environment is the one in which the interface was instantiated.
+    Interface {
+        decl: NodeId,
+        env: ParamEnv,
+        modport: Option<NodeId>,
+    },
+    /// A handle to an object of a class.
+    ///
+    /// The environment holds the parameters the class is specialized with.
+    Class {
+        decl: NodeId,
+        name: Name,
+        env: ParamEnv,
+    },
+    /// The type of the `null` handle, which can be assigned to any class
+    /// handle.
+    Null,
 }
 
 /// The number of values each bit of a type can assume.
@@ -101,6 +140,24 @@ impl<'t> TypeKind<'t> {
         }
     }
 
+    /// Check if this is a `real` or `shortreal` type.
+    pub fn is_real(&self) -> bool {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.is_real(),
+            TypeKind::Real | TypeKind::ShortReal => true,
+            _ => false,
+        }
+    }
+
+    /// Check if this is the `string` type.
+    pub fn is_string(&self) -> bool {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.is_string(),
+            TypeKind::String => true,
+            _ => false,
+        }
+    }
+
     /// Check if this is a struct type.
     pub fn is_struct(&self) -> bool {
         match *self {
@@ -115,10 +172,64 @@ impl<'t> TypeKind<'t> {
         match *self {
             TypeKind::Named(_, _, ty) => ty.is_array(),
             TypeKind::PackedArray(..) => true,
+            TypeKind::UnpackedArray(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Check if this is an unpacked array type.
+    pub fn is_unpacked_array(&self) -> bool {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.is_unpacked_array(),
+            TypeKind::UnpackedArray(..) => true,
             _ => false,
         }
     }
 
+    /// Check if this is a dynamically sized array, queue, associative array,
+    /// or string.
+    ///
+    /// Values of these types are not bit vectors, but handles to a container
+    /// managed by the simulation runtime.
+    pub fn is_dynamic(&self) -> bool {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.is_dynamic(),
+            TypeKind::DynamicArray(..)
+            | TypeKind::Queue(..)
+            | TypeKind::AssocArray(..)
+            | TypeKind::String => true,
+            _ => false,
+        }
+    }
+
+    /// Get the element type of a dynamic array, queue, or associative array.
+    ///
+    /// The elements of a string are of type `byte`.
+    pub fn get_dynamic_element(&self) -> Option<Type<'t>> {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.get_dynamic_element(),
+            TypeKind::DynamicArray(e) | TypeKind::Queue(e, _) | TypeKind::AssocArray(e, _) => {
+                Some(e)
+            }
+            TypeKind::String => Some(&BYTE_TYPE),
+            _ => None,
+        }
+    }
+
+    /// Get the index type of a dynamic array, queue, or associative array.
+    ///
+    /// Dynamic arrays, queues, and strings are indexed by `int`. Associative
+    /// arrays with a wildcard index `[*]` are indexed by `longint`.
+    pub fn get_dynamic_index(&self) -> Option<Type<'t>> {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.get_dynamic_index(),
+            TypeKind::DynamicArray(..) | TypeKind::Queue(..) | TypeKind::String => Some(&INT_TYPE),
+            TypeKind::AssocArray(_, Some(index)) => Some(index),
+            TypeKind::AssocArray(_, None) => Some(&LONGINT_TYPE),
+            _ => None,
+        }
+    }
+
     /// Get the definition of a struct.
     pub fn get_struct_def(&self) -> Option<NodeId> {
         match *self {
@@ -128,11 +239,49 @@ impl<'t> TypeKind<'t> {
         }
     }
 
+    /// Check if this is an interface type.
+    pub fn is_interface(&self) -> bool {
+        self.get_interface().is_some()
+    }
+
+    /// Get the declaration, environment, and modport of an interface.
+    pub fn get_interface(&self) -> Option<(NodeId, ParamEnv, Option<NodeId>)> {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.get_interface(),
+            TypeKind::Interface { decl, env, modport } => Some((decl, env, modport)),
+            _ => None,
+        }
+    }
+
+    /// Check if this is a class handle.
+    pub fn is_class(&self) -> bool {
+        self.get_class().is_some()
+    }
+
+    /// Get the declaration and environment of a class handle.
+    pub fn get_class(&self) -> Option<(NodeId, ParamEnv)> {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.get_class(),
+            TypeKind::Class { decl, env, .. } => Some((decl, env)),
+            _ => None,
+        }
+    }
+
+    /// Check if this is the type of the `null` handle.
+    pub fn is_null(&self) -> bool {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.is_null(),
+            TypeKind::Null => true,
+            _ => false,
+        }
+    }
+
     /// Get the element type of an array.
     pub fn get_array_element(&self) -> Option<Type<'t>> {
         match *self {
             TypeKind::Named(_, _, ty) => ty.get_array_element(),
             TypeKind::PackedArray(_, e) => Some(e),
+            TypeKind::UnpackedArray(_, e) => Some(e),
             _ => None,
         }
     }
@@ -142,10 +291,74 @@ impl<'t> TypeKind<'t> {
         match *self {
             TypeKind::Named(_, _, ty) => ty.get_array_length(),
             TypeKind::PackedArray(l, _) => Some(l),
+            TypeKind::UnpackedArray(r, _) => Some(r.size),
+            _ => None,
+        }
+    }
+
+    /// Get the range of an array or bit vector.
+    ///
+    /// Packed arrays are always indexed as `[size-1:0]`.
+    pub fn get_range(&self) -> Option<Range> {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.get_range(),
+            TypeKind::PackedArray(size, _) => Some(Range {
+                size,
+                dir: RangeDir::Down,
+                offset: 0,
+            }),
+            TypeKind::UnpackedArray(range, _) => Some(range),
+            TypeKind::BitVector { range, .. } => Some(range),
             _ => None,
         }
     }
 
+    /// Get the dimensions of the type.
+    ///
+    /// The dimensions are listed from the outermost unpacked dimension to the
+    /// innermost packed dimension, which is the order in which the array
+    /// query functions such as `$left(x, n)` number them.
+    pub fn dims(&self) -> Vec<Range> {
+        let mut dims = vec![];
+        let mut ty = self;
+        loop {
+            match *ty {
+                TypeKind::Named(_, _, inner) => ty = inner,
+                TypeKind::UnpackedArray(range, inner) => {
+                    dims.push(range);
+                    ty = inner;
+                }
+                TypeKind::PackedArray(_, inner) => {
+                    dims.push(ty.get_range().unwrap());
+                    ty = inner;
+                }
+                TypeKind::BitVector { range, .. } => {
+                    dims.push(range);
+                    break;
+                }
+                TypeKind::Int(width, _) => {
+                    dims.push(Range {
+                        size: width,
+                        dir: RangeDir::Down,
+                        offset: 0,
+                    });
+                    break;
+                }
+                _ => break,
+            }
+        }
+        dims
+    }
+
+    /// Get the number of unpacked dimensions of the type.
+    pub fn num_unpacked_dims(&self) -> usize {
+        match *self {
+            TypeKind::Named(_, _, ty) => ty.num_unpacked_dims(),
+            TypeKind::UnpackedArray(_, ty) => 1 + ty.num_unpacked_dims(),
+            _ => 0,
+        }
+    }
+
     /// Get the width of the type.
     ///
     /// Panics if the type is not an integer.
@@ -250,6 +463,9 @@ impl<'t> Display for TypeKind<'t> {
             TypeKind::Error => write!(f, "<error>"),
             TypeKind::Void => write!(f, "void"),
             TypeKind::Time => write!(f, "time"),
+            TypeKind::Real => write!(f, "real"),
+            TypeKind::ShortReal => write!(f, "shortreal"),
+            TypeKind::String => write!(f, "string"),
             TypeKind::Bit(Domain::TwoValued) => write!(f, "bit"),
             TypeKind::Bit(Domain::FourValued) => write!(f, "logic"),
             TypeKind::Int(32, Domain::TwoValued) => write!(f, "int"),
@@ -258,7 +474,16 @@ impl<'t> Display for TypeKind<'t> {
             TypeKind::Int(width, Domain::FourValued) => write!(f, "integer<{}>", width),
             TypeKind::Named(name, ..) => write!(f, "{}", name.value),
             TypeKind::Struct(_) => write!(f, "struct"),
+            TypeKind::Interface { .. } => write!(f, "interface"),
+            TypeKind::Class { name, .. } => write!(f, "{}", name),
+            TypeKind::Null => write!(f, "null"),
             TypeKind::PackedArray(length, ty) => write!(f, "{} [{}:0]", ty, length - 1),
+            TypeKind::UnpackedArray(range, ty) => write!(f, "{} ${}", ty, range),
+            TypeKind::DynamicArray(ty) => write!(f, "{} $[]", ty),
+            TypeKind::Queue(ty, None) => write!(f, "{} $[$]", ty),
+            TypeKind::Queue(ty, Some(max)) => write!(f, "{} $[$:{}]", ty, max),
+            TypeKind::AssocArray(ty, None) => write!(f, "{} $[*]", ty),
+            TypeKind::AssocArray(ty, Some(index)) => write!(f, "{} $[{}]", ty, index),
             TypeKind::BitScalar { domain, sign } => {
                 write!(f, "{}", domain.bit_name())?;
                 if sign == Sign::Signed {
@@ -366,6 +591,18 @@ pub static VOID_TYPE: TypeKind<'static> = TypeKind::Void;
 /// The `time` type.
 pub static TIME_TYPE: TypeKind<'static> = TypeKind::Time;
 
+/// The `real` type.
+pub static REAL_TYPE: TypeKind<'static> = TypeKind::Real;
+
+/// The `shortreal` type.
+pub static SHORTREAL_TYPE: TypeKind<'static> = TypeKind::ShortReal;
+
+/// The `string` type.
+pub static STRING_TYPE: TypeKind<'static> = TypeKind::String;
+
+/// The type of `null`.
+pub static NULL_TYPE: TypeKind<'static> = TypeKind::Null;
+
 /// The `bit` type.
 pub static BIT_TYPE: TypeKind<'static> = TypeKind::BitScalar {
     domain: ty::Domain::TwoValued,
@@ -447,6 +684,8 @@ pub fn bit_size_of_type<'gcx>(
     match *ty {
         TypeKind::Error | TypeKind::Void => Ok(0),
         TypeKind::Time => panic!("time value has no bit size"),
+        TypeKind::Real => Ok(64),
+        TypeKind::ShortReal => Ok(32),
         TypeKind::Bit(_) => Ok(1),
         TypeKind::Int(width, _) => Ok(width),
         TypeKind::Named(_, _, ty) => bit_size_of_type(cx, ty, env),
@@ -458,13 +697,42 @@ pub fn bit_size_of_type<'gcx>(
                 }) => fields,
                 _ => unreachable!(),
             };
+            let def = cx.struct_def(struct_id)?;
             let mut size = 0;
             for &field in fields {
-                size += bit_size_of_type(cx, cx.type_of(field, env)?, env)?;
+                let field_size = bit_size_of_type(cx, cx.type_of(field, env)?, env)?;
+                if def.is_union() {
+                    size = std::cmp::max(size, field_size);
+                } else {
+                    size += field_size;
+                }
             }
-            Ok(size)
+            Ok(size + def.tag_width())
         }
         TypeKind::PackedArray(elements, ty) => Ok(elements * bit_size_of_type(cx, ty, env)?),
+        TypeKind::UnpackedArray(range, ty) => Ok(range.size * bit_size_of_type(cx, ty, env)?),
+        TypeKind::Interface { .. } => Ok(0),
+        TypeKind::DynamicArray(..) | TypeKind::Queue(..) | TypeKind::AssocArray(..) => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` has no fixed bit size", ty))
+                    .add_note("Dynamic arrays, queues, and associative arrays cannot be packed."),
+            );
+            Err(())
+        }
+        TypeKind::String => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` has no fixed bit size", ty))
+                    .add_note("Strings cannot be packed. Use a cast to an integral type instead."),
+            );
+            Err(())
+        }
+        TypeKind::Class { .. } | TypeKind::Null => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` has no fixed bit size", ty))
+                    .add_note("Class handles cannot be packed."),
+            );
+            Err(())
+        }
         TypeKind::BitScalar { .. } => Ok(1),
         TypeKind::BitVector {
             range: Range { size, .. },
@@ -501,6 +769,23 @@ pub fn identical(a: Type, b: Type) -> bool {
             sa == sb && identical(ta, tb)
         }
 
+        (TypeKind::UnpackedArray(ra, ta), TypeKind::UnpackedArray(rb, tb)) => {
+            ra.size == rb.size && identical(ta, tb)
+        }
+
+        (TypeKind::DynamicArray(ta), TypeKind::DynamicArray(tb)) => identical(ta, tb),
+
+        (TypeKind::Queue(ta, ma), TypeKind::Queue(tb, mb)) => ma == mb && identical(ta, tb),
+
+        (TypeKind::AssocArray(ta, ia), TypeKind::AssocArray(tb, ib)) => {
+            identical(ta, tb)
+                && match (ia, ib) {
+                    (Some(ia), Some(ib)) => identical(ia, ib),
+                    (None, None) => true,
+                    _ => false,
+                }
+        }
+
         _ => a == b,
     }
 }
@@ -517,6 +802,9 @@ mod tests {
         assert_eq!(format!("{}", INT_TYPE), "int");
         assert_eq!(format!("{}", INTEGER_TYPE), "integer");
         assert_eq!(format!("{}", LONGINT_TYPE), "longint");
+        assert_eq!(format!("{}", REAL_TYPE), "real");
+        assert_eq!(format!("{}", SHORTREAL_TYPE), "shortreal");
+        assert_eq!(format!("{}", STRING_TYPE), "string");
 
         // Check the direction and offset.
         assert_eq!(
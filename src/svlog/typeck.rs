@@ -1,11 +1,13 @@
 // Copyright (c) 2016-2019 Fabian Schuiki
 
 use crate::{
+    ast_map::AstNode,
     crate_prelude::*,
     hir::HirNode,
+    resolver::interface_of,
     ty::{bit_size_of_type, Domain, Sign, Type, TypeKind},
     value::ValueKind,
-    ParamEnv, ParamEnvBinding,
+    ParamEnv, ParamEnvBinding, ParamEnvSource,
 };
 use num::{cast::ToPrimitive, BigInt, One, Signed};
 
@@ -18,7 +20,106 @@ pub(crate) fn type_of<'gcx>(
     let hir = cx.hir_of(node_id)?;
     #[allow(unreachable_patterns)]
     match hir {
-        HirNode::Port(p) => cx.map_to_type(p.ty, env),
+        HirNode::Port(p) => {
+            // Ports given as an expression over the declarations in the
+            // module body adopt the type of that expression.
+            let ty = match (p.ty, p.expr) {
+                // Ports without a data type, e.g. `input a`, are of type
+                // `logic`.
+                (Some(ty), _) => match cx.hir_of(ty)? {
+                    HirNode::Type(hir::Type {
+                        kind: hir::TypeKind::Implicit,
+                        ..
+                    }) => return Ok(&ty::LOGIC_TYPE),
+                    _ => cx.map_to_type(ty, env)?,
+                },
+                (None, Some(expr)) => {
+                    let ty = cx.need_self_determined_type(expr, env);
+                    return if ty.is_error() { Err(()) } else { Ok(ty) };
+                }
+                (None, None) => unreachable!(),
+            };
+            // Interface ports adopt the parameters of the interface instance
+            // they are connected to.
+            if let Some((decl, _, modport)) = ty.get_interface() {
+                if let Some(ParamEnvBinding::Indirect(conn)) =
+                    cx.param_env_data(env).find_type(node_id)
+                {
+                    // Connections through a modport, such as `bus.mst`, must
+                    // match the modport of the port, and restrict generic
+                    // interface ports to that modport (IEEE 1800-2017 §25.5).
+                    let conn_ty = cx.type_of(conn.0, conn.1)?;
+                    match conn_ty.get_interface() {
+                        Some((conn_decl, _, Some(conn_modport)))
+                            if conn_decl == decl
+                                && modport.is_some()
+                                && modport != Some(conn_modport) =>
+                        {
+                            let modport = cx.hir_of(modport.unwrap())?;
+                            let conn_modport = cx.hir_of(conn_modport)?;
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "{} requires {}, but is connected to {}",
+                                    p.desc_full(),
+                                    modport.desc_full(),
+                                    conn_modport.desc_full()
+                                ))
+                                .span(cx.span(conn.0))
+                                .add_note(format!("{} declared here:", p.desc_full()))
+                                .span(p.human_span()),
+                            );
+                            return Err(());
+                        }
+                        Some((conn_decl, conn_env, conn_modport)) if conn_decl == decl => {
+                            return Ok(cx.intern_type(TypeKind::Interface {
+                                decl,
+                                env: conn_env,
+                                modport: modport.or(conn_modport),
+                            }));
+                        }
+                        _ => {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "{} requires an instance of {}",
+                                    p.desc_full(),
+                                    cx.hir_of(decl)?.desc_full()
+                                ))
+                                .span(cx.span(conn.0)),
+                            );
+                            return Err(());
+                        }
+                    }
+                }
+            }
+            Ok(ty)
+        }
+        HirNode::Inst(inst) => match interface_of(cx, node_id)? {
+            Some((decl, _)) => {
+                let target = match cx.hir_of(inst.target)? {
+                    HirNode::InstTarget(x) => x,
+                    _ => unreachable!(),
+                };
+                let intf_env = cx.param_env(ParamEnvSource::ModuleInst {
+                    module: decl,
+                    inst: node_id,
+                    env,
+                    pos: &target.pos_params,
+                    named: &target.named_params,
+                })?;
+                Ok(cx.intern_type(TypeKind::Interface {
+                    decl,
+                    env: intf_env,
+                    modport: None,
+                }))
+            }
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!("{} has no type", inst.desc_full()))
+                        .span(inst.human_span()),
+                );
+                Err(())
+            }
+        },
         HirNode::Expr(e) => Ok(type_of_expr(cx, e, env)),
         HirNode::ValueParam(p) => {
             if is_explicit_type(cx, p.ty)? {
@@ -48,10 +149,14 @@ pub(crate) fn type_of<'gcx>(
             Err(())
         }
         HirNode::VarDecl(d) => {
-            if is_explicit_type(cx, d.ty)? {
-                cx.map_to_type(d.ty, env)
+            if is_explicit_type(cx, d.ty)? || !d.dims.is_empty() {
+                let ty = cx.map_to_type(d.ty, env)?;
+                map_unpacked_dims(cx, ty, &d.dims, env)
             } else if let Some(init) = d.init {
                 cx.type_of(init, env)
+            } else if let AstNode::NetDecl(..) | AstNode::PortDecl(..) = cx.ast_of(d.id)? {
+                // Nets without a data type are of type `logic`.
+                Ok(&ty::LOGIC_TYPE)
             } else {
                 cx.emit(
                     DiagBuilder2::error(format!(
@@ -86,6 +191,11 @@ pub(crate) fn type_of<'gcx>(
             map_type_kind(cx, v.enum_id, env, hir, kind)
         }
         HirNode::Package(_) => Ok(&ty::VOID_TYPE),
+        HirNode::Subroutine(sub) => match sub.retty {
+            Some(retty) => map_subroutine_type(cx, retty, env),
+            None => Ok(&ty::VOID_TYPE),
+        },
+        HirNode::SubroutinePort(port) => map_subroutine_type(cx, port.ty, env),
         HirNode::Assign(_) => unreachable!("has no type: {:?}", hir),
         _ => {
             error!("{:#?}", hir);
@@ -107,23 +217,49 @@ fn type_of_expr<'gcx>(cx: &impl Context<'gcx>, expr: &'gcx hir::Expr, env: Param
         // These expressions are have a fully self-determined type.
         hir::ExprKind::IntConst { .. }
         | hir::ExprKind::TimeConst(..)
-        | hir::ExprKind::StringConst(..)
+        | hir::ExprKind::RealConst(..)
         | hir::ExprKind::Ident(..)
         | hir::ExprKind::Scope(..)
         | hir::ExprKind::Concat(..)
         | hir::ExprKind::Cast(..)
         | hir::ExprKind::Inside(..)
+        | hir::ExprKind::Call(..)
+        | hir::ExprKind::MethodCall(..)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayQuery(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealConv(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::BitQuery(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealMath(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealTime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Time)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::STime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Display(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Severity(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::SimControl(..))
         | hir::ExprKind::Field(..)
-        | hir::ExprKind::Index(..) => cx.need_self_determined_type(expr.id, env),
+        | hir::ExprKind::Index(..)
+        | hir::ExprKind::This
+        | hir::ExprKind::Super
+        | hir::ExprKind::Null
+        | hir::ExprKind::Randomize(..) => cx.need_self_determined_type(expr.id, env),
 
         // Unsized constants infer their type from the context if possible, and
-        // otherwise fall back to a self-determined mode.
+        // otherwise fall back to a self-determined mode. Real contexts are
+        // handled by an implicit cast instead.
         hir::ExprKind::UnsizedConst(..) => cx
             .type_context(expr.id, env)
             .map(|x| x.ty())
+            .filter(|ty| !ty.is_real())
+            .unwrap_or_else(|| cx.need_self_determined_type(expr.id, env)),
+
+        // String literals become strings in a string context, and are packed
+        // arrays of bytes otherwise. See "5.9 String literals".
+        hir::ExprKind::StringConst(..) => cx
+            .type_context(expr.id, env)
+            .map(|x| x.ty())
+            .filter(|ty| ty.is_string())
             .unwrap_or_else(|| cx.need_self_determined_type(expr.id, env)),
 
         // Unary operators either return their internal operation type, or they
@@ -175,6 +311,10 @@ fn type_of_expr<'gcx>(cx: &impl Context<'gcx>, expr: &'gcx hir::Expr, env: Param
                 // And some have a fixed return type.
                 hir::BinaryOp::Eq
                 | hir::BinaryOp::Neq
+                | hir::BinaryOp::CaseEq
+                | hir::BinaryOp::CaseNeq
+                | hir::BinaryOp::WildcardEq
+                | hir::BinaryOp::WildcardNeq
                 | hir::BinaryOp::Lt
                 | hir::BinaryOp::Leq
                 | hir::BinaryOp::Gt
@@ -202,7 +342,15 @@ fn type_of_expr<'gcx>(cx: &impl Context<'gcx>, expr: &'gcx hir::Expr, env: Param
         hir::ExprKind::PositionalPattern(..)
         | hir::ExprKind::NamedPattern(..)
         | hir::ExprKind::RepeatPattern(..)
-        | hir::ExprKind::EmptyPattern => cx.need_type_context(expr.id, env).ty(),
+        | hir::ExprKind::EmptyPattern
+        | hir::ExprKind::ArrayNew(..)
+        | hir::ExprKind::New(..) => cx.need_type_context(expr.id, env).ty(),
+
+        // Tagged union expressions and pattern variables take their type from
+        // the union they construct or match.
+        hir::ExprKind::Tagged(..) | hir::ExprKind::PatternVar(..) => {
+            cx.need_type_context(expr.id, env).ty()
+        }
     }
 }
 
@@ -243,6 +391,24 @@ pub(crate) fn map_to_type<'gcx>(
             Err(())
         }
         HirNode::Typedef(def) => cx.map_to_type(def.ty, env),
+        HirNode::Class(class) => Ok(cx.intern_type(TypeKind::Class {
+            decl: class.id,
+            name: class.name.value,
+            env,
+        })),
+        HirNode::Interface(intf) => Ok(cx.intern_type(TypeKind::Interface {
+            decl: intf.id,
+            env: cx.default_param_env(),
+            modport: None,
+        })),
+        HirNode::Modport(modport) => match cx.ast_of(node_id)? {
+            AstNode::Modport(_, intf) => Ok(cx.intern_type(TypeKind::Interface {
+                decl: intf,
+                env: cx.default_param_env(),
+                modport: Some(modport.id),
+            })),
+            _ => unreachable!(),
+        },
 
         // Certain expressions are actually types. In that case we also support
         // a mapping to a type.
@@ -269,6 +435,200 @@ pub(crate) fn map_to_type<'gcx>(
     }
 }
 
+/// Determine the type of a signal accessed through an interface.
+///
+/// The `binding` is the interface instance or port through which the `signal`
+/// is accessed. The signal's type is evaluated in the environment of the
+/// interface instance.
+pub(crate) fn type_of_intf_signal<'gcx>(
+    cx: &impl Context<'gcx>,
+    binding: NodeId,
+    signal: NodeId,
+    env: ParamEnv,
+) -> Result<Type<'gcx>> {
+    match cx.type_of(binding, env)?.get_interface() {
+        Some((_, intf_env, _)) => cx.type_of(signal, intf_env),
+        None => unreachable!("{:?} is not an interface", binding),
+    }
+}
+
+/// Check that a signal accessed through a generic interface port is listed in
+/// the modport the port is connected through.
+///
+/// Accesses through ports which declare a modport are checked when the
+/// signal is resolved.
+fn check_intf_modport_access<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &hir::Expr,
+    sig: &resolver::IntfSignal,
+    env: ParamEnv,
+) -> Result<()> {
+    if sig.dir.is_some() {
+        return Ok(());
+    }
+    let modport = match cx.type_of(sig.binding, env)?.get_interface() {
+        Some((_, _, Some(id))) => match cx.hir_of(id)? {
+            HirNode::Modport(x) => x,
+            _ => unreachable!(),
+        },
+        _ => return Ok(()),
+    };
+    let name = match cx.hir_of(sig.signal)? {
+        HirNode::VarDecl(x) => x.name,
+        HirNode::Port(x) => x.name,
+        _ => unreachable!(),
+    };
+    if modport.find(name.value).is_some() {
+        return Ok(());
+    }
+    let binding = cx.hir_of(sig.binding)?;
+    cx.emit(
+        DiagBuilder2::error(format!(
+            "`{}` is not accessible through {}",
+            name,
+            modport.desc_full()
+        ))
+        .span(expr.span())
+        .add_note(format!(
+            "{} is connected through {}",
+            binding.desc_full(),
+            modport.desc_full()
+        )),
+    );
+    Err(())
+}
+
+/// Determine the type of an interface instance or port restricted to a
+/// modport, such as `bus.mst`.
+fn type_of_intf_modport<'gcx>(
+    cx: &impl Context<'gcx>,
+    binding: NodeId,
+    modport: NodeId,
+    env: ParamEnv,
+) -> Result<Type<'gcx>> {
+    match cx.type_of(binding, env)?.get_interface() {
+        Some((decl, intf_env, _)) => Ok(cx.intern_type(TypeKind::Interface {
+            decl,
+            env: intf_env,
+            modport: Some(modport),
+        })),
+        None => unreachable!("{:?} is not an interface", binding),
+    }
+}
+
+/// Map the return or argument type of a function or task.
+///
+/// Implicit types default to a single `logic` bit.
+fn map_subroutine_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<Type<'gcx>> {
+    if is_explicit_type(cx, node_id)? {
+        cx.map_to_type(node_id, env)
+    } else {
+        Ok(&ty::LOGIC_TYPE)
+    }
+}
+
+/// Wrap a type in unpacked array dimensions.
+///
+/// The first dimension is the outermost, such that `T x [a][b]` declares an
+/// array of `a` arrays of `b` elements of type `T`. Dynamic arrays, queues, and
+/// associative arrays are handled here as well.
+fn map_unpacked_dims<'gcx>(
+    cx: &impl Context<'gcx>,
+    ty: Type<'gcx>,
+    dims: &[hir::UnpackedDim],
+    env: ParamEnv,
+) -> Result<Type<'gcx>> {
+    let mut ty = ty;
+    for dim in dims.iter().rev() {
+        let range = match *dim {
+            hir::UnpackedDim::Size(size) => {
+                let span = cx.span(size);
+                match cx.constant_int_value_of(size, env)?.to_usize() {
+                    Some(size) if size > 0 => ty::Range {
+                        size,
+                        dir: ty::RangeDir::Up,
+                        offset: 0,
+                    },
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "array size `{}` must be a positive integer",
+                                span.extract()
+                            ))
+                            .span(span),
+                        );
+                        return Err(());
+                    }
+                }
+            }
+            hir::UnpackedDim::Unsized => {
+                ty = cx.mkty_dynamic_array(ty);
+                continue;
+            }
+            hir::UnpackedDim::Queue(max) => {
+                let max = match max {
+                    Some(max) => match cx.constant_int_value_of(max, env)?.to_usize() {
+                        Some(max) => Some(max),
+                        None => {
+                            let span = cx.span(max);
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "queue bound `{}` must be a non-negative integer",
+                                    span.extract()
+                                ))
+                                .span(span),
+                            );
+                            return Err(());
+                        }
+                    },
+                    None => None,
+                };
+                ty = cx.mkty_queue(ty, max);
+                continue;
+            }
+            hir::UnpackedDim::Associative(index) => {
+                let index = match index {
+                    Some(index) => Some(cx.map_to_type(index, env)?),
+                    None => None,
+                };
+                ty = cx.mkty_assoc_array(ty, index);
+                continue;
+            }
+            hir::UnpackedDim::Range(lhs, rhs) => {
+                let lhs_int = cx.constant_int_value_of(lhs, env)?;
+                let rhs_int = cx.constant_int_value_of(rhs, env)?;
+                let (dir, lo, hi) = if lhs_int < rhs_int {
+                    (ty::RangeDir::Up, lhs_int, rhs_int)
+                } else {
+                    (ty::RangeDir::Down, rhs_int, lhs_int)
+                };
+                let size = (hi - lo) + BigInt::one();
+                match (size.to_usize(), lo.to_isize()) {
+                    (Some(size), Some(offset)) => ty::Range { size, dir, offset },
+                    _ => {
+                        let span = Span::union(cx.span(lhs), cx.span(rhs));
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "array `[{}]` is too large",
+                                span.extract()
+                            ))
+                            .span(span)
+                            .add_note(format!("array would contain {} elements", size)),
+                        );
+                        return Err(());
+                    }
+                }
+            }
+        };
+        ty = cx.mkty_unpacked_array(range, ty);
+    }
+    Ok(ty)
+}
+
 /// Check if a type (given by its node id) is explicit.
 fn is_explicit_type<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<bool> {
     Ok(match cx.hir_of(node_id)? {
@@ -284,8 +644,8 @@ fn map_type_kind<'gcx>(
     cx: &impl Context<'gcx>,
     node_id: NodeId,
     env: ParamEnv,
-    root: &hir::Type,
-    kind: &hir::TypeKind,
+    root: &'gcx hir::Type,
+    kind: &'gcx hir::TypeKind,
 ) -> Result<Type<'gcx>> {
     #[allow(unreachable_patterns)]
     match *kind {
@@ -298,9 +658,11 @@ fn map_type_kind<'gcx>(
         hir::TypeKind::Builtin(hir::BuiltinType::Integer) => Ok(&ty::INTEGER_TYPE),
         hir::TypeKind::Builtin(hir::BuiltinType::LongInt) => Ok(&ty::LONGINT_TYPE),
         hir::TypeKind::Builtin(hir::BuiltinType::Time) => Ok(&ty::LONGINT_TYPE), // TODO(fschuiki): Fix this
-        hir::TypeKind::Builtin(hir::BuiltinType::String) => {
-            Ok(cx.mkty_packed_array(1, &ty::BYTE_TYPE))
-        }
+        hir::TypeKind::Builtin(hir::BuiltinType::Real) => Ok(&ty::REAL_TYPE),
+        hir::TypeKind::Builtin(hir::BuiltinType::ShortReal) => Ok(&ty::SHORTREAL_TYPE),
+        hir::TypeKind::Builtin(hir::BuiltinType::String) => Ok(&ty::STRING_TYPE),
+        // Events are emulated as a bit that is toggled by every trigger.
+        hir::TypeKind::Builtin(hir::BuiltinType::Event) => Ok(&ty::BIT_TYPE),
         hir::TypeKind::Named(name) => {
             let binding = cx.resolve_upwards_or_error(name, node_id)?;
             Ok(cx.mkty_named(name, (binding, env)))
@@ -310,7 +672,58 @@ fn map_type_kind<'gcx>(
             let binding = cx.resolve_downwards_or_error(name, within)?;
             Ok(cx.mkty_named(name, (binding, env)))
         }
-        hir::TypeKind::Struct(..) => Ok(cx.mkty_struct(node_id)),
+        hir::TypeKind::Specialized(base, ref pos, ref named) => {
+            let class_id = cx.resolve_node(base, env)?;
+            match cx.hir_of(class_id)? {
+                HirNode::Class(class) => Ok(cx.intern_type(TypeKind::Class {
+                    decl: class.id,
+                    name: class.name.value,
+                    env: cx.param_env(ParamEnvSource::ClassSpecialization {
+                        class: class.id,
+                        env,
+                        pos,
+                        named,
+                    })?,
+                })),
+                hir => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} cannot be given parameters",
+                            hir.desc_full()
+                        ))
+                        .span(root.human_span())
+                        .add_note("only parameterized classes can be specialized"),
+                    );
+                    Err(())
+                }
+            }
+        }
+        hir::TypeKind::Struct(ref fields) => {
+            let def = cx.struct_def(node_id)?;
+            if def.is_union() && def.packed && !def.is_tagged() {
+                let mut expected = None;
+                for &field in fields {
+                    let size = bit_size_of_type(cx, cx.type_of(field, env)?, env)?;
+                    match expected {
+                        Some(expected) if expected != size => {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "members of packed union must all have the same size; \
+                                     {} has {} bits instead of {}",
+                                    cx.hir_of(field)?.desc_full(),
+                                    size,
+                                    expected
+                                ))
+                                .span(cx.span(field)),
+                            );
+                            return Err(());
+                        }
+                        _ => expected = Some(size),
+                    }
+                }
+            }
+            Ok(cx.mkty_struct(node_id))
+        }
         hir::TypeKind::PackedArray(ref inner, lhs, rhs) => {
             let map_bound = |bound: NodeId| -> Result<&num::BigInt> {
                 match cx.constant_value_of(bound, env)?.kind {
@@ -471,8 +884,14 @@ fn self_determined_expr_type<'gcx>(
         // Time constants are of time type.
         hir::ExprKind::TimeConst(_) => Some(cx.mkty_time()),
 
-        // String literals are of string type.
-        hir::ExprKind::StringConst(_) => Some(cx.mkty_packed_array(1, &ty::BYTE_TYPE)),
+        // Real constants are of real type.
+        hir::ExprKind::RealConst(_) => Some(&ty::REAL_TYPE),
+
+        // String literals are packed arrays of bytes, with the empty string
+        // being a single null byte.
+        hir::ExprKind::StringConst(v) => {
+            Some(cx.mkty_bit_vector(8 * std::cmp::max(1, v.value.as_str().chars().count())))
+        }
 
         // Identifiers and scoped identifiers inherit their type from the bound
         // node.
@@ -489,6 +908,16 @@ fn self_determined_expr_type<'gcx>(
         hir::ExprKind::Concat(repeat, ref exprs) => {
             let mut failed = false;
 
+            // If any of the fields is a string, the result is a string. See
+            // "11.4.12.2 String concatenation".
+            if exprs.iter().any(|&expr| {
+                cx.type_of(expr, env)
+                    .map(|ty| ty.is_string())
+                    .unwrap_or(false)
+            }) {
+                return Some(&ty::STRING_TYPE);
+            }
+
             // Determine the cumulative width of all fields.
             // TODO(fschuiki): Use a more benign function that does not fail,
             // but returns an option which can be used to hint the user at the
@@ -537,10 +966,76 @@ fn self_determined_expr_type<'gcx>(
         // The `inside` expression evaluates to a boolean.
         hir::ExprKind::Inside(..) => Some(&ty::LOGIC_TYPE),
 
+        // Function calls evaluate to the function's return type.
+        hir::ExprKind::Call(..) => Some(
+            cx.resolve_call(expr.id)
+                .and_then(|mapping| cx.type_of(mapping.subroutine, env))
+                .unwrap_or(&ty::ERROR_TYPE),
+        ),
+
+        // Method calls on class objects evaluate to the method's return type.
+        // Method calls on dynamic arrays, queues, and associative arrays
+        // evaluate to the result type of the corresponding operation.
+        hir::ExprKind::MethodCall(target, ..) => Some(
+            cx.resolve_class_call(expr.id, env)
+                .and_then(|call| match call {
+                    Some(call) => match call.mapping {
+                        Some(ref mapping) => cx.type_of(mapping.subroutine, call.env),
+                        None => unreachable!(),
+                    },
+                    None => resolver::resolve_container_method(cx, expr.id, env)
+                        .and_then(|(op, _)| Ok(op.result_type(cx.type_of(target, env)?))),
+                })
+                .unwrap_or(&ty::ERROR_TYPE),
+        ),
+
+        // `this` and `super` refer to the object a method was called on.
+        hir::ExprKind::This | hir::ExprKind::Super => {
+            Some(class_handle_type(cx, expr, env).unwrap_or(&ty::ERROR_TYPE))
+        }
+
+        // `null` is compatible with any class handle.
+        hir::ExprKind::Null => Some(&ty::NULL_TYPE),
+
+        // `randomize()` returns whether the constraints could be solved.
+        hir::ExprKind::Randomize(..) => Some(&ty::INT_TYPE),
+
         // Most builtin functions evaluate to the integer type.
         hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_)) => Some(&ty::INT_TYPE),
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayQuery(..)) => Some(&ty::INT_TYPE),
+
+        // The real conversion functions have a fixed return type, given in
+        // "20.5 Conversion functions".
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealConv(conv, _)) => Some(match conv {
+            hir::RealConv::RealToInt => &ty::INTEGER_TYPE,
+            hir::RealConv::IntToReal | hir::RealConv::BitsToReal => &ty::REAL_TYPE,
+            hir::RealConv::BitsToShortReal => &ty::SHORTREAL_TYPE,
+            hir::RealConv::RealToBits => cx.mkty_bit_vector(64),
+            hir::RealConv::ShortRealToBits => cx.mkty_bit_vector(32),
+        }),
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealTime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealMath(..)) => Some(&ty::REAL_TYPE),
+
+        // The bit vector functions return a count or a single bit, as given
+        // in "20.9 Bit vector system functions".
+        hir::ExprKind::Builtin(hir::BuiltinCall::BitQuery(query, _)) => Some(match query {
+            hir::BitQuery::CountOnes => &ty::INT_TYPE,
+            hir::BitQuery::OneHot | hir::BitQuery::OneHot0 | hir::BitQuery::IsUnknown => {
+                &ty::BIT_TYPE
+            }
+        }),
+
+        // The integer time functions return an unsigned 64 or 32 bit value, as
+        // given in "20.3 Simulation time system functions".
+        hir::ExprKind::Builtin(hir::BuiltinCall::Time) => Some(cx.mkty_bit_vector(64)),
+        hir::ExprKind::Builtin(hir::BuiltinCall::STime) => Some(cx.mkty_bit_vector(32)),
+
+        // Display, severity, and simulation control tasks produce no value.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Display(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Severity(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::SimControl(..)) => Some(&ty::VOID_TYPE),
 
         // Sign casts reflect their argument, but with the sign changed.
         hir::ExprKind::Builtin(hir::BuiltinCall::Signed(arg)) => cx
@@ -550,10 +1045,41 @@ fn self_determined_expr_type<'gcx>(
             .self_determined_type(arg, env)
             .map(|x| x.change_sign(cx, Sign::Unsigned)),
 
-        // Member field accesses resolve to the type of the member.
+        // Member field accesses resolve to the type of the member, the type
+        // of the signal for accesses into an interface, or the type of the
+        // declaration for accesses into a generate block, through a
+        // hierarchical name, or into a class object. Modports of interface
+        // instances restrict the type of the instance.
         hir::ExprKind::Field(..) => Some(
-            cx.resolve_field_access(expr.id, env)
-                .and_then(|(_, _, field_id)| cx.type_of(field_id, env))
+            cx.resolve_intf_signal(expr.id)
+                .and_then(|sig| match sig {
+                    Some(sig) => {
+                        check_intf_modport_access(cx, expr, &sig, env)?;
+                        type_of_intf_signal(cx, sig.binding, sig.signal, env)
+                    }
+                    None => match resolver::resolve_gen_block_field(cx, expr.id)? {
+                        Some(binding) => {
+                            let env = resolver::gen_block_field_env(cx, expr.id, env)?;
+                            cx.type_of(binding, env)
+                        }
+                        None => match cx.resolve_hier_name(expr.id)? {
+                            Some(hier) => {
+                                cx.type_of(hier.target, resolver::hier_name_env(cx, &hier, env)?)
+                            }
+                            None => match cx.resolve_class_property(expr.id, env)? {
+                                Some((prop, prop_env)) => cx.type_of(prop, prop_env),
+                                None => match resolver::resolve_intf_modport(cx, expr.id)? {
+                                    Some((binding, modport)) => {
+                                        type_of_intf_modport(cx, binding, modport, env)
+                                    }
+                                    None => cx
+                                        .resolve_field_access(expr.id, env)
+                                        .and_then(|(_, _, field_id)| cx.type_of(field_id, env)),
+                                },
+                            },
+                        },
+                    },
+                })
                 .unwrap_or(&ty::ERROR_TYPE),
         ),
 
@@ -586,7 +1112,7 @@ fn self_determined_expr_type<'gcx>(
             // directly index into, map it to the equivalent simple bit vector
             // type first.
             let target_ty = cx.type_of(target, env).unwrap_or(&ty::ERROR_TYPE);
-            match *target_ty {
+            match *target_ty.resolve_name() {
                 TypeKind::PackedArray(_, ty) => {
                     if let Some(width) = width {
                         cx.intern_type(TypeKind::PackedArray(width, ty))
@@ -594,6 +1120,20 @@ fn self_determined_expr_type<'gcx>(
                         ty
                     }
                 }
+                TypeKind::UnpackedArray(range, ty) => {
+                    if let Some(width) = width {
+                        cx.mkty_unpacked_array(
+                            ty::Range {
+                                size: width,
+                                dir: range.dir,
+                                offset: 0,
+                            },
+                            ty,
+                        )
+                    } else {
+                        ty
+                    }
+                }
                 TypeKind::Bit(domain) | TypeKind::Int(_, domain) => {
                     cx.intern_type(TypeKind::BitVector {
                         domain,
@@ -631,6 +1171,14 @@ fn self_determined_expr_type<'gcx>(
                     },
                     dubbed: false,
                 }),
+                TypeKind::DynamicArray(ty)
+                | TypeKind::Queue(ty, _)
+                | TypeKind::AssocArray(ty, _)
+                    if width.is_none() =>
+                {
+                    ty
+                }
+                TypeKind::String if width.is_none() => &ty::BYTE_TYPE,
                 TypeKind::Error => (target_ty),
                 _ => {
                     let desc = cx
@@ -674,6 +1222,10 @@ fn self_determined_expr_type<'gcx>(
             // Handle the self-determined cases.
             hir::BinaryOp::Eq
             | hir::BinaryOp::Neq
+            | hir::BinaryOp::CaseEq
+            | hir::BinaryOp::CaseNeq
+            | hir::BinaryOp::WildcardEq
+            | hir::BinaryOp::WildcardNeq
             | hir::BinaryOp::Lt
             | hir::BinaryOp::Leq
             | hir::BinaryOp::Gt
@@ -752,7 +1304,7 @@ pub(crate) fn operation_type<'gcx>(
                 | hir::UnaryOp::PreDec
                 | hir::UnaryOp::PostInc
                 | hir::UnaryOp::PostDec => {
-                    let tc = cx.type_context(node_id, env).map(|x| x.ty());
+                    let tc = operator_type_context(cx, node_id, env);
                     let targ = cx.self_determined_type(arg, env);
                     unify_operator_types(cx, env, tc.into_iter().chain(targ.into_iter()))
                 }
@@ -793,7 +1345,7 @@ pub(crate) fn operation_type<'gcx>(
                 | hir::BinaryOp::BitNor
                 | hir::BinaryOp::BitXor
                 | hir::BinaryOp::BitXnor => {
-                    let tc = cx.type_context(node_id, env).map(|x| x.ty());
+                    let tc = operator_type_context(cx, node_id, env);
                     let tlhs = cx.self_determined_type(lhs, env);
                     let trhs = cx.self_determined_type(rhs, env);
                     unify_operator_types(
@@ -809,6 +1361,10 @@ pub(crate) fn operation_type<'gcx>(
                 // use the maximum bit width of the operands.
                 hir::BinaryOp::Eq
                 | hir::BinaryOp::Neq
+                | hir::BinaryOp::CaseEq
+                | hir::BinaryOp::CaseNeq
+                | hir::BinaryOp::WildcardEq
+                | hir::BinaryOp::WildcardNeq
                 | hir::BinaryOp::Lt
                 | hir::BinaryOp::Leq
                 | hir::BinaryOp::Gt
@@ -827,7 +1383,7 @@ pub(crate) fn operation_type<'gcx>(
                 | hir::BinaryOp::LogicShR
                 | hir::BinaryOp::ArithShL
                 | hir::BinaryOp::ArithShR => {
-                    let tc = cx.type_context(node_id, env).map(|x| x.ty());
+                    let tc = operator_type_context(cx, node_id, env);
                     let sdt = cx.self_determined_type(lhs, env);
                     unify_operator_types(cx, env, tc.into_iter().chain(sdt.into_iter()))
                 }
@@ -851,7 +1407,7 @@ pub(crate) fn operation_type<'gcx>(
         // Ternary operators operate on the maximum bitwidth given by their
         // arguments (self-determined type) and the type context.
         hir::ExprKind::Ternary(_, lhs, rhs) => {
-            let tc = cx.type_context(node_id, env).map(|x| x.ty());
+            let tc = operator_type_context(cx, node_id, env);
             let tlhs = cx.self_determined_type(lhs, env);
             let trhs = cx.self_determined_type(rhs, env);
             unify_operator_types(
@@ -884,15 +1440,57 @@ pub(crate) fn operation_type<'gcx>(
     }
 }
 
+/// Get the type context of an operator.
+///
+/// Real and string type contexts are ignored, since they do not turn the
+/// operation itself into a real or string operation. For example, `real r =
+/// 1/2` performs an integer division. See "11.8.1 Rules for expression types".
+fn operator_type_context<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Option<Type<'gcx>> {
+    cx.type_context(node_id, env)
+        .map(|x| x.ty())
+        .filter(|ty| !ty.is_real() && !ty.is_string())
+}
+
 /// Determine the bit length, sign, and value domain of the types that influence
 /// an expression.
+///
+/// If any of the types is real, the result is real as well.
 fn unify_operator_types<'gcx>(
     cx: &impl Context<'gcx>,
     env: ParamEnv,
     types: impl Iterator<Item = Type<'gcx>>,
 ) -> Option<Type<'gcx>> {
+    let types: Vec<_> = types.collect();
+
+    // String operands turn the entire operation into a string operation.
+    if types.iter().any(|ty| ty.is_string()) {
+        return Some(&ty::STRING_TYPE);
+    }
+
+    // Class handles can only be compared and selected among, which happens
+    // on the class type itself.
+    if let Some(&ty) = types.iter().find(|ty| ty.is_class()) {
+        return Some(ty);
+    }
+    if types.iter().any(|ty| ty.is_null()) {
+        return Some(&ty::NULL_TYPE);
+    }
+
+    // Real operands turn the entire operation into a real operation.
+    if types.iter().any(|ty| ty.resolve_name() == &ty::REAL_TYPE) {
+        return Some(&ty::REAL_TYPE);
+    }
+    if types.iter().any(|ty| ty.is_real()) {
+        return Some(&ty::SHORTREAL_TYPE);
+    }
+
     // Map the iterator to a sequence of sign, domain, and bit width tuples.
     let inner: Vec<_> = types
+        .into_iter()
         .flat_map(|ty| {
             bit_size_of_type(cx, ty, env).map(|w| ((ty.get_sign(), ty.get_value_domain(), w)))
         })
@@ -967,15 +1565,73 @@ pub(crate) fn type_context<'gcx>(
         }
         // TODO(fschuiki): Ports
         HirNode::VarDecl(v)
-            if v.init == Some(onto) && is_explicit_type(cx, v.ty).unwrap_or(false) =>
+            if v.init == Some(onto)
+                && (is_explicit_type(cx, v.ty).unwrap_or(false) || !v.dims.is_empty()) =>
         {
-            Some(cx.map_to_type(v.ty, env).unwrap_or(&ty::ERROR_TYPE).into())
+            Some(cx.type_of(v.id, env).unwrap_or(&ty::ERROR_TYPE).into())
         }
         HirNode::ValueParam(v)
             if v.default == Some(onto) && is_explicit_type(cx, v.ty).unwrap_or(false) =>
         {
             Some(cx.map_to_type(v.ty, env).unwrap_or(&ty::ERROR_TYPE).into())
         }
+        HirNode::Stmt(hir::Stmt {
+            kind: hir::StmtKind::Return(Some(expr)),
+            ..
+        }) if *expr == onto => {
+            let mut id = onto;
+            while let Some(parent) = cx.parent_node_id(id) {
+                if let Ok(HirNode::Subroutine(sub)) = cx.hir_of(parent) {
+                    return Some(cx.type_of(sub.id, env).unwrap_or(&ty::ERROR_TYPE).into());
+                }
+                id = parent;
+            }
+            None
+        }
+        // The arguments passed to the base class in `extends base(a, b)`
+        // have the types of the base class constructor's ports.
+        HirNode::Class(class) => {
+            let (base_id, base_env) = resolver::class_base_type(cx, class.id, env).ok()??;
+            let base = match cx.hir_of(base_id).ok()? {
+                HirNode::Class(x) => x,
+                _ => unreachable!(),
+            };
+            let index = class
+                .extends
+                .as_ref()?
+                .1
+                .iter()
+                .position(|arg| arg.expr == Some(onto))?;
+            let sub = match cx.hir_of(base.constructor()?.id).ok()? {
+                HirNode::Subroutine(x) => x,
+                _ => unreachable!(),
+            };
+            let &port = sub.ports.get(index)?;
+            Some(cx.type_of(port, base_env).unwrap_or(&ty::ERROR_TYPE).into())
+        }
+        HirNode::SubroutinePort(p) if p.default == Some(onto) => {
+            Some(cx.type_of(p.id, env).unwrap_or(&ty::ERROR_TYPE).into())
+        }
+        HirNode::Stmt(hir::Stmt {
+            kind: hir::StmtKind::Assign { lhs, rhs, .. },
+            ..
+        }) => {
+            if *lhs == onto {
+                cx.self_determined_type(*rhs, env).map(Into::into)
+            } else if *rhs == onto {
+                cx.self_determined_type(*lhs, env).map(Into::into)
+            } else {
+                None
+            }
+        }
+        // The patterns of a `case matches` statement are matched against the
+        // case expression.
+        HirNode::Stmt(hir::Stmt {
+            kind: hir::StmtKind::CaseMatches { expr, ref ways, .. },
+            ..
+        }) if ways.iter().any(|&(pattern, _)| pattern == onto) => {
+            Some(cx.type_of(*expr, env).unwrap_or(&ty::ERROR_TYPE).into())
+        }
         // TODO(fschuiki): Statements
         _ => None,
     }
@@ -1077,6 +1733,10 @@ fn type_context_imposed_by_expr<'gcx>(
             // operands also do not impose a type context on their operands.
             hir::BinaryOp::Eq
             | hir::BinaryOp::Neq
+            | hir::BinaryOp::CaseEq
+            | hir::BinaryOp::CaseNeq
+            | hir::BinaryOp::WildcardEq
+            | hir::BinaryOp::WildcardNeq
             | hir::BinaryOp::Lt
             | hir::BinaryOp::Leq
             | hir::BinaryOp::Gt
@@ -1118,6 +1778,50 @@ fn type_context_imposed_by_expr<'gcx>(
             cx.self_determined_type(expr.id, env).map(Into::into)
         }
 
+        // Function and task calls impose the type of each port on the
+        // corresponding argument.
+        hir::ExprKind::Call(..) => {
+            let mapping = cx.resolve_call(expr.id).ok()?;
+            let &(port, _) = mapping.args.iter().find(|&&(_, arg)| arg == onto)?;
+            cx.type_of(port, env).ok().map(Into::into)
+        }
+
+        // Method calls on class objects impose the type of each port on the
+        // corresponding argument. Method calls on dynamic arrays, queues,
+        // associative arrays, and strings impose the argument types of the
+        // corresponding operation.
+        hir::ExprKind::MethodCall(target, ..) if onto != target => {
+            if let Some(call) = cx.resolve_class_call(expr.id, env).ok()? {
+                return class_call_arg_type(cx, &call, onto);
+            }
+            let (op, args) = resolver::resolve_container_method(cx, expr.id, env).ok()?;
+            let index = args.iter().position(|&arg| arg == onto)?;
+            let arg_tys = op.arg_types(cx.type_of(target, env).ok()?);
+            arg_tys.get(index).map(|&ty| ty.into())
+        }
+
+        // Class constructors impose the type of each port on the corresponding
+        // argument.
+        hir::ExprKind::New(..) => {
+            let call = cx.resolve_class_call(expr.id, env).ok()??;
+            class_call_arg_type(cx, &call, onto)
+        }
+
+        // Dynamic array constructors impose the `int` type on the size, and
+        // their own type on the initial value.
+        hir::ExprKind::ArrayNew(size, _) if onto == size => Some((&ty::INT_TYPE).into()),
+        hir::ExprKind::ArrayNew(_, Some(init)) if onto == init => cx.type_context(expr.id, env),
+
+        // Tagged union expressions impose the type of the selected member on
+        // their argument.
+        hir::ExprKind::Tagged(_, Some(arg)) if onto == arg => {
+            let (def_id, index) = resolver::resolve_tagged_member(cx, expr.id, env).ok()?;
+            let def = cx.struct_def(def_id).ok()?;
+            cx.map_to_type(def.fields[index].ty, env)
+                .ok()
+                .map(Into::into)
+        }
+
         // Sign casts forward their type context to the argument..
         hir::ExprKind::Builtin(hir::BuiltinCall::Signed(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsigned(_)) => {
@@ -1127,3 +1831,62 @@ fn type_context_imposed_by_expr<'gcx>(
         _ => None,
     }
 }
+
+/// Determine the type a class method or constructor imposes on an argument.
+fn class_call_arg_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    call: &resolver::ClassCall,
+    onto: NodeId,
+) -> Option<TypeContext<'gcx>> {
+    let mapping = call.mapping.as_ref()?;
+    let &(port, _) = mapping.args.iter().find(|&&(_, arg)| arg == onto)?;
+    cx.type_of(port, call.env).ok().map(Into::into)
+}
+
+/// Determine the type of `this` or `super` within a class method.
+fn class_handle_type<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx hir::Expr,
+    env: ParamEnv,
+) -> Result<Type<'gcx>> {
+    let keyword = match expr.kind {
+        hir::ExprKind::Super => "super",
+        _ => "this",
+    };
+    let (class, member) = match resolver::enclosing_class(cx, expr.id) {
+        Some(x) => x,
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!("`{}` can only be used within a class", keyword))
+                    .span(expr.span),
+            );
+            return Err(());
+        }
+    };
+    if member.is_static {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`{}` cannot be used in static member `{}`",
+                keyword, member.name
+            ))
+            .span(expr.span),
+        );
+        return Err(());
+    }
+    let (class_id, env) = match expr.kind {
+        hir::ExprKind::Super => match resolver::class_base_type(cx, class.id, env)? {
+            Some(x) => x,
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!("{} has no base class", class.desc_full()))
+                        .span(expr.span)
+                        .add_note(format!("{} declared here:", class.desc_full()))
+                        .span(class.human_span()),
+                );
+                return Err(());
+            }
+        },
+        _ => (class.id, env),
+    };
+    cx.map_to_type(class_id, env)
+}
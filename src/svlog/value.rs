@@ -14,11 +14,16 @@
 use crate::{
     crate_prelude::*,
     hir::HirNode,
+    rand,
     ty::{bit_size_of_type, Type, TypeKind},
     ParamEnv, ParamEnvBinding,
 };
 use bit_vec::BitVec;
-use num::{BigInt, BigRational, One, ToPrimitive, Zero};
+use num::{BigInt, BigRational, FromPrimitive, One, ToPrimitive, Zero};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
 
 /// A verilog value.
 pub type Value<'t> = &'t ValueData<'t>;
@@ -44,8 +49,14 @@ pub enum ValueKind<'t> {
     Int(BigInt, BitVec, BitVec),
     /// An arbitrary precision time interval.
     Time(BigRational),
+    /// A floating-point number.
+    Real(Real),
+    /// A string of bytes.
+    String(Vec<u8>),
     /// A struct.
     StructOrArray(Vec<Value<'t>>),
+    /// The `null` class handle.
+    Null,
 }
 
 impl<'t> ValueData<'t> {
@@ -60,7 +71,10 @@ impl<'t> ValueData<'t> {
             ValueKind::Void => true,
             ValueKind::Int(ref v, ..) => v.is_zero(),
             ValueKind::Time(ref v) => v.is_zero(),
+            ValueKind::Real(v) => v.0 == 0.0,
+            ValueKind::String(ref v) => v.is_empty(),
             ValueKind::StructOrArray(_) => false,
+            ValueKind::Null => true,
         }
     }
 
@@ -71,6 +85,74 @@ impl<'t> ValueData<'t> {
             _ => None,
         }
     }
+
+    /// Convert the value to a floating-point number.
+    ///
+    /// Integers are converted according to the sign of their type.
+    pub fn get_real(&self) -> Option<f64> {
+        match self.kind {
+            ValueKind::Real(v) => Some(v.0),
+            ValueKind::Int(ref v, ..) => {
+                let w = self.ty.width();
+                let v = if self.ty.resolve_name().is_signed()
+                    && w > 0
+                    && *v >= BigInt::one() << (w - 1)
+                {
+                    v - (BigInt::one() << w)
+                } else {
+                    v.clone()
+                };
+                Some(v.to_f64().unwrap_or(0.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Create a new string value.
+pub fn make_string(ty: Type, value: Vec<u8>) -> ValueData {
+    assert!(ty.is_string());
+    ValueData {
+        ty: ty,
+        kind: ValueKind::String(value),
+    }
+}
+
+/// Convert a string to an integer, with the first character in the most
+/// significant byte.
+pub fn string_to_int(value: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(num::bigint::Sign::Plus, value)
+}
+
+/// Convert an integer to a string.
+///
+/// Each byte of the integer becomes a character, with the most significant
+/// byte first. Null bytes are ignored. See "6.16 String data type".
+pub fn int_to_string(value: &BigInt) -> Vec<u8> {
+    let (_, mut bytes) = value.to_bytes_be();
+    bytes.retain(|&b| b != 0);
+    bytes
+}
+
+/// A floating-point number.
+///
+/// Reals are compared and hashed by their bit pattern, such that values can be
+/// interned.
+#[derive(Debug, Clone, Copy)]
+pub struct Real(pub f64);
+
+impl PartialEq for Real {
+    fn eq(&self, other: &Real) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Real {}
+
+impl Hash for Real {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state)
+    }
 }
 
 /// Create a new integer value.
@@ -122,6 +204,36 @@ pub fn make_time(value: BigRational) -> ValueData<'static> {
     }
 }
 
+/// Create a new real value.
+///
+/// Panics if `ty` is not a real type. Rounds the value to single precision if
+/// `ty` is `shortreal`.
+pub fn make_real(ty: Type, value: f64) -> ValueData {
+    let value = match *ty.resolve_name() {
+        TypeKind::Real => value,
+        TypeKind::ShortReal => value as f32 as f64,
+        _ => panic!("create real value `{}` with non-real type {:?}", value, ty),
+    };
+    ValueData {
+        ty: ty,
+        kind: ValueKind::Real(Real(value)),
+    }
+}
+
+/// Convert a floating-point number to an integer value.
+///
+/// Rounds to the nearest integer, with ties rounded away from zero, unless
+/// `truncate` is set, in which case the value is truncated towards zero. See
+/// IEEE 1800-2017 §6.12.2.
+pub fn make_int_from_real(ty: Type, value: f64, truncate: bool) -> ValueData {
+    let value = if truncate {
+        value.trunc()
+    } else {
+        value.round()
+    };
+    make_int(ty, BigInt::from_f64(value).unwrap_or_else(BigInt::zero))
+}
+
 /// Create a new struct value.
 pub fn make_struct<'t>(ty: Type<'t>, fields: Vec<Value<'t>>) -> ValueData<'t> {
     assert!(ty.is_struct());
@@ -133,13 +245,66 @@ pub fn make_struct<'t>(ty: Type<'t>, fields: Vec<Value<'t>>) -> ValueData<'t> {
 
 /// Create a new array value.
 pub fn make_array<'t>(ty: Type<'t>, elements: Vec<Value<'t>>) -> ValueData<'t> {
-    assert!(ty.is_array());
+    assert!(ty.is_array() || ty.is_dynamic());
     ValueData {
         ty: ty,
         kind: ValueKind::StructOrArray(elements),
     }
 }
 
+/// Create a new class object value.
+///
+/// The properties are listed in the order determined by `object_properties`.
+pub fn make_object<'t>(ty: Type<'t>, props: Vec<Value<'t>>) -> ValueData<'t> {
+    assert!(ty.is_class());
+    ValueData {
+        ty: ty,
+        kind: ValueKind::StructOrArray(props),
+    }
+}
+
+/// Determine the properties stored in an object of a class.
+///
+/// Lists the non-static properties of the base classes first, followed by the
+/// ones of the class itself.
+pub(crate) fn object_properties<'gcx>(
+    cx: &impl Context<'gcx>,
+    class_id: NodeId,
+    env: ParamEnv,
+) -> Result<Vec<(NodeId, ParamEnv)>> {
+    let mut props = match resolver::class_base_type(cx, class_id, env)? {
+        Some((base_id, base_env)) => object_properties(cx, base_id, base_env)?,
+        None => vec![],
+    };
+    let class = match cx.hir_of(class_id)? {
+        HirNode::Class(x) => x,
+        _ => unreachable!(),
+    };
+    props.extend(
+        class
+            .members
+            .iter()
+            .filter(|m| m.kind == hir::ClassMemberKind::Property && !m.is_static)
+            .map(|m| (m.id, env)),
+    );
+    Ok(props)
+}
+
+/// Determine the initial value of a class property.
+pub(crate) fn property_init_value<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<Value<'gcx>> {
+    let ty = cx.type_of(node_id, env)?;
+    match cx.hir_of(node_id)? {
+        HirNode::VarDecl(hir::VarDecl {
+            init: Some(init), ..
+        }) => Ok(cast_value(cx, cx.constant_value_of(*init, env)?, ty)),
+        _ => Ok(cx.type_default_value(ty)),
+    }
+}
+
 /// Determine the constant value of a node.
 pub(crate) fn constant_value_of<'gcx>(
     cx: &impl Context<'gcx>,
@@ -148,18 +313,20 @@ pub(crate) fn constant_value_of<'gcx>(
 ) -> Result<Value<'gcx>> {
     let hir = cx.hir_of(node_id)?;
     match hir {
-        HirNode::Expr(expr) => const_expr(cx, expr, env),
+        HirNode::Expr(expr) => const_expr(cx, expr, env, None),
         HirNode::ValueParam(param) => {
             let env_data = cx.param_env_data(env);
             match env_data.find_value(node_id) {
                 Some(ParamEnvBinding::Indirect(assigned_id)) => {
-                    return cx.constant_value_of(assigned_id.0, assigned_id.1)
+                    let value = cx.constant_value_of(assigned_id.0, assigned_id.1)?;
+                    return Ok(cast_value(cx, value, cx.type_of(node_id, env)?));
                 }
                 Some(ParamEnvBinding::Direct(v)) => return Ok(v),
                 _ => (),
             }
             if let Some(default) = param.default {
-                return cx.constant_value_of(default, env);
+                let value = cx.constant_value_of(default, env)?;
+                return Ok(cast_value(cx, value, cx.type_of(node_id, env)?));
             }
             let mut d = DiagBuilder2::error(format!(
                 "{} not assigned and has no default",
@@ -193,7 +360,12 @@ pub(crate) fn constant_value_of<'gcx>(
             );
             Err(())
         }
-        HirNode::VarDecl(_) => {
+        HirNode::VarDecl(_) | HirNode::SubroutinePort(_) | HirNode::Subroutine(_) => {
+            // Variables, arguments, and the return value of a function only
+            // have a value during the evaluation of a constant function.
+            if let Some(ParamEnvBinding::Direct(v)) = cx.param_env_data(env).find_value(node_id) {
+                return Ok(v);
+            }
             cx.emit(
                 DiagBuilder2::error(format!("{} has no constant value", hir.desc_full()))
                     .span(hir.human_span()),
@@ -208,11 +380,53 @@ pub(crate) fn constant_value_of<'gcx>(
     }
 }
 
+/// Values of variables which override the bindings of a parameter environment.
+///
+/// Unlike the bindings of a parameter environment, these are neither interned
+/// nor cached. This makes them suitable for values which only hold for a
+/// single evaluation, such as the candidate solutions of the constraint
+/// solver.
+pub type ScratchValues<'gcx> = HashMap<NodeId, Value<'gcx>>;
+
+/// Determine the constant value of a node, given scratch values for some
+/// variables.
+///
+/// Expressions are evaluated without going through the query cache, such that
+/// neither the results nor the scratch values persist after the evaluation.
+pub(crate) fn scratch_value_of<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+    scratch: &ScratchValues<'gcx>,
+) -> Result<Value<'gcx>> {
+    if let Some(&value) = scratch.get(&node_id) {
+        return Ok(value);
+    }
+    match cx.hir_of(node_id)? {
+        HirNode::Expr(expr) => const_expr(cx, expr, env, Some(scratch)),
+        _ => cx.constant_value_of(node_id, env),
+    }
+}
+
+/// Determine the constant value of an operand, given optional scratch values.
+fn value_of<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    env: ParamEnv,
+    scratch: Option<&ScratchValues<'gcx>>,
+) -> Result<Value<'gcx>> {
+    match scratch {
+        Some(scratch) => scratch_value_of(cx, node_id, env, scratch),
+        None => cx.constant_value_of(node_id, env),
+    }
+}
+
 /// Determine the constant value of an expression.
 fn const_expr<'gcx>(
     cx: &impl Context<'gcx>,
     expr: &hir::Expr,
     env: ParamEnv,
+    scratch: Option<&ScratchValues<'gcx>>,
 ) -> Result<Value<'gcx>> {
     let ty = cx.type_of(expr.id, env)?;
     #[allow(unreachable_patterns)]
@@ -230,15 +444,34 @@ fn const_expr<'gcx>(
         ))),
         hir::ExprKind::UnsizedConst('0') => Ok(cx.intern_value(make_int(ty, num::zero()))),
         hir::ExprKind::UnsizedConst('1') => Ok(cx.intern_value(make_int(ty, num::one()))),
+        hir::ExprKind::UnsizedConst(c) => {
+            let w = ty.width();
+            let x = c == 'x' || c == 'X';
+            Ok(cx.intern_value(make_int_special(
+                ty,
+                num::zero(),
+                BitVec::from_elem(w, true),
+                BitVec::from_elem(w, x),
+            )))
+        }
         hir::ExprKind::TimeConst(ref k) => Ok(cx.intern_value(make_time(k.clone()))),
-        hir::ExprKind::StringConst(_) => Ok(cx.intern_value(make_array(
-            // TODO(fschuiki): Actually assemble a real string here!
-            cx.mkty_packed_array(1, &ty::BYTE_TYPE),
-            vec![cx.intern_value(make_int(&ty::BYTE_TYPE, num::zero()))],
-        ))),
+        hir::ExprKind::RealConst(k) => Ok(cx.intern_value(make_real(ty, k.0))),
+        hir::ExprKind::StringConst(v) => {
+            let bytes = v
+                .value
+                .as_str()
+                .chars()
+                .map(|c| c as u8)
+                .collect::<Vec<_>>();
+            if ty.is_string() {
+                Ok(cx.intern_value(make_string(ty, bytes)))
+            } else {
+                Ok(cx.intern_value(make_int(ty, string_to_int(&bytes))))
+            }
+        }
         hir::ExprKind::Ident(_) | hir::ExprKind::Scope(..) => {
             let binding = cx.resolve_node(expr.id, env)?;
-            match cx.constant_value_of(binding, env) {
+            match value_of(cx, binding, env, scratch) {
                 Ok(k) => Ok(k),
                 Err(_) => {
                     let hir = cx.hir_of(binding)?;
@@ -254,12 +487,34 @@ fn const_expr<'gcx>(
             }
         }
         hir::ExprKind::Unary(op, arg) => {
-            let arg_val = cx.constant_value_of(arg, env)?;
+            let arg_val = value_of(cx, arg, env, scratch)?;
             match arg_val.kind {
                 ValueKind::Int(ref arg, ..) => Ok(cx.intern_value(make_int(
                     ty,
                     const_unary_op_on_int(cx, expr.span, ty, op, arg)?,
                 ))),
+                ValueKind::Real(arg) => match op {
+                    hir::UnaryOp::Pos => Ok(cast_value(cx, arg_val, ty)),
+                    hir::UnaryOp::Neg => Ok(cast_value(
+                        cx,
+                        cx.intern_value(make_real(arg_val.ty, -arg.0)),
+                        ty,
+                    )),
+                    hir::UnaryOp::LogicNot => {
+                        Ok(cx.intern_value(make_int(ty, ((arg.0 == 0.0) as usize).into())))
+                    }
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "{} cannot be applied to real `{}`",
+                                op.desc_full(),
+                                arg.0,
+                            ))
+                            .span(expr.span()),
+                        );
+                        Err(())
+                    }
+                },
                 _ => {
                     cx.emit(
                         DiagBuilder2::error(format!(
@@ -273,14 +528,31 @@ fn const_expr<'gcx>(
             }
         }
         hir::ExprKind::Binary(op, lhs, rhs) => {
-            let lhs_val = cx.constant_value_of(lhs, env)?;
-            let rhs_val = cx.constant_value_of(rhs, env)?;
+            let lhs_val = value_of(cx, lhs, env, scratch)?;
+            let rhs_val = value_of(cx, rhs, env, scratch)?;
             match (&lhs_val.kind, &rhs_val.kind) {
+                (&ValueKind::Int(..), &ValueKind::Int(..))
+                    if op == hir::BinaryOp::Eq
+                        || op == hir::BinaryOp::Neq
+                        || op == hir::BinaryOp::CaseEq
+                        || op == hir::BinaryOp::CaseNeq
+                        || op == hir::BinaryOp::WildcardEq
+                        || op == hir::BinaryOp::WildcardNeq =>
+                {
+                    let op_ty = cx.operation_type(expr.id, env).unwrap_or(&ty::ERROR_TYPE);
+                    Ok(cx.intern_value(const_equality_op_on_int(ty, op_ty, op, lhs_val, rhs_val)))
+                }
                 (&ValueKind::Int(ref lhs, ..), &ValueKind::Int(ref rhs, ..)) => Ok(cx
                     .intern_value(make_int(
                         ty,
                         const_binary_op_on_int(cx, expr.span, ty, op, lhs, rhs)?,
                     ))),
+                (&ValueKind::Real(_), _) | (_, &ValueKind::Real(_)) => {
+                    const_binary_op_on_real(cx, expr.span, ty, op, lhs_val, rhs_val)
+                }
+                (&ValueKind::String(_), _) | (_, &ValueKind::String(_)) => {
+                    const_binary_op_on_string(cx, expr.span, ty, op, lhs_val, rhs_val)
+                }
                 _ => {
                     cx.emit(
                         DiagBuilder2::error(format!(
@@ -297,7 +569,7 @@ fn const_expr<'gcx>(
             Ok(cx.intern_value(make_int(ty, num::zero())))
         }
         hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(arg)) => {
-            let arg_val = cx.constant_value_of(arg, env)?;
+            let arg_val = value_of(cx, arg, env, scratch)?;
             let arg_int = match arg_val.kind {
                 ValueKind::Int(ref arg, ..) => arg,
                 _ => unreachable!(),
@@ -316,18 +588,261 @@ fn const_expr<'gcx>(
                 bit_size_of_type(cx, ty, env)?.into(),
             )))
         }
+        hir::ExprKind::Builtin(hir::BuiltinCall::ArrayQuery(func, arg, dim)) => {
+            let ty = cx.type_of(arg, env)?;
+            let dims = ty.dims();
+            let value = match func {
+                hir::ArrayQuery::Dimensions => {
+                    let (num_dynamic, inner) = strip_dynamic_dims(ty);
+                    BigInt::from(num_dynamic + inner.dims().len())
+                }
+                hir::ArrayQuery::UnpackedDimensions => {
+                    let (num_dynamic, inner) = strip_dynamic_dims(ty);
+                    BigInt::from(num_dynamic + inner.num_unpacked_dims())
+                }
+                _ => {
+                    let dim = match dim {
+                        Some(dim) => cx.constant_int_value_of(dim, env)?.clone(),
+                        None => BigInt::one(),
+                    };
+                    let range = match dim.to_usize() {
+                        Some(i) if i >= 1 && i <= dims.len() => dims[i - 1],
+                        _ => {
+                            cx.emit(
+                                DiagBuilder2::error(format!("`{}` has no dimension {}", ty, dim))
+                                    .span(expr.span()),
+                            );
+                            return Err(());
+                        }
+                    };
+                    let low = range.offset;
+                    let high = range.offset + range.size as isize - 1;
+                    let (left, right) = match range.dir {
+                        ty::RangeDir::Up => (low, high),
+                        ty::RangeDir::Down => (high, low),
+                    };
+                    BigInt::from(match func {
+                        hir::ArrayQuery::Left => left,
+                        hir::ArrayQuery::Right => right,
+                        hir::ArrayQuery::Low => low,
+                        hir::ArrayQuery::High => high,
+                        hir::ArrayQuery::Increment if left >= right => 1,
+                        hir::ArrayQuery::Increment => -1,
+                        hir::ArrayQuery::Size => range.size as isize,
+                        hir::ArrayQuery::Dimensions | hir::ArrayQuery::UnpackedDimensions => {
+                            unreachable!()
+                        }
+                    })
+                }
+            };
+            Ok(cx.intern_value(make_int(&ty::INT_TYPE, value)))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealConv(conv, arg)) => {
+            let arg_val = value_of(cx, arg, env, scratch)?;
+            let bits = || arg_val.get_int().and_then(|v| v.to_u64()).unwrap_or(0);
+            let real = || arg_val.get_real().unwrap_or(0.0);
+            Ok(cx.intern_value(match conv {
+                hir::RealConv::RealToInt => make_int_from_real(ty, real(), true),
+                hir::RealConv::IntToReal => make_real(ty, real()),
+                hir::RealConv::RealToBits => make_int(ty, real().to_bits().into()),
+                hir::RealConv::BitsToReal => make_real(ty, f64::from_bits(bits())),
+                hir::RealConv::ShortRealToBits => make_int(ty, (real() as f32).to_bits().into()),
+                hir::RealConv::BitsToShortReal => {
+                    make_real(ty, f32::from_bits(bits() as u32) as f64)
+                }
+            }))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::BitQuery(query, arg)) => {
+            let arg_val = value_of(cx, arg, env, scratch)?;
+            let (value, special_bits) = match arg_val.kind {
+                ValueKind::Int(ref v, ref special_bits, _) => (v, special_bits),
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a bit vector",
+                            cx.span(arg).extract()
+                        ))
+                        .span(cx.span(arg)),
+                    );
+                    return Err(());
+                }
+            };
+            // The x and z bits are zero in the value itself.
+            let ones = value.to_str_radix(2).chars().filter(|&c| c == '1').count();
+            let unknown = special_bits.any();
+            Ok(cx.intern_value(make_int(
+                ty,
+                BigInt::from(match query {
+                    hir::BitQuery::CountOnes => ones,
+                    hir::BitQuery::OneHot => (ones == 1 && !unknown) as usize,
+                    hir::BitQuery::OneHot0 => (ones <= 1 && !unknown) as usize,
+                    hir::BitQuery::IsUnknown => unknown as usize,
+                }),
+            )))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::RealMath(func, x, y)) => {
+            let x = cast_value(cx, value_of(cx, x, env, scratch)?, ty);
+            let y = match y {
+                Some(y) => cast_value(cx, value_of(cx, y, env, scratch)?, ty).get_real(),
+                None => None,
+            };
+            Ok(cx.intern_value(make_real(
+                ty,
+                func.apply(x.get_real().unwrap_or(0.0), y.unwrap_or(0.0)),
+            )))
+        }
+        hir::ExprKind::Cast(_, arg) => Ok(cast_value(cx, value_of(cx, arg, env, scratch)?, ty)),
         hir::ExprKind::Ternary(cond, true_expr, false_expr) => {
-            let cond_val = cx.constant_value_of(cond, env)?;
+            let cond_val = value_of(cx, cond, env, scratch)?;
             match cond_val.is_true() {
-                true => cx.constant_value_of(true_expr, env),
-                false => cx.constant_value_of(false_expr, env),
+                true => value_of(cx, true_expr, env, scratch),
+                false => value_of(cx, false_expr, env, scratch),
             }
         }
+        hir::ExprKind::Inside(lhs, ref ranges) => {
+            let value = value_of(cx, lhs, env, scratch)?;
+            let value = value.get_int().cloned().unwrap_or_else(BigInt::zero);
+            let int = |id| -> Result<BigInt> {
+                let v = value_of(cx, id, env, scratch)?;
+                Ok(v.get_int().cloned().unwrap_or_else(BigInt::zero))
+            };
+            let mut inside = false;
+            for range in ranges {
+                inside |= match range.value {
+                    hir::InsideRange::Single(x) => int(x)? == value,
+                    hir::InsideRange::Range(lo, hi) => int(lo)? <= value && value <= int(hi)?,
+                };
+            }
+            Ok(cx.intern_value(make_int(ty, (inside as usize).into())))
+        }
+        hir::ExprKind::Index(target, hir::IndexMode::One(index)) => {
+            let target_ty = cx.type_of(target, env)?;
+            let target_value = value_of(cx, target, env, scratch)?;
+            let index = match scratch {
+                Some(_) => value_of(cx, index, env, scratch)?
+                    .get_int()
+                    .cloned()
+                    .unwrap_or_default(),
+                None => cx.constant_int_value_of(index, env)?.clone(),
+            };
+            match target_value.kind {
+                ValueKind::StructOrArray(ref elements) if target_ty.is_array() => {
+                    let offset = target_ty.get_range().unwrap().offset;
+                    match (&index - BigInt::from(offset)).to_usize() {
+                        Some(i) if i < elements.len() => Ok(elements[i]),
+                        _ => Ok(cx.type_default_value(ty)),
+                    }
+                }
+                ValueKind::Int(ref v, ..) if !target_ty.dims().is_empty() => {
+                    let range = *target_ty.dims().last().unwrap();
+                    let modulus = BigInt::one() << target_ty.width();
+                    let v = ((v % &modulus) + &modulus) % &modulus;
+                    let bit = match (&index - BigInt::from(range.offset)).to_usize() {
+                        Some(i) if i < range.size => (v >> i) & BigInt::one(),
+                        _ => BigInt::zero(),
+                    };
+                    Ok(cx.intern_value(make_int(ty.resolve_name(), bit)))
+                }
+                _ => cx.unimp_msg("constant value computation of", expr),
+            }
+        }
+        hir::ExprKind::New(_) => {
+            let (class_id, class_env) = ty.get_class().unwrap();
+            let mut next = Some((class_id, class_env));
+            while let Some((id, env)) = next {
+                let class = match cx.hir_of(id)? {
+                    HirNode::Class(x) => x,
+                    _ => unreachable!(),
+                };
+                if class.constructor().is_some() {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} cannot be constructed in a constant expression",
+                            cx.hir_of(class_id)?.desc_full()
+                        ))
+                        .span(expr.span())
+                        .add_note(format!(
+                            "{} has a constructor, which cannot be evaluated",
+                            class.desc_full()
+                        ))
+                        .span(class.human_span()),
+                    );
+                    return Err(());
+                }
+                next = resolver::class_base_type(cx, id, env)?;
+            }
+            let props = object_properties(cx, class_id, class_env)?
+                .into_iter()
+                .map(|(id, env)| property_init_value(cx, id, env))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(cx.intern_value(make_object(ty, props)))
+        }
+        hir::ExprKind::Null => Ok(cx.type_default_value(ty)),
+        hir::ExprKind::Randomize(..) => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be used in a constant expression",
+                    expr.desc_full()
+                ))
+                .span(expr.span())
+                .add_note(
+                    "`randomize()` may only be called as a statement, on the right-hand side \
+                     of an assignment, or in a `return` statement of a constant function",
+                ),
+            );
+            Err(())
+        }
         hir::ExprKind::Field(target, _field_name) => {
-            let (_, field_index, _) = cx.resolve_field_access(expr.id, env)?;
-            let target_value = cx.constant_value_of(target, env)?;
+            if let Some(binding) = resolver::resolve_gen_block_field(cx, expr.id)? {
+                let env = resolver::gen_block_field_env(cx, expr.id, env)?;
+                return value_of(cx, binding, env, scratch);
+            }
+            if let Some(hier) = cx.resolve_hier_name(expr.id)? {
+                return value_of(
+                    cx,
+                    hier.target,
+                    resolver::hier_name_env(cx, &hier, env)?,
+                    scratch,
+                );
+            }
+            if let Some((prop, _)) = cx.resolve_class_property(expr.id, env)? {
+                let (class_id, class_env) = cx.type_of(target, env)?.get_class().unwrap();
+                let index = object_properties(cx, class_id, class_env)?
+                    .iter()
+                    .position(|&(id, _)| id == prop);
+                let object = value_of(cx, target, env, scratch)?;
+                return match (index, &object.kind) {
+                    (Some(index), ValueKind::StructOrArray(ref props)) => Ok(props[index]),
+                    (None, _) => value_of(cx, prop, env, scratch),
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` accesses a property of a null object",
+                                expr.span().extract()
+                            ))
+                            .span(expr.span()),
+                        );
+                        Err(())
+                    }
+                };
+            }
+            let (_, field_index, field_id) = cx.resolve_field_access(expr.id, env)?;
+            let target_value = value_of(cx, target, env, scratch)?;
             match target_value.kind {
                 ValueKind::StructOrArray(ref fields) => Ok(fields[field_index]),
+                // Union members overlay the same bits, starting at the LSB.
+                ValueKind::Int(ref bits, ..) => {
+                    let field_ty = cx.type_of(field_id, env)?.resolve_name();
+                    match *field_ty {
+                        TypeKind::Bit(..)
+                        | TypeKind::Int(..)
+                        | TypeKind::BitVector { .. }
+                        | TypeKind::BitScalar { .. } => {
+                            Ok(cx.intern_value(make_int(field_ty, bits.clone())))
+                        }
+                        _ => cx.unimp_msg("constant union member access of", expr),
+                    }
+                }
                 _ => Err(()),
             }
         }
@@ -339,22 +854,26 @@ fn const_expr<'gcx>(
             trace!("resolved {:?} to {:#?}", expr.kind, resolved);
             let fields = resolved
                 .into_iter()
-                .map(|(_, v)| cx.constant_value_of(v, env))
+                .map(|(_, v)| value_of(cx, v, env, scratch))
                 .collect::<Result<Vec<_>>>()?;
             let ty = cx.type_of(expr.id, env)?;
             let v = cx.intern_value(match *ty {
                 TypeKind::Named(_, _, TypeKind::Struct(..)) | TypeKind::Struct(..) => {
                     make_struct(ty, fields)
                 }
-                TypeKind::Named(_, _, TypeKind::PackedArray(..)) | TypeKind::PackedArray(..) => {
-                    make_array(ty, fields)
-                }
+                _ if ty.is_array() => make_array(ty, fields),
                 _ => unreachable!(),
             });
             trace!("pattern yielded {:#?}", v);
             Ok(v)
         }
-        hir::ExprKind::EmptyPattern => {
+        hir::ExprKind::EmptyPattern
+        | hir::ExprKind::Builtin(hir::BuiltinCall::RealTime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Time)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::STime)
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Display(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Severity(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::SimControl(..)) => {
             cx.emit(
                 DiagBuilder2::error(format!("{} has no constant value", expr.desc_full()))
                     .span(expr.span()),
@@ -363,12 +882,592 @@ fn const_expr<'gcx>(
         }
         hir::ExprKind::Concat(..) => {
             let mir = cx.mir_rvalue(expr.id, env);
-            const_mir(cx, mir)
+            const_mir(cx, mir, scratch)
+        }
+        hir::ExprKind::MethodCall(target, ..) if cx.type_of(target, env)?.is_string() => {
+            let mir = cx.mir_rvalue(expr.id, env);
+            const_mir(cx, mir, scratch)
         }
+        hir::ExprKind::Call(..) => const_call(cx, expr, env, scratch),
         _ => cx.unimp_msg("constant value computation of", expr),
     }
 }
 
+/// Evaluate a call to a constant function.
+///
+/// The arguments, local variables, and return value of the function are
+/// tracked as direct bindings in a parameter environment, which is updated as
+/// the statements in the function body are executed. Scratch values are bound
+/// in that environment as well.
+fn const_call<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &hir::Expr,
+    env: ParamEnv,
+    scratch: Option<&ScratchValues<'gcx>>,
+) -> Result<Value<'gcx>> {
+    let mapping = cx.resolve_call(expr.id)?;
+    let sub = match cx.hir_of(mapping.subroutine)? {
+        HirNode::Subroutine(x) => x,
+        _ => unreachable!(),
+    };
+    if !sub.is_function() {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "{} cannot be called in a constant expression",
+                sub.desc_full()
+            ))
+            .span(expr.span()),
+        );
+        return Err(());
+    }
+    let mut env_data = cx.param_env_data(env).clone();
+    for &(port_id, arg) in &mapping.args {
+        let port = match cx.hir_of(port_id)? {
+            HirNode::SubroutinePort(x) => x,
+            _ => unreachable!(),
+        };
+        if port.dir != ast::SubroutinePortDir::Input {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be called in a constant expression",
+                    sub.desc_full()
+                ))
+                .span(expr.span())
+                .add_note(format!(
+                    "constant functions may only have input arguments, but {} is not",
+                    port.desc_full()
+                ))
+                .span(port.human_span()),
+            );
+            return Err(());
+        }
+        let value = value_of(cx, arg, env, scratch)?;
+        let value = cast_value(cx, value, cx.type_of(port_id, env)?);
+        env_data.set_value(port_id, value);
+    }
+    for (&id, &value) in scratch.into_iter().flatten() {
+        env_data.set_value(id, value);
+    }
+    let retty = cx.type_of(sub.id, env)?;
+    env_data.set_value(sub.id, cx.type_default_value(retty));
+    let mut env = cx.intern_param_env(env_data);
+    for &stmt in &sub.stmts {
+        if const_stmt(cx, sub.id, stmt, &mut env)? {
+            break;
+        }
+    }
+    cx.constant_value_of(sub.id, env)
+}
+
+/// Execute a statement in a constant function.
+///
+/// Updates `env` with the values assigned by the statement. Returns `true` if
+/// the statement returned from the function.
+fn const_stmt<'gcx>(
+    cx: &impl Context<'gcx>,
+    sub: NodeId,
+    stmt_id: NodeId,
+    env: &mut ParamEnv,
+) -> Result<bool> {
+    let stmt = match cx.hir_of(stmt_id)? {
+        HirNode::Stmt(x) => x,
+        HirNode::VarDecl(decl) => {
+            let ty = cx.type_of(decl.id, *env)?;
+            let value = match decl.init {
+                Some(init) => cast_value(cx, const_rvalue(cx, init, env)?, ty),
+                None => cx.type_default_value(ty),
+            };
+            const_assign(cx, decl.id, value, env);
+            return Ok(false);
+        }
+        hir => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be executed in a constant function",
+                    hir.desc_full()
+                ))
+                .span(hir.span()),
+            );
+            return Err(());
+        }
+    };
+    match stmt.kind {
+        hir::StmtKind::Null => (),
+        hir::StmtKind::Block(ref stmts) | hir::StmtKind::InlineGroup { ref stmts, .. } => {
+            for &stmt in stmts {
+                if const_stmt(cx, sub, stmt, env)? {
+                    return Ok(true);
+                }
+            }
+        }
+        hir::StmtKind::Assign {
+            lhs,
+            rhs,
+            kind: hir::AssignKind::Block(op),
+        } => {
+            let ty = cx.type_of(lhs, *env)?;
+            let mut value = const_rvalue(cx, rhs, env)?;
+            if op != ast::AssignOp::Identity {
+                let op = match op {
+                    ast::AssignOp::Identity => unreachable!(),
+                    ast::AssignOp::Add => hir::BinaryOp::Add,
+                    ast::AssignOp::Sub => hir::BinaryOp::Sub,
+                    ast::AssignOp::Mul => hir::BinaryOp::Mul,
+                    ast::AssignOp::Div => hir::BinaryOp::Div,
+                    ast::AssignOp::Mod => hir::BinaryOp::Mod,
+                    ast::AssignOp::BitAnd => hir::BinaryOp::BitAnd,
+                    ast::AssignOp::BitOr => hir::BinaryOp::BitOr,
+                    ast::AssignOp::BitXor => hir::BinaryOp::BitXor,
+                    ast::AssignOp::LogicShL => hir::BinaryOp::LogicShL,
+                    ast::AssignOp::LogicShR => hir::BinaryOp::LogicShR,
+                    ast::AssignOp::ArithShL => hir::BinaryOp::ArithShL,
+                    ast::AssignOp::ArithShR => hir::BinaryOp::ArithShR,
+                };
+                let current = cx.constant_value_of(lhs, *env)?;
+                value = match (current.get_int(), value.get_int()) {
+                    (Some(lhs), Some(rhs)) => cx.intern_value(make_int(
+                        ty,
+                        const_binary_op_on_int(cx, stmt.span, ty, op, lhs, rhs)?,
+                    )),
+                    _ => {
+                        cx.emit(
+                            DiagBuilder2::error(format!(
+                                "{} cannot be applied to the given arguments",
+                                op.desc_full(),
+                            ))
+                            .span(stmt.span),
+                        );
+                        return Err(());
+                    }
+                };
+            }
+            const_store(cx, lhs, cast_value(cx, value, ty), env)?;
+        }
+        hir::StmtKind::Expr(expr) => const_expr_stmt(cx, expr, env)?,
+        hir::StmtKind::If {
+            cond,
+            main_stmt,
+            else_stmt,
+        } => {
+            if cx.constant_value_of(cond, *env)?.is_true() {
+                return const_stmt(cx, sub, main_stmt, env);
+            } else if let Some(else_stmt) = else_stmt {
+                return const_stmt(cx, sub, else_stmt, env);
+            }
+        }
+        hir::StmtKind::Loop { kind, body } => {
+            let mut count = match kind {
+                hir::LoopKind::Repeat(count) => {
+                    let count = cx.constant_value_of(count, *env)?;
+                    Some(count.get_int().cloned().unwrap_or_else(BigInt::zero))
+                }
+                _ => None,
+            };
+            if let hir::LoopKind::For(init, ..) = kind {
+                const_stmt(cx, sub, init, env)?;
+            }
+            loop {
+                let proceed = match kind {
+                    hir::LoopKind::Forever | hir::LoopKind::Do(_) => true,
+                    hir::LoopKind::Repeat(_) => {
+                        let count = count.as_mut().unwrap();
+                        *count -= 1;
+                        *count >= BigInt::zero()
+                    }
+                    hir::LoopKind::While(cond) | hir::LoopKind::For(_, cond, _) => {
+                        cx.constant_value_of(cond, *env)?.is_true()
+                    }
+                };
+                if !proceed {
+                    break;
+                }
+                if const_stmt(cx, sub, body, env)? {
+                    return Ok(true);
+                }
+                match kind {
+                    hir::LoopKind::Do(cond) if !cx.constant_value_of(cond, *env)?.is_true() => {
+                        break
+                    }
+                    hir::LoopKind::For(_, _, step) => const_expr_stmt(cx, step, env)?,
+                    _ => (),
+                }
+            }
+        }
+        hir::StmtKind::Case {
+            expr,
+            ref ways,
+            default,
+            ..
+        } => {
+            let value = cx.constant_value_of(expr, *env)?;
+            for &(ref exprs, stmt) in ways {
+                for &way_expr in exprs {
+                    if cx.constant_value_of(way_expr, *env)?.get_int() == value.get_int() {
+                        return const_stmt(cx, sub, stmt, env);
+                    }
+                }
+            }
+            if let Some(default) = default {
+                return const_stmt(cx, sub, default, env);
+            }
+        }
+        hir::StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                let value = const_rvalue(cx, expr, env)?;
+                let value = cast_value(cx, value, cx.type_of(sub, *env)?);
+                const_assign(cx, sub, value, env);
+            }
+            return Ok(true);
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be executed in a constant function",
+                    stmt.desc_full()
+                ))
+                .span(stmt.span()),
+            );
+            return Err(());
+        }
+    }
+    Ok(false)
+}
+
+/// Execute an expression statement in a constant function.
+///
+/// Only increment and decrement operators, function calls, and calls to
+/// `randomize()` are supported.
+fn const_expr_stmt<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr_id: NodeId,
+    env: &mut ParamEnv,
+) -> Result<()> {
+    let expr = match cx.hir_of(expr_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!(),
+    };
+    match expr.kind {
+        hir::ExprKind::Unary(op, arg)
+            if op == hir::UnaryOp::PreInc
+                || op == hir::UnaryOp::PreDec
+                || op == hir::UnaryOp::PostInc
+                || op == hir::UnaryOp::PostDec =>
+        {
+            let current = cx.constant_value_of(arg, *env)?;
+            let next = match current.get_int() {
+                Some(v) if op == hir::UnaryOp::PreInc || op == hir::UnaryOp::PostInc => v + 1,
+                Some(v) => v - 1,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "{} cannot be applied to the given arguments",
+                            op.desc_full(),
+                        ))
+                        .span(expr.span()),
+                    );
+                    return Err(());
+                }
+            };
+            let value = cx.intern_value(make_int(current.ty, next));
+            const_store(cx, arg, value, env)?;
+        }
+        hir::ExprKind::Call(..) => {
+            cx.constant_value_of(expr_id, *env)?;
+        }
+        hir::ExprKind::Randomize(..) => {
+            const_randomize(cx, expr, env)?;
+        }
+        hir::ExprKind::Cast(_, arg) if cx.type_of(expr_id, *env)?.is_void() => {
+            const_expr_stmt(cx, arg, env)?;
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be executed in a constant function",
+                    expr.desc_full()
+                ))
+                .span(expr.span()),
+            );
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate the right-hand side of an assignment or the value of a `return`
+/// statement in a constant function.
+///
+/// Calls to `randomize()` are executed here, since they update `env` with the
+/// randomized variables.
+fn const_rvalue<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr_id: NodeId,
+    env: &mut ParamEnv,
+) -> Result<Value<'gcx>> {
+    match cx.hir_of(expr_id)? {
+        HirNode::Expr(
+            expr @ hir::Expr {
+                kind: hir::ExprKind::Randomize(..),
+                ..
+            },
+        ) => const_randomize(cx, expr, env),
+        _ => cx.constant_value_of(expr_id, *env),
+    }
+}
+
+/// Execute a call to `randomize()` in a constant function.
+///
+/// Assigns the randomized variables in `env`, or leaves them unchanged if the
+/// constraints cannot be satisfied. Returns 1 or 0 accordingly.
+fn const_randomize<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx hir::Expr,
+    env: &mut ParamEnv,
+) -> Result<Value<'gcx>> {
+    let (target, args, constraints) = match expr.kind {
+        hir::ExprKind::Randomize(target, ref args, ref constraints) => (target, args, constraints),
+        _ => unreachable!(),
+    };
+
+    // Take the random number generator out of the context for the duration
+    // of the call, since the constraints may call other constant functions.
+    // These draw from a generator forked off this one in the meantime.
+    let mut rng = {
+        let mut randomizer = cx.gcx().randomizer.borrow_mut();
+        let nested = randomizer.fork();
+        std::mem::replace(&mut *randomizer, nested)
+    };
+    let result = match target {
+        hir::RandomizeTarget::Std => args
+            .iter()
+            .map(|&arg| const_assign_target(cx, arg, *env))
+            .collect::<Result<Vec<_>>>()
+            .and_then(|vars| {
+                rand::randomize_vars(cx, &vars, constraints, *env, expr.span, &mut rng)
+            }),
+        hir::RandomizeTarget::Object(object) => {
+            const_randomize_object(cx, object, constraints, *env, expr.span, &mut rng)
+        }
+        hir::RandomizeTarget::This => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be evaluated in a constant function",
+                    expr.desc_full()
+                ))
+                .span(expr.span())
+                .add_note("Only objects held in local variables can be randomized."),
+            );
+            Err(())
+        }
+    };
+    cx.gcx().randomizer.replace(rng);
+
+    let success = match result? {
+        Some(values) => {
+            for (id, value) in values {
+                const_assign(cx, id, value, env);
+            }
+            true
+        }
+        None => false,
+    };
+    Ok(cx.intern_value(make_int(&ty::INT_TYPE, (success as usize).into())))
+}
+
+/// Randomize an object held in a local variable of a constant function.
+///
+/// Returns the new value of the variable.
+fn const_randomize_object<'gcx>(
+    cx: &impl Context<'gcx>,
+    object: NodeId,
+    constraints: &'gcx [hir::ConstraintItem],
+    env: ParamEnv,
+    span: Span,
+    rng: &mut rand::Randomizer,
+) -> Result<Option<Vec<(NodeId, Value<'gcx>)>>> {
+    let var = const_assign_target(cx, object, env)?;
+    let value = cx.constant_value_of(var, env)?;
+    let props = match value.kind {
+        ValueKind::StructOrArray(ref props) => props,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is null and cannot be randomized",
+                    cx.span(object).extract()
+                ))
+                .span(cx.span(object)),
+            );
+            return Err(());
+        }
+    };
+    let (class_id, class_env) = value.ty.get_class().unwrap();
+    let ids = object_properties(cx, class_id, class_env)?;
+    let state: Vec<_> = ids
+        .iter()
+        .map(|&(id, _)| id)
+        .zip(props.iter().cloned())
+        .collect();
+    let randomized = match rand::randomize_object(
+        cx,
+        class_id,
+        class_env,
+        &state,
+        Some((constraints, env)),
+        span,
+        rng,
+    )? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let props = state
+        .into_iter()
+        .map(|(id, value)| {
+            randomized
+                .iter()
+                .find(|&&(rid, _)| rid == id)
+                .map(|&(_, v)| v)
+                .unwrap_or(value)
+        })
+        .collect();
+    Ok(Some(vec![(
+        var,
+        cx.intern_value(make_object(value.ty, props)),
+    )]))
+}
+
+/// Resolve the target of an assignment in a constant function.
+fn const_assign_target<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr_id: NodeId,
+    env: ParamEnv,
+) -> Result<NodeId> {
+    let binding = match cx.hir_of(expr_id)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(..),
+            ..
+        }) => Some(cx.resolve_node(expr_id, env)?),
+        _ => None,
+    };
+    match binding.map(|id| cx.param_env_data(env).find_value(id).map(|_| id)) {
+        Some(Some(id)) => Ok(id),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` cannot be assigned in a constant function",
+                    cx.span(expr_id).extract()
+                ))
+                .span(cx.span(expr_id))
+                .add_note(
+                    "Only the local variables, arguments, and return value of the function \
+                     may be assigned.",
+                ),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Assign a value to a local variable, or to a property of an object held in a
+/// local variable, during the evaluation of a constant function.
+fn const_store<'gcx>(
+    cx: &impl Context<'gcx>,
+    lhs: NodeId,
+    value: Value<'gcx>,
+    env: &mut ParamEnv,
+) -> Result<()> {
+    let property = match cx.hir_of(lhs)? {
+        HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Field(target, _),
+            ..
+        }) => cx
+            .resolve_class_property(lhs, *env)?
+            .map(|(prop, _)| (*target, prop)),
+        _ => None,
+    };
+    let (target, prop) = match property {
+        Some(x) => x,
+        None => {
+            let target = const_assign_target(cx, lhs, *env)?;
+            const_assign(cx, target, value, env);
+            return Ok(());
+        }
+    };
+
+    // Static properties are not part of the object, and fall through to the
+    // error in `const_assign_target`.
+    let var = const_assign_target(cx, target, *env)?;
+    let (class_id, class_env) = cx.type_of(var, *env)?.get_class().unwrap();
+    let index = match object_properties(cx, class_id, class_env)?
+        .iter()
+        .position(|&(id, _)| id == prop)
+    {
+        Some(x) => x,
+        None => return const_assign_target(cx, lhs, *env).map(|_| ()),
+    };
+    let object = cx.constant_value_of(var, *env)?;
+    let mut props = match object.kind {
+        ValueKind::StructOrArray(ref props) => props.clone(),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` accesses a property of a null object",
+                    cx.span(lhs).extract()
+                ))
+                .span(cx.span(lhs)),
+            );
+            return Err(());
+        }
+    };
+    props[index] = value;
+    const_assign(cx, var, cx.intern_value(make_object(object.ty, props)), env);
+    Ok(())
+}
+
+/// Assign a value to a node during the evaluation of a constant function.
+fn const_assign<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    value: Value<'gcx>,
+    env: &mut ParamEnv,
+) {
+    let mut env_data = cx.param_env_data(*env).clone();
+    env_data.set_value(node_id, value);
+    *env = cx.intern_param_env(env_data);
+}
+
+/// Convert an integer or real value to a different integer or real type.
+///
+/// Values of other types are returned unchanged.
+pub(crate) fn cast_value<'gcx>(
+    cx: &impl Context<'gcx>,
+    value: Value<'gcx>,
+    ty: Type<'gcx>,
+) -> Value<'gcx> {
+    let ty = ty.resolve_name();
+    let is_int = match *ty {
+        TypeKind::Bit(..)
+        | TypeKind::Int(..)
+        | TypeKind::BitVector { .. }
+        | TypeKind::BitScalar { .. } => true,
+        _ => false,
+    };
+    match value.kind {
+        ValueKind::Int(ref v, ..) if is_int && value.ty != ty => {
+            cx.intern_value(make_int(ty, v.clone()))
+        }
+        ValueKind::Int(..) if ty.is_real() => {
+            cx.intern_value(make_real(ty, value.get_real().unwrap()))
+        }
+        ValueKind::Real(v) if is_int => cx.intern_value(make_int_from_real(ty, v.0, false)),
+        ValueKind::Real(v) if ty.is_real() && value.ty != ty => cx.intern_value(make_real(ty, v.0)),
+        ValueKind::Int(ref v, ..) if ty.is_string() => {
+            cx.intern_value(make_string(ty, int_to_string(v)))
+        }
+        ValueKind::String(ref v) if is_int => cx.intern_value(make_int(ty, string_to_int(v))),
+        _ => value,
+    }
+}
+
 fn const_unary_op_on_int<'gcx>(
     cx: &impl Context<'gcx>,
     span: Span,
@@ -418,8 +1517,12 @@ fn const_binary_op_on_int<'gcx>(
             }
             result
         }
-        hir::BinaryOp::Eq => ((lhs == rhs) as usize).into(),
-        hir::BinaryOp::Neq => ((lhs != rhs) as usize).into(),
+        hir::BinaryOp::Eq | hir::BinaryOp::CaseEq | hir::BinaryOp::WildcardEq => {
+            ((lhs == rhs) as usize).into()
+        }
+        hir::BinaryOp::Neq | hir::BinaryOp::CaseNeq | hir::BinaryOp::WildcardNeq => {
+            ((lhs != rhs) as usize).into()
+        }
         hir::BinaryOp::Lt => ((lhs < rhs) as usize).into(),
         hir::BinaryOp::Leq => ((lhs <= rhs) as usize).into(),
         hir::BinaryOp::Gt => ((lhs > rhs) as usize).into(),
@@ -461,20 +1564,341 @@ fn const_binary_op_on_int<'gcx>(
     })
 }
 
-fn const_mir<'gcx>(cx: &impl Context<'gcx>, mir: &'gcx mir::Rvalue<'gcx>) -> Result<Value<'gcx>> {
+/// Apply an equality operator to two constant integers.
+///
+/// The operands are extended to the width of the operation type `op_ty`
+/// before they are compared, sign-extended if it is signed and zero-extended
+/// otherwise. Honors the x and z bits of the operands. The logical equality
+/// operators yield x if the result is ambiguous due to x or z bits, the case
+/// equality operators compare x and z bits literally, and the wildcard
+/// equality operators treat x and z bits in the right-hand side as wildcards.
+/// See "11.4.5 Equality operators" and "11.4.6 Wildcard equality operators".
+fn const_equality_op_on_int<'gcx>(
+    ty: Type<'gcx>,
+    op_ty: Type<'gcx>,
+    op: hir::BinaryOp,
+    lhs: Value<'gcx>,
+    rhs: Value<'gcx>,
+) -> ValueData<'gcx> {
+    let (width, signed) = match int_width(op_ty) {
+        Some(width) => (width, op_ty.is_signed()),
+        None => (
+            std::cmp::max(four_state_width(lhs), four_state_width(rhs)),
+            false,
+        ),
+    };
+    let lhs = four_state_bits(lhs, width, signed);
+    let rhs = four_state_bits(rhs, width, signed);
+    let is_special = |b: char| b == 'x' || b == 'z';
+
+    // Compare the bits, yielding `None` if the result is ambiguous.
+    let equal = match op {
+        hir::BinaryOp::CaseEq | hir::BinaryOp::CaseNeq => Some(lhs == rhs),
+        _ => {
+            let wildcard = op == hir::BinaryOp::WildcardEq || op == hir::BinaryOp::WildcardNeq;
+            let mut ambiguous = false;
+            let mut mismatch = false;
+            for (&a, &b) in lhs.iter().zip(rhs.iter()) {
+                if wildcard && is_special(b) {
+                    continue;
+                } else if is_special(a) || is_special(b) {
+                    ambiguous = true;
+                } else if a != b {
+                    mismatch = true;
+                }
+            }
+            if mismatch {
+                Some(false)
+            } else if ambiguous {
+                None
+            } else {
+                Some(true)
+            }
+        }
+    };
+    let negate = op == hir::BinaryOp::Neq
+        || op == hir::BinaryOp::CaseNeq
+        || op == hir::BinaryOp::WildcardNeq;
+    let w = ty.width();
+    match equal {
+        Some(equal) => make_int(ty, ((equal != negate) as usize).into()),
+        None => make_int_special(
+            ty,
+            num::zero(),
+            BitVec::from_elem(w, true),
+            BitVec::from_elem(w, true),
+        ),
+    }
+}
+
+/// Determine the width of an integer type, or `None` if it is not one.
+fn int_width(ty: Type) -> Option<usize> {
+    match *ty.resolve_name() {
+        TypeKind::Bit(..)
+        | TypeKind::Int(..)
+        | TypeKind::BitScalar { .. }
+        | TypeKind::BitVector { .. } => Some(ty.width()),
+        _ => None,
+    }
+}
+
+/// Determine the number of bits in a constant integer.
+///
+/// This is the width of the value's type, or the number of bits needed to
+/// represent it if the type has no width.
+fn four_state_width(value: Value) -> usize {
+    match value.kind {
+        ValueKind::Int(ref v, ref special_bits, _) => int_width(value.ty)
+            .unwrap_or_else(|| std::cmp::max(v.bits() as usize + 1, special_bits.len())),
+        _ => 0,
+    }
+}
+
+/// Decompose a constant integer into its `0`, `1`, `x`, and `z` bits.
+///
+/// The value is taken as a two's complement number of its own width, which
+/// is then sign-extended to `width` if `signed` is set, and zero-extended
+/// otherwise. The bits are returned LSB first.
+fn four_state_bits(value: Value, width: usize, signed: bool) -> Vec<char> {
+    let (v, special_bits, x_bits) = match value.kind {
+        ValueKind::Int(ref v, ref s, ref x) => (v, s, x),
+        _ => unreachable!(),
+    };
+    let own_width = four_state_width(value);
+    let modulus = BigInt::one() << own_width;
+    let v = ((v % &modulus) + &modulus) % &modulus;
+    let n = special_bits.len();
+    let mut bits: Vec<char> = (0..own_width)
+        .map(|i| {
+            if i < n && special_bits[n - 1 - i] {
+                if x_bits.get(n - 1 - i).unwrap_or(false) {
+                    'x'
+                } else {
+                    'z'
+                }
+            } else if ((&v >> i) & BigInt::one()).is_one() {
+                '1'
+            } else {
+                '0'
+            }
+        })
+        .collect();
+    let fill = match bits.last() {
+        Some(&msb) if signed => msb,
+        _ => '0',
+    };
+    bits.resize(width, fill);
+    bits
+}
+
+/// Apply a binary operator to two constants, at least one of which is real.
+///
+/// Integer operands are converted to real. Arithmetic results are converted
+/// to `ty`, which is the operation type.
+fn const_binary_op_on_real<'gcx>(
+    cx: &impl Context<'gcx>,
+    span: Span,
+    ty: Type<'gcx>,
+    op: hir::BinaryOp,
+    lhs: Value<'gcx>,
+    rhs: Value<'gcx>,
+) -> Result<Value<'gcx>> {
+    let (a, b) = match (lhs.get_real(), rhs.get_real()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be applied to the given arguments",
+                    op.desc_full(),
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+    let arith = |v: f64| cast_value(cx, cx.intern_value(make_real(&ty::REAL_TYPE, v)), ty);
+    let comp = |v: bool| cx.intern_value(make_int(ty, (v as usize).into()));
+    Ok(match op {
+        hir::BinaryOp::Add => arith(a + b),
+        hir::BinaryOp::Sub => arith(a - b),
+        hir::BinaryOp::Mul => arith(a * b),
+        hir::BinaryOp::Div => arith(a / b),
+        hir::BinaryOp::Pow => arith(a.powf(b)),
+        hir::BinaryOp::Eq | hir::BinaryOp::CaseEq => comp(a == b),
+        hir::BinaryOp::Neq | hir::BinaryOp::CaseNeq => comp(a != b),
+        hir::BinaryOp::Lt => comp(a < b),
+        hir::BinaryOp::Leq => comp(a <= b),
+        hir::BinaryOp::Gt => comp(a > b),
+        hir::BinaryOp::Geq => comp(a >= b),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be applied to constant reals `{}` and `{}`",
+                    op.desc_full(),
+                    a,
+                    b
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    })
+}
+
+/// Apply a binary operator to two constants, at least one of which is a
+/// string.
+///
+/// Strings are compared lexicographically. See "6.16 String data type".
+fn const_binary_op_on_string<'gcx>(
+    cx: &impl Context<'gcx>,
+    span: Span,
+    ty: Type<'gcx>,
+    op: hir::BinaryOp,
+    lhs: Value<'gcx>,
+    rhs: Value<'gcx>,
+) -> Result<Value<'gcx>> {
+    let lhs = cast_value(cx, lhs, &ty::STRING_TYPE);
+    let rhs = cast_value(cx, rhs, &ty::STRING_TYPE);
+    let (a, b) = match (&lhs.kind, &rhs.kind) {
+        (&ValueKind::String(ref a), &ValueKind::String(ref b)) => (a, b),
+        _ => unreachable!(),
+    };
+    let result = match op {
+        hir::BinaryOp::Eq | hir::BinaryOp::CaseEq => a == b,
+        hir::BinaryOp::Neq | hir::BinaryOp::CaseNeq => a != b,
+        hir::BinaryOp::Lt => a < b,
+        hir::BinaryOp::Leq => a <= b,
+        hir::BinaryOp::Gt => a > b,
+        hir::BinaryOp::Geq => a >= b,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be applied to constant strings",
+                    op.desc_full(),
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+    Ok(cx.intern_value(make_int(ty, (result as usize).into())))
+}
+
+/// Apply an operation to a constant string.
+///
+/// See "6.16 String data type" for the individual methods.
+fn const_string_op<'gcx>(
+    cx: &impl Context<'gcx>,
+    mir: &'gcx mir::Rvalue<'gcx>,
+    op: mir::ContainerOp,
+    value: Value<'gcx>,
+    args: &[Value<'gcx>],
+) -> Result<Value<'gcx>> {
+    use mir::ContainerOp;
+    let s = match value.kind {
+        ValueKind::String(ref s) => s,
+        _ => unreachable!("string op on {:?}", value),
+    };
+    let index = |i: usize| {
+        args[i]
+            .get_int()
+            .and_then(|v| v.to_usize())
+            .unwrap_or(std::usize::MAX)
+    };
+    let string = |v: Vec<u8>| cx.intern_value(make_string(mir.ty, v));
+    Ok(match op {
+        ContainerOp::Size => cx.intern_value(make_int(mir.ty, s.len().into())),
+        ContainerOp::Get => {
+            let c = s.get(index(0)).cloned().unwrap_or(0);
+            cx.intern_value(make_int(mir.ty, c.into()))
+        }
+        ContainerOp::Substr => {
+            let (i, j) = (index(0), index(1));
+            if i <= j && j < s.len() {
+                string(s[i..=j].to_vec())
+            } else {
+                string(vec![])
+            }
+        }
+        ContainerOp::ToUpper => string(s.to_ascii_uppercase()),
+        ContainerOp::ToLower => string(s.to_ascii_lowercase()),
+        ContainerOp::Atoi => {
+            let digits = s
+                .iter()
+                .skip_while(|&&c| c == b' ')
+                .take_while(|&&c| c == b'_' || (c as char).is_ascii_digit())
+                .filter(|&&c| c != b'_')
+                .map(|&c| c as char)
+                .collect::<String>();
+            let v = digits.parse::<BigInt>().unwrap_or_else(|_| BigInt::zero());
+            cx.intern_value(make_int(mir.ty, v))
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` cannot be used in a constant expression",
+                    mir.span.extract()
+                ))
+                .span(mir.span),
+            );
+            return Err(());
+        }
+    })
+}
+
+fn const_mir<'gcx>(
+    cx: &impl Context<'gcx>,
+    mir: &'gcx mir::Rvalue<'gcx>,
+    scratch: Option<&ScratchValues<'gcx>>,
+) -> Result<Value<'gcx>> {
     match mir.kind {
         mir::RvalueKind::Concat(ref values) => {
             let mut result = BigInt::zero();
             for value in values {
                 result <<= value.ty.width();
-                result |= const_mir(cx, value)?.get_int().expect("concat non-integer");
+                result |= const_mir(cx, value, scratch)?
+                    .get_int()
+                    .expect("concat non-integer");
             }
             Ok(cx.intern_value(make_int(mir.ty, result)))
         }
 
+        mir::RvalueKind::StringConcat(ref values) => {
+            let mut result = vec![];
+            for &value in values {
+                match const_mir(cx, value, scratch)?.kind {
+                    ValueKind::String(ref v) => result.extend(v),
+                    _ => unreachable!("concat non-string"),
+                }
+            }
+            Ok(cx.intern_value(make_string(mir.ty, result)))
+        }
+
+        mir::RvalueKind::CastIntToString(value) => {
+            Ok(cast_value(cx, const_mir(cx, value, scratch)?, mir.ty))
+        }
+
+        mir::RvalueKind::Const(k) => Ok(k),
+
+        mir::RvalueKind::ContainerOp {
+            op,
+            value,
+            ref args,
+            ..
+        } if value.ty.is_string() => {
+            let args = args
+                .iter()
+                .map(|arg| match *arg {
+                    mir::CallArg::Input(arg) => const_mir(cx, arg, scratch),
+                    _ => unreachable!(),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            const_string_op(cx, mir, op, const_mir(cx, value, scratch)?, &args)
+        }
+
         // By default fall back to the usual method of constant value
         // computation.
-        _ => cx.constant_value_of(mir.origin, mir.env),
+        _ => value_of(cx, mir.origin, mir.env, scratch),
     }
 }
 
@@ -489,6 +1913,23 @@ pub(crate) fn is_constant<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Res
     })
 }
 
+/// Strip the dynamic array, queue, and associative array dimensions off a type.
+///
+/// Returns the number of dimensions stripped and the remaining element type.
+fn strip_dynamic_dims(mut ty: Type) -> (usize, Type) {
+    let mut num = 0;
+    while !ty.is_string() {
+        match ty.get_dynamic_element() {
+            Some(elem) => {
+                num += 1;
+                ty = elem;
+            }
+            None => break,
+        }
+    }
+    (num, ty)
+}
+
 /// Determine the default value of a type.
 pub(crate) fn type_default_value<'gcx>(cx: &impl Context<'gcx>, ty: Type<'gcx>) -> Value<'gcx> {
     // TODO(fschuiki): Make this function return `Result<Value<_>>`.
@@ -497,16 +1938,33 @@ pub(crate) fn type_default_value<'gcx>(cx: &impl Context<'gcx>, ty: Type<'gcx>)
             ty: &ty::ERROR_TYPE,
             kind: ValueKind::Void,
         }),
-        TypeKind::Void => cx.intern_value(ValueData {
+        TypeKind::Void | TypeKind::Interface { .. } => cx.intern_value(ValueData {
             ty: &ty::VOID_TYPE,
             kind: ValueKind::Void,
         }),
         TypeKind::Time => cx.intern_value(make_time(Zero::zero())),
+        TypeKind::Class { .. } | TypeKind::Null => cx.intern_value(ValueData {
+            ty,
+            kind: ValueKind::Null,
+        }),
+        TypeKind::Real | TypeKind::ShortReal => cx.intern_value(make_real(ty, 0.0)),
+        TypeKind::String => cx.intern_value(make_string(ty, vec![])),
         TypeKind::Bit(..)
         | TypeKind::Int(..)
         | TypeKind::BitVector { .. }
         | TypeKind::BitScalar { .. } => cx.intern_value(make_int(ty, Zero::zero())),
         TypeKind::Named(_, _, ty) => type_default_value(cx, ty),
+        TypeKind::Struct(id) if cx.struct_def(id).unwrap().is_union() => {
+            let w = ty::bit_size_of_type(cx, ty, cx.default_param_env()).unwrap_or(0);
+            cx.intern_value(ValueData {
+                ty,
+                kind: ValueKind::Int(
+                    Zero::zero(),
+                    BitVec::from_elem(w, false),
+                    BitVec::from_elem(w, false),
+                ),
+            })
+        }
         TypeKind::Struct(id) => {
             let def = cx.struct_def(id).unwrap();
             let fields = def
@@ -527,5 +1985,14 @@ pub(crate) fn type_default_value<'gcx>(cx: &impl Context<'gcx>, ty: Type<'gcx>)
                 .take(length)
                 .collect(),
         )),
+        TypeKind::UnpackedArray(range, elem_ty) => cx.intern_value(make_array(
+            ty,
+            std::iter::repeat(cx.type_default_value(elem_ty))
+                .take(range.size)
+                .collect(),
+        )),
+        TypeKind::DynamicArray(..) | TypeKind::Queue(..) | TypeKind::AssocArray(..) => {
+            cx.intern_value(make_array(ty, vec![]))
+        }
     }
 }
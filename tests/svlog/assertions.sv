@@ -0,0 +1,21 @@
+module assertions (input bit clk, input bit rst, input bit req, output bit gnt);
+    sequence handshake(a, b);
+        a ##[1:3] b;
+    endsequence
+
+    property req_gnt;
+        @(posedge clk) disable iff (rst) req |=> handshake(req, gnt);
+    endproperty
+
+    req_granted: assert property (req_gnt) else $error("request not granted");
+    cover property (@(posedge clk) req[*2] ##1 gnt[->1]);
+    assume property (@(posedge clk) not (gnt throughout req ##1 !req));
+
+    always_ff @(posedge clk) begin
+        gnt <= req;
+        assert (gnt || !req);
+        assert #0 (!rst);
+    end
+endmodule
+
+//@ elab assertions
@@ -1 +1,20 @@
 (* dont_touch = "true" *)
+
+(* top *)
+module attrs (input bit clk);
+    (* keep, ram_style = "block" *) bit [7:0] a;
+    (* mark_debug = 1 *) wire bit b;
+    (* keep_hierarchy *) attrs_sub i_sub();
+    (* full_case *) assign b = a[0];
+    (* synthesis_only *)
+    always_ff @(posedge clk) (* parallel_case *) a <= a + 1;
+    always @(*) a = a;
+    // synopsys translate_off
+    initial $display("simulation only");
+    // synopsys translate_on
+endmodule
+
+module attrs_sub;
+endmodule
+
+//@ elab attrs
@@ -0,0 +1,13 @@
+module bind_top (input bit clk);
+    bit [7:0] count;
+    always_ff @(posedge clk) count <= count + 1;
+endmodule
+
+module bind_checker (input bit clk, input bit [7:0] value);
+    bit [7:0] last;
+    always_ff @(posedge clk) last <= value;
+endmodule
+
+bind bind_top bind_checker u_chk (.clk(clk), .value(count));
+
+//@ elab bind_top
@@ -0,0 +1,84 @@
+//@ elab top
+
+virtual class Shape;
+    protected int id;
+    static int count = 0;
+
+    function new(int id);
+        this.id = id;
+        count++;
+    endfunction
+
+    pure virtual function int area();
+
+    function int get_id();
+        return id;
+    endfunction
+endclass
+
+class Rect extends Shape;
+    local int w, h;
+
+    function new(int id, int w, int h);
+        super.new(id);
+        this.w = w;
+        this.h = h;
+    endfunction
+
+    virtual function int area();
+        return w * h;
+    endfunction
+endclass
+
+class Square extends Rect;
+    function new(int id, int s);
+        super.new(id, s, s);
+    endfunction
+endclass
+
+class Box #(type T = int, int N = 2);
+    T value;
+
+    function void put(T v);
+        value = v;
+    endfunction
+
+    function T get();
+        return value;
+    endfunction
+
+    static function int size();
+        return N;
+    endfunction
+endclass
+
+module top;
+    Shape s;
+    Rect r;
+    Square q;
+    Box#(byte, 4) b;
+    Box#(byte, 2 + 2) b2;
+    Box i;
+    int a;
+    bit [7:0] v;
+
+    initial begin
+        r = new(1, 2, 3);
+        s = r;
+        a = s.area();
+        a = s.get_id();
+        q = new(2, 4);
+        s = q;
+        if (s != null)
+            a = s.area();
+        b = new;
+        b2 = b;
+        b2.put(8'h42);
+        v = b.get();
+        a = b.size();
+        i = new;
+        i.value = 5;
+        a = i.value + Shape::count;
+        s = null;
+    end
+endmodule
@@ -0,0 +1,36 @@
+module top;
+    int i = -42;
+    logic [7:0] b = 8'hA5;
+    string s = "moore";
+    real r = 1.5;
+    shortreal sr = 0.25;
+    time t;
+    struct packed { logic [3:0] a; logic [3:0] b; } st;
+
+    initial begin
+        $display("hello, world");
+        $display("i = %d, %0d, %4d", i, i, i);
+        $display("b = %h %x %b %o %0b", b, b, b, b, b);
+        $display("s = %s, %10s, c = %c", s, s, b);
+        $display("r = %f %e %g %.3f", r, r, sr, r);
+        $display("100%% in %m");
+        $display(i, " ", b, , s, r);
+        $displayh(b);
+        $write("no newline ");
+        $writeb(b);
+        $display("st = %h, i as string = %s", st, i);
+        t = $time;
+        $display("t = %t, %0t, %d, %f", t, $stime, $time, $realtime);
+        $display;
+        $info("info %d", i);
+        $warning("warning");
+        $error("error %s", s);
+        if (i == 0) $fatal(2, "fatal %0d", i);
+        if (i == 1) $fatal;
+        if (i == 2) $stop;
+        $finish(0);
+        $finish;
+    end
+endmodule
+
+//@ elab top
@@ -0,0 +1,37 @@
+module top;
+	int q[$];
+	int bq[$:15];
+	bit [7:0] da[];
+	int aa[int];
+	int wa[*];
+	int x;
+	int k;
+
+	initial begin
+		q.push_back(1);
+		q.push_front(2);
+		q.insert(1, 3);
+		x = q.pop_front();
+		x = q.pop_back();
+		q[0] = 42;
+		x = q[0] + q.size();
+		q.delete(0);
+		q.delete();
+
+		da = new[4];
+		da[1] = 8'h12;
+		da = new[8](da);
+		x = da.size();
+
+		aa[5] = 1;
+		x = aa[5];
+		if (aa.exists(5))
+			aa.delete(5);
+		if (aa.first(k))
+			x = aa.next(k);
+		x = aa.num();
+		wa[17] = 3;
+	end
+endmodule
+
+//@ elab top
@@ -0,0 +1,35 @@
+// Bit vector and math functions
+// 20.8 and 20.9 of std-2017
+
+module top;
+	localparam bit [7:0] P = 8'b1011_0010;
+	localparam int ONES = $countones(P);
+	localparam bit HOT = $onehot(P), HOT0 = $onehot0(8'h00), HOT1 = $onehot(8'h10);
+	localparam bit UNK = $isunknown(4'b10x1), KNOWN = $isunknown(P);
+	localparam real LN = $ln(1.0), LOG = $log10(1000), ROOT = $sqrt(16);
+	localparam real POW = $pow(2, 10), ATAN = $atan2(1.0, 1.0), HYP = $hypot(3, 4);
+	localparam int CEIL = $ceil(2.1), FLOOR = $floor(-2.1);
+
+	int q[$];
+	int d[];
+	int a[string];
+	logic [15:0] v;
+	int i;
+	bit b;
+	real r;
+
+	initial begin
+		i = ONES + CEIL + FLOOR;
+		b = HOT | HOT0 | HOT1 | UNK | KNOWN;
+		r = LN + LOG + ROOT + POW + ATAN + HYP;
+		i = $countones(v);
+		b = $onehot(v);
+		b = $onehot0(v[3:0]);
+		b = $isunknown(v);
+		r = $sqrt(r) + $pow(r, i) + $exp(i) + $sin(r) + $atanh(0.5);
+		i = $size(q) + $left(q) + $right(q) + $high(d) + $low(d) + $increment(d);
+		i = $size(a) + $dimensions(q) + $unpacked_dimensions(d);
+	end
+endmodule
+
+//@ elab top
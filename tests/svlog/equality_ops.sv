@@ -0,0 +1,28 @@
+module A;
+	B #(4'b10x1 == 4'b1001) b1();
+	B #(4'b10x1 == 4'b0001) b2();
+	B #(4'b10x1 === 4'b10x1) b3();
+	B #(4'b10x1 !== 4'b10z1) b4();
+	B #(4'b1011 ==? 4'b10x1) b5();
+	B #(4'b1001 !=? 4'b11?1) b6();
+	B #(4'b1x01 ==? 4'b10z1) b7();
+endmodule
+
+module B #(int K);
+	int x = K;
+endmodule
+
+module C;
+	logic [7:0] a, b;
+	bit eq, ne, weq, wne, in;
+	always_comb begin
+		eq = a === b;
+		ne = a !== b;
+		weq = a ==? 8'b1010_xxzz;
+		wne = a !=? b;
+		in = a inside {8'b1???_0000, [1:3], b};
+	end
+endmodule
+
+//@ elab A
+//@ elab C
@@ -0,0 +1,86 @@
+//@ elab top
+
+module top;
+    event start, finish;
+    bit [7:0] a, b, c;
+    bit ready;
+    int count;
+
+    // Triggering an event wakes up the processes waiting for it.
+    initial begin
+        #1ns -> start;
+        wait (ready) -> finish;
+    end
+
+    initial begin
+        @(start);
+
+        // Wait for all branches.
+        fork
+            #2ns a = 1;
+            begin
+                #1ns b = 2;
+                #1ns b = 3;
+            end
+        join
+
+        // Wait for the first branch only, then for the rest.
+        fork
+            #1ns c = 4;
+            #5ns c = 5;
+        join_any
+        wait fork;
+
+        // Leave branches running in the background, then abort them.
+        fork
+            forever #1ns count++;
+            begin : nested
+                fork
+                    #3ns a = 6;
+                join_none
+            end
+        join_none
+        #4ns disable fork;
+
+        ready = 1;
+    end
+
+    // Disabling a named block skips its remaining statements.
+    initial begin : search
+        for (int i = 0; i < 8; i++) begin
+            if (a[i]) disable search;
+            #1ns;
+        end
+        count = -1;
+    end
+
+    always @(finish) count = 0;
+
+    // Variables of the process and declarations within the fork are shared
+    // with the branches.
+    bit [7:0] d;
+    initial begin
+        bit [7:0] x;
+        x = 1;
+        fork
+            bit [7:0] y = x + 1;
+            #1ns x = y;
+            begin
+                bit [7:0] z;
+                #2ns z = x;
+                fork
+                    d = z + y;
+                join
+            end
+        join
+    end
+
+    // A fork which is executed again while its branches are still running
+    // waits for them to complete first.
+    initial begin
+        for (int i = 0; i < 3; i++)
+            fork
+                #2ns count += i;
+            join_none
+    end
+endmodule
@@ -0,0 +1,30 @@
+module top;
+  logic [7:0] a, b;
+  bit [7:0] c;
+  logic d;
+  bit e, f, g;
+
+  initial begin
+    a = 'x;
+    b = 'z;
+    a = 8'bxx01_zz10;
+    c = a;
+    d = 1'bx;
+    casez (a)
+      8'b??01_0000: b = 1;
+      default: b = 2;
+    endcase
+
+    // Four-valued types are lowered as two-valued integers. Case equality of
+    // non-constant operands is the same as regular equality, and only the
+    // `x` and `z` bits of a constant pattern act as wildcards.
+    e = a === b;
+    f = a !== c;
+    g = a ==? 8'b1x0z_0101;
+    g = a !=? b;
+    c = b;
+    a = c;
+  end
+endmodule
+
+//@ elab top
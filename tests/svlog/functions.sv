@@ -0,0 +1,102 @@
+package pkg;
+    localparam int K = 3;
+    function automatic int twice(int x);
+        return 2 * x;
+    endfunction
+endpackage
+
+module top;
+    // Constant functions
+    function automatic int clog2(input int value);
+        int result = 0;
+        while ((1 << result) < value)
+            result++;
+        return result;
+    endfunction
+
+    function automatic int fact(int n);
+        if (n <= 1) return 1;
+        fact = n * fact(n - 1);
+    endfunction
+
+    function automatic int sum(int n, int step = 1);
+        int acc;
+        acc = 0;
+        for (int i = 0; i < n; i++)
+            acc += i * step;
+        return acc;
+    endfunction
+
+    localparam int A = clog2(17);
+    localparam int B = fact(5);
+    localparam int C = sum(5);
+    localparam int D = sum(.n(10), .step(3));
+    localparam int E = pkg::twice(pkg::K);
+    bit [A-1:0] a;
+    bit [B-1:0] b;
+    bit [C-1:0] c;
+    bit [D-1:0] d;
+    bit [E-1:0] e;
+
+    // Functions and tasks called at runtime
+    function automatic void split(input bit [7:0] x, output bit [3:0] hi, lo);
+        hi = x[7:4];
+        lo = x[3:0];
+    endfunction
+
+    function bit [7:0] incr(bit [7:0] x);
+        incr = x + 1;
+    endfunction
+
+    task automatic bump(inout int v);
+        v = v + pkg::twice(e);
+    endtask
+
+    // Tasks which suspend execution
+    bit clk;
+    task automatic wait_cycles(input int n, output int waited);
+        waited = 0;
+        if (n == 0) return;
+        repeat (n) begin
+            @(posedge clk);
+            waited++;
+        end
+    endtask
+
+    task automatic pulse(int n);
+        int waited;
+        #1ns clk = 1;
+        wait_cycles(n, waited);
+        wait (waited == n) clk = 0;
+    endtask
+
+    bit [7:0] f;
+    int g, h, i;
+    assign f = incr(a);
+
+    task automatic reset_g();
+        int zero;
+        zero = 0;
+        g = zero;
+    endtask
+
+    function automatic int one();
+        int k;
+        k = 1;
+        return k;
+    endfunction
+
+    initial reset_g();
+    initial i = one();
+
+    initial begin
+        bit [3:0] hi, lo;
+        split(f, hi, lo);
+        g = pkg::twice(h);
+        bump(g);
+        pulse(2);
+        wait_cycles(g, h);
+    end
+endmodule
+
+//@ elab top
@@ -0,0 +1,32 @@
+module A #(parameter int N = 2);
+    case (N)
+        0, 1: begin : g_small
+            logic x;
+            assign x = 0;
+        end
+        2: begin : g_two
+            logic x;
+            assign x = 1;
+        end
+        default: begin
+            logic x;
+        end
+    endcase
+
+    if (N > 4) begin
+        logic y;
+    end else if (N > 1) begin
+        logic y;
+        initial y = g_two.x;
+    end
+
+    for (genvar i = 0; i < 2; i++) begin : g_loop
+        logic [N-1:0] z;
+        assign z = i;
+    end
+
+    logic w;
+    assign w = g_two.x;
+endmodule
+
+//@ elab A
@@ -16,3 +16,20 @@ endmodule
 //|     %b1 = inst @B () ()
 //|     %b2 = inst @B () ()
 //| }
+
+module C #(parameter int N = 3);
+    for (genvar i = 0; i < N; i++) begin : g_loop
+        logic [i:0] x;
+        localparam int W = i + 1;
+        assign x = i;
+    end
+
+    logic [N-1:0] last;
+    logic [1:0] second;
+    int width;
+    assign last = g_loop[N-1].x;
+    assign width = g_loop[1].W;
+    always_comb second = g_loop[1].x;
+endmodule
+
+//@ elab C
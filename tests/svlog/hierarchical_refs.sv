@@ -0,0 +1,25 @@
+module top;
+    logic clk;
+    logic [7:0] observed;
+    logic [3:0] depth;
+    core u_core (clk);
+    defparam u_core.WIDTH = 8, u_core.u_fifo.DEPTH = 4;
+    assign depth = u_core.u_fifo.DEPTH;
+    always_comb observed = u_core.state + u_core.u_fifo.count;
+    initial assert (u_core.u_fifo.count == 0);
+endmodule
+
+module core #(parameter int WIDTH = 2) (input logic clk);
+    logic [WIDTH-1:0] state;
+    fifo u_fifo (clk);
+    always_ff @(posedge clk) state <= state + top.observed[0];
+endmodule
+
+module fifo #(parameter int DEPTH = 2) (input logic clk);
+    logic [7:0] count;
+    logic [7:0] mirror;
+    always_ff @(posedge clk) count <= count + DEPTH;
+    assign mirror = top.u_core.state;
+endmodule
+
+//@ elab top
@@ -8,5 +8,4 @@ module bar;
 	initial baz = magic;
 endmodule
 
-//@exclude
 //@ elab bar
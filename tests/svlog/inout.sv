@@ -0,0 +1,36 @@
+////////////////////////////////////////////
+///  Inout ports and tri-state drivers  ///
+////////////////////////////////////////////
+
+module pad (input logic oe, input logic [7:0] d, output logic [7:0] q, inout wire [7:0] io);
+	assign io = oe ? d : 'z;
+	assign q = io;
+endmodule
+
+// Inout ports are connected through the hierarchy.
+module top (input logic sel, input logic [7:0] a, b, output logic [7:0] qa, qb, inout wire [7:0] bus);
+	pad p0 (.oe(sel), .d(a), .q(qa), .io(bus));
+	pad p1 (.oe(!sel), .d(b), .q(qb), .io(bus));
+endmodule
+
+// Non-ANSI inout ports connect to a net in the module body.
+module nonansi (oe, d, io);
+	input oe;
+	input [3:0] d;
+	inout [3:0] io;
+	assign io = oe ? d : 4'bzzzz;
+endmodule
+
+// Multiple tri-state drivers of a local net.
+module bus (input logic en, input logic [3:0] a, b, output logic [3:0] y, output logic [3:0] v);
+	tri [3:0] shared;
+	assign shared = en ? a : 'z;
+	assign shared = en ? 'z : b;
+	assign y = shared;
+	tri1 [3:0] pulled;
+	nonansi n0 (.oe(en), .d(a), .io(pulled));
+	assign v = pulled;
+endmodule
+
+//@ elab top
+//@ elab bus
@@ -0,0 +1,57 @@
+interface bus_if #(parameter int W = 8) (input logic clk);
+    logic req;
+    logic gnt;
+    logic [W-1:0] data;
+    modport mst (input clk, gnt, output req, data);
+    modport slv (input clk, req, data, output gnt);
+endinterface
+
+module master (bus_if.mst bus);
+    always_ff @(posedge bus.clk) begin
+        bus.req <= ~bus.gnt;
+        bus.data <= bus.data + 1;
+    end
+endmodule
+
+module slave (bus_if.slv bus, output logic [15:0] q);
+    assign bus.gnt = bus.req;
+    assign q = bus.data;
+endmodule
+
+module monitor (bus_if bus);
+    logic seen;
+    always_comb seen = bus.req & bus.gnt;
+endmodule
+
+// Interfaces with procedures and continuous assignments of their own
+interface counter_if (input logic clk);
+    logic [3:0] count;
+    logic wrap;
+    assign wrap = &count;
+    always_ff @(posedge clk) count <= count + 1;
+    modport user (input count, wrap);
+endinterface
+
+module counter_user (counter_if.user cnt, output logic wrapped);
+    assign wrapped = cnt.wrap;
+endmodule
+
+module top (input logic clk, output logic [15:0] q, output logic wrapped);
+    bus_if #(16) b (clk);
+    master m (b);
+    slave s (.bus(b), .q(q));
+    monitor mon (.bus(b));
+    counter_if c0 (clk);
+    counter_if c1 (clk);
+    counter_user u (c1, wrapped);
+
+    // Instances connected through a modport of the interface instance
+    bus_if #(16) b2 (clk);
+    logic [15:0] q2;
+    logic wrapped0;
+    master m2 (b2.mst);
+    slave s2 (.bus(b2.slv), .q(q2));
+    counter_user u0 (c0.user, wrapped0);
+endmodule
+
+//@ elab top
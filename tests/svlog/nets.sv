@@ -0,0 +1,35 @@
+/////////////////////////////////////////
+///  Net types, strengths, and drivers  ///
+/////////////////////////////////////////
+
+module top (input logic a, input logic b, output logic y, output logic z, output logic u, output logic v);
+	// Net declaration assignments are continuous drivers.
+	wire w = a & b;
+	assign y = w;
+
+	// Wired-AND and wired-OR nets combine their drivers.
+	wand wa;
+	assign wa = a;
+	assign wa = b;
+	wor wo;
+	assign wo = a;
+	assign wo = b;
+
+	// Pull and supply nets are driven to a constant by their type.
+	tri1 t1;
+	supply0 gnd;
+	supply1 vdd;
+	assign z = wa ^ wo ^ t1 ^ gnd ^ vdd;
+
+	// The stronger driver wins.
+	wire (weak0, weak1) s = a;
+	assign (strong0, strong1) s = b;
+	assign u = s;
+
+	// An explicit driver overrides the pull of a `tri0` net.
+	tri0 p;
+	assign p = a;
+	assign v = p;
+endmodule
+
+//@ elab top
@@ -0,0 +1,35 @@
+module top;
+    logic [3:0] a;
+    logic [3:0] b;
+    logic c;
+    logic [7:0] d;
+    logic [1:0] e;
+    logic [5:0] f;
+    logic [2:0] g;
+    adder i_adder (a, b, c, d);
+    splitter i_splitter (.lo(e), .hi(f), .all(d));
+    joiner i_joiner (g, {a[0], c});
+endmodule
+
+module adder (x, y, cin, sum);
+    input [3:0] x, y;
+    input cin;
+    output [7:0] sum;
+    reg [7:0] sum;
+    always_comb sum = x + y + cin;
+endmodule
+
+module splitter (.lo(q[1:0]), .hi(q[7:2]), .all(p));
+    output [7:0] q;
+    input [7:0] p;
+    assign q = p;
+endmodule
+
+module joiner (z, {u, v});
+    output [2:0] z;
+    input u, v;
+    wire u, v;
+    assign z = {u, v, u ^ v};
+endmodule
+
+//@ elab top
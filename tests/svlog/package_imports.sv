@@ -0,0 +1,61 @@
+typedef logic [3:0] nibble_t;
+localparam int UNIT_W = 4;
+function automatic int unit_inc(int x);
+  return x + 1;
+endfunction
+
+package base_pkg;
+  localparam int WIDTH = 8;
+  localparam int DEPTH = 3;
+  typedef logic [WIDTH-1:0] word_t;
+  function automatic word_t invert(word_t x);
+    return ~x;
+  endfunction
+endpackage
+
+package mid_pkg;
+  import base_pkg::*;
+  export base_pkg::*;
+  localparam int SCALE = WIDTH * 2;
+endpackage
+
+package other_pkg;
+  localparam int DEPTH = 5;
+  localparam int ONLY_OTHER = 7;
+endpackage
+
+module sub #(parameter int N = base_pkg::DEPTH) (
+  input logic [base_pkg::WIDTH-1:0] i,
+  output logic [N-1:0] o
+);
+  assign o = i[N-1:0];
+endmodule
+
+module top;
+  import mid_pkg::*;
+  import other_pkg::ONLY_OTHER;
+  localparam int DEPTH = 2;
+  word_t a;
+  nibble_t n;
+  logic [base_pkg::WIDTH-1:0] b;
+  int c, d, e;
+  logic [DEPTH-1:0] o;
+  sub #(.N(mid_pkg::DEPTH - 1)) u_sub (.i(b), .o(o));
+  if (base_pkg::WIDTH > UNIT_W) begin : g_wide
+    assign e = mid_pkg::SCALE;
+  end
+  initial begin
+    a = invert(8'h0f);
+    n = UNIT_W;
+    b = base_pkg::invert(a);
+    c = SCALE + DEPTH + ONLY_OTHER + other_pkg::DEPTH;
+    d = unit_inc(mid_pkg::WIDTH);
+    case (c)
+      base_pkg::DEPTH: d = 0;
+      other_pkg::DEPTH: d = 1;
+      default: ;
+    endcase
+  end
+endmodule
+
+//@ elab top
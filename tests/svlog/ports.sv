@@ -14,9 +14,12 @@ module B (
 endmodule
 
 //@ elab B
-//| entity @B (i1$ %x, i1$ %z) (i1$ %y, i1$ %z0) {
+//| entity @B (i1$ %x, i1$ %z.i) (i1$ %y, i1$ %z.o, i1$ %z.oe) {
+//|     %z = sig i1 0
+//|     drv %z.o 0
+//|     drv %z.oe 0
+//|     drv %z %z.i
 //|     drv %y 0
-//|     drv %z0 0
 //| }
 
 
@@ -65,3 +68,21 @@ endmodule
 //| entity @E (i1$ %x, i1$ %y) -> () {
 //| }
 
+
+
+// Ports without a direction and type inherit them from the preceding port.
+module F (
+    input logic [7:0] x, y,
+    output logic [7:0] z
+);
+    assign z = x & y;
+endmodule
+
+//@ elab F
+//| entity @F (i8$ %x, i8$ %y) -> (i8$ %z) {
+//|     %x1 = prb i8$ %x
+//|     %y1 = prb i8$ %y
+//|     %0 = and i8 %x1, %y1
+//|     %1 = const time 0s 1e
+//|     drv i8$ %z, %0, %1
+//| }
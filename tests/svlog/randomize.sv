@@ -0,0 +1,72 @@
+//@ elab top
+
+class Packet;
+    rand bit [7:0] addr;
+    rand bit [3:0] len;
+    randc bit [2:0] tag;
+    rand int data [4];
+    bit wide;
+
+    constraint c_addr { addr inside {[8'h10:8'h1f], 8'h80}; }
+    constraint c_len {
+        len dist { 0 := 1, [1:7] :/ 6, 15 := 0 };
+        wide -> len > 4;
+        solve addr before len;
+    }
+    constraint c_data {
+        foreach (data[i]) {
+            data[i] >= 0;
+            data[i] < 100;
+            if (i > 0) data[i] > data[i-1];
+        }
+    }
+endclass
+
+class Jumbo extends Packet;
+    constraint c_len { len == 15; }
+endclass
+
+module top;
+    function automatic int pick_pair();
+        int a, b;
+        void'(std::randomize(a, b) with { a > 0; a < 10; b == a * 3; });
+        return a * 100 + b;
+    endfunction
+
+    function automatic int make_packet(bit wide, int max_addr);
+        Packet p;
+        int ok;
+        p = new;
+        p.wide = wide;
+        ok = p.randomize() with { addr < max_addr; };
+        if (ok == 0)
+            return -1;
+        return p.addr + p.len * 256 + p.data[3] * 4096;
+    endfunction
+
+    function automatic int make_jumbo();
+        Jumbo j;
+        bit ok;
+        j = new;
+        ok = j.randomize();
+        return ok ? j.len : -1;
+    endfunction
+
+    function automatic int impossible();
+        int x;
+        return std::randomize(x) with { x > 5; x < 3; };
+    endfunction
+
+    localparam int PAIR = pick_pair();
+    localparam int PACKET = make_packet(1, 8'h20);
+    localparam int JUMBO = make_jumbo();
+    localparam int NONE = impossible();
+
+    int a, b, c, d;
+    initial begin
+        a = PAIR;
+        b = PACKET;
+        c = JUMBO;
+        d = NONE;
+    end
+endmodule
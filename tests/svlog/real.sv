@@ -0,0 +1,40 @@
+module top;
+	localparam real PI = 3.14159;
+	localparam real SMALL = 1.5e-3;
+	localparam int ROUNDED = PI * 2;
+	localparam int TRUNCATED = $rtoi(-2.7);
+	localparam real HALF = 1/2;
+	localparam real THIRD = 1.0/3;
+	localparam bit [63:0] BITS = $realtobits(1.0);
+	localparam real BACK = $bitstoreal(BITS);
+
+	real r;
+	shortreal s;
+	realtime t;
+	int i;
+	bit b;
+	int ri = 2.5;
+	int ce = $ceil(2.1);
+	real ir = 3;
+
+	initial begin
+		r = PI + SMALL;
+		r = r * 2 - 1e2;
+		r = -r;
+		r = i;
+		i = r;
+		i = $rtoi(r);
+		r = $itor(i) / 4;
+		s = r;
+		r = s + 1.5;
+		b = r > 0.5;
+		b = r == PI;
+		if (r)
+			r++;
+		t = $realtime;
+		i = $realtobits(r);
+		r = $bitstoreal(i);
+	end
+endmodule
+
+//@ elab top
@@ -0,0 +1,36 @@
+module top #(parameter string NAME = "moore");
+	localparam string GREETING = {"hello ", NAME};
+	localparam int NAME_LEN = NAME.len();
+	localparam string UPPER = NAME.toupper();
+	localparam bit IS_MOORE = NAME == "moore";
+	localparam bit [23:0] PACKED = "abc";
+
+	string s;
+	string t = "tab\there";
+	int i;
+	byte c;
+	bit b;
+
+	initial begin
+		s = "abc";
+		s = {s, "def", NAME};
+		s = {2{"ab"}};
+		i = s.len();
+		c = s.getc(0);
+		c = s[1];
+		s[0] = "x";
+		s.putc(1, c);
+		t = s.substr(1, 2);
+		t = s.toupper();
+		t = t.tolower();
+		s = "42";
+		i = s.atoi();
+		s.itoa(i + 1);
+		b = s == t;
+		b = s < "zzz";
+		b = s != GREETING;
+		s = string'(PACKED);
+	end
+endmodule
+
+//@ elab top
@@ -0,0 +1,54 @@
+typedef struct packed {
+  logic [3:0] kind;
+  logic [11:0] length;
+} header_t;
+
+typedef union packed {
+  header_t hdr;
+  logic [15:0] raw;
+  logic [1:0][7:0] bytes;
+} packet_t;
+
+typedef union {
+  int i;
+  shortreal f;
+} number_t;
+
+typedef union tagged {
+  void Invalid;
+  logic [7:0] Small;
+  logic [15:0] Large;
+} value_t;
+
+module top;
+  packet_t p;
+  number_t n;
+  value_t v;
+  logic [15:0] result;
+  logic [3:0] kind;
+  int i;
+
+  initial begin
+    p.raw = 16'h5123;
+    kind = p.hdr.kind;
+    p.hdr.length = 12'h042;
+    result = p.bytes[0];
+    n.i = 42;
+    i = n.i;
+    v = tagged Small 8'h2a;
+    v = tagged Invalid;
+    v = tagged Large (p.raw + 16'd1);
+    case (v) matches
+      tagged Invalid: result = 0;
+      tagged Small .s: result = s;
+      tagged Large 16'hffff: result = 1;
+      tagged Large .l: result = l + 1;
+    endcase
+    case (v) matches
+      tagged Small .*: result = 2;
+      default: result = 3;
+    endcase
+  end
+endmodule
+
+//@ elab top
@@ -0,0 +1,36 @@
+module top (
+    input logic clk,
+    input logic we,
+    input logic [3:0] addr,
+    input logic [7:0] wdata,
+    output logic [7:0] rdata,
+    output int sizes
+);
+    logic [7:0] mem [0:15];
+    logic [7:0] rom [1:4] = '{8'h11, 8'h22, 8'h33, 8'h44};
+    bit [3:0] lut [4];
+    int nums [2:0] = '{3{7}};
+    logic [7:0] grid [2][3];
+
+    localparam int S = $size(mem);
+    localparam int L = $left(rom);
+    localparam int R = $right(nums);
+    localparam int D = $dimensions(grid);
+    localparam int U = $unpacked_dimensions(grid);
+    localparam int W = $size(grid, 2);
+
+    assign sizes = S + L + R + D + U + W;
+
+    initial begin
+        lut = '{0: 4'h1, default: 4'h0};
+        grid[1][2] = 8'hff;
+    end
+
+    always_ff @(posedge clk) begin
+        if (we)
+            mem[addr] <= wdata;
+        rdata <= mem[addr] ^ rom[2] ^ lut[1];
+    end
+endmodule
+
+//@ elab top